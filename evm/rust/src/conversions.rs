@@ -241,6 +241,7 @@ mod beefy {
 						.expect("next authority set length out of bounds"),
 					keyset_commitment: H256(value.nextAuthoritySet.root.0),
 				},
+				bls_keysets: Default::default(),
 			}
 		}
 	}
@@ -20,13 +20,14 @@
 #![deny(missing_docs)]
 
 use codec::{Decode, Encode};
+use hex_literal::hex;
 use polkadot_sdk::*;
 use sp_consensus_beefy::mmr::{BeefyAuthoritySet, MmrLeaf, MmrLeafVersion};
 use sp_core::H256;
 use sp_std::prelude::*;
 
 /// Client state definition for the light client
-#[derive(sp_std::fmt::Debug, Encode, Decode, PartialEq, Eq, Clone)]
+#[derive(sp_std::fmt::Debug, Encode, PartialEq, Eq, Clone)]
 pub struct ConsensusState {
 	/// Latest beefy height
 	pub latest_beefy_height: u32,
@@ -38,6 +39,53 @@ pub struct ConsensusState {
 	pub current_authorities: BeefyAuthoritySet<H256>,
 	/// Authorities for the next session
 	pub next_authorities: BeefyAuthoritySet<H256>,
+	/// Commitments to the BLS keys of the current and next authorities, used by
+	/// [`PROOF_TYPE_BLS`] proofs
+	pub bls_keysets: BlsKeysets,
+}
+
+impl Decode for ConsensusState {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let latest_beefy_height = Decode::decode(input)?;
+		let beefy_activation_block = Decode::decode(input)?;
+		let mmr_root_hash = Decode::decode(input)?;
+		let current_authorities = Decode::decode(input)?;
+		let next_authorities = Decode::decode(input)?;
+		// States written before BLS proofs were supported end here
+		let bls_keysets = match input.remaining_len()? {
+			Some(0) => BlsKeysets::default(),
+			_ => Decode::decode(input)?,
+		};
+
+		Ok(Self {
+			latest_beefy_height,
+			beefy_activation_block,
+			mmr_root_hash,
+			current_authorities,
+			next_authorities,
+			bls_keysets,
+		})
+	}
+}
+
+impl ConsensusState {
+	/// Rotate to the `next` authority set announced by the relay chain. The BLS keys of the new
+	/// next set are unknown until a [`BlsKeysetProof`] for it is verified.
+	pub fn rotate_authorities(&mut self, next: BeefyAuthoritySet<H256>) {
+		self.current_authorities = sp_std::mem::replace(&mut self.next_authorities, next);
+		self.bls_keysets.current = self.bls_keysets.next.take();
+	}
+}
+
+/// Merkle commitments to the BLS public keys of the authority sets tracked by
+/// [`ConsensusState`]. Leaves are `keccak256(bls_public_key)` in authority order, the same
+/// construction as the ECDSA keyset commitment.
+#[derive(sp_std::fmt::Debug, Default, Encode, Decode, PartialEq, Eq, Clone)]
+pub struct BlsKeysets {
+	/// BLS keys of the current authority set
+	pub current: Option<BeefyAuthoritySet<H256>>,
+	/// BLS keys of the next authority set
+	pub next: Option<BeefyAuthoritySet<H256>>,
 }
 
 /// Hash length definition for hashing algorithms used
@@ -146,6 +194,115 @@ pub struct Sp1BeefyProof {
 	pub nonce: H256,
 }
 
+/// Proof type identifier for aggregated BLS proofs
+pub const PROOF_TYPE_BLS: u8 = 0x02;
+
+/// Length of a `bls381` public key in Substrate's w3f TinyBLS381 scheme: the compressed G1
+/// public key followed by the compressed G2 public key.
+pub const BLS_PUBLIC_KEY_LEN: usize = 144;
+
+/// Offset of the G2 public key in a `bls381` public key. Signatures are in G1, so this is the
+/// key they are verified against.
+pub const BLS_G2_PUBLIC_KEY_OFFSET: usize = 48;
+
+/// Length of a `bls381` signature in Substrate's w3f TinyBLS381 scheme: the compressed G1
+/// signature followed by a proof that it matches the G1 public key.
+pub const BLS_SIGNATURE_LEN: usize = 112;
+
+/// Length of a compressed G1 signature, the part of a `bls381` signature that is aggregated.
+pub const BLS_G1_SIGNATURE_LEN: usize = 48;
+
+/// Length of an `ecdsa_bls381` authority id: the compressed ECDSA public key followed by the
+/// `bls381` public key.
+pub const ECDSA_BLS_AUTHORITY_ID_LEN: usize = 33 + BLS_PUBLIC_KEY_LEN;
+
+/// Length of an `ecdsa_bls381` authority signature: the ECDSA signature followed by the
+/// `bls381` signature.
+pub const ECDSA_BLS_SIGNATURE_LEN: usize = 65 + BLS_SIGNATURE_LEN;
+
+/// Relay chain storage key for beefy.authorities()
+pub const BEEFY_AUTHORITIES: [u8; 32] =
+	hex!("08c41974a97dbf15cfbec28365bea2da5e0621c4869aa60c02be9adcc98a0d1d");
+
+/// Relay chain storage key for beefy.nextAuthorities()
+pub const BEEFY_NEXT_AUTHORITIES: [u8; 32] =
+	hex!("08c41974a97dbf15cfbec28365bea2daaacf00b9b41fda7a9268821c2a2b3e4c");
+
+/// Relay chain storage key for beefy.validatorSetId()
+pub const BEEFY_VALIDATOR_SET_ID: [u8; 32] =
+	hex!("08c41974a97dbf15cfbec28365bea2da8f05bccc2f70ec66a32999c5761156be");
+
+/// `bls381` public key of an authority
+pub type TBlsPublicKey = [u8; BLS_PUBLIC_KEY_LEN];
+/// Aggregate of the G1 signatures of several authorities
+pub type TBlsAggregateSignature = [u8; BLS_G1_SIGNATURE_LEN];
+
+#[derive(Clone, sp_std::fmt::Debug, PartialEq, Eq, Encode, Decode)]
+/// Aggregate BLS signature of a commitment and the keys of its signers
+pub struct BlsAggregateSignature {
+	/// Bitfield of the authorities that signed, bit `i % 8` of byte `i / 8` set for authority `i`
+	pub signers: Vec<u8>,
+	/// Aggregate of the signers' G1 signatures over `commitment.encode()`
+	pub aggregate_signature: TBlsAggregateSignature,
+	/// `bls381` public keys of the signers, in authority order
+	pub signer_keys: Vec<TBlsPublicKey>,
+	/// Flat proof hashes for the signer keys merkle multi-proof against the BLS keyset
+	/// commitment.
+	pub keyset_proof: Vec<[u8; 32]>,
+}
+
+#[derive(Clone, sp_std::fmt::Debug, PartialEq, Eq, Encode, Decode)]
+/// Signature of the commitment in a [`BlsMmrProof`]
+pub enum BlsCommitmentSignature {
+	/// Aggregate BLS signature, verified against the client's BLS keys of the signing set
+	#[codec(index = 0)]
+	Aggregate(BlsAggregateSignature),
+	/// ECDSA halves of the authorities' signatures. Used when the client holds no BLS keys for
+	/// the signing set, so a [`BlsKeysetProof`] can restore them.
+	#[codec(index = 1)]
+	Ecdsa {
+		/// Signatures for this commitment
+		signatures: Vec<SignatureWithAuthorityIndex>,
+		/// Flat proof hashes for authorities merkle multi-proof.
+		authority_proof: Vec<[u8; 32]>,
+	},
+}
+
+#[derive(Clone, sp_std::fmt::Debug, PartialEq, Eq, Encode, Decode)]
+/// Proof of the BLS keys of the current and next authority sets, read from relay chain state
+pub struct BlsKeysetProof {
+	/// SCALE-encoded relay chain header whose hash is the mmr leaf's parent hash
+	pub header: Vec<u8>,
+	/// Storage proof of `beefy.validatorSetId()`, `beefy.authorities()` and
+	/// `beefy.nextAuthorities()` against the header's state root
+	pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Mmr Update signed by an `ecdsa_bls381` authority set
+pub struct BlsMmrProof {
+	/// Commitment
+	pub commitment: sp_consensus_beefy::Commitment<u32>,
+	/// Signature of the commitment
+	pub signature: BlsCommitmentSignature,
+	/// Latest leaf added to mmr
+	pub latest_mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+	/// Proof for the latest mmr leaf
+	pub mmr_proof: sp_mmr_primitives::LeafProof<H256>,
+	/// BLS keys of the authority sets in the state of the leaf's parent. Needed whenever the
+	/// client is missing the keys of its current or next set.
+	pub keysets: Option<BlsKeysetProof>,
+}
+
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Parachain headers update with an aggregate BLS proof
+pub struct BlsConsensusMessage {
+	/// Parachain headers
+	pub parachain: ParachainProof,
+	/// proof for finalized mmr root
+	pub mmr: BlsMmrProof,
+}
+
 /// finality proof
 #[cfg(feature = "std")]
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
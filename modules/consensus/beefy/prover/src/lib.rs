@@ -23,7 +23,7 @@
 pub use rs_merkle;
 
 use anyhow::anyhow;
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use hex_literal::hex;
 use polkadot_sdk::*;
 use primitive_types::H256;
@@ -39,14 +39,16 @@ use subxt::{
 use subxt_core::config::HashFor;
 
 use beefy_verifier_primitives::{
-	ConsensusMessage, ConsensusState, MmrProof, ParachainHeader, ParachainProof,
-	SignatureWithAuthorityIndex, SignedCommitment,
+	BlsAggregateSignature, BlsCommitmentSignature, BlsConsensusMessage, BlsKeysetProof, BlsKeysets,
+	BlsMmrProof, ConsensusMessage, ConsensusState, MmrProof, ParachainHeader, ParachainProof,
+	SignatureWithAuthorityIndex, SignedCommitment, BEEFY_NEXT_AUTHORITIES,
+	ECDSA_BLS_AUTHORITY_ID_LEN,
 };
 use relay::{
-	beefy_mmr_leaf_next_authorities, fetch_latest_beefy_justification, fetch_mmr_proof,
-	paras_parachains,
+	beefy_mmr_leaf_next_authorities, fetch_ecdsa_bls_justification,
+	fetch_latest_beefy_justification, fetch_mmr_proof, paras_parachains,
 };
-use util::hash_authority_addresses;
+use util::{aggregate_bls_signatures, hash_authority_addresses, hash_authority_bls_keys};
 
 /// Methods for querying the relay chain
 pub mod relay;
@@ -175,6 +177,7 @@ impl<R: Config, P: Config> Prover<R, P> {
 				Some(latest_beefy_finalized),
 			)
 			.await?,
+			bls_keysets: self.bls_keysets(Some(latest_beefy_finalized)).await?,
 		};
 
 		Ok(client_state)
@@ -214,6 +217,56 @@ impl<R: Config, P: Config> Prover<R, P> {
 		Ok(current_authorities)
 	}
 
+	/// Fetch the ECDSA+BLS authority ids stored under `key` at the provided height. Returns
+	/// `None` if the relay chain's BEEFY keys are ECDSA only.
+	async fn ecdsa_bls_authorities(
+		&self,
+		key: &[u8],
+		at: Option<HashFor<R>>,
+	) -> Result<Option<Vec<[u8; ECDSA_BLS_AUTHORITY_ID_LEN]>>, anyhow::Error> {
+		let data = self
+			.relay_rpc
+			.state_get_storage(key, at)
+			.await?
+			.ok_or_else(|| anyhow!("No beefy authorities found!"))?;
+		Ok(Vec::<[u8; ECDSA_BLS_AUTHORITY_ID_LEN]>::decode_all(&mut data.as_ref()).ok())
+	}
+
+	/// Fetch the BEEFY validator set id at the provided height
+	async fn beefy_validator_set_id(&self, at: Option<HashFor<R>>) -> Result<u64, anyhow::Error> {
+		self.relay_rpc
+			.state_get_storage(BEEFY_VALIDATOR_SET_ID.as_slice(), at)
+			.await?
+			.map(|data| u64::decode(&mut data.as_ref()))
+			.transpose()?
+			.ok_or_else(|| anyhow!("No beefy validator set id found!"))
+	}
+
+	/// Fetch the commitments to the BLS keys of the current and next authority sets at the
+	/// provided height. Both are empty if the relay chain isn't ECDSA+BLS double signing.
+	pub async fn bls_keysets(&self, at: Option<HashFor<R>>) -> Result<BlsKeysets, anyhow::Error> {
+		let set_id = self.beefy_validator_set_id(at).await?;
+		let keyset = |id: u64, authorities: Option<Vec<[u8; ECDSA_BLS_AUTHORITY_ID_LEN]>>| {
+			authorities.map(|authorities| {
+				let leaves = hash_authority_bls_keys(&authorities);
+				let tree = rs_merkle::MerkleTree::<util::MerkleHasher>::from_leaves(&leaves);
+				BeefyAuthoritySet {
+					id,
+					len: authorities.len() as u32,
+					keyset_commitment: tree.root().unwrap_or_default().into(),
+				}
+			})
+		};
+
+		Ok(BlsKeysets {
+			current: keyset(set_id, self.ecdsa_bls_authorities(&BEEFY_AUTHORITIES, at).await?),
+			next: keyset(
+				set_id + 1,
+				self.ecdsa_bls_authorities(&BEEFY_NEXT_AUTHORITIES, at).await?,
+			),
+		})
+	}
+
 	/// This will fetch the latest leaf in the mmr as well as a proof for this leaf in the latest
 	/// mmr root hash.
 	pub async fn consensus_proof(
@@ -276,4 +329,153 @@ impl<R: Config, P: Config> Prover<R, P> {
 
 		Ok(ConsensusMessage { mmr, parachain })
 	}
+	/// Build a BLS proof for the BEEFY justification at `block_number`. The BLS halves of the
+	/// `ecdsa_bls381` signatures are aggregated into one signature, unless the client holds no
+	/// BLS keys for the signing set. Then the proof carries the ECDSA halves instead. Whenever the
+	/// client is missing BLS keys, the proof also carries the keys of the current and next sets
+	/// when the leaf's parent state holds them.
+	pub async fn bls_consensus_proof(
+		&self,
+		block_number: u32,
+		consensus_state: &ConsensusState,
+	) -> Result<BlsConsensusMessage, anyhow::Error> {
+		let block_hash = self
+			.relay_rpc
+			.chain_get_block_hash(Some(block_number.into()))
+			.await?
+			.ok_or_else(|| anyhow!("Failed to query blockhash for blocknumber"))?;
+		let signed_commitment = fetch_ecdsa_bls_justification(&self.relay_rpc, block_hash).await?;
+		let commitment = signed_commitment.commitment.clone();
+
+		let (mmr_proof, latest_leaf) =
+			fetch_mmr_proof(&self.relay_rpc, block_number.try_into()?, self.query_batch_size)
+				.await?;
+
+		let authorities =
+			self.ecdsa_bls_authorities(&BEEFY_AUTHORITIES, Some(block_hash))
+				.await?
+				.ok_or_else(|| anyhow!("Relay chain BEEFY authorities have no BLS keys"))?;
+
+		let keyset = if commitment.validator_set_id == consensus_state.current_authorities.id {
+			&consensus_state.bls_keysets.current
+		} else {
+			&consensus_state.bls_keysets.next
+		};
+		let signed_with_bls =
+			keyset.as_ref().map(|keyset| keyset.id) == Some(commitment.validator_set_id);
+
+		let signature = if signed_with_bls {
+			let mut signers = vec![0u8; authorities.len().div_ceil(8)];
+			let mut indices = vec![];
+			for (index, signature) in signed_commitment.signatures.iter().enumerate() {
+				if signature.is_some() {
+					signers[index / 8] |= 1 << (index % 8);
+					indices.push(index);
+				}
+			}
+			let aggregate_signature = aggregate_bls_signatures(
+				signed_commitment.signatures.iter().flatten().map(|signature| &signature[65..]),
+			)?;
+
+			let tree = rs_merkle::MerkleTree::<util::MerkleHasher>::from_leaves(
+				&hash_authority_bls_keys(&authorities),
+			);
+			let keyset_proof = tree.proof(&indices).proof_hashes().to_vec();
+			let signer_keys = indices
+				.iter()
+				.map(|index| authorities[*index][33..].try_into())
+				.collect::<Result<Vec<_>, _>>()?;
+
+			BlsCommitmentSignature::Aggregate(BlsAggregateSignature {
+				signers,
+				aggregate_signature,
+				signer_keys,
+				keyset_proof,
+			})
+		} else {
+			log::trace!(
+				target: "beefy-prover",
+				"No BLS keys known for authority set {}, signing with ECDSA",
+				commitment.validator_set_id
+			);
+			let signatures = signed_commitment
+				.signatures
+				.iter()
+				.enumerate()
+				.filter_map(|(index, signature)| {
+					let mut ecdsa: [u8; 65] = signature.as_ref()?[..65].try_into().ok()?;
+					ecdsa[64] += 27;
+					Some(SignatureWithAuthorityIndex { index: index as u32, signature: ecdsa })
+				})
+				.collect::<Vec<_>>();
+			let authority_address_hashes =
+				hash_authority_addresses(authorities.iter().map(|id| id[..33].to_vec()).collect())?;
+			let authority_proof = build_authority_proof(&signatures, &authority_address_hashes);
+
+			BlsCommitmentSignature::Ecdsa { signatures, authority_proof }
+		};
+
+		let next_keyset = consensus_state.bls_keysets.next.as_ref().map(|keyset| keyset.id);
+		let keysets =
+			if !signed_with_bls || next_keyset != Some(latest_leaf.beefy_next_authority_set.id) {
+				self.keyset_proof(&latest_leaf).await?
+			} else {
+				None
+			};
+
+		let mmr = BlsMmrProof {
+			commitment,
+			signature,
+			latest_mmr_leaf: latest_leaf.clone(),
+			mmr_proof,
+			keysets,
+		};
+
+		let heads = paras_parachains(
+			&self.relay_rpc,
+			Some(HashFor::<R>::decode(&mut &*latest_leaf.parent_number_and_hash.1.encode())?),
+		)
+		.await?;
+
+		let parachain = build_parachain_proof(&self.para_ids, &heads);
+
+		Ok(BlsConsensusMessage { mmr, parachain })
+	}
+
+	/// Prove the BLS keys of the current and next authority sets from the state of the leaf's
+	/// parent. Returns `None` if the parent state doesn't hold the leaf's next set yet, which
+	/// happens for the leaf that enacts a new session.
+	pub async fn keyset_proof(
+		&self,
+		leaf: &sp_consensus_beefy::mmr::MmrLeaf<u32, H256, H256, H256>,
+	) -> Result<Option<BlsKeysetProof>, anyhow::Error> {
+		let parent_hash = HashFor::<R>::decode(&mut &*leaf.parent_number_and_hash.1.encode())?;
+		let set_id = self.beefy_validator_set_id(Some(parent_hash)).await?;
+		if set_id + 1 != leaf.beefy_next_authority_set.id {
+			return Ok(None);
+		}
+
+		let header = self
+			.relay_rpc
+			.chain_get_header(Some(parent_hash))
+			.await?
+			.ok_or_else(|| anyhow!("Header {parent_hash:?} not found"))?;
+		let proof = self
+			.relay_rpc
+			.state_get_read_proof(
+				vec![
+					BEEFY_VALIDATOR_SET_ID.as_slice(),
+					BEEFY_AUTHORITIES.as_slice(),
+					BEEFY_NEXT_AUTHORITIES.as_slice(),
+				],
+				Some(parent_hash),
+			)
+			.await?
+			.proof
+			.into_iter()
+			.map(|node| node.0)
+			.collect();
+
+		Ok(Some(BlsKeysetProof { header: header.encode(), proof }))
+	}
 }
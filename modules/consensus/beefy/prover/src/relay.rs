@@ -37,6 +37,8 @@ use subxt::{
 	Config,
 };
 
+use beefy_verifier_primitives::ECDSA_BLS_SIGNATURE_LEN;
+
 use crate::{
 	util::MerkleHasher, BEEFY_MMR_LEAF_BEEFY_NEXT_AUTHORITIES, BEEFY_VALIDATOR_SET_ID,
 	PARAS_PARACHAINS,
@@ -82,6 +84,32 @@ pub async fn fetch_latest_beefy_justification<T: Config>(
 	Ok((signed_commitment, latest_beefy_finalized))
 }
 
+/// Get the beefy justification for block_hash from a relay chain using ECDSA+BLS double signing
+pub async fn fetch_ecdsa_bls_justification<T: Config>(
+	rpc: &LegacyRpcMethods<T>,
+	block_hash: HashFor<T>,
+) -> Result<SignedCommitment<u32, [u8; ECDSA_BLS_SIGNATURE_LEN]>, anyhow::Error> {
+	let block = rpc
+		.chain_get_block(Some(block_hash))
+		.await?
+		.ok_or_else(|| anyhow!("Block {block_hash:?} not found"))?;
+
+	let beefy_justification = block
+		.justifications
+		.into_iter()
+		.flatten()
+		.find_map(|justification| {
+			(justification.0 == sp_consensus_beefy::BEEFY_ENGINE_ID).then(|| justification.1)
+		})
+		.ok_or_else(|| anyhow!("Block {block_hash:?} has no beefy justification"))?;
+	let VersionedFinalityProof::V1(signed_commitment) = VersionedFinalityProof::<
+		u32,
+		[u8; ECDSA_BLS_SIGNATURE_LEN],
+	>::decode(&mut &*beefy_justification)?;
+
+	Ok(signed_commitment)
+}
+
 /// Parathreads whitelisted to be added to the beefy mmr leaf parachains header root
 const BEEFY_WHITELISTED_PARATHREADS: &'static [u32] = &[3367];
 
@@ -24,7 +24,10 @@ use sp_trie::{LayoutV0, Recorder, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut
 use subxt::{backend::legacy::LegacyRpcMethods, Config};
 use subxt_core::config::HashFor;
 
-use beefy_verifier_primitives::{Hash, SignatureWithAuthorityIndex};
+use beefy_verifier_primitives::{
+	Hash, SignatureWithAuthorityIndex, BLS_G1_SIGNATURE_LEN, ECDSA_BLS_AUTHORITY_ID_LEN,
+};
+use w3f_bls::{SerializableToBytes, Signature, TinyBLS381};
 
 /// Holds the timestamp inherent alongside a merkle-patricia trie proof of its existence in a given
 /// block.
@@ -139,6 +142,34 @@ pub fn hash_authority_addresses(
 	Ok(authority_address_hashes)
 }
 
+/// Hash the BLS keys of ECDSA+BLS authority ids, the leaves of a BLS keyset commitment
+pub fn hash_authority_bls_keys(authorities: &[[u8; ECDSA_BLS_AUTHORITY_ID_LEN]]) -> Vec<Hash> {
+	authorities.iter().map(|id| keccak_256(&id[33..])).collect()
+}
+
+/// Aggregate the G1 signatures at the start of `bls381` signatures over the same message
+pub fn aggregate_bls_signatures<'a>(
+	signatures: impl IntoIterator<Item = &'a [u8]>,
+) -> Result<[u8; BLS_G1_SIGNATURE_LEN], anyhow::Error> {
+	let mut signatures = signatures.into_iter().map(|signature| {
+		let signature = signature
+			.get(..BLS_G1_SIGNATURE_LEN)
+			.ok_or_else(|| anyhow!("BLS signature is too short"))?;
+		Signature::<TinyBLS381>::from_bytes(signature)
+			.map_err(|e| anyhow!("Invalid BLS signature: {e:?}"))
+	});
+	let mut aggregate =
+		signatures.next().ok_or_else(|| anyhow!("No BLS signatures to aggregate"))??;
+	for signature in signatures {
+		aggregate.0 += signature?.0;
+	}
+
+	aggregate
+		.to_bytes()
+		.try_into()
+		.map_err(|_| anyhow!("Aggregate BLS signature has an invalid length"))
+}
+
 /// Merkle Hasher for mmr library
 #[derive(Clone)]
 pub struct MerkleHasher;
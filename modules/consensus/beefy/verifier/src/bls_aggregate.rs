@@ -0,0 +1,284 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Aggregated BLS BEEFY proof verification.
+//!
+//! Under `ecdsa_bls381` double signing every authority signs the commitment with both keys. The
+//! BLS halves are Substrate's w3f TinyBLS381 signatures, whose G1 parts are aggregated off-chain,
+//! so the verifier checks a single pairing instead of recovering 2/3+1 ECDSA signatures. The
+//! BEEFY keyset commitment only covers the ECDSA keys, so the client keeps its own commitment to
+//! each authority set's BLS keys, proven from the relay chain's `beefy` pallet storage.
+//!
+//! A client that is missing the BLS keys of the signing set, e.g. because no proof carried them
+//! during the previous session, accepts the ECDSA halves of the signatures instead, along with
+//! the proof that restores the keys.
+
+use crate::{
+	EcdsaRecover, MerkleHasher, check_participation_threshold, error::Error,
+	mmr_root_from_commitment, verify_mmr_leaf, verify_mmr_update_proof, verify_parachain_headers,
+};
+use alloc::{format, vec::Vec};
+use beefy_verifier_primitives::{
+	BEEFY_AUTHORITIES, BEEFY_NEXT_AUTHORITIES, BEEFY_VALIDATOR_SET_ID, BLS_G2_PUBLIC_KEY_OFFSET,
+	BlsAggregateSignature, BlsCommitmentSignature, BlsConsensusMessage, BlsKeysetProof,
+	BlsMmrProof, ConsensusState, ECDSA_BLS_AUTHORITY_ID_LEN, MmrProof, ParachainHeader,
+	SignedCommitment,
+};
+use codec::{Decode, Encode};
+use ismp::messaging::Keccak256;
+use polkadot_sdk::{
+	sp_consensus_beefy::{
+		Commitment,
+		mmr::{BeefyAuthoritySet, MmrLeaf},
+	},
+	sp_runtime::{
+		generic::Header,
+		traits::{BlakeTwo256, Header as _},
+	},
+	sp_trie::{LayoutV0, StorageProof, read_trie_value},
+};
+use primitive_types::H256;
+use rs_merkle::{MerkleProof, MerkleTree};
+use w3f_bls::{Message, PublicKey, SerializableToBytes, Signature, TinyBLS381};
+
+/// Verify an aggregated BLS consensus proof and return the new trusted consensus state and
+/// verified parachain headers.
+pub fn verify_bls_consensus<H: Keccak256 + EcdsaRecover + Send + Sync>(
+	trusted_state: ConsensusState,
+	proof: BlsConsensusMessage,
+) -> Result<(Vec<u8>, Vec<ParachainHeader>), Error> {
+	let (state, heads_root) = verify_bls_mmr_update_proof::<H>(trusted_state, proof.mmr)?;
+	let verified_headers = verify_parachain_headers::<H>(heads_root, proof.parachain)?;
+	Ok((state.encode(), verified_headers))
+}
+
+/// Verifies a new mmr root update signed by an `ecdsa_bls381` authority set. An aggregate
+/// signature is checked against the signing set's BLS keys with a single pairing, ECDSA
+/// signatures are verified like a naive proof. The BLS keys of the client's authority sets are
+/// then updated from the proof's keysets, if any.
+pub fn verify_bls_mmr_update_proof<H: Keccak256 + EcdsaRecover + Send + Sync>(
+	mut trusted_state: ConsensusState,
+	mmr: BlsMmrProof,
+) -> Result<(ConsensusState, H256), Error> {
+	let latest_height = mmr.commitment.block_number;
+
+	if trusted_state.latest_beefy_height >= latest_height {
+		return Err(Error::StaleHeight {
+			trusted_height: trusted_state.latest_beefy_height,
+			current_height: latest_height,
+		});
+	}
+
+	// The keyset proof is read from the state of the leaf's parent, so the leaf must be the one
+	// appended at the commitment's block.
+	let parent_number = mmr.latest_mmr_leaf.parent_number_and_hash.0;
+	if parent_number.saturating_add(1) != latest_height {
+		return Err(Error::StaleMmrLeaf { parent_number, block_number: latest_height });
+	}
+
+	let heads_root = match mmr.signature {
+		BlsCommitmentSignature::Aggregate(signature) => {
+			verify_aggregate_signature::<H>(&trusted_state, &mmr.commitment, signature)?;
+
+			let mmr_root = mmr_root_from_commitment(&mmr.commitment)?;
+			verify_mmr_leaf::<H>(&mmr.latest_mmr_leaf, &mmr.mmr_proof, mmr_root)?;
+
+			let next_authority_set = &mmr.latest_mmr_leaf.beefy_next_authority_set;
+			if next_authority_set.id > trusted_state.next_authorities.id {
+				trusted_state.rotate_authorities(next_authority_set.clone());
+			}
+			trusted_state.latest_beefy_height = latest_height;
+
+			mmr.latest_mmr_leaf.leaf_extra
+		},
+		BlsCommitmentSignature::Ecdsa { signatures, authority_proof } => {
+			let (state, heads_root) = verify_mmr_update_proof::<H>(
+				trusted_state,
+				MmrProof {
+					signed_commitment: SignedCommitment { commitment: mmr.commitment, signatures },
+					latest_mmr_leaf: mmr.latest_mmr_leaf.clone(),
+					mmr_proof: mmr.mmr_proof,
+					authority_proof,
+				},
+			)?;
+			trusted_state = state;
+			heads_root
+		},
+	};
+
+	if let Some(keyset_proof) = mmr.keysets {
+		let (current, next) = verify_keyset_proof::<H>(&mmr.latest_mmr_leaf, keyset_proof)?;
+		if current.id == trusted_state.current_authorities.id {
+			trusted_state.bls_keysets.current = Some(current);
+		}
+		if next.id == trusted_state.next_authorities.id {
+			trusted_state.bls_keysets.next = Some(next);
+		}
+	}
+
+	Ok((trusted_state, heads_root))
+}
+
+/// Verifies the aggregate BLS signature of `commitment` against the BLS keys of the signers,
+/// proven against the client's BLS keyset commitment of the signing authority set.
+fn verify_aggregate_signature<H: Keccak256>(
+	trusted_state: &ConsensusState,
+	commitment: &Commitment<u32>,
+	signature: BlsAggregateSignature,
+) -> Result<(), Error> {
+	let (authority_set, keyset) =
+		if commitment.validator_set_id == trusted_state.current_authorities.id {
+			(&trusted_state.current_authorities, &trusted_state.bls_keysets.current)
+		} else if commitment.validator_set_id == trusted_state.next_authorities.id {
+			(&trusted_state.next_authorities, &trusted_state.bls_keysets.next)
+		} else {
+			return Err(Error::UnknownAuthoritySet { id: commitment.validator_set_id });
+		};
+	let keyset = keyset
+		.as_ref()
+		.filter(|keyset| keyset.id == authority_set.id)
+		.ok_or(Error::UnknownBlsKeyset { id: authority_set.id })?;
+
+	let signers = signer_indices(&signature.signers, keyset.len)?;
+	if !check_participation_threshold(signers.len() as u32, keyset.len) {
+		return Err(Error::SuperMajorityRequired);
+	}
+	if signers.len() != signature.signer_keys.len() {
+		return Err(Error::InvalidSignerBitfield);
+	}
+
+	let key_hashes = signature
+		.signer_keys
+		.iter()
+		.map(|key| H::keccak256(key).into())
+		.collect::<Vec<[u8; 32]>>();
+	let merkle_proof = MerkleProof::<MerkleHasher<H>>::new(signature.keyset_proof);
+	if !merkle_proof.verify(
+		keyset.keyset_commitment.into(),
+		&signers,
+		&key_hashes,
+		keyset.len as usize,
+	) {
+		return Err(Error::InvalidBlsAuthoritiesProof);
+	}
+
+	// TinyBLS381 signs in G1 and verifies against the G2 half of each double public key. The
+	// keys are registered with a proof of possession, which is what makes aggregating
+	// signatures over one message safe.
+	let mut keys = signature.signer_keys.iter().map(|key| {
+		PublicKey::<TinyBLS381>::from_bytes(&key[BLS_G2_PUBLIC_KEY_OFFSET..])
+			.map_err(|_| Error::InvalidBlsPublicKey)
+	});
+	let mut aggregate_key = keys.next().ok_or(Error::SuperMajorityRequired)??;
+	for key in keys {
+		aggregate_key.0 += key?.0;
+	}
+	let aggregate_signature = Signature::<TinyBLS381>::from_bytes(&signature.aggregate_signature)
+		.map_err(|_| Error::BlsSignatureVerificationFailed)?;
+
+	// Substrate's `bls381` signs the raw message under an empty context, the ECDSA half of the
+	// pair signs its keccak256 hash.
+	let message = Message::new(b"", &commitment.encode());
+	if !aggregate_signature.verify(&message, &aggregate_key) {
+		return Err(Error::BlsSignatureVerificationFailed);
+	}
+
+	Ok(())
+}
+
+/// Verifies the BLS keys of the relay chain's current and next authority sets from the state of
+/// the leaf's parent block, and returns the commitments to them.
+pub fn verify_keyset_proof<H: Keccak256>(
+	leaf: &MmrLeaf<u32, H256, H256, H256>,
+	proof: BlsKeysetProof,
+) -> Result<(BeefyAuthoritySet<H256>, BeefyAuthoritySet<H256>), Error> {
+	let header = Header::<u32, BlakeTwo256>::decode(&mut &proof.header[..])
+		.map_err(|e| Error::InvalidKeysetProof(format!("Cannot decode relay header: {e:?}")))?;
+	if header.hash() != leaf.parent_number_and_hash.1 {
+		return Err(Error::InvalidKeysetProof("Header is not the leaf's parent".into()));
+	}
+
+	let db = StorageProof::new(proof.proof).into_memory_db::<BlakeTwo256>();
+	let read = |key: &[u8]| {
+		read_trie_value::<LayoutV0<BlakeTwo256>, _>(&db, header.state_root(), key, None, None)
+			.map_err(|e| Error::InvalidKeysetProof(format!("Error reading from trie: {e:?}")))?
+			.ok_or_else(|| Error::InvalidKeysetProof(format!("Missing storage value {key:?}")))
+	};
+	let authorities = |key: &[u8]| {
+		Vec::<[u8; ECDSA_BLS_AUTHORITY_ID_LEN]>::decode(&mut &read(key)?[..])
+			.map_err(|e| Error::InvalidKeysetProof(format!("Cannot decode authorities: {e:?}")))
+	};
+
+	let set_id = u64::decode(&mut &read(&BEEFY_VALIDATOR_SET_ID)?[..])
+		.map_err(|e| Error::InvalidKeysetProof(format!("Cannot decode set id: {e:?}")))?;
+	let current = authorities(&BEEFY_AUTHORITIES)?;
+	let next = authorities(&BEEFY_NEXT_AUTHORITIES)?;
+
+	// The leaf commits to the ECDSA keys of the next set, so its BLS keys must be read from the
+	// same state.
+	let next_authority_set = &leaf.beefy_next_authority_set;
+	if set_id.saturating_add(1) != next_authority_set.id ||
+		next.len() != next_authority_set.len as usize
+	{
+		return Err(Error::InvalidKeysetProof(format!(
+			"State holds next set {} of {} authorities, leaf announces set {} of {}",
+			set_id.saturating_add(1),
+			next.len(),
+			next_authority_set.id,
+			next_authority_set.len
+		)));
+	}
+
+	let keyset = |id: u64, authorities: &[[u8; ECDSA_BLS_AUTHORITY_ID_LEN]]| BeefyAuthoritySet {
+		id,
+		len: authorities.len() as u32,
+		keyset_commitment: bls_keyset_commitment::<H>(authorities),
+	};
+	Ok((keyset(set_id, &current), keyset(next_authority_set.id, &next)))
+}
+
+/// Merkle root over `keccak256(bls_public_key)` of `ecdsa_bls381` authority ids, in authority
+/// order.
+pub fn bls_keyset_commitment<H: Keccak256>(
+	authorities: &[[u8; ECDSA_BLS_AUTHORITY_ID_LEN]],
+) -> H256 {
+	let leaves = authorities
+		.iter()
+		.map(|id| H::keccak256(&id[33..]).into())
+		.collect::<Vec<[u8; 32]>>();
+	MerkleTree::<MerkleHasher<H>>::from_leaves(&leaves)
+		.root()
+		.unwrap_or_default()
+		.into()
+}
+
+/// Indices of the set bits in `bitfield`, rejecting bits beyond the authority set.
+fn signer_indices(bitfield: &[u8], authorities: u32) -> Result<Vec<usize>, Error> {
+	let mut indices = Vec::new();
+	for (byte_index, byte) in bitfield.iter().enumerate() {
+		for bit in 0..8 {
+			if byte & (1 << bit) == 0 {
+				continue;
+			}
+			let index = byte_index * 8 + bit;
+			if index >= authorities as usize {
+				return Err(Error::InvalidSignerBitfield);
+			}
+			indices.push(index);
+		}
+	}
+
+	Ok(indices)
+}
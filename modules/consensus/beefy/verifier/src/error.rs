@@ -1,5 +1,5 @@
 //! Typed errors for the BEEFY consensus support — both the verifier
-//! (`verify_consensus`, `verify_sp1_consensus`) and the `ismp-beefy`
+//! (`verify_consensus`, `verify_sp1_consensus`, `verify_bls_consensus`) and the `ismp-beefy`
 //! client wrapper.
 //!
 //! The same enum spans both layers so the ismp client doesn't have to
@@ -73,6 +73,28 @@ pub enum Error {
 	/// The SP1 Groth16 verifier rejected the proof bytes.
 	#[error("SP1 proof verification failed")]
 	Sp1VerificationFailed,
+	/// The client holds no BLS keys for the authority set that signed the commitment.
+	#[error("No BLS keyset for authority set id {id}")]
+	UnknownBlsKeyset {
+		/// Authority set id from the commitment.
+		id: u64,
+	},
+	/// The signer bitfield marks authorities outside the set, or disagrees with the number of
+	/// signer keys in the proof.
+	#[error("Invalid signer bitfield")]
+	InvalidSignerBitfield,
+	/// The merkle multi-proof of the signers' BLS keys does not verify.
+	#[error("Invalid BLS authorities proof")]
+	InvalidBlsAuthoritiesProof,
+	/// A signer's BLS public key is not a valid compressed G2 point.
+	#[error("Invalid BLS public key")]
+	InvalidBlsPublicKey,
+	/// The aggregate BLS signature does not verify against the signers' aggregate key.
+	#[error("BLS signature verification failed")]
+	BlsSignatureVerificationFailed,
+	/// The proof of the authority sets' BLS keys does not verify.
+	#[error("Invalid BLS keyset proof: {0}")]
+	InvalidKeysetProof(String),
 
 	// -- ismp-beefy client wrapper --
 	/// The trusted state failed to SCALE-decode into a `ConsensusState`.
@@ -87,7 +109,10 @@ pub enum Error {
 	/// The SP1 proof payload failed to SCALE-decode.
 	#[error("Cannot decode SP1 proof: {0}")]
 	DecodeSp1Proof(String),
-	/// The leading proof byte didn't match either `PROOF_TYPE_NAIVE` or `PROOF_TYPE_SP1`.
+	/// The aggregated BLS proof payload failed to SCALE-decode.
+	#[error("Cannot decode BLS proof: {0}")]
+	DecodeBlsProof(String),
+	/// The leading proof byte isn't an allowed `PROOF_TYPE_*`.
 	#[error("Unknown proof type: {0}")]
 	UnknownProofType(u8),
 	/// A parachain header in the consensus message failed to SCALE-decode.
@@ -16,13 +16,15 @@
 //! BEEFY consensus proof verifier.
 //!
 //! Provides a chain-agnostic verifier for BEEFY finality proofs originating from a Polkadot-style
-//! relay chain, as well as an [`sp1`] module that verifies SP1-compressed BEEFY proofs.
+//! relay chain, as well as an [`sp1`] module that verifies SP1-compressed BEEFY proofs and a
+//! [`bls_aggregate`] module that verifies aggregate BLS signatures from ECDSA+BLS double signing.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
 extern crate alloc;
 
+pub mod bls_aggregate;
 pub mod error;
 pub mod sp1;
 #[cfg(test)]
@@ -41,6 +43,10 @@ use merkle_mountain_range::{
 	Error as MmrError, Merge as MmrMerge, MerkleProof as MmrMerkleProof, leaf_index_to_mmr_size,
 	leaf_index_to_pos,
 };
+use polkadot_sdk::{
+	sp_consensus_beefy::{Commitment, mmr::MmrLeaf},
+	sp_mmr_primitives::LeafProof,
+};
 use primitive_types::H256;
 use rs_merkle::{Hasher, MerkleProof};
 
@@ -132,15 +138,7 @@ pub fn verify_mmr_update_proof<H: Keccak256 + EcdsaRecover + Send + Sync>(
 		return Err(Error::SuperMajorityRequired);
 	}
 
-	let mmr_root_data = commitment
-		.payload
-		.get_raw(&MMR_ROOT_PAYLOAD_ID)
-		.ok_or(Error::MmrRootHashMissing)?;
-
-	if mmr_root_data.len() != 32 {
-		return Err(Error::InvalidMmrRootHashLength { len: mmr_root_data.len() });
-	}
-	let mmr_root = H256::from_slice(mmr_root_data);
+	let mmr_root = mmr_root_from_commitment(&commitment)?;
 
 	let commitment_hash = H::keccak256(&commitment.encode());
 	let mut authority_leaves: Vec<[u8; 32]> = Vec::new();
@@ -174,11 +172,10 @@ pub fn verify_mmr_update_proof<H: Keccak256 + EcdsaRecover + Send + Sync>(
 		Err(Error::InvalidAuthoritiesProof)?;
 	}
 
-	verify_mmr_leaf::<H>(&mmr, mmr_root)?;
+	verify_mmr_leaf::<H>(&mmr.latest_mmr_leaf, &mmr.mmr_proof, mmr_root)?;
 
 	if mmr.latest_mmr_leaf.beefy_next_authority_set.id > trusted_state.next_authorities.id {
-		trusted_state.current_authorities = trusted_state.next_authorities.clone();
-		trusted_state.next_authorities = mmr.latest_mmr_leaf.beefy_next_authority_set.clone();
+		trusted_state.rotate_authorities(mmr.latest_mmr_leaf.beefy_next_authority_set.clone());
 	}
 
 	trusted_state.latest_beefy_height = latest_height;
@@ -222,8 +219,23 @@ pub fn verify_parachain_headers<H: Keccak256>(
 	Ok(parachain_proof.parachains)
 }
 
+/// Read the mmr root hash out of a commitment's payload
+fn mmr_root_from_commitment(commitment: &Commitment<u32>) -> Result<H256, Error> {
+	let mmr_root_data = commitment
+		.payload
+		.get_raw(&MMR_ROOT_PAYLOAD_ID)
+		.ok_or(Error::MmrRootHashMissing)?;
+
+	if mmr_root_data.len() != 32 {
+		return Err(Error::InvalidMmrRootHashLength { len: mmr_root_data.len() });
+	}
+
+	Ok(H256::from_slice(mmr_root_data))
+}
+
 fn verify_mmr_leaf<H: Keccak256 + Send + Sync>(
-	mmr: &MmrProof,
+	latest_mmr_leaf: &MmrLeaf<u32, H256, H256, H256>,
+	leaf_proof: &LeafProof<H256>,
 	mmr_root: H256,
 ) -> Result<(), Error> {
 	// `leaf_indices` is supplied by the relayer in the unsigned consensus message;
@@ -231,16 +243,16 @@ fn verify_mmr_leaf<H: Keccak256 + Send + Sync>(
 	// after the BEEFY signature and authority membership checks had already succeeded.
 	// This verifier checks a single MMR leaf, so reject any proof that does not carry
 	// exactly one leaf index.
-	if mmr.mmr_proof.leaf_indices.len() != 1 {
+	if leaf_proof.leaf_indices.len() != 1 {
 		Err(Error::InvalidMmrProof)?
 	}
-	let leaf_index = mmr.mmr_proof.leaf_indices[0];
-	let leaf_hash = H::keccak256(&mmr.latest_mmr_leaf.encode());
+	let leaf_index = leaf_proof.leaf_indices[0];
+	let leaf_hash = H::keccak256(&latest_mmr_leaf.encode());
 	let mmr_size = leaf_index_to_mmr_size(leaf_index);
 
 	let mmr_proof = MmrMerkleProof::<[u8; 32], KeccakMerge<H>>::new(
 		mmr_size,
-		mmr.mmr_proof.items.iter().map(|h| (*h).into()).collect(),
+		leaf_proof.items.iter().map(|h| (*h).into()).collect(),
 	);
 	let leaf_pos = leaf_index_to_pos(leaf_index);
 	let leaf = (leaf_pos, leaf_hash.into());
@@ -110,8 +110,7 @@ pub fn verify_sp1_consensus<H: Keccak256 + Send + Sync>(
 
 	let mut new_state = trusted_state;
 	if proof.mmr_leaf.beefy_next_authority_set.id > new_state.next_authorities.id {
-		new_state.current_authorities = new_state.next_authorities.clone();
-		new_state.next_authorities = proof.mmr_leaf.beefy_next_authority_set.clone();
+		new_state.rotate_authorities(proof.mmr_leaf.beefy_next_authority_set.clone());
 	}
 	new_state.latest_beefy_height = proof.block_number;
 
@@ -16,7 +16,7 @@
 use codec::{Decode, Encode};
 use hex_literal::hex;
 use polkadot_sdk::{sp_consensus_beefy::VersionedFinalityProof, *};
-use sp_core::H256;
+use sp_core::{H256, KeccakHasher, Pair as _, ecdsa_bls381};
 use sp_io::hashing::keccak_256;
 use subxt::{PolkadotConfig, backend::legacy::LegacyRpcMethods, ext::subxt_rpcs::rpc_params};
 
@@ -24,11 +24,15 @@ use beefy_prover::{
 	Prover,
 	relay::{fetch_mmr_proof, paras_parachains},
 	rs_merkle::MerkleTree,
-	util::{MerkleHasher, hash_authority_addresses},
+	util::{
+		MerkleHasher, aggregate_bls_signatures, hash_authority_addresses, hash_authority_bls_keys,
+	},
 };
 use beefy_verifier_primitives::{
-	ConsensusMessage, ConsensusState, MmrProof, ParachainHeader, ParachainProof,
-	SignatureWithAuthorityIndex, SignedCommitment,
+	BEEFY_AUTHORITIES, BEEFY_NEXT_AUTHORITIES, BEEFY_VALIDATOR_SET_ID, BlsAggregateSignature,
+	BlsCommitmentSignature, BlsKeysetProof, BlsKeysets, BlsMmrProof, ConsensusMessage,
+	ConsensusState, ECDSA_BLS_AUTHORITY_ID_LEN, ECDSA_BLS_SIGNATURE_LEN, MmrProof, ParachainHeader,
+	ParachainProof, SignatureWithAuthorityIndex, SignedCommitment,
 };
 use ismp::messaging::Keccak256;
 use polkadot_sdk::sp_consensus_beefy::{
@@ -38,7 +42,12 @@ use polkadot_sdk::sp_consensus_beefy::{
 };
 use sp_mmr_primitives::LeafProof;
 
-use crate::{EcdsaRecover, error::Error, verify_consensus, verify_mmr_update_proof};
+use crate::{
+	EcdsaRecover,
+	bls_aggregate::{bls_keyset_commitment, verify_bls_mmr_update_proof},
+	error::Error,
+	verify_consensus, verify_mmr_update_proof,
+};
 
 struct TestHost;
 
@@ -319,7 +328,8 @@ fn test_sp1_verify_consensus_accepts_solidity_fixture() {
 
 	// Proof payload matches SP1Beefy.sol:verifyConsensus's `abi.decode(...)` call:
 	// a sequence of four top-level types, not a struct wrapper.
-	type ProofTuple = sol! { (MiniCommitment, PartialBeefyMmrLeaf, ParachainHeader[], bytes, bytes32) };
+	type ProofTuple =
+		sol! { (MiniCommitment, PartialBeefyMmrLeaf, ParachainHeader[], bytes, bytes32) };
 	let (commitment, leaf, headers, plonk_proof, nonce) =
 		<ProofTuple as SolType>::abi_decode_sequence(&proof_bytes).expect("decode proof tuple");
 	let sp1_proof = Sp1BeefyProof {
@@ -392,6 +402,7 @@ fn rejects_sub_supermajority_from_named_authority_set() {
 		mmr_root_hash: H256::zero(),
 		current_authorities: authority_set(CURRENT_SET_ID, 100),
 		next_authorities: authority_set(NEXT_SET_ID, 3),
+		bls_keysets: Default::default(),
 	};
 
 	let payload = Payload::from_single_entry(*b"mh", MmrRootHash::zero().0.to_vec());
@@ -424,6 +435,7 @@ fn rejects_sp1_proof_carrying_a_stale_mmr_leaf() {
 		mmr_root_hash: H256::zero(),
 		current_authorities: authority_set(SET_ID, 100),
 		next_authorities: authority_set(SET_ID + 1, 100),
+		bls_keysets: Default::default(),
 	};
 
 	let mut proof = Sp1BeefyProof {
@@ -453,8 +465,357 @@ fn rejects_sp1_proof_carrying_a_stale_mmr_leaf() {
 
 	// Swap in a leaf from an earlier block, as an attacker replaying a historical leaf would.
 	proof.mmr_leaf.parent_number_and_hash.0 = BLOCK_NUMBER - 500;
-	let stale = sp_io::TestExternalities::default().execute_with(|| {
-		crate::sp1::verify_sp1_consensus::<TestHost>(trusted_state, proof, VKEY)
-	});
+	let stale = sp_io::TestExternalities::default()
+		.execute_with(|| crate::sp1::verify_sp1_consensus::<TestHost>(trusted_state, proof, VKEY));
 	assert!(matches!(stale, Err(Error::StaleMmrLeaf { .. })), "got {stale:?}");
 }
+
+/// An `ecdsa_bls381` authority, the same key pair type relay chains double sign BEEFY
+/// commitments with.
+fn bls_pair(index: u8) -> ecdsa_bls381::Pair {
+	ecdsa_bls381::Pair::from_seed_slice(&[index + 1; 32]).unwrap()
+}
+
+fn bls_authority(index: u8) -> [u8; ECDSA_BLS_AUTHORITY_ID_LEN] {
+	bls_pair(index).public().as_ref().try_into().unwrap()
+}
+
+/// Double sign `message` the way BEEFY does, ECDSA over its keccak256 hash and BLS over the raw
+/// message.
+fn bls_sign(index: u8, message: &[u8]) -> [u8; ECDSA_BLS_SIGNATURE_LEN] {
+	bls_pair(index)
+		.sign_with_hasher::<KeccakHasher>(message)
+		.as_ref()
+		.try_into()
+		.unwrap()
+}
+
+fn bls_keyset(id: ValidatorSetId, authorities: &[u8]) -> BeefyAuthoritySet<H256> {
+	let authorities = authorities.iter().map(|i| bls_authority(*i)).collect::<Vec<_>>();
+	BeefyAuthoritySet {
+		id,
+		len: authorities.len() as u32,
+		keyset_commitment: bls_keyset_commitment::<TestHost>(&authorities),
+	}
+}
+
+/// The ECDSA keyset commitment the relay chain's mmr leaves carry for `authorities`
+fn ecdsa_keyset(id: ValidatorSetId, authorities: &[u8]) -> BeefyAuthoritySet<H256> {
+	let leaves = hash_authority_addresses(
+		authorities.iter().map(|i| bls_authority(*i)[..33].to_vec()).collect(),
+	)
+	.unwrap();
+	BeefyAuthoritySet {
+		id,
+		len: authorities.len() as u32,
+		keyset_commitment: MerkleTree::<MerkleHasher>::from_leaves(&leaves).root().unwrap().into(),
+	}
+}
+
+/// A commitment to the mmr holding only `latest_mmr_leaf`, whose root is that leaf's hash
+fn bls_commitment(
+	set_id: ValidatorSetId,
+	block_number: u32,
+	latest_mmr_leaf: &MmrLeaf<u32, H256, H256, H256>,
+) -> Commitment<u32> {
+	let mmr_root = TestHost::keccak256(&latest_mmr_leaf.encode());
+	let payload = Payload::from_single_entry(*b"mh", mmr_root.0.to_vec());
+	Commitment { payload, block_number, validator_set_id: set_id }
+}
+
+/// A BLS proof for a commitment at `block_number`, signed by `signers` (positions in
+/// `authorities`) of authority set `set_id`.
+fn bls_mmr_proof(
+	set_id: ValidatorSetId,
+	block_number: u32,
+	authorities: &[u8],
+	signers: &[usize],
+	latest_mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+) -> BlsMmrProof {
+	let commitment = bls_commitment(set_id, block_number, &latest_mmr_leaf);
+	let message = commitment.encode();
+	let signatures =
+		signers.iter().map(|i| bls_sign(authorities[*i], &message)).collect::<Vec<_>>();
+	let aggregate_signature =
+		aggregate_bls_signatures(signatures.iter().map(|signature| &signature[65..])).unwrap();
+
+	let mut bitfield = vec![0u8; authorities.len().div_ceil(8)];
+	for i in signers {
+		bitfield[i / 8] |= 1 << (i % 8);
+	}
+
+	let ids = authorities.iter().map(|i| bls_authority(*i)).collect::<Vec<_>>();
+	let keyset_proof = MerkleTree::<MerkleHasher>::from_leaves(&hash_authority_bls_keys(&ids))
+		.proof(signers)
+		.proof_hashes()
+		.to_vec();
+
+	BlsMmrProof {
+		commitment,
+		signature: BlsCommitmentSignature::Aggregate(BlsAggregateSignature {
+			signers: bitfield,
+			aggregate_signature,
+			signer_keys: signers.iter().map(|i| ids[*i][33..].try_into().unwrap()).collect(),
+			keyset_proof,
+		}),
+		latest_mmr_leaf,
+		mmr_proof: LeafProof { leaf_indices: vec![0], leaf_count: 1, items: vec![] },
+		keysets: None,
+	}
+}
+
+/// Like [`bls_mmr_proof`], with the ECDSA halves of the signatures instead of a BLS aggregate
+fn ecdsa_bls_mmr_proof(
+	set_id: ValidatorSetId,
+	block_number: u32,
+	authorities: &[u8],
+	signers: &[usize],
+	latest_mmr_leaf: MmrLeaf<u32, H256, H256, H256>,
+) -> BlsMmrProof {
+	let commitment = bls_commitment(set_id, block_number, &latest_mmr_leaf);
+	let message = commitment.encode();
+	let signatures = signers
+		.iter()
+		.map(|i| {
+			let mut signature: [u8; 65] =
+				bls_sign(authorities[*i], &message)[..65].try_into().unwrap();
+			signature[64] += 27;
+			SignatureWithAuthorityIndex { signature, index: *i as u32 }
+		})
+		.collect();
+
+	let leaves = hash_authority_addresses(
+		authorities.iter().map(|i| bls_authority(*i)[..33].to_vec()).collect(),
+	)
+	.unwrap();
+	let authority_proof = MerkleTree::<MerkleHasher>::from_leaves(&leaves)
+		.proof(signers)
+		.proof_hashes()
+		.to_vec();
+
+	BlsMmrProof {
+		commitment,
+		signature: BlsCommitmentSignature::Ecdsa { signatures, authority_proof },
+		latest_mmr_leaf,
+		mmr_proof: LeafProof { leaf_indices: vec![0], leaf_count: 1, items: vec![] },
+		keysets: None,
+	}
+}
+
+/// A proof of the `beefy` pallet storage at `number`, with the given authorities of set
+/// `set_id` and of the next set. Returns the proof and the hash of the header it is read from.
+fn bls_keyset_proof(
+	number: u32,
+	set_id: ValidatorSetId,
+	current: &[u8],
+	next: &[u8],
+) -> (BlsKeysetProof, H256) {
+	use polkadot_sdk::{
+		sp_runtime::{
+			generic::Header,
+			traits::{BlakeTwo256, Header as _},
+		},
+		sp_trie::{LayoutV0, MemoryDB, TrieDBMutBuilder, TrieMut},
+	};
+
+	let ids =
+		|authorities: &[u8]| authorities.iter().map(|i| bls_authority(*i)).collect::<Vec<_>>();
+	let mut db = MemoryDB::<BlakeTwo256>::default();
+	let mut state_root = H256::zero();
+	{
+		let mut trie =
+			TrieDBMutBuilder::<LayoutV0<BlakeTwo256>>::new(&mut db, &mut state_root).build();
+		trie.insert(&BEEFY_VALIDATOR_SET_ID, &set_id.encode()).unwrap();
+		trie.insert(&BEEFY_AUTHORITIES, &ids(current).encode()).unwrap();
+		trie.insert(&BEEFY_NEXT_AUTHORITIES, &ids(next).encode()).unwrap();
+	}
+	let header = Header::<u32, BlakeTwo256>::new(
+		number,
+		H256::zero(),
+		state_root,
+		H256::zero(),
+		Default::default(),
+	);
+	let proof = BlsKeysetProof {
+		header: header.encode(),
+		proof: db.drain().into_values().map(|(node, _)| node).collect(),
+	};
+	(proof, header.hash())
+}
+
+fn mmr_leaf(
+	parent_number: u32,
+	parent_hash: H256,
+	next_authority_set: BeefyNextAuthoritySet<H256>,
+) -> MmrLeaf<u32, H256, H256, H256> {
+	MmrLeaf {
+		version: MmrLeafVersion::new(0, 0),
+		parent_number_and_hash: (parent_number, parent_hash),
+		beefy_next_authority_set: next_authority_set,
+		leaf_extra: H256::zero(),
+	}
+}
+
+fn bls_trusted_state(set_id: ValidatorSetId, height: u32) -> ConsensusState {
+	ConsensusState {
+		latest_beefy_height: height,
+		beefy_activation_block: 0,
+		mmr_root_hash: H256::zero(),
+		current_authorities: authority_set(set_id, 4),
+		next_authorities: authority_set(set_id + 1, 4),
+		bls_keysets: BlsKeysets { current: Some(bls_keyset(set_id, &[0, 1, 2, 3])), next: None },
+	}
+}
+
+#[test]
+fn verifies_aggregate_bls_signature_from_supermajority() {
+	const SET_ID: ValidatorSetId = 42;
+	const BLOCK_NUMBER: u32 = 1_000;
+
+	let trusted_state = bls_trusted_state(SET_ID, BLOCK_NUMBER - 1);
+	let leaf = mmr_leaf(BLOCK_NUMBER - 1, H256::zero(), authority_set(SET_ID + 1, 4));
+
+	let proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &[0, 1, 2, 3], &[0, 1, 3], leaf.clone());
+	let (state, _) = sp_io::TestExternalities::default()
+		.execute_with(|| verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof))
+		.unwrap();
+	assert_eq!(state.latest_beefy_height, BLOCK_NUMBER);
+	assert_eq!(state.bls_keysets, trusted_state.bls_keysets);
+
+	let proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &[0, 1, 2, 3], &[0, 1], leaf);
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state, proof);
+	assert!(matches!(result, Err(Error::SuperMajorityRequired)), "got {result:?}");
+}
+
+// The aggregate is over Substrate's `bls381` signatures of the raw commitment, a signature over
+// its keccak256 hash (what the ECDSA half signs) must not verify.
+#[test]
+fn rejects_bls_signatures_over_the_commitment_hash() {
+	const SET_ID: ValidatorSetId = 42;
+	const BLOCK_NUMBER: u32 = 1_000;
+
+	let trusted_state = bls_trusted_state(SET_ID, BLOCK_NUMBER - 1);
+	let leaf = mmr_leaf(BLOCK_NUMBER - 1, H256::zero(), authority_set(SET_ID + 1, 4));
+	let mut proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &[0, 1, 2, 3], &[0, 1, 2], leaf);
+
+	let hash = TestHost::keccak256(&proof.commitment.encode());
+	let signatures = [0u8, 1, 2].map(|i| bls_sign(i, &hash.0));
+	let BlsCommitmentSignature::Aggregate(ref mut signature) = proof.signature else {
+		unreachable!()
+	};
+	signature.aggregate_signature =
+		aggregate_bls_signatures(signatures.iter().map(|signature| &signature[65..])).unwrap();
+
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state, proof);
+	assert!(matches!(result, Err(Error::BlsSignatureVerificationFailed)), "got {result:?}");
+}
+
+#[test]
+fn rejects_bls_proof_whose_signers_do_not_match() {
+	const SET_ID: ValidatorSetId = 42;
+	const BLOCK_NUMBER: u32 = 1_000;
+
+	let trusted_state = bls_trusted_state(SET_ID, BLOCK_NUMBER - 1);
+	let leaf = mmr_leaf(BLOCK_NUMBER - 1, H256::zero(), authority_set(SET_ID + 1, 4));
+	let authorities = [0, 1, 2, 3];
+	fn aggregate(proof: &mut BlsMmrProof) -> &mut BlsAggregateSignature {
+		match proof.signature {
+			BlsCommitmentSignature::Aggregate(ref mut signature) => signature,
+			_ => unreachable!(),
+		}
+	}
+
+	// The bitfield names a different authority than the keys proven against the keyset
+	let mut proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &authorities, &[0, 1, 2], leaf.clone());
+	aggregate(&mut proof).signers = vec![0b1011];
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof);
+	assert!(matches!(result, Err(Error::InvalidBlsAuthoritiesProof)), "got {result:?}");
+
+	// The aggregate signature comes from a different set of signers than the keys
+	let mut proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &authorities, &[0, 1, 2], leaf.clone());
+	let mut other = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &authorities, &[0, 1, 3], leaf.clone());
+	aggregate(&mut proof).aggregate_signature = aggregate(&mut other).aggregate_signature;
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof);
+	assert!(matches!(result, Err(Error::BlsSignatureVerificationFailed)), "got {result:?}");
+
+	// Bits beyond the authority set
+	let mut proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &authorities, &[0, 1, 2], leaf.clone());
+	aggregate(&mut proof).signers = vec![0b0001_0111];
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof);
+	assert!(matches!(result, Err(Error::InvalidSignerBitfield)), "got {result:?}");
+
+	// No BLS keys known for the signing set
+	let proof = bls_mmr_proof(SET_ID + 1, BLOCK_NUMBER, &authorities, &[0, 1, 2], leaf);
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state, proof);
+	assert!(matches!(result, Err(Error::UnknownBlsKeyset { id }) if id == SET_ID + 1));
+}
+
+// A proof for the session's mandatory block rotates the authority set and carries the BLS keys
+// of the newly announced next set, read from the state of the leaf's parent.
+#[test]
+fn bls_proof_rotates_authorities_and_learns_the_next_keyset() {
+	const SET_ID: ValidatorSetId = 42;
+	const BLOCK_NUMBER: u32 = 1_000;
+	let next_authorities = [10u8, 11, 12, 13];
+
+	let mut trusted_state = bls_trusted_state(SET_ID, BLOCK_NUMBER - 1);
+	trusted_state.bls_keysets.next = Some(bls_keyset(SET_ID + 1, &[4, 5, 6, 7]));
+
+	let (keyset_proof, parent_hash) =
+		bls_keyset_proof(BLOCK_NUMBER - 1, SET_ID + 1, &[4, 5, 6, 7], &next_authorities);
+	let leaf = mmr_leaf(BLOCK_NUMBER - 1, parent_hash, authority_set(SET_ID + 2, 4));
+	let mut proof = bls_mmr_proof(SET_ID + 1, BLOCK_NUMBER, &[4, 5, 6, 7], &[0, 1, 2], leaf);
+	proof.keysets = Some(keyset_proof.clone());
+
+	let (state, _) =
+		verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof.clone()).unwrap();
+	assert_eq!(state.current_authorities.id, SET_ID + 1);
+	assert_eq!(state.bls_keysets.current, trusted_state.bls_keysets.next);
+	assert_eq!(state.bls_keysets.next, Some(bls_keyset(SET_ID + 2, &next_authorities)));
+
+	// The keyset must come from the leaf's parent block
+	let (other, _) = bls_keyset_proof(BLOCK_NUMBER, SET_ID + 1, &[4, 5, 6, 7], &next_authorities);
+	proof.keysets = Some(other);
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state, proof);
+	assert!(matches!(result, Err(Error::InvalidKeysetProof(_))), "got {result:?}");
+}
+
+// A client that missed the proof carrying the next keyset rotates without BLS keys for its new
+// current set. It recovers with an ECDSA-signed proof that restores the keys, after which
+// aggregate signatures verify again.
+#[test]
+fn ecdsa_signed_proof_restores_missing_bls_keysets() {
+	const SET_ID: ValidatorSetId = 42;
+	const BLOCK_NUMBER: u32 = 1_000;
+	let current = [4u8, 5, 6, 7];
+	let next = [10u8, 11, 12, 13];
+
+	let trusted_state = ConsensusState {
+		latest_beefy_height: BLOCK_NUMBER - 1,
+		beefy_activation_block: 0,
+		mmr_root_hash: H256::zero(),
+		current_authorities: ecdsa_keyset(SET_ID, &current),
+		next_authorities: ecdsa_keyset(SET_ID + 1, &next),
+		bls_keysets: Default::default(),
+	};
+
+	let (keyset_proof, parent_hash) = bls_keyset_proof(BLOCK_NUMBER - 1, SET_ID, &current, &next);
+	let leaf = mmr_leaf(BLOCK_NUMBER - 1, parent_hash, ecdsa_keyset(SET_ID + 1, &next));
+
+	let proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER, &current, &[0, 1, 2], leaf.clone());
+	let result = verify_bls_mmr_update_proof::<TestHost>(trusted_state.clone(), proof);
+	assert!(matches!(result, Err(Error::UnknownBlsKeyset { id }) if id == SET_ID));
+
+	let mut proof = ecdsa_bls_mmr_proof(SET_ID, BLOCK_NUMBER, &current, &[0, 1, 2], leaf);
+	proof.keysets = Some(keyset_proof);
+	let (state, _) = sp_io::TestExternalities::default()
+		.execute_with(|| verify_bls_mmr_update_proof::<TestHost>(trusted_state, proof))
+		.unwrap();
+	assert_eq!(state.bls_keysets.current, Some(bls_keyset(SET_ID, &current)));
+	assert_eq!(state.bls_keysets.next, Some(bls_keyset(SET_ID + 1, &next)));
+
+	let leaf = mmr_leaf(BLOCK_NUMBER, H256::zero(), ecdsa_keyset(SET_ID + 1, &next));
+	let proof = bls_mmr_proof(SET_ID, BLOCK_NUMBER + 1, &current, &[1, 2, 3], leaf);
+	let (state, _) = verify_bls_mmr_update_proof::<TestHost>(state, proof).unwrap();
+	assert_eq!(state.latest_beefy_height, BLOCK_NUMBER + 1);
+}
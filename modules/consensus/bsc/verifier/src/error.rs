@@ -71,6 +71,17 @@ pub enum Error {
 		/// Source header height in the submitted update.
 		update: u64,
 	},
+	/// A catch-up update carried no updates.
+	#[error("Catch-up update is empty")]
+	EmptyCatchUp,
+	/// The updates in a catch-up update weigh more than a single update may.
+	#[error("Catch-up update weighs {total}, at most {max} is allowed")]
+	CatchUpTooHeavy {
+		/// Total weight of the updates.
+		total: usize,
+		/// Maximum allowed.
+		max: usize,
+	},
 	/// No epoch length configured for the client.
 	#[error("Epoch length not set")]
 	EpochLengthNotSet,
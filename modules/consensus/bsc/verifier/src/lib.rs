@@ -22,7 +22,10 @@ use alloc::vec::Vec;
 use bls_utils::aggregate_public_keys;
 use geth_primitives::{CodecHeader, Header};
 use ismp::messaging::Keccak256;
-use primitives::{compute_epoch, parse_extra, BscClientUpdate, Config, VALIDATOR_BIT_SET_SIZE};
+use primitives::{
+	compute_epoch, parse_extra, BscClientUpdate, Config, MAX_CATCH_UP_WEIGHT,
+	VALIDATOR_BIT_SET_SIZE,
+};
 use sp_core::H256;
 use ssz_rs::{Bitvector, Deserialize};
 use sync_committee_primitives::constants::BlsPublicKey;
@@ -243,6 +246,21 @@ pub fn ensure_finalized_epoch_consistent(
 	Ok(())
 }
 
+/// Bound the work a proof carrying `updates` may cost to verify.
+///
+/// Every update is charged a fixed weight on top of its epoch ancestry, so the proof may weigh no
+/// more than a single update carrying its full epoch ancestry, however many updates it chains.
+pub fn ensure_catch_up_bounded(updates: &[BscClientUpdate]) -> Result<(), Error> {
+	if updates.is_empty() {
+		Err(Error::EmptyCatchUp)?
+	}
+	let total = updates.iter().map(BscClientUpdate::catch_up_weight).sum::<usize>();
+	if total > MAX_CATCH_UP_WEIGHT {
+		Err(Error::CatchUpTooHeavy { total, max: MAX_CATCH_UP_WEIGHT })?
+	}
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -419,4 +437,77 @@ mod tests {
 			.expect_err("non-adjacent vote must be rejected");
 		assert!(format!("{err}").contains("not the direct child"), "unexpected error: {err:?}");
 	}
+
+	/// Plain updates keep their original encoding, and a catch-up proof round-trips through its
+	/// prefixed encoding.
+	#[test]
+	fn decodes_single_and_catch_up_proofs() {
+		use primitives::{BscCatchUpUpdate, BscConsensusProof};
+
+		let update = update_with(plain_header(10, H256::repeat_byte(0x11)));
+		let proof = BscConsensusProof::Update(update.clone()).encode_proof();
+		assert_eq!(proof, codec::Encode::encode(&update));
+		let decoded = BscConsensusProof::decode_proof(&proof).expect("valid proof");
+		assert!(matches!(decoded, BscConsensusProof::Update(_)));
+
+		let catch_up = BscConsensusProof::CatchUp(BscCatchUpUpdate {
+			updates: vec![update.clone(), update].try_into().expect("within bound"),
+		});
+		let decoded =
+			BscConsensusProof::decode_proof(&catch_up.encode_proof()).expect("valid proof");
+		assert_eq!(decoded.into_updates().len(), 2);
+	}
+
+	/// A plain update whose parent hash happens to start with the catch-up prefix is still a
+	/// plain update, and neither form may carry trailing bytes.
+	#[test]
+	fn decodes_plain_updates_that_look_like_catch_ups() {
+		use primitives::{BscCatchUpUpdate, BscConsensusProof, CATCH_UP_PREFIX};
+
+		let mut parent_hash = H256::repeat_byte(0x11);
+		parent_hash.0[..4].copy_from_slice(&CATCH_UP_PREFIX);
+		let update = update_with(plain_header(10, parent_hash));
+		let proof = BscConsensusProof::Update(update.clone()).encode_proof();
+		assert!(proof.starts_with(&CATCH_UP_PREFIX));
+		let decoded = BscConsensusProof::decode_proof(&proof).expect("valid proof");
+		assert!(matches!(decoded, BscConsensusProof::Update(_)));
+
+		let mut trailing = proof.clone();
+		trailing.push(0);
+		BscConsensusProof::decode_proof(&trailing).expect_err("trailing bytes must be rejected");
+
+		let mut catch_up = BscConsensusProof::CatchUp(BscCatchUpUpdate {
+			updates: vec![update].try_into().expect("within bound"),
+		})
+		.encode_proof();
+		catch_up.push(0);
+		BscConsensusProof::decode_proof(&catch_up).expect_err("trailing bytes must be rejected");
+	}
+
+	/// A catch-up proof may not be empty, nor weigh more than a single update may, and every
+	/// update it chains is charged for.
+	#[test]
+	fn bounds_catch_up_updates() {
+		use primitives::{CATCH_UP_UPDATE_WEIGHT, MAX_CATCH_UP_UPDATES};
+
+		assert!(matches!(ensure_catch_up_bounded(&[]), Err(Error::EmptyCatchUp)));
+
+		let plain = update_with(plain_header(10, H256::repeat_byte(0x11)));
+		let mut full = plain.clone();
+		full.epoch_header_ancestry =
+			vec![plain_header(9, H256::zero()); 1000].try_into().expect("within bound");
+		ensure_catch_up_bounded(&[full]).expect("a single update is within bounds");
+
+		ensure_catch_up_bounded(&vec![plain.clone(); MAX_CATCH_UP_UPDATES as usize])
+			.expect("a full chain of plain updates is within bounds");
+
+		// 1000 ancestry headers fit in a single update, but not split across two
+		let mut half = plain;
+		half.epoch_header_ancestry =
+			vec![plain_header(9, H256::zero()); 500].try_into().expect("within bound");
+		let err = ensure_catch_up_bounded(&[half.clone(), half])
+			.expect_err("the second update's weight exceeds the bound");
+		let expected = 2 * CATCH_UP_UPDATE_WEIGHT + 1000;
+		assert!(matches!(err, Error::CatchUpTooHeavy { total, .. } if total == expected));
+	}
 }
@@ -18,7 +18,7 @@ use alloy_primitives::{B256, FixedBytes};
 use alloy_rlp::Decodable;
 use alloy_rlp_derive::{RlpDecodable, RlpEncodable};
 use anyhow::anyhow;
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use geth_primitives::CodecHeader;
 use ismp::messaging::Keccak256;
 use polkadot_sdk::*;
@@ -33,6 +33,19 @@ const VALIDATOR_NUMBER_SIZE: usize = 1; // // Fixed number of extra prefix bytes
 const ADDRESS_LENGTH: usize = 20;
 const TURN_LENGTH_SIZE: usize = 1;
 pub const VALIDATOR_BIT_SET_SIZE: usize = 64;
+/// Maximum number of updates carried by a [`BscCatchUpUpdate`]. Every epoch the client falls
+/// behind costs a sync update and an enactment update, so this covers 8 epochs.
+pub const MAX_CATCH_UP_UPDATES: u32 = 16;
+/// Weight charged for verifying an update's headers and aggregate signature, measured in epoch
+/// ancestry headers.
+pub const CATCH_UP_UPDATE_WEIGHT: usize = 50;
+/// Maximum weight of all the updates in a [`BscCatchUpUpdate`], the weight of a single
+/// [`BscClientUpdate`] carrying its full epoch ancestry.
+pub const MAX_CATCH_UP_WEIGHT: usize = CATCH_UP_UPDATE_WEIGHT + 1000;
+/// Prefix identifying an encoded [`BscCatchUpUpdate`]. A plain [`BscClientUpdate`] may also start
+/// with these bytes, so a proof is only read as a catch-up when it doesn't decode completely as a
+/// plain update, see [`BscConsensusProof::decode_proof`].
+pub const CATCH_UP_PREFIX: [u8; 4] = *b"bscc";
 
 /// This trait should be used to host parameters that could be potentially be different for mainnet
 /// and testnet and affect how headers are verified
@@ -68,6 +81,72 @@ pub struct BscClientUpdate {
 	pub epoch_header_ancestry: sp_runtime::BoundedVec<CodecHeader, ConstU32<1000>>,
 }
 
+/// A chain of updates that walks the client through several validator set handovers at once.
+///
+/// The updates are applied in order, each one against the state the previous one produced, so a
+/// client that fell several epochs behind can be brought up to date in a single consensus message.
+#[derive(Debug, Encode, Decode, Clone)]
+pub struct BscCatchUpUpdate {
+	/// Sync and enactment updates, in the order they should be applied
+	pub updates: sp_runtime::BoundedVec<BscClientUpdate, ConstU32<MAX_CATCH_UP_UPDATES>>,
+}
+
+/// A consensus proof submitted to the BSC client
+#[derive(Debug, Clone)]
+pub enum BscConsensusProof {
+	/// A single update, encoded as a plain [`BscClientUpdate`]
+	Update(BscClientUpdate),
+	/// A chain of updates, encoded as [`CATCH_UP_PREFIX`] followed by a [`BscCatchUpUpdate`]
+	CatchUp(BscCatchUpUpdate),
+}
+
+impl BscConsensusProof {
+	/// Decode a consensus proof. Single updates keep their original encoding, so proofs from
+	/// relayers that don't know about catch-up updates still decode.
+	///
+	/// A proof that decodes as a plain update with no bytes left over is one, whatever its first
+	/// bytes are. Only otherwise is it read as [`CATCH_UP_PREFIX`] followed by a catch-up update,
+	/// which must also consume the whole proof.
+	pub fn decode_proof(proof: &[u8]) -> Result<Self, codec::Error> {
+		match (
+			BscClientUpdate::decode_all(&mut &proof[..]),
+			proof.strip_prefix(&CATCH_UP_PREFIX[..]),
+		) {
+			(Ok(update), _) => Ok(Self::Update(update)),
+			(Err(_), Some(mut catch_up)) =>
+				Ok(Self::CatchUp(BscCatchUpUpdate::decode_all(&mut catch_up)?)),
+			(Err(err), None) => Err(err),
+		}
+	}
+
+	/// Encode the consensus proof for submission
+	pub fn encode_proof(&self) -> Vec<u8> {
+		match self {
+			Self::Update(update) => update.encode(),
+			Self::CatchUp(catch_up) => {
+				let mut proof = CATCH_UP_PREFIX.to_vec();
+				catch_up.encode_to(&mut proof);
+				proof
+			},
+		}
+	}
+
+	/// The updates carried by this proof, in the order they should be applied
+	pub fn into_updates(self) -> Vec<BscClientUpdate> {
+		match self {
+			Self::Update(update) => alloc::vec![update],
+			Self::CatchUp(catch_up) => catch_up.updates.into_inner(),
+		}
+	}
+}
+
+impl BscClientUpdate {
+	/// Weight charged for this update in a [`BscCatchUpUpdate`], see [`MAX_CATCH_UP_WEIGHT`]
+	pub fn catch_up_weight(&self) -> usize {
+		CATCH_UP_UPDATE_WEIGHT + self.epoch_header_ancestry.len()
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct ExtraData {
 	pub extra_vanity: Vec<u8>,
@@ -68,6 +68,11 @@ pub const FINALIZED_ROOT_INDEX_LOG2: u64 = 5;
 pub const EXECUTION_PAYLOAD_INDEX_LOG2: u64 = 5;
 pub const NEXT_SYNC_COMMITTEE_INDEX_LOG2: u64 = 5;
 
+/// Generalized index of the `execution_payload` in the `BeaconBlockBody`, unchanged since Capella.
+/// This is the index light client `execution_branch`es prove against the header's `body_root`.
+pub const EXECUTION_PAYLOAD_BODY_INDEX: u64 = 25;
+pub const EXECUTION_PAYLOAD_BODY_INDEX_LOG2: u64 = 4;
+
 pub const ETH1_DATA_VOTES_BOUND_ETH: usize = (EPOCHS_PER_ETH1_VOTING_PERIOD * 32) as usize;
 pub const ETH1_DATA_VOTES_BOUND_GNO: usize = (EPOCHS_PER_ETH1_VOTING_PERIOD * 16) as usize;
 
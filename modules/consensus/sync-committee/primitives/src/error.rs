@@ -8,6 +8,7 @@ pub enum Error {
 	InvalidBitVec,
 	ErrorConvertingAncestorBlock,
 	InvalidNodeBytes,
+	InvalidFork,
 }
 
 impl Display for Error {
@@ -18,6 +19,7 @@ impl Display for Error {
 			Error::InvalidProof => write!(f, "Invalid proof",),
 			Error::InvalidBitVec => write!(f, "Invalid bit vec",),
 			Error::InvalidNodeBytes => write!(f, "Invalid node bytes",),
+			Error::InvalidFork => write!(f, "Invalid fork",),
 			Error::ErrorConvertingAncestorBlock => write!(f, "Error deriving ancestor block",),
 		}
 	}
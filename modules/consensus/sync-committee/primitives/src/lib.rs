@@ -12,5 +12,6 @@ pub mod domains;
 pub mod electra;
 pub mod error;
 mod ssz;
+pub mod spec;
 pub mod types;
 pub mod util;
@@ -0,0 +1,153 @@
+//! Beacon chain network parameters that are stored as data rather than compiled in.
+
+use crate::{
+	constants::{Config, Epoch, Version},
+	error::Error,
+};
+use alloc::{vec, vec::Vec};
+use codec::{Decode, DecodeWithMemTracking, Encode};
+
+/// A fork of the beacon chain, activated at `epoch`.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, DecodeWithMemTracking, scale_info::TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fork {
+	/// The fork version
+	#[cfg_attr(feature = "std", serde(with = "serde_hex_utils::as_hex"))]
+	pub version: Version,
+	/// Epoch the fork activates at
+	pub epoch: Epoch,
+}
+
+/// The genesis and fork schedule of a beacon chain network. Unlike the presets on [`Config`],
+/// these differ between networks and change over time, so they can be updated without a code
+/// change.
+#[derive(
+	Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeWithMemTracking, scale_info::TypeInfo,
+)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetworkSpec {
+	/// Root of the genesis validator set, part of every signing domain
+	#[cfg_attr(feature = "std", serde(with = "serde_hex_utils::as_hex"))]
+	pub genesis_validators_root: [u8; 32],
+	/// Fork version at genesis
+	#[cfg_attr(feature = "std", serde(with = "serde_hex_utils::as_hex"))]
+	pub genesis_fork_version: Version,
+	/// Forks after genesis, ordered by activation epoch
+	pub forks: Vec<Fork>,
+}
+
+impl NetworkSpec {
+	/// The spec compiled into `C`.
+	pub fn from_config<C: Config>() -> Self {
+		let forks = vec![
+			Fork { version: C::ALTAIR_FORK_VERSION, epoch: C::ALTAIR_FORK_EPOCH },
+			Fork { version: C::BELLATRIX_FORK_VERSION, epoch: C::BELLATRIX_FORK_EPOCH },
+			Fork { version: C::CAPELLA_FORK_VERSION, epoch: C::CAPELLA_FORK_EPOCH },
+			Fork { version: C::DENEB_FORK_VERSION, epoch: C::DENEB_FORK_EPOCH },
+			Fork { version: C::ELECTRA_FORK_VERSION, epoch: C::ELECTRA_FORK_EPOCH },
+			Fork { version: C::FULU_FORK_VERSION, epoch: C::FULU_FORK_EPOCH },
+		];
+
+		Self {
+			genesis_validators_root: C::GENESIS_VALIDATORS_ROOT,
+			genesis_fork_version: C::GENESIS_FORK_VERSION,
+			forks,
+		}
+	}
+
+	/// Return the fork version at the given `epoch`.
+	pub fn fork_version(&self, epoch: Epoch) -> Version {
+		self.forks
+			.iter()
+			.rev()
+			.find(|fork| epoch >= fork.epoch)
+			.map(|fork| fork.version)
+			.unwrap_or(self.genesis_fork_version)
+	}
+
+	/// Check that forks are ordered by epoch and fork versions are unique.
+	pub fn validate(&self) -> Result<(), Error> {
+		let ordered = self.forks.windows(2).all(|pair| pair[0].epoch <= pair[1].epoch);
+		let versions = core::iter::once(self.genesis_fork_version)
+			.chain(self.forks.iter().map(|fork| fork.version))
+			.collect::<Vec<_>>();
+		let unique = versions
+			.iter()
+			.enumerate()
+			.all(|(i, version)| !versions[i + 1..].contains(version));
+		if !ordered || !unique {
+			Err(Error::InvalidFork)?
+		}
+
+		Ok(())
+	}
+
+	/// Schedule `fork`, or reschedule it if a fork with the same version is pending. Forks that
+	/// are active at `current_epoch` can no longer be changed.
+	pub fn schedule_fork(&mut self, fork: Fork, current_epoch: Epoch) -> Result<(), Error> {
+		if fork.epoch <= current_epoch {
+			Err(Error::InvalidFork)?
+		}
+		if let Some(existing) = self.forks.iter().find(|existing| existing.version == fork.version)
+		{
+			if existing.epoch <= current_epoch {
+				Err(Error::InvalidFork)?
+			}
+		}
+
+		let mut spec = self.clone();
+		spec.forks.retain(|existing| existing.version != fork.version);
+		spec.forks.push(fork);
+		spec.forks.sort_by_key(|fork| fork.epoch);
+		spec.validate()?;
+		*self = spec;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::constants::{mainnet::Mainnet, sepolia::Sepolia};
+
+	#[test]
+	fn compiled_spec_matches_compute_fork_version() {
+		let spec = NetworkSpec::from_config::<Sepolia>();
+		spec.validate().unwrap();
+		for epoch in [0, 49, 50, 56_832, 132_608, 222_464, 272_640, u64::MAX] {
+			assert_eq!(
+				spec.fork_version(epoch),
+				crate::util::compute_fork_version::<Sepolia>(epoch)
+			);
+		}
+	}
+
+	#[test]
+	fn forks_can_only_be_scheduled_in_the_future() {
+		let mut spec = NetworkSpec::from_config::<Mainnet>();
+		let last = *spec.forks.last().unwrap();
+		let current_epoch = last.epoch + 10;
+		let next = Fork { version: [9, 0, 0, 0], epoch: current_epoch + 100 };
+
+		// active forks are immutable
+		assert!(spec
+			.schedule_fork(Fork { epoch: current_epoch + 1, ..last }, current_epoch)
+			.is_err());
+		assert!(spec
+			.schedule_fork(Fork { epoch: current_epoch, ..next }, current_epoch)
+			.is_err());
+
+		spec.schedule_fork(next, current_epoch).unwrap();
+		assert_eq!(spec.fork_version(next.epoch - 1), last.version);
+		assert_eq!(spec.fork_version(next.epoch), next.version);
+
+		// pending forks can be rescheduled
+		spec.schedule_fork(Fork { epoch: next.epoch + 50, ..next }, current_epoch)
+			.unwrap();
+		assert_eq!(spec.forks.len(), 7);
+		assert_eq!(spec.fork_version(next.epoch), last.version);
+	}
+}
@@ -16,7 +16,8 @@ pub struct ExecutionPayloadProof {
 	pub block_number: u64,
 	/// merkle mutli proof for the state_root & block_number in the [`ExecutionPayload`].
 	pub multi_proof: Vec<Node>,
-	/// merkle proof for the `ExecutionPayload` in the [`BeaconBlockBody`].
+	/// merkle proof for the `ExecutionPayload` in the [`BeaconState`], or in the
+	/// [`BeaconBlockBody`] when sourced from the beacon light client API.
 	pub execution_payload_branch: Vec<Node>,
 	/// timestamp
 	pub timestamp: u64,
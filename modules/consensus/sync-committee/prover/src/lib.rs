@@ -11,24 +11,30 @@ use ssz_rs::{Merkleized, Node};
 use tracing::instrument;
 
 use sync_committee_primitives::{
-	consensus_types::{BeaconBlock, BeaconBlockHeader, BeaconState, Checkpoint, Validator},
+	consensus_types::{
+		BeaconBlock, BeaconBlockHeader, BeaconState, Checkpoint, SyncCommittee, Validator,
+	},
 	constants::{
 		BlsPublicKey, Config, Root, BYTES_PER_LOGS_BLOOM, EPOCHS_PER_HISTORICAL_VECTOR,
-		EPOCHS_PER_SLASHINGS_VECTOR, HISTORICAL_ROOTS_LIMIT, MAX_ATTESTATIONS,
-		MAX_ATTESTER_SLASHINGS, MAX_BLS_TO_EXECUTION_CHANGES, MAX_BYTES_PER_TRANSACTION,
-		MAX_COMMITTEES_PER_SLOT, MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD, MAX_DEPOSITS,
-		MAX_DEPOSIT_REQUESTS_PER_PAYLOAD, MAX_EXTRA_DATA_BYTES, MAX_PROPOSER_SLASHINGS,
-		MAX_TRANSACTIONS_PER_PAYLOAD, MAX_VALIDATORS_PER_COMMITTEE, MAX_VOLUNTARY_EXITS,
-		MAX_WITHDRAWALS_PER_PAYLOAD, MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD,
+		EPOCHS_PER_SLASHINGS_VECTOR, EXECUTION_PAYLOAD_BODY_INDEX_LOG2, HISTORICAL_ROOTS_LIMIT,
+		MAX_ATTESTATIONS, MAX_ATTESTER_SLASHINGS, MAX_BLS_TO_EXECUTION_CHANGES,
+		MAX_BYTES_PER_TRANSACTION, MAX_COMMITTEES_PER_SLOT, MAX_CONSOLIDATION_REQUESTS_PER_PAYLOAD,
+		MAX_DEPOSITS, MAX_DEPOSIT_REQUESTS_PER_PAYLOAD, MAX_EXTRA_DATA_BYTES,
+		MAX_PROPOSER_SLASHINGS, MAX_TRANSACTIONS_PER_PAYLOAD, MAX_VALIDATORS_PER_COMMITTEE,
+		MAX_VOLUNTARY_EXITS, MAX_WITHDRAWALS_PER_PAYLOAD, MAX_WITHDRAWAL_REQUESTS_PER_PAYLOAD,
 		PENDING_CONSOLIDATIONS_LIMIT, PENDING_DEPOSITS_LIMIT, PENDING_PARTIAL_WITHDRAWALS_LIMIT,
 		SLOTS_PER_HISTORICAL_ROOT, SYNC_COMMITTEE_SIZE, VALIDATOR_REGISTRY_LIMIT,
 	},
 	deneb::MAX_BLOB_COMMITMENTS_PER_BLOCK,
+	spec::NetworkSpec,
 	types::{
 		ExecutionPayloadProof, FinalityProof, SyncCommitteeUpdate, VerifierState,
 		VerifierStateUpdate,
 	},
-	util::{compute_sync_committee_period_at_slot, should_have_sync_committee_update},
+	util::{
+		compute_epoch_at_slot, compute_sync_committee_period_at_slot,
+		should_have_sync_committee_update,
+	},
 };
 use sync_committee_verifier::crypto::pubkey_to_projective;
 
@@ -36,6 +42,10 @@ use crate::{
 	middleware::SwitchProviderMiddleware,
 	responses::{
 		finality_checkpoint_response::FinalityCheckpoint,
+		genesis_response::Genesis,
+		light_client_response::{
+			LightClientBootstrap, LightClientFinalityUpdate, LightClientHeader, LightClientUpdate,
+		},
 		sync_committee_response::NodeSyncCommittee,
 	},
 	routes::*,
@@ -46,6 +56,7 @@ pub mod middleware;
 #[warn(unused_variables)]
 pub mod responses;
 pub mod routes;
+pub mod spec;
 
 #[cfg(test)]
 mod test;
@@ -69,6 +80,19 @@ pub type BeaconStateType<
 	PROPOSER_LOOK_AHEAD_LIMIT,
 >;
 
+/// Where the prover sources light client updates from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverBackend {
+	/// Prove against full beacon states from the debug api. Requires a node that serves
+	/// `/eth/v2/debug/beacon/states`, which for historical states means an archive node.
+	#[default]
+	BeaconState,
+	/// Use the branches served by the standard `/eth/v1/beacon/light_client` api. Full states
+	/// are only fetched when the api has no suitable update.
+	LightClient,
+}
+
 pub struct SyncCommitteeProver<
 	C: Config,
 	const ETH1_DATA_VOTES_BOUND: usize,
@@ -77,6 +101,7 @@ pub struct SyncCommitteeProver<
 	pub primary_url: String,
 	pub providers: Vec<String>,
 	pub client: ClientWithMiddleware,
+	pub backend: ProverBackend,
 	pub phantom: PhantomData<C>,
 }
 
@@ -88,6 +113,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			primary_url: self.primary_url.clone(),
 			client: self.client.clone(),
 			providers: self.providers.clone(),
+			backend: self.backend,
 			phantom: PhantomData,
 		}
 	}
@@ -105,9 +131,17 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			primary_url: providers.get(0).expect("There must be atleast one provider").clone(),
 			providers,
 			client,
+			backend: ProverBackend::default(),
 			phantom: PhantomData,
 		}
 	}
+
+	/// Select where light client updates are sourced from
+	pub fn with_backend(mut self, backend: ProverBackend) -> Self {
+		self.backend = backend;
+		self
+	}
+
 	#[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
 	pub async fn fetch_finalized_checkpoint(
 		&self,
@@ -259,6 +293,161 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 		Ok(beacon_state)
 	}
 
+	#[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
+	pub async fn fetch_light_client_bootstrap(
+		&self,
+		block_root: &str,
+	) -> Result<LightClientBootstrap, anyhow::Error> {
+		trace!(target: "sync-committee-prover", "Fetching light client bootstrap {block_root}");
+		let full_url = self.generate_route(&light_client_bootstrap_route(block_root))?;
+		let response = self.client.get(full_url).send().await.map_err(|e| {
+			anyhow!("Failed to fetch light client bootstrap for {block_root} due to error {e:?}")
+		})?;
+
+		let response_data =
+			response
+				.json::<responses::light_client_response::BootstrapResponse>()
+				.await
+				.map_err(|e| {
+					anyhow!("Failed to fetch light client bootstrap for {block_root} due to error {e:?}")
+				})?;
+		Ok(response_data.data)
+	}
+
+	#[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
+	pub async fn fetch_light_client_updates(
+		&self,
+		start_period: u64,
+		count: u64,
+	) -> Result<Vec<LightClientUpdate>, anyhow::Error> {
+		trace!(target: "sync-committee-prover", "Fetching light client updates {start_period}..+{count}");
+		let full_url = self.generate_route(&light_client_updates_route(start_period, count))?;
+		let response =
+			self.client.get(full_url).send().await.map_err(|e| {
+				anyhow!("Failed to fetch light client updates for period {start_period} due to error {e:?}")
+			})?;
+
+		let response_data = response
+			.json::<Vec<responses::light_client_response::UpdateResponse>>()
+			.await
+			.map_err(|e| {
+				anyhow!("Failed to fetch light client updates for period {start_period} due to error {e:?}")
+			})?;
+		Ok(response_data.into_iter().map(|update| update.data).collect())
+	}
+
+	#[instrument(level = "trace", target = "sync-committee-prover", skip(self))]
+	pub async fn fetch_light_client_finality_update(
+		&self,
+	) -> Result<LightClientFinalityUpdate, anyhow::Error> {
+		trace!(target: "sync-committee-prover", "Fetching light client finality update");
+		let full_url = self.generate_route(&light_client_finality_update_route())?;
+		let response = self.client.get(full_url).send().await.map_err(|e| {
+			anyhow!("Failed to fetch light client finality update due to error {e:?}")
+		})?;
+
+		let response_data = response
+			.json::<responses::light_client_response::FinalityUpdateResponse>()
+			.await
+			.map_err(|e| {
+				anyhow!("Failed to fetch light client finality update due to error {e:?}")
+			})?;
+		Ok(response_data.data)
+	}
+
+	pub async fn fetch_genesis(&self) -> Result<Genesis, anyhow::Error> {
+		let full_url = self.generate_route(&genesis_route())?;
+		let response = self
+			.client
+			.get(full_url)
+			.send()
+			.await
+			.map_err(|e| anyhow!("Failed to fetch genesis due to error {e:?}"))?;
+
+		let response_data = response
+			.json::<responses::genesis_response::Response>()
+			.await
+			.map_err(|e| anyhow!("Failed to fetch genesis due to error {e:?}"))?;
+		Ok(response_data.data)
+	}
+
+	/// Build the network spec from the contents of a beacon `config.yaml`, with the genesis
+	/// validators root of the connected node.
+	pub async fn fetch_network_spec(
+		&self,
+		config_yaml: &str,
+	) -> Result<NetworkSpec, anyhow::Error> {
+		let genesis = self.fetch_genesis().await?;
+		spec::parse_config_yaml(config_yaml, genesis.genesis_validators_root.0)
+	}
+
+	/// Fetch the sync committees at `block_id`. With the light client backend these come from
+	/// the bootstrap and the best update of the period, instead of the full beacon state.
+	pub async fn fetch_verifier_state(
+		&self,
+		block_id: &str,
+	) -> Result<VerifierState, anyhow::Error> {
+		let header = self.fetch_header(block_id).await?;
+		let (header, current_sync_committee, next_sync_committee) = match self.backend {
+			ProverBackend::BeaconState => {
+				let state = self.fetch_beacon_state(&header.slot.to_string()).await?;
+				(header, state.current_sync_committee, state.next_sync_committee)
+			},
+			ProverBackend::LightClient => self.light_client_sync_committees(header).await?,
+		};
+
+		Ok(VerifierState {
+			latest_finalized_epoch: compute_epoch_at_slot::<C>(header.slot),
+			state_period: compute_sync_committee_period_at_slot::<C>(header.slot),
+			finalized_header: header,
+			current_sync_committee,
+			next_sync_committee,
+		})
+	}
+
+	/// Bootstrap the sync committees at `header`. The next sync committee is only known once the
+	/// period has an update, so early in a period the client is bootstrapped at the finalized
+	/// header of the previous period's update instead, and catches up with regular updates.
+	async fn light_client_sync_committees(
+		&self,
+		header: BeaconBlockHeader,
+	) -> Result<
+		(BeaconBlockHeader, SyncCommittee<SYNC_COMMITTEE_SIZE>, SyncCommittee<SYNC_COMMITTEE_SIZE>),
+		anyhow::Error,
+	> {
+		let period = compute_sync_committee_period_at_slot::<C>(header.slot);
+		let (header, update) = match self.fetch_light_client_updates(period, 1).await?.pop() {
+			Some(update) => (header, update),
+			None => {
+				trace!(target: "sync-committee-prover", "No light client update for period {period} yet, bootstrapping from the previous period");
+				let previous = period.checked_sub(1).ok_or_else(|| {
+					anyhow!("No light client update available for sync committee period {period}")
+				})?;
+				let update =
+					self.fetch_light_client_updates(previous, 1).await?.pop().ok_or_else(|| {
+						anyhow!("No light client update available for sync committee periods {previous} and {period}")
+					})?;
+				let header = update.finalized_header.beacon.clone();
+				// The update's next sync committee follows the period of its attested header
+				if compute_sync_committee_period_at_slot::<C>(header.slot) != previous ||
+					compute_sync_committee_period_at_slot::<C>(
+						update.attested_header.beacon.slot,
+					) != previous
+				{
+					Err(anyhow!(
+						"Light client update for period {previous} doesn't finalize a header in the period"
+					))?
+				}
+				(header, update)
+			},
+		};
+
+		let root = header.clone().hash_tree_root()?;
+		let bootstrap =
+			self.fetch_light_client_bootstrap(&format!("0x{}", hex::encode(root.0))).await?;
+		Ok((header, bootstrap.current_sync_committee, update.next_sync_committee))
+	}
+
 	fn generate_route(&self, path: &str) -> Result<Url, anyhow::Error> {
 		let url = Url::parse(&format!("{}{}", self.primary_url.clone(), path))?;
 		Ok(url)
@@ -269,7 +458,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 	/// we use `head`
 	pub async fn fetch_light_client_update(
 		&self,
-		mut client_state: VerifierState,
+		client_state: VerifierState,
 		finality_checkpoint: Checkpoint,
 		latest_block_id: Option<&str>,
 	) -> Result<Option<VerifierStateUpdate>, anyhow::Error> {
@@ -280,6 +469,65 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			return Ok(None);
 		}
 
+		if self.backend == ProverBackend::LightClient {
+			if let Some(update) = self.light_client_api_update(&client_state).await? {
+				return Ok(Some(update));
+			}
+			trace!(target: "sync-committee-prover", "No suitable update from the light client api, proving against the beacon state");
+		}
+
+		self.beacon_state_update(client_state, finality_checkpoint, latest_block_id)
+			.await
+	}
+
+	/// Build an update from the light client api. Returns `None` when the api has no update the
+	/// client can verify, e.g. when the best update of a new period is not finalized yet.
+	async fn light_client_api_update(
+		&self,
+		client_state: &VerifierState,
+	) -> Result<Option<VerifierStateUpdate>, anyhow::Error> {
+		let state_period = client_state.state_period;
+		let finality_update = self.fetch_light_client_finality_update().await?;
+		let signature_period =
+			compute_sync_committee_period_at_slot::<C>(finality_update.signature_slot);
+
+		let update = if should_have_sync_committee_update(state_period, signature_period) {
+			// Finality updates don't carry the next sync committee, rotate with the best update
+			// of the new period instead.
+			match self.fetch_light_client_updates(signature_period, 1).await?.pop() {
+				Some(update) => light_client_api_update_to_verifier_update::<C>(update)?,
+				None => return Ok(None),
+			}
+		} else if signature_period == state_period {
+			light_client_api_update_to_verifier_update::<C>(LightClientUpdate {
+				attested_header: finality_update.attested_header,
+				next_sync_committee: Default::default(),
+				next_sync_committee_branch: vec![],
+				finalized_header: finality_update.finalized_header,
+				finality_branch: finality_update.finality_branch,
+				sync_aggregate: finality_update.sync_aggregate,
+				signature_slot: finality_update.signature_slot,
+			})?
+		} else {
+			return Ok(None);
+		};
+
+		let Some(update) = update else { return Ok(None) };
+		if update.finality_proof.epoch <= client_state.latest_finalized_epoch ||
+			update.attested_header.slot <= client_state.finalized_header.slot
+		{
+			return Ok(None);
+		}
+
+		Ok(Some(update))
+	}
+
+	async fn beacon_state_update(
+		&self,
+		mut client_state: VerifierState,
+		finality_checkpoint: Checkpoint,
+		latest_block_id: Option<&str>,
+	) -> Result<Option<VerifierStateUpdate>, anyhow::Error> {
 		trace!(target: "sync-committee-prover", "A new epoch has been finalized {}", finality_checkpoint.epoch);
 		// Find the highest block with the a threshhold number of sync committee signatures
 		let latest_header = self.fetch_header(latest_block_id.unwrap_or("head")).await?;
@@ -383,6 +631,24 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 		period: u64,
 	) -> Result<VerifierStateUpdate, anyhow::Error> {
 		trace!(target: "sync-committee-prover", "latest_update_for_period {period}");
+		if self.backend == ProverBackend::LightClient {
+			let update = match self.fetch_light_client_updates(period, 1).await?.pop() {
+				Some(update) => light_client_api_update_to_verifier_update::<C>(update)?,
+				None => None,
+			};
+			if let Some(update) = update {
+				return Ok(update);
+			}
+			trace!(target: "sync-committee-prover", "No finalized light client update for period {period}, proving against the beacon state");
+		}
+
+		self.beacon_state_update_for_period(period).await
+	}
+
+	async fn beacon_state_update_for_period(
+		&self,
+		period: u64,
+	) -> Result<VerifierStateUpdate, anyhow::Error> {
 		let mut higest_slot_in_epoch = ((period * C::EPOCHS_PER_SYNC_COMMITTEE_PERIOD) *
 			C::SLOTS_PER_EPOCH) +
 			(C::EPOCHS_PER_SYNC_COMMITTEE_PERIOD * C::SLOTS_PER_EPOCH) -
@@ -472,6 +738,88 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 	}
 }
 
+/// Convert an update from the light client api. Updates without a sync committee carry an
+/// empty `next_sync_committee_branch`. Returns `None` if the update has no finalized header, or
+/// if a sync committee update was not attested in its signature period, which the verifier
+/// rejects.
+pub fn light_client_api_update_to_verifier_update<C: Config>(
+	update: LightClientUpdate,
+) -> anyhow::Result<Option<VerifierStateUpdate>> {
+	let LightClientUpdate {
+		attested_header,
+		next_sync_committee,
+		next_sync_committee_branch,
+		mut finalized_header,
+		finality_branch,
+		sync_aggregate,
+		signature_slot,
+	} = update;
+
+	if finality_branch.iter().all(|node| *node == Node::default()) ||
+		finalized_header.beacon.slot == 0
+	{
+		return Ok(None);
+	}
+
+	let sync_committee_update = if next_sync_committee_branch.is_empty() {
+		None
+	} else {
+		if compute_sync_committee_period_at_slot::<C>(attested_header.beacon.slot) !=
+			compute_sync_committee_period_at_slot::<C>(signature_slot)
+		{
+			return Ok(None);
+		}
+		Some(SyncCommitteeUpdate { next_sync_committee, next_sync_committee_branch })
+	};
+
+	Ok(Some(VerifierStateUpdate {
+		attested_header: attested_header.beacon,
+		sync_committee_update,
+		execution_payload: prove_light_client_execution_payload::<C>(&mut finalized_header)?,
+		finalized_header: finalized_header.beacon,
+		finality_proof: light_client_finality_proof::<C>(finality_branch)?,
+		sync_aggregate,
+		signature_slot,
+	}))
+}
+
+/// The light client api proves the finalized block root, one level below the finalized
+/// checkpoint the verifier expects. The first node of the branch is the checkpoint epoch.
+pub fn light_client_finality_proof<C: Config>(
+	finality_branch: Vec<Node>,
+) -> anyhow::Result<FinalityProof> {
+	if finality_branch.len() != C::FINALIZED_ROOT_INDEX_LOG2 as usize + 1 {
+		Err(anyhow!("Unexpected finality branch length {}", finality_branch.len()))?
+	}
+	let epoch = u64::from_le_bytes(finality_branch[0].0[..8].try_into().expect("Infallible"));
+
+	Ok(FinalityProof { epoch, finality_branch: finality_branch[1..].to_vec() })
+}
+
+/// Prove the execution payload of a light client header against its block body.
+#[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
+pub fn prove_light_client_execution_payload<C: Config>(
+	header: &mut LightClientHeader,
+) -> anyhow::Result<ExecutionPayloadProof> {
+	if header.execution_branch.len() != EXECUTION_PAYLOAD_BODY_INDEX_LOG2 as usize {
+		Err(anyhow!("Unexpected execution branch length {}", header.execution_branch.len()))?
+	}
+	let indices = [
+		C::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize,
+		C::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize,
+		C::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize,
+	];
+	let multi_proof = ssz_rs::generate_proof(&mut header.execution, indices.as_slice())?;
+
+	Ok(ExecutionPayloadProof {
+		state_root: H256::from_slice(header.execution.state_root.as_slice()),
+		block_number: header.execution.block_number,
+		timestamp: header.execution.timestamp,
+		multi_proof,
+		execution_payload_branch: header.execution_branch.clone(),
+	})
+}
+
 #[instrument(level = "trace", target = "sync-committee-prover", skip_all)]
 pub fn prove_execution_payload<
 	C: Config,
@@ -0,0 +1,13 @@
+use sync_committee_primitives::constants::Root;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Response {
+	pub data: Genesis,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Genesis {
+	pub genesis_time: String,
+	pub genesis_validators_root: Root,
+	pub genesis_fork_version: String,
+}
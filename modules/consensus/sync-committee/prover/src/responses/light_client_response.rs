@@ -0,0 +1,58 @@
+use sync_committee_primitives::{
+	consensus_types::{BeaconBlockHeader, ExecutionPayloadHeader, SyncAggregate, SyncCommittee},
+	constants::{Root, BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES, SYNC_COMMITTEE_SIZE},
+};
+
+/// Response of `/eth/v1/beacon/light_client/bootstrap/{block_root}`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BootstrapResponse {
+	pub data: LightClientBootstrap,
+}
+
+/// Response of `/eth/v1/beacon/light_client/finality_update`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FinalityUpdateResponse {
+	pub data: LightClientFinalityUpdate,
+}
+
+/// An item of the `/eth/v1/beacon/light_client/updates` response
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UpdateResponse {
+	pub data: LightClientUpdate,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LightClientHeader {
+	pub beacon: BeaconBlockHeader,
+	pub execution: ExecutionPayloadHeader<BYTES_PER_LOGS_BLOOM, MAX_EXTRA_DATA_BYTES>,
+	pub execution_branch: Vec<Root>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LightClientBootstrap {
+	pub header: LightClientHeader,
+	pub current_sync_committee: SyncCommittee<SYNC_COMMITTEE_SIZE>,
+	pub current_sync_committee_branch: Vec<Root>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LightClientUpdate {
+	pub attested_header: LightClientHeader,
+	pub next_sync_committee: SyncCommittee<SYNC_COMMITTEE_SIZE>,
+	pub next_sync_committee_branch: Vec<Root>,
+	pub finalized_header: LightClientHeader,
+	pub finality_branch: Vec<Root>,
+	pub sync_aggregate: SyncAggregate<SYNC_COMMITTEE_SIZE>,
+	#[serde(with = "serde_hex_utils::as_string")]
+	pub signature_slot: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LightClientFinalityUpdate {
+	pub attested_header: LightClientHeader,
+	pub finalized_header: LightClientHeader,
+	pub finality_branch: Vec<Root>,
+	pub sync_aggregate: SyncAggregate<SYNC_COMMITTEE_SIZE>,
+	#[serde(with = "serde_hex_utils::as_string")]
+	pub signature_slot: u64,
+}
@@ -19,3 +19,15 @@ pub fn beacon_state_route(state_id: &str) -> String {
 pub fn finality_checkpoints(state_id: &str) -> String {
 	format!("/eth/v1/beacon/states/{state_id}/finality_checkpoints")
 }
+pub fn light_client_bootstrap_route(block_root: &str) -> String {
+	format!("/eth/v1/beacon/light_client/bootstrap/{block_root}")
+}
+pub fn light_client_updates_route(start_period: u64, count: u64) -> String {
+	format!("/eth/v1/beacon/light_client/updates?start_period={start_period}&count={count}")
+}
+pub fn light_client_finality_update_route() -> String {
+	"/eth/v1/beacon/light_client/finality_update".to_string()
+}
+pub fn genesis_route() -> String {
+	"/eth/v1/beacon/genesis".to_string()
+}
@@ -0,0 +1,112 @@
+//! Parsing of network specs from a beacon node `config.yaml`.
+
+use anyhow::{anyhow, Context};
+use std::collections::BTreeMap;
+use sync_committee_primitives::{
+	constants::Version,
+	spec::{Fork, NetworkSpec},
+};
+
+/// Build a [`NetworkSpec`] from the contents of a standard beacon `config.yaml`, such as the ones
+/// published in the `eth-clients` repositories or generated for a devnet. The genesis validators
+/// root is not part of the config, it's served by the `/eth/v1/beacon/genesis` endpoint.
+///
+/// Every `<NAME>_FORK_VERSION` with a matching `<NAME>_FORK_EPOCH` becomes a fork, so forks
+/// added after this was written are picked up without a code change.
+pub fn parse_config_yaml(
+	config_yaml: &str,
+	genesis_validators_root: [u8; 32],
+) -> anyhow::Result<NetworkSpec> {
+	let mut values = BTreeMap::new();
+	let mut order = vec![];
+	for line in config_yaml.lines() {
+		let line = line.split('#').next().unwrap_or_default().trim();
+		let Some((key, value)) = line.split_once(':') else { continue };
+		let value = value.trim().trim_matches(|c: char| c == '\'' || c == '"');
+		if value.is_empty() {
+			continue;
+		}
+		order.push(key.trim());
+		values.insert(key.trim(), value);
+	}
+
+	let genesis_fork_version = values
+		.get("GENESIS_FORK_VERSION")
+		.ok_or_else(|| anyhow!("GENESIS_FORK_VERSION is missing from the config"))
+		.and_then(|value| parse_version(value))?;
+
+	let mut forks = vec![];
+	for key in order {
+		let Some(name) = key.strip_suffix("_FORK_VERSION") else { continue };
+		if name == "GENESIS" {
+			continue;
+		}
+		let epoch_key = format!("{name}_FORK_EPOCH");
+		let epoch = values
+			.get(epoch_key.as_str())
+			.ok_or_else(|| anyhow!("{epoch_key} is missing from the config"))?
+			.parse::<u64>()
+			.with_context(|| format!("Invalid {epoch_key}"))?;
+		forks.push(Fork { version: parse_version(values[key])?, epoch });
+	}
+	// Stable, so forks activated at the same epoch keep the order of the config
+	forks.sort_by_key(|fork| fork.epoch);
+
+	let spec = NetworkSpec { genesis_validators_root, genesis_fork_version, forks };
+	spec.validate().map_err(|err| anyhow!("Invalid network spec: {err}"))?;
+
+	Ok(spec)
+}
+
+fn parse_version(value: &str) -> anyhow::Result<Version> {
+	let bytes = hex::decode(value.trim_start_matches("0x"))
+		.with_context(|| format!("Invalid fork version {value}"))?;
+	bytes.try_into().map_err(|_| anyhow!("Fork version {value} is not 4 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sync_committee_primitives::constants::{sepolia::Sepolia, Config};
+
+	const SEPOLIA_CONFIG: &str = r#"
+# Extends the mainnet preset
+PRESET_BASE: 'mainnet'
+CONFIG_NAME: 'sepolia'
+
+# Genesis
+MIN_GENESIS_ACTIVE_VALIDATOR_COUNT: 1300
+GENESIS_FORK_VERSION: 0x90000069
+GENESIS_DELAY: 86400
+
+# Forking
+ALTAIR_FORK_VERSION: 0x90000070
+ALTAIR_FORK_EPOCH: 50
+BELLATRIX_FORK_VERSION: 0x90000071
+BELLATRIX_FORK_EPOCH: 100
+CAPELLA_FORK_VERSION: 0x90000072
+CAPELLA_FORK_EPOCH: 56832
+DENEB_FORK_VERSION: 0x90000073
+DENEB_FORK_EPOCH: 132608
+ELECTRA_FORK_VERSION: 0x90000074
+ELECTRA_FORK_EPOCH: 222464
+FULU_FORK_VERSION: 0x90000075
+FULU_FORK_EPOCH: 272640
+"#;
+
+	#[test]
+	fn parses_the_sepolia_config() {
+		let spec = parse_config_yaml(SEPOLIA_CONFIG, Sepolia::GENESIS_VALIDATORS_ROOT).unwrap();
+		assert_eq!(spec, NetworkSpec::from_config::<Sepolia>());
+	}
+
+	#[test]
+	fn picks_up_new_forks() {
+		let config = format!(
+			"{SEPOLIA_CONFIG}GLOAS_FORK_VERSION: '0x90000076'\nGLOAS_FORK_EPOCH: 18446744073709551615\n"
+		);
+		let spec = parse_config_yaml(&config, Sepolia::GENESIS_VALIDATORS_ROOT).unwrap();
+		assert_eq!(spec.forks.last(), Some(&Fork { version: [0x90, 0, 0, 0x76], epoch: u64::MAX }));
+		assert_eq!(spec.fork_version(300_000), [0x90, 0, 0, 0x75]);
+	}
+}
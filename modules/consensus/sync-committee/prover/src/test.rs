@@ -293,6 +293,55 @@ async fn test_prover() {
 	}
 }
 
+#[test]
+fn light_client_finality_branch_is_rooted_at_the_checkpoint() {
+	let epoch = 1_234_567u64;
+	let mut epoch_leaf = [0u8; 32];
+	epoch_leaf[..8].copy_from_slice(&epoch.to_le_bytes());
+	let mut branch = vec![Node::from_bytes(epoch_leaf)];
+	branch.extend(
+		(0..KurtosisDevnet::FINALIZED_ROOT_INDEX_LOG2).map(|i| Node::from_bytes([i as u8; 32])),
+	);
+
+	let proof = light_client_finality_proof::<KurtosisDevnet>(branch.clone()).unwrap();
+	assert_eq!(proof.epoch, epoch);
+	assert_eq!(proof.finality_branch, branch[1..].to_vec());
+
+	branch.pop();
+	assert!(light_client_finality_proof::<KurtosisDevnet>(branch).is_err());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_light_client_backend() {
+	let sync_committee_prover = setup_prover().with_backend(ProverBackend::LightClient);
+	let mut client_state = sync_committee_prover.fetch_verifier_state("finalized").await.unwrap();
+
+	let mut count = 0;
+	while count < 2 {
+		tokio::time::sleep(std::time::Duration::from_secs(12 * 32)).await;
+		let checkpoint = sync_committee_prover
+			.fetch_finalized_checkpoint(Some("head"))
+			.await
+			.unwrap()
+			.finalized;
+		let Some(update) = sync_committee_prover
+			.fetch_light_client_update(client_state.clone(), checkpoint, None)
+			.await
+			.unwrap()
+		else {
+			continue;
+		};
+		client_state =
+			verify_sync_committee_attestation::<KurtosisDevnet>(client_state, update).unwrap();
+		println!(
+			"Sucessfully verified Ethereum block at slot {:?}",
+			client_state.finalized_header.slot
+		);
+		count += 1;
+	}
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_switch_provider_middleware() {
@@ -15,17 +15,35 @@ use ssz_rs::{
 	prelude::is_valid_merkle_branch,
 };
 use sync_committee_primitives::{
-	consensus_types::Checkpoint,
-	constants::{Config, DOMAIN_SYNC_COMMITTEE, Root},
-	types::{VerifierState, VerifierStateUpdate},
+	consensus_types::{BeaconBlockHeader, Checkpoint},
+	constants::{
+		Config, DOMAIN_SYNC_COMMITTEE, EXECUTION_PAYLOAD_BODY_INDEX,
+		EXECUTION_PAYLOAD_BODY_INDEX_LOG2, Root,
+	},
+	spec::NetworkSpec,
+	types::{ExecutionPayloadProof, VerifierState, VerifierStateUpdate},
 	util::{
-		compute_domain, compute_epoch_at_slot, compute_fork_version, compute_signing_root,
+		compute_domain, compute_epoch_at_slot, compute_signing_root,
 		compute_sync_committee_period_at_slot, should_have_sync_committee_update,
 	},
 };
 
 /// This function simply verifies a sync committee's attestation & it's finalized counterpart.
 pub fn verify_sync_committee_attestation<C: Config>(
+	trusted_state: VerifierState,
+	update: VerifierStateUpdate,
+) -> Result<VerifierState, Error> {
+	verify_sync_committee_attestation_with_spec::<C>(
+		&NetworkSpec::from_config::<C>(),
+		trusted_state,
+		update,
+	)
+}
+
+/// Verifies a sync committee's attestation like [`verify_sync_committee_attestation`], using the
+/// genesis and fork schedule in `spec` instead of the ones compiled into `C`.
+pub fn verify_sync_committee_attestation_with_spec<C: Config>(
+	spec: &NetworkSpec,
 	trusted_state: VerifierState,
 	mut update: VerifierStateUpdate,
 ) -> Result<VerifierState, Error> {
@@ -120,13 +138,13 @@ pub fn verify_sync_committee_attestation<C: Config>(
 		.filter_map(|(bit, key)| if !(*bit) { Some(key.clone()) } else { None })
 		.collect::<Vec<_>>();
 
-	let fork_version = compute_fork_version::<C>(compute_epoch_at_slot::<C>(update.signature_slot));
+	let fork_version = spec.fork_version(compute_epoch_at_slot::<C>(update.signature_slot));
 
 	let domain = compute_domain(
 		DOMAIN_SYNC_COMMITTEE,
 		Some(fork_version),
-		Some(Root::from_bytes(C::GENESIS_VALIDATORS_ROOT.try_into().expect("Infallible"))),
-		C::GENESIS_FORK_VERSION,
+		Some(Root::from_bytes(spec.genesis_validators_root)),
+		spec.genesis_fork_version,
 	)
 	.map_err(|_| Error::InvalidUpdate("Failed to compute domain".into()))?;
 
@@ -175,47 +193,7 @@ pub fn verify_sync_committee_attestation<C: Config>(
 	}
 
 	// verify the associated execution header of the finalized beacon header.
-	let mut execution_payload = update.execution_payload;
-	let execution_payload_indices = [
-		GeneralizedIndex(C::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize),
-		GeneralizedIndex(C::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize),
-		GeneralizedIndex(C::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize),
-	];
-	// `calculate_multi_merkle_root` panics on a short `multi_proof` because its final
-	// `objects.get(&GeneralizedIndex(1)).unwrap()` cannot reconstruct the root. Reject
-	// proofs whose helper-node count does not match what the algorithm requires so an
-	// attacker-controlled `multi_proof` cannot panic the runtime via the public unsigned
-	// consensus update path.
-	if execution_payload.multi_proof.len() != get_helper_indices(&execution_payload_indices).len()
-	{
-		Err(Error::InvalidMerkleBranch("Execution payload multiproof length".into()))?;
-	}
-	let execution_payload_root = calculate_multi_merkle_root(
-		&[
-			Node::from_bytes(execution_payload.state_root.as_ref().try_into().expect("Infallible")),
-			execution_payload.block_number.hash_tree_root().map_err(|_| {
-				Error::MerkleizationError("Failed to hash execution payload".into())
-			})?,
-			execution_payload
-				.timestamp
-				.hash_tree_root()
-				.map_err(|_| Error::MerkleizationError("Failed to hash timestamp".into()))?,
-		],
-		&execution_payload.multi_proof,
-		&execution_payload_indices,
-	);
-
-	let is_merkle_branch_valid = is_valid_merkle_branch(
-		&execution_payload_root,
-		execution_payload.execution_payload_branch.iter(),
-		C::EXECUTION_PAYLOAD_INDEX_LOG2 as usize,
-		C::EXECUTION_PAYLOAD_INDEX as usize,
-		&update.finalized_header.state_root,
-	);
-
-	if !is_merkle_branch_valid {
-		Err(Error::InvalidMerkleBranch("Execution payload branch".into()))?;
-	}
+	verify_execution_payload::<C>(update.execution_payload, &update.finalized_header)?;
 
 	if let Some(mut sync_committee_update) = update.sync_committee_update.clone() {
 		let sync_root = sync_committee_update
@@ -260,6 +238,70 @@ pub fn verify_sync_committee_attestation<C: Config>(
 	Ok(verifier_state)
 }
 
+/// Verifies the execution payload of `finalized_header`, proven either against its state root or
+/// against its body root.
+fn verify_execution_payload<C: Config>(
+	mut execution_payload: ExecutionPayloadProof,
+	finalized_header: &BeaconBlockHeader,
+) -> Result<(), Error> {
+	let execution_payload_indices = [
+		GeneralizedIndex(C::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize),
+		GeneralizedIndex(C::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize),
+		GeneralizedIndex(C::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize),
+	];
+	// `calculate_multi_merkle_root` panics on a short `multi_proof` because its final
+	// `objects.get(&GeneralizedIndex(1)).unwrap()` cannot reconstruct the root. Reject
+	// proofs whose helper-node count does not match what the algorithm requires so an
+	// attacker-controlled `multi_proof` cannot panic the runtime via the public unsigned
+	// consensus update path.
+	if execution_payload.multi_proof.len() != get_helper_indices(&execution_payload_indices).len() {
+		Err(Error::InvalidMerkleBranch("Execution payload multiproof length".into()))?;
+	}
+	let execution_payload_root = calculate_multi_merkle_root(
+		&[
+			Node::from_bytes(execution_payload.state_root.as_ref().try_into().expect("Infallible")),
+			execution_payload.block_number.hash_tree_root().map_err(|_| {
+				Error::MerkleizationError("Failed to hash execution payload".into())
+			})?,
+			execution_payload
+				.timestamp
+				.hash_tree_root()
+				.map_err(|_| Error::MerkleizationError("Failed to hash timestamp".into()))?,
+		],
+		&execution_payload.multi_proof,
+		&execution_payload_indices,
+	);
+
+	// The payload is proven either against the finalized state, or against the finalized block
+	// body as served by the beacon light client API. Both commit to the same payload header, the
+	// branch length tells them apart.
+	let is_merkle_branch_valid = if execution_payload.execution_payload_branch.len() ==
+		EXECUTION_PAYLOAD_BODY_INDEX_LOG2 as usize
+	{
+		is_valid_merkle_branch(
+			&execution_payload_root,
+			execution_payload.execution_payload_branch.iter(),
+			EXECUTION_PAYLOAD_BODY_INDEX_LOG2 as usize,
+			EXECUTION_PAYLOAD_BODY_INDEX as usize,
+			&finalized_header.body_root,
+		)
+	} else {
+		is_valid_merkle_branch(
+			&execution_payload_root,
+			execution_payload.execution_payload_branch.iter(),
+			C::EXECUTION_PAYLOAD_INDEX_LOG2 as usize,
+			C::EXECUTION_PAYLOAD_INDEX as usize,
+			&finalized_header.state_root,
+		)
+	};
+
+	if !is_merkle_branch_valid {
+		Err(Error::InvalidMerkleBranch("Execution payload branch".into()))?;
+	}
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod supermajority_tests {
 	use super::*;
@@ -348,3 +390,107 @@ mod supermajority_tests {
 		);
 	}
 }
+
+#[cfg(test)]
+mod execution_payload_tests {
+	use super::*;
+	use sync_committee_primitives::constants::sepolia::Sepolia;
+
+	/// A payload proof with arbitrary helper nodes, along with the payload root it commits to.
+	fn payload_proof() -> (ExecutionPayloadProof, Node) {
+		let indices = [
+			GeneralizedIndex(Sepolia::EXECUTION_PAYLOAD_STATE_ROOT_INDEX as usize),
+			GeneralizedIndex(Sepolia::EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX as usize),
+			GeneralizedIndex(Sepolia::EXECUTION_PAYLOAD_TIMESTAMP_INDEX as usize),
+		];
+		let mut proof = ExecutionPayloadProof {
+			block_number: 100,
+			timestamp: 1_700_000_000,
+			multi_proof: (0..get_helper_indices(&indices).len())
+				.map(|i| Node::from_bytes([i as u8 + 1; 32]))
+				.collect(),
+			..Default::default()
+		};
+		proof.state_root.0 = [7u8; 32];
+
+		let root = calculate_multi_merkle_root(
+			&[
+				Node::from_bytes(proof.state_root.0),
+				proof.block_number.hash_tree_root().unwrap(),
+				proof.timestamp.hash_tree_root().unwrap(),
+			],
+			&proof.multi_proof,
+			&indices,
+		);
+		(proof, root)
+	}
+
+	/// The root a `branch` for the leaf at generalized `index` commits to.
+	fn branch_root(leaf: Node, branch: &[Node], index: u64) -> Node {
+		calculate_multi_merkle_root(&[leaf], branch, &[GeneralizedIndex(index as usize)])
+	}
+
+	#[test]
+	fn verifies_payloads_against_the_block_body() {
+		let (mut proof, payload_root) = payload_proof();
+		proof.execution_payload_branch = (0..EXECUTION_PAYLOAD_BODY_INDEX_LOG2)
+			.map(|i| Node::from_bytes([0xb0 + i as u8; 32]))
+			.collect();
+		let body_root = branch_root(
+			payload_root,
+			&proof.execution_payload_branch,
+			EXECUTION_PAYLOAD_BODY_INDEX,
+		);
+
+		let header = BeaconBlockHeader { body_root, ..Default::default() };
+		assert!(verify_execution_payload::<Sepolia>(proof.clone(), &header).is_ok());
+
+		// A body branch is never checked against the state root
+		let header = BeaconBlockHeader { state_root: body_root, ..Default::default() };
+		assert!(matches!(
+			verify_execution_payload::<Sepolia>(proof, &header),
+			Err(Error::InvalidMerkleBranch(_))
+		));
+	}
+
+	#[test]
+	fn verifies_payloads_against_the_beacon_state() {
+		let (mut proof, payload_root) = payload_proof();
+		proof.execution_payload_branch = (0..Sepolia::EXECUTION_PAYLOAD_INDEX_LOG2)
+			.map(|i| Node::from_bytes([0x50 + i as u8; 32]))
+			.collect();
+		let state_root = branch_root(
+			payload_root,
+			&proof.execution_payload_branch,
+			Sepolia::EXECUTION_PAYLOAD_INDEX,
+		);
+
+		let header = BeaconBlockHeader { state_root, ..Default::default() };
+		assert!(verify_execution_payload::<Sepolia>(proof.clone(), &header).is_ok());
+
+		let header = BeaconBlockHeader { body_root: state_root, ..Default::default() };
+		assert!(matches!(
+			verify_execution_payload::<Sepolia>(proof, &header),
+			Err(Error::InvalidMerkleBranch(_))
+		));
+	}
+
+	#[test]
+	fn rejects_payloads_that_do_not_match_the_body() {
+		let (mut proof, payload_root) = payload_proof();
+		proof.execution_payload_branch =
+			vec![Node::from_bytes([0xb0; 32]); EXECUTION_PAYLOAD_BODY_INDEX_LOG2 as usize];
+		let body_root = branch_root(
+			payload_root,
+			&proof.execution_payload_branch,
+			EXECUTION_PAYLOAD_BODY_INDEX,
+		);
+		let header = BeaconBlockHeader { body_root, ..Default::default() };
+
+		proof.block_number += 1;
+		assert!(matches!(
+			verify_execution_payload::<Sepolia>(proof, &header),
+			Err(Error::InvalidMerkleBranch(_))
+		));
+	}
+}
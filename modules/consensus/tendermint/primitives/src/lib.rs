@@ -6,15 +6,15 @@ pub use address::account_id_from_public_key;
 
 pub mod verifier;
 pub use cometbft::{
-	PublicKey as PubKey,
 	block::{
-		Commit, CommitSig, Header, Height, Id, parts::Header as PartSetHeader,
-		signed_header::SignedHeader,
+		parts::Header as PartSetHeader, signed_header::SignedHeader, Commit, CommitSig, Header,
+		Height, Id,
 	},
 	chain::Id as ChainId,
 	hash::Hash,
 	time::Time,
 	validator::{Info as Validator, Set as ValidatorSet},
+	PublicKey as PubKey,
 };
 
 pub use cometbft_proto::types::v1;
@@ -22,7 +22,7 @@ pub use cometbft_proto::types::v1;
 pub use verifier::{
 	CodecConsensusProof, CodecSignedHeader, CodecTrustedState, CodecValidator, ConsensusProof,
 	TendermintCodecHeader, TrustedState, UpdatedTrustedState, VerificationError,
-	VerificationOptions,
+	VerificationOptions, VersionedConsensusProof,
 };
 
 pub mod prover;
@@ -211,6 +211,11 @@ pub struct ConsensusProof {
 	pub signed_header: SignedHeader,
 	/// Next validator set  (optional) - target height + 1
 	pub next_validators: Option<Vec<Validator>>,
+	/// Validator set that signed the header (optional), only needed when it is neither the
+	/// trusted validator set nor the trusted next validator set, i.e when skipping past a
+	/// validator set rotation
+	#[serde(default)]
+	pub validators: Option<Vec<Validator>>,
 }
 
 #[derive(Encode, Decode, Debug, Clone, TypeInfo)]
@@ -219,6 +224,30 @@ pub struct CodecConsensusProof {
 	pub signed_header: CodecSignedHeader,
 	/// Next validator set  (optional) - target height + 1
 	pub next_validators: Option<Vec<CodecValidator>>,
+	/// Validator set that signed the header (optional)
+	pub validators: Option<Vec<CodecValidator>>,
+}
+
+/// Consensus proof without the validator set that signed the header, the format used before
+/// header updates could skip past validator set rotations.
+#[derive(Encode, Decode, Debug, Clone, TypeInfo)]
+pub struct CodecConsensusProofV0 {
+	/// Signed header containing the block header and commit
+	pub signed_header: CodecSignedHeader,
+	/// Next validator set  (optional) - target height + 1
+	pub next_validators: Option<Vec<CodecValidator>>,
+}
+
+/// Versioned wire format of a [`ConsensusProof`]. The version is the SCALE variant index, so a
+/// proof in a format the verifier doesn't know fails to decode rather than being misread.
+#[derive(Encode, Decode, Debug, Clone, TypeInfo)]
+pub enum VersionedConsensusProof {
+	/// Signed header and next validator set
+	#[codec(index = 0)]
+	V0(CodecConsensusProofV0),
+	/// Adds the validator set that signed the header
+	#[codec(index = 1)]
+	V1(CodecConsensusProof),
 }
 
 #[derive(Encode, Decode, Debug, Clone, TypeInfo)]
@@ -349,21 +378,40 @@ impl CodecConsensusProof {
 	/// Convert back to ConsensusProof
 	pub fn to_consensus_proof(&self) -> Result<ConsensusProof, String> {
 		let signed_header = self.signed_header.to_signed_header()?;
-		let next_validators = if let Some(ref validators) = self.next_validators {
-			Some(
-				validators
-					.iter()
-					.map(|codec_validator| codec_validator.to_validator())
-					.collect::<Result<Vec<_>, _>>()?,
-			)
-		} else {
-			None
-		};
+		let next_validators = to_validators(&self.next_validators)?;
+		let validators = to_validators(&self.validators)?;
 
-		Ok(ConsensusProof { signed_header, next_validators })
+		Ok(ConsensusProof { signed_header, next_validators, validators })
 	}
 }
 
+impl VersionedConsensusProof {
+	/// Convert back to ConsensusProof
+	pub fn to_consensus_proof(&self) -> Result<ConsensusProof, String> {
+		match self {
+			VersionedConsensusProof::V0(proof) => Ok(ConsensusProof::new(
+				proof.signed_header.to_signed_header()?,
+				to_validators(&proof.next_validators)?,
+			)),
+			VersionedConsensusProof::V1(proof) => proof.to_consensus_proof(),
+		}
+	}
+}
+
+fn to_validators(
+	validators: &Option<Vec<CodecValidator>>,
+) -> Result<Option<Vec<Validator>>, String> {
+	validators
+		.as_ref()
+		.map(|validators| {
+			validators
+				.iter()
+				.map(|codec_validator| codec_validator.to_validator())
+				.collect::<Result<Vec<_>, _>>()
+		})
+		.transpose()
+}
+
 impl CodecSignedHeader {
 	/// Convert back to SignedHeader
 	pub fn to_signed_header(&self) -> Result<SignedHeader, String> {
@@ -589,10 +637,21 @@ impl From<&ConsensusProof> for CodecConsensusProof {
 				.next_validators
 				.as_ref()
 				.map(|validators| validators.iter().map(CodecValidator::from).collect()),
+			validators: proof
+				.validators
+				.as_ref()
+				.map(|validators| validators.iter().map(CodecValidator::from).collect()),
 		}
 	}
 }
 
+impl From<&ConsensusProof> for VersionedConsensusProof {
+	/// Encodes in the latest format
+	fn from(proof: &ConsensusProof) -> Self {
+		VersionedConsensusProof::V1(CodecConsensusProof::from(proof))
+	}
+}
+
 impl From<&SignedHeader> for CodecSignedHeader {
 	fn from(signed_header: &SignedHeader) -> Self {
 		CodecSignedHeader {
@@ -702,7 +761,14 @@ impl From<&cometbft::public_key::PublicKey> for CodecPublicKey {
 
 impl ConsensusProof {
 	pub fn new(signed_header: SignedHeader, next_validators: Option<Vec<Validator>>) -> Self {
-		Self { signed_header, next_validators }
+		Self { signed_header, next_validators, validators: None }
+	}
+
+	/// Attach the validator set that signed the header, for headers that skip past a validator
+	/// set rotation
+	pub fn with_validators(mut self, validators: Vec<Validator>) -> Self {
+		self.validators = Some(validators);
+		self
 	}
 
 	pub fn height(&self) -> u64 {
@@ -1,23 +1,124 @@
-use tendermint_primitives::{Client, ConsensusProof, ProverError, TrustedState};
+use std::{
+	collections::BTreeMap,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use tendermint_primitives::{
+	Client, ConsensusProof, ProverError, SignedHeader, TrustedState, Validator, ValidatorSet,
+	VerificationError,
+};
+use tendermint_verifier::{validate_validator_set_hash, verify_header_update, MAX_HEADER_UPDATES};
+
+/// A header along with the validator sets needed to verify it
+#[derive(Clone)]
+struct LightBlock {
+	signed_header: SignedHeader,
+	validators: Vec<Validator>,
+	next_validators: Option<Vec<Validator>>,
+}
 
 /// Main function to prove a header update
-/// This constructs a consensus proof that can be verified by the verifier
+/// Constructs the chain of consensus proofs that takes `trusted_state` to `target_height`, using
+/// the light client skipping algorithm: the target is tried first, and whenever the trusted
+/// validators hold too little voting power over a header, the next attempt is halfway between
+/// the trusted height and that header. Each proof in the chain is verified against the trusted
+/// state produced by the one before it. The chain holds at most [`MAX_HEADER_UPDATES`] proofs,
+/// if the target needs more hops the chain ends at the last header reached and the next update
+/// continues from there. Otherwise the last proof is for `target_height`.
 pub async fn prove_header_update(
 	rpc_client: &impl Client,
-	_trusted_state: &TrustedState,
+	trusted_state: &TrustedState,
 	target_height: u64,
-) -> Result<ConsensusProof, ProverError> {
-	let target_signed_header = rpc_client.signed_header(target_height).await?;
+) -> Result<Vec<ConsensusProof>, ProverError> {
+	if target_height <= trusted_state.height {
+		return Err(ProverError::InvalidHeight(format!(
+			"target height {target_height} is not above the trusted height {}",
+			trusted_state.height
+		)));
+	}
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|e| ProverError::TimestampError(e.to_string()))?
+		.as_secs();
 
-	let next_validators = if target_signed_header.header.next_validators_hash.is_empty() {
+	let mut light_blocks = BTreeMap::new();
+	let mut trusted_state = trusted_state.clone();
+	let mut proofs = vec![];
+	let mut pivot = target_height;
+	while trusted_state.height < target_height {
+		if !light_blocks.contains_key(&pivot) {
+			light_blocks.insert(pivot, fetch_light_block(rpc_client, pivot).await?);
+		}
+		let proof = consensus_proof(&trusted_state, light_blocks[&pivot].clone());
+		match verify_header_update(trusted_state.clone(), proof.clone(), now) {
+			Ok(updated_state) => {
+				tracing::trace!(
+					"Verified header at {pivot} against trusted height {}",
+					trusted_state.height
+				);
+				trusted_state = updated_state.trusted_state;
+				proofs.push(proof);
+				pivot = target_height;
+				if proofs.len() == MAX_HEADER_UPDATES && trusted_state.height < target_height {
+					tracing::trace!(
+						"Stopping at height {} after {MAX_HEADER_UPDATES} header updates",
+						trusted_state.height
+					);
+					break;
+				}
+			},
+			Err(VerificationError::NotEnoughTrust(_)) if pivot > trusted_state.height + 1 => {
+				pivot = trusted_state.height + (pivot - trusted_state.height) / 2;
+			},
+			Err(e) =>
+				return Err(ProverError::ProofConstructionError(format!(
+					"header at {pivot} cannot be verified from trusted height {}: {e}",
+					trusted_state.height
+				))),
+		}
+	}
+
+	Ok(proofs)
+}
+
+async fn fetch_light_block(
+	rpc_client: &impl Client,
+	height: u64,
+) -> Result<LightBlock, ProverError> {
+	let signed_header = rpc_client.signed_header(height).await?;
+	let validators = rpc_client.validators(height).await?;
+	let next_validators = if signed_header.header.next_validators_hash.is_empty() {
 		None
 	} else {
-		Some(rpc_client.next_validators(target_height).await?)
+		Some(rpc_client.next_validators(height).await?)
 	};
 
-	let consensus_proof = ConsensusProof::new(target_signed_header, next_validators);
+	Ok(LightBlock { signed_header, validators, next_validators })
+}
 
-	Ok(consensus_proof)
+/// The proof only carries the header's validator set when the verifier can't find it in the
+/// trusted state, i.e when the header skips past a validator set rotation.
+fn consensus_proof(trusted_state: &TrustedState, light_block: LightBlock) -> ConsensusProof {
+	let validators_hash = light_block.signed_header.header.validators_hash;
+	let is_trusted = validate_validator_set_hash(
+		&ValidatorSet::new(trusted_state.validators.clone(), None),
+		validators_hash,
+		false,
+	)
+	.is_ok() ||
+		validate_validator_set_hash(
+			&ValidatorSet::new(trusted_state.next_validators.clone(), None),
+			validators_hash,
+			true,
+		)
+		.is_ok();
+
+	let proof = ConsensusProof::new(light_block.signed_header, light_block.next_validators);
+	if is_trusted {
+		proof
+	} else {
+		proof.with_validators(light_block.validators)
+	}
 }
 
 /// Prove a header for misbehaviour detection
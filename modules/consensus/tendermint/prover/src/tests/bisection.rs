@@ -0,0 +1,191 @@
+//! Bisection over a local chain of signed headers, no RPC needed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cometbft::{
+	block::{self, header::Version, Commit, CommitSig, Round},
+	chain,
+	vote::{self, Power, ValidatorIndex, Vote},
+	Hash, PublicKey, Signature, Time,
+};
+use polkadot_sdk::sp_core::{ed25519, Pair};
+use tendermint_primitives::{
+	Client, ConsensusProof, ProverError, SignedHeader, TrustedState, Validator, ValidatorSet,
+	VerificationError, VerificationOptions,
+};
+use tendermint_verifier::{verify_header_updates, MAX_HEADER_UPDATES};
+
+use crate::prove_header_update;
+
+const CHAIN_ID: &str = "bisection-1";
+
+const TARGET: u64 = 20;
+
+/// A chain whose validator set rotates to a disjoint set after heights 5 and 10, so no header
+/// past a rotation can be verified from a trusted state before it.
+struct RotatingChain {
+	sets: [Vec<ed25519::Pair>; 3],
+	genesis: u64,
+}
+
+impl RotatingChain {
+	fn new() -> Self {
+		let set = |set: u8| (1..=4).map(|i| ed25519::Pair::from_seed(&[set * 4 + i; 32])).collect();
+		Self { sets: [set(0), set(1), set(2)], genesis: now() - 100 }
+	}
+
+	fn signers(&self, height: u64) -> &[ed25519::Pair] {
+		match height {
+			0..=5 => &self.sets[0],
+			6..=10 => &self.sets[1],
+			_ => &self.sets[2],
+		}
+	}
+
+	fn validators_at(&self, height: u64) -> Vec<Validator> {
+		self.signers(height)
+			.iter()
+			.map(|pair| {
+				let public_key = PublicKey::from_raw_ed25519(&pair.public().0).unwrap();
+				Validator::new(public_key, Power::from(10u32))
+			})
+			.collect()
+	}
+
+	fn time(&self, height: u64) -> Time {
+		Time::from_unix_timestamp((self.genesis + height) as i64, 0).unwrap()
+	}
+
+	fn header(&self, height: u64) -> SignedHeader {
+		let validators = self.validators_at(height);
+		let header = block::Header {
+			version: Version { block: 11, app: 0 },
+			chain_id: chain::Id::try_from(CHAIN_ID).unwrap(),
+			height: block::Height::try_from(height).unwrap(),
+			time: self.time(height),
+			last_block_id: None,
+			last_commit_hash: None,
+			data_hash: None,
+			validators_hash: ValidatorSet::new(validators.clone(), None).hash(),
+			next_validators_hash: ValidatorSet::new(self.validators_at(height + 1), None).hash(),
+			consensus_hash: Hash::None,
+			app_hash: Default::default(),
+			last_results_hash: None,
+			evidence_hash: None,
+			proposer_address: validators[0].address,
+		};
+		let block_id = block::Id { hash: header.hash(), part_set_header: Default::default() };
+
+		// Every validator precommits the same canonical vote
+		let vote = Vote {
+			vote_type: vote::Type::Precommit,
+			height: header.height,
+			round: Round::default(),
+			block_id: Some(block_id),
+			timestamp: Some(header.time),
+			validator_address: validators[0].address,
+			validator_index: ValidatorIndex::try_from(0u32).unwrap(),
+			signature: None,
+			extension: vec![],
+			extension_signature: None,
+		};
+		let sign_bytes = vote.to_signable_vec(header.chain_id.clone()).unwrap();
+		let signatures = self
+			.signers(height)
+			.iter()
+			.zip(&validators)
+			.map(|(pair, validator)| CommitSig::BlockIdFlagCommit {
+				validator_address: validator.address,
+				timestamp: header.time,
+				signature: Signature::try_from(pair.sign(&sign_bytes).0.to_vec()).ok(),
+			})
+			.collect();
+		let commit =
+			Commit { height: header.height, round: Round::default(), block_id, signatures };
+
+		SignedHeader::new(header, commit).unwrap()
+	}
+
+	fn trusted_state(&self, height: u64) -> TrustedState {
+		let next_validators = self.validators_at(height + 1);
+		let next_validators_hash = ValidatorSet::new(next_validators.clone(), None).hash();
+		TrustedState::new(
+			CHAIN_ID.to_string(),
+			height,
+			self.genesis + height,
+			[1u8; 32],
+			self.validators_at(height),
+			next_validators,
+			next_validators_hash.as_bytes().try_into().unwrap(),
+			3600,
+			VerificationOptions::default(),
+		)
+	}
+}
+
+#[async_trait::async_trait]
+impl Client for RotatingChain {
+	async fn latest_height(&self) -> Result<u64, ProverError> {
+		Ok(TARGET)
+	}
+
+	async fn signed_header(&self, height: u64) -> Result<SignedHeader, ProverError> {
+		Ok(self.header(height))
+	}
+
+	async fn validators(&self, height: u64) -> Result<Vec<Validator>, ProverError> {
+		Ok(self.validators_at(height))
+	}
+
+	async fn next_validators(&self, height: u64) -> Result<Vec<Validator>, ProverError> {
+		Ok(self.validators_at(height + 1))
+	}
+
+	async fn chain_id(&self) -> Result<String, ProverError> {
+		Ok(CHAIN_ID.to_string())
+	}
+
+	async fn is_healthy(&self) -> Result<bool, ProverError> {
+		Ok(true)
+	}
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[tokio::test]
+async fn bisects_across_validator_set_rotations() {
+	let chain = RotatingChain::new();
+	let trusted_state = chain.trusted_state(1);
+
+	// Each rotation is crossed at the last header of the outgoing set
+	let proofs = prove_header_update(&chain, &trusted_state, TARGET).await.unwrap();
+	assert_eq!(
+		proofs.iter().map(ConsensusProof::height).collect::<Vec<_>>(),
+		vec![5, 8, 9, 10, 20]
+	);
+	let updated = verify_header_updates(trusted_state.clone(), proofs, now()).unwrap();
+	assert_eq!(updated.trusted_state.height, TARGET);
+	assert_eq!(updated.trusted_state.validators, chain.validators_at(TARGET));
+
+	// The target can't be reached in a single hop
+	let direct = ConsensusProof::new(chain.header(TARGET), Some(chain.validators_at(TARGET + 1)))
+		.with_validators(chain.validators_at(TARGET));
+	assert!(matches!(
+		verify_header_updates(trusted_state, vec![direct], now()),
+		Err(VerificationError::NotEnoughTrust(_))
+	));
+}
+
+#[test]
+fn rejects_update_chains_past_the_cap() {
+	let chain = RotatingChain::new();
+	let proof = ConsensusProof::new(chain.header(2), Some(chain.validators_at(3)));
+	assert!(verify_header_updates(chain.trusted_state(1), vec![proof.clone()], now()).is_ok());
+
+	// The length is checked before any header is verified
+	let result =
+		verify_header_updates(chain.trusted_state(1), vec![proof; MAX_HEADER_UPDATES + 1], now());
+	assert!(matches!(result, Err(VerificationError::Invalid(_))));
+}
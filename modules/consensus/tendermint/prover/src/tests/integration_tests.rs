@@ -412,16 +412,17 @@ mod tests {
 				}
 			}
 
-			let consensus_proof =
+			let consensus_proofs =
 				prove_header_update(&client, &trusted_state, target_height).await?;
+			let consensus_proof = consensus_proofs.last().cloned().ok_or("empty update chain")?;
 			consensus_proof.validate()?;
 
 			let current_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
 			trace!("Attempt {}: Verifying consensus proof for height {}", attempt, target_height);
 
-			match tendermint_verifier::verify_header_update(
+			match tendermint_verifier::verify_header_updates(
 				trusted_state.clone(),
-				consensus_proof.clone(),
+				consensus_proofs,
 				current_time,
 			) {
 				Ok(updated_state) => {
@@ -556,17 +557,18 @@ mod tests {
 				}
 			}
 
-			let consensus_proof =
+			let consensus_proofs =
 				prove_header_update(&client, &trusted_state, target_height).await?;
+			let consensus_proof = consensus_proofs.last().cloned().ok_or("empty update chain")?;
 
 			consensus_proof.validate()?;
 
 			let current_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
 			trace!("Attempt {}: Verifying consensus proof for height {}", attempt, target_height);
 
-			match tendermint_verifier::verify_header_update(
+			match tendermint_verifier::verify_header_updates(
 				trusted_state.clone(),
-				consensus_proof.clone(),
+				consensus_proofs,
 				current_time,
 			) {
 				Ok(updated_state) => {
@@ -661,7 +663,10 @@ mod tests {
 
 		// The proof's next validator set is the set the attack actually targets, so assert
 		// the same guard covers it whenever the proved update carries one.
-		let proof = prove_header_update(&client, &trusted_state, latest_height).await?;
+		let proof = prove_header_update(&client, &trusted_state, latest_height)
+			.await?
+			.pop()
+			.ok_or("empty update chain")?;
 		let mut poisoned_proof = CodecConsensusProof::from(&proof);
 		if let Some(next_validators) = poisoned_proof.next_validators.as_mut() {
 			for validator in next_validators.iter_mut() {
@@ -1 +1,2 @@
+mod bisection;
 pub mod integration_tests;
@@ -14,4 +14,6 @@ pub mod sp_io_verifier;
 pub mod verifier;
 
 pub use sp_io_verifier::{validate_validator_set_hash, SpIoVerifier};
-pub use verifier::{verify_header_update, verify_misbehaviour_header};
+pub use verifier::{
+	verify_header_update, verify_header_updates, verify_misbehaviour_header, MAX_HEADER_UPDATES,
+};
@@ -1,5 +1,5 @@
 use core::time::Duration;
-use prost::alloc::{collections::BTreeSet, format, string::ToString};
+use prost::alloc::{collections::BTreeSet, format, string::ToString, vec::Vec};
 
 use cometbft::{block::Height, chain::Id, trust_threshold::TrustThresholdFraction, Hash, Time};
 use cometbft_light_client_verifier::{
@@ -17,6 +17,11 @@ use tendermint_primitives::{
 
 use crate::sp_io_verifier::validate_validator_set_hash;
 
+/// Most header updates accepted in a single chain, so the cost of verifying an update is bounded.
+/// A prover that needs more hops submits the first ones and continues from there in the next
+/// update.
+pub const MAX_HEADER_UPDATES: usize = 16;
+
 /// Main verification function for header updates
 pub fn verify_header_update(
 	trusted_state: TrustedState,
@@ -42,7 +47,7 @@ pub fn verify_header_update(
 		next_validators_hash,
 	};
 
-	let validators = extract_validators(&trusted_state, &consensus_proof)?;
+	let (validators, signing_validators) = extract_validators(&trusted_state, &consensus_proof)?;
 	let next_validators = consensus_proof
 		.next_validators
 		.as_ref()
@@ -70,7 +75,8 @@ pub fn verify_header_update(
 
 	match result {
 		Verdict::Success => {
-			let updated_state = create_updated_trusted_state(&trusted_state, &consensus_proof)?;
+			let updated_state =
+				create_updated_trusted_state(&trusted_state, &consensus_proof, signing_validators)?;
 			Ok(updated_state)
 		},
 		Verdict::NotEnoughTrust(tally) =>
@@ -79,6 +85,34 @@ pub fn verify_header_update(
 	}
 }
 
+/// Verify a chain of header updates, each one against the trusted state produced by the one
+/// before it. This is how skipping verification is submitted: every hop must be trusted by the
+/// previous one, and the last hop is the target header. Chains longer than
+/// [`MAX_HEADER_UPDATES`] are rejected.
+pub fn verify_header_updates(
+	trusted_state: TrustedState,
+	consensus_proofs: Vec<ConsensusProof>,
+	current_time: u64,
+) -> Result<UpdatedTrustedState, VerificationError> {
+	if consensus_proofs.len() > MAX_HEADER_UPDATES {
+		return Err(VerificationError::Invalid(format!(
+			"{} header updates exceed the maximum of {MAX_HEADER_UPDATES}",
+			consensus_proofs.len()
+		)));
+	}
+
+	let mut updated_state = None;
+	let mut trusted_state = trusted_state;
+	for consensus_proof in consensus_proofs {
+		let updated = verify_header_update(trusted_state, consensus_proof, current_time)?;
+		trusted_state = updated.trusted_state.clone();
+		updated_state = Some(updated);
+	}
+
+	updated_state
+		.ok_or_else(|| VerificationError::Invalid("No header updates provided".to_string()))
+}
+
 /// Verify a header for misbehaviour detection (more relaxed verification)
 pub fn verify_misbehaviour_header(
 	trusted_state: TrustedState,
@@ -87,7 +121,7 @@ pub fn verify_misbehaviour_header(
 ) -> Result<UpdatedTrustedState, VerificationError> {
 	consensus_proof.validate().map_err(|e| VerificationError::Invalid(e))?;
 
-	let validators = extract_validators(&trusted_state, &consensus_proof)?;
+	let (validators, signing_validators) = extract_validators(&trusted_state, &consensus_proof)?;
 	let next_validators = consensus_proof
 		.next_validators
 		.as_ref()
@@ -133,7 +167,8 @@ pub fn verify_misbehaviour_header(
 
 	match result {
 		Verdict::Success => {
-			let updated_state = create_updated_trusted_state(&trusted_state, &consensus_proof)?;
+			let updated_state =
+				create_updated_trusted_state(&trusted_state, &consensus_proof, signing_validators)?;
 			Ok(updated_state)
 		},
 		Verdict::NotEnoughTrust(tally) =>
@@ -144,11 +179,12 @@ pub fn verify_misbehaviour_header(
 
 /// Validates which validator set the signed header references and check next_validators_hash
 /// rotation. Returns the correct ValidatorSet for the header and validates next_validators if
-/// rotation is signaled.
+/// rotation is signaled. Headers that skip past a rotation are signed by a set that is neither
+/// trusted set, that set is taken from the proof and also returned on its own.
 fn extract_validators<'a>(
 	trusted_state: &'a TrustedState,
 	consensus_proof: &'a ConsensusProof,
-) -> Result<ValidatorSet, VerificationError> {
+) -> Result<(ValidatorSet, Option<Vec<cometbft::validator::Info>>), VerificationError> {
 	let header = &consensus_proof.signed_header.header;
 	let current_set = ValidatorSet::new(trusted_state.validators.clone(), None);
 	let next_set = ValidatorSet::new(trusted_state.next_validators.clone(), None);
@@ -158,10 +194,24 @@ fn extract_validators<'a>(
 		validate_validator_set_hash(&current_set, header.validators_hash, false);
 	let next_hash_result = validate_validator_set_hash(&next_set, header.validators_hash, true);
 
-	let validators = if current_hash_result.is_ok() {
-		current_set
+	let (validators, signing_validators) = if current_hash_result.is_ok() {
+		(current_set, None)
 	} else if next_hash_result.is_ok() {
-		next_set
+		(next_set, None)
+	} else if let Some(provided) = consensus_proof.validators.as_ref() {
+		// The light client verifier only accepts a non-adjacent header if enough of the trusted
+		// next validators signed it, so the provided set is safe to use once it hashes to the
+		// header's validators_hash.
+		ensure_unique_addresses(provided)?;
+		let provided_set = ValidatorSet::new(provided.clone(), None);
+		validate_validator_set_hash(&provided_set, header.validators_hash, false).map_err(
+			|_| {
+				VerificationError::Invalid(format!(
+					"Provided validators hash does not match signed_header.validators_hash"
+				))
+			},
+		)?;
+		(provided_set, Some(provided.clone()))
 	} else {
 		return Err(VerificationError::Invalid(format!(
 			"Unknown validator set hash: {:?}",
@@ -191,7 +241,7 @@ fn extract_validators<'a>(
 		}
 	}
 
-	Ok(validators)
+	Ok((validators, signing_validators))
 }
 
 // Helper functions for type conversion
@@ -231,11 +281,13 @@ fn ensure_unique_addresses(
 fn create_updated_trusted_state(
 	old_trusted_state: &TrustedState,
 	consensus_proof: &ConsensusProof,
+	signing_validators: Option<Vec<cometbft::validator::Info>>,
 ) -> Result<UpdatedTrustedState, VerificationError> {
 	let header = &consensus_proof.signed_header.header;
 
-	// Promote next_validators to validators
-	let validators = old_trusted_state.next_validators.clone();
+	// Promote next_validators to validators, unless the header skipped past them
+	let validators =
+		signing_validators.unwrap_or_else(|| old_trusted_state.next_validators.clone());
 
 	// Only a signalled rotation may replace the stored next set, and only with a list
 	// that hashes to the new signed next_validators_hash. When the interval is unchanged
@@ -295,13 +347,13 @@ fn create_updated_trusted_state(
 
 #[cfg(test)]
 mod tests {
-	use super::ensure_unique_addresses;
+	use super::{ensure_unique_addresses, verify_header_updates};
 	use cometbft::{
 		validator::{Info, ProposerPriority},
 		vote::Power,
 		PublicKey,
 	};
-	use tendermint_primitives::account_id_from_public_key;
+	use tendermint_primitives::{account_id_from_public_key, TrustedState};
 
 	// A valid ed25519 public key (RFC 8032 test vector 1).
 	const ED25519_PUBKEY: [u8; 32] = [
@@ -330,4 +382,9 @@ mod tests {
 	fn rejects_duplicate_addresses() {
 		assert!(ensure_unique_addresses(&[validator(), validator()]).is_err());
 	}
+
+	#[test]
+	fn rejects_empty_update_chain() {
+		assert!(verify_header_updates(TrustedState::default(), vec![], 0).is_err());
+	}
 }
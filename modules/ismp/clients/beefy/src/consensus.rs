@@ -16,8 +16,8 @@
 use alloc::{boxed::Box, collections::BTreeMap, format, vec, vec::Vec};
 use beefy_verifier::{error::Error as BeefyError, verify_consensus};
 use beefy_verifier_primitives::{
-	ConsensusMessage, ConsensusState, MmrProof, PROOF_TYPE_NAIVE, PROOF_TYPE_SP1, ParachainProof,
-	Sp1BeefyProof,
+	BlsConsensusMessage, ConsensusMessage, ConsensusState, MmrProof, PROOF_TYPE_BLS,
+	PROOF_TYPE_NAIVE, PROOF_TYPE_SP1, ParachainProof, Sp1BeefyProof,
 };
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
@@ -103,6 +103,14 @@ where
 					&vkey,
 				)?
 			},
+			PROOF_TYPE_BLS => {
+				let bls_proof: BlsConsensusMessage = codec::Decode::decode(&mut &payload[..])
+					.map_err(|e| BeefyError::DecodeBlsProof(format!("{e:?}")))?;
+				beefy_verifier::bls_aggregate::verify_bls_consensus::<SubstrateCrypto>(
+					consensus_state,
+					bls_proof,
+				)?
+			},
 			_ => return Err(BeefyError::UnknownProofType(*proof_type).into()),
 		};
 
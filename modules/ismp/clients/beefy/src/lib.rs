@@ -19,7 +19,7 @@ extern crate alloc;
 extern crate core;
 pub mod consensus;
 
-pub use beefy_verifier_primitives::{PROOF_TYPE_NAIVE, PROOF_TYPE_SP1};
+pub use beefy_verifier_primitives::{PROOF_TYPE_BLS, PROOF_TYPE_NAIVE, PROOF_TYPE_SP1};
 pub use consensus::{BEEFY_CONSENSUS_ID, BeefyConsensusClient};
 
 use polkadot_sdk::*;
@@ -50,7 +50,7 @@ pub trait BeefyClientConfig {
 
 	/// Allowed proof types. Controls which consensus proof formats this client will
 	/// accept. On mainnet set to `&[PROOF_TYPE_SP1]`, on testnets set to
-	/// `&[PROOF_TYPE_NAIVE, PROOF_TYPE_SP1]`. A proof whose type byte is not listed is
-	/// rejected with [`beefy_verifier::error::Error::UnknownProofType`] before verification.
+	/// `&[PROOF_TYPE_NAIVE, PROOF_TYPE_SP1, PROOF_TYPE_BLS]`. A proof whose type byte is not listed
+	/// is rejected with [`beefy_verifier::error::Error::UnknownProofType`] before verification.
 	fn allowed_proof_types() -> &'static [u8];
 }
@@ -6,8 +6,8 @@ extern crate alloc;
 use alloc::{boxed::Box, collections::BTreeMap, vec, vec::Vec};
 pub use bsc_verifier::primitives::{Mainnet, Testnet};
 use bsc_verifier::{
-	ensure_finalized_epoch_consistent,
-	primitives::{compute_epoch, parse_extra, BscClientUpdate},
+	ensure_catch_up_bounded, ensure_finalized_epoch_consistent,
+	primitives::{compute_epoch, parse_extra, BscClientUpdate, BscConsensusProof},
 	verify_bsc_header, Error, NextValidators, VerificationResult,
 };
 use codec::{Decode, Encode};
@@ -79,93 +79,30 @@ impl<
 		trusted_consensus_state: Vec<u8>,
 		proof: Vec<u8>,
 	) -> Result<(Vec<u8>, ismp::consensus::VerifiedCommitments), ismp::error::Error> {
-		let bsc_client_update = BscClientUpdate::decode(&mut &proof[..])
-			.map_err(|_| Error::DecodeBscClientUpdate)?;
+		// A catch-up proof carries several updates, each applied against the state the previous
+		// one produced, so a client that fell behind can cross several epochs in one message.
+		let updates = BscConsensusProof::decode_proof(&proof)
+			.map_err(|_| Error::DecodeBscClientUpdate)?
+			.into_updates();
+		ensure_catch_up_bounded(&updates)?;
 
 		let mut consensus_state = ConsensusState::decode(&mut &trusted_consensus_state[..])
 			.map_err(|_| Error::DecodeConsensusState)?;
-
-		if consensus_state.finalized_height >= bsc_client_update.source_header.number.low_u64() {
-			Err(Error::ExpiredUpdate {
-				current: consensus_state.finalized_height,
-				update: bsc_client_update.source_header.number.low_u64(),
-			})?
-		}
-
 		let epoch_length = Pallet::<T>::epoch_length().ok_or(Error::EpochLengthNotSet)?;
-		if let Some(next_validators) = consensus_state.next_validators.clone() {
-			let attested_number = bsc_client_update.attested_header.number.low_u64();
-			let attested_epoch = compute_epoch(attested_number, epoch_length);
-			let rotation_epoch = compute_epoch(next_validators.rotation_block, epoch_length);
-			// Promote the pending validator set only when the submitted update is in the
-			// specific epoch where that set is scheduled to activate, and the attested
-			// header has reached the recorded `rotation_block`. The previous rule —
-			// "any update whose `attested.number % epoch_length` is past the rotation
-			// midpoint" — promoted the pending set in any later epoch, so an attacker
-			// holding the keys of a stale `next_validators` (e.g. retired or compromised
-			// validators) could submit an update many epochs later, get their set
-			// promoted to `current_validators`, and then have their forged
-			// `source_header`'s `state_root` accepted as a BSC state commitment. Binding
-			// rotation to the recorded `rotation_block`'s epoch prevents that reuse.
-			if attested_epoch == rotation_epoch && attested_number >= next_validators.rotation_block {
-				// During authority set rotation, the source header must be from the same epoch as
-				// the attested header.
-				let source_header_epoch =
-					compute_epoch(bsc_client_update.source_header.number.low_u64(), epoch_length);
-				if source_header_epoch != attested_epoch {
-					Err(Error::SourceHeaderEpochMismatch {
-						attested_epoch,
-						source_epoch: source_header_epoch,
-					})?
-				}
-				consensus_state.current_validators = next_validators.validators;
-				consensus_state.next_validators = None;
-				consensus_state.current_epoch = attested_epoch;
-			}
-		}
 
-		let VerificationResult { hash, finalized_header, next_validators } =
-			verify_bsc_header::<H, C>(
-				&consensus_state.current_validators,
-				bsc_client_update,
-				epoch_length,
-			)?;
+		let commitments = updates
+			.into_iter()
+			.map(|update| apply_update::<H, C>(&mut consensus_state, update, epoch_length))
+			.collect::<Result<Vec<_>, _>>()?;
 
 		let mut state_machine_map: BTreeMap<StateMachineId, Vec<StateCommitmentHeight>> =
 			BTreeMap::new();
-
-		let state_commitment = StateCommitmentHeight {
-			commitment: StateCommitment {
-				timestamp: finalized_header.timestamp,
-				overlay_root: None,
-				state_root: finalized_header.state_root,
-			},
-			height: finalized_header.number.low_u64(),
-		};
-		consensus_state.finalized_hash = hash;
-
-		if let Some(next_validators) = next_validators {
-			consensus_state.next_validators = Some(next_validators);
-		}
-		consensus_state.finalized_height = finalized_header.number.low_u64();
-
-		// Reject any update that would leave `finalized_height` running ahead of the validator set
-		// the client holds — this would permanently strand the client (see
-		// `ensure_finalized_epoch_consistent`). The finalized height may only cross an epoch
-		// boundary via a validator-set-staging (sync) update.
-		ensure_finalized_epoch_consistent(
-			consensus_state.finalized_height,
-			consensus_state.current_epoch,
-			consensus_state.next_validators.is_some(),
-			epoch_length,
-		)?;
-
 		state_machine_map.insert(
 			StateMachineId {
 				state_id: StateMachine::Evm(consensus_state.chain_id),
 				consensus_state_id,
 			},
-			vec![state_commitment],
+			commitments,
 		);
 
 		Ok((consensus_state.encode(), state_machine_map))
@@ -246,3 +183,82 @@ impl<
 		}
 	}
 }
+
+/// Apply a single update to `consensus_state`, returning the state commitment it finalizes.
+fn apply_update<H: IsmpHost, C: bsc_verifier::primitives::Config>(
+	consensus_state: &mut ConsensusState,
+	bsc_client_update: BscClientUpdate,
+	epoch_length: u64,
+) -> Result<StateCommitmentHeight, Error> {
+	if consensus_state.finalized_height >= bsc_client_update.source_header.number.low_u64() {
+		Err(Error::ExpiredUpdate {
+			current: consensus_state.finalized_height,
+			update: bsc_client_update.source_header.number.low_u64(),
+		})?
+	}
+
+	if let Some(next_validators) = consensus_state.next_validators.clone() {
+		let attested_number = bsc_client_update.attested_header.number.low_u64();
+		let attested_epoch = compute_epoch(attested_number, epoch_length);
+		let rotation_epoch = compute_epoch(next_validators.rotation_block, epoch_length);
+		// Promote the pending validator set only when the submitted update is in the
+		// specific epoch where that set is scheduled to activate, and the attested
+		// header has reached the recorded `rotation_block`. The previous rule —
+		// "any update whose `attested.number % epoch_length` is past the rotation
+		// midpoint" — promoted the pending set in any later epoch, so an attacker
+		// holding the keys of a stale `next_validators` (e.g. retired or compromised
+		// validators) could submit an update many epochs later, get their set
+		// promoted to `current_validators`, and then have their forged
+		// `source_header`'s `state_root` accepted as a BSC state commitment. Binding
+		// rotation to the recorded `rotation_block`'s epoch prevents that reuse.
+		if attested_epoch == rotation_epoch && attested_number >= next_validators.rotation_block {
+			// During authority set rotation, the source header must be from the same epoch as
+			// the attested header.
+			let source_header_epoch =
+				compute_epoch(bsc_client_update.source_header.number.low_u64(), epoch_length);
+			if source_header_epoch != attested_epoch {
+				Err(Error::SourceHeaderEpochMismatch {
+					attested_epoch,
+					source_epoch: source_header_epoch,
+				})?
+			}
+			consensus_state.current_validators = next_validators.validators;
+			consensus_state.next_validators = None;
+			consensus_state.current_epoch = attested_epoch;
+		}
+	}
+
+	let VerificationResult { hash, finalized_header, next_validators } = verify_bsc_header::<H, C>(
+		&consensus_state.current_validators,
+		bsc_client_update,
+		epoch_length,
+	)?;
+
+	let state_commitment = StateCommitmentHeight {
+		commitment: StateCommitment {
+			timestamp: finalized_header.timestamp,
+			overlay_root: None,
+			state_root: finalized_header.state_root,
+		},
+		height: finalized_header.number.low_u64(),
+	};
+	consensus_state.finalized_hash = hash;
+
+	if let Some(next_validators) = next_validators {
+		consensus_state.next_validators = Some(next_validators);
+	}
+	consensus_state.finalized_height = finalized_header.number.low_u64();
+
+	// Reject any update that would leave `finalized_height` running ahead of the validator set
+	// the client holds — this would permanently strand the client (see
+	// `ensure_finalized_epoch_consistent`). The finalized height may only cross an epoch
+	// boundary via a validator-set-staging (sync) update.
+	ensure_finalized_epoch_consistent(
+		consensus_state.finalized_height,
+		consensus_state.current_epoch,
+		consensus_state.next_validators.is_some(),
+		epoch_length,
+	)?;
+
+	Ok(state_commitment)
+}
@@ -268,105 +268,7 @@ where
 		proof_1: Vec<u8>,
 		proof_2: Vec<u8>,
 	) -> Result<(), Error> {
-		// decode the consensus state
-		let consensus_state: ConsensusState =
-			codec::Decode::decode(&mut &trusted_consensus_state[..])
-				.map_err(|e| GrandpaError::DecodeConsensusState(format!("{e:?}")))?;
-
-		let first_proof: FinalityProof<SubstrateHeader> = codec::Decode::decode(&mut &proof_1[..])
-			.map_err(|e| GrandpaError::DecodeFinalityProof(format!("{e:?}")))?;
-
-		let second_proof: FinalityProof<SubstrateHeader> = codec::Decode::decode(&mut &proof_2[..])
-			.map_err(|e| GrandpaError::DecodeFinalityProof(format!("{e:?}")))?;
-
-		if first_proof.block == second_proof.block {
-			return Err(GrandpaError::FraudProofsSameBlock.into());
-		}
-
-		let first_headers = AncestryChain::<SubstrateHeader>::new(&first_proof.unknown_headers);
-		let first_target = first_proof
-			.unknown_headers
-			.iter()
-			.max_by_key(|h| *h.number())
-			.ok_or(GrandpaError::UnknownHeadersEmpty)?;
-
-		let second_headers = AncestryChain::<SubstrateHeader>::new(&second_proof.unknown_headers);
-		let second_target = second_proof
-			.unknown_headers
-			.iter()
-			.max_by_key(|h| *h.number())
-			.ok_or(GrandpaError::UnknownHeadersEmpty)?;
-
-		if first_target.hash() != first_proof.block || second_target.hash() != second_proof.block {
-			return Err(GrandpaError::FraudProofsDifferentChain.into());
-		}
-
-		let first_base = first_proof
-			.unknown_headers
-			.iter()
-			.min_by_key(|h| *h.number())
-			.ok_or(GrandpaError::UnknownHeadersEmpty)?;
-		let first_chain = first_headers
-			.ancestry(first_base.hash(), first_target.hash())
-			.map_err(|_| GrandpaError::InvalidAncestry)?;
-
-		let second_base = second_proof
-			.unknown_headers
-			.iter()
-			.min_by_key(|h| *h.number())
-			.ok_or(GrandpaError::UnknownHeadersEmpty)?;
-		let second_chain = second_headers
-			.ancestry(second_base.hash(), second_target.hash())
-			.map_err(|_| GrandpaError::InvalidAncestry)?;
-
-		let first_parent = first_base.parent_hash();
-		let second_parent = second_base.parent_hash();
-
-		if first_parent != second_parent {
-			return Err(GrandpaError::FraudProofsDifferentAncestor.into());
-		}
-
-		// Equivocation means two finalized blocks on competing branches. If one
-		// target appears in the other's ancestry then they share a canonical
-		// chain and this is just ordinary finality moving forward.
-		if first_chain.contains(&second_proof.block) ||
-			second_chain.contains(&first_proof.block)
-		{
-			return Err(GrandpaError::FraudProofsSameBranch.into());
-		}
-
-		let first_justification =
-			GrandpaJustification::<SubstrateHeader>::decode(&mut &first_proof.justification[..])
-				.map_err(|e| GrandpaError::DecodeJustification(format!("{e:?}")))?;
-
-		let second_justification =
-			GrandpaJustification::<SubstrateHeader>::decode(&mut &second_proof.justification[..])
-				.map_err(|e| GrandpaError::DecodeJustification(format!("{e:?}")))?;
-
-		if first_proof.block != first_justification.commit.target_hash ||
-			second_proof.block != second_justification.commit.target_hash
-		{
-			Err(GrandpaError::JustificationTargetMismatch)?
-		}
-
-		if first_justification.commit.target_hash != consensus_state.latest_hash &&
-			second_justification.commit.target_hash != consensus_state.latest_hash
-		{
-			Err(GrandpaError::JustificationConsensusMismatch)?
-		}
-
-		let first_valid = first_justification
-			.verify(consensus_state.current_set_id, &consensus_state.current_authorities)
-			.is_ok();
-		let second_valid = second_justification
-			.verify(consensus_state.current_set_id, &consensus_state.current_authorities)
-			.is_ok();
-
-		if !first_valid || !second_valid {
-			Err(GrandpaError::InvalidJustification)?
-		}
-
-		Ok(())
+		verify_fraud_proof(trusted_consensus_state, proof_1, proof_2)
 	}
 
 	fn state_machine(&self, id: StateMachine) -> Result<Box<dyn StateMachineClient>, Error> {
@@ -407,6 +309,112 @@ pub(crate) fn envelope_matches_state_machine(
 	)
 }
 
+/// Verify that two finality proofs finalize blocks on competing branches under the trusted
+/// authority set. This doesn't touch runtime storage, so off-chain tooling can check a fraud
+/// proof before submitting it.
+pub fn verify_fraud_proof(
+	trusted_consensus_state: Vec<u8>,
+	proof_1: Vec<u8>,
+	proof_2: Vec<u8>,
+) -> Result<(), Error> {
+	// decode the consensus state
+	let consensus_state: ConsensusState = codec::Decode::decode(&mut &trusted_consensus_state[..])
+		.map_err(|e| GrandpaError::DecodeConsensusState(format!("{e:?}")))?;
+
+	let first_proof: FinalityProof<SubstrateHeader> = codec::Decode::decode(&mut &proof_1[..])
+		.map_err(|e| GrandpaError::DecodeFinalityProof(format!("{e:?}")))?;
+
+	let second_proof: FinalityProof<SubstrateHeader> = codec::Decode::decode(&mut &proof_2[..])
+		.map_err(|e| GrandpaError::DecodeFinalityProof(format!("{e:?}")))?;
+
+	if first_proof.block == second_proof.block {
+		return Err(GrandpaError::FraudProofsSameBlock.into());
+	}
+
+	let first_headers = AncestryChain::<SubstrateHeader>::new(&first_proof.unknown_headers);
+	let first_target = first_proof
+		.unknown_headers
+		.iter()
+		.max_by_key(|h| *h.number())
+		.ok_or(GrandpaError::UnknownHeadersEmpty)?;
+
+	let second_headers = AncestryChain::<SubstrateHeader>::new(&second_proof.unknown_headers);
+	let second_target = second_proof
+		.unknown_headers
+		.iter()
+		.max_by_key(|h| *h.number())
+		.ok_or(GrandpaError::UnknownHeadersEmpty)?;
+
+	if first_target.hash() != first_proof.block || second_target.hash() != second_proof.block {
+		return Err(GrandpaError::FraudProofsDifferentChain.into());
+	}
+
+	let first_base = first_proof
+		.unknown_headers
+		.iter()
+		.min_by_key(|h| *h.number())
+		.ok_or(GrandpaError::UnknownHeadersEmpty)?;
+	let first_chain = first_headers
+		.ancestry(first_base.hash(), first_target.hash())
+		.map_err(|_| GrandpaError::InvalidAncestry)?;
+
+	let second_base = second_proof
+		.unknown_headers
+		.iter()
+		.min_by_key(|h| *h.number())
+		.ok_or(GrandpaError::UnknownHeadersEmpty)?;
+	let second_chain = second_headers
+		.ancestry(second_base.hash(), second_target.hash())
+		.map_err(|_| GrandpaError::InvalidAncestry)?;
+
+	let first_parent = first_base.parent_hash();
+	let second_parent = second_base.parent_hash();
+
+	if first_parent != second_parent {
+		return Err(GrandpaError::FraudProofsDifferentAncestor.into());
+	}
+
+	// Equivocation means two finalized blocks on competing branches. If one
+	// target appears in the other's ancestry then they share a canonical
+	// chain and this is just ordinary finality moving forward.
+	if first_chain.contains(&second_proof.block) || second_chain.contains(&first_proof.block) {
+		return Err(GrandpaError::FraudProofsSameBranch.into());
+	}
+
+	let first_justification =
+		GrandpaJustification::<SubstrateHeader>::decode(&mut &first_proof.justification[..])
+			.map_err(|e| GrandpaError::DecodeJustification(format!("{e:?}")))?;
+
+	let second_justification =
+		GrandpaJustification::<SubstrateHeader>::decode(&mut &second_proof.justification[..])
+			.map_err(|e| GrandpaError::DecodeJustification(format!("{e:?}")))?;
+
+	if first_proof.block != first_justification.commit.target_hash ||
+		second_proof.block != second_justification.commit.target_hash
+	{
+		Err(GrandpaError::JustificationTargetMismatch)?
+	}
+
+	if first_justification.commit.target_hash != consensus_state.latest_hash &&
+		second_justification.commit.target_hash != consensus_state.latest_hash
+	{
+		Err(GrandpaError::JustificationConsensusMismatch)?
+	}
+
+	let first_valid = first_justification
+		.verify(consensus_state.current_set_id, &consensus_state.current_authorities)
+		.is_ok();
+	let second_valid = second_justification
+		.verify(consensus_state.current_set_id, &consensus_state.current_authorities)
+		.is_ok();
+
+	if !first_valid || !second_valid {
+		Err(GrandpaError::InvalidJustification)?
+	}
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod envelope_binding_tests {
 	use super::*;
@@ -43,8 +43,10 @@ use pallet_ismp_host_executive::Config as HostExecutiveConfig;
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use tendermint_ics23_primitives::ICS23HostFunctions;
-use tendermint_primitives::{CodecConsensusProof, CodecTrustedState, TrustedState};
-use tendermint_verifier::verify_header_update;
+use tendermint_primitives::{
+	CodecTrustedState, ConsensusProof, TrustedState, VersionedConsensusProof,
+};
+use tendermint_verifier::{verify_header_updates, MAX_HEADER_UPDATES};
 
 /// Consensus client ID for Polygon
 pub const POLYGON_CONSENSUS_CLIENT_ID: ConsensusClientId = *b"PLGN";
@@ -58,9 +60,30 @@ pub const POLYGON_TESTNET_CHAIN_ID: u32 = 80002;
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct PolygonConsensusUpdate {
 	/// Serialized Tendermint light client update (signed header, validator set, etc.)
-	pub tendermint_proof: CodecConsensusProof,
+	pub tendermint_proof: VersionedConsensusProof,
 	/// Milestone update
 	pub milestone_update: Option<MilestoneUpdate>,
+	/// Intermediate headers between the trusted state and `tendermint_proof`, verified in order
+	/// before it. Empty unless the update skips past validator set rotations.
+	pub ancestry: Vec<VersionedConsensusProof>,
+}
+
+impl PolygonConsensusUpdate {
+	/// Convert the intermediate headers of this update, rejecting more than the verifier accepts
+	/// before converting any of them
+	pub fn ancestry(&self) -> Result<Vec<ConsensusProof>, PolygonError> {
+		if self.ancestry.len() >= MAX_HEADER_UPDATES {
+			Err(PolygonError::VerifyHeaderUpdate(alloc::format!(
+				"{} intermediate headers exceed the maximum of {}",
+				self.ancestry.len(),
+				MAX_HEADER_UPDATES - 1
+			)))?
+		}
+		self.ancestry
+			.iter()
+			.map(|proof| proof.to_consensus_proof().map_err(PolygonError::ConvertTendermintProof))
+			.collect()
+	}
 }
 
 /// Milestone update containing EVM header and proof data
@@ -242,7 +265,9 @@ impl<H: IsmpHost + Send + Sync + Default + 'static, T: HostExecutiveConfig> Cons
 
 		let time = host.timestamp().as_secs();
 
-		let updated_state = verify_header_update(trusted_state, consensus_proof.clone(), time)
+		let mut consensus_proofs = polygon_consensus_update.ancestry()?;
+		consensus_proofs.push(consensus_proof.clone());
+		let updated_state = verify_header_updates(trusted_state, consensus_proofs, time)
 			.map_err(|e| PolygonError::VerifyHeaderUpdate(e.to_string()))?;
 
 		let mut state_machine_map: BTreeMap<StateMachineId, Vec<StateCommitmentHeight>> =
@@ -388,9 +413,14 @@ impl<H: IsmpHost + Send + Sync + Default + 'static, T: HostExecutiveConfig> Cons
 
 		let time = host.timestamp().as_secs();
 
-		verify_header_update(trusted_state.clone(), consensus_proof_1, time)
+		let mut consensus_proofs_1 = update_1.ancestry()?;
+		consensus_proofs_1.push(consensus_proof_1);
+		let mut consensus_proofs_2 = update_2.ancestry()?;
+		consensus_proofs_2.push(consensus_proof_2);
+
+		verify_header_updates(trusted_state.clone(), consensus_proofs_1, time)
 			.map_err(|e| PolygonError::VerifyHeaderUpdate(e.to_string()))?;
-		verify_header_update(trusted_state, consensus_proof_2, time)
+		verify_header_updates(trusted_state, consensus_proofs_2, time)
 			.map_err(|e| PolygonError::VerifyHeaderUpdate(e.to_string()))?;
 
 		Ok(())
@@ -91,11 +91,11 @@ impl<
 			.map_err(|_| SyncCommitteeError::DecodeConsensusState)?;
 
 		let new_light_client_state =
-			sync_committee_verifier::verify_sync_committee_attestation::<C>(
+			sync_committee_verifier::verify_sync_committee_attestation_with_spec::<C>(
+				&consensus_state.network_spec::<C>(),
 				consensus_state.light_client_state,
 				consensus_update.clone(),
-			)
-			?;
+			)?;
 
 		let mut state_machine_map: BTreeMap<StateMachineId, Vec<StateCommitmentHeight>> =
 			BTreeMap::new();
@@ -132,6 +132,7 @@ impl<
 				.map_err(|_| SyncCommitteeError::ConvertLightClientState)?,
 			l2_consensus: consensus_state.l2_consensus,
 			chain_id: consensus_state.chain_id,
+			network_spec: consensus_state.network_spec,
 		};
 
 		Ok((new_consensus_state.encode(), state_machine_map))
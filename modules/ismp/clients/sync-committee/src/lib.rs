@@ -31,4 +31,4 @@ pub mod types;
 pub use beacon_client::*;
 pub use sync_committee_verifier::error::Error;
 
-pub use sync_committee_primitives::constants;
+pub use sync_committee_primitives::{constants, spec};
@@ -23,9 +23,10 @@ pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 	use ismp::{
-		consensus::StateMachineId,
+		consensus::{ConsensusStateId, StateMachineId},
 		host::{IsmpHost, StateMachine},
 	};
+	use sync_committee_primitives::spec::{Fork, NetworkSpec};
 
 	use sp_core::H256;
 
@@ -55,6 +56,12 @@ pub mod pallet {
 		ErrorDecodingConsensusState,
 		/// Error storing consensus state
 		ErrorStoringConsensusState,
+		/// The network spec has unordered forks or duplicate fork versions
+		InvalidNetworkSpec,
+		/// The fork is already active, or conflicts with the fork schedule
+		InvalidFork,
+		/// The consensus state has no network spec to schedule the fork in
+		NetworkSpecNotSet,
 	}
 
 	/// Additional L2s added after the consensus client has been initialized
@@ -78,22 +85,13 @@ pub mod pallet {
 		) -> DispatchResult {
 			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
 
-			let host = <T as Config<I>>::IsmpHost::default();
 			let StateMachineId { consensus_state_id, state_id: state_machine } = state_machine_id;
-
-			let encoded_consensus_state = host
-				.consensus_state(consensus_state_id)
-				.map_err(|_| Error::<T, I>::ErrorFetchingConsensusState)?;
-			let mut consensus_state: ConsensusState =
-				codec::Decode::decode(&mut &encoded_consensus_state[..])
-					.map_err(|_| Error::<T, I>::ErrorDecodingConsensusState)?;
-
-			consensus_state.l2_consensus.insert(state_machine, l2_consensus);
+			Self::mutate_consensus_state(consensus_state_id, |consensus_state| {
+				consensus_state.l2_consensus.insert(state_machine, l2_consensus);
+				Ok(())
+			})?;
 			SupportedStatemachines::<T, I>::insert(state_machine_id.state_id, true);
 
-			let encoded_consensus_state = consensus_state.encode();
-			host.store_consensus_state(consensus_state_id, encoded_consensus_state)
-				.map_err(|_| Error::<T, I>::ErrorStoringConsensusState)?;
 			Ok(())
 		}
 
@@ -108,5 +106,66 @@ pub mod pallet {
 			SupportedStatemachines::<T, I>::insert(state_machine_id.state_id, true);
 			Ok(())
 		}
+
+		/// Replace the genesis and fork schedule the consensus state is verified against
+		#[pallet::call_index(2)]
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_network_spec(
+			origin: OriginFor<T>,
+			consensus_state_id: ConsensusStateId,
+			network_spec: NetworkSpec,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+			network_spec.validate().map_err(|_| Error::<T, I>::InvalidNetworkSpec)?;
+
+			Self::mutate_consensus_state(consensus_state_id, |consensus_state| {
+				consensus_state.network_spec = Some(network_spec);
+				Ok(())
+			})
+		}
+
+		/// Schedule an upcoming fork, or reschedule a pending one with the same version
+		#[pallet::call_index(3)]
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().reads_writes(1, 1))]
+		pub fn schedule_fork(
+			origin: OriginFor<T>,
+			consensus_state_id: ConsensusStateId,
+			fork: Fork,
+		) -> DispatchResult {
+			<T as Config<I>>::AdminOrigin::ensure_origin(origin)?;
+
+			Self::mutate_consensus_state(consensus_state_id, |consensus_state| {
+				let current_epoch = consensus_state.light_client_state.latest_finalized_epoch;
+				consensus_state
+					.network_spec
+					.as_mut()
+					.ok_or(Error::<T, I>::NetworkSpecNotSet)?
+					.schedule_fork(fork, current_epoch)
+					.map_err(|_| Error::<T, I>::InvalidFork)?;
+				Ok(())
+			})
+		}
+	}
+
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
+		fn mutate_consensus_state(
+			consensus_state_id: ConsensusStateId,
+			f: impl FnOnce(&mut ConsensusState) -> DispatchResult,
+		) -> DispatchResult {
+			let host = <T as Config<I>>::IsmpHost::default();
+			let encoded_consensus_state = host
+				.consensus_state(consensus_state_id)
+				.map_err(|_| Error::<T, I>::ErrorFetchingConsensusState)?;
+			let mut consensus_state: ConsensusState =
+				codec::Decode::decode(&mut &encoded_consensus_state[..])
+					.map_err(|_| Error::<T, I>::ErrorDecodingConsensusState)?;
+
+			f(&mut consensus_state)?;
+
+			let encoded_consensus_state = consensus_state.encode();
+			host.store_consensus_state(consensus_state_id, encoded_consensus_state)
+				.map_err(|_| Error::<T, I>::ErrorStoringConsensusState)?;
+			Ok(())
+		}
 	}
 }
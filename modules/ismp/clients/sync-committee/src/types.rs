@@ -16,14 +16,45 @@ use alloc::{collections::BTreeMap, vec::Vec};
 use codec::{Decode, DecodeWithMemTracking, Encode};
 use ismp::host::StateMachine;
 use primitive_types::H160;
-use sync_committee_primitives::types::{VerifierState, VerifierStateUpdate};
+use sync_committee_primitives::{
+	constants::Config,
+	spec::NetworkSpec,
+	types::{VerifierState, VerifierStateUpdate},
+};
 
-#[derive(Debug, Encode, Decode, Clone)]
+#[derive(Debug, Encode, Clone)]
 pub struct ConsensusState {
 	pub frozen_height: Option<u64>,
 	pub light_client_state: VerifierState,
 	pub l2_consensus: BTreeMap<StateMachine, L2Consensus>,
 	pub chain_id: u32,
+	/// Genesis and fork schedule of the network, falls back to the spec compiled into the
+	/// client's [`Config`] when absent.
+	pub network_spec: Option<NetworkSpec>,
+}
+
+impl ConsensusState {
+	/// The network spec this consensus state is verified against
+	pub fn network_spec<C: Config>(&self) -> NetworkSpec {
+		self.network_spec.clone().unwrap_or_else(NetworkSpec::from_config::<C>)
+	}
+}
+
+impl Decode for ConsensusState {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let frozen_height = Decode::decode(input)?;
+		let light_client_state = Decode::decode(input)?;
+		let l2_consensus = Decode::decode(input)?;
+		let chain_id = Decode::decode(input)?;
+		// Consensus states created before network specs were stored end here. They are decoded
+		// from the byte slice the host stores them as, which always knows its remaining length.
+		let network_spec = match input.remaining_len()? {
+			Some(0) => None,
+			_ => Decode::decode(input)?,
+		};
+
+		Ok(Self { frozen_height, light_client_state, l2_consensus, chain_id, network_spec })
+	}
 }
 
 #[derive(Encode, Decode)]
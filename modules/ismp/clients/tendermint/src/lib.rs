@@ -24,8 +24,10 @@ use ismp::{
 };
 
 use pallet_ismp_host_executive::Config as HostExecutiveConfig;
-use tendermint_primitives::{CodecConsensusProof, CodecTrustedState, TrustedState};
-use tendermint_verifier::verify_header_update;
+use tendermint_primitives::{
+	CodecTrustedState, ConsensusProof, TrustedState, VersionedConsensusProof,
+};
+use tendermint_verifier::{verify_header_updates, MAX_HEADER_UPDATES};
 
 /// Default consensus client ID for Tendermint
 pub const DEFAULT_TENDERMINT_CONSENSUS_CLIENT_ID: ConsensusClientId = *b"TNDR";
@@ -34,7 +36,28 @@ pub const DEFAULT_TENDERMINT_CONSENSUS_CLIENT_ID: ConsensusClientId = *b"TNDR";
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct TendermintConsensusUpdate {
 	/// Serialized Tendermint light client update (signed header, validator set, etc.)
-	pub tendermint_proof: CodecConsensusProof,
+	pub tendermint_proof: VersionedConsensusProof,
+	/// Intermediate headers between the trusted state and `tendermint_proof`, verified in order
+	/// before it. Empty unless the update skips past validator set rotations.
+	pub ancestry: Vec<VersionedConsensusProof>,
+}
+
+impl TendermintConsensusUpdate {
+	/// Convert the intermediate headers of this update, rejecting more than the verifier accepts
+	/// before converting any of them
+	pub fn ancestry(&self) -> Result<Vec<ConsensusProof>, Error> {
+		if self.ancestry.len() >= MAX_HEADER_UPDATES {
+			Err(Error::Custom(alloc::format!(
+				"{} intermediate headers exceed the maximum of {}",
+				self.ancestry.len(),
+				MAX_HEADER_UPDATES - 1
+			)))?
+		}
+		self.ancestry
+			.iter()
+			.map(|proof| proof.to_consensus_proof().map_err(Error::Custom))
+			.collect()
+	}
 }
 
 /// The trusted consensus state for Tendermint
@@ -95,7 +118,9 @@ impl<
 
 		let time = host.timestamp().as_secs();
 
-		let updated_state = verify_header_update(trusted_state, consensus_proof.clone(), time)
+		let mut consensus_proofs = tendermint_consensus_update.ancestry()?;
+		consensus_proofs.push(consensus_proof.clone());
+		let updated_state = verify_header_updates(trusted_state, consensus_proofs, time)
 			.map_err(|e| ismp::error::Error::Custom(e.to_string()))?;
 
 		let mut state_machine_map: BTreeMap<StateMachineId, Vec<StateCommitmentHeight>> =
@@ -184,9 +209,14 @@ impl<
 
 		let time = host.timestamp().as_secs();
 
-		verify_header_update(trusted_state.clone(), consensus_proof_1, time)
+		let mut consensus_proofs_1 = update_1.ancestry()?;
+		consensus_proofs_1.push(consensus_proof_1);
+		let mut consensus_proofs_2 = update_2.ancestry()?;
+		consensus_proofs_2.push(consensus_proof_2);
+
+		verify_header_updates(trusted_state.clone(), consensus_proofs_1, time)
 			.map_err(|e| Error::Custom(e.to_string()))?;
-		verify_header_update(trusted_state, consensus_proof_2, time)
+		verify_header_updates(trusted_state, consensus_proofs_2, time)
 			.map_err(|e| Error::Custom(e.to_string()))?;
 
 		Ok(())
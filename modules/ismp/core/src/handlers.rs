@@ -96,6 +96,7 @@ where
 		Message::Request(req) => request::handle(host, req),
 		Message::Response(resp) => response::handle(host, resp),
 		Message::Timeout(timeout) => timeout::handle(host, timeout),
+		Message::AggregatedRequest(req) => request::handle_aggregated(host, req),
 	}
 }
 
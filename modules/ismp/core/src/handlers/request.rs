@@ -20,8 +20,8 @@ use crate::{
 	events::{Event, RequestResponseHandled},
 	handlers::{validate_state_machine, MessageResult},
 	host::{IsmpHost, StateMachine},
-	messaging::{dedup_requests, hash_request, RequestMessage},
-	router::Request,
+	messaging::{dedup_requests, hash_request, AggregatedRequestMessage, Proof, RequestMessage},
+	router::{PostRequest, Request},
 };
 use alloc::vec::Vec;
 use sp_weights::Weight;
@@ -35,7 +35,82 @@ where
 		Err(Error::EmptyBatch)?
 	}
 
-	let state_machine = validate_state_machine(host, msg.proof.height)?;
+	// Reject duplicate requests within the batch. Wire format is `Vec`,
+	// so this is the line of defence against an attacker padding a
+	// batch with identical requests.
+	let wrapped: Vec<Request> = msg.requests.iter().cloned().map(Request::Post).collect();
+	dedup_requests::<H>(&wrapped)?;
+
+	for req in wrapped.iter() {
+		ensure_dispatchable(host, req)?;
+	}
+	verify_batch(host, &msg.requests, &msg.proof)?;
+
+	Ok(dispatch(host, msg.requests, msg.signer))
+}
+
+/// Validate and verify every batch in an aggregated request message, each against its own state
+/// machine height, then dispatch all the requests to their modules.
+///
+/// The challenge period, frozen client and proxy checks are performed independently for every
+/// proof height, so a single batch proven at a height still within its challenge period fails the
+/// entire message. Requests that were already received or have timed out are skipped rather than
+/// failing the message: an aggregated backlog spans many heights, and one of its requests being
+/// delivered or timed out in the meantime shouldn't hold back the rest.
+pub fn handle_aggregated<H>(
+	host: &H,
+	msg: AggregatedRequestMessage,
+) -> Result<MessageResult, anyhow::Error>
+where
+	H: IsmpHost,
+{
+	if msg.batches.is_empty() || msg.batches.iter().any(|batch| batch.requests.is_empty()) {
+		Err(Error::EmptyBatch)?
+	}
+
+	// Duplicates are rejected across all batches, a request proven at two different
+	// heights must not be dispatched twice.
+	let requests = msg.requests();
+	let wrapped: Vec<Request> = requests.iter().cloned().map(Request::Post).collect();
+	dedup_requests::<H>(&wrapped)?;
+
+	for batch in msg.batches.iter() {
+		verify_batch(host, &batch.requests, &batch.proof)?;
+	}
+
+	let requests = requests
+		.into_iter()
+		.filter(|post| ensure_dispatchable(host, &Request::Post(post.clone())).is_ok())
+		.collect();
+
+	Ok(dispatch(host, requests, msg.signer))
+}
+
+/// Requests that already have a receipt or have timed out can't be dispatched
+fn ensure_dispatchable<H>(host: &H, req: &Request) -> Result<(), Error>
+where
+	H: IsmpHost,
+{
+	// If a receipt exists for any request then it's a duplicate and it is not dispatched
+	if host.request_receipt(req).is_some() {
+		Err(Error::DuplicateRequest { meta: req.clone().into() })?
+	}
+
+	// can't dispatch timed out requests
+	if req.timed_out(host.timestamp()) {
+		Err(Error::RequestTimeout { meta: req.clone().into() })?
+	}
+
+	Ok(())
+}
+
+/// Checks the destination and proxy rules for a batch of requests and verifies their membership
+/// proof against the state commitment at the proof height.
+fn verify_batch<H>(host: &H, requests: &[PostRequest], proof: &Proof) -> Result<(), anyhow::Error>
+where
+	H: IsmpHost,
+{
+	let state_machine = validate_state_machine(host, proof.height)?;
 	let consensus_clients = host.consensus_clients();
 	let check_state_machine_client = |state_machine: StateMachine| {
 		consensus_clients
@@ -44,26 +119,8 @@ where
 			.is_none()
 	};
 
-	let router = host.ismp_router();
-
-	// Reject duplicate requests within the batch. Wire format is `Vec`,
-	// so this is the line of defence against an attacker padding a
-	// batch with identical requests.
-	let wrapped: Vec<Request> = msg.requests.iter().cloned().map(Request::Post).collect();
-	dedup_requests::<H>(&wrapped)?;
-
-	for req in msg.requests.iter() {
+	for req in requests.iter() {
 		let req = Request::Post(req.clone());
-		// If a receipt exists for any request then it's a duplicate and it is not dispatched
-		if host.request_receipt(&req).is_some() {
-			Err(Error::DuplicateRequest { meta: req.clone().into() })?
-		}
-
-		// can't dispatch timed out requests
-		if req.timed_out(host.timestamp()) {
-			Err(Error::RequestTimeout { meta: req.clone().into() })?
-		}
-
 		// either the host is a router and can accept requests on behalf of any chain
 		// or the request must be intended for this chain
 		if req.dest_chain() != host.host_state_machine() && !host.is_router() {
@@ -74,42 +131,49 @@ where
 
 		// in order to allow proxies, the host must configure the given state machine
 		// as it's proxy and must not have a state machine client for the source chain
-		let allow_proxy = host.is_allowed_proxy(&msg.proof.height.id.state_id) &&
+		let allow_proxy = host.is_allowed_proxy(&proof.height.id.state_id) &&
 			check_state_machine_client(source_chain);
 
 		// check if the request is allowed to be proxied
-		if source_chain != msg.proof.height.id.state_id && !allow_proxy {
+		if source_chain != proof.height.id.state_id && !allow_proxy {
 			Err(Error::RequestProxyProhibited { meta: req.clone().into() })?
 		}
 	}
 
 	// Verify membership proof
-	let state = host.state_machine_commitment(msg.proof.height)?;
-	let commitments = msg
-		.requests
+	let state = host.state_machine_commitment(proof.height)?;
+	let commitments = requests
 		.iter()
 		.map(|post| hash_request::<H>(&Request::Post(post.clone())))
 		.collect();
-	state_machine.verify_membership(host, commitments, state, &msg.proof)?;
+	state_machine.verify_membership(host, commitments, state, proof)?;
 
+	Ok(())
+}
+
+/// Dispatch verified requests to their destination modules
+fn dispatch<H>(host: &H, requests: Vec<PostRequest>, signer: Vec<u8>) -> MessageResult
+where
+	H: IsmpHost,
+{
+	let router = host.ismp_router();
 	let mut total_weights = Weight::zero();
-	let result = msg
-		.requests
+	let result = requests
 		.into_iter()
 		.map(|request| {
 			let wrapped_req = Request::Post(request.clone());
 			let mut lambda = || {
 				let cb = router.module_for_id(request.to.clone())?;
-				// Re-check the receipt right before dispatch. The up-front pass above
-				// runs before any callback executes; a prior request's on_accept in
-				// this same batch could have stored a receipt for this request
-				// (directly or by re-entering the handler), and we must not invoke
-				// on_accept a second time.
+				// Re-check the receipt right before dispatch. The receipt checks made
+				// before dispatch run before any callback executes; a prior request's
+				// on_accept in this same batch could have stored a receipt for this
+				// request (directly or by re-entering the handler), and we must not
+				// invoke on_accept a second time.
 				if host.request_receipt(&wrapped_req).is_some() {
 					Err(Error::DuplicateRequest { meta: wrapped_req.clone().into() })?
 				}
 				// Store request receipt to prevent reentrancy attack
-				let signer = host.store_request_receipt(&wrapped_req, &msg.signer)?;
+				let signer = host.store_request_receipt(&wrapped_req, &signer)?;
 				let res = cb.on_accept(request.clone()).map(|weight| {
 					total_weights.saturating_accrue(weight);
 
@@ -131,5 +195,5 @@ where
 		})
 		.collect::<Vec<_>>();
 
-	Ok(MessageResult::Request { events: result, weight: total_weights })
+	MessageResult::Request { events: result, weight: total_weights }
 }
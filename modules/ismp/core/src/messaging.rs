@@ -126,6 +126,43 @@ pub struct RequestMessage {
 	pub signer: Vec<u8>,
 }
 
+/// A batch of requests proven against a single state machine height.
+#[derive(
+	Debug, Clone, Encode, DecodeWithMemTracking, Decode, scale_info::TypeInfo, PartialEq, Eq,
+)]
+pub struct RequestBatch {
+	/// Requests from source chain
+	pub requests: Vec<PostRequest>,
+	/// Membership batch proof for these requests
+	pub proof: Proof,
+}
+
+/// An aggregated request message holds several batches of requests, each proven against its own
+/// state machine height. This allows relayers deliver a backlog of requests committed across
+/// multiple heights of the source state machine in a single transaction.
+#[derive(
+	Debug, Clone, Encode, DecodeWithMemTracking, Decode, scale_info::TypeInfo, PartialEq, Eq,
+)]
+pub struct AggregatedRequestMessage {
+	/// Request batches, each with its own membership proof
+	pub batches: Vec<RequestBatch>,
+	/// Signer information. Ideally should be their account identifier
+	pub signer: Vec<u8>,
+}
+
+impl AggregatedRequestMessage {
+	/// Returns all the requests across every batch in this message
+	pub fn requests(&self) -> Vec<PostRequest> {
+		self.batches.iter().flat_map(|batch| batch.requests.iter().cloned()).collect()
+	}
+}
+
+impl From<RequestMessage> for RequestBatch {
+	fn from(msg: RequestMessage) -> Self {
+		RequestBatch { requests: msg.requests, proof: msg.proof }
+	}
+}
+
 /// A response message holds a batch of GetRequests being responded to.
 ///
 /// Post-#840 the protocol no longer carries `PostResponse`; the only
@@ -228,6 +265,9 @@ pub enum Message {
 	/// A request timeout message
 	#[codec(index = 4)]
 	Timeout(TimeoutMessage),
+	/// A request message with proofs spanning multiple state machine heights
+	#[codec(index = 5)]
+	AggregatedRequest(AggregatedRequestMessage),
 }
 
 /// The ISMP Message with Weight consumed by the message
@@ -121,8 +121,10 @@ pub struct GetRequest {
 	/// `<https://github.com/paritytech/substrate/blob/master/frame/support/src/storage/types/nmap.rs#L39-L48>`
 	/// `<https://github.com/paritytech/substrate/blob/master/frame/support/src/storage/types/value.rs#L37>`
 	/// EVM Keys
-	/// For fetching keys from EVM contracts each key should either be 52 bytes or 20 bytes
+	/// For fetching keys from EVM contracts each key should either be 52, 24 or 20 bytes
 	/// For 52 byte keys we expect it to be a concatenation of contract address and slot hash
+	/// For 24 byte keys we expect it to be the big-endian block number, transaction index and log
+	/// index of an event log
 	/// For 20 bytes we expect it to be a contract or account address
 	#[serde(with = "serde_hex_utils::seq_of_hex")]
 	pub keys: Vec<Vec<u8>>,
@@ -51,8 +51,8 @@ pub enum EvmStateMachineError {
 	/// The number of verified values doesn't match the number of supplied keys.
 	#[error("Mismatched values/keys: the proof did not account for every key")]
 	MismatchedValuesAndKeys,
-	/// A query key length didn't match any supported layout (20, 32, or 52 bytes).
-	#[error("Unsupported Key type, found a key whose length is not one of 20, 32 or 52")]
+	/// A query key length didn't match any supported layout (20, 24, 32, or 52 bytes).
+	#[error("Unsupported Key type, found a key whose length is not one of 20, 24, 32 or 52")]
 	UnsupportedKeyLength,
 	/// The same key was supplied more than once.
 	///
@@ -73,6 +73,33 @@ pub enum EvmStateMachineError {
 	/// for.
 	#[error("The storage proof contains a contract that was not requested")]
 	UnrequestedContractProof,
+	/// The proof is missing the receipt proof for at least one queried block.
+	#[error("The receipt proof is incomplete, missing some block proofs")]
+	IncompleteReceiptProof,
+	/// The proof carries a receipt proof for a block no log was requested from.
+	#[error("The receipt proof contains a block that was not requested")]
+	UnrequestedReceiptProof,
+	/// A log was queried from a block whose hash is not kept by the history contract at the
+	/// proof height.
+	#[error("Block {block} is outside the block hash history window at height {height}")]
+	BlockOutsideHistoryWindow {
+		/// The queried block
+		block: u64,
+		/// Height of the state proof
+		height: u64,
+	},
+	/// The block header failed to RLP-decode or is for a different block.
+	#[error("Invalid header for block {0}")]
+	InvalidBlockHeader(u64),
+	/// The history contract proof has no hash for the block.
+	#[error("Block hash for block {0} is not present in proof")]
+	BlockHashNotFound(u64),
+	/// The block header does not hash to the block hash in the history contract.
+	#[error("Header does not match the block hash of block {0}")]
+	BlockHashMismatch(u64),
+	/// A transaction receipt failed to RLP-decode.
+	#[error("Error decoding transaction receipt")]
+	ReceiptDecodeError,
 	/// Failed to SCALE-decode the EVM state proof from the proof bytes.
 	#[error("Cannot decode evm state proof")]
 	StateProofDecodeError,
@@ -107,6 +134,7 @@ use pallet_ismp_host_executive::EvmHosts;
 use prelude::*;
 
 pub mod presets;
+pub mod receipts;
 pub mod substrate_evm;
 pub mod tendermint;
 pub mod types;
@@ -167,6 +195,7 @@ pub fn verify_state_proof<H: Keccak256 + Send + Sync>(
 	let mut map = BTreeMap::new();
 	let mut contract_to_keys = BTreeMap::new();
 	let mut contract_account_queries = Vec::new();
+	let mut log_queries = Vec::new();
 
 	// Group keys by the contract address they belong to
 	for key in keys {
@@ -176,6 +205,7 @@ pub fn verify_state_proof<H: Keccak256 + Send + Sync>(
 		// contract or account address.
 		// For keys that are 32 bytes we expect that to be a slothash in
 		// the Ismp EVM host
+		// For keys that are 24 bytes we expect a (block, tx index, log index) naming an event log
 		let contract_address = if key.len() == 52 {
 			H160::from_slice(&key[..20])
 		} else if key.len() == 32 {
@@ -183,6 +213,9 @@ pub fn verify_state_proof<H: Keccak256 + Send + Sync>(
 		} else if key.len() == 20 {
 			contract_account_queries.push(H160::from_slice(&key));
 			continue;
+		} else if key.len() == receipts::LOG_KEY_LENGTH {
+			log_queries.push(key);
+			continue;
 		} else {
 			return Err(EvmStateMachineError::UnsupportedKeyLength.into());
 		};
@@ -220,6 +253,15 @@ pub fn verify_state_proof<H: Keccak256 + Send + Sync>(
 		return Err(EvmStateMachineError::UnrequestedContractProof.into());
 	}
 
+	let logs = receipts::verify_log_proofs::<H>(
+		log_queries,
+		proof.height.height,
+		root,
+		&evm_state_proof.contract_proof,
+		evm_state_proof.receipt_proofs,
+	)?;
+	map.extend(logs);
+
 	for (contract_address, storage_proof) in evm_state_proof.storage_proof {
 		// Unreachable: the correspondence check above already rejected any entry without a
 		// requested key. Kept as a hard error rather than a skip so the invariant survives if
@@ -343,18 +385,17 @@ mod bounded_verification_tests {
 	use super::*;
 	use crate::types::EvmStateProof;
 	use codec::Encode;
-	use ismp::consensus::{StateMachineHeight, StateMachineId};
-	use ismp::host::StateMachine;
+	use ismp::{
+		consensus::{StateMachineHeight, StateMachineId},
+		host::StateMachine,
+	};
 
 	/// A proof whose bytes are irrelevant: the duplicate-key check runs before decoding, so
 	/// these tests never reach it. That ordering is the point — a repeated key costs nothing.
 	fn dummy_proof() -> Proof {
 		Proof {
 			height: StateMachineHeight {
-				id: StateMachineId {
-					state_id: StateMachine::Evm(1),
-					consensus_state_id: *b"ETH0",
-				},
+				id: StateMachineId { state_id: StateMachine::Evm(1), consensus_state_id: *b"ETH0" },
 				height: 1,
 			},
 			proof: vec![0xde, 0xad, 0xbe, 0xef],
@@ -449,7 +490,11 @@ mod bounded_verification_tests {
 	fn rejects_a_storage_proof_for_an_unrequested_contract() {
 		let mut storage_proof = BTreeMap::new();
 		storage_proof.insert(vec![0xab; 20], alloc::vec![alloc::vec![0u8; 8]]);
-		let proof = proof_with(EvmStateProof { contract_proof: vec![], storage_proof });
+		let proof = proof_with(EvmStateProof {
+			contract_proof: vec![],
+			storage_proof,
+			receipt_proofs: BTreeMap::new(),
+		});
 
 		let err = verify_state_proof::<UnusedHasher>(vec![], H256::zero(), &proof, H160::zero())
 			.expect_err("an unrequested contract proof must be rejected");
@@ -470,7 +515,11 @@ mod bounded_verification_tests {
 		let mut storage_proof = BTreeMap::new();
 		storage_proof.insert(requested, alloc::vec![alloc::vec![0u8; 8]]);
 		storage_proof.insert(vec![0xcd; 20], alloc::vec![alloc::vec![0u8; 8]]);
-		let proof = proof_with(EvmStateProof { contract_proof: vec![], storage_proof });
+		let proof = proof_with(EvmStateProof {
+			contract_proof: vec![],
+			storage_proof,
+			receipt_proofs: BTreeMap::new(),
+		});
 
 		let err = verify_state_proof::<StubHasher>(vec![key], H256::zero(), &proof, H160::zero())
 			.expect_err("padding must be rejected");
@@ -484,8 +533,11 @@ mod bounded_verification_tests {
 	/// requested and nothing supplied the loop has no entries, so verification completes.
 	#[test]
 	fn an_exactly_corresponding_proof_is_accepted() {
-		let proof =
-			proof_with(EvmStateProof { contract_proof: vec![], storage_proof: BTreeMap::new() });
+		let proof = proof_with(EvmStateProof {
+			contract_proof: vec![],
+			storage_proof: BTreeMap::new(),
+			receipt_proofs: BTreeMap::new(),
+		});
 
 		let values = verify_state_proof::<UnusedHasher>(vec![], H256::zero(), &proof, H160::zero())
 			.expect("an empty query with an empty proof must verify");
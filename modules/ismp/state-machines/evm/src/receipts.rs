@@ -0,0 +1,506 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Event log proofs against the receipts root of EVM block headers.
+//!
+//! A log is named by a 24-byte key holding the big-endian `(block, tx index, log index)`, see
+//! [`log_key`]. The header of `block` is tied to the proven state root through the block hash
+//! history contract introduced in EIP-2935, which keeps the hashes of the last
+//! [`HISTORY_SERVE_WINDOW`] blocks in its storage. The receipt is then read from the receipts root
+//! of that header. This means logs can only be queried for blocks within that window before the
+//! proof height, and only on chains that have activated EIP-2935.
+
+use crate::{
+	prelude::*,
+	types::{EvmReceiptProof, KeccakHasher},
+	utils::{get_contract_account, get_value_from_proof},
+	EvmStateMachineError,
+};
+use alloc::format;
+use alloy_rlp::{Decodable, Header as RlpHeader};
+use ethereum_triedb::{EIP1186Layout, StorageProof};
+use geth_primitives::Header;
+use ismp::{error::Error, messaging::Keccak256};
+use primitive_types::{H160, H256, U256};
+use trie_db::{Trie, TrieDBBuilder};
+
+/// Address of the EIP-2935 block hash history contract
+pub const HISTORY_STORAGE_ADDRESS: H160 =
+	H160(hex_literal::hex!("0000F90827F1C53a10cb7A02335B175320002935"));
+
+/// Number of recent block hashes kept by the history contract
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+/// Length of a key naming an event log
+pub const LOG_KEY_LENGTH: usize = 24;
+
+/// Returns the key naming the log at `log_index` emitted by the transaction at `tx_index` in
+/// `block`.
+pub fn log_key(block: u64, tx_index: u64, log_index: u64) -> Vec<u8> {
+	let mut key = Vec::with_capacity(LOG_KEY_LENGTH);
+	key.extend_from_slice(&block.to_be_bytes());
+	key.extend_from_slice(&tx_index.to_be_bytes());
+	key.extend_from_slice(&log_index.to_be_bytes());
+	key
+}
+
+/// Splits a log key into its `(block, tx index, log index)`
+pub fn decode_log_key(key: &[u8]) -> Option<(u64, u64, u64)> {
+	if key.len() != LOG_KEY_LENGTH {
+		return None;
+	}
+	let word = |i: usize| {
+		let mut bytes = [0u8; 8];
+		bytes.copy_from_slice(&key[i * 8..(i + 1) * 8]);
+		u64::from_be_bytes(bytes)
+	};
+	Some((word(0), word(1), word(2)))
+}
+
+/// Returns the storage slot holding the hash of `block` in the history contract
+pub fn block_hash_slot(block: u64) -> H256 {
+	H256(U256::from(block % HISTORY_SERVE_WINDOW).to_big_endian())
+}
+
+/// Returns the storage trie key of [`block_hash_slot`]
+pub fn block_hash_slot_key<H: Keccak256>(block: u64) -> H256 {
+	H::keccak256(&block_hash_slot(block).0)
+}
+
+/// Verifies the logs named by `keys` against the receipt proofs of the blocks they were emitted
+/// in. `contract_proof` must contain the account proof of [`HISTORY_STORAGE_ADDRESS`] under
+/// `state_root`, the state root of the block at `height`.
+///
+/// Returns the RLP-encoded log for every key, or `None` if the block has no such transaction or
+/// the transaction emitted no such log.
+pub fn verify_log_proofs<H: Keccak256 + Send + Sync>(
+	keys: Vec<Vec<u8>>,
+	height: u64,
+	state_root: H256,
+	contract_proof: &[Vec<u8>],
+	mut receipt_proofs: BTreeMap<u64, EvmReceiptProof>,
+) -> Result<BTreeMap<Vec<u8>, Option<Vec<u8>>>, Error> {
+	let mut block_to_keys: BTreeMap<u64, Vec<(Vec<u8>, u64, u64)>> = BTreeMap::new();
+	for key in keys {
+		let (block, tx_index, log_index) =
+			decode_log_key(&key).ok_or(EvmStateMachineError::UnsupportedKeyLength)?;
+		block_to_keys.entry(block).or_default().push((key, tx_index, log_index));
+	}
+
+	// Receipt proofs must correspond exactly to the queried blocks, for the same reasons as
+	// storage proofs: each entry costs a header decode and a trie rebuild.
+	if block_to_keys.keys().any(|block| !receipt_proofs.contains_key(block)) {
+		return Err(EvmStateMachineError::IncompleteReceiptProof.into());
+	}
+	if receipt_proofs.keys().any(|block| !block_to_keys.contains_key(block)) {
+		return Err(EvmStateMachineError::UnrequestedReceiptProof.into());
+	}
+
+	let mut map = BTreeMap::new();
+	if block_to_keys.is_empty() {
+		return Ok(map);
+	}
+
+	if let Some(block) = block_to_keys
+		.keys()
+		.find(|block| **block >= height || height - **block > HISTORY_SERVE_WINDOW)
+	{
+		return Err(EvmStateMachineError::BlockOutsideHistoryWindow { block: *block, height }.into());
+	}
+
+	let history_root =
+		get_contract_account::<H>(contract_proof.to_vec(), &HISTORY_STORAGE_ADDRESS.0, state_root)?
+			.storage_root
+			.0
+			.into();
+
+	for (block, keys) in block_to_keys {
+		let proof = receipt_proofs
+			.remove(&block)
+			.ok_or(EvmStateMachineError::IncompleteReceiptProof)?;
+		let receipts_root = verify_header::<H>(block, &proof, history_root)?;
+
+		let db = StorageProof::new(proof.receipts_proof).into_memory_db::<KeccakHasher<H>>();
+		let trie =
+			TrieDBBuilder::<EIP1186Layout<KeccakHasher<H>>>::new(&db, &receipts_root).build();
+		for (key, tx_index, log_index) in keys {
+			let receipt = trie
+				.get(&alloy_rlp::encode(tx_index))
+				.map_err(|e| EvmStateMachineError::TrieReadError(format!("{e:?}")))?;
+			let log = match receipt {
+				Some(receipt) => log_at(&receipt, log_index)?,
+				None => None,
+			};
+			map.insert(key, log);
+		}
+	}
+
+	Ok(map)
+}
+
+/// Checks the header in `proof` against the hash of `block` stored in the history contract and
+/// returns its receipts root.
+fn verify_header<H: Keccak256 + Send + Sync>(
+	block: u64,
+	proof: &EvmReceiptProof,
+	history_root: H256,
+) -> Result<H256, Error> {
+	let header = <Header as Decodable>::decode(&mut &proof.header[..])
+		.map_err(|_| EvmStateMachineError::InvalidBlockHeader(block))?;
+	if header.number != alloy_primitives::U256::from(block) {
+		return Err(EvmStateMachineError::InvalidBlockHeader(block).into());
+	}
+
+	let stored = get_value_from_proof::<H>(
+		block_hash_slot_key::<H>(block).0.to_vec(),
+		history_root,
+		proof.block_hash_proof.clone(),
+	)?
+	.ok_or(EvmStateMachineError::BlockHashNotFound(block))?;
+	// Storage values are RLP-encoded with their leading zeros stripped
+	let stored = <alloy_primitives::U256 as Decodable>::decode(&mut &*stored)
+		.map_err(|_| EvmStateMachineError::BlockHashNotFound(block))?;
+	if H256(stored.to_be_bytes::<32>()) != H::keccak256(&proof.header) {
+		return Err(EvmStateMachineError::BlockHashMismatch(block).into());
+	}
+
+	Ok(header.receipts_root.0.into())
+}
+
+/// Returns the RLP encoding of the log at `index` in an encoded transaction receipt, or `None`
+/// if the receipt has fewer logs.
+pub fn log_at(receipt: &[u8], index: u64) -> Result<Option<Vec<u8>>, Error> {
+	// EIP-2718 typed receipts are prefixed with their type, legacy receipts are a bare RLP list
+	let mut buf = match receipt.first() {
+		Some(ty) if *ty <= 0x7f => &receipt[1..],
+		_ => receipt,
+	};
+	let mut fields = list_payload(&mut buf)?;
+	// status or post state, cumulative gas used and logs bloom
+	for _ in 0..3 {
+		next_item(&mut fields)?;
+	}
+
+	let mut logs = list_payload(&mut fields)?;
+	let mut current = 0;
+	while !logs.is_empty() {
+		let (_, log) = next_item(&mut logs)?;
+		if current == index {
+			return Ok(Some(log.to_vec()));
+		}
+		current += 1;
+	}
+
+	Ok(None)
+}
+
+/// Splits the next RLP item off `buf`, returning its header and full encoding
+fn next_item<'a>(buf: &mut &'a [u8]) -> Result<(RlpHeader, &'a [u8]), EvmStateMachineError> {
+	let start = *buf;
+	let header = RlpHeader::decode(buf).map_err(|_| EvmStateMachineError::ReceiptDecodeError)?;
+	let end = (start.len() - buf.len())
+		.checked_add(header.payload_length)
+		.filter(|end| *end <= start.len())
+		.ok_or(EvmStateMachineError::ReceiptDecodeError)?;
+	*buf = &start[end..];
+	Ok((header, &start[..end]))
+}
+
+/// Splits the next RLP list off `buf`, returning its payload
+fn list_payload<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], EvmStateMachineError> {
+	let (header, item) = next_item(buf)?;
+	if !header.list {
+		return Err(EvmStateMachineError::ReceiptDecodeError);
+	}
+	Ok(&item[item.len() - header.payload_length..])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		types::{Account, EvmStateProof},
+		verify_state_proof,
+	};
+	use alloy_primitives::{Address, Bytes, FixedBytes, B256};
+	use alloy_rlp_derive::RlpEncodable;
+	use codec::{Decode, Encode};
+	use ethereum_triedb::MemoryDB;
+	use ismp::{
+		consensus::{StateMachineHeight, StateMachineId},
+		host::StateMachine,
+		messaging::Proof,
+	};
+	use polkadot_sdk::sp_io::hashing::keccak_256;
+	use trie_db::{TrieDBMutBuilder, TrieMut};
+
+	#[derive(RlpEncodable, Clone)]
+	struct Log {
+		address: Address,
+		topics: Vec<B256>,
+		data: Bytes,
+	}
+
+	#[derive(RlpEncodable)]
+	struct Receipt {
+		status: u8,
+		cumulative_gas_used: u64,
+		logs_bloom: FixedBytes<256>,
+		logs: Vec<Log>,
+	}
+
+	struct TestHasher;
+	impl Keccak256 for TestHasher {
+		fn keccak256(bytes: &[u8]) -> H256 {
+			keccak_256(bytes).into()
+		}
+	}
+
+	/// Builds a trie holding `entries`, returning its root and all of its nodes as a proof
+	fn build_trie(entries: Vec<(Vec<u8>, Vec<u8>)>) -> (H256, Vec<Vec<u8>>) {
+		let mut db = MemoryDB::<KeccakHasher<TestHasher>>::default();
+		let mut root = H256::zero();
+		{
+			let mut trie = TrieDBMutBuilder::<EIP1186Layout<KeccakHasher<TestHasher>>>::new(
+				&mut db, &mut root,
+			)
+			.build();
+			for (key, value) in entries {
+				trie.insert(&key, &value).unwrap();
+			}
+		}
+		let nodes = db.drain().into_values().map(|(node, _)| node).collect();
+		(root, nodes)
+	}
+
+	fn encode_header(number: u64, receipts_root: H256) -> Vec<u8> {
+		alloy_rlp::encode(Header {
+			parent_hash: B256::ZERO,
+			uncle_hash: B256::ZERO,
+			coinbase: Address::ZERO,
+			state_root: B256::ZERO,
+			transactions_root: B256::ZERO,
+			receipts_root: receipts_root.0.into(),
+			logs_bloom: FixedBytes::ZERO,
+			difficulty: alloy_primitives::U256::ZERO,
+			number: alloy_primitives::U256::from(number),
+			gas_limit: 30_000_000,
+			gas_used: 42_000,
+			timestamp: 1_700_000_000,
+			extra_data: Bytes::new(),
+			mix_hash: B256::ZERO,
+			nonce: FixedBytes::ZERO,
+			base_fee_per_gas: None,
+			withdrawals_hash: None,
+			blob_gas_used: None,
+			excess_blob_gas_used: None,
+			parent_beacon_root: None,
+			requests_hash: None,
+		})
+	}
+
+	/// Proves `header` as block `block` through the history contract, returning the state root
+	/// and the proofs of the contract account and its block hash slot
+	fn history_proof(block: u64, header: &[u8]) -> (H256, Vec<Vec<u8>>, Vec<Vec<u8>>) {
+		let hash = alloy_primitives::U256::from_be_bytes(keccak_256(header));
+		let (history_root, block_hash_proof) = build_trie(vec![(
+			block_hash_slot_key::<TestHasher>(block).0.to_vec(),
+			alloy_rlp::encode(hash),
+		)]);
+		let account = Account {
+			nonce: 1,
+			balance: alloy_primitives::U256::ZERO,
+			storage_root: history_root.0.into(),
+			code_hash: B256::ZERO,
+		};
+		let (state_root, contract_proof) = build_trie(vec![(
+			keccak_256(&HISTORY_STORAGE_ADDRESS.0).to_vec(),
+			alloy_rlp::encode(account),
+		)]);
+		(state_root, contract_proof, block_hash_proof)
+	}
+
+	fn logs() -> Vec<Log> {
+		(0..3u8)
+			.map(|i| Log {
+				address: Address::repeat_byte(i),
+				topics: vec![B256::repeat_byte(i + 1)],
+				data: Bytes::from(vec![i; i as usize * 7]),
+			})
+			.collect()
+	}
+
+	fn proof_at(height: u64, receipt_proofs: BTreeMap<u64, EvmReceiptProof>) -> Proof {
+		let state_proof = EvmStateProof {
+			contract_proof: vec![],
+			storage_proof: BTreeMap::new(),
+			receipt_proofs,
+		};
+		Proof {
+			height: StateMachineHeight {
+				id: StateMachineId { state_id: StateMachine::Evm(1), consensus_state_id: *b"ETH0" },
+				height,
+			},
+			proof: state_proof.encode(),
+		}
+	}
+
+	fn empty_receipt_proof() -> EvmReceiptProof {
+		EvmReceiptProof { header: vec![], block_hash_proof: vec![], receipts_proof: vec![] }
+	}
+
+	#[test]
+	fn log_keys_round_trip() {
+		let key = log_key(19_000_000, 42, 7);
+		assert_eq!(key.len(), LOG_KEY_LENGTH);
+		assert_eq!(decode_log_key(&key), Some((19_000_000, 42, 7)));
+		assert_eq!(decode_log_key(&key[1..]), None);
+	}
+
+	#[test]
+	fn reads_logs_from_legacy_and_typed_receipts() {
+		let receipt = alloy_rlp::encode(Receipt {
+			status: 1,
+			cumulative_gas_used: 21_000,
+			logs_bloom: FixedBytes::ZERO,
+			logs: logs(),
+		});
+		let mut typed = vec![0x02];
+		typed.extend_from_slice(&receipt);
+
+		for encoded in [receipt, typed] {
+			for (index, log) in logs().into_iter().enumerate() {
+				assert_eq!(log_at(&encoded, index as u64).unwrap(), Some(alloy_rlp::encode(log)));
+			}
+			assert_eq!(log_at(&encoded, 3).unwrap(), None);
+		}
+	}
+
+	#[test]
+	fn rejects_truncated_receipts() {
+		let receipt = alloy_rlp::encode(Receipt {
+			status: 1,
+			cumulative_gas_used: 21_000,
+			logs_bloom: FixedBytes::ZERO,
+			logs: logs(),
+		});
+		assert!(log_at(&receipt[..receipt.len() - 1], 2).is_err());
+	}
+
+	#[test]
+	fn proofs_without_receipt_proofs_still_decode() {
+		let legacy = (vec![vec![1u8; 4]], BTreeMap::<Vec<u8>, Vec<Vec<u8>>>::new()).encode();
+		let decoded = EvmStateProof::decode(&mut &legacy[..]).expect("legacy proof decodes");
+		assert_eq!(decoded.contract_proof, vec![vec![1u8; 4]]);
+		assert!(decoded.receipt_proofs.is_empty());
+	}
+
+	#[test]
+	fn verifies_logs_against_the_receipts_root() {
+		let receipt = |logs: Vec<Log>| Receipt {
+			status: 1,
+			cumulative_gas_used: 21_000,
+			logs_bloom: FixedBytes::ZERO,
+			logs,
+		};
+		let mut typed = vec![0x02];
+		typed.extend_from_slice(&alloy_rlp::encode(receipt(logs()[2..].to_vec())));
+		let (receipts_root, receipts_proof) = build_trie(vec![
+			(alloy_rlp::encode(0u64), alloy_rlp::encode(receipt(logs()))),
+			(alloy_rlp::encode(1u64), typed),
+		]);
+
+		let block = 95;
+		let header = encode_header(block, receipts_root);
+		let (state_root, contract_proof, block_hash_proof) = history_proof(block, &header);
+		let receipt_proof = EvmReceiptProof { header, block_hash_proof, receipts_proof };
+		let proof = Proof {
+			proof: EvmStateProof {
+				contract_proof: contract_proof.clone(),
+				storage_proof: BTreeMap::new(),
+				receipt_proofs: [(block, receipt_proof.clone())].into_iter().collect(),
+			}
+			.encode(),
+			..proof_at(100, BTreeMap::new())
+		};
+
+		let keys = vec![
+			log_key(block, 0, 1),
+			log_key(block, 1, 0),
+			log_key(block, 0, 3),
+			log_key(block, 2, 0),
+		];
+		let values =
+			verify_state_proof::<TestHasher>(keys.clone(), state_root, &proof, H160::zero())
+				.expect("valid receipt proof must verify");
+		assert_eq!(values[&keys[0]], Some(alloy_rlp::encode(logs()[1].clone())));
+		assert_eq!(values[&keys[1]], Some(alloy_rlp::encode(logs()[2].clone())));
+		// no such log and no such transaction
+		assert_eq!(values[&keys[2]], None);
+		assert_eq!(values[&keys[3]], None);
+
+		// a header that isn't the one stored in the history contract is rejected
+		let forged =
+			EvmReceiptProof { header: encode_header(block, H256::repeat_byte(1)), ..receipt_proof };
+		let proof = Proof {
+			proof: EvmStateProof {
+				contract_proof,
+				storage_proof: BTreeMap::new(),
+				receipt_proofs: [(block, forged)].into_iter().collect(),
+			}
+			.encode(),
+			..proof_at(100, BTreeMap::new())
+		};
+		let err = verify_state_proof::<TestHasher>(keys, state_root, &proof, H160::zero())
+			.expect_err("forged header must be rejected");
+		assert!(format!("{err:?}").contains("does not match the block hash"), "got {err:?}");
+	}
+
+	#[test]
+	fn rejects_log_queries_without_a_receipt_proof() {
+		let proof = proof_at(100, BTreeMap::new());
+		let err = verify_state_proof::<TestHasher>(
+			vec![log_key(99, 0, 0)],
+			H256::zero(),
+			&proof,
+			H160::zero(),
+		)
+		.expect_err("missing receipt proof must be rejected");
+		assert!(format!("{err:?}").contains("receipt proof is incomplete"), "got {err:?}");
+	}
+
+	#[test]
+	fn rejects_receipt_proofs_for_unrequested_blocks() {
+		let proof = proof_at(100, [(98, empty_receipt_proof())].into_iter().collect());
+		let err = verify_state_proof::<TestHasher>(vec![], H256::zero(), &proof, H160::zero())
+			.expect_err("unrequested receipt proof must be rejected");
+		assert!(format!("{err:?}").contains("not requested"), "got {err:?}");
+	}
+
+	#[test]
+	fn rejects_blocks_outside_the_history_window() {
+		for (block, height) in [(100, 100), (101, 100), (100, 100 + HISTORY_SERVE_WINDOW + 1)] {
+			let proof = proof_at(height, [(block, empty_receipt_proof())].into_iter().collect());
+			let err = verify_state_proof::<TestHasher>(
+				vec![log_key(block, 0, 0)],
+				H256::zero(),
+				&proof,
+				H160::zero(),
+			)
+			.expect_err("blocks outside the window must be rejected");
+			assert!(format!("{err:?}").contains("history window"), "got {err:?}");
+		}
+	}
+}
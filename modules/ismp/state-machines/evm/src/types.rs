@@ -35,13 +35,39 @@ impl<H: Keccak256 + Send + Sync> Hasher for KeccakHasher<H> {
 	}
 }
 
-#[derive(Encode, Decode, Clone)]
+#[derive(Encode, Clone)]
 pub struct EvmStateProof {
 	/// Contract account proof
 	pub contract_proof: Vec<Vec<u8>>,
 	/// A map of contract address to the associated account trie proof for all keys requested from
 	/// the contract
 	pub storage_proof: BTreeMap<Vec<u8>, Vec<Vec<u8>>>,
+	/// A map of block number to the receipt proof for all logs requested from the block
+	pub receipt_proofs: BTreeMap<u64, EvmReceiptProof>,
+}
+
+impl Decode for EvmStateProof {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let contract_proof = Decode::decode(input)?;
+		let storage_proof = Decode::decode(input)?;
+		// Proofs encoded before receipt proofs were introduced end here
+		let receipt_proofs = match input.remaining_len()? {
+			Some(0) => BTreeMap::new(),
+			_ => Decode::decode(input)?,
+		};
+
+		Ok(EvmStateProof { contract_proof, storage_proof, receipt_proofs })
+	}
+}
+
+#[derive(Encode, Decode, Clone)]
+pub struct EvmReceiptProof {
+	/// The RLP-encoded block header
+	pub header: Vec<u8>,
+	/// Storage proof of the block hash in the EIP-2935 history contract
+	pub block_hash_proof: Vec<Vec<u8>>,
+	/// Receipt trie proof for all the transactions queried in the block
+	pub receipts_proof: Vec<Vec<u8>>,
 }
 
 #[derive(Encode, Decode, Clone)]
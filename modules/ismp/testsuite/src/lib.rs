@@ -29,11 +29,11 @@ use ismp::{
 	},
 	dispatcher::{DispatchGet, DispatchPost, DispatchRequest, FeeMetadata, IsmpDispatcher},
 	error::Error,
-	handlers::handle_incoming_message,
+	handlers::{handle_incoming_message, MessageResult},
 	host::{IsmpHost, StateMachine},
 	messaging::{
-		hash_request, ConsensusMessage, FraudProofMessage, Message, Proof, RequestMessage,
-		ResponseMessage, TimeoutMessage,
+		hash_request, AggregatedRequestMessage, ConsensusMessage, FraudProofMessage, Message,
+		Proof, RequestBatch, RequestMessage, ResponseMessage, TimeoutMessage,
 	},
 	router::{GetRequest, GetResponse, PostRequest, Request},
 };
@@ -768,3 +768,123 @@ pub fn check_get_timeout_message_dedup<H: IsmpHost>(host: &H) -> Result<(), &'st
 	assert!(matches!(res, Err(Error::DuplicateRequest { .. })), "got: {res:?}");
 	Ok(())
 }
+
+/// `AggregatedRequestMessage` repeating a request across two batches must be
+/// rejected with `DuplicateRequest`, even though each batch is unique on its own.
+pub fn check_aggregated_request_message_dedup<H: IsmpHost>(host: &H) -> Result<(), &'static str> {
+	let intermediate_state = setup_with_elapsed_challenge_period(host);
+
+	let post = PostRequest {
+		source: intermediate_state.height.id.state_id,
+		dest: host.host_state_machine(),
+		nonce: 0,
+		from: vec![0u8; 32],
+		to: vec![0u8; 32],
+		timeout_timestamp: 0,
+		body: vec![0u8; 64],
+	};
+
+	let batch = RequestBatch {
+		requests: vec![post.clone()],
+		proof: Proof { height: intermediate_state.height, proof: vec![] },
+	};
+	let (signature, ..) = create_relayer_signer(vec![post.clone(), post].encode(), &[1u8; 32]);
+
+	let request_message = Message::AggregatedRequest(AggregatedRequestMessage {
+		batches: vec![batch.clone(), batch],
+		signer: signature,
+	});
+
+	let res = handle_incoming_message(host, request_message).map_err(|e| e.downcast().unwrap());
+	assert!(matches!(res, Err(Error::DuplicateRequest { .. })), "got: {res:?}");
+	Ok(())
+}
+
+/// Every batch in an `AggregatedRequestMessage` is checked against the challenge
+/// period of its own proof height. A batch proven at a height that was only just
+/// committed fails the whole message, even when the other batches are valid.
+pub fn check_aggregated_request_challenge_period<H: IsmpHost>(
+	host: &H,
+) -> Result<(), &'static str> {
+	let intermediate_state = setup_with_elapsed_challenge_period(host);
+
+	let fresh_height = StateMachineHeight { id: intermediate_state.height.id, height: 2 };
+	host.store_state_machine_commitment(fresh_height, intermediate_state.commitment)
+		.unwrap();
+	host.store_state_machine_update_time(fresh_height, host.timestamp()).unwrap();
+
+	let post = |nonce| PostRequest {
+		source: intermediate_state.height.id.state_id,
+		dest: host.host_state_machine(),
+		nonce,
+		from: vec![0u8; 32],
+		to: vec![0u8; 32],
+		timeout_timestamp: 0,
+		body: vec![0u8; 64],
+	};
+
+	let (signature, ..) = create_relayer_signer(vec![post(0), post(1)].encode(), &[1u8; 32]);
+	let request_message = Message::AggregatedRequest(AggregatedRequestMessage {
+		batches: vec![
+			RequestBatch {
+				requests: vec![post(0)],
+				proof: Proof { height: intermediate_state.height, proof: vec![] },
+			},
+			RequestBatch {
+				requests: vec![post(1)],
+				proof: Proof { height: fresh_height, proof: vec![] },
+			},
+		],
+		signer: signature,
+	});
+
+	let res = handle_incoming_message(host, request_message).map_err(|e| e.downcast().unwrap());
+	assert!(
+		matches!(
+			res,
+			Err(Error::ChallengePeriodNotElapsed { state_machine_id, .. })
+				if state_machine_id == fresh_height.id
+		),
+		"got: {res:?}"
+	);
+	Ok(())
+}
+
+/// Requests in an `AggregatedRequestMessage` that were already received are skipped, the rest of
+/// the backlog is still dispatched.
+pub fn check_aggregated_request_skips_received<H: IsmpHost>(host: &H) -> Result<(), &'static str> {
+	let intermediate_state = setup_with_elapsed_challenge_period(host);
+
+	let post = |nonce| PostRequest {
+		source: intermediate_state.height.id.state_id,
+		dest: host.host_state_machine(),
+		nonce,
+		from: vec![0u8; 32],
+		to: vec![0u8; 32],
+		timeout_timestamp: 0,
+		body: vec![0u8; 64],
+	};
+	host.store_request_receipt(&Request::Post(post(0)), &vec![]).unwrap();
+
+	let (signature, ..) = create_relayer_signer(vec![post(0), post(1)].encode(), &[1u8; 32]);
+	let request_message = Message::AggregatedRequest(AggregatedRequestMessage {
+		batches: vec![
+			RequestBatch {
+				requests: vec![post(0)],
+				proof: Proof { height: intermediate_state.height, proof: vec![] },
+			},
+			RequestBatch {
+				requests: vec![post(1)],
+				proof: Proof { height: intermediate_state.height, proof: vec![] },
+			},
+		],
+		signer: signature,
+	});
+
+	let res = handle_incoming_message(host, request_message).map_err(|e| e.downcast().unwrap());
+	assert!(
+		matches!(res, Ok(MessageResult::Request { ref events, .. }) if events.len() == 1),
+		"got: {res:?}"
+	);
+	Ok(())
+}
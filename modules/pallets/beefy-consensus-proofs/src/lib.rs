@@ -480,7 +480,7 @@ pub mod pallet {
 					}
 					Some(nonce)
 				},
-				types::PROOF_TYPE_NAIVE => None,
+				types::PROOF_TYPE_NAIVE | types::PROOF_TYPE_BLS => None,
 				_ => Err(Error::<T>::UnknownProofType)?,
 			};
 
@@ -792,7 +792,8 @@ pub mod pallet {
 
 		/// First-proof verification path:
 		///
-		/// 1. ABI-decode the proof into the SCALE shape `ismp-beefy` consumes.
+		/// 1. ABI-decode the proof into the SCALE shape `ismp-beefy` consumes. BLS proofs are
+		///    SCALE-encoded already and pass through unchanged.
 		/// 2. Dispatch `Message::Consensus` through `ismp::handlers::handle_incoming_message`,
 		///    which routes to `BeefyConsensusClient::verify_consensus`. That runs the full BEEFY /
 		///    SP1 check and persists consensus state + parachain commitments. The verifier's own
@@ -834,6 +835,8 @@ pub mod pallet {
 					let scale_proof: beefy_verifier_primitives::ConsensusMessage = abi_proof.into();
 					[&[types::PROOF_TYPE_NAIVE], scale_proof.encode().as_slice()].concat()
 				},
+				// BLS proofs have no Solidity verifier and are submitted SCALE-encoded already.
+				types::PROOF_TYPE_BLS => proof.to_vec(),
 				_ => Err(Error::<T>::UnknownProofType)?,
 			};
 
@@ -66,7 +66,7 @@
 use anyhow::anyhow;
 use codec::Encode;
 use ismp::{
-	consensus::{ConsensusClientId, StateMachineHeight, StateMachineId},
+	consensus::{ConsensusClientId, ConsensusStateId, StateMachineHeight, StateMachineId},
 	events::Event,
 	router::{GetResponse, Request},
 };
@@ -75,7 +75,7 @@ use jsonrpsee::{
 	proc_macros::rpc,
 	types::{ErrorObject, ErrorObjectOwned},
 };
-use pallet_ismp::{child_trie::CHILD_TRIE_PREFIX, offchain::LeafIndexQuery};
+use pallet_ismp::{child_trie::CHILD_TRIE_PREFIX, offchain::LeafIndexQuery, RequestFee};
 use pallet_ismp_runtime_api::IsmpRuntimeApi;
 use polkadot_sdk::*;
 use sc_client_api::{Backend, BlockBackend, ChildInfo, ProofProvider, StateBackend};
@@ -190,6 +190,15 @@ where
 	#[method(name = "ismp_queryStateMachineLatestHeight")]
 	fn query_state_machine_latest_height(&self, id: StateMachineId) -> RpcResult<u64>;
 
+	/// Query the current fee, priority tip and pending fee bumps for an outgoing request
+	#[method(name = "ismp_queryRequestFee")]
+	fn query_request_fee(&self, commitment: H256) -> RpcResult<RequestFee<u128>>;
+
+	/// Query the timestamp in seconds at which a consensus client expires, unless it is updated
+	/// before then
+	#[method(name = "ismp_queryConsensusExpiry")]
+	fn query_consensus_expiry(&self, consensus_state_id: ConsensusStateId) -> RpcResult<u64>;
+
 	/// Query ISMP Events that were deposited in a series of blocks
 	/// Using String keys because HashMap fails to deserialize when key is not a String
 	#[method(name = "ismp_queryEvents")]
@@ -369,6 +378,26 @@ where
 		})
 	}
 
+	fn query_request_fee(&self, commitment: H256) -> RpcResult<RequestFee<u128>> {
+		let api = self.client.runtime_api();
+		let at = self.client.info().best_hash;
+		api.request_fee(at, commitment)
+			.map_err(|e| {
+				runtime_error_into_rpc_error(format!("Error fetching request fee: {e:?}"))
+			})?
+			.ok_or_else(|| runtime_error_into_rpc_error("Request fee not found"))
+	}
+
+	fn query_consensus_expiry(&self, consensus_state_id: ConsensusStateId) -> RpcResult<u64> {
+		let api = self.client.runtime_api();
+		let at = self.client.info().best_hash;
+		api.consensus_expiry(at, consensus_state_id)
+			.map_err(|e| {
+				runtime_error_into_rpc_error(format!("Error fetching consensus expiry: {e:?}"))
+			})?
+			.ok_or_else(|| runtime_error_into_rpc_error("Consensus client not found"))
+	}
+
 	fn query_events(
 		&self,
 		from: BlockNumberOrHash<Block::Hash>,
@@ -22,15 +22,17 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use ismp::{
-	consensus::{ConsensusClientId, StateMachineHeight, StateMachineId},
+	consensus::{ConsensusClientId, ConsensusStateId, StateMachineHeight, StateMachineId},
 	host::StateMachine,
 	router::{GetResponse, Request},
 };
+use pallet_ismp::RequestFee;
 use polkadot_sdk::*;
 use primitive_types::H256;
 
 sp_api::decl_runtime_apis! {
 	/// Required runtime APIs needed for client subsystems like the RPC
+	#[api_version(3)]
 	pub trait IsmpRuntimeApi<Hash: codec::Codec> {
 		/// Should return the host's state machine identifier
 		fn host_state_machine() -> StateMachine;
@@ -58,5 +60,14 @@ sp_api::decl_runtime_apis! {
 
 		/// Fetch the responses for the given commitments.
 		fn responses(response_commitments: Vec<H256>) -> Vec<GetResponse>;
+
+		/// Fetch the current fee, priority tip and pending fee bumps for an outgoing request.
+		#[api_version(2)]
+		fn request_fee(request_commitment: H256) -> Option<RequestFee<u128>>;
+
+		/// Return the timestamp in seconds at which the consensus client expires, unless it is
+		/// updated before then.
+		#[api_version(3)]
+		fn consensus_expiry(consensus_state_id: ConsensusStateId) -> Option<u64>;
 	}
 }
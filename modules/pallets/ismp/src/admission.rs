@@ -0,0 +1,219 @@
+// Copyright (c) 2025 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admission control for incoming requests
+//!
+//! Every request routed through pallet-ismp is checked against the [`AdmissionQuota`]s configured
+//! for its source chain and its destination module before the module's `on_accept` runs. Quotas
+//! bound the number of requests and request body bytes admitted per window of blocks, so a single
+//! noisy application can't starve everyone else of block weight.
+//!
+//! Quotas are only consumed by requests that the destination module accepts. A refused request
+//! has its receipt removed by the ISMP handler, so it can be delivered again once the window
+//! rolls over.
+use polkadot_sdk::*;
+
+use crate::{AdmissionQuotas, AdmissionUsage, Config, Pallet};
+use alloc::{boxed::Box, format, vec::Vec};
+use codec::{Decode, DecodeWithMemTracking, Encode};
+use core::marker::PhantomData;
+use frame_support::{traits::Get, weights::Weight};
+use ismp::{
+	error::Error as IsmpError,
+	host::StateMachine,
+	module::IsmpModule,
+	router::{GetResponse, PostRequest, Request},
+};
+use sp_runtime::traits::UniqueSaturatedInto;
+
+/// Identifies the traffic an [`AdmissionQuota`] applies to
+#[derive(
+	Debug,
+	Clone,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	scale_info::TypeInfo,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+)]
+pub enum QuotaKey {
+	/// All requests originating from this state machine
+	SourceChain(StateMachine),
+	/// All requests addressed to this module id
+	DestinationModule(Vec<u8>),
+}
+
+/// Limits on the requests admitted for a [`QuotaKey`] within a window of blocks
+#[derive(
+	Debug, Clone, Encode, Decode, DecodeWithMemTracking, scale_info::TypeInfo, PartialEq, Eq,
+)]
+pub struct AdmissionQuota {
+	/// Length of the window in blocks, a window of 1 makes this a per-block quota
+	pub window: u32,
+	/// Maximum number of requests admitted per window
+	pub max_requests: Option<u32>,
+	/// Maximum number of request body bytes admitted per window
+	pub max_bytes: Option<u64>,
+}
+
+/// Quota consumed by a [`QuotaKey`] within its current window
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	Default,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	scale_info::TypeInfo,
+	PartialEq,
+	Eq,
+)]
+pub struct QuotaUsage {
+	/// Index of the window this usage was recorded in, i.e `block_number / window`
+	pub window: u64,
+	/// Number of requests admitted in the window
+	pub requests: u32,
+	/// Number of request body bytes admitted in the window
+	pub bytes: u64,
+}
+
+/// Why a request was refused by admission control
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdmissionError {
+	/// The request count quota for `key` is exhausted for the current window
+	RequestQuotaExhausted {
+		/// The exhausted quota
+		key: QuotaKey,
+		/// Requests admitted per window
+		limit: u32,
+	},
+	/// The byte quota for `key` can't fit the request in the current window
+	ByteQuotaExhausted {
+		/// The exhausted quota
+		key: QuotaKey,
+		/// Bytes left in the current window
+		remaining: u64,
+		/// Size of the request body
+		required: u64,
+	},
+}
+
+impl core::fmt::Display for AdmissionError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			AdmissionError::RequestQuotaExhausted { key, limit } => {
+				write!(f, "request quota for {key:?} exhausted: {limit} requests per window")
+			},
+			AdmissionError::ByteQuotaExhausted { key, remaining, required } => write!(
+				f,
+				"byte quota for {key:?} exhausted: have {remaining} bytes, needed {required}"
+			),
+		}
+	}
+}
+
+/// Returns the quota keys a request is checked against
+fn quota_keys(request: &PostRequest) -> [QuotaKey; 2] {
+	[QuotaKey::SourceChain(request.source), QuotaKey::DestinationModule(request.to.clone())]
+}
+
+impl<T: Config> Pallet<T> {
+	/// Returns the usage of `key` for the window containing the current block. Usage recorded in
+	/// an earlier window is discarded.
+	fn current_usage(key: &QuotaKey, quota: &AdmissionQuota) -> QuotaUsage {
+		let block: u64 = frame_system::Pallet::<T>::block_number().unique_saturated_into();
+		let window = block / quota.window.max(1) as u64;
+		let usage = AdmissionUsage::<T>::get(key);
+		if usage.window == window {
+			usage
+		} else {
+			QuotaUsage { window, ..Default::default() }
+		}
+	}
+
+	/// Checks `request` against every quota that applies to it, without consuming any of them.
+	pub fn check_admission(request: &PostRequest) -> Result<(), AdmissionError> {
+		let required = request.body.len() as u64;
+		for key in quota_keys(request) {
+			let Some(quota) = AdmissionQuotas::<T>::get(&key) else { continue };
+			let usage = Self::current_usage(&key, &quota);
+
+			if let Some(limit) = quota.max_requests {
+				if usage.requests >= limit {
+					return Err(AdmissionError::RequestQuotaExhausted { key, limit });
+				}
+			}
+
+			if let Some(limit) = quota.max_bytes {
+				let remaining = limit.saturating_sub(usage.bytes);
+				if required > remaining {
+					return Err(AdmissionError::ByteQuotaExhausted { key, remaining, required });
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Charges an accepted `request` against every quota that applies to it.
+	pub fn record_admission(request: &PostRequest) {
+		let bytes = request.body.len() as u64;
+		for key in quota_keys(request) {
+			let Some(quota) = AdmissionQuotas::<T>::get(&key) else { continue };
+			let mut usage = Self::current_usage(&key, &quota);
+			usage.requests = usage.requests.saturating_add(1);
+			usage.bytes = usage.bytes.saturating_add(bytes);
+			AdmissionUsage::<T>::insert(key, usage);
+		}
+	}
+}
+
+/// Wraps the modules returned by the runtime's router, so that every incoming request passes
+/// admission control before it is handed to the module.
+pub(crate) struct AdmissionControlledModule<T> {
+	inner: Box<dyn IsmpModule>,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config> AdmissionControlledModule<T> {
+	pub fn new(inner: Box<dyn IsmpModule>) -> Self {
+		Self { inner, _phantom: PhantomData }
+	}
+}
+
+impl<T: Config> IsmpModule for AdmissionControlledModule<T> {
+	fn on_accept(&self, request: PostRequest) -> Result<Weight, anyhow::Error> {
+		Pallet::<T>::check_admission(&request).map_err(|err| {
+			IsmpError::Custom(format!("Request refused by admission control: {err}"))
+		})?;
+
+		let weight = self.inner.on_accept(request.clone())?;
+		Pallet::<T>::record_admission(&request);
+
+		Ok(weight.saturating_add(<T as frame_system::Config>::DbWeight::get().reads_writes(4, 2)))
+	}
+
+	fn on_response(&self, response: GetResponse) -> Result<Weight, anyhow::Error> {
+		self.inner.on_response(response)
+	}
+
+	fn on_timeout(&self, request: Request) -> Result<Weight, anyhow::Error> {
+		self.inner.on_timeout(request)
+	}
+}
@@ -16,7 +16,10 @@
 //! Implementation for the low-level ISMP Dispatcher
 use polkadot_sdk::*;
 
-use crate::{offchain::LeafIndexAndPos, Config, Event, Pallet, RELAYER_FEE_ACCOUNT};
+use crate::{
+	admission::AdmissionControlledModule, offchain::LeafIndexAndPos, ordered::OrderedChannelModule,
+	Config, Event, OrderedModules, Pallet, RELAYER_FEE_ACCOUNT,
+};
 use alloc::{boxed::Box, format, vec::Vec};
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
@@ -153,7 +156,8 @@ where
 
 /// Router installed by the pallet's [`IsmpHost`]. Intercepts the well known
 /// protocol withdrawal id and otherwise defers to the runtime's configured
-/// router.
+/// router, placing the returned module behind admission control and, for
+/// ordered modules, an ordered channel in front of that.
 pub(crate) struct IsmpHostRouter<T> {
 	inner: Box<dyn IsmpRouter>,
 	_phantom: PhantomData<T>,
@@ -163,6 +167,15 @@ impl<T: Config> IsmpHostRouter<T> {
 	pub fn new(inner: Box<dyn IsmpRouter>) -> Self {
 		Self { inner, _phantom: PhantomData }
 	}
+
+	/// The module for `id` behind admission control, without an ordered channel. Ordered
+	/// channels hand their buffered requests to this module.
+	pub fn admitted_module_for_id(
+		&self,
+		id: Vec<u8>,
+	) -> Result<Box<dyn IsmpModule>, anyhow::Error> {
+		Ok(Box::new(AdmissionControlledModule::<T>::new(self.inner.module_for_id(id)?)))
+	}
 }
 
 impl<T: Config> IsmpRouter for IsmpHostRouter<T> {
@@ -171,7 +184,12 @@ impl<T: Config> IsmpRouter for IsmpHostRouter<T> {
 			return Ok(Box::new(HyperbridgeWithdrawalModule::<T>::default()));
 		}
 
-		self.inner.module_for_id(id)
+		let ordered = OrderedModules::<T>::get(&id);
+		let module = self.admitted_module_for_id(id)?;
+		if ordered {
+			return Ok(Box::new(OrderedChannelModule::<T>::new(module)));
+		}
+		Ok(module)
 	}
 }
 
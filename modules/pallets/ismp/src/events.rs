@@ -63,6 +63,11 @@ impl<T: Config> TryFrom<PalletEvent<T>> for ismp::events::Event {
 			PalletEvent::ConsensusClientFrozen { .. } |
 			PalletEvent::Errors { .. } |
 			PalletEvent::RelayerFeeWithdrawn { .. } |
+			PalletEvent::MessageFunded { .. } |
+			PalletEvent::PriorityTipSet { .. } |
+			PalletEvent::OrderedRequestBuffered { .. } |
+			PalletEvent::OrderedSequencesSkipped { .. } |
+			PalletEvent::OrderedDeliveryFailed { .. } |
 			PalletEvent::__Ignore(_, _) => Err(()),
 		}
 	}
@@ -197,6 +197,12 @@ where
 						.ok()
 						.and_then(|sig| sig.verify_and_get_sr25519_pubkey(&data, None).ok())
 				},
+				Message::AggregatedRequest(msg) => {
+					let data = sp_io::hashing::keccak_256(&msg.requests().encode());
+					Signature::decode(&mut &msg.signer[..])
+						.ok()
+						.and_then(|sig| sig.verify_and_get_sr25519_pubkey(&data, None).ok())
+				},
 				_ => None,
 			};
 
@@ -239,6 +239,7 @@ impl<T: Config> IsmpHost for Pallet<T> {
 		let meta = child_trie::RequestCommitments::<T>::get(hash)
 			.ok_or_else(|| Error::Custom("Request Commitment not found".to_string()))?;
 		child_trie::RequestCommitments::<T>::remove(hash);
+		Pallet::<T>::clear_fee_bumps(hash);
 		Ok(meta.encode())
 	}
 
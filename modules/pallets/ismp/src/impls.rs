@@ -21,12 +21,14 @@ use crate::{
 	dispatcher::{FeeMetadata, RequestMetadata},
 	fee_handler::FeeHandler,
 	offchain::{self, ForkIdentifier, Leaf, LeafIndexAndPos, OffchainDBProvider},
-	Config, Error, Event, Pallet,
+	Config, ConsensusClientUpdateTime, Error, Event, FeeBumps, Pallet, PriorityTips, RequestFee,
+	UnbondingPeriod,
 };
 use alloc::{string::ToString, vec, vec::Vec};
 use codec::Decode;
 use frame_system::Phase;
 use ismp::{
+	consensus::ConsensusStateId,
 	events,
 	handlers::{handle_incoming_message, MessageResult},
 	messaging::{hash_request, Message, MessageWithWeight},
@@ -203,6 +205,26 @@ impl<T: Config> Pallet<T> {
 	pub fn responses(commitments: Vec<H256>) -> Vec<GetResponse> {
 		commitments.into_iter().filter_map(|cm| Self::response(cm)).collect()
 	}
+
+	/// Returns the current fee, priority tip and unclaimed fee bumps for an outgoing request.
+	pub fn request_fee(commitment: H256) -> Option<RequestFee<T::Balance>> {
+		let metadata = RequestCommitments::<T>::get(commitment)?;
+		let pending_bumps = if metadata.claimed { None } else { FeeBumps::<T>::get(commitment) };
+
+		Some(RequestFee {
+			fee: metadata.fee.fee,
+			tip: PriorityTips::<T>::get(commitment),
+			pending_bumps,
+		})
+	}
+
+	/// Returns the timestamp in seconds at which the consensus client expires, unless it is
+	/// updated before then. That is its last update time plus its unbonding period.
+	pub fn consensus_expiry(consensus_state_id: ConsensusStateId) -> Option<u64> {
+		let last_update = ConsensusClientUpdateTime::<T>::get(consensus_state_id)?;
+		let unbonding_period = UnbondingPeriod::<T>::get(consensus_state_id)?;
+		Some(last_update.saturating_add(unbonding_period))
+	}
 }
 
 impl<T: Config> ForkIdentifier<T> for Pallet<T> {
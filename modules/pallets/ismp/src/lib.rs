@@ -20,6 +20,7 @@
 extern crate alloc;
 extern crate core;
 
+pub mod admission;
 pub mod child_trie;
 pub mod dispatcher;
 pub mod errors;
@@ -29,6 +30,7 @@ pub mod host;
 mod impls;
 pub mod migrations;
 pub mod offchain;
+pub mod ordered;
 mod utils;
 pub mod weights;
 use crate::offchain::Leaf;
@@ -45,9 +47,11 @@ pub mod pallet {
 
 	use crate::{
 		child_trie::{RequestCommitments, ResponseCommitments, CHILD_TRIE_PREFIX},
+		dispatcher::IsmpHostRouter,
 		errors::HandlingError,
 		fee_handler::FeeHandler,
 	};
+	pub use admission::{AdmissionQuota, QuotaKey, QuotaUsage};
 	use alloc::collections::BTreeMap;
 	use codec::{Codec, Encode};
 	use core::fmt::Debug;
@@ -67,8 +71,9 @@ pub mod pallet {
 		handlers,
 		host::{IsmpHost, StateMachine},
 		messaging::{CreateConsensusState, Message},
-		router::IsmpRouter,
+		router::{IsmpRouter, PostRequest},
 	};
+	pub use ordered::{ChannelId, OutgoingChannel};
 	use sp_core::{storage::ChildInfo, H256};
 	use sp_runtime::{
 		traits::{AccountIdConversion, AtLeast32BitUnsigned},
@@ -304,6 +309,68 @@ pub mod pallet {
 	#[pallet::getter(fn responded)]
 	pub type Responded<T: Config> = StorageMap<_, Identity, H256, bool, ValueQuery>;
 
+	/// Priority tips attached to outgoing messages, keyed by the message commitment. A tip is
+	/// paid out to relayers as part of the message fee, it is tracked separately so relayers can
+	/// decide which messages to deliver first.
+	#[pallet::storage]
+	pub type PriorityTips<T: Config> = StorageMap<_, Identity, H256, T::Balance, ValueQuery>;
+
+	/// Fees added to outgoing messages after they were dispatched, keyed by the message
+	/// commitment. Entries are removed once the fee is claimed or refunded.
+	#[pallet::storage]
+	pub type FeeBumps<T: Config> = StorageMap<_, Identity, H256, FeeBump<T::Balance>, OptionQuery>;
+
+	/// Admission quotas for incoming requests, keyed by the source chain or destination module
+	/// they apply to. See [`admission`] for how they are enforced.
+	#[pallet::storage]
+	pub type AdmissionQuotas<T: Config> =
+		StorageMap<_, Blake2_128Concat, QuotaKey, AdmissionQuota, OptionQuery>;
+
+	/// Quota consumed by each [`QuotaKey`] within its current window
+	#[pallet::storage]
+	pub type AdmissionUsage<T: Config> =
+		StorageMap<_, Blake2_128Concat, QuotaKey, QuotaUsage, ValueQuery>;
+
+	/// Modules that receive requests through ordered channels, see [`ordered`]
+	#[pallet::storage]
+	pub type OrderedModules<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	/// Sequence number of the next request to be delivered on an incoming ordered channel
+	#[pallet::storage]
+	pub type NextSequence<T: Config> = StorageMap<_, Blake2_128Concat, ChannelId, u64, ValueQuery>;
+
+	/// Requests that arrived ahead of their turn on an incoming ordered channel, keyed by their
+	/// sequence number
+	#[pallet::storage]
+	pub type BufferedRequests<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ChannelId,
+		Twox64Concat,
+		u64,
+		PostRequest,
+		OptionQuery,
+	>;
+
+	/// Number of requests in [`BufferedRequests`] for each channel
+	#[pallet::storage]
+	pub type BufferedRequestCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, ChannelId, u32, ValueQuery>;
+
+	/// Number of requests in [`BufferedRequests`] across all channels
+	#[pallet::storage]
+	pub type TotalBufferedRequests<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Sender side state of outgoing ordered channels, keyed by `(dest, from, to)`
+	#[pallet::storage]
+	pub type OutgoingChannels<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		(StateMachine, Vec<u8>, Vec<u8>),
+		OutgoingChannel,
+		ValueQuery,
+	>;
+
 	/// Latest nonce for messages sent from this chain
 	#[pallet::storage]
 	#[pallet::getter(fn nonce)]
@@ -448,35 +515,7 @@ pub mod pallet {
 		) -> DispatchResult {
 			let account = ensure_signed(origin)?;
 
-			let metadata = match message.commitment {
-				MessageCommitment::Request(commitment) => RequestCommitments::<T>::get(commitment),
-				MessageCommitment::Response(commitment) =>
-					ResponseCommitments::<T>::get(commitment),
-			};
-
-			let Some(mut metadata) = metadata else {
-				return Err(Error::<T>::MessageNotFound.into());
-			};
-
-			T::Currency::transfer(
-				&account,
-				&RELAYER_FEE_ACCOUNT.into_account_truncating(),
-				message.amount,
-				Preservation::Expendable,
-			)?;
-
-			match message.commitment {
-				MessageCommitment::Request(commiment) => {
-					metadata.fee.fee += message.amount;
-					RequestCommitments::<T>::insert(commiment, metadata);
-				},
-				MessageCommitment::Response(commiment) => {
-					metadata.fee.fee += message.amount;
-					ResponseCommitments::<T>::insert(commiment, metadata);
-				},
-			};
-
-			Ok(())
+			Self::fund(&account, message)
 		}
 
 		/// Set the number of state commitments retained per chain, overriding
@@ -505,6 +544,128 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Add more funds to a batch of messages (requests or responses) in a single call. This
+		/// is all-or-nothing: if any of the messages can't be funded, none of them are.
+		///
+		/// Should not be called on messages that have been completed (delivered or timed-out) as
+		/// those funds will be lost forever.
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().writes(5 * messages.len() as u64))]
+		#[pallet::call_index(6)]
+		#[frame_support::transactional]
+		pub fn fund_messages(
+			origin: OriginFor<T>,
+			messages: Vec<FundMessageParams<T::Balance>>,
+		) -> DispatchResult {
+			let account = ensure_signed(origin)?;
+
+			for message in messages {
+				Self::fund(&account, message)?;
+			}
+
+			Ok(())
+		}
+
+		/// Raise the priority tip on a message (request or response). `tip` is the new total tip,
+		/// only the difference from the current tip is charged and it is added to the message fee,
+		/// so relayers are paid the tip on delivery like any other fee.
+		///
+		/// Emits [`Event::PriorityTipSet`] if successful.
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().reads_writes(2, 6))]
+		#[pallet::call_index(7)]
+		pub fn set_priority_tip(
+			origin: OriginFor<T>,
+			commitment: MessageCommitment,
+			tip: T::Balance,
+		) -> DispatchResult {
+			let account = ensure_signed(origin)?;
+
+			let hash = match commitment {
+				MessageCommitment::Request(hash) | MessageCommitment::Response(hash) => hash,
+			};
+			let current = PriorityTips::<T>::get(hash);
+			ensure!(tip > current, Error::<T>::TipNotIncreased);
+
+			Self::fund(&account, FundMessageParams { commitment, amount: tip - current })?;
+			PriorityTips::<T>::insert(hash, tip);
+
+			Self::deposit_event(Event::<T>::PriorityTipSet { commitment: hash, tip });
+
+			Ok(())
+		}
+
+		/// Set or remove admission quotas for incoming requests. A `None` quota removes the
+		/// quota for that key. Usage recorded against a key is reset whenever its quota changes.
+		///
+		/// The dispatch origin for this call must be `T::AdminOrigin`.
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().writes(2 * quotas.len() as u64))]
+		#[pallet::call_index(8)]
+		pub fn set_admission_quotas(
+			origin: OriginFor<T>,
+			quotas: BTreeMap<QuotaKey, Option<AdmissionQuota>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				quotas.values().flatten().all(|quota| quota.window > 0),
+				Error::<T>::InvalidAdmissionQuota
+			);
+			for (key, quota) in quotas {
+				AdmissionUsage::<T>::remove(&key);
+				match quota {
+					Some(quota) => AdmissionQuotas::<T>::insert(key, quota),
+					None => AdmissionQuotas::<T>::remove(key),
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Register or unregister modules that receive requests through ordered channels.
+		///
+		/// The dispatch origin for this call must be `T::AdminOrigin`.
+		#[pallet::weight(<T as frame_system::Config>::DbWeight::get().writes(modules.len() as u64))]
+		#[pallet::call_index(9)]
+		pub fn set_ordered_modules(
+			origin: OriginFor<T>,
+			modules: BTreeMap<Vec<u8>, bool>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			for (module, ordered) in modules {
+				if ordered {
+					OrderedModules::<T>::insert(module, true);
+				} else {
+					OrderedModules::<T>::remove(module);
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Deliver any buffered requests on an ordered channel whose turn has come, skipping
+		/// gaps that have timed out. Gaps are otherwise only skipped when the next request on the
+		/// channel arrives. Also retries a buffered request its module previously failed to
+		/// handle.
+		///
+		/// Charged for the worst case of a full channel buffer, the unused weight of requests
+		/// that weren't drained is refunded.
+		#[pallet::weight(weight().saturating_mul(ordered::MAX_BUFFERED_REQUESTS as u64))]
+		#[pallet::call_index(10)]
+		pub fn drain_ordered_channel(
+			origin: OriginFor<T>,
+			channel: ChannelId,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			ensure!(OrderedModules::<T>::get(&channel.dest_module), Error::<T>::ModuleNotOrdered);
+			let module = IsmpHostRouter::<T>::new(Box::new(T::Router::default()))
+				.admitted_module_for_id(channel.dest_module.clone())
+				.map_err(|_| Error::<T>::ModuleNotOrdered)?;
+			let weight = Self::drain_channel(&channel, module.as_ref());
+
+			Ok(Some(weight).into())
+		}
 	}
 
 	/// Pallet Events
@@ -580,6 +741,54 @@ pub mod pallet {
 			/// The withdrawal beneficiary
 			account: T::AccountId,
 		},
+		/// The fee on an outgoing message was raised
+		MessageFunded {
+			/// Message commitment
+			commitment: H256,
+			/// Amount the fee was raised by
+			amount: <T as Config>::Balance,
+			/// New total fee for the message
+			fee: <T as Config>::Balance,
+		},
+		/// The priority tip on an outgoing message was raised
+		PriorityTipSet {
+			/// Message commitment
+			commitment: H256,
+			/// New total priority tip for the message
+			tip: <T as Config>::Balance,
+		},
+		/// A request arrived ahead of its turn on an ordered channel and was buffered
+		OrderedRequestBuffered {
+			/// The ordered channel
+			channel: ChannelId,
+			/// Sequence number of the request
+			sequence: u64,
+		},
+		/// Sequence numbers on an ordered channel were skipped because they timed out
+		OrderedSequencesSkipped {
+			/// The ordered channel
+			channel: ChannelId,
+			/// First skipped sequence number
+			from: u64,
+			/// Sequence number delivery resumes from
+			to: u64,
+		},
+		/// The destination module failed to handle a buffered request on an ordered channel. The
+		/// request stays buffered and the channel waits on it until it is delivered or times out.
+		OrderedDeliveryFailed {
+			/// The ordered channel
+			channel: ChannelId,
+			/// Sequence number of the request
+			sequence: u64,
+		},
+		/// A buffered request on an ordered channel timed out before its turn came and was
+		/// dropped
+		OrderedRequestExpired {
+			/// The ordered channel
+			channel: ChannelId,
+			/// Sequence number of the request
+			sequence: u64,
+		},
 	}
 
 	/// Pallet errors
@@ -599,6 +808,12 @@ pub mod pallet {
 		ErrorChargingFee,
 		/// A state machine commitment cap must be non-zero
 		InvalidCommitmentCap,
+		/// A priority tip can only be raised
+		TipNotIncreased,
+		/// An admission quota window must be non-zero
+		InvalidAdmissionQuota,
+		/// The module does not receive requests through ordered channels
+		ModuleNotOrdered,
 	}
 
 	/// This allows users execute ISMP datagrams for free. Use with caution.
@@ -687,6 +902,11 @@ pub mod pallet {
 						.iter()
 						.map(|request| hash_request::<Pallet<T>>(request))
 						.collect::<Vec<_>>(),
+					Message::AggregatedRequest(message) => message
+						.requests()
+						.into_iter()
+						.map(|post| hash_request::<Pallet<T>>(&Request::Post(post)))
+						.collect::<Vec<_>>(),
 				})
 				.collect::<Vec<_>>();
 			tags.sort();
@@ -730,6 +950,63 @@ pub mod pallet {
 	}
 
 	impl<T: Config> Pallet<T> {
+		/// Transfer `message.amount` from `account` to the relayer fee account and add it to the
+		/// fee of the message, recording it as a [`FeeBump`].
+		pub fn fund(
+			account: &T::AccountId,
+			message: FundMessageParams<T::Balance>,
+		) -> DispatchResult {
+			let metadata = match message.commitment {
+				MessageCommitment::Request(commitment) => RequestCommitments::<T>::get(commitment),
+				MessageCommitment::Response(commitment) =>
+					ResponseCommitments::<T>::get(commitment),
+			};
+
+			let Some(mut metadata) = metadata else {
+				return Err(Error::<T>::MessageNotFound.into());
+			};
+
+			T::Currency::transfer(
+				account,
+				&RELAYER_FEE_ACCOUNT.into_account_truncating(),
+				message.amount,
+				Preservation::Expendable,
+			)?;
+
+			metadata.fee.fee += message.amount;
+			let commitment = match message.commitment {
+				MessageCommitment::Request(commitment) => {
+					RequestCommitments::<T>::insert(commitment, metadata.clone());
+					commitment
+				},
+				MessageCommitment::Response(commitment) => {
+					ResponseCommitments::<T>::insert(commitment, metadata.clone());
+					commitment
+				},
+			};
+
+			FeeBumps::<T>::mutate(commitment, |bump| {
+				let bump = bump.get_or_insert_with(Default::default);
+				bump.total += message.amount;
+				bump.count += 1;
+			});
+
+			Self::deposit_event(Event::<T>::MessageFunded {
+				commitment,
+				amount: message.amount,
+				fee: metadata.fee.fee,
+			});
+
+			Ok(())
+		}
+
+		/// Removes the priority tip and fee bumps recorded for a message, called once its fee has
+		/// been claimed by a relayer or refunded to the payer.
+		pub fn clear_fee_bumps(commitment: H256) {
+			PriorityTips::<T>::remove(commitment);
+			FeeBumps::<T>::remove(commitment);
+		}
+
 		/// Number of state commitments retained for `id`: the
 		/// [`StateMachineCommitmentCap`] override if set, otherwise
 		/// [`MAX_STATE_MACHINE_COMMITMENTS`].
@@ -0,0 +1,374 @@
+// Copyright (c) 2025 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ordered channels on top of ISMP requests
+//!
+//! ISMP requests are delivered in whatever order relayers submit them. Modules registered as
+//! ordered through [`Pallet::set_ordered_modules`](crate::Pallet::set_ordered_modules) instead
+//! receive requests strictly in sequence for every `(source, source_module, dest_module)`
+//! [`ChannelId`]. The sequence number is carried in the first 8 bytes of the request body as a
+//! big-endian `u64`, see [`encode_sequenced`]. On EVM this is `abi.encodePacked(uint64(sequence),
+//! body)`. Substrate senders can use [`Pallet::dispatch_ordered`].
+//!
+//! Requests that arrive ahead of their turn are buffered and handed to the module once the gap
+//! is filled. A gap is skipped once the lowest buffered request has timed out. Senders keep
+//! timeouts non-decreasing along a channel, so every missing request must have timed out as well
+//! and can no longer be delivered. The missing requests are never observed directly, this is a
+//! deliberate liveness trade-off: a request stuck behind a lost sequence waits at most until its
+//! own timeout. Only requests with a timeout can be buffered, otherwise a gap could never be
+//! skipped.
+//!
+//! If the module fails to handle a buffered request once its turn comes, the request stays in the
+//! buffer and the channel does not advance. It is retried on the next delivery on the channel or
+//! through [`Pallet::drain_ordered_channel`](crate::Pallet::drain_ordered_channel).
+//!
+//! The ISMP handler stores the receipt of a buffered request when it is accepted into the buffer.
+//! If the request then times out in the buffer, the receipt is deleted again so that the request
+//! can be timed out on its source.
+use polkadot_sdk::*;
+
+use crate::{
+	BufferedRequestCount, BufferedRequests, Config, Event, NextSequence, OutgoingChannels, Pallet,
+	TotalBufferedRequests,
+};
+use alloc::{boxed::Box, format, vec::Vec};
+use codec::{Decode, DecodeWithMemTracking, Encode};
+use core::marker::PhantomData;
+use frame_support::{
+	storage::with_transaction_opaque_err,
+	traits::{Get, UnixTime},
+	weights::Weight,
+};
+use ismp::{
+	dispatcher::{DispatchPost, DispatchRequest, FeeMetadata, IsmpDispatcher},
+	error::Error as IsmpError,
+	host::{IsmpHost, StateMachine},
+	module::IsmpModule,
+	router::{GetResponse, PostRequest, Request},
+};
+use sp_core::H256;
+use sp_runtime::TransactionOutcome;
+
+/// Maximum number of out-of-order requests buffered per channel. Requests beyond this are
+/// refused and can be delivered again once the buffer drains.
+pub const MAX_BUFFERED_REQUESTS: u32 = 64;
+
+/// Maximum number of out-of-order requests buffered across all channels
+pub const MAX_TOTAL_BUFFERED_REQUESTS: u32 = 4096;
+
+/// Identifies an ordered channel
+#[derive(
+	Debug,
+	Clone,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	scale_info::TypeInfo,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+)]
+pub struct ChannelId {
+	/// The state machine requests on this channel originate from
+	pub source: StateMachine,
+	/// The sending module
+	pub source_module: Vec<u8>,
+	/// The receiving module
+	pub dest_module: Vec<u8>,
+}
+
+impl From<&PostRequest> for ChannelId {
+	fn from(request: &PostRequest) -> Self {
+		ChannelId {
+			source: request.source,
+			source_module: request.from.clone(),
+			dest_module: request.to.clone(),
+		}
+	}
+}
+
+/// Sender side state of an ordered channel
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	Default,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	scale_info::TypeInfo,
+	PartialEq,
+	Eq,
+)]
+pub struct OutgoingChannel {
+	/// Sequence number of the next request
+	pub next_sequence: u64,
+	/// Timeout timestamp of the last request, `0` means it never times out
+	pub last_timeout: u64,
+}
+
+/// Prefixes `body` with its channel sequence number
+pub fn encode_sequenced(sequence: u64, body: &[u8]) -> Vec<u8> {
+	let mut encoded = sequence.to_be_bytes().to_vec();
+	encoded.extend_from_slice(body);
+	encoded
+}
+
+/// Splits a request body into its channel sequence number and the actual payload
+pub fn decode_sequenced(body: &[u8]) -> Option<(u64, &[u8])> {
+	if body.len() < 8 {
+		return None;
+	}
+	let (sequence, payload) = body.split_at(8);
+	Some((u64::from_be_bytes(sequence.try_into().ok()?), payload))
+}
+
+/// Returns `request` with the sequence number stripped from its body
+fn unwrap_sequenced(mut request: PostRequest) -> PostRequest {
+	request.body = request.body.split_off(8);
+	request
+}
+
+impl<T: Config> Pallet<T> {
+	/// Dispatch a post request on the ordered channel `(host, dispatch.from, dispatch.to)` to
+	/// `dispatch.dest`. The timeout may not be earlier than that of the previous request on the
+	/// channel, otherwise the receiver would be unable to tell when a gap can be skipped.
+	///
+	/// Timed-out requests are handed back to the sending module with the sequence number still
+	/// prefixed to their body.
+	pub fn dispatch_ordered(
+		dispatch: DispatchPost,
+		fee: FeeMetadata<T::AccountId, T::Balance>,
+	) -> Result<H256, anyhow::Error> {
+		let key = (dispatch.dest, dispatch.from.clone(), dispatch.to.clone());
+		let mut channel = OutgoingChannels::<T>::get(&key);
+		let timeout = if dispatch.timeout == 0 {
+			0
+		} else {
+			<T::TimestampProvider as UnixTime>::now()
+				.as_secs()
+				.saturating_add(dispatch.timeout)
+		};
+		let monotonic = match (channel.next_sequence, channel.last_timeout, timeout) {
+			(0, _, _) => true,
+			(_, 0, timeout) => timeout == 0,
+			(_, _, 0) => true,
+			(_, last, timeout) => timeout >= last,
+		};
+		if !monotonic {
+			Err(IsmpError::Custom(format!(
+				"Ordered channel timeouts must not decrease: previous {}, got {timeout}",
+				channel.last_timeout
+			)))?
+		}
+
+		let body = encode_sequenced(channel.next_sequence, &dispatch.body);
+		let commitment = Pallet::<T>::default()
+			.dispatch_request(DispatchRequest::Post(DispatchPost { body, ..dispatch }), fee)?;
+
+		channel.next_sequence += 1;
+		channel.last_timeout = timeout;
+		OutgoingChannels::<T>::insert(key, channel);
+
+		Ok(commitment)
+	}
+
+	/// Hands buffered requests on `channel` to `module` for as long as they are in sequence,
+	/// skipping gaps that can no longer be filled. Requests that timed out in the buffer are
+	/// dropped instead of delivered. Draining stops at the first request the module fails to
+	/// handle, it stays buffered in its place and is retried on the next drain.
+	pub fn drain_channel(channel: &ChannelId, module: &dyn IsmpModule) -> Weight {
+		let db_weight = <T as frame_system::Config>::DbWeight::get();
+		let now = <T::TimestampProvider as UnixTime>::now();
+		let mut weight = Weight::zero();
+		loop {
+			let next = NextSequence::<T>::get(channel);
+			weight.saturating_accrue(db_weight.reads(2));
+			if let Some(request) = BufferedRequests::<T>::get(channel, next) {
+				if request.timed_out(now) {
+					Self::remove_buffered(channel, next);
+					Self::release_receipt(request);
+					weight.saturating_accrue(db_weight.reads_writes(3, 5));
+					Self::deposit_event(Event::<T>::OrderedRequestExpired {
+						channel: channel.clone(),
+						sequence: next,
+					});
+					continue;
+				}
+
+				// a failed attempt must not leave partial writes behind, the request is retried
+				let result = with_transaction_opaque_err(|| {
+					let result = module.on_accept(unwrap_sequenced(request));
+					if result.is_ok() {
+						TransactionOutcome::Commit(result)
+					} else {
+						TransactionOutcome::Rollback(result)
+					}
+				});
+				match result {
+					Ok(Ok(used)) => {
+						Self::remove_buffered(channel, next);
+						weight.saturating_accrue(used.saturating_add(db_weight.reads_writes(2, 4)));
+					},
+					_ => {
+						Self::deposit_event(Event::<T>::OrderedDeliveryFailed {
+							channel: channel.clone(),
+							sequence: next,
+						});
+						break;
+					},
+				}
+				continue;
+			}
+
+			// Nothing proves that the missing requests timed out, they may simply not have been
+			// relayed yet. This is the intended liveness trade-off: senders keep timeouts
+			// non-decreasing along a channel, so once the lowest buffered request has timed out,
+			// every request before it has timed out too and can no longer be delivered. A
+			// request stuck behind a lost sequence therefore waits at most until the lowest
+			// buffered one times out.
+			let lowest =
+				BufferedRequests::<T>::iter_prefix(channel).min_by_key(|(sequence, _)| *sequence);
+			weight.saturating_accrue(db_weight.reads(MAX_BUFFERED_REQUESTS as u64));
+			match lowest {
+				Some((sequence, request)) if request.timed_out(now) => {
+					NextSequence::<T>::insert(channel, sequence);
+					weight.saturating_accrue(db_weight.writes(1));
+					Self::deposit_event(Event::<T>::OrderedSequencesSkipped {
+						channel: channel.clone(),
+						from: next,
+						to: sequence,
+					});
+				},
+				_ => break,
+			}
+		}
+
+		weight
+	}
+
+	/// Removes the buffered request at `sequence` once it has been handled and moves the channel
+	/// past it
+	fn remove_buffered(channel: &ChannelId, sequence: u64) {
+		BufferedRequests::<T>::remove(channel, sequence);
+		BufferedRequestCount::<T>::mutate(channel, |count| *count = count.saturating_sub(1));
+		TotalBufferedRequests::<T>::mutate(|count| *count = count.saturating_sub(1));
+		NextSequence::<T>::insert(channel, sequence + 1);
+	}
+
+	/// Deletes the receipt the ISMP handler stored for a buffered request that will never reach
+	/// its module, so that it can be timed out on its source chain.
+	fn release_receipt(request: PostRequest) {
+		let _ = Pallet::<T>::default().delete_request_receipt(&Request::Post(request));
+	}
+
+	/// Buffers a request that arrived ahead of its turn on `channel`
+	fn buffer_request(
+		channel: ChannelId,
+		sequence: u64,
+		request: PostRequest,
+	) -> Result<(), anyhow::Error> {
+		if request.timeout_timestamp == 0 {
+			Err(IsmpError::Custom(format!(
+				"Sequence {sequence} on {channel:?} never times out and can only be delivered in order"
+			)))?
+		}
+		if request.timed_out(<T::TimestampProvider as UnixTime>::now()) {
+			Err(IsmpError::Custom(format!("Sequence {sequence} on {channel:?} has timed out")))?
+		}
+
+		let count = BufferedRequestCount::<T>::get(&channel);
+		if count >= MAX_BUFFERED_REQUESTS {
+			Err(IsmpError::Custom(format!("Ordered channel buffer is full for {channel:?}")))?
+		}
+		let total = TotalBufferedRequests::<T>::get();
+		if total >= MAX_TOTAL_BUFFERED_REQUESTS {
+			Err(IsmpError::Custom("Ordered channel buffers are full".into()))?
+		}
+
+		// Senders keep timeouts non-decreasing along a channel, a request that breaks this would
+		// let a gap be skipped while it can still be filled.
+		let misordered = BufferedRequests::<T>::iter_prefix(&channel).any(|(buffered, other)| {
+			(buffered < sequence && other.timeout_timestamp > request.timeout_timestamp) ||
+				(buffered > sequence && other.timeout_timestamp < request.timeout_timestamp)
+		});
+		if misordered {
+			Err(IsmpError::Custom(format!(
+				"Timeout of sequence {sequence} on {channel:?} is out of order with buffered requests"
+			)))?
+		}
+
+		BufferedRequests::<T>::insert(&channel, sequence, request);
+		BufferedRequestCount::<T>::insert(&channel, count + 1);
+		TotalBufferedRequests::<T>::put(total + 1);
+		Self::deposit_event(Event::<T>::OrderedRequestBuffered { channel, sequence });
+
+		Ok(())
+	}
+}
+
+/// Wraps modules registered as ordered, so that they receive requests in sequence. The inner
+/// module is already behind admission control, so buffered requests are only charged against
+/// admission quotas once they are delivered.
+pub(crate) struct OrderedChannelModule<T> {
+	inner: Box<dyn IsmpModule>,
+	_phantom: PhantomData<T>,
+}
+
+impl<T: Config> OrderedChannelModule<T> {
+	pub fn new(inner: Box<dyn IsmpModule>) -> Self {
+		Self { inner, _phantom: PhantomData }
+	}
+}
+
+impl<T: Config> IsmpModule for OrderedChannelModule<T> {
+	fn on_accept(&self, request: PostRequest) -> Result<Weight, anyhow::Error> {
+		let db_weight = <T as frame_system::Config>::DbWeight::get();
+		let Some((sequence, _)) = decode_sequenced(&request.body) else {
+			return Err(
+				IsmpError::Custom("Ordered request is missing its sequence number".into()).into()
+			);
+		};
+		let channel = ChannelId::from(&request);
+		let next = NextSequence::<T>::get(&channel);
+
+		if sequence < next {
+			Err(IsmpError::Custom(format!(
+				"Sequence {sequence} on {channel:?} was already delivered or skipped"
+			)))?
+		}
+
+		if sequence > next {
+			Pallet::<T>::buffer_request(channel, sequence, request)?;
+			return Ok(db_weight.reads_writes(3 + MAX_BUFFERED_REQUESTS as u64, 3));
+		}
+
+		let mut weight = self.inner.on_accept(unwrap_sequenced(request))?;
+		NextSequence::<T>::insert(&channel, next + 1);
+		weight.saturating_accrue(db_weight.reads_writes(1, 1));
+		weight.saturating_accrue(Pallet::<T>::drain_channel(&channel, self.inner.as_ref()));
+
+		Ok(weight)
+	}
+
+	fn on_response(&self, response: GetResponse) -> Result<Weight, anyhow::Error> {
+		self.inner.on_response(response)
+	}
+
+	fn on_timeout(&self, request: Request) -> Result<Weight, anyhow::Error> {
+		self.inner.on_timeout(request)
+	}
+}
@@ -88,6 +88,41 @@ pub struct FundMessageParams<Balance> {
 	pub amount: Balance,
 }
 
+/// Running total of the fees added to a message after it was dispatched
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	Default,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	scale_info::TypeInfo,
+	PartialEq,
+	Eq,
+)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct FeeBump<Balance> {
+	/// Sum of all the amounts the message has been funded with since dispatch
+	pub total: Balance,
+	/// Number of times the message has been funded since dispatch
+	pub count: u32,
+}
+
+/// The current fee attached to an outgoing request, as reported by the runtime api
+#[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Deserialize, serde::Serialize))]
+pub struct RequestFee<Balance> {
+	/// Total fee that will be paid out for delivering the request. This already includes the
+	/// priority tip and every fee bump.
+	pub fee: Balance,
+	/// Priority tip attached to the request
+	pub tip: Balance,
+	/// Fee bumps that have not yet been claimed by a relayer. This is `None` once the fee has
+	/// been claimed or if the request was never bumped.
+	pub pending_bumps: Option<FeeBump<Balance>>,
+}
+
 /// Receipt for a Response
 #[derive(Debug, Clone, Encode, Decode, scale_info::TypeInfo, PartialEq, Eq)]
 pub struct ResponseReceipt {
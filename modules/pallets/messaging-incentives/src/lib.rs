@@ -130,6 +130,12 @@ where
 				.iter()
 				.map(|p| core::cmp::max(p.body.len() as u32, 32))
 				.sum::<u32>(),
+			Message::AggregatedRequest(req) => req
+				.batches
+				.iter()
+				.flat_map(|batch| batch.requests.iter())
+				.map(|p| core::cmp::max(p.body.len() as u32, 32))
+				.sum::<u32>(),
 			_ => 0,
 		}
 	}
@@ -143,6 +149,8 @@ where
 				(&msg.signer, sp_io::hashing::keccak_256(&msg.requests.encode())),
 			Message::Response(msg) =>
 				(&msg.signer, sp_io::hashing::keccak_256(&msg.requests.encode())),
+			Message::AggregatedRequest(msg) =>
+				(&msg.signer, sp_io::hashing::keccak_256(&msg.requests().encode())),
 			_ => return None,
 		};
 		Signature::decode(&mut &signer[..])
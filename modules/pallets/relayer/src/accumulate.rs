@@ -153,7 +153,8 @@ where
 			match RequestCommitments::<T>::get(req) {
 				Some(mut leaf_meta) => {
 					leaf_meta.claimed = true;
-					RequestCommitments::<T>::insert(req, leaf_meta)
+					RequestCommitments::<T>::insert(req, leaf_meta);
+					pallet_ismp::Pallet::<T>::clear_fee_bumps(req);
 				},
 				// Unreachable
 				None => {},
@@ -452,7 +452,7 @@ impl ismp_beefy::BeefyClientConfig for Test {
 	}
 
 	fn allowed_proof_types() -> &'static [u8] {
-		&[ismp_beefy::PROOF_TYPE_NAIVE, ismp_beefy::PROOF_TYPE_SP1]
+		&[ismp_beefy::PROOF_TYPE_NAIVE, ismp_beefy::PROOF_TYPE_SP1, ismp_beefy::PROOF_TYPE_BLS]
 	}
 }
 
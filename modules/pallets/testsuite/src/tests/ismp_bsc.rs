@@ -0,0 +1,217 @@
+// Copyright (c) 2025 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use crate::runtime::{new_test_ext, Ismp, Test};
+use alloy_primitives::{Bytes, FixedBytes, B256};
+use alloy_rlp::Encodable;
+use bsc_verifier::primitives::{
+	BscCatchUpUpdate, BscClientUpdate, BscConsensusProof, VoteAttestationData, VoteData,
+};
+use codec::{Decode, Encode};
+use geth_primitives::{CodecHeader, Header};
+use ismp::{
+	consensus::{ConsensusClient, StateMachineId},
+	host::StateMachine,
+	messaging::Keccak256,
+};
+use ismp_bsc::{pallet::EpochLength, BscClient, ConsensusState, Testnet, BSC_CONSENSUS_ID};
+use primitive_types::{H160, H256, U256};
+
+const EPOCH_LENGTH: u64 = 200;
+const CHAIN_ID: u32 = 97;
+
+/// A BSC validator set whose members all sign every vote
+struct ValidatorSet {
+	/// Key material the members' secret keys are derived from
+	seeds: Vec<Vec<u8>>,
+	public_keys: Vec<Vec<u8>>,
+}
+
+impl ValidatorSet {
+	fn new(seed: u8) -> Self {
+		let seeds = (0..3).map(|i| vec![seed + i; 32]).collect::<Vec<_>>();
+		let public_keys = seeds.iter().map(|seed| bls::sk_to_pk(bls::keygen(seed))).collect();
+		Self { seeds, public_keys }
+	}
+
+	fn bls_keys<T: for<'a> TryFrom<&'a [u8]>>(&self) -> Vec<T> {
+		self.public_keys
+			.iter()
+			.map(|key| key.as_slice().try_into().ok().expect("48 byte public key"))
+			.collect()
+	}
+
+	/// Header whose extra data attests to `source` and `target`, signed by the whole set
+	fn attest(&self, number: u64, source: &CodecHeader, target: &CodecHeader) -> CodecHeader {
+		let data = VoteData {
+			source_number: source.number.low_u64(),
+			source_hash: B256::from(hash(source).0),
+			target_number: target.number.low_u64(),
+			target_hash: B256::from(hash(target).0),
+		};
+		let message = Ismp::keccak256(&alloy_rlp::encode(data.clone())).0.to_vec();
+		let dst = bls::DST_ETHEREUM.as_bytes().to_vec();
+		let signatures = self
+			.seeds
+			.iter()
+			.map(|seed| bls::sign(bls::keygen(seed), &message, &dst).expect("valid secret key"))
+			.collect::<Vec<_>>();
+		let signature = bls::aggregate(&signatures).expect("valid signatures");
+
+		let attestation = VoteAttestationData {
+			vote_address_set: (1 << self.seeds.len()) - 1,
+			agg_signature: FixedBytes::<96>::from_slice(&signature),
+			data,
+			extra: Bytes::new(),
+		};
+		let mut extra_data = vec![0u8; 32];
+		attestation.encode(&mut extra_data);
+		extra_data.extend_from_slice(&[0u8; 65]);
+
+		header(number, H256::repeat_byte(0xaa), extra_data)
+	}
+
+	/// Epoch boundary header announcing this set
+	fn epoch_header(&self, number: u64, parent_hash: H256) -> CodecHeader {
+		let mut extra_data = vec![0u8; 32];
+		extra_data.push(self.public_keys.len() as u8);
+		for (i, key) in self.public_keys.iter().enumerate() {
+			extra_data.extend_from_slice(&H160::repeat_byte(i as u8 + 1).0);
+			extra_data.extend_from_slice(key);
+		}
+		extra_data.extend_from_slice(&[0u8; 65]);
+
+		header(number, parent_hash, extra_data)
+	}
+}
+
+fn header(number: u64, parent_hash: H256, extra_data: Vec<u8>) -> CodecHeader {
+	CodecHeader {
+		parent_hash,
+		uncle_hash: H256::zero(),
+		coinbase: H160::zero(),
+		state_root: H256::from_low_u64_be(number),
+		transactions_root: H256::zero(),
+		receipts_root: H256::zero(),
+		logs_bloom: Default::default(),
+		difficulty: U256::zero(),
+		number: U256::from(number),
+		gas_limit: 0,
+		gas_used: 0,
+		timestamp: number,
+		extra_data,
+		mix_hash: H256::zero(),
+		nonce: Default::default(),
+		base_fee_per_gas: None,
+		withdrawals_hash: None,
+		blob_gas_used: None,
+		excess_blob_gas_used: None,
+		parent_beacon_root: None,
+		requests_hash: None,
+	}
+}
+
+fn hash(header: &CodecHeader) -> H256 {
+	Header::from(header).hash::<Ismp>()
+}
+
+/// An update finalizing `source` through its direct child, attested by `signers`
+fn update(signers: &ValidatorSet, source: CodecHeader) -> BscClientUpdate {
+	let number = source.number.low_u64();
+	let target = header(number + 1, hash(&source), vec![]);
+	BscClientUpdate {
+		attested_header: signers.attest(number + 2, &source, &target),
+		source_header: source,
+		target_header: target,
+		epoch_header_ancestry: Default::default(),
+	}
+}
+
+/// A client two epochs behind catches up in a single message: each epoch is synced by the set it
+/// replaces, then enacted by the new set, with every update verified against the state the
+/// previous one produced.
+#[test]
+fn verifies_two_epoch_catch_up() {
+	new_test_ext().execute_with(|| {
+		EpochLength::<Test>::put(EPOCH_LENGTH);
+		let sets = [ValidatorSet::new(1), ValidatorSet::new(11), ValidatorSet::new(21)];
+
+		let trusted = ConsensusState {
+			current_validators: sets[0].bls_keys(),
+			next_validators: None,
+			finalized_height: 150,
+			finalized_hash: H256::zero(),
+			current_epoch: 0,
+			chain_id: CHAIN_ID,
+		};
+
+		let updates = vec![
+			// sync epoch 1, staging the set announced in its epoch header
+			update(&sets[0], sets[1].epoch_header(EPOCH_LENGTH, H256::repeat_byte(1))),
+			// enact epoch 1, the staged set now signs
+			update(&sets[1], header(EPOCH_LENGTH + 10, H256::repeat_byte(2), vec![])),
+			// sync epoch 2
+			update(&sets[1], sets[2].epoch_header(2 * EPOCH_LENGTH, H256::repeat_byte(3))),
+			// enact epoch 2
+			update(&sets[2], header(2 * EPOCH_LENGTH + 10, H256::repeat_byte(4), vec![])),
+		];
+		let proof = BscConsensusProof::CatchUp(BscCatchUpUpdate {
+			updates: updates.clone().try_into().expect("within bound"),
+		});
+
+		let client = BscClient::<Ismp, Test, Testnet>::default();
+		let host = Ismp::default();
+
+		// the enactment can't be verified before its set is synced
+		let out_of_order = BscConsensusProof::CatchUp(BscCatchUpUpdate {
+			updates: vec![updates[1].clone(), updates[0].clone()].try_into().expect("within bound"),
+		});
+		client
+			.verify_consensus(
+				&host,
+				BSC_CONSENSUS_ID,
+				trusted.encode(),
+				out_of_order.encode_proof(),
+			)
+			.expect_err("enactment signed by an unsynced set must be rejected");
+
+		let (state, commitments) = client
+			.verify_consensus(&host, BSC_CONSENSUS_ID, trusted.encode(), proof.encode_proof())
+			.expect("catch-up should verify");
+
+		let state = ConsensusState::decode(&mut &state[..]).expect("valid consensus state");
+		let expected = ConsensusState {
+			current_validators: sets[2].bls_keys(),
+			next_validators: None,
+			finalized_height: 2 * EPOCH_LENGTH + 10,
+			finalized_hash: hash(&updates[3].source_header),
+			current_epoch: 2,
+			chain_id: CHAIN_ID,
+		};
+		assert_eq!(state, expected);
+
+		let id = StateMachineId {
+			state_id: StateMachine::Evm(CHAIN_ID),
+			consensus_state_id: BSC_CONSENSUS_ID,
+		};
+		let heights = commitments[&id].iter().map(|c| c.height).collect::<Vec<_>>();
+		assert_eq!(
+			heights,
+			vec![EPOCH_LENGTH, EPOCH_LENGTH + 10, 2 * EPOCH_LENGTH, 2 * EPOCH_LENGTH + 10]
+		);
+	});
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2025 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use std::collections::BTreeMap;
+
+use crate::runtime::{new_test_ext, Ismp, RuntimeOrigin, Test};
+use codec::{Decode, Encode};
+use frame_support::{assert_noop, assert_ok};
+use ismp::host::{IsmpHost, StateMachine};
+use ismp_sync_committee::{
+	constants::sepolia::Sepolia,
+	pallet::{Error, Pallet},
+	spec::{Fork, NetworkSpec},
+	types::{ConsensusState, L2Consensus},
+	BEACON_CONSENSUS_ID,
+};
+use polkadot_sdk::*;
+use primitive_types::H160;
+use sp_core::crypto::AccountId32;
+
+const FINALIZED_EPOCH: u64 = 300_000;
+
+fn consensus_state(network_spec: Option<NetworkSpec>) -> ConsensusState {
+	let mut consensus_state = ConsensusState {
+		frozen_height: None,
+		light_client_state: Default::default(),
+		l2_consensus: BTreeMap::from([(
+			StateMachine::Evm(11155420),
+			L2Consensus::OpL2Oracle(H160::repeat_byte(1)),
+		)]),
+		chain_id: 11155111,
+		network_spec,
+	};
+	consensus_state.light_client_state.latest_finalized_epoch = FINALIZED_EPOCH;
+	consensus_state
+}
+
+fn stored_consensus_state() -> ConsensusState {
+	let encoded = Ismp::default().consensus_state(BEACON_CONSENSUS_ID).unwrap();
+	ConsensusState::decode(&mut &encoded[..]).unwrap()
+}
+
+#[test]
+fn consensus_states_without_a_network_spec_still_decode() {
+	let state = consensus_state(None);
+	// the layout consensus states were stored with before network specs
+	let old = (state.frozen_height, &state.light_client_state, &state.l2_consensus, state.chain_id)
+		.encode();
+
+	let decoded = ConsensusState::decode(&mut &old[..]).unwrap();
+	assert_eq!(decoded.network_spec, None);
+	assert_eq!(decoded.light_client_state, state.light_client_state);
+	assert_eq!(decoded.l2_consensus, state.l2_consensus);
+	assert_eq!(decoded.chain_id, state.chain_id);
+	// and fall back to the compiled spec
+	assert_eq!(decoded.network_spec::<Sepolia>(), NetworkSpec::from_config::<Sepolia>());
+
+	// re-encoding upgrades them to the new layout
+	let upgraded = decoded.encode();
+	assert_eq!(upgraded[..old.len()], old[..]);
+	assert_eq!(ConsensusState::decode(&mut &upgraded[..]).unwrap().network_spec, None);
+
+	let spec =
+		NetworkSpec { genesis_fork_version: [7, 0, 0, 0], ..NetworkSpec::from_config::<Sepolia>() };
+	let encoded = consensus_state(Some(spec.clone())).encode();
+	let decoded = ConsensusState::decode(&mut &encoded[..]).unwrap();
+	assert_eq!(decoded.network_spec, Some(spec.clone()));
+	assert_eq!(decoded.network_spec::<Sepolia>(), spec);
+}
+
+#[test]
+fn set_network_spec_requires_the_admin_origin_and_a_valid_spec() {
+	new_test_ext().execute_with(|| {
+		let spec = NetworkSpec::from_config::<Sepolia>();
+
+		// there is no consensus state to update yet
+		assert_noop!(
+			Pallet::<Test>::set_network_spec(
+				RuntimeOrigin::root(),
+				BEACON_CONSENSUS_ID,
+				spec.clone()
+			),
+			Error::<Test>::ErrorFetchingConsensusState
+		);
+
+		Ismp::default()
+			.store_consensus_state(BEACON_CONSENSUS_ID, consensus_state(None).encode())
+			.unwrap();
+
+		assert_noop!(
+			Pallet::<Test>::set_network_spec(
+				RuntimeOrigin::signed(AccountId32::new([1; 32])),
+				BEACON_CONSENSUS_ID,
+				spec.clone()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		let mut unordered = spec.clone();
+		unordered.forks.reverse();
+		assert_noop!(
+			Pallet::<Test>::set_network_spec(RuntimeOrigin::root(), BEACON_CONSENSUS_ID, unordered),
+			Error::<Test>::InvalidNetworkSpec
+		);
+
+		let mut duplicate = spec.clone();
+		duplicate.forks[1].version = duplicate.forks[0].version;
+		assert_noop!(
+			Pallet::<Test>::set_network_spec(RuntimeOrigin::root(), BEACON_CONSENSUS_ID, duplicate),
+			Error::<Test>::InvalidNetworkSpec
+		);
+
+		assert_ok!(Pallet::<Test>::set_network_spec(
+			RuntimeOrigin::root(),
+			BEACON_CONSENSUS_ID,
+			spec.clone()
+		));
+		let stored = stored_consensus_state();
+		assert_eq!(stored.network_spec, Some(spec));
+		// the rest of the consensus state is left alone
+		assert_eq!(stored.l2_consensus, consensus_state(None).l2_consensus);
+		assert_eq!(stored.light_client_state.latest_finalized_epoch, FINALIZED_EPOCH);
+	})
+}
+
+#[test]
+fn schedule_fork_only_changes_pending_forks() {
+	new_test_ext().execute_with(|| {
+		Ismp::default()
+			.store_consensus_state(BEACON_CONSENSUS_ID, consensus_state(None).encode())
+			.unwrap();
+		let next = Fork { version: [0x99, 0, 0, 0], epoch: FINALIZED_EPOCH + 100 };
+
+		assert_noop!(
+			Pallet::<Test>::schedule_fork(
+				RuntimeOrigin::signed(AccountId32::new([1; 32])),
+				BEACON_CONSENSUS_ID,
+				next
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		// a fork can't be scheduled on the compiled spec
+		assert_noop!(
+			Pallet::<Test>::schedule_fork(RuntimeOrigin::root(), BEACON_CONSENSUS_ID, next),
+			Error::<Test>::NetworkSpecNotSet
+		);
+
+		let spec = NetworkSpec::from_config::<Sepolia>();
+		assert_ok!(Pallet::<Test>::set_network_spec(
+			RuntimeOrigin::root(),
+			BEACON_CONSENSUS_ID,
+			spec.clone()
+		));
+
+		// forks at or before the finalized epoch are already active
+		assert_noop!(
+			Pallet::<Test>::schedule_fork(
+				RuntimeOrigin::root(),
+				BEACON_CONSENSUS_ID,
+				Fork { epoch: FINALIZED_EPOCH, ..next }
+			),
+			Error::<Test>::InvalidFork
+		);
+		let active = *spec.forks.first().unwrap();
+		assert_noop!(
+			Pallet::<Test>::schedule_fork(
+				RuntimeOrigin::root(),
+				BEACON_CONSENSUS_ID,
+				Fork { epoch: FINALIZED_EPOCH + 1, ..active }
+			),
+			Error::<Test>::InvalidFork
+		);
+
+		assert_ok!(Pallet::<Test>::schedule_fork(RuntimeOrigin::root(), BEACON_CONSENSUS_ID, next));
+		// a pending fork can be rescheduled
+		let rescheduled = Fork { epoch: next.epoch + 50, ..next };
+		assert_ok!(Pallet::<Test>::schedule_fork(
+			RuntimeOrigin::root(),
+			BEACON_CONSENSUS_ID,
+			rescheduled
+		));
+
+		let stored = stored_consensus_state().network_spec.unwrap();
+		assert_eq!(stored.forks.len(), spec.forks.len() + 1);
+		assert_eq!(stored.forks.last(), Some(&rescheduled));
+		assert_eq!(stored.fork_version(next.epoch), spec.fork_version(next.epoch));
+		assert_eq!(stored.fork_version(rescheduled.epoch), rescheduled.version);
+	})
+}
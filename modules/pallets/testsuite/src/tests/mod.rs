@@ -9,8 +9,10 @@ mod pallet_ismp_relayer;
 mod pallet_state_coprocessor;
 
 mod common;
+mod ismp_bsc;
 mod ismp_parachain;
 mod ismp_pharos;
+mod ismp_sync_committee;
 mod pallet_collator_manager;
 mod pallet_consensus_incentives;
 mod pallet_hyper_fungible_token;
@@ -41,16 +41,20 @@ use ismp::{
 	router::{GetResponse, PostRequest, Request},
 };
 use ismp_testsuite::{
-	check_challenge_period, check_client_expiry, check_get_timeout_message_dedup,
-	check_post_timeout_message_dedup, check_request_message_dedup, check_response_message_dedup,
-	create_relayer_signer, get_response_already_received_check, missing_state_commitment_check,
-	post_request_timeout_check, write_outgoing_commitments,
+	check_aggregated_request_challenge_period, check_aggregated_request_message_dedup,
+	check_aggregated_request_skips_received, check_challenge_period, check_client_expiry,
+	check_get_timeout_message_dedup, check_post_timeout_message_dedup, check_request_message_dedup,
+	check_response_message_dedup, create_relayer_signer, get_response_already_received_check,
+	missing_state_commitment_check, post_request_timeout_check, write_outgoing_commitments,
 };
 use pallet_ismp::{
 	child_trie::{RequestCommitments, RequestReceipts},
 	offchain::Leaf,
-	CommitmentQueueState, CommitmentQueueStates, FundMessageParams, MessageCommitment,
-	StateCommitmentQueue, StateMachineCommitmentCap, RELAYER_FEE_ACCOUNT,
+	ordered::{encode_sequenced, MAX_BUFFERED_REQUESTS},
+	AdmissionQuota, AdmissionUsage, BufferedRequestCount, BufferedRequests, ChannelId,
+	CommitmentQueueState, CommitmentQueueStates, FeeBump, FundMessageParams, MessageCommitment,
+	NextSequence, QuotaKey, QuotaUsage, StateCommitmentQueue, StateMachineCommitmentCap,
+	TotalBufferedRequests, RELAYER_FEE_ACCOUNT,
 };
 use pallet_ismp_relayer::withdrawal::Signature;
 
@@ -135,6 +139,23 @@ fn should_reject_expired_check_clients() {
 	})
 }
 
+#[test]
+fn consensus_expiry_is_last_update_plus_unbonding_period() {
+	let mut ext = new_test_ext();
+
+	ext.execute_with(|| {
+		let host = Ismp::default();
+		assert_eq!(Ismp::consensus_expiry(MOCK_CONSENSUS_STATE_ID), None);
+
+		host.store_unbonding_period(MOCK_CONSENSUS_STATE_ID, 1_000).unwrap();
+		assert_eq!(Ismp::consensus_expiry(MOCK_CONSENSUS_STATE_ID), None);
+
+		host.store_consensus_update_time(MOCK_CONSENSUS_STATE_ID, Duration::from_secs(5_000))
+			.unwrap();
+		assert_eq!(Ismp::consensus_expiry(MOCK_CONSENSUS_STATE_ID), Some(6_000));
+	})
+}
+
 #[test]
 fn should_reject_duplicate_post_requests_in_request_message() {
 	let mut ext = new_test_ext();
@@ -150,6 +171,51 @@ fn should_reject_duplicate_post_requests_in_request_message() {
 	})
 }
 
+#[test]
+fn should_reject_duplicate_post_requests_across_aggregated_batches() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		set_timestamp(None);
+		let host = Ismp::default();
+		let id = StateMachineId {
+			state_id: StateMachine::Evm(11155111),
+			consensus_state_id: MOCK_CONSENSUS_STATE_ID,
+		};
+		host.store_challenge_period(id, 1_000_000).unwrap();
+		check_aggregated_request_message_dedup(&host).unwrap()
+	})
+}
+
+#[test]
+fn should_check_challenge_period_for_each_aggregated_batch_height() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		set_timestamp(None);
+		let host = Ismp::default();
+		let id = StateMachineId {
+			state_id: StateMachine::Evm(11155111),
+			consensus_state_id: MOCK_CONSENSUS_STATE_ID,
+		};
+		host.store_challenge_period(id, 1_000_000).unwrap();
+		check_aggregated_request_challenge_period(&host).unwrap()
+	})
+}
+
+#[test]
+fn should_skip_received_requests_in_aggregated_batches() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		set_timestamp(None);
+		let host = Ismp::default();
+		let id = StateMachineId {
+			state_id: StateMachine::Evm(11155111),
+			consensus_state_id: MOCK_CONSENSUS_STATE_ID,
+		};
+		host.store_challenge_period(id, 1_000_000).unwrap();
+		check_aggregated_request_skips_received(&host).unwrap()
+	})
+}
+
 #[test]
 fn should_reject_duplicate_get_requests_in_response_message() {
 	let mut ext = new_test_ext();
@@ -550,6 +616,435 @@ fn test_fund_message() {
 	});
 }
 
+#[test]
+fn test_fund_messages_and_priority_tip() {
+	let mut ext = new_test_ext();
+	let account: AccountId32 = H256::random().0.into();
+	let host = Ismp::default();
+
+	ext.execute_with(|| {
+		Balances::mint_into(&account, 50 * UNIT).unwrap();
+
+		let commitments = (0..2u64)
+			.map(|height| {
+				let msg = DispatchGet {
+					dest: StateMachine::Evm(1),
+					from: vec![0u8; 32],
+					keys: vec![vec![1u8; 32]],
+					context: Default::default(),
+					height,
+					timeout: 2_000_000_000,
+				};
+				host.dispatch_request(
+					DispatchRequest::Get(msg),
+					FeeMetadata { payer: account.clone().into(), fee: 5 * UNIT },
+				)
+				.unwrap()
+			})
+			.collect::<Vec<_>>();
+
+		// fund both requests in one call
+		Ismp::fund_messages(
+			Origin::<Test>::Signed(account.clone()).into(),
+			commitments
+				.iter()
+				.map(|commitment| FundMessageParams {
+					commitment: MessageCommitment::Request(*commitment),
+					amount: 5 * UNIT,
+				})
+				.collect(),
+		)
+		.unwrap();
+
+		assert_eq!(Balances::balance(&RELAYER_FEE_ACCOUNT.into_account_truncating()), 20 * UNIT);
+
+		// a batch with an unknown commitment is rejected as a whole
+		assert!(Ismp::fund_messages(
+			Origin::<Test>::Signed(account.clone()).into(),
+			vec![
+				FundMessageParams {
+					commitment: MessageCommitment::Request(commitments[0]),
+					amount: UNIT,
+				},
+				FundMessageParams {
+					commitment: MessageCommitment::Request(H256::random()),
+					amount: UNIT,
+				},
+			],
+		)
+		.is_err());
+		assert_eq!(Balances::balance(&RELAYER_FEE_ACCOUNT.into_account_truncating()), 20 * UNIT);
+
+		// tips are folded into the fee, only the difference is charged when raised
+		Ismp::set_priority_tip(
+			Origin::<Test>::Signed(account.clone()).into(),
+			MessageCommitment::Request(commitments[0]),
+			2 * UNIT,
+		)
+		.unwrap();
+		Ismp::set_priority_tip(
+			Origin::<Test>::Signed(account.clone()).into(),
+			MessageCommitment::Request(commitments[0]),
+			3 * UNIT,
+		)
+		.unwrap();
+		assert!(Ismp::set_priority_tip(
+			Origin::<Test>::Signed(account.clone()).into(),
+			MessageCommitment::Request(commitments[0]),
+			UNIT,
+		)
+		.is_err());
+
+		assert_eq!(Balances::balance(&RELAYER_FEE_ACCOUNT.into_account_truncating()), 23 * UNIT);
+
+		let fee = Ismp::request_fee(commitments[0]).unwrap();
+		assert_eq!(fee.fee, 13 * UNIT);
+		assert_eq!(fee.tip, 3 * UNIT);
+		assert_eq!(fee.pending_bumps, Some(FeeBump { total: 8 * UNIT, count: 3 }));
+
+		let fee = Ismp::request_fee(commitments[1]).unwrap();
+		assert_eq!(fee.fee, 10 * UNIT);
+		assert_eq!(fee.tip, 0);
+		assert_eq!(fee.pending_bumps, Some(FeeBump { total: 5 * UNIT, count: 1 }));
+
+		// bumps are no longer pending once the fee has been claimed
+		let mut metadata = RequestCommitments::<Test>::get(commitments[1]).unwrap();
+		metadata.claimed = true;
+		RequestCommitments::<Test>::insert(commitments[1], metadata);
+		assert_eq!(Ismp::request_fee(commitments[1]).unwrap().pending_bumps, None);
+	});
+}
+
+#[test]
+fn admission_quotas_refuse_requests_without_consuming_them() {
+	new_test_ext().execute_with(|| {
+		let host = Ismp::default();
+		let source = StateMachine::Evm(11155111);
+		let module_a = vec![1u8; 32];
+		let module_b = vec![2u8; 32];
+		let post = |to: &Vec<u8>, body: Vec<u8>| PostRequest {
+			source,
+			dest: host.host_state_machine(),
+			nonce: 0,
+			from: vec![],
+			to: to.clone(),
+			timeout_timestamp: 0,
+			body,
+		};
+
+		let quotas = BTreeMap::from([
+			(
+				QuotaKey::SourceChain(source),
+				Some(AdmissionQuota { window: 2, max_requests: Some(3), max_bytes: None }),
+			),
+			(
+				QuotaKey::DestinationModule(module_a.clone()),
+				Some(AdmissionQuota { window: 1, max_requests: None, max_bytes: Some(64) }),
+			),
+		]);
+		pallet_ismp::Pallet::<Test>::set_admission_quotas(RuntimeOrigin::root(), quotas).unwrap();
+
+		let router = host.ismp_router();
+		let a = router.module_for_id(module_a.clone()).unwrap();
+		let b = router.module_for_id(module_b.clone()).unwrap();
+
+		a.on_accept(post(&module_a, vec![0u8; 48])).unwrap();
+		// doesn't fit in module a's byte quota
+		assert!(a.on_accept(post(&module_a, vec![0u8; 32])).is_err());
+		// requests the module itself rejects don't consume any quota
+		let error_module = router.module_for_id(ERROR_MODULE_ID.to_vec()).unwrap();
+		assert!(error_module.on_accept(post(&ERROR_MODULE_ID.to_vec(), vec![])).is_err());
+		assert_eq!(
+			AdmissionUsage::<Test>::get(QuotaKey::SourceChain(source)),
+			QuotaUsage { window: 0, requests: 1, bytes: 48 }
+		);
+
+		b.on_accept(post(&module_b, vec![])).unwrap();
+		a.on_accept(post(&module_a, vec![0u8; 16])).unwrap();
+		// the source chain quota is now exhausted for every module
+		assert!(b.on_accept(post(&module_b, vec![])).is_err());
+
+		// both quotas roll over in the next window, so the refused requests can be retried
+		System::set_block_number(2);
+		a.on_accept(post(&module_a, vec![0u8; 32])).unwrap();
+		b.on_accept(post(&module_b, vec![])).unwrap();
+
+		// a zero-length window is rejected
+		assert_noop!(
+			pallet_ismp::Pallet::<Test>::set_admission_quotas(
+				RuntimeOrigin::root(),
+				BTreeMap::from([(
+					QuotaKey::SourceChain(source),
+					Some(AdmissionQuota { window: 0, max_requests: None, max_bytes: None }),
+				)]),
+			),
+			pallet_ismp::Error::<Test>::InvalidAdmissionQuota
+		);
+	})
+}
+
+#[test]
+fn ordered_channels_deliver_in_sequence_and_skip_timed_out_gaps() {
+	new_test_ext().execute_with(|| {
+		set_timestamp(Some(1_000_000));
+		let host = Ismp::default();
+		let module = vec![3u8; 32];
+		let post = |sequence: u64, timeout_timestamp: u64| PostRequest {
+			source: StateMachine::Evm(11155111),
+			dest: host.host_state_machine(),
+			nonce: sequence,
+			from: vec![4u8; 32],
+			to: module.clone(),
+			timeout_timestamp,
+			body: encode_sequenced(sequence, b"payload"),
+		};
+		let channel = ChannelId::from(&post(0, 0));
+
+		pallet_ismp::Pallet::<Test>::set_ordered_modules(
+			RuntimeOrigin::root(),
+			BTreeMap::from([(module.clone(), true)]),
+		)
+		.unwrap();
+		let ordered = host.ismp_router().module_for_id(module.clone()).unwrap();
+
+		// sequence 1 arrives first and waits for sequence 0
+		ordered.on_accept(post(1, 1_000_400)).unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 0);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 1);
+
+		ordered.on_accept(post(0, 1_000_400)).unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 2);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 0);
+		assert_eq!(TotalBufferedRequests::<Test>::get(), 0);
+
+		// already delivered
+		assert!(ordered.on_accept(post(1, 0)).is_err());
+		// not sequenced at all
+		assert!(ordered.on_accept(PostRequest { body: vec![1, 2], ..post(2, 0) }).is_err());
+
+		// sequence 2 never shows up, sequence 3 timed out after it was buffered, so 2 must
+		// have timed out as well
+		host.store_request_receipt(&Request::Post(post(3, 1_000_500)), &vec![]).unwrap();
+		ordered.on_accept(post(3, 1_000_500)).unwrap();
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 2);
+
+		set_timestamp(Some(1_001_000_000));
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 4);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 0);
+		assert_eq!(TotalBufferedRequests::<Test>::get(), 0);
+		// sequence 3 expired in the buffer instead of being delivered, its receipt is gone so it
+		// can be timed out on the source
+		System::assert_has_event(
+			pallet_ismp::Event::<Test>::OrderedRequestExpired {
+				channel: channel.clone(),
+				sequence: 3,
+			}
+			.into(),
+		);
+		assert!(host.request_receipt(&Request::Post(post(3, 1_000_500))).is_none());
+
+		// unordered modules are left alone
+		assert_noop!(
+			pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+				RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+				ChannelId { dest_module: vec![5u8; 32], ..channel },
+			),
+			pallet_ismp::Error::<Test>::ModuleNotOrdered
+		);
+	})
+}
+
+#[test]
+fn ordered_channel_buffers_are_bounded() {
+	new_test_ext().execute_with(|| {
+		set_timestamp(Some(1_000_000));
+		let host = Ismp::default();
+		let module = vec![3u8; 32];
+		let post = |from: u8, sequence: u64, timeout_timestamp: u64| PostRequest {
+			source: StateMachine::Evm(11155111),
+			dest: host.host_state_machine(),
+			nonce: sequence,
+			from: vec![from; 32],
+			to: module.clone(),
+			timeout_timestamp,
+			body: encode_sequenced(sequence, b"payload"),
+		};
+		let channel = ChannelId::from(&post(4, 0, 0));
+
+		pallet_ismp::Pallet::<Test>::set_ordered_modules(
+			RuntimeOrigin::root(),
+			BTreeMap::from([(module.clone(), true)]),
+		)
+		.unwrap();
+		let ordered = host.ismp_router().module_for_id(module.clone()).unwrap();
+
+		// a request without a timeout could never be skipped
+		assert!(ordered.on_accept(post(4, 2, 0)).is_err());
+		// nor can a request that already timed out be buffered
+		assert!(ordered.on_accept(post(4, 2, 500)).is_err());
+
+		// timeouts must not decrease along the channel
+		ordered.on_accept(post(4, 2, 1_000_500)).unwrap();
+		assert!(ordered.on_accept(post(4, 3, 1_000_400)).is_err());
+		assert!(ordered.on_accept(post(4, 1, 1_000_600)).is_err());
+		ordered.on_accept(post(4, 1, 1_000_500)).unwrap();
+		ordered.on_accept(post(4, 3, 1_000_600)).unwrap();
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 3);
+
+		// fill the rest of the channel buffer
+		for sequence in 4..(MAX_BUFFERED_REQUESTS as u64 + 1) {
+			ordered.on_accept(post(4, sequence, 1_000_600)).unwrap();
+		}
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), MAX_BUFFERED_REQUESTS);
+		assert!(ordered.on_accept(post(4, MAX_BUFFERED_REQUESTS as u64 + 1, 1_000_600)).is_err());
+
+		// other channels have their own buffers, all of them count towards the total
+		ordered.on_accept(post(5, 1, 1_000_600)).unwrap();
+		assert_eq!(TotalBufferedRequests::<Test>::get(), MAX_BUFFERED_REQUESTS + 1);
+	})
+}
+
+#[test]
+fn ordered_channel_skips_a_lost_sequence_once_the_lowest_buffered_request_times_out() {
+	new_test_ext().execute_with(|| {
+		set_timestamp(Some(1_000_000));
+		let host = Ismp::default();
+		let module = vec![3u8; 32];
+		let post = |sequence: u64, timeout_timestamp: u64| PostRequest {
+			source: StateMachine::Evm(11155111),
+			dest: host.host_state_machine(),
+			nonce: sequence,
+			from: vec![4u8; 32],
+			to: module.clone(),
+			timeout_timestamp,
+			body: encode_sequenced(sequence, b"payload"),
+		};
+		let channel = ChannelId::from(&post(0, 0));
+
+		pallet_ismp::Pallet::<Test>::set_ordered_modules(
+			RuntimeOrigin::root(),
+			BTreeMap::from([(module.clone(), true)]),
+		)
+		.unwrap();
+		let ordered = host.ismp_router().module_for_id(module.clone()).unwrap();
+
+		// sequence 0 is lost, 1 and 2 wait behind it
+		ordered.on_accept(post(1, 1_000_500)).unwrap();
+		ordered.on_accept(post(2, 1_002_000)).unwrap();
+
+		// nothing is skipped while the lowest buffered request is still deliverable
+		set_timestamp(Some(1_000_400_000));
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 0);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 2);
+
+		// once sequence 1 has timed out, sequence 0 has too, so the gap is skipped, 1 expires and
+		// 2 is delivered
+		set_timestamp(Some(1_001_000_000));
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+		System::assert_has_event(
+			pallet_ismp::Event::<Test>::OrderedSequencesSkipped {
+				channel: channel.clone(),
+				from: 0,
+				to: 1,
+			}
+			.into(),
+		);
+		System::assert_has_event(
+			pallet_ismp::Event::<Test>::OrderedRequestExpired {
+				channel: channel.clone(),
+				sequence: 1,
+			}
+			.into(),
+		);
+		assert_eq!(NextSequence::<Test>::get(&channel), 3);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 0);
+		assert_eq!(TotalBufferedRequests::<Test>::get(), 0);
+	})
+}
+
+#[test]
+fn failed_ordered_delivery_keeps_the_request_buffered() {
+	new_test_ext().execute_with(|| {
+		set_timestamp(Some(1_000_000));
+		let host = Ismp::default();
+		let post = |sequence: u64| PostRequest {
+			source: StateMachine::Evm(11155111),
+			dest: host.host_state_machine(),
+			nonce: sequence,
+			from: vec![4u8; 32],
+			to: ERROR_MODULE_ID.to_vec(),
+			timeout_timestamp: 1_000_500,
+			body: encode_sequenced(sequence, b"payload"),
+		};
+		let channel = ChannelId::from(&post(0));
+
+		pallet_ismp::Pallet::<Test>::set_ordered_modules(
+			RuntimeOrigin::root(),
+			BTreeMap::from([(ERROR_MODULE_ID.to_vec(), true)]),
+		)
+		.unwrap();
+		let ordered = host.ismp_router().module_for_id(ERROR_MODULE_ID.to_vec()).unwrap();
+
+		// the ISMP handler stores the receipts before the requests are buffered
+		for sequence in [1, 2] {
+			host.store_request_receipt(&Request::Post(post(sequence)), &vec![]).unwrap();
+			ordered.on_accept(post(sequence)).unwrap();
+		}
+
+		NextSequence::<Test>::insert(&channel, 1);
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+
+		// the channel waits on the failed request instead of losing it, and doesn't move on to
+		// the next one
+		System::assert_has_event(
+			pallet_ismp::Event::<Test>::OrderedDeliveryFailed {
+				channel: channel.clone(),
+				sequence: 1,
+			}
+			.into(),
+		);
+		assert_eq!(NextSequence::<Test>::get(&channel), 1);
+		assert!(BufferedRequests::<Test>::contains_key(&channel, 1));
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 2);
+		assert!(host.request_receipt(&Request::Post(post(1))).is_some());
+
+		// it is only given up on once it times out, which releases its receipt
+		set_timestamp(Some(1_001_000_000));
+		pallet_ismp::Pallet::<Test>::drain_ordered_channel(
+			RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+			channel.clone(),
+		)
+		.unwrap();
+		assert_eq!(NextSequence::<Test>::get(&channel), 3);
+		assert_eq!(BufferedRequestCount::<Test>::get(&channel), 0);
+		assert!(host.request_receipt(&Request::Post(post(1))).is_none());
+		assert!(host.request_receipt(&Request::Post(post(2))).is_none());
+	})
+}
+
 // Regression test for the "Priority is too low (100 vs 100)" pool rejection:
 // consensus updates that advance no state machine (e.g. validator-set rotations
 // during sync) must each get a content-unique `provides` tag and a priority
@@ -668,7 +1163,6 @@ fn should_charge_fee_for_request() {
 	});
 }
 
-
 fn queue_test_state_machine() -> StateMachineId {
 	StateMachineId { state_id: StateMachine::Evm(97), consensus_state_id: *b"mock" }
 }
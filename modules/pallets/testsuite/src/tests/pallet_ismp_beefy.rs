@@ -233,6 +233,7 @@ fn test_unknown_proof_type_rejected() {
 			mmr_root_hash: H256::default(),
 			current_authorities: Default::default(),
 			next_authorities: Default::default(),
+			bls_keysets: Default::default(),
 		};
 
 		// Prefix with 0xFF — unknown proof type
@@ -262,6 +263,7 @@ fn test_empty_proof_rejected() {
 			mmr_root_hash: H256::default(),
 			current_authorities: Default::default(),
 			next_authorities: Default::default(),
+			bls_keysets: Default::default(),
 		};
 
 		let result =
@@ -272,3 +274,28 @@ fn test_empty_proof_rejected() {
 		assert!(err.contains("Empty proof"), "Expected empty proof error, got: {err}");
 	});
 }
+
+#[test]
+fn test_malformed_bls_proof_rejected() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		let host = Ismp::default();
+		let consensus_client = host.consensus_client(BEEFY_CONSENSUS_ID).unwrap();
+		let consensus_state = ConsensusState {
+			latest_beefy_height: 0,
+			beefy_activation_block: 0,
+			mmr_root_hash: H256::default(),
+			current_authorities: Default::default(),
+			next_authorities: Default::default(),
+			bls_keysets: Default::default(),
+		};
+
+		let proof = [&[ismp_beefy::PROOF_TYPE_BLS], &[0xFFu8; 8][..]].concat();
+
+		let result =
+			consensus_client.verify_consensus(&host, *b"BEEF", consensus_state.encode(), proof);
+
+		let err = result.unwrap_err().to_string();
+		assert!(err.contains("Cannot decode BLS proof"), "Expected BLS decode error, got: {err}");
+	});
+}
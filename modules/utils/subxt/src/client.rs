@@ -3,16 +3,20 @@ use std::time::Duration;
 use anyhow::Context;
 use reconnecting_jsonrpsee_ws_client::FixedInterval;
 use subxt::{
-	backend::rpc::RpcClient, ext::subxt_rpcs::client::reconnecting_rpc_client::RpcClientBuilder,
+	backend::rpc::RpcClient,
+	ext::subxt_rpcs::client::reconnecting_rpc_client::{
+		RpcClient as ReconnectingRpcClient, RpcClientBuilder,
+	},
 	OnlineClient,
 };
 
+/// Connect a websocket rpc client that reconnects to `rpc_ws` whenever the connection drops.
 #[cfg(feature = "std")]
-pub async fn ws_client<T: subxt::Config>(
+pub async fn ws_rpc_client(
 	rpc_ws: &str,
 	max_rpc_payload_size: u32,
-) -> Result<(OnlineClient<T>, RpcClient), anyhow::Error> {
-	let rpc_client = RpcClientBuilder::new()
+) -> Result<ReconnectingRpcClient, anyhow::Error> {
+	RpcClientBuilder::new()
 		// retry every second
 		.retry_policy(FixedInterval::new(Duration::from_secs(1)))
 		.max_request_size(max_rpc_payload_size)
@@ -24,7 +28,15 @@ pub async fn ws_client<T: subxt::Config>(
 		)
 		.build(rpc_ws)
 		.await
-		.context(format!("Failed to connect to substrate rpc {rpc_ws}"))?;
+		.context(format!("Failed to connect to substrate rpc {rpc_ws}"))
+}
+
+#[cfg(feature = "std")]
+pub async fn ws_client<T: subxt::Config>(
+	rpc_ws: &str,
+	max_rpc_payload_size: u32,
+) -> Result<(OnlineClient<T>, RpcClient), anyhow::Error> {
+	let rpc_client = ws_rpc_client(rpc_ws, max_rpc_payload_size).await?;
 
 	let client = OnlineClient::<T>::from_rpc_client(rpc_client.clone())
 		.await
@@ -182,6 +182,37 @@ pub mod signer {
 		await_extrinsic::<T>(progress, wait_for_finalization).await
 	}
 
+	/// Like [`send_extrinsic`], but for keys that can't sign synchronously, such as keys held by
+	/// a remote signer. `sign` is handed the signer payload of the extrinsic and returns the
+	/// sr25519 signature over it.
+	pub async fn send_extrinsic_with_signature<T, Tx, F, Fut>(
+		client: &OnlineClient<T>,
+		account_id: T::AccountId,
+		sign: F,
+		payload: &Tx,
+		wait_for_finalization: bool,
+	) -> Result<HashFor<T>, anyhow::Error>
+	where
+		T: subxt::Config,
+		Tx: Payload,
+		F: FnOnce(Vec<u8>) -> Fut,
+		Fut: core::future::Future<Output = Result<[u8; 64], anyhow::Error>>,
+		T::AccountId: Into<T::Address> + Clone + 'static,
+		T::Signature: From<MultiSignature> + Send + Sync,
+		<T::ExtrinsicParams as ExtrinsicParams<T>>::Params: Send + Sync + DefaultParams,
+	{
+		refresh_runtime_version(client).await?;
+		let params = DefaultParams::default_params();
+		let mut partial = client.tx().create_partial(payload, &account_id, params).await?;
+		let signature = sign(partial.signer_payload()).await?;
+		let ext = partial.sign_with_account_and_signature(
+			&account_id,
+			&MultiSignature::Sr25519(signature).into(),
+		);
+		let progress = ext.submit_and_watch().await.context("Failed to submit signed extrinsic")?;
+		await_extrinsic::<T>(progress, wait_for_finalization).await
+	}
+
 	/// Drive a submitted extrinsic to inclusion (or finalization), assert it executed
 	/// successfully, and return the hash of the block it landed in.
 	async fn await_extrinsic<T: subxt::Config>(
@@ -12,7 +12,7 @@ use ismp::{
 	consensus::{StateCommitment, StateMachineHeight, StateMachineId},
 	host::StateMachine,
 	messaging::{
-		CreateConsensusState, Message, Proof, ResponseMessage, StateCommitmentHeight,
+		CreateConsensusState, Message, Proof, RequestBatch, ResponseMessage, StateCommitmentHeight,
 		TimeoutMessage,
 	},
 	router::{GetRequest, PostRequest, Request},
@@ -61,6 +61,16 @@ fn to_single_message_value(message: &Message) -> Value<()> {
 			let timeout_variant = timeout_message_to_value(msg);
 			Value::variant("Timeout", Composite::unnamed(vec![timeout_variant]))
 		},
+		Message::AggregatedRequest(msg) => {
+			let inner_struct = Value::named_composite(vec![
+				(
+					"batches",
+					Value::unnamed_composite(msg.batches.iter().map(request_batch_to_value)),
+				),
+				("signer", Value::from_bytes(msg.signer.clone())),
+			]);
+			Value::variant("AggregatedRequest", Composite::unnamed(vec![inner_struct]))
+		},
 	}
 }
 
@@ -80,6 +90,16 @@ fn response_message_to_composite(msg: &ResponseMessage) -> Composite<()> {
 	])
 }
 
+fn request_batch_to_value(batch: &RequestBatch) -> Value<()> {
+	Value::named_composite(vec![
+		(
+			"requests".to_string(),
+			Value::unnamed_composite(batch.requests.iter().map(post_request_to_value)),
+		),
+		("proof".to_string(), proof_to_value(&batch.proof)),
+	])
+}
+
 fn timeout_message_to_value(msg: &TimeoutMessage) -> Value<()> {
 	match msg {
 		TimeoutMessage::Post { requests, timeout_proof } => Value::variant(
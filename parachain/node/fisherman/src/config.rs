@@ -470,6 +470,8 @@ mod tests {
 				max_concurent_queries: None,
 				poll_interval: None,
 				fee_token_decimals: None,
+				additional_rpc_ws: vec![],
+				rpc_health: Default::default(),
 			}
 		}
 
@@ -54,7 +54,7 @@ use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 
 use ::ismp::{
-	consensus::{ConsensusClientId, StateMachineHeight, StateMachineId},
+	consensus::{ConsensusClientId, ConsensusStateId, StateMachineHeight, StateMachineId},
 	host::StateMachine,
 	router::{GetResponse, Request},
 };
@@ -252,7 +252,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: Cow::Borrowed("gargantua"),
 	impl_name: Cow::Borrowed("gargantua"),
 	authoring_version: 1,
-	spec_version: 8_400,
+	spec_version: 8_500,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -808,9 +808,9 @@ impl frame_support::traits::Contains<RuntimeCall> for ReputationCallFilter {
 /// Gargantua routes all BEEFY consensus updates through `pallet-beefy-consensus-proofs`, which
 /// requires each proof to pass SP1 zkVM verification before it can advance the BEEFY state.
 /// Allowing raw updates through `handle_unsigned` would bypass that requirement entirely, so
-/// any batch that carries a BEEFY consensus message is rejected here. `fund_message` is also
-/// disabled because gargantua uses the bandwidth model for request fees; per-message top-ups
-/// have no role in that accounting.
+/// any batch that carries a BEEFY consensus message is rejected here. `fund_message`,
+/// `fund_messages` and `set_priority_tip` are also disabled because gargantua uses the bandwidth
+/// model for request fees; per-message top-ups and tips have no role in that accounting.
 ///
 /// A consensus message only names the state it updates, so we ask the host which client owns
 /// that state and compare against BEEFY. Reading from the host remains correct even as more
@@ -820,7 +820,11 @@ impl frame_support::traits::Contains<RuntimeCall> for IsmpCallFilter {
 	fn contains(call: &RuntimeCall) -> bool {
 		use ::ismp::{host::IsmpHost, messaging::Message};
 		match call {
-			RuntimeCall::Ismp(pallet_ismp::Call::fund_message { .. }) => false,
+			RuntimeCall::Ismp(
+				pallet_ismp::Call::fund_message { .. } |
+				pallet_ismp::Call::fund_messages { .. } |
+				pallet_ismp::Call::set_priority_tip { .. },
+			) => false,
 			RuntimeCall::Ismp(pallet_ismp::Call::handle_unsigned { messages }) => {
 				let host = Ismp::default();
 				!messages.iter().any(|message| match message {
@@ -1266,6 +1270,16 @@ impl_runtime_apis! {
 		fn responses(commitments: Vec<H256>) -> Vec<GetResponse> {
 			Ismp::responses(commitments)
 		}
+
+		/// Get the current fee for an outgoing request
+		fn request_fee(commitment: H256) -> Option<pallet_ismp::RequestFee<Balance>> {
+			Ismp::request_fee(commitment)
+		}
+
+		/// Return the timestamp at which the consensus client expires
+		fn consensus_expiry(id: ConsensusStateId) -> Option<u64> {
+			Ismp::consensus_expiry(id)
+		}
 	}
 
 	impl ismp_parachain_runtime_api::IsmpParachainApi<Block> for Runtime {
@@ -1,9 +1,10 @@
 //! Simnode coverage for the runtime base call filter (`IsmpCallFilter`).
 //!
-//! The filter blocks `Ismp::fund_message` outright and blocks `Ismp::handle_unsigned`
-//! when the batch carries a BEEFY consensus update. We submit each call as a *signed*
-//! extrinsic on purpose: a signed extrinsic skips `validate_unsigned` and goes straight
-//! to dispatch, where the filter runs before the call body. A blocked call comes back as
+//! The filter blocks `Ismp::fund_message`, `Ismp::fund_messages` and `Ismp::set_priority_tip`
+//! outright, since all three write the message fee into the commitment child trie, and blocks
+//! `Ismp::handle_unsigned` when the batch carries a BEEFY consensus update. We submit each call
+//! as a *signed* extrinsic on purpose: a signed extrinsic skips `validate_unsigned` and goes
+//! straight to dispatch, where the filter runs before the call body. A blocked call comes back as
 //! `System::CallFiltered`; a call the filter lets through reaches the body and trips
 //! `ensure_none` with `BadOrigin`. Telling those two apart is the whole test, and it lets
 //! us exercise the filter without building real BEEFY proofs.
@@ -87,16 +88,38 @@ fn consensus_batch(state_id: [u8; 4]) -> DynamicPayload {
 	)
 }
 
+/// A request `MessageCommitment` for an arbitrary hash
+fn request_commitment() -> Value {
+	Value::variant(
+		"Request",
+		Composite::unnamed(vec![Value::unnamed_composite(vec![Value::from_bytes([0u8; 32])])]),
+	)
+}
+
+/// `FundMessageParams` for an arbitrary request
+fn fund_message_params() -> Value {
+	Value::named_composite(vec![("commitment", request_commitment()), ("amount", Value::u128(0))])
+}
+
 /// A `fund_message` payload. Contents are arbitrary since the filter rejects it before the
 /// commitment is ever looked up; we only need it to decode.
 fn fund_message() -> DynamicPayload {
-	let commitment = Value::variant(
-		"Request",
-		Composite::unnamed(vec![Value::unnamed_composite(vec![Value::from_bytes([0u8; 32])])]),
-	);
-	let params =
-		Value::named_composite(vec![("commitment", commitment), ("amount", Value::u128(0))]);
-	subxt::dynamic::tx("Ismp", "fund_message", vec![params])
+	subxt::dynamic::tx("Ismp", "fund_message", vec![fund_message_params()])
+}
+
+/// A `fund_messages` payload, the batch variant of [`fund_message`].
+fn fund_messages() -> DynamicPayload {
+	subxt::dynamic::tx(
+		"Ismp",
+		"fund_messages",
+		vec![Value::unnamed_composite(vec![fund_message_params()])],
+	)
+}
+
+/// A `set_priority_tip` payload, which funds the message through the same path as
+/// [`fund_message`].
+fn set_priority_tip() -> DynamicPayload {
+	subxt::dynamic::tx("Ismp", "set_priority_tip", vec![request_commitment(), Value::u128(1)])
 }
 
 /// Submit a sudo-wrapped call signed by Alice and wait for finalization.
@@ -178,8 +201,8 @@ fn is_bad_origin(err: &DispatchError) -> bool {
 
 #[tokio::test]
 #[ignore]
-async fn ismp_call_filter_blocks_only_beefy_consensus_and_fund_message() -> Result<(), anyhow::Error>
-{
+async fn ismp_call_filter_blocks_only_beefy_consensus_and_message_funding(
+) -> Result<(), anyhow::Error> {
 	let port = env::var("PORT").unwrap_or_else(|_| "9990".into());
 	let url = format!("ws://127.0.0.1:{port}");
 	let (client, rpc_client) =
@@ -195,9 +218,15 @@ async fn ismp_call_filter_blocks_only_beefy_consensus_and_fund_message() -> Resu
 		subxt::dynamic::tx("System", "set_storage", vec![storage_kv_list_to_value(&kv_list)]);
 	submit_sudo(&client, &rpc_client, set_storage).await?;
 
-	// fund_message is always filtered.
-	let err = dispatch_error(&client, &rpc_client, fund_message(), Keyring::Bob).await?;
-	assert!(is_call_filtered(&err), "fund_message must be filtered, got {err:?}");
+	// Every call that funds a message is always filtered.
+	for (name, call) in [
+		("fund_message", fund_message()),
+		("fund_messages", fund_messages()),
+		("set_priority_tip", set_priority_tip()),
+	] {
+		let err = dispatch_error(&client, &rpc_client, call, Keyring::Bob).await?;
+		assert!(is_call_filtered(&err), "{name} must be filtered, got {err:?}");
+	}
 
 	// handle_unsigned carrying a BEEFY consensus update is filtered.
 	let err =
@@ -436,6 +436,8 @@ async fn build_hyperbridge_provider(
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 	let resolved = cfg.resolve().await?;
 	let state_machine = resolved.state_machine();
@@ -12,14 +12,14 @@ use std::time::Duration;
 
 use alloy::{
 	eips::eip7702::Authorization,
-	primitives::{Address, B256, U256},
+	primitives::{Address, Signature, B256, U256},
 	providers::Provider,
 	rpc::types::TransactionRequest,
-	signers::SignerSync,
 };
 use anyhow::{anyhow, Context};
 use primitive_types::{H160, H256};
 use tesseract_evm::EvmClient;
+use tesseract_primitives::signer::RelayerSigner;
 
 /// EIP-7702 delegation indicator prefix `0xef0100`.
 const DELEGATION_INDICATOR: [u8; 3] = [0xef, 0x01, 0x00];
@@ -112,9 +112,12 @@ pub async fn ensure_delegated(client: &EvmClient) -> anyhow::Result<()> {
 
 	let sig_hash: B256 = authorization.signature_hash();
 	let signature = client
-		.private_key_signer
-		.sign_hash_sync(&sig_hash)
+		.signing_key
+		.sign(sig_hash.as_slice())
+		.await
 		.context("failed to sign EIP-7702 authorization")?;
+	let signature = Signature::from_raw(&signature)
+		.context("signer returned a malformed EIP-7702 authorization signature")?;
 	let signed = authorization.into_signed(signature);
 
 	let mut tx = TransactionRequest::default()
@@ -242,10 +242,10 @@ where
 }
 
 /// Extracts the parachain block number from the consensus proof bytes.
-/// The proof format is `proof_type_byte || ABI-encoded proof`. The parachain
-/// header is SCALE-encoded `sp_runtime::generic::Header<u32, H256>`.
+/// The proof format is `proof_type_byte || ABI-encoded proof`, or a SCALE-encoded
+/// proof for BLS. The parachain header is SCALE-encoded `sp_runtime::generic::Header<u32, H256>`.
 fn extract_parachain_height(proof_bytes: &[u8]) -> Option<u32> {
-	use pallet_beefy_consensus_proofs::types::{PROOF_TYPE_NAIVE, PROOF_TYPE_SP1};
+	use pallet_beefy_consensus_proofs::types::{PROOF_TYPE_BLS, PROOF_TYPE_NAIVE, PROOF_TYPE_SP1};
 
 	let proof_type = *proof_bytes.first()?;
 	let abi_payload = &proof_bytes[1..];
@@ -266,6 +266,12 @@ fn extract_parachain_height(proof_bytes: &[u8]) -> Option<u32> {
 			.ok()?;
 			proof.parachain.parachains.first()?.header.to_vec()
 		},
+		PROOF_TYPE_BLS => {
+			let proof =
+				beefy_verifier_primitives::BlsConsensusMessage::decode(&mut &abi_payload[..])
+					.ok()?;
+			proof.parachain.parachains.into_iter().next()?.header
+		},
 		_ => return None,
 	};
 
@@ -16,22 +16,24 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Context};
-use codec::Decode;
+use codec::{Decode, Encode};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use subxt::{
 	config::{ExtrinsicParams, HashFor},
+	ext::subxt_rpcs::rpc_params,
 	tx::DefaultParams,
 	utils::{AccountId32, MultiSignature, H256},
 };
 
+use beefy_prover::relay::fetch_latest_beefy_justification;
 use beefy_verifier_primitives::ConsensusState;
 use ismp::{
 	consensus::ConsensusStateId,
-	messaging::{CreateConsensusState, Message},
+	messaging::{ConsensusMessage, CreateConsensusState, Message},
 };
 use ismp_abi::ecdsa_beefy::BeefyConsensusState;
-use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_primitives::{ConsensusRefresh, IsmpHost, IsmpProvider};
 use tesseract_substrate::SubstrateClient;
 use zk_beefy::BeefyProver as Sp1BeefyProverTrait;
 
@@ -382,4 +384,59 @@ where
 	fn provider(&self) -> Arc<dyn IsmpProvider> {
 		Arc::new(self.client.clone())
 	}
+
+	/// Proves the latest BEEFY finalized relay chain block directly to the counterparty, for
+	/// routes that have gone without messages for too long.
+	async fn refresh_consensus(
+		&self,
+		counterparty: Arc<dyn IsmpProvider>,
+	) -> anyhow::Result<ConsensusRefresh> {
+		let counterparty_state_machine = counterparty.state_machine_id().state_id;
+		let encoded = counterparty
+			.query_consensus_state(None, self.config.consensus_state_id)
+			.await
+			.context("Could not fetch consenus state")?;
+		let consensus_state = ConsensusState::decode(&mut &encoded[..])?;
+
+		let inner = self.prover.inner();
+		let finalized = inner
+			.relay_rpc_client
+			.request::<HashFor<R>>("beefy_getFinalizedHead", rpc_params![])
+			.await?;
+		let (commitment, _) = fetch_latest_beefy_justification(&inner.relay_rpc, finalized).await?;
+		let finalized_height = commitment.commitment.block_number;
+		if finalized_height <= consensus_state.latest_beefy_height {
+			tracing::info!(
+				target: crate::LOG_TARGET, "{counterparty_state_machine} is already at the latest BEEFY height {}",
+				consensus_state.latest_beefy_height
+			);
+			return Ok(ConsensusRefresh::UpToDate);
+		}
+
+		// a proof for a later set can only be verified after the mandatory handover
+		let set_id = commitment.commitment.validator_set_id;
+		if set_id != consensus_state.current_authorities.id &&
+			set_id != consensus_state.next_authorities.id
+		{
+			Err(anyhow!(
+				"{counterparty_state_machine} is waiting for a handover to set {}, latest justification is for set {set_id}",
+				consensus_state.next_authorities.id
+			))?
+		}
+
+		let consensus_proof = self.prover.consensus_proof(commitment, consensus_state).await?;
+		let message = ConsensusMessage {
+			consensus_proof,
+			consensus_state_id: self.config.consensus_state_id,
+			signer: H256::random().encode(),
+		};
+		counterparty
+			.submit(vec![Message::Consensus(message)], self.client.state_machine_id().state_id)
+			.await?;
+		tracing::info!(
+			target: crate::LOG_TARGET, "Refreshed consensus client on {counterparty_state_machine} at {finalized_height}"
+		);
+
+		Ok(ConsensusRefresh::Refreshed)
+	}
 }
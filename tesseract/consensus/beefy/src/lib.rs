@@ -34,6 +34,7 @@ use tesseract_substrate::{SubstrateClient, SubstrateConfig};
 
 pub mod backend;
 pub mod host;
+pub mod pool;
 pub mod prover;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,7 +89,7 @@ impl BeefyConfig {
 				Arc::new(backend::OnchainBackend::<P>::new(
 					client.client.clone(),
 					client.rpc_client.clone(),
-					client.signer.clone(),
+					client.signer()?,
 					sm_id,
 				))
 			},
@@ -0,0 +1,589 @@
+// Copyright (C) 2023 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Distributed SP1 proving.
+//!
+//! Proving in-process means one slow SP1 proof holds up the next epoch. With a proving pool the
+//! BEEFY prover becomes a coordinator: it publishes each proving job to a Redis queue and waits
+//! for the result, while any number of [`ProvingWorker`]s, possibly on other machines, take jobs
+//! off the queue and prove them with their own SP1 prover.
+//!
+//! A worker holds a job for `job_timeout` seconds. If the worker dies or its proof fails, the job
+//! becomes visible again and another worker retries it, up to `max_attempts` times. Jobs are
+//! identified by the commitment and account they prove, so a job requested twice, e.g. by a
+//! restarted coordinator, is queued and proven once and both requests share the result. Proofs
+//! are kept for a day, while an abandoned job is forgotten shortly after its waiters have seen
+//! the failure, so the next request queues it afresh.
+
+use alloy_sol_types::SolValue;
+use anyhow::{anyhow, Context};
+use codec::{Decode, Encode};
+use polkadot_sdk::*;
+use primitive_types::H256;
+use redis::AsyncCommands;
+use rsmq_async::{RedisBytes, Rsmq, RsmqConnection, RsmqError, RsmqMessage, RsmqOptions};
+use serde::{Deserialize, Serialize};
+use sp_consensus_beefy::{ecdsa_crypto::Signature, SignedCommitment};
+use std::{
+	future::Future,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use zk_beefy::BeefyProver as Sp1BeefyProverTrait;
+
+use beefy_verifier_primitives::ConsensusState;
+
+/// How often the coordinator checks for a result and idle workers check for jobs
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long proofs are kept around for late or duplicate requests
+const RESULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long the failure of an abandoned job is kept around. Long enough for every waiting
+/// coordinator to see it, after which the job can be queued again.
+const FAILED_RESULT_TTL: Duration = Duration::from_secs(2 * POLL_INTERVAL.as_secs());
+
+/// Configuration of the proving pool, shared by the coordinator and its workers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvingPoolConfig {
+	/// Redis host
+	pub url: String,
+	/// Redis port
+	pub port: u16,
+	/// Redis username
+	pub username: Option<String>,
+	/// Redis password
+	pub password: Option<String>,
+	/// Redis db
+	#[serde(default)]
+	pub db: u8,
+	/// RSMQ namespace, also prefixes the keys holding job results
+	#[serde(default = "default_namespace")]
+	pub ns: String,
+	/// Queue name for proving jobs
+	#[serde(default = "default_queue")]
+	pub queue: String,
+	/// Seconds a worker may hold a job before it is handed to another worker
+	#[serde(default = "default_job_timeout")]
+	pub job_timeout: u64,
+	/// Number of times a job is handed out before it is abandoned
+	#[serde(default = "default_max_attempts")]
+	pub max_attempts: u64,
+	/// Seconds a failed job waits before it is handed out again
+	#[serde(default = "default_retry_delay")]
+	pub retry_delay: u64,
+}
+
+fn default_namespace() -> String {
+	"rsmq".into()
+}
+
+fn default_queue() -> String {
+	"sp1-proving-jobs".into()
+}
+
+fn default_job_timeout() -> u64 {
+	60 * 60
+}
+
+fn default_max_attempts() -> u64 {
+	3
+}
+
+fn default_retry_delay() -> u64 {
+	30
+}
+
+impl ProvingPoolConfig {
+	/// How long the coordinator waits for a job: every attempt timing out, plus the delay
+	/// between attempts.
+	fn deadline(&self) -> Duration {
+		(Duration::from_secs(self.job_timeout) + Duration::from_secs(self.retry_delay)) *
+			self.max_attempts as u32
+	}
+}
+
+/// Everything a worker needs to produce the SP1 proof for one BEEFY commitment
+#[derive(Clone, Debug, Encode, Decode)]
+pub struct ProvingJob {
+	/// The signed commitment to prove
+	pub signed_commitment: SignedCommitment<u32, Signature>,
+	/// The consensus state the proof will be verified against
+	pub consensus_state: ConsensusState,
+	/// Account committed into the proof as its nonce. This is the coordinator's submission
+	/// account, not the worker's.
+	pub account: H256,
+}
+
+impl ProvingJob {
+	/// Identifier used to de-duplicate jobs. Two jobs for the same commitment and account
+	/// produce interchangeable proofs.
+	pub fn id(&self) -> H256 {
+		sp_core::keccak_256(&(&self.signed_commitment.commitment, self.account).encode()).into()
+	}
+}
+
+impl TryFrom<RedisBytes> for ProvingJob {
+	type Error = Vec<u8>;
+
+	fn try_from(value: RedisBytes) -> Result<Self, Self::Error> {
+		let bytes = value.into_bytes();
+		Self::decode(&mut &bytes[..]).map_err(|err| {
+			tracing::error!(target: crate::LOG_TARGET, "Failed to decode ProvingJob: {err:?}");
+			bytes
+		})
+	}
+}
+
+impl Into<RedisBytes> for ProvingJob {
+	fn into(self) -> RedisBytes {
+		self.encode().into()
+	}
+}
+
+/// The result of a proving job
+#[derive(Clone, Debug, Encode, Decode)]
+pub enum ProvingOutcome {
+	/// The ABI-encoded `SP1BeefyProof`
+	Proof(Vec<u8>),
+	/// The job was abandoned, with the reason
+	Failed(String),
+}
+
+/// Redis-backed queue of SP1 proving jobs and their results
+#[derive(Clone)]
+pub struct ProvingPool {
+	config: ProvingPoolConfig,
+	rsmq: Arc<Mutex<Rsmq>>,
+	connection: Arc<Mutex<redis::aio::ConnectionManager>>,
+}
+
+impl ProvingPool {
+	/// Connect to the pool, creating the job queue if it doesn't exist yet
+	pub async fn new(config: ProvingPoolConfig) -> Result<Self, anyhow::Error> {
+		let mut rsmq = Rsmq::new(RsmqOptions {
+			host: config.url.clone(),
+			port: config.port,
+			username: config.username.clone(),
+			password: config.password.clone(),
+			db: config.db,
+			ns: config.ns.clone(),
+			realtime: false,
+		})
+		.await?;
+		let result = rsmq
+			.create_queue(
+				config.queue.as_str(),
+				Some(Duration::from_secs(config.job_timeout)),
+				Some(Duration::ZERO),
+				Some(-1),
+			)
+			.await;
+		if !(matches!(result, Ok(_) | Err(RsmqError::QueueExists))) {
+			result.context("Failed to create proving job queue")?
+		}
+
+		let connection = redis::Client::open(redis::ConnectionInfo {
+			addr: redis::ConnectionAddr::Tcp(config.url.clone(), config.port),
+			redis: redis::RedisConnectionInfo {
+				db: config.db as i64,
+				username: config.username.clone(),
+				password: config.password.clone(),
+			},
+		})?
+		.get_connection_manager()
+		.await?;
+
+		Ok(Self {
+			config,
+			rsmq: Arc::new(Mutex::new(rsmq)),
+			connection: Arc::new(Mutex::new(connection)),
+		})
+	}
+
+	/// Get reference to the config
+	pub fn config(&self) -> &ProvingPoolConfig {
+		&self.config
+	}
+
+	/// Hand a job to the pool and wait for one of its workers to prove it. Returns the
+	/// ABI-encoded `SP1BeefyProof`. A job that is already queued is not queued again, the call
+	/// waits for the pending result instead.
+	pub async fn prove(&self, job: ProvingJob) -> Result<Vec<u8>, anyhow::Error> {
+		let id = job.id();
+		let deadline = Instant::now() + self.config.deadline();
+
+		if let Some(outcome) = self.outcome(&id).await? {
+			return outcome.into_result(id);
+		}
+
+		let queued: Option<String> = redis::cmd("SET")
+			.arg(self.job_key(&id))
+			.arg(job.signed_commitment.commitment.block_number)
+			.arg("NX")
+			.arg("EX")
+			.arg(self.config.deadline().as_secs())
+			.query_async(&mut *self.connection.lock().await)
+			.await?;
+		if queued.is_some() {
+			let block_number = job.signed_commitment.commitment.block_number;
+			self.rsmq
+				.lock()
+				.await
+				.send_message(self.config.queue.as_str(), job, Some(Duration::ZERO))
+				.await?;
+			tracing::info!(target: crate::LOG_TARGET, "Queued SP1 proving job {id:?} for block {block_number}");
+		} else {
+			tracing::info!(target: crate::LOG_TARGET, "SP1 proving job {id:?} is already queued, waiting for its result");
+		}
+
+		loop {
+			if let Some(outcome) = self.outcome(&id).await? {
+				return outcome.into_result(id);
+			}
+			if Instant::now() >= deadline {
+				Err(anyhow!("Timed out waiting for SP1 proving job {id:?}"))?
+			}
+			tokio::time::sleep(POLL_INTERVAL).await;
+		}
+	}
+
+	/// The result of the job, if a worker has finished it
+	async fn outcome(&self, id: &H256) -> Result<Option<ProvingOutcome>, anyhow::Error> {
+		let encoded: Option<Vec<u8>> =
+			self.connection.lock().await.get(self.result_key(id)).await?;
+		encoded
+			.map(|encoded| ProvingOutcome::decode(&mut &encoded[..]))
+			.transpose()
+			.map_err(Into::into)
+	}
+
+	/// Take the next job off the queue. It stays hidden from other workers for `job_timeout`.
+	async fn next_job(&self) -> Result<Option<RsmqMessage<ProvingJob>>, anyhow::Error> {
+		let job = self
+			.rsmq
+			.lock()
+			.await
+			.receive_message::<ProvingJob>(
+				self.config.queue.as_str(),
+				Some(Duration::from_secs(self.config.job_timeout)),
+			)
+			.await?;
+		Ok(job)
+	}
+
+	/// Take the next job off the queue and settle it with `prove`, which returns the
+	/// ABI-encoded `SP1BeefyProof`. Returns false if the queue was empty.
+	async fn process_next_job<F, Fut>(&self, prove: F) -> Result<bool, anyhow::Error>
+	where
+		F: FnOnce(ProvingJob) -> Fut,
+		Fut: Future<Output = Result<Vec<u8>, anyhow::Error>>,
+	{
+		let Some(message) = self.next_job().await? else { return Ok(false) };
+		let job = message.message;
+		let id = job.id();
+		let max_attempts = self.config.max_attempts;
+
+		// A worker that outlived its lease may have finished the job already
+		if self.outcome(&id).await?.is_some() {
+			self.discard(&message.id).await?;
+			return Ok(true);
+		}
+
+		if message.rc > max_attempts {
+			let reason = format!("Abandoned after {max_attempts} attempts");
+			tracing::error!(target: crate::LOG_TARGET, "SP1 proving job {id:?}: {reason}");
+			self.complete(&message.id, &id, ProvingOutcome::Failed(reason)).await?;
+			return Ok(true);
+		}
+
+		let block_number = job.signed_commitment.commitment.block_number;
+		tracing::info!(target: crate::LOG_TARGET, "Proving SP1 job {id:?} for block {block_number}, attempt {}", message.rc);
+		match prove(job).await {
+			Ok(proof) => {
+				self.complete(&message.id, &id, ProvingOutcome::Proof(proof)).await?;
+				tracing::info!(target: crate::LOG_TARGET, "Proved SP1 job {id:?} for block {block_number}");
+			},
+			Err(err) if message.rc >= max_attempts => {
+				tracing::error!(target: crate::LOG_TARGET, "SP1 proving job {id:?} failed: {err:?}");
+				self.complete(&message.id, &id, ProvingOutcome::Failed(err.to_string())).await?;
+			},
+			Err(err) => {
+				tracing::warn!(target: crate::LOG_TARGET, "SP1 proving job {id:?} failed, will retry: {err:?}");
+				self.release(&message.id).await?;
+			},
+		}
+
+		Ok(true)
+	}
+
+	/// Publish the job's result and remove it from the queue. A failure also clears the job's
+	/// de-duplication key, so the job is queued again the next time it is requested.
+	async fn complete(
+		&self,
+		message_id: &str,
+		id: &H256,
+		outcome: ProvingOutcome,
+	) -> Result<(), anyhow::Error> {
+		let ttl = match outcome {
+			ProvingOutcome::Proof(_) => RESULT_TTL,
+			ProvingOutcome::Failed(_) => FAILED_RESULT_TTL,
+		};
+		let mut connection = self.connection.lock().await;
+		connection
+			.set_ex::<_, _, ()>(self.result_key(id), outcome.encode(), ttl.as_secs())
+			.await?;
+		if matches!(outcome, ProvingOutcome::Failed(_)) {
+			connection.del::<_, ()>(self.job_key(id)).await?;
+		}
+		drop(connection);
+		self.discard(message_id).await
+	}
+
+	/// Remove a job from the queue without publishing a result
+	async fn discard(&self, message_id: &str) -> Result<(), anyhow::Error> {
+		self.rsmq
+			.lock()
+			.await
+			.delete_message(self.config.queue.as_str(), message_id)
+			.await?;
+		Ok(())
+	}
+
+	/// Hand a failed job back to the queue after `retry_delay`
+	async fn release(&self, message_id: &str) -> Result<(), anyhow::Error> {
+		self.rsmq
+			.lock()
+			.await
+			.change_message_visibility(
+				self.config.queue.as_str(),
+				message_id,
+				Duration::from_secs(self.config.retry_delay),
+			)
+			.await?;
+		Ok(())
+	}
+
+	fn job_key(&self, id: &H256) -> String {
+		format!("{}:sp1-job:{id:?}", self.config.ns)
+	}
+
+	fn result_key(&self, id: &H256) -> String {
+		format!("{}:sp1-result:{id:?}", self.config.ns)
+	}
+}
+
+impl ProvingOutcome {
+	fn into_result(self, id: H256) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			ProvingOutcome::Proof(proof) => Ok(proof),
+			ProvingOutcome::Failed(reason) =>
+				Err(anyhow!("SP1 proving job {id:?} failed: {reason}")),
+		}
+	}
+}
+
+/// Takes jobs off a [`ProvingPool`] and proves them with a local SP1 prover
+pub struct ProvingWorker<R: subxt::Config, P: subxt::Config, B: Sp1BeefyProverTrait> {
+	prover: zk_beefy::Prover<R, P, B>,
+	pool: ProvingPool,
+}
+
+impl<R, P, B> ProvingWorker<R, P, B>
+where
+	R: subxt::Config,
+	P: subxt::Config,
+	B: Sp1BeefyProverTrait,
+	zk_beefy::Prover<R, P, B>: Clone,
+{
+	/// Constructs an instance of the [`ProvingWorker`]
+	pub fn new(prover: zk_beefy::Prover<R, P, B>, pool: ProvingPool) -> Self {
+		Self { prover, pool }
+	}
+
+	/// Process jobs until the process is stopped
+	pub async fn run(&self) {
+		loop {
+			match self.process_next_job().await {
+				Ok(true) => {},
+				Ok(false) => tokio::time::sleep(POLL_INTERVAL).await,
+				Err(err) => {
+					tracing::error!(target: crate::LOG_TARGET, "Proving worker error: {err:?}");
+					tokio::time::sleep(POLL_INTERVAL).await
+				},
+			}
+		}
+	}
+
+	/// Prove the next job on the queue. Returns false if the queue was empty.
+	async fn process_next_job(&self) -> Result<bool, anyhow::Error> {
+		let prover = self.prover.clone();
+		self.pool
+			.process_next_job(|job| async move {
+				// Commit the coordinator's account, it is the one submitting the proof
+				let prover = zk_beefy::Prover { account: job.account, ..prover };
+				let proof =
+					prover.consensus_proof(job.signed_commitment, job.consensus_state).await?;
+				Ok(proof.abi_encode_params())
+			})
+			.await
+	}
+}
+
+/// These tests need a Redis server on 127.0.0.1:6379. Run with:
+///
+/// ```sh
+/// cargo test -p tesseract-beefy pool -- --ignored
+/// ```
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_consensus_beefy::{known_payloads::MMR_ROOT_ID, Commitment, Payload};
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	async fn pool(queue: &str, job_timeout: u64, max_attempts: u64) -> ProvingPool {
+		ProvingPool::new(ProvingPoolConfig {
+			url: "127.0.0.1".into(),
+			port: 6379,
+			username: None,
+			password: None,
+			db: 0,
+			ns: format!("sp1-pool-test-{}", H256::random().to_low_u64_be()),
+			queue: queue.into(),
+			job_timeout,
+			max_attempts,
+			retry_delay: 0,
+		})
+		.await
+		.unwrap()
+	}
+
+	fn job(block_number: u32) -> ProvingJob {
+		ProvingJob {
+			signed_commitment: SignedCommitment {
+				commitment: Commitment {
+					payload: Payload::from_single_entry(MMR_ROOT_ID, H256::random().0.to_vec()),
+					block_number,
+					validator_set_id: 0,
+				},
+				signatures: vec![],
+			},
+			consensus_state: ConsensusState {
+				latest_beefy_height: 0,
+				beefy_activation_block: 0,
+				mmr_root_hash: Default::default(),
+				current_authorities: Default::default(),
+				next_authorities: Default::default(),
+				bls_keysets: Default::default(),
+			},
+			account: H256::random(),
+		}
+	}
+
+	/// Work off the queue forever, failing the first `failures` proving attempts. The proof of a
+	/// job is its id.
+	async fn work(pool: &ProvingPool, attempts: &AtomicUsize, failures: usize) {
+		loop {
+			let processed = pool
+				.process_next_job(|job| async move {
+					let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+					if attempt < failures {
+						Err(anyhow!("Attempt {attempt} failed"))
+					} else {
+						Ok(job.id().0.to_vec())
+					}
+				})
+				.await
+				.unwrap();
+			if !processed {
+				tokio::time::sleep(Duration::from_millis(100)).await;
+			}
+		}
+	}
+
+	#[tokio::test]
+	#[ignore]
+	async fn proves_a_job_requested_twice_once() {
+		let pool = pool("dedup", 60, 3).await;
+		let job = job(1);
+		let attempts = AtomicUsize::new(0);
+
+		let requests = futures::future::join(pool.prove(job.clone()), pool.prove(job.clone()));
+		let (first, second) = tokio::select! {
+			proofs = requests => proofs,
+			_ = work(&pool, &attempts, 0) => unreachable!(),
+		};
+
+		assert_eq!(first.unwrap(), job.id().0.to_vec());
+		assert_eq!(second.unwrap(), job.id().0.to_vec());
+		assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+		// A late request is answered from the stored result
+		assert_eq!(pool.prove(job.clone()).await.unwrap(), job.id().0.to_vec());
+		assert_eq!(attempts.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	#[ignore]
+	async fn retries_a_failed_job() {
+		let pool = pool("retry", 60, 3).await;
+		let job = job(1);
+		let attempts = AtomicUsize::new(0);
+
+		let proof = tokio::select! {
+			proof = pool.prove(job.clone()) => proof,
+			_ = work(&pool, &attempts, 2) => unreachable!(),
+		};
+
+		assert_eq!(proof.unwrap(), job.id().0.to_vec());
+		assert_eq!(attempts.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test]
+	#[ignore]
+	async fn requeues_an_abandoned_job() {
+		let pool = pool("abandon", 60, 2).await;
+		let job = job(1);
+		let attempts = AtomicUsize::new(0);
+
+		let result = tokio::select! {
+			result = pool.prove(job.clone()) => result,
+			_ = work(&pool, &attempts, 2) => unreachable!(),
+		};
+
+		assert!(result.unwrap_err().to_string().contains("failed"));
+		assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+		// The failure isn't cached, the next request proves the job again
+		tokio::time::sleep(FAILED_RESULT_TTL + Duration::from_secs(1)).await;
+		let proof = tokio::select! {
+			proof = pool.prove(job.clone()) => proof,
+			_ = work(&pool, &attempts, 2) => unreachable!(),
+		};
+
+		assert_eq!(proof.unwrap(), job.id().0.to_vec());
+		assert_eq!(attempts.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test]
+	#[ignore]
+	async fn times_out_without_workers() {
+		let pool = pool("timeout", 1, 1).await;
+
+		let err = pool.prove(job(1)).await.unwrap_err();
+
+		assert!(err.to_string().contains("Timed out"), "{err:?}");
+	}
+}
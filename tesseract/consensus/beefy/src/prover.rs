@@ -57,6 +57,7 @@ use zk_beefy::BeefyProver as Sp1BeefyProverTrait;
 use crate::{
 	backend::{ConsensusProof, ProofBackend},
 	extract_para_id,
+	pool::{ProvingJob, ProvingPool, ProvingPoolConfig},
 };
 
 /// Deserializes a 4-byte `ConsensusStateId` from a string (e.g. `"DOT0"` or `"PAS0"`).
@@ -105,6 +106,9 @@ pub enum ProofVariant {
 	/// Delegate signature verification to an SP1 zero-knowledge proof (SP1Beefy).
 	#[serde(alias = "zk")]
 	Sp1,
+	/// Verify a single aggregate of the authorities' BLS signatures. Requires a relay chain
+	/// with ECDSA+BLS double signing.
+	Bls,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +122,9 @@ pub struct ProverConfig {
 	/// Which proof variant to produce.
 	#[serde(default)]
 	pub proof_variant: ProofVariant,
+	/// Hand SP1 proving to the workers of this pool instead of proving in-process. Only used by
+	/// the `sp1` proof variant.
+	pub sp1_pool: Option<ProvingPoolConfig>,
 	/// Maximum size in bytes for the rpc payloads, both requests & responses.
 	pub max_rpc_payload_size: Option<u32>,
 	/// Query batch size for mmr leaves
@@ -148,12 +155,18 @@ pub struct BeefyProver<
 /// to redis as frequently as they change. Ensuring that it can always be rehydrated.
 pub const REDIS_CONSENSUS_STATE_KEY: &'static str = "consensus_state";
 
+/// Maximum number of pending epoch changes that are proven concurrently
+const MAX_CONCURRENT_EPOCH_PROOFS: usize = 8;
+
 /// Proof type identifier for naive proofs (BeefyV1)
 pub const PROOF_TYPE_ECDSA: u8 = 0x00;
 
 /// Proof type identifier for ZK proofs (SP1Beefy)
 pub const PROOF_TYPE_SP1: u8 = 0x01;
 
+/// Proof type identifier for aggregated BLS proofs
+pub const PROOF_TYPE_BLS: u8 = 0x02;
+
 impl<R, P, B, Q> BeefyProver<R, P, B, Q>
 where
 	R: subxt::Config + Send + Sync + Clone,
@@ -190,19 +203,7 @@ where
 		signed_commitment: SignedCommitment<u32, Signature>,
 		consensus_state: ConsensusState,
 	) -> Result<Vec<u8>, anyhow::Error> {
-		let encoded = match self.prover {
-			Prover::Ecdsa(ref naive, _) => {
-				let message: BeefyConsensusProof =
-					naive.consensus_proof(signed_commitment).await?.into();
-				[&[PROOF_TYPE_ECDSA], message.abi_encode_params().as_slice()].concat()
-			},
-			Prover::Sp1(ref zk) => {
-				let message = zk.consensus_proof(signed_commitment, consensus_state).await?;
-				[&[PROOF_TYPE_SP1], message.abi_encode_params().as_slice()].concat()
-			},
-		};
-
-		Ok(encoded)
+		self.prover.consensus_proof(signed_commitment, consensus_state).await
 	}
 
 	/// Returns the latest set of ismp messages that have been finalized and the latest finalized
@@ -274,6 +275,16 @@ where
 		&self,
 		consensus_state: &ProverConsensusState,
 	) -> Result<(Option<(HashFor<R>, u64)>, generic::Header<u32, BlakeTwo256>), anyhow::Error> {
+		let (epochs, header) = self.query_finalized_epochs(consensus_state).await?;
+		Ok((epochs.into_iter().next(), header))
+	}
+
+	/// Queries for all authority set changes in between the latest relay chain block finalized
+	/// by beefy and the last known finalized block, oldest first.
+	pub async fn query_finalized_epochs(
+		&self,
+		consensus_state: &ProverConsensusState,
+	) -> Result<(Vec<(HashFor<R>, u64)>, generic::Header<u32, BlakeTwo256>), anyhow::Error> {
 		let initial_height = consensus_state.inner.latest_beefy_height;
 		let relay_rpc = self.prover.inner().relay_rpc.clone();
 		let from = relay_rpc
@@ -314,13 +325,13 @@ where
 
 		tracing::trace!(target: crate::LOG_TARGET, "Latest set ID: {:#?}", changes_iter.clone().next_back());
 
-		let block_hash_and_set_id = changes_iter
+		let block_hashes_and_set_ids = changes_iter
 			.filter(|(_, set_id)| *set_id >= consensus_state.inner.next_authorities.id)
-			.next();
+			.collect::<Vec<_>>();
 
-		tracing::trace!(target: crate::LOG_TARGET, "Block hash and set id: {:#?}", block_hash_and_set_id);
+		tracing::trace!(target: crate::LOG_TARGET, "Block hashes and set ids: {:#?}", block_hashes_and_set_ids);
 
-		Ok((block_hash_and_set_id, header))
+		Ok((block_hashes_and_set_ids, header))
 	}
 
 	/// Performs a linear search for the BEEFY justification which finalizes the given epoch
@@ -387,14 +398,21 @@ where
 				// proof on the pallet; for Redis / in-memory it's the last saved progress.
 				let mut consensus_state = self.backend.load_state().await?;
 
-				let (update, latest_beefy_header) =
-					self.query_next_finalized_epoch(&consensus_state).await?;
-
-				match update {
-					Some((epoch_change_block_hash, next_set_id)) => {
+				let (epochs, latest_beefy_header) =
+					self.query_finalized_epochs(&consensus_state).await?;
+
+				if !epochs.is_empty() {
+					// Each epoch's proof only needs the consensus state left behind by the epoch
+					// before it, which follows from the relay chain. So the proofs for all pending
+					// epochs are generated concurrently and submitted in order.
+					let mut state = consensus_state.clone();
+					let mut updates = vec![];
+					for (epoch_change_block_hash, next_set_id) in
+						epochs.into_iter().take(MAX_CONCURRENT_EPOCH_PROOFS)
+					{
 						// invariant, update should always be for the next set
 						tracing::info!(target: crate::LOG_TARGET, "Next authority set: {next_set_id}");
-						assert_eq!(next_set_id, consensus_state.inner.next_authorities.id);
+						assert_eq!(next_set_id, state.inner.next_authorities.id);
 
 						let epoch_change_header = relay_rpc
 							.chain_get_header(Some(epoch_change_block_hash))
@@ -406,7 +424,7 @@ where
 							.await?
 						else {
 							// justification not yet available, retry next tick
-							return Ok(());
+							break;
 						};
 
 						tracing::info!(
@@ -414,10 +432,6 @@ where
 							commitment.commitment
 						);
 
-						let consensus_proof = self
-							.consensus_proof(commitment.clone(), consensus_state.inner.clone())
-							.await?;
-
 						let finalized_hash = relay_rpc
 							.chain_get_block_hash(Some(commitment.commitment.block_number.into()))
 							.await?
@@ -428,13 +442,37 @@ where
 							para_id,
 						)
 						.await?;
-						let finalized_parachain_height: u64 = para_header.number.into();
 
+						let proof_state = state.inner.clone();
+						// Advance the locally-computed view. Rotate authorities: new current =
+						// old next, new next = queried from the relay at the epoch-change hash.
+						state.finalized_parachain_height = para_header.number.into();
+						state.inner.latest_beefy_height = commitment.commitment.block_number;
+						state.inner.rotate_authorities(
+							beefy_prover::relay::beefy_mmr_leaf_next_authorities(
+								&self.prover.inner().relay_rpc,
+								Some(epoch_change_block_hash),
+							)
+							.await?,
+						);
+						updates.push((commitment, next_set_id, proof_state, state.clone()));
+					}
+
+					let proofs = futures::future::join_all(updates.iter().map(
+						|(commitment, _, proof_state, _)| {
+							self.consensus_proof(commitment.clone(), proof_state.clone())
+						},
+					))
+					.await;
+
+					for ((commitment, set_id, _, state), consensus_proof) in
+						updates.into_iter().zip(proofs)
+					{
 						let message = ConsensusProof {
 							finalized_height: commitment.commitment.block_number,
-							set_id: next_set_id,
+							set_id,
 							message: ConsensusMessage {
-								consensus_proof,
+								consensus_proof: consensus_proof?,
 								consensus_state_id: self.config.consensus_state_id,
 								signer: H256::random().encode(),
 							},
@@ -444,29 +482,14 @@ where
 						tracing::info!("Sending mandatory consensus proof");
 						self.backend.send_mandatory_proof(&destinations, message).await?;
 
-						// Advance the locally-computed view and persist it to the backend.
-						// Rotate authorities inline: new current = old next, new next =
-						// queried from the relay at the epoch-change hash.
-						consensus_state.finalized_parachain_height = finalized_parachain_height;
-						consensus_state.inner.latest_beefy_height =
-							commitment.commitment.block_number;
-						consensus_state.inner.current_authorities =
-							consensus_state.inner.next_authorities.clone();
-						consensus_state.inner.next_authorities =
-							beefy_prover::relay::beefy_mmr_leaf_next_authorities(
-								&self.prover.inner().relay_rpc,
-								Some(epoch_change_block_hash),
-							)
-							.await?;
 						tracing::info!(
 							target: crate::LOG_TARGET, "Rotated authority set. Current {}, Next: {}",
-							consensus_state.inner.current_authorities.id,
-							consensus_state.inner.next_authorities.id,
+							state.inner.current_authorities.id,
+							state.inner.next_authorities.id,
 						);
-						self.backend.save_state(&consensus_state).await?;
-						return Ok(()); // check for next updates
-					},
-					None => {},
+						self.backend.save_state(&state).await?;
+					}
+					return Ok(()); // check for next updates
 				}
 
 				let (latest_parachain_height, messages) = self
@@ -578,12 +601,17 @@ where
 	}
 }
 
-/// Beefy prover, can produce ECDSA or SP1 proofs
+/// Beefy prover, can produce ECDSA, SP1 or aggregated BLS proofs
 pub enum Prover<R: subxt::Config, P: subxt::Config, B: Sp1BeefyProverTrait> {
 	/// ECDSA prover — verifies all 2/3+1 signatures on-chain
 	Ecdsa(beefy_prover::Prover<R, P>, PhantomData<B>),
 	/// SP1 prover — delegates signature verification to an SP1 ZK program
 	Sp1(zk_beefy::Prover<R, P, B>),
+	/// SP1 coordinator — hands proving to the workers of a [`ProvingPool`], committing the
+	/// given submission account into each proof
+	Sp1Pool(beefy_prover::Prover<R, P>, ProvingPool, H256),
+	/// BLS prover — aggregates the authorities' BLS signatures into one
+	Bls(beefy_prover::Prover<R, P>, PhantomData<B>),
 }
 
 impl<R, P, B> Clone for Prover<R, P, B>
@@ -598,6 +626,8 @@ where
 		match self {
 			Prover::Ecdsa(p, _) => Prover::Ecdsa(p.clone(), PhantomData),
 			Prover::Sp1(p) => Prover::Sp1(p.clone()),
+			Prover::Sp1Pool(p, pool, account) => Prover::Sp1Pool(p.clone(), pool.clone(), *account),
+			Prover::Bls(p, _) => Prover::Bls(p.clone(), PhantomData),
 		}
 	}
 }
@@ -651,11 +681,15 @@ where
 		};
 
 		let prover = match config.proof_variant {
-			ProofVariant::Sp1 => {
-				let sp1_prover = zk_beefy::LocalProver::new().await?;
-				Prover::Sp1(zk_beefy::Prover::new(prover, sp1_prover, account))
+			ProofVariant::Sp1 => match config.sp1_pool {
+				Some(pool) => Prover::Sp1Pool(prover, ProvingPool::new(pool).await?, account),
+				None => {
+					let sp1_prover = zk_beefy::LocalProver::new().await?;
+					Prover::Sp1(zk_beefy::Prover::new(prover, sp1_prover, account))
+				},
 			},
 			ProofVariant::Ecdsa => Prover::Ecdsa(prover, PhantomData),
+			ProofVariant::Bls => Prover::Bls(prover, PhantomData),
 		};
 
 		Ok(prover)
@@ -673,10 +707,46 @@ where
 	pub fn inner(&self) -> &beefy_prover::Prover<R, P> {
 		match self {
 			Prover::Sp1(ref p) => &p.inner,
+			Prover::Sp1Pool(ref p, _, _) => p,
 			Prover::Ecdsa(ref p, _) => p,
+			Prover::Bls(ref p, _) => p,
 		}
 	}
 
+	/// Generate an encoded proof for the given commitment
+	pub async fn consensus_proof(
+		&self,
+		signed_commitment: SignedCommitment<u32, Signature>,
+		consensus_state: ConsensusState,
+	) -> Result<Vec<u8>, anyhow::Error> {
+		let encoded = match *self {
+			Prover::Ecdsa(ref naive, _) => {
+				let message: BeefyConsensusProof =
+					naive.consensus_proof(signed_commitment).await?.into();
+				[&[PROOF_TYPE_ECDSA], message.abi_encode_params().as_slice()].concat()
+			},
+			Prover::Sp1(ref zk) => {
+				let message = zk.consensus_proof(signed_commitment, consensus_state).await?;
+				[&[PROOF_TYPE_SP1], message.abi_encode_params().as_slice()].concat()
+			},
+			Prover::Sp1Pool(_, ref pool, account) => {
+				let job = ProvingJob { signed_commitment, consensus_state, account };
+				[&[PROOF_TYPE_SP1], pool.prove(job).await?.as_slice()].concat()
+			},
+			Prover::Bls(ref bls, _) => {
+				let message = bls
+					.bls_consensus_proof(
+						signed_commitment.commitment.block_number,
+						&consensus_state,
+					)
+					.await?;
+				[&[PROOF_TYPE_BLS], message.encode().as_slice()].concat()
+			},
+		};
+
+		Ok(encoded)
+	}
+
 	/// Construct the initial [`ProverConsensusState`] for use by both the verifier and prover.
 	pub async fn query_initial_consensus_state(
 		&self,
@@ -715,6 +785,7 @@ where
 			latest_beefy_height: signed_commitment.commitment.block_number,
 			current_authorities: current_authority_set.clone(),
 			next_authorities: next_authority_set.clone(),
+			bls_keysets: inner.bls_keysets(Some(latest_finalized_head)).await?,
 		};
 
 		Ok(ProverConsensusState {
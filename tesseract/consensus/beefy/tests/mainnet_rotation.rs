@@ -202,6 +202,7 @@ async fn rotate_authorities_across_all_chains() -> anyhow::Result<()> {
 		para_rpc_ws: para_url.clone(),
 		para_ids: vec![para_id],
 		proof_variant: ProofVariant::Ecdsa,
+		sp1_pool: None,
 		max_rpc_payload_size: None,
 		query_batch_size: None,
 	};
@@ -222,6 +223,8 @@ async fn rotate_authorities_across_all_chains() -> anyhow::Result<()> {
 			max_concurent_queries: None,
 			poll_interval: None,
 			fee_token_decimals: None,
+			additional_rpc_ws: vec![],
+			rpc_health: Default::default(),
 		}
 		.resolve()
 		.await?,
@@ -14,7 +14,10 @@
 
 use anyhow::{anyhow, Error};
 use bsc_verifier::{
-	primitives::{compute_epoch, parse_extra, BscClientUpdate, Config, VALIDATOR_BIT_SET_SIZE},
+	primitives::{
+		compute_epoch, parse_extra, BscCatchUpUpdate, BscClientUpdate, BscConsensusProof, Config,
+		MAX_CATCH_UP_UPDATES, MAX_CATCH_UP_WEIGHT, VALIDATOR_BIT_SET_SIZE,
+	},
 	verify_bsc_header, VerificationResult,
 };
 use codec::{Decode, Encode};
@@ -32,11 +35,6 @@ use bsc_prover::get_rotation_block;
 use ssz_rs::{Bitvector, Deserialize};
 use tesseract_primitives::{IsmpHost, IsmpProvider};
 
-/// Maximum number of consensus messages bundled into a single transaction while
-/// the light client is catching up. Batching amortizes the per-transaction
-/// overhead instead of submitting each sync/enactment update on its own.
-const MAX_CONSENSUS_MESSAGES_PER_TX: usize = 8;
-
 #[async_trait::async_trait]
 impl<C: Config> IsmpHost for BscPosHost<C> {
 	async fn start_consensus(
@@ -117,17 +115,19 @@ impl<C: Config> IsmpHost for BscPosHost<C> {
 						));
 					};
 
-					// While catching up, batch up to `MAX_CONSENSUS_MESSAGES_PER_TX` chained
-					// updates into a single transaction instead of submitting them one by one.
-					// A sync update only *stages* the next validator set on-chain; an enactment
-					// update must then promote it before the following epoch can be synced. We
-					// therefore advance a local copy of the consensus state after each update so
-					// every subsequent update is generated against the state the previous one will
-					// produce on-chain, keeping the whole batch valid when applied in order.
+					// While catching up, chain up to `MAX_CATCH_UP_UPDATES` updates into a single
+					// catch-up proof instead of submitting them one by one. A sync update only
+					// *stages* the next validator set on-chain; an enactment update must then
+					// promote it before the following epoch can be synced. We therefore advance a
+					// local copy of the consensus state after each update so every subsequent
+					// update is generated against the state the previous one will produce
+					// on-chain, keeping the whole chain valid when the verifier applies it in
+					// order.
 					let mut working_state = consensus_state.clone();
-					let mut batch: Vec<ConsensusMessage> = Vec::new();
+					let mut batch: Vec<BscClientUpdate> = Vec::new();
+					let mut weight = 0;
 					let mut batch_error = None;
-					while batch.len() < MAX_CONSENSUS_MESSAGES_PER_TX {
+					while batch.len() < MAX_CATCH_UP_UPDATES as usize {
 						match next_consensus_update(
 							&client,
 							&counterparty,
@@ -137,14 +137,20 @@ impl<C: Config> IsmpHost for BscPosHost<C> {
 						)
 						.await
 						{
-							Ok(Some((message, update, verification))) => {
+							Ok(Some((update, verification))) => {
+								// The verifier bounds the weight of the whole chain, the rest is
+								// picked up by the next proof.
+								weight += update.catch_up_weight();
+								if weight > MAX_CATCH_UP_WEIGHT && !batch.is_empty() {
+									break;
+								}
 								apply_update_locally(
 									&mut working_state,
 									&update,
 									&verification,
 									epoch_length,
 								);
-								batch.push(message);
+								batch.push(update);
 							},
 							// No further catch-up update is due right now.
 							Ok(None) => break,
@@ -158,7 +164,15 @@ impl<C: Config> IsmpHost for BscPosHost<C> {
 					// Submit whatever we managed to collect; a mid-batch error only surfaces when
 					// we couldn't make any progress at all.
 					if !batch.is_empty() {
-						return Some((Ok(Some(batch)), (interval, Some(consensus_state))));
+						log::info!(
+							target: crate::LOG_TARGET,
+							"Catching up {} on {} with {} update(s)",
+							client.state_machine,
+							counterparty.state_machine_id().state_id,
+							batch.len(),
+						);
+						let message = catch_up_message(&client, batch);
+						return Some((Ok(Some(vec![message])), (interval, Some(consensus_state))));
 					}
 					if let Some(err) = batch_error {
 						return Some((Err(err), (interval, None)));
@@ -252,20 +266,39 @@ impl<C: Config> IsmpHost for BscPosHost<C> {
 	}
 }
 
+/// Build the consensus message carrying a chain of catch-up updates. A lone update is sent as a
+/// plain [`BscClientUpdate`].
+fn catch_up_message<C: Config>(
+	client: &BscPosHost<C>,
+	mut updates: Vec<BscClientUpdate>,
+) -> ConsensusMessage {
+	let proof = if updates.len() == 1 {
+		BscConsensusProof::Update(updates.remove(0))
+	} else {
+		BscConsensusProof::CatchUp(BscCatchUpUpdate {
+			updates: updates.try_into().expect("Batch is capped at MAX_CATCH_UP_UPDATES"),
+		})
+	};
+	ConsensusMessage {
+		consensus_proof: proof.encode_proof(),
+		consensus_state_id: client.consensus_state_id,
+		signer: H160::random().0.to_vec(),
+	}
+}
+
 /// Compute the next catch-up consensus update for the given `consensus_state`.
 ///
-/// Returns the ready-to-submit [`ConsensusMessage`] alongside the decoded update and its
-/// [`VerificationResult`] so the caller can apply the state transition locally and chain further
-/// updates into the same batch. Returns `Ok(None)` when no sync or authority-set enactment update
-/// is currently due (i.e. the client is not behind), so the caller can fall through to the
-/// steady-state notification path.
+/// Returns the update alongside its [`VerificationResult`] so the caller can apply the state
+/// transition locally and chain further updates into the same catch-up proof. Returns `Ok(None)`
+/// when no sync or authority-set enactment update is currently due (i.e. the client is not behind),
+/// so the caller can fall through to the steady-state notification path.
 async fn next_consensus_update<C: Config>(
 	client: &BscPosHost<C>,
 	counterparty: &Arc<dyn IsmpProvider>,
 	consensus_state: &ConsensusState,
 	attested_header: &CodecHeader,
 	epoch_length: u64,
-) -> Result<Option<(ConsensusMessage, BscClientUpdate, VerificationResult)>, anyhow::Error> {
+) -> Result<Option<(BscClientUpdate, VerificationResult)>, anyhow::Error> {
 	let current_epoch = max(
 		compute_epoch(consensus_state.finalized_height, epoch_length),
 		consensus_state.current_epoch,
@@ -364,12 +397,7 @@ async fn next_consensus_update<C: Config>(
 						},
 					};
 
-					let message = ConsensusMessage {
-						consensus_proof: update.encode(),
-						consensus_state_id: client.consensus_state_id,
-						signer: H160::random().0.to_vec(),
-					};
-					return Ok(Some((message, update, verification)));
+					return Ok(Some((update, verification)));
 				},
 				Ok(None) => block += 1,
 				Err(_) =>
@@ -475,12 +503,7 @@ async fn next_consensus_update<C: Config>(
 					if !update.epoch_header_ancestry.is_empty() ||
 						update.source_header.number.low_u64() == epoch_block_number
 					{
-						let message = ConsensusMessage {
-							consensus_proof: update.encode(),
-							consensus_state_id: client.consensus_state_id,
-							signer: H160::random().0.to_vec(),
-						};
-						return Ok(Some((message, update, verification)));
+						return Ok(Some((update, verification)));
 					}
 
 					block += 1;
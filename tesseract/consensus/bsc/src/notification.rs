@@ -7,7 +7,10 @@ use bsc_verifier::primitives::{compute_epoch, BscClientUpdate, Config};
 use sp_core::H160;
 
 use std::{cmp::max, sync::Arc};
-use tesseract_primitives::IsmpProvider;
+use tesseract_primitives::{
+	proof_cache::{cached_proof, evict_proofs},
+	IsmpProvider,
+};
 
 use crate::{BscPosHost, KeccakHasher};
 
@@ -35,16 +38,21 @@ pub async fn consensus_notification<C: Config>(
 		return Ok((None, None));
 	}
 
-	let mut bsc_client_update = client
-		.prover
-		.fetch_bsc_update::<KeccakHasher>(UpdateParams {
-			attested_header,
-			epoch_length,
-			epoch: current_epoch,
-			fetch_val_set_change: false,
-			validator_size: consensus_state.current_validators.len() as u64,
-		})
-		.await?;
+	let trusted_height = consensus_state.finalized_height;
+	let mut bsc_client_update = cached_proof(client.consensus_state_id, trusted_height, || async {
+		let update = client
+			.prover
+			.fetch_bsc_update::<KeccakHasher>(UpdateParams {
+				attested_header,
+				epoch_length,
+				epoch: current_epoch,
+				fetch_val_set_change: false,
+				validator_size: consensus_state.current_validators.len() as u64,
+			})
+			.await?;
+		Ok::<_, anyhow::Error>(update.map(|update| (update.source_header.number.low_u64(), update)))
+	})
+	.await?;
 	// Dry run the update so we know it will succeed, this ensures client does not get stalled
 	// If the update is a None value, we want to try again in the next tick
 	let dry_run_result = if let Some(update) = bsc_client_update.as_ref() {
@@ -62,6 +70,9 @@ pub async fn consensus_notification<C: Config>(
 	// If the dry run failed, we skip the update
 	if !dry_run_result {
 		log::info!(target: crate::LOG_TARGET, "Skipping invalid update in bsc client");
+		if bsc_client_update.is_some() {
+			evict_proofs(client.consensus_state_id, trusted_height).await;
+		}
 		bsc_client_update = None
 	}
 	return Ok((bsc_client_update, cs_state));
@@ -22,7 +22,7 @@ use tesseract_grandpa::{GrandpaConfig, GrandpaHost};
 use tesseract_parachain::ParachainConfig;
 use tesseract_pharos::PharosConfig;
 use tesseract_polygon::PolygonPosConfig;
-use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_primitives::{ConsensusRefresh, IsmpHost, IsmpProvider};
 use tesseract_substrate::{SubstrateClient, SubstrateConfig};
 use tesseract_sync_committee::SyncCommitteeConfig;
 use tesseract_tendermint::TendermintConfig;
@@ -178,6 +178,16 @@ where
 			AnyHost::Grandpa(grandpa) => grandpa.provider(),
 		}
 	}
+
+	async fn refresh_consensus(
+		&self,
+		counterparty: Arc<dyn IsmpProvider>,
+	) -> anyhow::Result<ConsensusRefresh> {
+		match self {
+			AnyHost::Beefy(beefy) => beefy.refresh_consensus(counterparty).await,
+			AnyHost::Grandpa(grandpa) => grandpa.refresh_consensus(counterparty).await,
+		}
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -239,8 +249,9 @@ impl HyperbridgeHostConfig {
 		let host = match self.host {
 			ConsensusHost::Beefy { substrate, prover, beefy, redis } => {
 				let client = SubstrateClient::<P>::new(substrate).await?;
-				// Commit the submission signer as the SP1 nonce (see `pallet-beefy-consensus-proofs`).
-				// `SubstrateClient::address` is the signer's 32-byte sr25519 public key.
+				// Commit the submission signer as the SP1 nonce (see
+				// `pallet-beefy-consensus-proofs`). `SubstrateClient::address` is the signer's
+				// 32-byte sr25519 public key.
 				let account: H256 = <[u8; 32]>::try_from(client.address.as_slice())
 					.map_err(|_| anyhow!("beefy submission signer account must be 32 bytes"))?
 					.into();
@@ -0,0 +1,360 @@
+// Copyright (C) 2023 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GRANDPA equivocation watcher for standalone chains.
+//!
+//! A single node only follows one fork, so the watcher subscribes to the justifications of several
+//! nodes of the chain. When the block the counterparty finalized at its latest height has a
+//! competing justification at the same height, and both are signed by the authority set the
+//! counterparty trusts, the authorities equivocated. The watcher then walks both branches back
+//! to their fork point and submits the two finality proofs as a [`FraudProofMessage`], which
+//! freezes the consensus client.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	sync::Arc,
+	time::Duration,
+};
+
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use subxt::{
+	backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+	config::HashFor,
+	ext::subxt_rpcs::rpc_params,
+	utils::H256,
+};
+use tokio::{sync::mpsc, task::JoinSet};
+
+use grandpa_prover::JustificationNotification;
+use grandpa_verifier_primitives::{
+	justification::GrandpaJustification, ConsensusState, DefaultHeader, FinalityProof,
+};
+use ismp::{
+	consensus::ConsensusStateId,
+	messaging::{FraudProofMessage, Message},
+};
+use tesseract_primitives::IsmpProvider;
+
+/// Maximum number of blocks the watcher walks back looking for the fork point
+const MAX_FORK_DEPTH: usize = 512;
+
+/// Delay before re-subscribing to a node whose justification stream ended
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(10);
+
+/// Configuration for the equivocation watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquivocationConfig {
+	/// Additional RPC urls of nodes of the chain to watch, next to the host's own `rpc`. They
+	/// should be independent nodes, an equivocation is only visible across nodes on both forks.
+	pub rpcs: Vec<String>,
+	/// How often to check the counterparty's consensus state, in seconds. Defaults to 60.
+	pub check_interval: Option<u64>,
+}
+
+/// A node's view of the chain's block tree
+#[async_trait::async_trait]
+pub trait ForkChoice: Send + Sync {
+	/// Fetch the header with the given hash, if this node knows it
+	async fn header(&self, hash: H256) -> Result<Option<DefaultHeader>, anyhow::Error>;
+}
+
+/// A node of the chain, reached over RPC
+pub struct RpcNode<H: subxt::Config> {
+	/// rpc methods for the node
+	pub rpc: LegacyRpcMethods<H>,
+	/// rpc client for subscriptions
+	pub rpc_client: RpcClient,
+}
+
+impl<H: subxt::Config> RpcNode<H> {
+	/// Connect to the node at the given url
+	pub async fn new(url: &str) -> Result<Self, anyhow::Error> {
+		let rpc_client = RpcClient::from_url(url).await?;
+		Ok(Self { rpc: LegacyRpcMethods::new(rpc_client.clone()), rpc_client })
+	}
+}
+
+#[async_trait::async_trait]
+impl<H> ForkChoice for RpcNode<H>
+where
+	H: subxt::Config + Send + Sync,
+	HashFor<H>: From<H256>,
+{
+	async fn header(&self, hash: H256) -> Result<Option<DefaultHeader>, anyhow::Error> {
+		self.rpc
+			.chain_get_header(Some(hash.into()))
+			.await?
+			.map(|header| DefaultHeader::decode(&mut &*header.encode()))
+			.transpose()
+			.map_err(Into::into)
+	}
+}
+
+/// A justification and the node that reported it
+#[derive(Clone, Debug)]
+struct Observed {
+	justification: Vec<u8>,
+	node: usize,
+}
+
+/// Justifications reported by the watched nodes, by height and the hash they finalize
+#[derive(Default)]
+pub struct ObservedJustifications {
+	by_height: BTreeMap<u32, BTreeMap<H256, Observed>>,
+}
+
+impl ObservedJustifications {
+	/// Record an encoded justification reported by the node at index `node`
+	pub fn note(&mut self, node: usize, justification: Vec<u8>) -> Result<(), anyhow::Error> {
+		let decoded = GrandpaJustification::<DefaultHeader>::decode(&mut &justification[..])?;
+		let (number, hash) = decoded.target();
+		self.by_height
+			.entry(number)
+			.or_default()
+			.entry(hash)
+			.or_insert(Observed { justification, node });
+		Ok(())
+	}
+
+	/// Forget justifications below `height`
+	pub fn prune(&mut self, height: u32) {
+		self.by_height = self.by_height.split_off(&height);
+	}
+
+	/// Build the two finality proofs of an equivocation against `consensus_state`: the
+	/// justification of the block it finalized, and a competing justification at the same
+	/// height signed by the same authority set. Each proof carries its branch back to the fork
+	/// point. Returns `None` if no watched node reported such a pair.
+	pub async fn equivocation(
+		&self,
+		consensus_state: &ConsensusState,
+		nodes: &[Arc<dyn ForkChoice>],
+	) -> Result<Option<(FinalityProof<DefaultHeader>, FinalityProof<DefaultHeader>)>, anyhow::Error>
+	{
+		let Some(observed) = self.by_height.get(&consensus_state.latest_height) else {
+			return Ok(None);
+		};
+		let Some(trusted) = observed.get(&consensus_state.latest_hash) else {
+			if !observed.is_empty() {
+				log::warn!(
+					target: crate::LOG_TARGET,
+					"No watched node finalized {:?} at {}, but the counterparty did",
+					consensus_state.latest_hash, consensus_state.latest_height
+				);
+			}
+			return Ok(None);
+		};
+
+		let is_valid = |observed: &Observed| {
+			GrandpaJustification::<DefaultHeader>::decode(&mut &observed.justification[..])
+				.map_err(|e| anyhow!("{e:?}"))
+				.and_then(|justification| {
+					justification.verify(
+						consensus_state.current_set_id,
+						&consensus_state.current_authorities,
+					)
+				})
+		};
+		if let Err(err) = is_valid(trusted) {
+			log::warn!(target: crate::LOG_TARGET, "Justification of the trusted block does not verify: {err:?}");
+			return Ok(None);
+		}
+
+		for (hash, conflicting) in observed {
+			if *hash == consensus_state.latest_hash {
+				continue;
+			}
+			if let Err(err) = is_valid(conflicting) {
+				log::trace!(target: crate::LOG_TARGET, "Ignoring invalid justification for {hash:?}: {err:?}");
+				continue;
+			}
+
+			let (first, second) = fork_branches(
+				(consensus_state.latest_hash, &*nodes[trusted.node]),
+				(*hash, &*nodes[conflicting.node]),
+			)
+			.await?;
+			let first = FinalityProof {
+				block: consensus_state.latest_hash,
+				justification: trusted.justification.clone(),
+				unknown_headers: first,
+			};
+			let second = FinalityProof {
+				block: *hash,
+				justification: conflicting.justification.clone(),
+				unknown_headers: second,
+			};
+			return Ok(Some((first, second)));
+		}
+
+		Ok(None)
+	}
+}
+
+/// Walk two blocks at the same height back to their fork point. Returns both branches in
+/// ascending order, starting at the first block after the common ancestor.
+async fn fork_branches(
+	(first, first_node): (H256, &dyn ForkChoice),
+	(second, second_node): (H256, &dyn ForkChoice),
+) -> Result<(Vec<DefaultHeader>, Vec<DefaultHeader>), anyhow::Error> {
+	let mut first_branch = vec![fetch(first_node, first).await?];
+	let mut second_branch = vec![fetch(second_node, second).await?];
+	loop {
+		let (first_base, second_base) = (&first_branch[0], &second_branch[0]);
+		if first_base.parent_hash == second_base.parent_hash {
+			break;
+		}
+		if first_branch.len() >= MAX_FORK_DEPTH {
+			Err(anyhow!(
+				"Fork point of {first:?} and {second:?} is over {MAX_FORK_DEPTH} blocks deep"
+			))?
+		}
+		let first_parent = fetch(first_node, first_base.parent_hash).await?;
+		let second_parent = fetch(second_node, second_base.parent_hash).await?;
+		first_branch.insert(0, first_parent);
+		second_branch.insert(0, second_parent);
+	}
+
+	Ok((first_branch, second_branch))
+}
+
+async fn fetch(node: &dyn ForkChoice, hash: H256) -> Result<DefaultHeader, anyhow::Error> {
+	node.header(hash).await?.ok_or_else(|| anyhow!("Header {hash:?} not found"))
+}
+
+/// Watches a standalone chain's nodes for GRANDPA equivocations against the consensus state
+/// the counterparty holds, and reports them as fraud proofs
+pub struct EquivocationWatcher {
+	/// Consensus state id on counterparty chain
+	pub consensus_state_id: ConsensusStateId,
+	/// The watched nodes, along with their rpc clients for subscriptions
+	pub nodes: Vec<(Arc<dyn ForkChoice>, RpcClient)>,
+	/// How often to check the counterparty's consensus state
+	pub check_interval: Duration,
+}
+
+impl EquivocationWatcher {
+	/// Follow the nodes' justifications and check them against the counterparty's consensus
+	/// state until the process is stopped
+	pub async fn run(self, counterparty: Arc<dyn IsmpProvider>) -> Result<(), anyhow::Error> {
+		let (sender, mut receiver) = mpsc::unbounded_channel();
+		// the followers are aborted along with the watcher when the set is dropped
+		let mut followers = JoinSet::new();
+		for (index, (_, rpc_client)) in self.nodes.iter().enumerate() {
+			followers.spawn(follow_justifications(index, rpc_client.clone(), sender.clone()));
+		}
+
+		let nodes = self.nodes.iter().map(|(node, _)| node.clone()).collect::<Vec<_>>();
+		let mut observed = ObservedJustifications::default();
+		let mut reported = BTreeSet::new();
+		let mut interval = tokio::time::interval(self.check_interval);
+		loop {
+			tokio::select! {
+				Some((node, justification)) = receiver.recv() => {
+					if let Err(err) = observed.note(node, justification) {
+						log::error!(target: crate::LOG_TARGET, "Failed to decode justification from node {node}: {err:?}");
+					}
+				},
+				_ = interval.tick() => {
+					let result = self
+						.check(&mut observed, &nodes, &mut reported, counterparty.clone())
+						.await;
+					if let Err(err) = result {
+						log::error!(target: crate::LOG_TARGET, "Equivocation check failed: {err:?}");
+					}
+				},
+			}
+		}
+	}
+
+	/// Check the observed justifications against the counterparty's consensus state and submit
+	/// a fraud proof for an equivocation that hasn't been reported yet
+	async fn check(
+		&self,
+		observed: &mut ObservedJustifications,
+		nodes: &[Arc<dyn ForkChoice>],
+		reported: &mut BTreeSet<(H256, H256)>,
+		counterparty: Arc<dyn IsmpProvider>,
+	) -> Result<(), anyhow::Error> {
+		let consensus_state_bytes =
+			counterparty.query_consensus_state(None, self.consensus_state_id).await?;
+		let consensus_state: ConsensusState = Decode::decode(&mut &consensus_state_bytes[..])?;
+		observed.prune(consensus_state.latest_height);
+
+		let Some((first, second)) = observed.equivocation(&consensus_state, nodes).await? else {
+			return Ok(());
+		};
+		if !reported.insert((first.block, second.block)) {
+			return Ok(());
+		}
+
+		log::warn!(
+			target: "tesseract",
+			"🚨 GRANDPA equivocation at height {}: {:?} and {:?} are both finalized, submitting fraud proof to {}",
+			consensus_state.latest_height, first.block, second.block, counterparty.name()
+		);
+		let message = FraudProofMessage {
+			proof_1: first.encode(),
+			proof_2: second.encode(),
+			consensus_state_id: self.consensus_state_id,
+			signer: H256::random().0.to_vec(),
+		};
+		counterparty
+			.submit(vec![Message::FraudProof(message)], counterparty.state_machine_id().state_id)
+			.await?;
+
+		Ok(())
+	}
+}
+
+/// Forward the justifications a node finalizes, re-subscribing whenever the stream ends
+async fn follow_justifications(
+	node: usize,
+	rpc_client: RpcClient,
+	sender: mpsc::UnboundedSender<(usize, Vec<u8>)>,
+) {
+	loop {
+		let subscription = rpc_client
+			.subscribe::<JustificationNotification>(
+				"grandpa_subscribeJustifications",
+				rpc_params![],
+				"grandpa_unsubscribeJustifications",
+			)
+			.await;
+		match subscription {
+			Ok(mut subscription) =>
+				while let Some(result) = subscription.next().await {
+					match result {
+						Ok(JustificationNotification(justification)) => {
+							if sender.send((node, justification.0)).is_err() {
+								return;
+							}
+						},
+						Err(err) => {
+							log::error!(target: crate::LOG_TARGET, "Justification stream of node {node} failed: {err:?}");
+							break;
+						},
+					}
+				},
+			Err(err) => {
+				log::error!(target: crate::LOG_TARGET, "Failed to subscribe to justifications of node {node}: {err:?}");
+			},
+		}
+		tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+	}
+}
@@ -24,6 +24,7 @@ use subxt::{
 	tx::DefaultParams,
 	utils::{AccountId32, MultiSignature, H256},
 };
+use tokio::task::JoinSet;
 
 use grandpa_verifier_primitives::ConsensusState;
 use ismp::{
@@ -36,7 +37,10 @@ use ismp_grandpa::{
 };
 use tesseract_primitives::{IsmpHost, IsmpProvider};
 
-use crate::GrandpaHost;
+use crate::{
+	equivocation::{EquivocationWatcher, ForkChoice, RpcNode},
+	GrandpaHost,
+};
 
 #[async_trait::async_trait]
 impl<H, C> IsmpHost for GrandpaHost<H, C>
@@ -63,6 +67,34 @@ where
 		&self,
 		counterparty: Arc<dyn IsmpProvider>,
 	) -> Result<(), anyhow::Error> {
+		// Tasks tied to this consensus loop. Dropping the set aborts them, so a restart or
+		// shutdown of the relayer doesn't leave a stale watcher behind.
+		let mut tasks = JoinSet::new();
+		if let (StateMachine::Substrate(_), Some(config)) =
+			(self.state_machine, self.config.grandpa.equivocation.clone())
+		{
+			let mut nodes = vec![];
+			for url in std::iter::once(&self.config.grandpa.rpc).chain(config.rpcs.iter()) {
+				let node = RpcNode::<H>::new(url).await?;
+				let rpc_client = node.rpc_client.clone();
+				nodes.push((Arc::new(node) as Arc<dyn ForkChoice>, rpc_client));
+			}
+			let watcher = EquivocationWatcher {
+				consensus_state_id: self.consensus_state_id,
+				nodes,
+				check_interval: Duration::from_secs(config.check_interval.unwrap_or(60)),
+			};
+			let counterparty = counterparty.clone();
+			tasks.spawn(async move {
+				if let Err(err) = watcher.run(counterparty.clone()).await {
+					log::error!(
+						target: "tesseract",
+						"Equivocation watcher for {} failed: {err:?}", counterparty.name()
+					);
+				}
+			});
+		}
+
 		let client = GrandpaHost::clone(&self);
 
 		let interval = tokio::time::interval(Duration::from_secs(
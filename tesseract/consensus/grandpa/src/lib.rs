@@ -32,7 +32,10 @@ use ismp::{consensus::ConsensusStateId, host::StateMachine};
 use tesseract_primitives::IsmpHost;
 use tesseract_substrate::{SubstrateClient, SubstrateConfig};
 
+pub mod equivocation;
 mod host;
+#[cfg(test)]
+mod tests;
 
 /// Default maximum block range to prove finality for, roughly 4 hours of blocks
 /// on a typical Substrate chain with 6-second block time.
@@ -89,6 +92,8 @@ pub struct HostConfig {
 	pub para_ids: Vec<u32>,
 	/// Maximum block range to prove finality for
 	pub max_block_range: Option<u32>,
+	/// Watch for GRANDPA equivocations, standalone chains only
+	pub equivocation: Option<equivocation::EquivocationConfig>,
 }
 
 #[derive(Clone)]
@@ -0,0 +1,220 @@
+// Copyright (C) 2023 Polytope Labs.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, sync::Arc};
+
+use codec::{Decode, Encode};
+use polkadot_sdk::sp_runtime::traits::Header as _;
+use sp_core::{ed25519, Pair};
+use subxt::utils::H256;
+
+use grandpa_verifier_primitives::{
+	justification::GrandpaJustification, ConsensusState, DefaultHeader,
+};
+use ismp_grandpa::consensus::verify_fraud_proof;
+
+use crate::equivocation::{ForkChoice, ObservedJustifications};
+
+/// A node that only knows the blocks of its own fork
+#[derive(Default)]
+struct MockChain {
+	headers: HashMap<H256, DefaultHeader>,
+}
+
+impl MockChain {
+	fn import(&mut self, headers: &[DefaultHeader]) {
+		self.headers
+			.extend(headers.iter().map(|header| (header.hash(), header.clone())));
+	}
+}
+
+#[async_trait::async_trait]
+impl ForkChoice for MockChain {
+	async fn header(&self, hash: H256) -> Result<Option<DefaultHeader>, anyhow::Error> {
+		Ok(self.headers.get(&hash).cloned())
+	}
+}
+
+fn child(parent: &DefaultHeader, fork: u8) -> DefaultHeader {
+	DefaultHeader::new(
+		parent.number + 1,
+		Default::default(),
+		H256::repeat_byte(fork),
+		parent.hash(),
+		Default::default(),
+	)
+}
+
+/// Extend `parent` by `length` blocks
+fn branch(parent: &DefaultHeader, fork: u8, length: usize) -> Vec<DefaultHeader> {
+	let mut headers = vec![child(parent, fork)];
+	while headers.len() < length {
+		headers.push(child(headers.last().unwrap(), fork));
+	}
+	headers
+}
+
+fn authorities(seed: u8) -> Vec<ed25519::Pair> {
+	(0..4).map(|i| ed25519::Pair::from_seed(&[seed + i; 32])).collect()
+}
+
+/// A justification for `target` signed by every authority
+fn justify(target: &DefaultHeader, set_id: u64, authorities: &[ed25519::Pair]) -> Vec<u8> {
+	let round = 1;
+	let precommit =
+		finality_grandpa::Precommit { target_hash: target.hash(), target_number: target.number };
+	let precommits = authorities
+		.iter()
+		.map(|pair| {
+			let message = finality_grandpa::Message::Precommit(precommit.clone());
+			let signature = pair.sign(&(message, round, set_id).encode());
+			finality_grandpa::SignedPrecommit {
+				precommit: precommit.clone(),
+				signature: signature.into(),
+				id: pair.public().into(),
+			}
+		})
+		.collect();
+	let justification = GrandpaJustification::<DefaultHeader> {
+		round,
+		commit: finality_grandpa::Commit {
+			target_hash: target.hash(),
+			target_number: target.number,
+			precommits,
+		},
+		votes_ancestries: vec![],
+	};
+	justification.encode()
+}
+
+fn consensus_state(finalized: &DefaultHeader, authorities: &[ed25519::Pair]) -> ConsensusState {
+	ConsensusState {
+		current_authorities: authorities.iter().map(|pair| (pair.public().into(), 1)).collect(),
+		current_set_id: 1,
+		latest_height: finalized.number,
+		latest_hash: finalized.hash(),
+		slot_duration: 6,
+	}
+}
+
+/// Two nodes following forks of the chain that diverge after block 2
+fn forked_nodes() -> (Vec<DefaultHeader>, Vec<DefaultHeader>, Vec<Arc<dyn ForkChoice>>) {
+	let genesis = DefaultHeader::new(
+		0,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Default::default(),
+	);
+	let common = branch(&genesis, 0, 2);
+	let first = branch(common.last().unwrap(), 1, 3);
+	let second = branch(common.last().unwrap(), 2, 3);
+
+	let mut first_node = MockChain::default();
+	first_node.import(&common);
+	first_node.import(&first);
+	let mut second_node = MockChain::default();
+	second_node.import(&common);
+	second_node.import(&second);
+
+	(first, second, vec![Arc::new(first_node), Arc::new(second_node)])
+}
+
+#[tokio::test]
+async fn detects_equivocation_across_forks() {
+	let (first, second, nodes) = forked_nodes();
+	let keys = authorities(1);
+	let (first_head, second_head) = (first.last().unwrap(), second.last().unwrap());
+
+	let mut observed = ObservedJustifications::default();
+	observed.note(0, justify(first_head, 1, &keys)).unwrap();
+	observed.note(1, justify(second_head, 1, &keys)).unwrap();
+
+	let state = consensus_state(first_head, &keys);
+	let (proof_1, proof_2) = observed.equivocation(&state, &nodes).await.unwrap().unwrap();
+
+	assert_eq!(proof_1.block, first_head.hash());
+	assert_eq!(proof_2.block, second_head.hash());
+	assert_eq!(proof_1.unknown_headers, first);
+	assert_eq!(proof_2.unknown_headers, second);
+	assert_eq!(proof_1.unknown_headers[0].parent_hash, proof_2.unknown_headers[0].parent_hash);
+	for proof in [proof_1, proof_2] {
+		let justification =
+			GrandpaJustification::<DefaultHeader>::decode(&mut &proof.justification[..]).unwrap();
+		justification.verify(state.current_set_id, &state.current_authorities).unwrap();
+	}
+}
+
+#[tokio::test]
+async fn built_proofs_pass_fraud_proof_verification() {
+	let (first, second, nodes) = forked_nodes();
+	let keys = authorities(1);
+	let (first_head, second_head) = (first.last().unwrap(), second.last().unwrap());
+
+	let mut observed = ObservedJustifications::default();
+	observed.note(0, justify(first_head, 1, &keys)).unwrap();
+	observed.note(1, justify(second_head, 1, &keys)).unwrap();
+
+	let state = consensus_state(first_head, &keys);
+	let (proof_1, proof_2) = observed.equivocation(&state, &nodes).await.unwrap().unwrap();
+
+	verify_fraud_proof(state.encode(), proof_1.encode(), proof_2.encode())
+		.expect("the counterparty should accept the fraud proof");
+	verify_fraud_proof(state.encode(), proof_1.encode(), proof_1.encode())
+		.expect_err("a block can't equivocate with itself");
+}
+
+#[tokio::test]
+async fn ignores_single_finalized_fork() {
+	let (first, _, nodes) = forked_nodes();
+	let keys = authorities(1);
+	let head = first.last().unwrap();
+
+	let mut observed = ObservedJustifications::default();
+	observed.note(0, justify(head, 1, &keys)).unwrap();
+	observed.note(1, justify(head, 1, &keys)).unwrap();
+
+	let state = consensus_state(head, &keys);
+	assert!(observed.equivocation(&state, &nodes).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn ignores_conflicts_from_unknown_authorities() {
+	let (first, second, nodes) = forked_nodes();
+	let keys = authorities(1);
+	let (first_head, second_head) = (first.last().unwrap(), second.last().unwrap());
+
+	let mut observed = ObservedJustifications::default();
+	observed.note(0, justify(first_head, 1, &keys)).unwrap();
+	observed.note(1, justify(second_head, 1, &authorities(100))).unwrap();
+
+	let state = consensus_state(first_head, &keys);
+	assert!(observed.equivocation(&state, &nodes).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn prunes_finalized_heights() {
+	let (first, second, nodes) = forked_nodes();
+	let keys = authorities(1);
+	let (first_head, second_head) = (first.last().unwrap(), second.last().unwrap());
+
+	let mut observed = ObservedJustifications::default();
+	observed.note(0, justify(first_head, 1, &keys)).unwrap();
+	observed.note(1, justify(second_head, 1, &keys)).unwrap();
+	observed.prune(first_head.number + 1);
+
+	let state = consensus_state(first_head, &keys);
+	assert!(observed.equivocation(&state, &nodes).await.unwrap().is_none());
+}
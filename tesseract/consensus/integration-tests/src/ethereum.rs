@@ -671,6 +671,8 @@ async fn sync_committee_tests() -> Result<(), anyhow::Error> {
 		let sync_commitee_config = SyncHostConfig {
 			beacon_http_urls: vec!["https://rpc-gbc.chiadochain.net".to_string()],
 			consensus_update_frequency: 60,
+			prover_backend: Default::default(),
+			network_config: None,
 		};
 
 		Arc::new(
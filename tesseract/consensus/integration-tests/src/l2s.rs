@@ -69,6 +69,8 @@ async fn setup_clients() -> Result<
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 
 	let host = tesseract_grandpa::HostConfig {
@@ -77,6 +79,7 @@ async fn setup_clients() -> Result<
 		consensus_update_frequency: Some(60),
 		para_ids: vec![],
 		max_block_range: None,
+		equivocation: None,
 	};
 
 	let hyperbridge_grandpa_config = GrandpaConfig { substrate: config_a, grandpa: host };
@@ -100,6 +103,8 @@ async fn setup_clients() -> Result<
 		let sync_commitee_config = tesseract_sync_committee::HostConfig {
 			beacon_http_urls: vec![beacon_url.clone()],
 			consensus_update_frequency: 60,
+			prover_backend: Default::default(),
+			network_config: None,
 		};
 
 		SyncCommitteeHost::<
@@ -216,15 +221,10 @@ pub async fn set_arbitrum_config_on_hyperbridge(
 ) -> Result<(), anyhow::Error> {
 	let client = hyperbridge_chain.substrate_client;
 
-	let binding = client.signer.public();
-	let public_key_slice: &[u8] = binding.as_ref();
+	let pair = client.signer()?;
+	let account_id = AccountId32::from(pair.public().0);
 
-	let public_key_array: [u8; 32] =
-		public_key_slice.try_into().expect("sr25519 public key should be 32 bytes");
-
-	let account_id = AccountId32::from(public_key_array);
-
-	let signer = InMemorySigner { account_id: account_id.into(), signer: client.signer.clone() };
+	let signer = InMemorySigner { account_id: account_id.into(), signer: pair };
 
 	let state_machine_id_value = state_machine_id_to_value(&state_machine_id);
 
@@ -250,15 +250,10 @@ pub async fn set_optimism_config_on_hyperbridge(
 
 	let client = hyperbridge_chain.substrate_client;
 
-	let binding = client.signer.public();
-	let public_key_slice: &[u8] = binding.as_ref();
-
-	let public_key_array: [u8; 32] =
-		public_key_slice.try_into().expect("sr25519 public key should be 32 bytes");
-
-	let account_id = AccountId32::from(public_key_array);
+	let pair = client.signer()?;
+	let account_id = AccountId32::from(pair.public().0);
 
-	let signer = InMemorySigner { account_id: account_id.into(), signer: client.signer.clone() };
+	let signer = InMemorySigner { account_id: account_id.into(), signer: pair };
 
 	let state_machine_id_value = state_machine_id_to_value(&state_machine_id);
 
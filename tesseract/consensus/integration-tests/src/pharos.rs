@@ -50,6 +50,9 @@ async fn pharos_consensus_updates() -> anyhow::Result<()> {
 		client_type: Default::default(),
 		initial_height: None,
 		transport: Default::default(),
+		tx_manager: Default::default(),
+		price_oracle: Default::default(),
+		rpc_health: Default::default(),
 	};
 
 	let host_config = PharosHostConfig {
@@ -71,6 +74,8 @@ async fn pharos_consensus_updates() -> anyhow::Result<()> {
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 	let chain_a = SubstrateClient::<Hyperbridge>::new(config_a).await?;
 
@@ -36,6 +36,9 @@ async fn polygon_consensus_updates() -> anyhow::Result<()> {
 		client_type: Default::default(),
 		initial_height: None,
 		transport: tesseract_evm::transport::RpcTransport::Standard,
+		tx_manager: Default::default(),
+		price_oracle: Default::default(),
+		rpc_health: Default::default(),
 	};
 
 	let host_config = HostConfig {
@@ -62,6 +65,8 @@ async fn polygon_consensus_updates() -> anyhow::Result<()> {
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 	let chain_a = SubstrateClient::<Hyperbridge>::new(config_a).await?;
 
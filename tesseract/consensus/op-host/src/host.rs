@@ -504,9 +504,13 @@ async fn submit_state_proposal(
 		.unwrap_or(get_chain_gas_limit(client.l1_state_machine));
 
 	// Fetch L1 gas price
+	let price_oracle = client
+		.l1_price_oracle
+		.as_ref()
+		.ok_or_else(|| anyhow!("State proposals require a proposer config"))?;
 	let gas_breakdown = get_current_gas_cost_in_usd(
 		client.l1_state_machine,
-		client.ismp_host.0.into(),
+		price_oracle,
 		client.beacon_execution_client.clone(),
 	)
 	.await?;
@@ -4,11 +4,9 @@ pub const LOG_TARGET: &str = "consensus-op-host";
 use abi::{DisputeGameFactory, FaultDisputeGame, L2OutputOracle};
 use alloy::{
 	eips::BlockId,
-	network::EthereumWallet,
 	primitives::{Address, B256},
 	providers::{Provider, ProviderBuilder},
 	rpc::types::Filter,
-	signers::local::PrivateKeySigner,
 	sol_types::SolEvent,
 };
 use anyhow::anyhow;
@@ -24,11 +22,14 @@ use reqwest::Client;
 use reqwest_chain::ChainMiddleware;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use serde::{Deserialize, Serialize};
-use sp_core::{bytes::from_hex, Pair};
 use sp_crypto_hashing::keccak_256;
 use std::sync::Arc;
 use sync_committee_prover::middleware::SwitchProviderMiddleware;
-use tesseract_evm::{derive_map_key, AlloyProvider, AlloySignerProvider, EvmClient, EvmConfig};
+use tesseract_evm::{
+	derive_map_key,
+	price_oracle::{PriceOracle, PriceOracleConfig},
+	AlloyProvider, AlloySignerProvider, EvmClient, EvmConfig,
+};
 use tesseract_primitives::{Hasher, IsmpHost, IsmpProvider};
 
 pub mod abi;
@@ -72,7 +73,7 @@ pub struct HostConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProposerConfig {
-	/// Proposer account, private key
+	/// Proposer account key, in any of the formats accepted for relayer signers
 	pub proposer: String,
 	/// beacon consensus client rpc
 	pub beacon_consensus_rpcs: Vec<String>,
@@ -82,6 +83,9 @@ pub struct ProposerConfig {
 	pub proposer_interval: u64,
 	/// Address of the official op-proposer
 	pub op_proposer: String,
+	/// Sources for the USD price of the L1 native token, used to price state proposals
+	#[serde(default)]
+	pub price_oracle: PriceOracleConfig,
 }
 
 impl OpConfig {
@@ -120,6 +124,8 @@ pub struct OpHost {
 	pub provider: Arc<dyn IsmpProvider>,
 	/// Transaction signer
 	pub proposer: Option<Arc<AlloySignerProvider>>,
+	/// Prices the L1 gas of state proposals, set alongside `proposer`
+	pub(crate) l1_price_oracle: Option<Arc<PriceOracle>>,
 	/// L1 state machine id
 	pub l1_state_machine: StateMachine,
 	/// beacon consensus client
@@ -234,42 +240,34 @@ impl OpHost {
 		let consensus_state_id = inner.consensus_state_id;
 		let provider = Arc::new(inner);
 
-		let (proposer, beacon_consensus_client) =
-			if let Some(proposer_config) = host.proposer_config.clone() {
-				let bytes = match from_hex(proposer_config.proposer.as_str()) {
-					Ok(bytes) => bytes,
-					Err(_) => {
-						// it's probably a file.
-						let contents =
-							tokio::fs::read_to_string(proposer_config.proposer.as_str()).await?;
-						from_hex(contents.as_str())?
-					},
-				};
-
-				let signer = sp_core::ecdsa::Pair::from_seed_slice(&bytes)?;
-				let signing_key =
-					alloy::signers::k256::ecdsa::SigningKey::from_slice(signer.seed().as_slice())?;
-				let wallet_signer = PrivateKeySigner::from_signing_key(signing_key);
-				let wallet = EthereumWallet::from(wallet_signer);
-
-				let beacon_provider = tesseract_evm::create_provider(&host.ethereum_rpc_url)?;
-				let signer_provider =
-					ProviderBuilder::new().wallet(wallet).connect_provider(beacon_provider);
-
-				let client = ClientBuilder::new(Client::new())
-					.with(ChainMiddleware::new(SwitchProviderMiddleware::_new(
-						proposer_config.beacon_consensus_rpcs,
-					)))
-					.build();
-
-				(Some(Arc::new(signer_provider)), Some(client))
-			} else {
-				(None, None)
-			};
+		let beacon_client = Arc::new(beacon_client);
+		let (proposer, l1_price_oracle, beacon_consensus_client) = if let Some(proposer_config) =
+			host.proposer_config.clone()
+		{
+			let (_, wallet) =
+				tesseract_evm::signer::load_wallet(Some(proposer_config.proposer.as_str())).await?;
+
+			let beacon_provider = tesseract_evm::create_provider(&host.ethereum_rpc_url)?;
+			let signer_provider =
+				ProviderBuilder::new().wallet(wallet).connect_provider(beacon_provider);
+
+			let client = ClientBuilder::new(Client::new())
+				.with(ChainMiddleware::new(SwitchProviderMiddleware::_new(
+					proposer_config.beacon_consensus_rpcs,
+				)))
+				.build();
+
+			let price_oracle =
+				PriceOracle::new(proposer_config.price_oracle, ismp_host, beacon_client.clone())?;
+
+			(Some(Arc::new(signer_provider)), Some(Arc::new(price_oracle)), Some(client))
+		} else {
+			(None, None, None)
+		};
 
 		Ok(Self {
 			op_execution_client: Arc::new(el),
-			beacon_execution_client: Arc::new(beacon_client),
+			beacon_execution_client: beacon_client,
 			l2_oracle: host.l2_oracle,
 			dispute_game_factory: host.dispute_game_factory,
 			message_parser: host.message_parser,
@@ -280,6 +278,7 @@ impl OpHost {
 			consensus_state_id,
 			provider,
 			proposer,
+			l1_price_oracle,
 			l1_state_machine,
 			beacon_consensus_client,
 			l1_consensus_state_id: {
@@ -31,7 +31,7 @@ use serde::{Deserialize, Serialize};
 use std::{marker::PhantomData, sync::Arc, time::Duration};
 use tesseract_evm::EvmConfig;
 use tesseract_pharos_evm::PharosEvmClient;
-use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_primitives::{proof_cache::evict_proofs, IsmpHost, IsmpProvider};
 
 mod notification;
 
@@ -182,7 +182,7 @@ impl<C: Config + 'static> IsmpHost for PharosHost<C> {
 			interval.as_mut().tick().await;
 
 			match consensus_notification(&client, counterparty_clone.clone()).await {
-				Ok(Some(update)) => {
+				Ok(Some((trusted_height, update))) => {
 					let consensus_message = ConsensusMessage {
 						consensus_proof: update.encode(),
 						consensus_state_id: client.consensus_state_id,
@@ -207,7 +207,8 @@ impl<C: Config + 'static> IsmpHost for PharosHost<C> {
 						log::error!(
 							target: "tesseract", "Failed to submit transaction to {}: {err:?}",
 							counterparty.name()
-						)
+						);
+						evict_proofs(client.consensus_state_id, trusted_height).await;
 					}
 				},
 				Ok(None) => {
@@ -20,12 +20,13 @@ use codec::Decode;
 use ismp_pharos::ConsensusState;
 use pharos_primitives::{Config, VerifierStateUpdate};
 use std::sync::Arc;
-use tesseract_primitives::IsmpProvider;
+use tesseract_primitives::{proof_cache::cached_proof, IsmpProvider};
 
+/// Returns the update along with the trusted height it builds on
 pub async fn consensus_notification<C: Config>(
 	client: &PharosHost<C>,
 	counterparty: Arc<dyn IsmpProvider>,
-) -> Result<Option<VerifierStateUpdate>, anyhow::Error> {
+) -> Result<Option<(u64, VerifierStateUpdate)>, anyhow::Error> {
 	let counterparty_finalized = counterparty.query_finalized_height().await?;
 	let consensus_state_bytes = counterparty
 		.query_consensus_state(Some(counterparty_finalized), client.consensus_state_id)
@@ -46,57 +47,63 @@ pub async fn consensus_notification<C: Config>(
 		return Ok(None);
 	}
 
-	let current_epoch = consensus_state.current_epoch;
-	let latest_epoch = client
-		.prover
-		.fetch_current_epoch(latest_block)
-		.await
-		.map_err(|e| anyhow::anyhow!("Failed to read currentEpoch: {e}"))?;
-
-	log::info!(
-		target: crate::LOG_TARGET,
-		"New block available. Latest: {} (epoch {}), Finalized: {} (epoch {})",
-		latest_block,
-		latest_epoch,
-		consensus_state.finalized_height,
-		current_epoch,
-	);
-
-	// Determine the target block for the update.
-	// If we've crossed epoch boundaries, walk back to find the first block of the new epoch.
-	let target_block = if latest_epoch > current_epoch {
-		// Epoch changed, search for the first block of the new epoch
-		let boundary = client
+	let trusted_height = consensus_state.finalized_height;
+	let update = cached_proof(client.consensus_state_id, trusted_height, || async {
+		let current_epoch = consensus_state.current_epoch;
+		let latest_epoch = client
 			.prover
-			.find_epoch_boundary(consensus_state.finalized_height, latest_block, current_epoch)
+			.fetch_current_epoch(latest_block)
 			.await
-			.map_err(|e| anyhow::anyhow!("Failed to find epoch boundary: {e}"))?;
+			.map_err(|e| anyhow::anyhow!("Failed to read currentEpoch: {e}"))?;
 
 		log::info!(
 			target: crate::LOG_TARGET,
-			"Epoch boundary detected at block {}. Transition {} -> {}",
-			boundary,
+			"New block available. Latest: {} (epoch {}), Finalized: {} (epoch {})",
+			latest_block,
+			latest_epoch,
+			consensus_state.finalized_height,
 			current_epoch,
-			current_epoch + 1
 		);
-		boundary
-	} else {
+
+		// Determine the target block for the update.
+		// If we've crossed epoch boundaries, walk back to find the first block of the new epoch.
+		let target_block = if latest_epoch > current_epoch {
+			// Epoch changed, search for the first block of the new epoch
+			let boundary = client
+				.prover
+				.find_epoch_boundary(consensus_state.finalized_height, latest_block, current_epoch)
+				.await
+				.map_err(|e| anyhow::anyhow!("Failed to find epoch boundary: {e}"))?;
+
+			log::info!(
+				target: crate::LOG_TARGET,
+				"Epoch boundary detected at block {}. Transition {} -> {}",
+				boundary,
+				current_epoch,
+				current_epoch + 1
+			);
+			boundary
+		} else {
+			log::trace!(
+				target: crate::LOG_TARGET,
+				"Same epoch. Syncing latest block {}",
+				latest_block
+			);
+			latest_block
+		};
+
+		let update = client.prover.fetch_block_update(target_block).await?;
+
 		log::trace!(
 			target: crate::LOG_TARGET,
-			"Same epoch. Syncing latest block {}",
-			latest_block
+			"Fetched update for block {}{}",
+			target_block,
+			if update.validator_set_proof.is_some() { " (with validator set proof)" } else { "" }
 		);
-		latest_block
-	};
-
-	let update = client.prover.fetch_block_update(target_block).await?;
 
-	log::trace!(
-		target: crate::LOG_TARGET,
-		"Fetched update for block {}{}",
-		target_block,
-		if update.validator_set_proof.is_some() { " (with validator set proof)" } else { "" }
-	);
+		Ok::<_, anyhow::Error>(Some((target_block, update)))
+	})
+	.await?;
 
-	Ok(Some(update))
+	Ok(update.map(|update| (trusted_height, update)))
 }
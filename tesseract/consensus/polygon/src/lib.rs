@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::{future::pending, sync::Arc, time::Duration};
 use tendermint_primitives::{self, Client, CodecTrustedState};
 use tesseract_evm::{EvmClient, EvmConfig};
-use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_primitives::{proof_cache::evict_proofs, IsmpHost, IsmpProvider};
 
 mod client;
 mod notification;
@@ -143,7 +143,7 @@ impl IsmpHost for PolygonPosHost {
 			loop {
 				interval.as_mut().tick().await;
 				match consensus_notification(&client, counterparty_clone.clone()).await {
-					Ok(Some(update)) => {
+					Ok(Some((trusted_height, update))) => {
 						use ismp::messaging::ConsensusMessage;
 						let consensus_message = ConsensusMessage {
 							consensus_proof: update.encode(),
@@ -165,7 +165,8 @@ impl IsmpHost for PolygonPosHost {
 							log::error!(
 								target: "tesseract", "Failed to submit transaction to {}: {err:?}",
 								counterparty.name()
-							)
+							);
+							evict_proofs(client.consensus_state_id, trusted_height).await;
 						}
 					},
 					Ok(None) => {
@@ -7,17 +7,16 @@ use ibc_core_commitment_types::{
 use ics23::CommitmentProof;
 use std::{result::Result::Ok, sync::Arc, vec::Vec};
 use tendermint_ics23_primitives::ICS23HostFunctions;
-use tendermint_primitives::{
-	Client, CodecConsensusProof, ConsensusProof, TrustedState, ValidatorSet,
-};
-use tendermint_verifier::validate_validator_set_hash;
-use tesseract_primitives::IsmpProvider;
+use tendermint_primitives::{Client, TrustedState, VersionedConsensusProof};
+use tendermint_prover::prove_header_update;
+use tesseract_primitives::{proof_cache::cached_proof, IsmpProvider};
 
-/// Notification logic for Polygon POS relayer
+/// Notification logic for Polygon POS relayer. Returns the update along with the trusted height
+/// it builds on.
 pub async fn consensus_notification(
 	client: &PolygonPosHost,
 	_counterparty: Arc<dyn IsmpProvider>,
-) -> anyhow::Result<Option<PolygonConsensusUpdate>> {
+) -> anyhow::Result<Option<(u64, PolygonConsensusUpdate)>> {
 	let latest_height = client.prover.latest_height().await?;
 
 	let consensus_state_serialized: Vec<u8> =
@@ -30,115 +29,40 @@ pub async fn consensus_notification(
 		TrustedState::try_from(consensus_state.clone().tendermint_state)
 			.map_err(anyhow::Error::msg)?;
 
-	let untrusted_header = client.prover.signed_header(latest_height).await?;
-
-	let validator_set_hash_match = validate_validator_set_hash(
-		&ValidatorSet::new(trusted_state.validators.clone(), None),
-		untrusted_header.header.validators_hash,
-		false,
-	);
-
-	let next_validator_set_hash_match = validate_validator_set_hash(
-		&ValidatorSet::new(trusted_state.next_validators.clone(), None),
-		untrusted_header.header.validators_hash,
-		true,
-	);
-
-	let maybe_milestone_update =
-		build_milestone_update(client, untrusted_header.header.height.value(), &consensus_state)
-			.await?;
-
-	match validator_set_hash_match.is_ok() && next_validator_set_hash_match.is_ok() {
-		true => {
-			log::trace!(target: crate::LOG_TARGET, "Onchain Validator set matches signed header, constructing consensus proof");
-			let next_validators = client.prover.next_validators(latest_height).await?;
-
-			return Ok(Some(PolygonConsensusUpdate {
-				tendermint_proof: CodecConsensusProof::from(&ConsensusProof::new(
-					untrusted_header.clone(),
-					if untrusted_header.header.next_validators_hash.is_empty() {
-						None
-					} else {
-						Some(next_validators)
-					},
-				)),
-				milestone_update: maybe_milestone_update,
-			}));
-		},
-		false => {
-			log::trace!(target: crate::LOG_TARGET, "No match found between onchain validator set latest header, will begin syncing");
-			// Binary search for the highest height whose validator set still
-			// matches the trusted state. Validator sets only rotate at span
-			// boundaries, so the predicate "validator set matches the trusted
-			// state" is monotonic over height: true at/just above the trusted
-			// height and false once the set rotates. We want the largest matching
-			// height to advance the client as far as possible in a single update.
-			let mut lo = trusted_state.height + 1;
-			let mut hi = latest_height - 1;
-			let mut matched_header = None;
-			let mut matched_height = 0u64;
-			while lo <= hi {
-				let mid = lo + (hi - lo) / 2;
-				log::trace!(target: crate::LOG_TARGET, "Checking for validator set match at {mid}");
-				let header = match client.prover.signed_header(mid).await {
-					Ok(h) => h,
-					Err(e) => {
-						log::trace!(target: crate::LOG_TARGET, "Error fetching tendermint header for {mid}, will retry \n {e:?}");
-						continue;
-					},
-				};
-
-				let validator_set_hash_match = validate_validator_set_hash(
-					&ValidatorSet::new(trusted_state.validators.clone(), None),
-					header.header.validators_hash,
-					false,
-				);
-				let next_validator_set_hash_match = validate_validator_set_hash(
-					&ValidatorSet::new(trusted_state.next_validators.clone(), None),
-					header.header.validators_hash,
-					true,
-				);
-				if validator_set_hash_match.is_ok() || next_validator_set_hash_match.is_ok() {
-					log::trace!(target: crate::LOG_TARGET, "validator set match found at {mid}");
-					matched_header = Some(header);
-					matched_height = mid;
-					lo = mid + 1;
-				} else {
-					hi = mid - 1;
-				}
-			}
-
-			if matched_header.is_some() {
-				let matched_header = matched_header.expect("Header must be present if found");
-				let next_validators = client.prover.next_validators(matched_height).await?;
-
-				// Also attempt to construct a milestone update corresponding to the matched header
-				// height
-				let maybe_milestone_update = build_milestone_update(
-					client,
-					matched_header.header.height.value(),
-					&consensus_state,
-				)
-				.await?;
-
-				return Ok(Some(PolygonConsensusUpdate {
-					tendermint_proof: CodecConsensusProof::from(&ConsensusProof::new(
-						matched_header.clone(),
-						if matched_header.header.next_validators_hash.is_empty() {
-							None
-						} else {
-							Some(next_validators)
-						},
-					)),
-					milestone_update: maybe_milestone_update,
-				}));
-			} else {
-				log::error!(target: crate::LOG_TARGET, "Fatal error, failed to find any header that matches onchain validator set");
-			}
-		},
+	if latest_height <= trusted_state.height {
+		log::trace!(target: crate::LOG_TARGET, "No new update found for polygon");
+		return Ok(None);
 	}
-	log::trace!(target: crate::LOG_TARGET, "No new update found for polygon");
-	Ok(None)
+
+	// Only the header proofs are cached, the milestone update depends on the last finalized block
+	// of this counterparty.
+	let header_proofs = cached_proof(client.consensus_state_id, trusted_state.height, || async {
+		// Bisects towards the trusted height whenever the trusted validators can't vouch for a
+		// header, so updates across span rotations come back as a chain of headers.
+		let mut consensus_proofs =
+			prove_header_update(&client.prover, &trusted_state, latest_height).await?;
+		let consensus_proof = consensus_proofs
+			.pop()
+			.ok_or_else(|| anyhow::anyhow!("Prover returned no headers for {latest_height}"))?;
+		log::trace!(target: crate::LOG_TARGET, "Constructed consensus proof for {latest_height} with {} intermediate headers", consensus_proofs.len());
+
+		let height = consensus_proof.height();
+		let ancestry =
+			consensus_proofs.iter().map(VersionedConsensusProof::from).collect::<Vec<_>>();
+		Ok::<_, anyhow::Error>(Some((
+			height,
+			(height, VersionedConsensusProof::from(&consensus_proof), ancestry),
+		)))
+	})
+	.await?;
+	let Some((height, tendermint_proof, ancestry)) = header_proofs else { return Ok(None) };
+
+	let milestone_update = build_milestone_update(client, height, &consensus_state).await?;
+
+	Ok(Some((
+		trusted_state.height,
+		PolygonConsensusUpdate { tendermint_proof, milestone_update, ancestry },
+	)))
 }
 
 async fn build_milestone_update(
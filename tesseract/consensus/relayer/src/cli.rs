@@ -2,10 +2,14 @@ use anyhow::anyhow;
 use clap::Parser;
 use codec::Decode;
 use ismp::host::StateMachine;
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 use tesseract_beefy::backend::ProofBackend;
 use tesseract_consensus_config::create_client_map;
-use tesseract_primitives::IsmpHost;
+use tesseract_primitives::{
+	expiry::{serve_metrics, track_expiry, ExpiryMetrics, Registry},
+	proof_cache::ProofCache,
+	IsmpHost,
+};
 use tesseract_substrate::config::{Blake2SubstrateChain, KeccakSubstrateChain};
 
 use crate::{
@@ -48,8 +52,18 @@ impl Cli {
 			anyhow!("[hyperbridge] section required for the consensus/prover binary")
 		})?;
 
+		let relayer = relayer.unwrap_or_default();
+		let registry = match relayer.metrics {
+			Some(_) => {
+				let registry = Registry::new();
+				ExpiryMetrics::register(&registry)?;
+				Some(registry)
+			},
+			None => None,
+		};
+
 		let tokio_handle = tokio::runtime::Handle::current();
-		let mut task_manager = TaskManager::new(tokio_handle, None)?;
+		let mut task_manager = TaskManager::new(tokio_handle, registry.as_ref())?;
 
 		let hyperbridge = hyperbridge_config
 			.clone()
@@ -64,8 +78,24 @@ impl Cli {
 			beefy_host.backend.init_queues(&state_machines).await?;
 		}
 
+		if let Some(ref dir) = relayer.proof_cache {
+			ProofCache::init(dir).await?;
+			log::info!(target: crate::LOG_TARGET, "Caching consensus proofs in {dir}");
+		}
+
+		if let (Some(address), Some(registry)) = (relayer.metrics.clone(), registry) {
+			let address = address
+				.parse()
+				.map_err(|err| anyhow!("Invalid metrics address {address}: {err}"))?;
+			log::info!(target: crate::LOG_TARGET, "Serving metrics on {address}");
+			task_manager.spawn_handle().spawn("metrics", "metrics", async move {
+				if let Err(err) = serve_metrics(address, registry).await {
+					log::error!(target: crate::LOG_TARGET, "Metrics server has terminated: {err:?}")
+				}
+			});
+		}
+
 		let clients = create_client_map(chains.clone()).await?;
-		let relayer = relayer.unwrap_or_default();
 
 		if let Some(ref state_machine_str) = self.base {
 			let state_machine = StateMachine::from_str(state_machine_str.as_str())
@@ -121,6 +151,27 @@ impl Cli {
 					}
 				},
 			);
+
+			// Track the expiry of both clients, forcing updates on routes that go quiet
+			let expiry = relayer.expiry.clone().unwrap_or_default();
+			let hyperbridge: Arc<dyn IsmpHost> = Arc::new(
+				hyperbridge_config
+					.clone()
+					.into_client::<Blake2SubstrateChain, KeccakSubstrateChain>()
+					.await?,
+			);
+			let mut routes = vec![(client.clone(), hyperbridge.provider())];
+			if relayer.enable_hyperbridge_consensus {
+				routes.push((hyperbridge, client.provider()));
+			}
+			let trackers = routes
+				.into_iter()
+				.map(|(host, counterparty)| track_expiry(host, counterparty, expiry.clone()));
+			task_manager.spawn_handle().spawn(
+				"expiry",
+				"expiry",
+				futures::future::join_all(trackers).map(|_| ()),
+			);
 		}
 
 		// If there is a configuration for the maximum interval between consensus updates, then
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tesseract_consensus_config::HostKind;
 use tesseract_evm::EvmConfig;
+use tesseract_primitives::expiry::ExpiryConfig;
 use tesseract_substrate::SubstrateConfig;
 
 use toml::{Table, Value};
@@ -19,6 +20,15 @@ pub struct RelayerConfig {
 	/// Enables the hyperbridge host
 	#[serde(default = "default_true")]
 	pub enable_hyperbridge_consensus: bool,
+	/// Directory of the on-disk consensus proof cache shared by all consensus hosts. Proofs are
+	/// not cached if this is not supplied.
+	pub proof_cache: Option<String>,
+	/// Consensus client expiry tracking, enabled with the default margins if this is not
+	/// supplied.
+	pub expiry: Option<ExpiryConfig>,
+	/// Address to serve Prometheus metrics on, e.g. `127.0.0.1:9615`. Metrics are not served if
+	/// this is not supplied.
+	pub metrics: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -31,6 +41,9 @@ impl Default for RelayerConfig {
 			challenge_period: None,
 			maximum_update_intervals: None,
 			enable_hyperbridge_consensus: true,
+			proof_cache: None,
+			expiry: None,
+			metrics: None,
 		}
 	}
 }
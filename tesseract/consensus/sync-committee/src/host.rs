@@ -29,7 +29,7 @@ use sync_committee_primitives::{constants::Config, util::compute_sync_committee_
 
 use crate::notification::consensus_notification;
 use op_verifier::{CANNON, _PERMISSIONED};
-use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_primitives::{proof_cache::evict_proofs, IsmpHost, IsmpProvider};
 
 #[async_trait::async_trait]
 impl<
@@ -94,7 +94,7 @@ impl<
 							consensus_state_id: client.consensus_state_id,
 							signer: H160::random().0.to_vec(),
 						};
-						return Some((Ok::<_, Error>(Some(update)), interval));
+						return Some((Ok::<_, Error>(Some((None, update))), interval));
 					},
 					Ok(None) => {},
 					Err(err) =>
@@ -147,13 +147,16 @@ impl<
 					})
 					.await
 				{
-					Ok(Some(beacon_message)) => {
+					Ok(Some((trusted_height, beacon_message))) => {
 						let update = ConsensusMessage {
 							consensus_proof: beacon_message.encode(),
 							consensus_state_id: client.consensus_state_id,
 							signer: H160::random().0.to_vec(),
 						};
-						return Some((Ok::<_, Error>(Some(update)), interval));
+						return Some((
+							Ok::<_, Error>(Some((Some(trusted_height), update))),
+							interval,
+						));
 					},
 					Ok(None) => return Some((Ok::<_, Error>(None), interval)),
 					Err(err) =>
@@ -180,7 +183,7 @@ impl<
 		let provider = self.provider();
 		while let Some(item) = stream.next().await {
 			match item {
-				Ok(consensus_message) => {
+				Ok((trusted_height, consensus_message)) => {
 					log::info!(
 						target: "tesseract",
 						"🛰️ Transmitting consensus message from {} to {}",
@@ -196,7 +199,10 @@ impl<
 						log::error!(
 							target: "tesseract", "Failed to submit transaction to {}: {err:?}",
 							counterparty.name()
-						)
+						);
+						if let Some(trusted_height) = trusted_height {
+							evict_proofs(self.consensus_state_id, trusted_height).await;
+						}
 					}
 				},
 				Err(e) => {
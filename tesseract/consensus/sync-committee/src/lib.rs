@@ -17,6 +17,7 @@
 pub const LOG_TARGET: &str = "consensus-sync-committee";
 
 use alloy::providers::Provider;
+use anyhow::Context;
 use arb_host::{ArbConfig, ArbHost};
 use ismp::{consensus::ConsensusStateId, host::StateMachine};
 pub use ismp_sync_committee::types::{BeaconClientUpdate, ConsensusState};
@@ -33,11 +34,10 @@ use sync_committee_primitives::{
 		gnosis, Config, ETH1_DATA_VOTES_BOUND_ETH, ETH1_DATA_VOTES_BOUND_GNO,
 		PROPOSER_LOOK_AHEAD_LIMIT_ETHEREUM, PROPOSER_LOOK_AHEAD_LIMIT_GNO,
 	},
-	types::VerifierState,
-	util::{compute_epoch_at_slot, compute_sync_committee_period_at_slot},
+	spec::NetworkSpec,
+	types::{VerifierState, VerifierStateUpdate},
 };
-use sync_committee_prover::SyncCommitteeProver;
-pub use sync_committee_verifier::verify_sync_committee_attestation;
+use sync_committee_prover::{ProverBackend, SyncCommitteeProver};
 use tesseract_evm::{EvmClient, EvmConfig};
 use tesseract_primitives::{IsmpHost, IsmpProvider};
 
@@ -60,6 +60,15 @@ pub struct HostConfig {
 
 	/// Interval in seconds at which consensus updates should happen
 	pub consensus_update_frequency: u64,
+
+	/// Where updates are proven from, `beacon_state` (default) or `light_client`. The
+	/// `light_client` backend works with beacon nodes that don't serve the debug api.
+	#[serde(default)]
+	pub prover_backend: ProverBackend,
+
+	/// Path to the beacon `config.yaml` of the network. When set, new consensus states carry
+	/// the fork schedule it describes instead of the one compiled into the runtime.
+	pub network_config: Option<String>,
 }
 
 impl SyncCommitteeConfig {
@@ -143,6 +152,9 @@ pub struct SyncCommitteeHost<
 
 	// retry policy for consensus client requests
 	pub retry: again::RetryPolicy,
+
+	/// Network spec stored in new consensus states, parsed from the configured `config.yaml`
+	pub network_spec: Option<NetworkSpec>,
 }
 
 impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LIMIT: usize>
@@ -153,10 +165,19 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 		evm: &EvmConfig,
 		l2_config: BTreeMap<StateMachine, L2Config>,
 	) -> Result<Self, anyhow::Error> {
-		let prover = SyncCommitteeProver::new(host.beacon_http_urls.clone());
+		let prover = SyncCommitteeProver::new(host.beacon_http_urls.clone())
+			.with_backend(host.prover_backend);
 		let el = tesseract_evm::create_provider(&evm.rpc_urls)?;
 
 		let provider = Arc::new(EvmClient::new(evm.clone()).await?);
+		let network_spec = match host.network_config.as_ref() {
+			Some(path) => {
+				let config_yaml = std::fs::read_to_string(path)
+					.with_context(|| format!("Failed to read network config {path}"))?;
+				Some(prover.fetch_network_spec(&config_yaml).await?)
+			},
+			None => None,
+		};
 
 		// Create the hosts for the L2s if config is present
 		let mut l2_clients = BTreeMap::new();
@@ -184,6 +205,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			consensus_update_frequency: Duration::from_secs(host.consensus_update_frequency),
 			el: Arc::new(el),
 			retry: again::RetryPolicy::fixed(Duration::from_millis(500)),
+			network_spec,
 		})
 	}
 
@@ -193,16 +215,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 		trusted_block_id: Option<&str>,
 	) -> Result<ConsensusState, anyhow::Error> {
 		let block_id = trusted_block_id.unwrap_or("finalized");
-		let block_header = self.prover.fetch_header(&block_id).await?;
-		let state = self.prover.fetch_beacon_state(&block_header.slot.to_string()).await?;
-
-		let client_state = VerifierState {
-			finalized_header: block_header.clone(),
-			latest_finalized_epoch: compute_epoch_at_slot::<C>(block_header.slot),
-			current_sync_committee: state.current_sync_committee,
-			next_sync_committee: state.next_sync_committee,
-			state_period: compute_sync_committee_period_at_slot::<C>(block_header.slot),
-		};
+		let client_state = self.prover.fetch_verifier_state(block_id).await?;
 
 		let mut l2_consensus = BTreeMap::new();
 		for (state_machine, address) in params.l2_oracle_address {
@@ -226,6 +239,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			light_client_state: client_state,
 			chain_id: chain_id as u32,
 			l2_consensus,
+			network_spec: self.network_spec.clone(),
 		};
 
 		Ok(consensus_state)
@@ -262,6 +276,7 @@ impl<C: Config, const ETH1_DATA_VOTES_BOUND: usize, const PROPOSER_LOOK_AHEAD_LI
 			el: self.el.clone(),
 			retry: self.retry.clone(),
 			provider: self.provider.clone(),
+			network_spec: self.network_spec.clone(),
 		}
 	}
 }
@@ -271,3 +286,16 @@ pub struct GetConsensusStateParams {
 	pub rollup_core_address: BTreeMap<StateMachine, H160>,
 	pub dispute_factory_address: BTreeMap<StateMachine, (H160, Vec<u32>)>,
 }
+
+/// Verify a sync committee update against `consensus_state`, using the network spec stored in it
+/// like the on-chain client does.
+pub fn verify_sync_committee_attestation<C: Config>(
+	consensus_state: &ConsensusState,
+	update: VerifierStateUpdate,
+) -> Result<VerifierState, sync_committee_verifier::error::Error> {
+	sync_committee_verifier::verify_sync_committee_attestation_with_spec::<C>(
+		&consensus_state.network_spec::<C>(),
+		consensus_state.light_client_state.clone(),
+		update,
+	)
+}
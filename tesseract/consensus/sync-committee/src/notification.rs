@@ -9,7 +9,7 @@ use sync_committee_primitives::{
 	consensus_types::Checkpoint,
 	constants::{Config, Root},
 };
-use tesseract_primitives::IsmpProvider;
+use tesseract_primitives::{proof_cache::cached_proof, IsmpProvider};
 
 use crate::SyncCommitteeHost;
 
@@ -21,6 +21,7 @@ pub struct EventResponse {
 	pub execution_optimistic: bool,
 }
 
+/// Returns the update along with the trusted slot it builds on
 pub async fn consensus_notification<
 	T: Config + Send + Sync + 'static,
 	const ETH1_DATA_VOTES_BOUND: usize,
@@ -29,7 +30,7 @@ pub async fn consensus_notification<
 	client: &SyncCommitteeHost<T, ETH1_DATA_VOTES_BOUND, PROPOSER_LOOK_AHEAD_LIMIT>,
 	counterparty: Arc<dyn IsmpProvider>,
 	checkpoint: Checkpoint,
-) -> Result<Option<BeaconClientUpdate>, anyhow::Error> {
+) -> Result<Option<(u64, BeaconClientUpdate)>, anyhow::Error> {
 	let consensus_state =
 		counterparty.query_consensus_state(None, client.consensus_state_id).await?;
 	let consensus_state = ConsensusState::decode(&mut &*consensus_state)?;
@@ -38,15 +39,22 @@ pub async fn consensus_notification<
 		state_id: client.state_machine,
 		consensus_state_id: client.consensus_state_id,
 	};
-	let update = client
-		.prover
-		.fetch_light_client_update(light_client_state.clone(), checkpoint.clone(), None)
-		.await?;
+	let trusted_height = light_client_state.finalized_header.slot;
+	let update = cached_proof(client.consensus_state_id, trusted_height, || async {
+		let update = client
+			.prover
+			.fetch_light_client_update(light_client_state.clone(), checkpoint.clone(), None)
+			.await?;
+		Ok::<_, anyhow::Error>(update.map(|consensus_update| {
+			(consensus_update.finalized_header.slot, BeaconClientUpdate { consensus_update })
+		}))
+	})
+	.await?;
 
 	let execution_layer_height = counterparty.query_latest_height(state_machine_id).await? as u64;
 
-	let consensus_update = if let Some(update) = update {
-		update
+	let consensus_update = if let Some(BeaconClientUpdate { consensus_update }) = update {
+		consensus_update
 	} else {
 		trace!(target: "sync-committee-prover", "light client update is none");
 		return Ok(None);
@@ -63,5 +71,5 @@ pub async fn consensus_notification<
 
 	let message = BeaconClientUpdate { consensus_update };
 
-	Ok(Some(message))
+	Ok(Some((trusted_height, message)))
 }
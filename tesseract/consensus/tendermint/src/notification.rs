@@ -2,10 +2,8 @@ use crate::{ConsensusState, TendermintHost};
 use codec::Decode;
 use ismp_tendermint::TendermintConsensusUpdate;
 use std::{result::Result::Ok, sync::Arc, vec::Vec};
-use tendermint_primitives::{
-	Client, CodecConsensusProof, ConsensusProof, TrustedState, ValidatorSet,
-};
-use tendermint_verifier::validate_validator_set_hash;
+use tendermint_primitives::{Client, TrustedState, VersionedConsensusProof};
+use tendermint_prover::prove_header_update;
 use tesseract_primitives::IsmpProvider;
 
 /// Notification logic for Tendermint relayer
@@ -24,90 +22,22 @@ pub async fn consensus_notification(
 	let trusted_state: TrustedState =
 		TrustedState::try_from(consensus_state.tendermint_state).map_err(anyhow::Error::msg)?;
 
-	let untrusted_header = client.prover.signed_header(latest_height).await?;
-
-	let validator_set_hash_match = validate_validator_set_hash(
-		&ValidatorSet::new(trusted_state.validators.clone(), None),
-		untrusted_header.header.validators_hash,
-		false,
-	);
-
-	let next_validator_set_hash_match = validate_validator_set_hash(
-		&ValidatorSet::new(trusted_state.next_validators.clone(), None),
-		untrusted_header.header.validators_hash,
-		true,
-	);
-
-	match validator_set_hash_match.is_ok() && next_validator_set_hash_match.is_ok() {
-		true => {
-			log::trace!(target: crate::LOG_TARGET, "Onchain Validator set matches signed header, constructing consensus proof");
-			let next_validators = client.prover.next_validators(latest_height).await?;
-
-			return Ok(Some(TendermintConsensusUpdate {
-				tendermint_proof: CodecConsensusProof::from(&ConsensusProof::new(
-					untrusted_header.clone(),
-					if untrusted_header.header.next_validators_hash.is_empty() {
-						None
-					} else {
-						Some(next_validators)
-					},
-				)),
-			}));
-		},
-		false => {
-			log::trace!(target: crate::LOG_TARGET, "No match found between onchain validator set latest header, will begin syncing");
-			// Backward traversal
-			let mut height = latest_height - 1;
-			let mut matched_header = None;
-			while height > trusted_state.height {
-				log::trace!(target: crate::LOG_TARGET, "Checking for validator set match at {height}");
-				let header_res = client.prover.signed_header(height).await;
-				let header = match header_res {
-					Ok(h) => h,
-					Err(e) => {
-						log::trace!(target: crate::LOG_TARGET, "Error fetching tendermint header for {height}, will retry \n {e:?}");
-						continue;
-					},
-				};
-
-				let validator_set_hash_match = validate_validator_set_hash(
-					&ValidatorSet::new(trusted_state.validators.clone(), None),
-					header.header.validators_hash,
-					false,
-				);
-				let next_validator_set_hash_match = validate_validator_set_hash(
-					&ValidatorSet::new(trusted_state.next_validators.clone(), None),
-					header.header.validators_hash,
-					true,
-				);
-				if validator_set_hash_match.is_ok() && next_validator_set_hash_match.is_ok() {
-					log::trace!(target: crate::LOG_TARGET, "validator set match found at {height}");
-					matched_header = Some(header);
-					break;
-				}
-				height -= 1;
-			}
-
-			if matched_header.is_some() {
-				let matched_height = height;
-				let matched_header = matched_header.expect("Header must be present if found");
-				let next_validators = client.prover.next_validators(matched_height).await?;
-
-				return Ok(Some(TendermintConsensusUpdate {
-					tendermint_proof: CodecConsensusProof::from(&ConsensusProof::new(
-						matched_header.clone(),
-						if matched_header.header.next_validators_hash.is_empty() {
-							None
-						} else {
-							Some(next_validators)
-						},
-					)),
-				}));
-			} else {
-				log::error!(target: crate::LOG_TARGET, "Fatal error, failed to find any header that matches onchain validator set");
-			}
-		},
+	if latest_height <= trusted_state.height {
+		log::trace!(target: crate::LOG_TARGET, "No new update found");
+		return Ok(None);
 	}
-	log::trace!(target: crate::LOG_TARGET, "No new update found");
-	Ok(None)
+
+	// Bisects towards the trusted height whenever the trusted validators can't vouch for a
+	// header, so updates across validator set rotations come back as a chain of headers.
+	let mut consensus_proofs =
+		prove_header_update(client.prover.as_ref(), &trusted_state, latest_height).await?;
+	let consensus_proof = consensus_proofs
+		.pop()
+		.ok_or_else(|| anyhow::anyhow!("Prover returned no headers for {latest_height}"))?;
+	log::trace!(target: crate::LOG_TARGET, "Constructed consensus proof for {latest_height} with {} intermediate headers", consensus_proofs.len());
+
+	Ok(Some(TendermintConsensusUpdate {
+		tendermint_proof: VersionedConsensusProof::from(&consensus_proof),
+		ancestry: consensus_proofs.iter().map(VersionedConsensusProof::from).collect(),
+	}))
 }
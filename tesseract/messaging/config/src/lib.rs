@@ -90,6 +90,38 @@ impl AnyConfig {
 		})
 	}
 
+	/// Point the client at a single rpc endpoint instead of the configured ones, e.g. a local
+	/// fork of the chain. Only the endpoint messages are submitted through is replaced.
+	pub fn with_rpc_url(self, url: String) -> Self {
+		match self {
+			Self::Substrate(mut config) => {
+				config.rpc_ws = url;
+				config.additional_rpc_ws.clear();
+				Self::Substrate(config)
+			},
+			Self::Evm(mut config) => {
+				config.rpc_urls = vec![url];
+				Self::Evm(config)
+			},
+			Self::Tendermint(mut tendermint_config) => {
+				tendermint_config.evm_config.rpc_urls = vec![url];
+				Self::Tendermint(tendermint_config)
+			},
+			Self::SubstrateEvm(mut substrate_evm_config) => {
+				substrate_evm_config.evm.rpc_urls = vec![url];
+				Self::SubstrateEvm(substrate_evm_config)
+			},
+			Self::PharosEvm(mut config) => {
+				config.rpc_urls = vec![url];
+				Self::PharosEvm(config)
+			},
+			Self::Tron(mut config) => {
+				config.evm.rpc_urls = vec![url];
+				Self::Tron(config)
+			},
+		}
+	}
+
 	/// The chain's signing key as configured. Returns the raw string from the
 	/// per-chain TOML block, which is the secp256k1 private key (EVM family) or
 	/// SR25519 seed (Substrate). `None` signals that this chain does not
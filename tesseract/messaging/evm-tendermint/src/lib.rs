@@ -97,6 +97,10 @@ impl<T: EvmStoreKeys> IsmpProvider for TendermintEvmClient<T> {
 		self.inner.query_challenge_period(id).await
 	}
 
+	async fn query_consensus_expiry(&self, id: ConsensusStateId) -> Result<Duration, Error> {
+		self.inner.query_consensus_expiry(id).await
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, Error> {
 		self.inner.query_timestamp().await
 	}
@@ -298,8 +302,8 @@ impl<T: EvmStoreKeys> IsmpProvider for TendermintEvmClient<T> {
 		self.inner.address()
 	}
 
-	fn sign(&self, msg: &[u8]) -> Signature {
-		self.inner.sign(msg)
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error> {
+		self.inner.sign(msg).await
 	}
 
 	async fn set_latest_finalized_height(
@@ -334,6 +338,10 @@ impl<T: EvmStoreKeys> IsmpProvider for TendermintEvmClient<T> {
 	async fn fee_token_decimals(&self) -> Result<u8, Error> {
 		self.inner.fee_token_decimals().await
 	}
+
+	async fn gas_price(&self) -> Result<Option<U256>, Error> {
+		self.inner.gas_price().await
+	}
 }
 
 #[async_trait::async_trait]
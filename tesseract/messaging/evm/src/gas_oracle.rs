@@ -1,5 +1,6 @@
 use crate::{
 	abi::{arb_gas_info::ArbGasInfoInstance, ovm_gas_price_oracle::OvmGasPriceOracleInstance},
+	price_oracle::PriceOracle,
 	AlloyProvider,
 };
 use alloy::{
@@ -7,36 +8,14 @@ use alloy::{
 	primitives::{Address, Bytes as AlloyBytes, U256 as AlloyU256},
 	providers::Provider,
 };
-use alloy_sol_macro::sol;
 use anyhow::{anyhow, Error};
 use hex_literal::hex;
 use ismp::host::StateMachine;
-use ismp_abi::evm_host::EvmHostInstance;
 use primitive_types::U256;
 use serde::Deserialize;
-use sp_core::H160;
 use std::{fmt::Debug, sync::Arc};
 use tesseract_primitives::Cost;
 
-sol!(
-	#[allow(missing_docs)]
-	#[sol(rpc)]
-	#[derive(Debug, PartialEq, Eq)]
-	interface IUniswapV2Router {
-		function getAmountsIn(uint256 amountOut, address[] memory path) public view returns (uint256[] memory amounts);
-		function WETH() external pure returns (address);
-	}
-);
-
-sol!(
-	#[allow(missing_docs)]
-	#[sol(rpc)]
-	#[derive(Debug, PartialEq, Eq)]
-	interface IERC20 {
-		function decimals() external view returns (uint8);
-	}
-);
-
 const ARB_GAS_INFO: [u8; 20] = hex!("000000000000000000000000000000000000006c");
 const OP_GAS_ORACLE: [u8; 20] = hex!("420000000000000000000000000000000000000F");
 
@@ -99,14 +78,12 @@ pub struct GasBreakdown {
 	pub unit_wei_cost: U256,
 }
 
-use geth_primitives::{
-	alloy_u256_to_primitive, primitive_u256_to_alloy as primitive_to_alloy_u256,
-};
+use geth_primitives::alloy_u256_to_primitive;
 
 /// Function gets current gas price (for execution) in wei and return the equivalent in USD,
 pub async fn get_current_gas_cost_in_usd(
 	chain: StateMachine,
-	ismp_host_address: H160,
+	price_oracle: &PriceOracle,
 	client: Arc<AlloyProvider>,
 ) -> Result<GasBreakdown, Error> {
 	let mut gas_price = U256::zero();
@@ -151,16 +128,15 @@ pub async fn get_current_gas_cost_in_usd(
 		},
 		chain => Err(anyhow!("Unknown chain: {chain:?}"))?,
 	}
-	// A reverting Uniswap pool (e.g. ds-math-sub-underflow on chains whose
-	// wrapper has thin liquidity, or a `WETH()` call that returns no data on
-	// chains without a canonical WETH on this router) should not poison gas
-	// estimation. Fee profitability calculations downstream see a zero USD
-	// price for native gas in that case, which is fine for delivery — the
-	// raw `gas_price` we just fetched from the node still flows through to
-	// the actual transaction submission.
-	let token_usd = get_price_from_uniswap_router(ismp_host_address, client)
-		.await
-		.unwrap_or_default();
+	// A failing price oracle (e.g. every source reverting, stale or out of
+	// bounds) should not poison gas estimation. Fee profitability calculations
+	// downstream see a zero USD price for native gas in that case, which is
+	// fine for delivery — the raw `gas_price` we just fetched from the node
+	// still flows through to the actual transaction submission.
+	let token_usd = price_oracle.price().await.unwrap_or_else(|err| {
+		log::warn!(target: crate::LOG_TARGET, "No native token price for {chain:?}: {err:?}");
+		U256::zero()
+	});
 
 	let unit_wei = get_cost_of_one_wei(token_usd);
 	let gas_price_cost = convert_27_decimals_to_18_decimals(unit_wei * gas_price)?;
@@ -180,49 +156,6 @@ fn get_cost_of_one_wei(eth_usd: U256) -> U256 {
 	eth_usd / eth_to_wei
 }
 
-async fn get_price_from_uniswap_router(
-	ismp_host: H160,
-	client: Arc<AlloyProvider>,
-) -> Result<U256, Error> {
-	let host = EvmHostInstance::new(Address::from_slice(&ismp_host.0), client.clone());
-	let params = host.hostParams().block(BlockId::latest()).call().await?;
-
-	// There are no uniswap pool on testnet, return 1 usd as native token value
-	if params.hyperbridge.0.starts_with(b"KUSAMA") {
-		return Ok(U256::from(10).pow(U256::from(27)));
-	}
-
-	let uniswap_v2 = H160::from_slice(params.uniswapV2.as_slice());
-	let fee_token = Address::from_slice(params.feeToken.as_slice());
-
-	if uniswap_v2 == H160::zero() {
-		return Err(anyhow!("Uniswap V2 Router not configured in Host Params"));
-	}
-
-	let router = IUniswapV2Router::IUniswapV2RouterInstance::new(params.uniswapV2, client.clone());
-	let native_token = router.WETH().block(BlockId::latest()).call().await?;
-
-	let fee_token_contract = IERC20::IERC20Instance::new(fee_token, client.clone());
-	let fee_token_decimals = fee_token_contract.decimals().block(BlockId::latest()).call().await?;
-
-	let native_token_contract = IERC20::IERC20Instance::new(native_token, client.clone());
-	let native_decimals = native_token_contract.decimals().block(BlockId::latest()).call().await?;
-
-	let path = vec![fee_token, native_token];
-	let amount_out = primitive_to_alloy_u256(U256::from(10).pow(U256::from(native_decimals)));
-
-	let amounts = router.getAmountsIn(amount_out, path).block(BlockId::latest()).call().await?;
-
-	if amounts.is_empty() {
-		return Err(anyhow!("Invalid amounts returned from Uniswap V2 Router"));
-	}
-
-	let amount_stable = alloy_u256_to_primitive(amounts[0]);
-
-	let target_decimals = 27;
-	Ok(amount_stable * U256::from(10).pow(U256::from(target_decimals - fee_token_decimals as u32)))
-}
-
 /// Returns the L2 data cost for a given transaction data in usd
 pub async fn get_l2_data_cost(
 	rlp_tx: AlloyBytes,
@@ -268,6 +201,7 @@ mod test {
 		get_current_gas_cost_in_usd, get_l2_data_cost, ARBITRUM_CHAIN_ID, BSC_CHAIN_ID,
 		ETHEREUM_CHAIN_ID, GNOSIS_CHAIN_ID, POLYGON_CHAIN_ID,
 	};
+	use crate::price_oracle::PriceOracle;
 	use alloy::{primitives::Bytes, providers::RootProvider};
 	use ismp::host::StateMachine;
 	use std::sync::Arc;
@@ -283,10 +217,11 @@ mod test {
 			.parse()
 			.unwrap();
 		let client = Arc::new(RootProvider::new_http(ethereum_rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd = get_current_gas_cost_in_usd(
 			StateMachine::Evm(ETHEREUM_CHAIN_ID),
-			ismp_host,
+			&oracle,
 			client.clone(),
 		)
 		.await
@@ -305,10 +240,11 @@ mod test {
 			.parse()
 			.unwrap();
 		let client = Arc::new(RootProvider::new_http(rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd = get_current_gas_cost_in_usd(
 			StateMachine::Evm(POLYGON_CHAIN_ID),
-			ismp_host,
+			&oracle,
 			client.clone(),
 		)
 		.await
@@ -327,10 +263,11 @@ mod test {
 			.parse()
 			.unwrap();
 		let client = Arc::new(RootProvider::new_http(ethereum_rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd = get_current_gas_cost_in_usd(
 			StateMachine::Evm(GNOSIS_CHAIN_ID),
-			ismp_host,
+			&oracle,
 			client.clone(),
 		)
 		.await
@@ -349,9 +286,10 @@ mod test {
 			.parse()
 			.unwrap();
 		let client = Arc::new(RootProvider::new_http(rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd =
-			get_current_gas_cost_in_usd(StateMachine::Evm(BSC_CHAIN_ID), ismp_host, client.clone())
+			get_current_gas_cost_in_usd(StateMachine::Evm(BSC_CHAIN_ID), &oracle, client.clone())
 				.await
 				.unwrap();
 
@@ -369,10 +307,11 @@ mod test {
 			.parse()
 			.unwrap();
 		let client = Arc::new(RootProvider::new_http(ethereum_rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd = get_current_gas_cost_in_usd(
 			StateMachine::Evm(ARBITRUM_CHAIN_ID),
-			ismp_host,
+			&oracle,
 			client.clone(),
 		)
 		.await
@@ -392,9 +331,10 @@ mod test {
 		let ethereum_rpc_uri =
 			std::env::var("BASE_MAINNET_URL").expect("op url is not set in .env.");
 		let client = Arc::new(RootProvider::new_http(ethereum_rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 
 		let ethereum_gas_cost_in_usd =
-			get_current_gas_cost_in_usd(StateMachine::Evm(8453), ismp_host, client.clone())
+			get_current_gas_cost_in_usd(StateMachine::Evm(8453), &oracle, client.clone())
 				.await
 				.unwrap();
 
@@ -412,8 +352,9 @@ mod test {
 		let ethereum_rpc_uri =
 			std::env::var("BASE_MAINNET_URL").expect("op url is not set in .env.");
 		let client = Arc::new(RootProvider::new_http(ethereum_rpc_uri.parse().unwrap()));
+		let oracle = PriceOracle::new(Default::default(), ismp_host, client.clone()).unwrap();
 		let ethereum_gas_cost_in_usd =
-			get_current_gas_cost_in_usd(StateMachine::Evm(8453), ismp_host, client.clone())
+			get_current_gas_cost_in_usd(StateMachine::Evm(8453), &oracle, client.clone())
 				.await
 				.unwrap();
 		let data_cost = get_l2_data_cost(
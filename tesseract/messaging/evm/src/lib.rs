@@ -1,7 +1,10 @@
 /// Log/tracing target for this crate.
 pub const LOG_TARGET: &str = "messaging-evm";
 
-use crate::{abi::EvmHostInstance, transport::RpcTransport};
+use crate::{
+	abi::EvmHostInstance,
+	transport::{HealthService, RpcTransport},
+};
 
 use alloy::{
 	eips::BlockId,
@@ -14,36 +17,43 @@ use alloy::{
 		},
 		Identity, Provider, ProviderBuilder, RootProvider,
 	},
-	signers::local::PrivateKeySigner,
 };
 use ismp::{consensus::ConsensusStateId, events::Event, host::StateMachine, messaging::Message};
-use polkadot_sdk::frame_support::crypto::ecdsa::ECDSAExt;
 use primitive_types::{H256, U256};
 
-use evm_state_machine::presets::{REQUEST_COMMITMENTS_SLOT, REQUEST_RECEIPTS_SLOT};
+use evm_state_machine::{
+	presets::{REQUEST_COMMITMENTS_SLOT, REQUEST_RECEIPTS_SLOT},
+	types::EvmReceiptProof,
+};
 
 use ismp_abi::evm_host::{StateCommitment, StateMachineHeight};
+use price_oracle::{PriceOracle, PriceOracleConfig};
 use serde::{Deserialize, Serialize};
-use sp_core::{bytes::from_hex, Pair, H160};
+use sp_core::H160;
 use sp_crypto_hashing::keccak_256;
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
 use tesseract_primitives::{
+	health::{EndpointHealthConfig, EndpointPool},
 	queue::{start_pipeline, PipelineQueue},
-	IsmpProvider, StateMachineUpdated, StreamError, TxResult,
+	signer::Signer,
+	IsmpProvider, StateMachineUpdated, StreamError,
 };
-use tx::handle_message_submission;
+use tx::{dispatch_messages, DispatchedMessages};
+use tx_manager::{TxManager, TxManagerConfig};
 
 pub mod abi;
 mod byzantine;
 pub mod gas_oracle;
+pub mod price_oracle;
+pub mod provider;
 pub mod registry;
+pub mod signer;
 pub mod transport;
-use tx::wait_for_transaction_receipt;
-pub mod provider;
 
 // #[cfg(test)]
 // mod test;
 pub mod tx;
+pub mod tx_manager;
 
 pub type AlloyProvider = RootProvider;
 
@@ -182,6 +192,12 @@ pub struct EvmConfig {
 	pub gas_price_buffer: Option<u32>,
 	/// The client type the rpc is running, defaults to Geth
 	pub client_type: Option<ClientType>,
+	/// Nonce management, fee bumping and persistence of relayer transactions
+	#[serde(default)]
+	pub tx_manager: TxManagerConfig,
+	/// Sources and bounds for the native token's USD price, used in profitability checks
+	#[serde(default)]
+	pub price_oracle: PriceOracleConfig,
 	/// Initial height from which to start querying messages
 	pub initial_height: Option<u64>,
 	/// Selects the JSON-RPC transport variant.  Defaults to [`RpcTransport::Standard`].
@@ -189,6 +205,10 @@ pub struct EvmConfig {
 	/// EIP-1559 fields (`type`, `accessList`).
 	#[serde(default)]
 	pub transport: RpcTransport,
+	/// Health tracking of `rpc_urls` when more than one is configured. Reads are routed to the
+	/// healthiest endpoint and transactions are broadcast to several.
+	#[serde(default)]
+	pub rpc_health: EndpointHealthConfig,
 }
 
 impl EvmConfig {
@@ -276,8 +296,11 @@ impl Default for EvmConfig {
 			poll_interval: Default::default(),
 			gas_price_buffer: Default::default(),
 			client_type: Default::default(),
+			tx_manager: Default::default(),
+			price_oracle: Default::default(),
 			initial_height: Default::default(),
 			transport: Default::default(),
+			rpc_health: Default::default(),
 		}
 	}
 }
@@ -288,7 +311,7 @@ pub struct EvmClient {
 	pub client: Arc<AlloyProvider>,
 	/// One provider per configured RPC URL, used by the byzantine handler to
 	/// fan out queries and reach a quorum independently of `client`'s
-	/// fallback transport. Empty for single-URL chains where quorum is not
+	/// health-routed transport. Empty for single-URL chains where quorum is not
 	/// meaningful.
 	pub byzantine_providers: Vec<Arc<AlloyProvider>>,
 	/// Transaction signer provider. For chains the operator did not configure
@@ -316,42 +339,34 @@ pub struct EvmClient {
 	pub chain_id: u64,
 	/// Client type
 	pub client_type: ClientType,
-	/// Private key signer for synchronous signing operations. Same dummy-key
-	/// note as `signer`.
-	pub private_key_signer: PrivateKeySigner,
+	/// Signs messages with the relayer's key, which may be held by a remote
+	/// signer. Same dummy-key note as `signer`.
+	pub signing_key: Arc<Signer>,
+	/// Assigns nonces to and tracks the transactions sent by `signer`, shared with every client
+	/// signing with the same key on this chain
+	pub tx_manager: Arc<TxManager>,
+	/// USD price of the native token
+	pub price_oracle: Arc<PriceOracle>,
 	/// Producer for state machine updated stream
 	state_machine_update_sender: Arc<
 		tokio::sync::Mutex<
 			Option<tokio::sync::broadcast::Sender<Result<StateMachineUpdated, StreamError>>>,
 		>,
 	>,
-	/// Tx submission pipeline
-	queue: Option<Arc<PipelineQueue<Vec<Message>, anyhow::Result<TxResult>>>>,
+	/// Tx submission pipeline, broadcasts the transactions of one submission at a time
+	queue: Option<Arc<PipelineQueue<Vec<Message>, anyhow::Result<DispatchedMessages>>>>,
 }
 
 impl EvmClient {
 	pub async fn new(config: EvmConfig) -> Result<Self, anyhow::Error> {
 		let config_clone = config.clone();
-		// If the operator configured a signer, parse it; otherwise generate a
+		// If the operator configured a signer, load it; otherwise generate a
 		// throwaway one so all the signer-shaped fields downstream still
 		// type-check. Inbound-only chains never reach a path that signs,
 		// because the relayer's `outbound_enabled()` filter keeps them out
 		// of outbound and fee-withdrawal tasks before any signing call.
-		let signer_pair = match config.signer.as_deref() {
-			Some(raw) => {
-				let bytes = match from_hex(raw) {
-					Ok(bytes) => bytes,
-					Err(_) => {
-						// Treat the value as a file path containing hex bytes.
-						let contents = tokio::fs::read_to_string(raw).await?;
-						from_hex(contents.as_str())?
-					},
-				};
-				sp_core::ecdsa::Pair::from_seed_slice(&bytes)?
-			},
-			None => sp_core::ecdsa::Pair::generate().0,
-		};
-		let address = signer_pair.public().to_eth_address().expect("Infallible").to_vec();
+		let (signing_key, wallet) = signer::load_wallet(config.signer.as_deref()).await?;
+		let address = signer::eth_address(signing_key.as_ref())?.to_vec();
 
 		if config.rpc_urls.is_empty() {
 			return Err(anyhow::anyhow!("At least one RPC URL must be provided"));
@@ -389,8 +404,22 @@ impl EvmClient {
 					Ok(alloy::transports::http::Http::with_client(http_client.clone(), url))
 				})
 				.collect::<Result<_, anyhow::Error>>()?;
-			let active_count = transports.len();
-			let service = alloy::transports::layers::FallbackService::new(transports, active_count);
+			// Reads are routed to the healthiest endpoint and transactions are broadcast to
+			// several. Every endpoint's head and latency are probed in the background so the
+			// ranking also reflects the endpoints that aren't receiving requests.
+			let pool =
+				Arc::new(EndpointPool::new(config.rpc_urls.clone(), config.rpc_health.clone()));
+			let probes = transports
+				.iter()
+				.map(|http| {
+					RootProvider::new(alloy::rpc::client::RpcClient::new(http.clone(), false))
+				})
+				.collect::<Vec<_>>();
+			pool.spawn_probe(move |index| {
+				let provider: RootProvider = probes[index].clone();
+				async move { Ok(provider.get_block_number().await?) }
+			});
+			let service = HealthService::new(transports, pool);
 			match config.transport {
 				RpcTransport::Tron => {
 					use crate::transport::TronLayer;
@@ -431,11 +460,9 @@ impl EvmClient {
 
 		let chain_id = client.get_chain_id().await?;
 
-		// Build the signer provider. Whether `signer_pair` was parsed from
-		// the configured key or freshly generated for an inbound-only chain,
+		// Build the signer provider. Whether `signing_key` was loaded from
+		// the configured source or freshly generated for an inbound-only chain,
 		// the type shape is the same downstream.
-		let private_key_signer = PrivateKeySigner::from_slice(signer_pair.seed().as_slice())?;
-		let wallet = EthereumWallet::from(private_key_signer.clone());
 		let signer_provider = ProviderBuilder::new().wallet(wallet).connect_provider(root_provider);
 		let signer = Arc::new(signer_provider);
 
@@ -456,6 +483,15 @@ impl EvmClient {
 			bytes
 		};
 
+		let tx_manager =
+			TxManager::shared(config.tx_manager.clone(), chain_id, Address::from_slice(&address));
+
+		let price_oracle = Arc::new(PriceOracle::new(
+			config.price_oracle.clone(),
+			config.ismp_host.expect("Resolved"),
+			client.clone(),
+		)?);
+
 		let mut partial_client = Self {
 			client,
 			byzantine_providers,
@@ -468,7 +504,9 @@ impl EvmClient {
 			config: config_clone,
 			chain_id,
 			client_type: config.client_type.unwrap_or_default(),
-			private_key_signer,
+			signing_key,
+			tx_manager,
+			price_oracle,
 			state_machine_update_sender: Arc::new(tokio::sync::Mutex::new(None)),
 			queue: None,
 		};
@@ -476,9 +514,10 @@ impl EvmClient {
 		let partial_client_clone = partial_client.clone();
 		let queue = start_pipeline(move |messages| {
 			let client = partial_client_clone.clone();
-			async move { handle_message_submission(&client, messages).await }
+			async move { dispatch_messages(&client, messages).await }
 		});
 		partial_client.queue = Some(Arc::new(queue));
+		partial_client.tx_manager.resume(&partial_client)?;
 		Ok(partial_client)
 	}
 
@@ -535,9 +574,8 @@ impl EvmClient {
 		let call = contract.setConsensusState(Bytes::from(consensus_state), height, commitment);
 
 		let gas = call.estimate_gas().await?;
-		let pending = call.gas(gas).send().await?;
-		let tx_hash = *pending.tx_hash();
-		wait_for_transaction_receipt(H256::from_slice(tx_hash.as_slice()), self).await?;
+		let pending = self.tx_manager.send(self, call.gas(gas).into_transaction_request()).await?;
+		self.tx_manager.wait(pending).await?;
 
 		Ok(())
 	}
@@ -570,6 +608,71 @@ impl EvmClient {
 		derive_map_key(key.0.to_vec(), REQUEST_RECEIPTS_SLOT)
 	}
 
+	/// Builds a proof of the receipts of the transactions at `tx_indices` in `block` against the
+	/// block's receipts root. The block hash proof is left for the caller to fill in.
+	pub async fn query_receipt_proof(
+		&self,
+		block: u64,
+		tx_indices: BTreeSet<u64>,
+	) -> Result<EvmReceiptProof, anyhow::Error> {
+		use alloy::eips::eip2718::Encodable2718;
+		use ethereum_triedb::{keccak::KeccakHasher, EIP1186Layout, MemoryDB};
+		use trie_db::{Recorder, Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut};
+
+		let header = self
+			.client
+			.get_block_by_number(block.into())
+			.await?
+			.ok_or_else(|| anyhow::anyhow!("Block {block} not found"))?
+			.header;
+		let receipts = self
+			.client
+			.get_block_receipts(block.into())
+			.await?
+			.ok_or_else(|| anyhow::anyhow!("Receipts for block {block} not found"))?;
+
+		let mut db = MemoryDB::<KeccakHasher>::default();
+		let mut root = H256::default();
+		{
+			let mut trie =
+				TrieDBMutBuilder::<EIP1186Layout<KeccakHasher>>::new(&mut db, &mut root).build();
+			for (index, receipt) in receipts.into_iter().enumerate() {
+				let encoded = receipt.inner.map_logs(|log| log.inner).encoded_2718();
+				trie.insert(&alloy_rlp::encode(index as u64), &encoded)
+					.map_err(|err| anyhow::anyhow!("Failed to build receipts trie: {err:?}"))?;
+			}
+		}
+		if root.0 != header.receipts_root.0 {
+			Err(anyhow::anyhow!(
+				"Receipts of block {block} don't match its receipts root, the chain likely uses receipt types that are not supported"
+			))?
+		}
+
+		let mut recorder = Recorder::<EIP1186Layout<KeccakHasher>>::new();
+		{
+			let trie = TrieDBBuilder::<EIP1186Layout<KeccakHasher>>::new(&db, &root)
+				.with_recorder(&mut recorder)
+				.build();
+			for index in tx_indices {
+				trie.get(&alloy_rlp::encode(index))
+					.map_err(|err| anyhow::anyhow!("Failed to read receipts trie: {err:?}"))?;
+			}
+		}
+		let receipts_proof = recorder
+			.drain()
+			.into_iter()
+			.map(|record| record.data)
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.collect();
+
+		Ok(EvmReceiptProof {
+			header: alloy_rlp::encode(&header.inner),
+			block_hash_proof: vec![],
+			receipts_proof,
+		})
+	}
+
 	pub async fn host_manager(&self) -> Result<H160, anyhow::Error> {
 		let host_addr = Address::from_slice(&self.ismp_host.0);
 		let contract = EvmHostInstance::new(host_addr, self.client.clone());
@@ -644,7 +747,9 @@ impl Clone for EvmClient {
 			config: self.config.clone(),
 			chain_id: self.chain_id.clone(),
 			client_type: self.client_type.clone(),
-			private_key_signer: self.private_key_signer.clone(),
+			signing_key: self.signing_key.clone(),
+			tx_manager: self.tx_manager.clone(),
+			price_oracle: self.price_oracle.clone(),
 			state_machine_update_sender: self.state_machine_update_sender.clone(),
 			queue: self.queue.clone(),
 		}
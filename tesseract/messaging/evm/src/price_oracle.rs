@@ -0,0 +1,654 @@
+//! USD price of a chain's native token, used to price gas for profitability checks.
+//!
+//! The price is read from every configured [`PriceSource`]. Quotes older than
+//! [`PriceOracleConfig::max_age_secs`] are dropped, as are quotes further than
+//! [`PriceOracleConfig::max_deviation_bps`] from the median of the rest, and the median of what
+//! remains is used. A single manipulated pool or stuck feed can't move the price as long as the
+//! honest sources are in the majority.
+
+use crate::AlloyProvider;
+use alloy::{
+	eips::BlockId,
+	primitives::{aliases::U24, Address},
+};
+use alloy_sol_macro::sol;
+use anyhow::{anyhow, Context, Error};
+use futures::future::join_all;
+use geth_primitives::{alloy_u256_to_primitive, primitive_u256_to_alloy};
+use ismp_abi::evm_host::EvmHostInstance;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sp_core::H160;
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+sol!(
+	#[allow(missing_docs)]
+	#[sol(rpc)]
+	#[derive(Debug, PartialEq, Eq)]
+	interface IUniswapV2Router {
+		function getAmountsIn(uint256 amountOut, address[] memory path) public view returns (uint256[] memory amounts);
+		function WETH() external pure returns (address);
+	}
+);
+
+sol!(
+	#[allow(missing_docs)]
+	#[sol(rpc)]
+	#[derive(Debug, PartialEq, Eq)]
+	interface IUniswapV3Pool {
+		function token0() external view returns (address);
+		function token1() external view returns (address);
+		function fee() external view returns (uint24);
+		function observe(uint32[] calldata secondsAgos) external view returns (int56[] memory tickCumulatives, uint160[] memory secondsPerLiquidityCumulativeX128s);
+	}
+);
+
+sol!(
+	#[allow(missing_docs)]
+	#[sol(rpc)]
+	#[derive(Debug, PartialEq, Eq)]
+	interface AggregatorV3Interface {
+		function decimals() external view returns (uint8);
+		function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound);
+	}
+);
+
+sol!(
+	#[allow(missing_docs)]
+	#[sol(rpc)]
+	#[derive(Debug, PartialEq, Eq)]
+	interface IERC20 {
+		function decimals() external view returns (uint8);
+	}
+);
+
+/// Prices are USD values with this many decimals
+pub const PRICE_DECIMALS: u32 = 27;
+
+/// Configuration for the [`PriceOracle`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PriceOracleConfig {
+	/// Sources to read the price from. Defaults to the uniswap v2 router in the host params.
+	pub sources: Vec<PriceSourceConfig>,
+	/// Quotes last updated longer ago than this many seconds are ignored
+	pub max_age_secs: u64,
+	/// Quotes further than this from the median, in basis points, are ignored
+	pub max_deviation_bps: u32,
+	/// Minimum number of quotes that must survive the bounds for the price to be used
+	pub min_sources: usize,
+}
+
+impl Default for PriceOracleConfig {
+	fn default() -> Self {
+		Self {
+			sources: vec![PriceSourceConfig::HostRouter],
+			max_age_secs: 60 * 60,
+			max_deviation_bps: 500,
+			min_sources: 1,
+		}
+	}
+}
+
+/// A source of the native token's USD price
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceSourceConfig {
+	/// The fee token's spot price on the uniswap v2 router configured in the host params
+	HostRouter,
+	/// A Chainlink `<native>/USD` aggregator
+	Chainlink {
+		/// Aggregator contract address
+		aggregator: Address,
+	},
+	/// Time weighted average price of a uniswap v3 pool pairing the native token with a USD
+	/// stablecoin
+	UniswapV3Twap {
+		/// Pool contract address
+		pool: Address,
+		/// The wrapped native token, one of the pool's two tokens
+		native_token: Address,
+		/// Length of the averaging window in seconds
+		#[serde(default = "default_twap_window")]
+		window_secs: u32,
+	},
+	/// A fixed price in USD, e.g "0.998". Never stale.
+	Static {
+		/// Price in USD
+		price: String,
+	},
+	/// A JSON file kept up to date by another process. It holds `{"price": "2451.37",
+	/// "updated_at": 1700000000}`, or an object of such entries when `key` is set. The file's
+	/// modification time is used when `updated_at` is missing.
+	File {
+		/// Path to the file
+		path: PathBuf,
+		/// Entry to read from the file
+		#[serde(default)]
+		key: Option<String>,
+	},
+}
+
+fn default_twap_window() -> u32 {
+	30 * 60
+}
+
+/// A price reported by a [`PriceSource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceQuote {
+	/// USD price of one native token, with [`PRICE_DECIMALS`] decimals
+	pub price: U256,
+	/// Unix timestamp in seconds of when the price was last updated
+	pub updated_at: u64,
+}
+
+/// Reports the USD price of a chain's native token
+#[async_trait::async_trait]
+pub trait PriceSource: Send + Sync {
+	/// Name used in logs
+	fn name(&self) -> String;
+
+	/// Fetch the current price
+	async fn quote(&self) -> Result<PriceQuote, Error>;
+}
+
+/// Aggregates the configured [`PriceSource`]s into a single price
+pub struct PriceOracle {
+	config: PriceOracleConfig,
+	sources: Vec<Box<dyn PriceSource>>,
+}
+
+impl PriceOracle {
+	/// Build the sources in `config` for the chain `client` is connected to
+	pub fn new(
+		config: PriceOracleConfig,
+		ismp_host: H160,
+		client: Arc<AlloyProvider>,
+	) -> Result<Self, Error> {
+		let sources = config
+			.sources
+			.iter()
+			.map(|source| {
+				let source: Box<dyn PriceSource> = match source.clone() {
+					PriceSourceConfig::HostRouter =>
+						Box::new(HostRouterSource { ismp_host, client: client.clone() }),
+					PriceSourceConfig::Chainlink { aggregator } =>
+						Box::new(ChainlinkSource { aggregator, client: client.clone() }),
+					PriceSourceConfig::UniswapV3Twap { pool, native_token, window_secs } => {
+						if window_secs == 0 {
+							Err(anyhow!("TWAP window of pool {pool} must not be empty"))?
+						}
+						Box::new(UniswapV3TwapSource {
+							pool,
+							native_token,
+							window_secs,
+							client: client.clone(),
+						})
+					},
+					PriceSourceConfig::Static { price } =>
+						Box::new(StaticSource { price: parse_usd(&price)? }),
+					PriceSourceConfig::File { path, key } => Box::new(FileSource { path, key }),
+				};
+				Ok(source)
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		Ok(Self::with_sources(config, sources))
+	}
+
+	/// Aggregate the given sources, ignoring the ones in `config`
+	pub fn with_sources(config: PriceOracleConfig, sources: Vec<Box<dyn PriceSource>>) -> Self {
+		Self { config, sources }
+	}
+
+	/// USD price of one native token, with [`PRICE_DECIMALS`] decimals
+	pub async fn price(&self) -> Result<U256, Error> {
+		let results = join_all(self.sources.iter().map(|source| source.quote())).await;
+		let quotes = self
+			.sources
+			.iter()
+			.zip(results)
+			.filter_map(|(source, result)| match result {
+				Ok(quote) => Some((source.name(), quote)),
+				Err(err) => {
+					tracing::warn!(
+						target: crate::LOG_TARGET,
+						source = source.name(),
+						"failed to fetch native token price: {err:?}",
+					);
+					None
+				},
+			})
+			.collect::<Vec<_>>();
+
+		aggregate(&quotes, now(), &self.config)
+	}
+}
+
+/// Median of the `quotes` that are fresh and close to the median of the fresh quotes
+pub fn aggregate(
+	quotes: &[(String, PriceQuote)],
+	now: u64,
+	config: &PriceOracleConfig,
+) -> Result<U256, Error> {
+	let fresh = quotes
+		.iter()
+		.filter(|(name, quote)| {
+			let stale = now.saturating_sub(quote.updated_at) > config.max_age_secs;
+			if stale {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					source = name,
+					updated_at = quote.updated_at,
+					"ignoring stale native token price",
+				);
+			}
+			!stale
+		})
+		.collect::<Vec<_>>();
+	let Some(reference) = median(fresh.iter().map(|(_, quote)| quote.price).collect()) else {
+		Err(anyhow!("No fresh native token price available"))?
+	};
+
+	let bound = reference * config.max_deviation_bps / 10_000u32;
+	let accepted = fresh
+		.into_iter()
+		.filter(|(name, quote)| {
+			let deviation = quote.price.max(reference) - quote.price.min(reference);
+			if deviation > bound {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					source = name,
+					price = %quote.price,
+					median = %reference,
+					"ignoring native token price too far from the median",
+				);
+			}
+			deviation <= bound
+		})
+		.map(|(_, quote)| quote.price)
+		.collect::<Vec<_>>();
+
+	if accepted.len() < config.min_sources.max(1) {
+		Err(anyhow!(
+			"Only {} native token prices within bounds, {} required",
+			accepted.len(),
+			config.min_sources.max(1)
+		))?
+	}
+
+	Ok(median(accepted).expect("at least one price was accepted"))
+}
+
+fn median(mut prices: Vec<U256>) -> Option<U256> {
+	prices.sort();
+	let mid = prices.len() / 2;
+	match prices.len() {
+		0 => None,
+		len if len % 2 == 1 => Some(prices[mid]),
+		_ => Some((prices[mid - 1] + prices[mid]) / 2),
+	}
+}
+
+/// Parse a decimal USD amount, e.g "2451.37", into a price with [`PRICE_DECIMALS`] decimals
+pub fn parse_usd(value: &str) -> Result<U256, Error> {
+	let value = value.trim();
+	let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+	let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+	if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction) {
+		Err(anyhow!("Invalid USD amount {value:?}"))?
+	}
+
+	let scale = U256::from(10).pow(PRICE_DECIMALS.into());
+	let whole = if whole.is_empty() { U256::zero() } else { U256::from_dec_str(whole)? };
+	let fraction = &fraction[..fraction.len().min(PRICE_DECIMALS as usize)];
+	let fraction = if fraction.is_empty() {
+		U256::zero()
+	} else {
+		U256::from_dec_str(fraction)? *
+			U256::from(10).pow((PRICE_DECIMALS as usize - fraction.len()).into())
+	};
+
+	Ok(whole * scale + fraction)
+}
+
+/// USD price of the native token from the time weighted average tick of a uniswap v3 pool whose
+/// other token is a USD stablecoin
+pub fn twap_price(
+	tick_cumulatives: (i64, i64),
+	window_secs: u32,
+	native_is_token0: bool,
+	decimals: (u8, u8),
+) -> Result<U256, Error> {
+	let delta = tick_cumulatives.1 - tick_cumulatives.0;
+	let window = window_secs as i64;
+	// Round towards negative infinity, like the pool's oracle library
+	let mut tick = delta / window;
+	if delta < 0 && delta % window != 0 {
+		tick -= 1;
+	}
+
+	// Price of one whole token0 in whole token1
+	let price = 1.0001f64.powi(tick as i32) * 10f64.powi(decimals.0 as i32 - decimals.1 as i32);
+	let price = if native_is_token0 { price } else { 1.0 / price };
+	if !price.is_finite() || price <= 0.0 {
+		Err(anyhow!("Invalid TWAP tick {tick}"))?
+	}
+
+	parse_usd(&format!("{price:.18}"))
+}
+
+/// The fee token's spot price on the host's uniswap v2 router
+pub struct HostRouterSource {
+	/// `IsmpHost` contract address
+	pub ismp_host: H160,
+	/// Client for the chain the host is deployed on
+	pub client: Arc<AlloyProvider>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for HostRouterSource {
+	fn name(&self) -> String {
+		"host-router".to_string()
+	}
+
+	async fn quote(&self) -> Result<PriceQuote, Error> {
+		let host =
+			EvmHostInstance::new(Address::from_slice(&self.ismp_host.0), self.client.clone());
+		let params = host.hostParams().block(BlockId::latest()).call().await?;
+
+		// There are no uniswap pool on testnet, return 1 usd as native token value
+		if params.hyperbridge.0.starts_with(b"KUSAMA") {
+			return Ok(PriceQuote {
+				price: U256::from(10).pow(PRICE_DECIMALS.into()),
+				updated_at: now(),
+			});
+		}
+
+		let fee_token = Address::from_slice(params.feeToken.as_slice());
+		if params.uniswapV2 == Address::ZERO {
+			return Err(anyhow!("Uniswap V2 Router not configured in Host Params"));
+		}
+
+		let router =
+			IUniswapV2Router::IUniswapV2RouterInstance::new(params.uniswapV2, self.client.clone());
+		let native_token = router.WETH().block(BlockId::latest()).call().await?;
+
+		let fee_token_contract = IERC20::IERC20Instance::new(fee_token, self.client.clone());
+		let fee_token_decimals =
+			fee_token_contract.decimals().block(BlockId::latest()).call().await?;
+
+		let native_token_contract = IERC20::IERC20Instance::new(native_token, self.client.clone());
+		let native_decimals =
+			native_token_contract.decimals().block(BlockId::latest()).call().await?;
+
+		let path = vec![fee_token, native_token];
+		let amount_out = primitive_u256_to_alloy(U256::from(10).pow(U256::from(native_decimals)));
+
+		let amounts = router.getAmountsIn(amount_out, path).block(BlockId::latest()).call().await?;
+
+		if amounts.is_empty() {
+			return Err(anyhow!("Invalid amounts returned from Uniswap V2 Router"));
+		}
+
+		let amount_stable = alloy_u256_to_primitive(amounts[0]);
+		let price = amount_stable *
+			U256::from(10).pow(U256::from(PRICE_DECIMALS - fee_token_decimals as u32));
+		Ok(PriceQuote { price, updated_at: now() })
+	}
+}
+
+/// A Chainlink `<native>/USD` aggregator
+pub struct ChainlinkSource {
+	/// Aggregator contract address
+	pub aggregator: Address,
+	/// Client for the chain the aggregator is deployed on
+	pub client: Arc<AlloyProvider>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for ChainlinkSource {
+	fn name(&self) -> String {
+		format!("chainlink:{}", self.aggregator)
+	}
+
+	async fn quote(&self) -> Result<PriceQuote, Error> {
+		let aggregator = AggregatorV3Interface::AggregatorV3InterfaceInstance::new(
+			self.aggregator,
+			self.client.clone(),
+		);
+		let decimals = aggregator.decimals().block(BlockId::latest()).call().await? as u32;
+		let round = aggregator.latestRoundData().block(BlockId::latest()).call().await?;
+
+		if !round.answer.is_positive() {
+			Err(anyhow!("Aggregator {} reported a price of {}", self.aggregator, round.answer))?
+		}
+		if decimals > PRICE_DECIMALS {
+			Err(anyhow!("Aggregator {} has {decimals} decimals", self.aggregator))?
+		}
+		let answer = alloy_u256_to_primitive(round.answer.into_raw());
+		let price = answer * U256::from(10).pow((PRICE_DECIMALS - decimals).into());
+		let updated_at = u64::try_from(round.updatedAt)
+			.map_err(|_| anyhow!("Invalid update time {}", round.updatedAt))?;
+
+		Ok(PriceQuote { price, updated_at })
+	}
+}
+
+/// Time weighted average price of a uniswap v3 pool
+pub struct UniswapV3TwapSource {
+	/// Pool contract address
+	pub pool: Address,
+	/// The wrapped native token, one of the pool's two tokens
+	pub native_token: Address,
+	/// Length of the averaging window in seconds
+	pub window_secs: u32,
+	/// Client for the chain the pool is deployed on
+	pub client: Arc<AlloyProvider>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for UniswapV3TwapSource {
+	fn name(&self) -> String {
+		format!("uniswap-v3-twap:{}", self.pool)
+	}
+
+	async fn quote(&self) -> Result<PriceQuote, Error> {
+		let pool = IUniswapV3Pool::IUniswapV3PoolInstance::new(self.pool, self.client.clone());
+		let token0 = pool.token0().block(BlockId::latest()).call().await?;
+		let token1 = pool.token1().block(BlockId::latest()).call().await?;
+		let native_is_token0 = match self.native_token {
+			token if token == token0 => true,
+			token if token == token1 => false,
+			token => Err(anyhow!("Pool {} does not hold token {token}", self.pool))?,
+		};
+		// Make sure this is a pool, not some other contract with token getters
+		let _: U24 = pool.fee().block(BlockId::latest()).call().await?;
+
+		let mut decimals = [0u8; 2];
+		for (decimals, token) in decimals.iter_mut().zip([token0, token1]) {
+			*decimals = IERC20::IERC20Instance::new(token, self.client.clone())
+				.decimals()
+				.block(BlockId::latest())
+				.call()
+				.await?;
+		}
+
+		let observations = pool
+			.observe(vec![self.window_secs, 0])
+			.block(BlockId::latest())
+			.call()
+			.await
+			.with_context(|| {
+				format!("Pool {} has no observations {}s old", self.pool, self.window_secs)
+			})?;
+		let [then, latest] = observations.tickCumulatives[..] else {
+			Err(anyhow!("Pool {} returned unexpected observations", self.pool))?
+		};
+		let price = twap_price(
+			(then.as_i64(), latest.as_i64()),
+			self.window_secs,
+			native_is_token0,
+			(decimals[0], decimals[1]),
+		)?;
+
+		Ok(PriceQuote { price, updated_at: now() })
+	}
+}
+
+/// A fixed price
+pub struct StaticSource {
+	/// USD price of one native token, with [`PRICE_DECIMALS`] decimals
+	pub price: U256,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for StaticSource {
+	fn name(&self) -> String {
+		"static".to_string()
+	}
+
+	async fn quote(&self) -> Result<PriceQuote, Error> {
+		Ok(PriceQuote { price: self.price, updated_at: now() })
+	}
+}
+
+/// A price written to a JSON file by another process
+pub struct FileSource {
+	/// Path to the file
+	pub path: PathBuf,
+	/// Entry to read from the file
+	pub key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FileEntry {
+	price: String,
+	updated_at: Option<u64>,
+}
+
+#[async_trait::async_trait]
+impl PriceSource for FileSource {
+	fn name(&self) -> String {
+		format!("file:{}", self.path.display())
+	}
+
+	async fn quote(&self) -> Result<PriceQuote, Error> {
+		let path = &self.path;
+		let contents =
+			std::fs::read(path).with_context(|| format!("Failed to read price file {path:?}"))?;
+		let entry = match &self.key {
+			Some(key) => serde_json::from_slice::<BTreeMap<String, FileEntry>>(&contents)?
+				.remove(key)
+				.ok_or_else(|| anyhow!("Price file {path:?} has no entry {key:?}"))?,
+			None => serde_json::from_slice::<FileEntry>(&contents)?,
+		};
+
+		let updated_at = match entry.updated_at {
+			Some(updated_at) => updated_at,
+			None => std::fs::metadata(path)?
+				.modified()?
+				.duration_since(UNIX_EPOCH)
+				.unwrap_or_default()
+				.as_secs(),
+		};
+
+		Ok(PriceQuote { price: parse_usd(&entry.price)?, updated_at })
+	}
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn usd(value: &str) -> U256 {
+		parse_usd(value).unwrap()
+	}
+
+	fn quotes(prices: &[(&str, u64)]) -> Vec<(String, PriceQuote)> {
+		prices
+			.iter()
+			.enumerate()
+			.map(|(i, (price, updated_at))| {
+				(format!("source-{i}"), PriceQuote { price: usd(price), updated_at: *updated_at })
+			})
+			.collect()
+	}
+
+	#[test]
+	fn parses_usd_amounts() {
+		let scale = U256::from(10).pow(PRICE_DECIMALS.into());
+		assert_eq!(usd("2451"), U256::from(2451) * scale);
+		assert_eq!(usd("2451.37"), U256::from(245137) * scale / 100);
+		assert_eq!(usd(".5"), scale / 2);
+		assert_eq!(usd("0.000000000000000000000000001"), U256::one());
+		assert!(parse_usd("").is_err());
+		assert!(parse_usd("1,5").is_err());
+		assert!(parse_usd("-1").is_err());
+	}
+
+	#[test]
+	fn takes_the_median_of_fresh_quotes() {
+		let config = PriceOracleConfig { max_deviation_bps: 10_000, ..Default::default() };
+		let now = 10_000;
+		assert_eq!(
+			aggregate(&quotes(&[("2000", now), ("2010", now), ("1990", now)]), now, &config)
+				.unwrap(),
+			usd("2000")
+		);
+		assert_eq!(
+			aggregate(&quotes(&[("2000", now), ("2010", now)]), now, &config).unwrap(),
+			usd("2005")
+		);
+
+		// The stale quote is ignored
+		let stale = now - config.max_age_secs - 1;
+		assert_eq!(
+			aggregate(&quotes(&[("2000", now), ("2010", now), ("50", stale)]), now, &config)
+				.unwrap(),
+			usd("2005")
+		);
+		assert!(aggregate(&quotes(&[("2000", stale)]), now, &config).is_err());
+		assert!(aggregate(&[], now, &config).is_err());
+	}
+
+	#[test]
+	fn ignores_outliers() {
+		let config = PriceOracleConfig { max_deviation_bps: 200, ..Default::default() };
+		let now = 10_000;
+
+		// A manipulated pool doesn't drag the price
+		let prices = quotes(&[("2000", now), ("2010", now), ("1990", now), ("9000", now)]);
+		assert_eq!(aggregate(&prices, now, &config).unwrap(), usd("2000"));
+
+		// Unless too few sources agree
+		let config = PriceOracleConfig { min_sources: 3, ..config };
+		let prices = quotes(&[("2000", now), ("2010", now), ("9000", now), ("9500", now)]);
+		assert!(aggregate(&prices, now, &config).is_err());
+	}
+
+	#[test]
+	fn converts_twap_ticks_to_prices() {
+		// USDC (6 decimals) is token0, WETH (18 decimals) token1, ETH at ~2000 USD
+		let tick = ((1e12f64 / 2000.0).ln() / 1.0001f64.ln()).round() as i64;
+		let window = 1800;
+		let price =
+			twap_price((1_000, 1_000 + tick * window as i64), window, false, (6, 18)).unwrap();
+		let price = price / U256::from(10).pow((PRICE_DECIMALS - 2).into());
+		assert!((199_980..=200_020).contains(&price.as_u64()), "{price}");
+
+		// The same pool with the tokens the other way round
+		let price = twap_price((0, -tick * window as i64), window, true, (18, 6)).unwrap();
+		let price = price / U256::from(10).pow((PRICE_DECIMALS - 2).into());
+		assert!((199_980..=200_020).contains(&price.as_u64()), "{price}");
+	}
+}
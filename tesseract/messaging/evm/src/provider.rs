@@ -13,7 +13,10 @@ use anyhow::{anyhow, Error};
 use beefy_verifier_primitives::ConsensusState;
 use codec::Encode;
 
-use evm_state_machine::types::EvmStateProof;
+use evm_state_machine::{
+	receipts::{block_hash_slot, decode_log_key, HISTORY_STORAGE_ADDRESS},
+	types::EvmStateProof,
+};
 
 use geth_primitives::alloy_u256_to_primitive;
 use ismp::{
@@ -21,12 +24,12 @@ use ismp::{
 	events::{Event, StateCommitmentVetoed},
 	messaging::{Message, StateCommitmentHeight},
 };
-use ismp_abi::evm_host::{GetRequestHandled, PostRequestHandled};
+use ismp_abi::evm_host::{GetRequestHandled, PostRequestHandled, PostRequestTimeoutHandled};
 use pallet_ismp_host_executive::{EvmHostParam, HostParam};
 
 use crate::{
 	gas_oracle::{get_current_gas_cost_in_usd, get_l2_data_cost},
-	tx::get_chain_gas_limit,
+	tx::{await_dispatched, get_chain_gas_limit},
 };
 use ethereum_triedb::StorageProof;
 use futures::{stream::FuturesOrdered, FutureExt};
@@ -37,10 +40,15 @@ use ismp::{
 };
 use primitive_types::U256;
 use sp_core::{H160, H256};
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	sync::Arc,
+	time::Duration,
+};
 use tesseract_primitives::{
-	wait_for_challenge_period, BoxStream, EstimateGasReturnParams, IsmpProvider, Query, Signature,
-	StateMachineUpdated, StateProofQueryType, StorageKey, TxResult,
+	signer::RelayerSigner, wait_for_challenge_period, BoxStream, EstimateGasReturnParams,
+	IsmpProvider, Query, Signature, StateMachineUpdated, StateProofQueryType, StorageKey, TxResult,
+	BEEFY_CONSENSUS_STATE_ID,
 };
 
 use ismp_abi::ecdsa_beefy::BeefyConsensusState;
@@ -176,6 +184,23 @@ impl IsmpProvider for EvmClient {
 		Ok(Duration::from_secs(value.try_into().unwrap_or(0)))
 	}
 
+	async fn query_consensus_expiry(&self, id: ConsensusStateId) -> Result<Duration, Error> {
+		// The host contract tracks a single consensus client, hyperbridge's BEEFY client.
+		if id != BEEFY_CONSENSUS_STATE_ID {
+			Err(anyhow!(
+				"EvmHost on {:?} only tracks the BEEFY consensus client, cannot query expiry of {:?}",
+				self.state_machine,
+				String::from_utf8_lossy(&id)
+			))?
+		}
+		let host_addr = Address::from_slice(&self.ismp_host.0);
+		let contract = EvmHostInstance::new(host_addr, self.client.clone());
+		let update_time = contract.consensusUpdateTime().block(BlockId::latest()).call().await?;
+		let unbonding_period = contract.unStakingPeriod().block(BlockId::latest()).call().await?;
+		let expiry = update_time.saturating_add(unbonding_period);
+		Ok(Duration::from_secs(expiry.try_into().unwrap_or(u64::MAX)))
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, Error> {
 		let host_addr = Address::from_slice(&self.ismp_host.0);
 		let contract = EvmHostInstance::new(host_addr, self.client.clone());
@@ -207,6 +232,7 @@ impl IsmpProvider for EvmClient {
 					.into_iter()
 					.collect()
 			},
+			receipt_proofs: Default::default(),
 		};
 		Ok(proof.encode())
 	}
@@ -242,6 +268,7 @@ impl IsmpProvider for EvmClient {
 						.map(|bytes| bytes.to_vec())
 						.collect(),
 					storage_proof: map,
+					receipt_proofs: Default::default(),
 				};
 				state_proof.encode()
 			},
@@ -250,10 +277,17 @@ impl IsmpProvider for EvmClient {
 				let mut map: BTreeMap<Vec<u8>, Vec<Vec<u8>>> = BTreeMap::new();
 				let mut contract_addresses_to_keys = BTreeMap::new();
 
+				let mut log_queries: BTreeMap<u64, BTreeSet<u64>> = BTreeMap::new();
+
 				for key in keys {
+					if let Some((block, tx_index, _)) = decode_log_key(&key) {
+						log_queries.entry(block).or_default().insert(tx_index);
+						continue;
+					}
+
 					if key.len() != 20 && key.len() != 52 {
 						Err(anyhow!(
-							"All arbitrary keys must have a length of 52 bytes or 20 bytes when querying state proofs, found key with length {}",
+							"All arbitrary keys must have a length of 52, 24 or 20 bytes when querying state proofs, found key with length {}",
 							key.len()
 						))?
 					}
@@ -291,11 +325,35 @@ impl IsmpProvider for EvmClient {
 					}
 				}
 
+				let mut receipt_proofs = BTreeMap::new();
+				if !log_queries.is_empty() {
+					// Block headers are proven through their hash in the history contract
+					let history = Address::from_slice(&HISTORY_STORAGE_ADDRESS.0);
+					let blocks: Vec<u64> = log_queries.keys().copied().collect();
+					let slots = blocks
+						.iter()
+						.map(|block| B256::from_slice(&block_hash_slot(*block).0))
+						.collect();
+					let proof = self.client.get_proof(history, slots).block_id(at.into()).await?;
+					contract_proofs.push(StorageProof::new(
+						proof.account_proof.into_iter().map(|node| node.to_vec()),
+					));
+
+					for (block, block_hash_proof) in blocks.into_iter().zip(proof.storage_proof) {
+						let tx_indices = log_queries.remove(&block).unwrap_or_default();
+						let mut receipt_proof = self.query_receipt_proof(block, tx_indices).await?;
+						receipt_proof.block_hash_proof =
+							block_hash_proof.proof.into_iter().map(|node| node.to_vec()).collect();
+						receipt_proofs.insert(block, receipt_proof);
+					}
+				}
+
 				let contract_proof = StorageProof::merge(contract_proofs);
 
 				let state_proof = EvmStateProof {
 					contract_proof: contract_proof.into_nodes().into_iter().collect(),
 					storage_proof: map,
+					receipt_proofs,
 				};
 				state_proof.encode()
 			},
@@ -664,7 +722,9 @@ impl IsmpProvider for EvmClient {
 			.as_ref()
 			.ok_or_else(|| anyhow!("Transaction submission pipeline was not initialized"))?
 			.clone();
-		queue.send(messages).await?
+		// Only the broadcast goes through the pipeline, receipts are awaited concurrently
+		let dispatched = queue.send(messages).await??;
+		await_dispatched(self, dispatched).await
 	}
 
 	fn request_commitment_full_key(&self, commitment: H256) -> Vec<Vec<u8>> {
@@ -681,16 +741,9 @@ impl IsmpProvider for EvmClient {
 		self.address.clone()
 	}
 
-	fn sign(&self, msg: &[u8]) -> Signature {
-		use alloy::signers::SignerSync;
-		let hash = B256::from_slice(msg);
-		let signature = self
-			.private_key_signer
-			.sign_hash_sync(&hash)
-			.expect("Infallible")
-			.as_bytes()
-			.to_vec();
-		Signature::Evm { address: self.address.clone(), signature }
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error> {
+		let signature = self.signing_key.sign(msg).await?;
+		Ok(Signature::Evm { address: self.address.clone(), signature })
 	}
 
 	async fn set_latest_finalized_height(
@@ -780,6 +833,10 @@ impl IsmpProvider for EvmClient {
 
 		Ok(decimals)
 	}
+
+	async fn gas_price(&self) -> Result<Option<U256>, anyhow::Error> {
+		Ok(Some(U256::from(self.client.get_gas_price().await?)))
+	}
 }
 
 pub enum CheckTraceForEventParams {
@@ -792,6 +849,9 @@ pub enum CheckTraceForEventParams {
 	/// relayer fee transfer and returns without reverting — so the event is the only
 	/// reliable signal that the delivery actually landed.
 	Response,
+	/// `EvmHost.PostRequestTimeoutHandled`, emitted once `handlePostRequestTimeouts` has
+	/// refunded the request and notified the module that sent it.
+	Timeout,
 }
 
 pub fn check_trace_for_event(
@@ -844,6 +904,8 @@ fn any_frame_has_event(
 		let matched = match event_in {
 			CheckTraceForEventParams::Request => PostRequestHandled::decode_log(&prim_log).is_ok(),
 			CheckTraceForEventParams::Response => GetRequestHandled::decode_log(&prim_log).is_ok(),
+			CheckTraceForEventParams::Timeout =>
+				PostRequestTimeoutHandled::decode_log(&prim_log).is_ok(),
 		};
 		if matched {
 			return true;
@@ -912,7 +974,7 @@ async fn estimate_gas_for_tx_requests(
 
 	let gas_breakdown = get_current_gas_cost_in_usd(
 		client_outer.state_machine,
-		client_outer.ismp_host.0.into(),
+		&client_outer.price_oracle,
 		client_outer.client.clone(),
 	)
 	.await?;
@@ -967,7 +1029,19 @@ async fn estimate_gas_for_tx_requests(
 										);
 									}
 								},
-								_ => unreachable!("Only request/responses are estimated"),
+								Message::Timeout(_) => {
+									successful_execution = check_trace_for_event(
+										&call_frame,
+										CheckTraceForEventParams::Timeout,
+									);
+									if !successful_execution {
+										log::trace!(
+											target: crate::LOG_TARGET, "debug_traceCall timeout message failed on {:?}",
+											client.state_machine
+										);
+									}
+								},
+								_ => unreachable!("Only request/responses/timeouts are estimated"),
 							};
 
 							if successful_execution && is_orbit_chain(client.chain_id as u32) {
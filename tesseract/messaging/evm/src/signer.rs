@@ -0,0 +1,67 @@
+//! Transaction signing with the relayer's configured [`Signer`]
+
+use alloy::{
+	consensus::SignableTransaction,
+	network::{EthereumWallet, TxSigner},
+	primitives::{Address, Signature},
+};
+use anyhow::anyhow;
+use polkadot_sdk::frame_support::crypto::ecdsa::ECDSAExt;
+use std::sync::Arc;
+use tesseract_primitives::signer::{KeyType, RelayerSigner, Signer};
+
+/// Signs EVM transactions with a [`Signer`], which may hold the key in memory or delegate to a
+/// remote signer.
+#[derive(Clone)]
+pub struct EvmTxSigner {
+	signer: Arc<Signer>,
+	address: Address,
+}
+
+impl EvmTxSigner {
+	/// Wrap a secp256k1 signer
+	pub fn new(signer: Arc<Signer>) -> Result<Self, anyhow::Error> {
+		let address = Address::from(eth_address(signer.as_ref())?);
+		Ok(Self { signer, address })
+	}
+}
+
+#[async_trait::async_trait]
+impl TxSigner<Signature> for EvmTxSigner {
+	fn address(&self) -> Address {
+		self.address
+	}
+
+	async fn sign_transaction(
+		&self,
+		tx: &mut dyn SignableTransaction<Signature>,
+	) -> alloy::signers::Result<Signature> {
+		let hash = tx.signature_hash();
+		let signature =
+			self.signer.sign(hash.as_slice()).await.map_err(alloy::signers::Error::other)?;
+		Signature::from_raw(&signature).map_err(alloy::signers::Error::other)
+	}
+}
+
+/// The Ethereum address of a secp256k1 signer
+pub fn eth_address(signer: &dyn RelayerSigner) -> Result<[u8; 20], anyhow::Error> {
+	if signer.key_type() != KeyType::Secp256k1 {
+		Err(anyhow!("EVM chains require a secp256k1 signer"))?
+	}
+	let public_key: [u8; 33] = signer
+		.public_key()
+		.try_into()
+		.map_err(|_| anyhow!("Expected a 33 byte compressed public key"))?;
+	sp_core::ecdsa::Public::from_raw(public_key)
+		.to_eth_address()
+		.map_err(|_| anyhow!("Invalid secp256k1 public key"))
+}
+
+/// Load the key named by a `signer` config value and build a wallet for it
+pub async fn load_wallet(
+	signer: Option<&str>,
+) -> Result<(Arc<Signer>, EthereumWallet), anyhow::Error> {
+	let signer = Arc::new(Signer::load(signer, KeyType::Secp256k1).await?);
+	let wallet = EthereumWallet::new(EvmTxSigner::new(signer.clone())?);
+	Ok((signer, wallet))
+}
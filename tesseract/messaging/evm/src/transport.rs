@@ -19,17 +19,27 @@
 //! alloy RPC client.  This Tower layer intercepts every outgoing JSON-RPC
 //! request and strips the `type` and `accessList` fields that TRON's JSON-RPC
 //! proxy cannot parse.
+//!
+//! Chains configured with several `rpc_urls` are served by a [`HealthService`], which routes each
+//! request according to the health of the endpoints tracked in an [`EndpointPool`].
 
 use alloy_json_rpc::{Request, RequestPacket, ResponsePacket, SerializedRequest};
-use alloy_transport::{TransportError, TransportErrorKind};
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use futures::{stream::FuturesUnordered, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::{
 	future::Future,
 	pin::Pin,
+	sync::Arc,
 	task::{Context, Poll},
+	time::Instant,
 };
+use tesseract_primitives::health::EndpointPool;
 use tower::{Layer, Service};
 
+/// RPC methods that broadcast a transaction, sent to several endpoints at once.
+const BROADCAST_METHODS: &[&str] = &["eth_sendRawTransaction"];
+
 /// RPC methods whose params contain transaction-like objects with `type`/`accessList`.
 const TX_OBJECT_METHODS: &[&str] = &[
 	"eth_call",
@@ -107,6 +117,138 @@ where
 	}
 }
 
+/// Tower service over the transports of several endpoints of the same chain. Reads are sent to
+/// the healthiest endpoint and retried on the next one if the transport fails. Transactions are
+/// broadcast to the healthiest few endpoints at once and the first endpoint to accept them
+/// answers. If none accepts, the first rejection is returned.
+///
+/// Only transport failures count against an endpoint, JSON-RPC error responses (reverts, nonce
+/// errors, ...) are returned to the caller as they are.
+#[derive(Debug, Clone)]
+pub struct HealthService<S> {
+	transports: Arc<Vec<S>>,
+	pool: Arc<EndpointPool>,
+}
+
+impl<S> HealthService<S> {
+	/// Route requests over `transports`, whose endpoints are tracked in `pool` in the same order.
+	pub fn new(transports: Vec<S>, pool: Arc<EndpointPool>) -> Self {
+		Self { transports: Arc::new(transports), pool }
+	}
+}
+
+impl<S> Service<RequestPacket> for HealthService<S>
+where
+	S: Service<
+			RequestPacket,
+			Response = ResponsePacket,
+			Error = TransportError,
+			Future = TransportFut<'static>,
+		> + Send
+		+ Sync
+		+ Clone
+		+ 'static,
+{
+	type Response = ResponsePacket;
+	type Error = TransportError;
+	type Future = TransportFut<'static>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		// Every call clones the transport it sends to
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, req: RequestPacket) -> Self::Future {
+		let transports = self.transports.clone();
+		let pool = self.pool.clone();
+		Box::pin(async move {
+			if is_broadcast(&req) {
+				// Spawned so the slower sends still reach their endpoint once one succeeds
+				let mut sends = pool
+					.broadcast_targets()
+					.into_iter()
+					.map(|index| {
+						tokio::spawn(send(
+							transports[index].clone(),
+							pool.clone(),
+							index,
+							req.clone(),
+						))
+					})
+					.collect::<FuturesUnordered<_>>();
+				let (mut rejected, mut last_err) = (None, None);
+				while let Some(res) = sends.next().await {
+					match res.map_err(TransportErrorKind::custom).and_then(|res| res) {
+						Ok(response) if !is_error(&response) => return Ok(response),
+						// Another endpoint may still accept the transaction
+						Ok(response) => rejected = rejected.or(Some(response)),
+						Err(err) => last_err = Some(err),
+					}
+				}
+				return match (rejected, last_err) {
+					(Some(response), _) => Ok(response),
+					(None, err) =>
+						Err(err
+							.unwrap_or_else(|| TransportErrorKind::custom_str("no rpc endpoints"))),
+				};
+			}
+
+			let mut last_err = None;
+			for index in pool.ranked() {
+				match send(transports[index].clone(), pool.clone(), index, req.clone()).await {
+					Ok(response) => return Ok(response),
+					Err(err) => last_err = Some(err),
+				}
+			}
+			Err(last_err.unwrap_or_else(|| TransportErrorKind::custom_str("no rpc endpoints")))
+		})
+	}
+}
+
+/// Send `req` to the endpoint at `index` and record the outcome.
+async fn send<S>(
+	mut transport: S,
+	pool: Arc<EndpointPool>,
+	index: usize,
+	req: RequestPacket,
+) -> Result<ResponsePacket, TransportError>
+where
+	S: Service<
+		RequestPacket,
+		Response = ResponsePacket,
+		Error = TransportError,
+		Future = TransportFut<'static>,
+	>,
+{
+	let started = Instant::now();
+	let res = transport.call(req).await;
+	match &res {
+		Ok(_) => pool.record_success(index, started.elapsed()),
+		Err(err) => {
+			tracing::debug!(target: crate::LOG_TARGET, url = %pool.url(index), ?err, "rpc request failed");
+			pool.record_failure(index);
+		},
+	}
+	res
+}
+
+/// Returns true if every request in the packet broadcasts a transaction.
+fn is_broadcast(req: &RequestPacket) -> bool {
+	match req {
+		RequestPacket::Single(req) => BROADCAST_METHODS.contains(&req.method()),
+		RequestPacket::Batch(batch) =>
+			!batch.is_empty() && batch.iter().all(|req| BROADCAST_METHODS.contains(&req.method())),
+	}
+}
+
+/// Returns true if any response in the packet is a JSON-RPC error.
+fn is_error(response: &ResponsePacket) -> bool {
+	match response {
+		ResponsePacket::Single(response) => response.payload.is_error(),
+		ResponsePacket::Batch(batch) => batch.iter().any(|response| response.payload.is_error()),
+	}
+}
+
 /// Strip `type` and `accessList` from the params of a single JSON-RPC request.
 /// Only processes methods that send transaction-like objects; all others pass through
 /// with zero overhead.
@@ -157,6 +299,10 @@ fn strip_request(req: SerializedRequest) -> Result<SerializedRequest, TransportE
 mod tests {
 	use super::*;
 	use serde_json::json;
+	use std::{
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
 
 	fn make_serialized_request(
 		method: &'static str,
@@ -238,6 +384,16 @@ mod tests {
 		assert_eq!(obj.get("data").unwrap(), "0x1234");
 	}
 
+	#[test]
+	fn only_transaction_broadcasts_are_fanned_out() {
+		let send = make_serialized_request("eth_sendRawTransaction", json!(["0x02f8"]));
+		let call = make_serialized_request("eth_call", json!([{"to": "0xdead"}, "latest"]));
+		assert!(is_broadcast(&RequestPacket::Single(send.clone())));
+		assert!(!is_broadcast(&RequestPacket::Single(call.clone())));
+		assert!(is_broadcast(&RequestPacket::Batch(vec![send.clone(), send.clone()])));
+		assert!(!is_broadcast(&RequestPacket::Batch(vec![send, call])));
+	}
+
 	#[test]
 	fn rpc_transport_default_is_standard() {
 		assert_eq!(RpcTransport::default(), RpcTransport::Standard);
@@ -251,4 +407,88 @@ mod tests {
 		let tron: RpcTransport = serde_json::from_str("\"tron\"").unwrap();
 		assert_eq!(tron, RpcTransport::Tron);
 	}
+
+	/// An endpoint answering every request with `response` after `delay`, or failing if it's
+	/// `None`.
+	#[derive(Clone)]
+	struct Endpoint {
+		response: Option<&'static str>,
+		delay: Duration,
+		calls: Arc<AtomicUsize>,
+	}
+
+	impl Endpoint {
+		fn new(response: Option<&'static str>, delay: u64) -> Self {
+			Self { response, delay: Duration::from_millis(delay), calls: Default::default() }
+		}
+	}
+
+	impl Service<RequestPacket> for Endpoint {
+		type Response = ResponsePacket;
+		type Error = TransportError;
+		type Future = TransportFut<'static>;
+
+		fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+			Poll::Ready(Ok(()))
+		}
+
+		fn call(&mut self, _req: RequestPacket) -> Self::Future {
+			let endpoint = self.clone();
+			Box::pin(async move {
+				tokio::time::sleep(endpoint.delay).await;
+				endpoint.calls.fetch_add(1, Ordering::SeqCst);
+				match endpoint.response {
+					Some(response) => Ok(serde_json::from_str(response).unwrap()),
+					None => Err(TransportErrorKind::custom_str("connection refused")),
+				}
+			})
+		}
+	}
+
+	const ACCEPTED: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x01"}"#;
+	const REJECTED: &str =
+		r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"nonce too low"}}"#;
+
+	fn service(endpoints: &[Endpoint]) -> HealthService<Endpoint> {
+		let urls = (0..endpoints.len()).map(|i| format!("https://rpc-{i}")).collect();
+		HealthService::new(
+			endpoints.to_vec(),
+			Arc::new(EndpointPool::new(urls, Default::default())),
+		)
+	}
+
+	fn request(method: &'static str) -> RequestPacket {
+		RequestPacket::Single(make_serialized_request(method, json!(["0x02f8"])))
+	}
+
+	#[tokio::test]
+	async fn broadcasts_wait_for_an_endpoint_that_accepts() {
+		let endpoints = [Endpoint::new(Some(REJECTED), 0), Endpoint::new(Some(ACCEPTED), 20)];
+		let response = service(&endpoints).call(request("eth_sendRawTransaction")).await.unwrap();
+		assert!(!is_error(&response));
+	}
+
+	#[tokio::test]
+	async fn broadcasts_return_the_rejection_if_no_endpoint_accepts() {
+		let endpoints = [Endpoint::new(Some(REJECTED), 10), Endpoint::new(None, 0)];
+		let response = service(&endpoints).call(request("eth_sendRawTransaction")).await.unwrap();
+		assert!(is_error(&response));
+
+		let endpoints = [Endpoint::new(None, 0), Endpoint::new(None, 0)];
+		assert!(service(&endpoints).call(request("eth_sendRawTransaction")).await.is_err());
+	}
+
+	#[tokio::test]
+	async fn reads_fail_over_on_transport_errors_only() {
+		let endpoints = [Endpoint::new(None, 0), Endpoint::new(Some(ACCEPTED), 0)];
+		let response = service(&endpoints).call(request("eth_call")).await.unwrap();
+		assert!(!is_error(&response));
+		assert_eq!(endpoints[1].calls.load(Ordering::SeqCst), 1);
+
+		// A JSON-RPC error is the node's answer, it's returned without trying another endpoint
+		let endpoints = [Endpoint::new(Some(REJECTED), 0), Endpoint::new(Some(ACCEPTED), 0)];
+		let response = service(&endpoints).call(request("eth_call")).await.unwrap();
+		assert!(is_error(&response));
+		assert_eq!(endpoints[1].calls.load(Ordering::SeqCst), 0);
+	}
 }
@@ -4,6 +4,7 @@ use crate::{
 		CRONOS_TESTNET_CHAIN_ID, GNOSIS_CHAIN_ID, INJECTIVE_CHAIN_ID, INJECTIVE_TESTNET_CHAIN_ID,
 		SEI_CHAIN_ID, SEI_TESTNET_CHAIN_ID,
 	},
+	tx_manager::PendingTx,
 	EvmClient,
 };
 use alloy::{
@@ -16,21 +17,23 @@ use alloy::{
 use alloy_sol_types::SolEvent;
 use anyhow::anyhow;
 use codec::Decode;
+use futures::future::join_all;
 use ismp::{
 	host::StateMachine,
-	messaging::{hash_request, Message, ResponseMessage},
+	messaging::{hash_request, Message, ResponseMessage, TimeoutMessage},
 	router::{GetResponse, Request},
 };
 use ismp_abi::{
 	evm_host::{GetRequestHandled, NewEpoch, PostRequestHandled},
 	handler::handler_v2::{
 		GetResponseLeaf, GetResponseMessage, HandlerV2Instance, PostRequestLeaf,
-		PostRequestMessage, Proof, StateMachineHeight,
+		PostRequestMessage, PostRequestTimeoutMessage, Proof, StateMachineHeight,
 	},
 };
 use pallet_ismp::offchain::{LeafIndexAndPos, Proof as MmrProof};
 use primitive_types::{H256, U256};
 use std::{collections::BTreeSet, time::Duration};
+use substrate_state_machine::StateMachineProof;
 use tesseract_primitives::{Hasher, NewEpochEvent, Query, TxReceipt, TxResult};
 
 use crate::gas_oracle::get_current_gas_cost_in_usd;
@@ -38,7 +41,7 @@ use crate::gas_oracle::get_current_gas_cost_in_usd;
 // ── Pure helpers ──────────────────────────────────────────────────────────────
 
 /// Check if an error is a rate limit (429) or other retryable RPC error.
-fn is_rate_limit_error(err: &anyhow::Error) -> bool {
+pub(crate) fn is_rate_limit_error(err: &anyhow::Error) -> bool {
 	if let Some(transport_err) = err.downcast_ref::<TransportError>() {
 		return match transport_err {
 			TransportError::Transport(kind) => kind.is_retry_err(),
@@ -100,6 +103,26 @@ fn build_get_response_message(msg: &ResponseMessage) -> anyhow::Result<GetRespon
 	Ok(GetResponseMessage { proof, responses })
 }
 
+/// Build the `handlePostRequestTimeouts` message from a timeout message. The proof is a
+/// non-membership proof of the request receipts in hyperbridge's state.
+///
+/// Get timeouts on EVM hosts need a non-membership proof of the response receipts on hyperbridge,
+/// which [`TimeoutMessage::Get`] doesn't carry, so they are rejected.
+fn build_post_timeout_message(msg: &TimeoutMessage) -> anyhow::Result<PostRequestTimeoutMessage> {
+	let TimeoutMessage::Post { requests, timeout_proof } = msg else {
+		return Err(anyhow!("Get request timeouts are not supported by the handler"));
+	};
+	let proof = StateMachineProof::decode(&mut &*timeout_proof.proof)?;
+	Ok(PostRequestTimeoutMessage {
+		timeouts: requests.iter().cloned().map(Into::into).collect(),
+		height: StateMachineHeight {
+			stateMachineId: extract_state_machine_id(&timeout_proof.height.id.state_id)?,
+			height: AlloyU256::from(timeout_proof.height.height),
+		},
+		proof: proof.storage_proof.into_iter().map(Bytes::from).collect(),
+	})
+}
+
 /// Extract handled-message commitments from a receipt's logs.
 ///
 /// `PostRequestHandled` carries the post request's commitment, `GetRequestHandled` the
@@ -220,7 +243,7 @@ async fn fetch_gas_price(client: &EvmClient, debug_trace: bool) -> anyhow::Resul
 
 	let mut price = get_current_gas_cost_in_usd(
 		client.state_machine,
-		client.ismp_host.0.into(),
+		&client.price_oracle,
 		client.client.clone(),
 	)
 	.await?
@@ -270,7 +293,7 @@ pub async fn wait_for_transaction_receipt(
 
 /// Build unsigned `TransactionRequest`s for a batch of ISMP messages.
 ///
-/// Returns the requests and the gas price used (needed for cancellation bumping).
+/// Returns the requests and the gas price used, which the transaction manager prices them from.
 /// Pass `debug_trace = true` to skip gas price (except on Erigon).
 pub async fn generate_contract_calls(
 	client: &EvmClient,
@@ -321,9 +344,17 @@ pub async fn generate_contract_calls(
 				(call.calldata().clone(), gas_with_buffer(gas))
 			},
 
-			Message::Timeout(_) => return Err(anyhow!("Timeout messages not supported by relayer")),
+			Message::Timeout(msg) => {
+				let message = build_post_timeout_message(msg)?;
+				let call = contract.handlePostRequestTimeouts(ismp_host, message);
+				let gas = call.estimate_gas().await.unwrap_or_else(|_| chain_gas_limit / 4);
+				(call.calldata().clone(), gas_with_buffer(gas))
+			},
 
 			Message::FraudProof(_) => return Err(anyhow!("Unexpected fraud proof message")),
+
+			Message::AggregatedRequest(_) =>
+				return Err(anyhow!("Aggregated request messages are not supported by the handler")),
 		};
 
 		txs.push(build_tx_request(from, handler_addr, calldata, gas_price, gas_limit));
@@ -377,11 +408,16 @@ async fn build_batch_inner_calls(
 				contract.handleGetResponses(ismp_host, message).calldata().clone()
 			},
 
-			Message::Timeout(_) =>
-				return Err(anyhow!("Timeout messages are not supported by batchCall")),
+			Message::Timeout(msg) => {
+				let message = build_post_timeout_message(msg)?;
+				contract.handlePostRequestTimeouts(ismp_host, message).calldata().clone()
+			},
 
 			Message::FraudProof(_) =>
 				return Err(anyhow!("Unexpected fraud proof message in batchCall")),
+
+			Message::AggregatedRequest(_) =>
+				return Err(anyhow!("Aggregated request messages are not supported by batchCall")),
 		};
 		inner.push(calldata);
 	}
@@ -438,17 +474,17 @@ pub async fn generate_batched_contract_calls(
 	Ok((txs, gas_price))
 }
 
-/// Submit a full batch of ISMP messages as a single `IHandlerV2.batchCall` transaction.
+/// Broadcast a full batch of ISMP messages as a single `IHandlerV2.batchCall` transaction.
 ///
 /// One tx replaces what would otherwise be N separate txs (one per message),
 /// cutting gas overhead and nonce management complexity. Atomic: if any
 /// inner call reverts, the whole transaction reverts.
-pub async fn submit_batch_messages(
+pub async fn dispatch_batch_messages(
 	client: &EvmClient,
 	messages: Vec<Message>,
-) -> anyhow::Result<SubmitOutcome> {
+) -> anyhow::Result<DispatchedMessages> {
 	if messages.is_empty() {
-		return Ok((BTreeSet::new(), Vec::new(), Vec::new()));
+		return Ok(DispatchedMessages::default());
 	}
 
 	let handler_addr = Address::from_slice(&client.handler().await?.0);
@@ -465,212 +501,118 @@ pub async fn submit_batch_messages(
 	let tx_request =
 		build_tx_request(from, handler_addr, calldata, gas_price, gas_with_buffer(gas));
 
-	let nonce = client.signer.get_transaction_count(from).await?;
-	let tx = tx_request.nonce(nonce).transaction_type(0);
-
 	// Consensus updates and application messages share the batch — count the
 	// latter separately so logs make it obvious when a tx is carrying real
 	// work vs. just advancing the light client.
 	let non_consensus_msgs =
 		messages.iter().filter(|m| !matches!(m, Message::Consensus(_))).count();
 
+	// The manager re-prices the transaction until it's included, a stuck
+	// batch is never cancelled.
+	let pending = client.tx_manager.send(client, tx_request).await?;
+	let tx_hash = H256::from_slice(pending.tx_hash.as_slice());
 	tracing::info!(
 		target: crate::LOG_TARGET, chain = ?client.state_machine,
 		msgs = messages.len(),
 		non_consensus_msgs,
 		calldata_bytes = calldata_len,
 		gas_estimate = gas,
-		nonce,
-		"dispatching HandlerV2.batchCall",
-	);
-
-	// Bounded rate-limit retry. If the remote is throttling for longer than
-	// MAX_RATE_LIMIT_RETRIES * 1s, give up and let the outbound task retry on
-	// the next ProofAccepted event — avoids pinning a task forever.
-	const MAX_RATE_LIMIT_RETRIES: u32 = 5;
-	let mut attempt = 0u32;
-	let pending = loop {
-		match client.signer.send_transaction(tx.clone()).await {
-			Ok(p) => break p,
-			Err(err) => {
-				let err = anyhow::Error::from(err);
-				if is_rate_limit_error(&err) {
-					attempt += 1;
-					if attempt > MAX_RATE_LIMIT_RETRIES {
-						return Err(anyhow!(
-							"batchCall to {:?} exceeded {} rate-limit retries",
-							client.state_machine,
-							MAX_RATE_LIMIT_RETRIES,
-						));
-					}
-					tracing::info!(
-						target: crate::LOG_TARGET, chain = ?client.state_machine,
-						attempt,
-						max = MAX_RATE_LIMIT_RETRIES,
-						"rate limited; retrying batchCall in 1s",
-					);
-					tokio::time::sleep(Duration::from_secs(10)).await;
-				} else {
-					return Err(err);
-				}
-			},
-		}
-	};
-
-	let tx_hash = H256::from_slice(pending.tx_hash().as_slice());
-	let (events, new_epochs) = match wait_for_success(client, tx_hash).await? {
-		Some(evs) => evs,
-		None => {
-			cancel_transaction(client, from, nonce, gas_price, tx_hash).await;
-			return Err(anyhow!("batchCall to {:?} was cancelled", client.state_machine));
-		},
-	};
-	tracing::info!(
-		target: crate::LOG_TARGET, chain = ?client.state_machine,
+		nonce = pending.nonce,
 		?tx_hash,
-		events = events.len(),
-		new_epochs = ?new_epochs,
-		"batchCall included",
+		"dispatched HandlerV2.batchCall",
 	);
 
-	// Atomic semantics: if the tx succeeded every inner call did, so no
-	// per-message unsuccessful bucket.
-	Ok((events, Vec::new(), new_epochs))
-}
-
-/// Result of a single transaction submission: which message commitments
-/// fired, which messages didn't, and any `NewEpoch` logs the relayer
-/// should claim on Hyperbridge.
-type SubmitOutcome = (BTreeSet<H256>, Vec<Message>, Vec<NewEpochEvent>);
-
-/// Send a zero-value self-transfer at 10× gas to evict a stuck transaction from the mempool.
-#[tracing::instrument(skip_all, fields(chain = ?client.state_machine))]
-async fn cancel_transaction(
-	client: &EvmClient,
-	from: Address,
-	nonce: u64,
-	gas_price: U256,
-	stuck_tx: H256,
-) {
-	tracing::warn!(target: crate::LOG_TARGET, "Cancelling stuck tx {stuck_tx:#?} at nonce {nonce}",);
-	let cancel_tx = TransactionRequest::default()
-		.to(from)
-		.value(AlloyU256::ZERO)
-		.gas_price((gas_price * U256::from(10)).low_u128())
-		.nonce(nonce)
-		.transaction_type(0);
-
-	let Ok(pending) = client.signer.send_transaction(cancel_tx).await else { return };
-	let cancel_hash = H256::from_slice(pending.tx_hash().as_slice());
-
-	if let Ok(Some(receipt)) = wait_for_transaction_receipt(cancel_hash, client).await {
-		let status = if receipt.inner.status_or_post_state() == Eip658Value::Eip658(true) {
-			"succeeded"
-		} else {
-			"reverted"
-		};
-		tracing::info!(target: crate::LOG_TARGET, "Cancellation tx for {:?} {status}", client.state_machine);
-	}
+	Ok(DispatchedMessages {
+		txs: vec![DispatchedTx { pending, messages: messages.clone(), batched: true }],
+		unsent: Vec::new(),
+		messages,
+	})
 }
 
-/// Submit ISMP messages as EVM transactions.
+/// Broadcast ISMP messages as EVM transactions, one per message.
 ///
-/// Retries individual sends on rate-limit errors. On timeout, consensus messages are retried once
-/// with 2× gas on the same nonce; all other messages are cancelled with a zero-value self-transfer
-/// on the same nonce so the sequence slot is freed for the next round.
-pub async fn submit_messages(
+/// The transactions are handed to the [`TxManager`](crate::tx_manager::TxManager) in message
+/// order, so they execute in that order. Stuck transactions are re-priced on their nonce rather
+/// than cancelled. If a message can't be sent, neither can the ones after it: they are reported
+/// as unsuccessful once the sent ones are confirmed.
+pub async fn dispatch_messages_individually(
 	client: &EvmClient,
 	messages: Vec<Message>,
-) -> anyhow::Result<SubmitOutcome> {
-	let (tx_requests, gas_price) = generate_contract_calls(client, &messages, false).await?;
-
-	let mut events = BTreeSet::new();
-	let mut unsuccessful = Vec::new();
-	let mut new_epochs: Vec<NewEpochEvent> = Vec::new();
-
-	let from = Address::from_slice(&client.address);
-
-	for (idx, tx) in tx_requests.into_iter().enumerate() {
-		// Fetch the pending nonce upfront so we can reuse it if the tx gets stuck.
-		let nonce = client.signer.get_transaction_count(from).await?;
-		let tx = tx.nonce(nonce).transaction_type(0);
-
-		// Retry the send on rate limits.
-		let pending = loop {
-			match client.signer.send_transaction(tx.clone()).await {
-				Ok(p) => break p,
-				Err(err) => {
-					let err = anyhow::Error::from(err);
-					if is_rate_limit_error(&err) {
-						tracing::info!(target: crate::LOG_TARGET, chain = ?client.state_machine, "Rate limited, retrying submission in 1s");
-						tokio::time::sleep(Duration::from_secs(1)).await;
-					} else {
-						return Err(err);
-					}
-				},
-			}
-		};
-
-		let tx_hash = H256::from_slice(pending.tx_hash().as_slice());
-
-		let (evs, epochs) = match wait_for_success(client, tx_hash).await? {
-			Some(evs) => evs,
-			None => {
-				cancel_transaction(client, from, nonce, gas_price, tx_hash).await;
-				return Err(anyhow!("Transaction to {:?} was cancelled", client.state_machine));
+) -> anyhow::Result<DispatchedMessages> {
+	let (tx_requests, _) = generate_contract_calls(client, &messages, false).await?;
+
+	let mut txs = Vec::with_capacity(tx_requests.len());
+	let mut unsent = Vec::new();
+	for (tx, message) in tx_requests.into_iter().zip(messages.iter()) {
+		if !unsent.is_empty() {
+			unsent.push(message.clone());
+			continue;
+		}
+		match client.tx_manager.send(client, tx).await {
+			Ok(pending) =>
+				txs.push(DispatchedTx { pending, messages: vec![message.clone()], batched: false }),
+			// Nothing was sent, there's nothing to wait for
+			Err(err) if txs.is_empty() => return Err(err),
+			Err(err) => {
+				tracing::warn!(
+					target: crate::LOG_TARGET, chain = ?client.state_machine,
+					"failed to send transaction, holding back the messages after it: {err:?}",
+				);
+				unsent.push(message.clone());
 			},
-		};
-
-		if matches!(messages[idx], Message::Request(_) | Message::Response(_)) && evs.is_empty() {
-			unsuccessful.push(messages[idx].clone());
 		}
-		events.extend(evs);
-		new_epochs.extend(epochs);
 	}
 
-	if !events.is_empty() {
-		tracing::trace!(
-			target: crate::LOG_TARGET, chain = ?client.state_machine,
-			"Got {} receipts",
-			events.len(),
-		);
-	}
+	Ok(DispatchedMessages { messages, txs, unsent })
+}
+
+/// A transaction broadcast for one or more messages
+struct DispatchedTx {
+	pending: PendingTx,
+	/// The messages the transaction carries
+	messages: Vec<Message>,
+	/// Whether the messages were sent in a single atomic `IHandlerV2.batchCall`
+	batched: bool,
+}
 
-	Ok((events, unsuccessful, new_epochs))
+/// Transactions broadcast for a set of messages, see [`dispatch_messages`]
+#[derive(Default)]
+pub struct DispatchedMessages {
+	/// Every message that was handed in, in order
+	messages: Vec<Message>,
+	/// The transactions that were broadcast
+	txs: Vec<DispatchedTx>,
+	/// Messages that were never sent
+	unsent: Vec<Message>,
 }
 
-/// Wait for a transaction to be mined and verify it succeeded.
+/// Verify a transaction succeeded and extract what it delivered.
 ///
-/// Returns `Some((commitments, new_epochs))` on success — `commitments`
-/// from `PostRequestHandled` and `GetRequestHandled` logs, `new_epochs` from
+/// Returns `(commitments, new_epochs)` — `commitments` from
+/// `PostRequestHandled` and `GetRequestHandled` logs, `new_epochs` from
 /// every `EvmHost::NewEpoch(set_id, relayer)` log that names this
 /// client as the relayer (empty when no such logs are present, multiple
 /// entries when a single tx batched multiple consensus messages). Each
 /// `NewEpochEvent` carries the destination block in which the log was
 /// emitted, so the outbound-claim task can later prove `_epochs[set_id]`
 /// at exactly that height.
-/// Returns `None` on timeout and `Err` if the tx reverted.
-#[tracing::instrument(skip(client), fields(chain = ?client.state_machine, ?tx_hash))]
-pub async fn wait_for_success(
+/// Returns `Err` if the tx reverted.
+pub fn check_receipt(
 	client: &EvmClient,
-	tx_hash: H256,
-) -> anyhow::Result<Option<(BTreeSet<H256>, Vec<NewEpochEvent>)>> {
-	match wait_for_transaction_receipt(tx_hash, client).await? {
-		Some(receipt) =>
-			if receipt.inner.status_or_post_state() == Eip658Value::Eip658(true) {
-				tracing::info!(target: crate::LOG_TARGET, "Tx for {:?} succeeded", client.state_machine);
-				let commitments = extract_event_commitments(&receipt);
-				let new_epochs = extract_new_epochs_for_self(&receipt, &client.address);
-				Ok(Some((commitments, new_epochs)))
-			} else {
-				tracing::info!(
-					target: crate::LOG_TARGET, "Tx {:?} for {:?} reverted",
-					receipt.transaction_hash,
-					client.state_machine
-				);
-				Err(anyhow!("Transaction reverted"))
-			},
-		None => Ok(None),
+	receipt: &TransactionReceipt,
+) -> anyhow::Result<(BTreeSet<H256>, Vec<NewEpochEvent>)> {
+	if receipt.inner.status_or_post_state() == Eip658Value::Eip658(true) {
+		tracing::info!(target: crate::LOG_TARGET, "Tx for {:?} succeeded", client.state_machine);
+		let commitments = extract_event_commitments(receipt);
+		let new_epochs = extract_new_epochs_for_self(receipt, &client.address);
+		Ok((commitments, new_epochs))
+	} else {
+		tracing::info!(
+			target: crate::LOG_TARGET, "Tx {:?} for {:?} reverted",
+			receipt.transaction_hash,
+			client.state_machine
+		);
+		Err(anyhow!("Transaction reverted"))
 	}
 }
 
@@ -715,31 +657,108 @@ fn build_tx_receipts(
 	TxResult { receipts: results, unsuccessful, new_epochs }
 }
 
-/// Top-level submission entry.
+/// Broadcast the transactions delivering `messages`, without waiting for them to be included.
 ///
 /// - **Batch of 1** (e.g. the mandatory-consensus-only chunks from the outbound rotation catch-up)
-///   routes through the legacy per-message [`submit_messages`] path. Wrapping a single call in
-///   `IHandlerV2.batchCall` adds a self-delegatecall frame with no upside, costs extra gas, and
-///   makes the receipt harder to interpret downstream.
-/// - **Batch of ≥2** dispatches through [`submit_batch_messages`], the atomic
+///   routes through the legacy per-message [`dispatch_messages_individually`] path. Wrapping a
+///   single call in `IHandlerV2.batchCall` adds a self-delegatecall frame with no upside, costs
+///   extra gas, and makes the receipt harder to interpret downstream.
+/// - **Batch of ≥2** dispatches through [`dispatch_batch_messages`], the atomic
 ///   `IHandlerV2.batchCall` path. Chains whose handler doesn't implement `IHandlerV2` will revert
 ///   at the handler address — the legacy serial-submit fallback is no longer supported for real
 ///   batches.
-pub async fn handle_message_submission(
+///
+/// The client's submission pipeline runs this for one batch at a time, so transactions are sent
+/// in the order the batches were submitted. Their receipts are awaited outside the pipeline with
+/// [`await_dispatched`], so the next batch is sent while the previous one is still pending.
+pub async fn dispatch_messages(
 	client: &EvmClient,
 	messages: Vec<Message>,
+) -> anyhow::Result<DispatchedMessages> {
+	match messages.len() {
+		0 => Ok(DispatchedMessages::default()),
+		1 => dispatch_messages_individually(client, messages).await,
+		_ => dispatch_batch_messages(client, messages).await,
+	}
+}
+
+/// Wait for every transaction of a dispatch to be confirmed and collect what they delivered.
+///
+/// Every transaction is accounted for on its own: the requests and responses carried by one that
+/// reverted, timed out or was never sent are reported as unsuccessful, without discarding what
+/// the other transactions delivered. An error is only returned if none of them succeeded.
+pub async fn await_dispatched(
+	client: &EvmClient,
+	dispatched: DispatchedMessages,
 ) -> anyhow::Result<TxResult> {
+	let DispatchedMessages { messages, txs, unsent } = dispatched;
 	if messages.is_empty() {
 		return Ok(TxResult::default());
 	}
 
-	let (receipts, unsuccessful, new_epochs) = if messages.len() == 1 {
-		submit_messages(client, messages.clone()).await?
-	} else {
-		submit_batch_messages(client, messages.clone()).await?
-	};
+	let results = join_all(txs.into_iter().map(|tx| async move {
+		let result = client
+			.tx_manager
+			.wait(tx.pending)
+			.await
+			.and_then(|receipt| check_receipt(client, &receipt));
+		(result, tx.messages, tx.batched)
+	}))
+	.await;
+
+	let is_delivery = |msg: &Message| matches!(msg, Message::Request(_) | Message::Response(_));
+	let mut events = BTreeSet::new();
+	let mut unsuccessful: Vec<Message> = unsent.into_iter().filter(is_delivery).collect();
+	let mut new_epochs: Vec<NewEpochEvent> = Vec::new();
+	let mut delivered_any = false;
+	let mut last_error = None;
+	for (result, carried, batched) in results {
+		match result {
+			Ok((evs, epochs)) => {
+				delivered_any = true;
+				// Atomic semantics: if a batch succeeded every inner call did, so
+				// only individually sent messages can go undelivered.
+				if !batched && evs.is_empty() {
+					unsuccessful.extend(carried.into_iter().filter(is_delivery));
+				}
+				events.extend(evs);
+				new_epochs.extend(epochs);
+			},
+			Err(err) => {
+				tracing::warn!(
+					target: crate::LOG_TARGET, chain = ?client.state_machine,
+					msgs = carried.len(),
+					"transaction failed: {err:?}",
+				);
+				unsuccessful.extend(carried.into_iter().filter(is_delivery));
+				last_error = Some(err);
+			},
+		}
+	}
+
+	if let Some(err) = last_error.filter(|_| !delivered_any) {
+		return Err(err);
+	}
+
+	if !events.is_empty() {
+		tracing::trace!(
+			target: crate::LOG_TARGET, chain = ?client.state_machine,
+			"Got {} receipts",
+			events.len(),
+		);
+	}
+
 	let height = client.client.get_block_number().await?;
-	Ok(build_tx_receipts(receipts, unsuccessful, messages, height, new_epochs))
+	Ok(build_tx_receipts(events, unsuccessful, messages, height, new_epochs))
+}
+
+/// Send `messages` and wait for them to be delivered, see [`dispatch_messages`].
+pub async fn handle_message_submission(
+	client: &EvmClient,
+	messages: Vec<Message>,
+) -> anyhow::Result<TxResult> {
+	let dispatched = dispatch_messages(client, messages).await?;
+	await_dispatched(client, dispatched).await
 }
 
 #[cfg(test)]
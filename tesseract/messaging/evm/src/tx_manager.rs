@@ -0,0 +1,852 @@
+//! Transaction manager for relayer submissions.
+//!
+//! Every transaction the relayer sends on an EVM chain goes through a [`TxManager`]. There is one
+//! manager per signer on every chain, shared by all the clients in the process that sign with the
+//! same key. Nonces are assigned locally, so several transactions can be in flight at once and
+//! execute in the order they were handed to the manager. Once broadcast, a transaction is tracked
+//! by a background task until it's confirmed, whether or not anyone waits for it. A transaction
+//! that isn't included within [`TxManagerConfig::resubmit_after_secs`] is re-priced: its fees are
+//! raised geometrically by [`TxManagerConfig::fee_bump_percent`], up to
+//! [`TxManagerConfig::max_fee_per_gas`], and the replacement is broadcast on the same nonce.
+//! Deliveries are never cancelled.
+//!
+//! A receipt only counts once its block is [`TxManagerConfig::confirmations`] deep and still
+//! canonical. Receipts from blocks that were reorged out are ignored and the transaction is
+//! tracked, and re-priced, as if it was never included.
+//!
+//! When a [`TxManagerConfig::journal`] is configured, in-flight transactions are persisted to it
+//! and picked up again after a restart.
+
+use crate::{tx::is_rate_limit_error, EvmClient};
+use alloy::{
+	consensus::TxReceipt as _,
+	primitives::{Address, B256},
+	providers::Provider,
+	rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use anyhow::anyhow;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, LazyLock, Weak,
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{oneshot, OnceCell, OwnedSemaphorePermit, Semaphore};
+
+/// Nodes reject replacements that don't raise fees by at least this much
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// Number of consecutive polls that must find the nonce used up without any of our transactions
+/// being included before the transaction is given up on.
+const NONCE_CONSUMED_POLLS: u32 = 3;
+
+/// Maximum number of times a broadcast is retried when the rpc is rate limiting.
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Managers in use in this process, by chain id and signer
+static MANAGERS: LazyLock<Mutex<HashMap<(u64, Address), Weak<TxManager>>>> =
+	LazyLock::new(Default::default);
+
+/// Configuration for the [`TxManager`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TxManagerConfig {
+	/// Maximum number of transactions in flight at once. Sending waits for an earlier
+	/// transaction to be confirmed once this many are pending.
+	pub max_in_flight: usize,
+	/// Seconds to wait for inclusion before a transaction is re-priced
+	pub resubmit_after_secs: u64,
+	/// Percentage fees are raised by every time a transaction is re-priced. Values below
+	/// [`MIN_REPLACEMENT_BUMP_PERCENT`] are raised to it.
+	pub fee_bump_percent: u64,
+	/// Upper bound in wei on the max fee per gas, or the gas price for legacy transactions
+	pub max_fee_per_gas: Option<u128>,
+	/// Number of blocks, including its own, a receipt's block must be buried under
+	pub confirmations: u64,
+	/// Seconds a submission waits for its receipt. Transactions still pending after this keep
+	/// being tracked and re-priced in the background, but the submission reports an error.
+	pub receipt_timeout_secs: u64,
+	/// Seconds between receipt polls
+	pub poll_interval_secs: u64,
+	/// Whether to send EIP-1559 transactions, detected from the latest block when omitted
+	pub eip1559: Option<bool>,
+	/// File in-flight transactions are persisted to. Must not be shared between chains.
+	pub journal: Option<PathBuf>,
+}
+
+impl Default for TxManagerConfig {
+	fn default() -> Self {
+		Self {
+			max_in_flight: 16,
+			resubmit_after_secs: 60,
+			fee_bump_percent: 20,
+			max_fee_per_gas: None,
+			confirmations: 1,
+			receipt_timeout_secs: 10 * 60,
+			poll_interval_secs: 7,
+			eip1559: None,
+			journal: None,
+		}
+	}
+}
+
+/// Fees offered by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fees {
+	/// A legacy transaction
+	Legacy {
+		/// Gas price in wei
+		gas_price: u128,
+	},
+	/// An EIP-1559 transaction
+	Eip1559 {
+		/// Max fee per gas in wei
+		max_fee_per_gas: u128,
+		/// Max priority fee per gas in wei
+		max_priority_fee_per_gas: u128,
+	},
+}
+
+impl Fees {
+	/// The most the transaction pays per unit of gas
+	pub fn max_fee_per_gas(&self) -> u128 {
+		match self {
+			Fees::Legacy { gas_price } => *gas_price,
+			Fees::Eip1559 { max_fee_per_gas, .. } => *max_fee_per_gas,
+		}
+	}
+
+	/// Set these fees on `tx`
+	pub fn apply(&self, mut tx: TransactionRequest) -> TransactionRequest {
+		match *self {
+			Fees::Legacy { gas_price } => {
+				tx.gas_price = Some(gas_price);
+				tx.max_fee_per_gas = None;
+				tx.max_priority_fee_per_gas = None;
+				tx.transaction_type = Some(0);
+			},
+			Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => {
+				tx.gas_price = None;
+				tx.max_fee_per_gas = Some(max_fee_per_gas);
+				tx.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+				tx.transaction_type = Some(2);
+			},
+		}
+		tx
+	}
+
+	/// Fees for a replacement of a transaction paying `self`. Every fee is raised by `percent`, or
+	/// to the `market` rate if that is higher, and the max fee is capped at `cap`. Returns `None`
+	/// if the cap leaves no room for a replacement nodes would accept.
+	pub fn bump(&self, percent: u64, market: Option<Fees>, cap: Option<u128>) -> Option<Fees> {
+		let percent = percent.max(MIN_REPLACEMENT_BUMP_PERCENT);
+		let raise = |fee: u128| (fee.saturating_mul(100 + percent as u128) / 100).max(fee + 1);
+		let minimum = |fee: u128| {
+			fee.saturating_mul(100 + MIN_REPLACEMENT_BUMP_PERCENT as u128).div_ceil(100)
+		};
+		let cap = cap.unwrap_or(u128::MAX);
+
+		match (*self, market) {
+			(Fees::Legacy { gas_price }, market) => {
+				let market = match market {
+					Some(Fees::Legacy { gas_price }) => gas_price,
+					_ => 0,
+				};
+				let bumped = raise(gas_price).max(market).min(cap);
+				(bumped >= minimum(gas_price)).then_some(Fees::Legacy { gas_price: bumped })
+			},
+			(Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas }, market) => {
+				let (market_fee, market_tip) = match market {
+					Some(Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas }) =>
+						(max_fee_per_gas, max_priority_fee_per_gas),
+					_ => (0, 0),
+				};
+				let fee = raise(max_fee_per_gas).max(market_fee).min(cap);
+				let tip = raise(max_priority_fee_per_gas).max(market_tip).min(fee);
+				let valid =
+					fee >= minimum(max_fee_per_gas) && tip >= minimum(max_priority_fee_per_gas);
+				valid.then_some(Fees::Eip1559 {
+					max_fee_per_gas: fee,
+					max_priority_fee_per_gas: tip,
+				})
+			},
+		}
+	}
+}
+
+/// A transaction the manager is waiting on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InFlightTx {
+	/// The transaction's nonce
+	pub nonce: u64,
+	/// The latest version of the transaction, including its nonce and fees
+	pub request: TransactionRequest,
+	/// Fees offered by the latest version
+	pub fees: Fees,
+	/// Hashes of every version that was broadcast, oldest first
+	pub hashes: Vec<B256>,
+	/// Unix timestamp in seconds of the last broadcast
+	pub last_sent: u64,
+	/// Number of times the transaction was re-priced
+	pub bumps: u32,
+}
+
+/// Contents of a [`TxManagerConfig::journal`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+	/// Chain the transactions were sent on
+	chain_id: u64,
+	/// Account the transactions were sent from
+	from: Address,
+	/// Transactions in flight
+	transactions: Vec<InFlightTx>,
+}
+
+/// Where a receipt stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptStatus {
+	/// Included in a canonical block, but not yet buried deep enough
+	Included,
+	/// Included in a canonical block that is buried deep enough
+	Confirmed,
+	/// Included in a block that is no longer canonical
+	Reorged,
+}
+
+/// Classify a receipt from `block`, whose hash was `receipt_block_hash`, given the hash of the
+/// canonical block at that height and the latest block number.
+pub fn receipt_status(
+	block: u64,
+	receipt_block_hash: Option<B256>,
+	canonical_hash: Option<B256>,
+	latest: u64,
+	confirmations: u64,
+) -> ReceiptStatus {
+	if receipt_block_hash.is_none() || receipt_block_hash != canonical_hash {
+		return ReceiptStatus::Reorged;
+	}
+	if latest.saturating_sub(block) + 1 >= confirmations.max(1) {
+		ReceiptStatus::Confirmed
+	} else {
+		ReceiptStatus::Included
+	}
+}
+
+/// A transaction handed to the manager, see [`TxManager::wait`]
+pub struct PendingTx {
+	/// The transaction's nonce
+	pub nonce: u64,
+	/// Hash of the first broadcast
+	pub tx_hash: B256,
+	/// Resolved by the task tracking the transaction
+	receipt: oneshot::Receiver<anyhow::Result<TransactionReceipt>>,
+}
+
+/// Outcome of a single poll of an in-flight transaction
+enum Poll {
+	Confirmed(TransactionReceipt),
+	Pending,
+	NonceConsumed,
+}
+
+/// Assigns nonces to, prices, tracks and re-prices a signer's transactions on one chain
+pub struct TxManager {
+	config: TxManagerConfig,
+	chain_id: u64,
+	from: Address,
+	/// Nonce the next transaction is sent with, `None` until it's fetched from the chain
+	next_nonce: tokio::sync::Mutex<Option<u64>>,
+	in_flight: Mutex<BTreeMap<u64, InFlightTx>>,
+	/// One for every transaction in flight, held by the task tracking it
+	permits: Arc<Semaphore>,
+	eip1559: OnceCell<bool>,
+	resumed: AtomicBool,
+}
+
+impl TxManager {
+	/// Create a manager for the transactions `from` sends on `chain_id`
+	pub fn new(config: TxManagerConfig, chain_id: u64, from: Address) -> Self {
+		let permits = Arc::new(Semaphore::new(config.max_in_flight.max(1)));
+		Self {
+			config,
+			chain_id,
+			from,
+			next_nonce: Default::default(),
+			in_flight: Default::default(),
+			permits,
+			eip1559: OnceCell::new(),
+			resumed: AtomicBool::new(false),
+		}
+	}
+
+	/// The manager for the transactions `from` sends on `chain_id`. Clients signing with the same
+	/// key on the same chain share a manager, so they never hand out the same nonce. The config
+	/// of the first client is used.
+	pub fn shared(config: TxManagerConfig, chain_id: u64, from: Address) -> Arc<Self> {
+		let mut managers = MANAGERS.lock();
+		managers.retain(|_, manager| manager.strong_count() > 0);
+		if let Some(manager) = managers.get(&(chain_id, from)).and_then(Weak::upgrade) {
+			if manager.config != config {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					chain_id,
+					?from,
+					"signer is shared with another client, using that client's tx_manager config",
+				);
+			}
+			return manager;
+		}
+		let manager = Arc::new(Self::new(config, chain_id, from));
+		managers.insert((chain_id, from), Arc::downgrade(&manager));
+		manager
+	}
+
+	/// Transactions currently in flight, by nonce
+	pub fn in_flight(&self) -> BTreeMap<u64, InFlightTx> {
+		self.in_flight.lock().clone()
+	}
+
+	/// Load the transactions persisted in the journal and track them in the background until
+	/// they are included.
+	pub fn resume(self: &Arc<Self>, client: &EvmClient) -> anyhow::Result<()> {
+		let Some(path) = &self.config.journal else { return Ok(()) };
+		// Shared managers are only resumed by the first client
+		if self.resumed.swap(true, Ordering::SeqCst) {
+			return Ok(());
+		}
+		let journal = load_journal(path)?;
+		if journal.transactions.is_empty() {
+			return Ok(());
+		}
+		if journal.chain_id != self.chain_id || journal.from != self.from {
+			Err(anyhow!(
+				"Transaction journal {path:?} belongs to {:?} on chain {}, refusing to resume it",
+				journal.from,
+				journal.chain_id
+			))?
+		}
+
+		let nonces = journal.transactions.iter().map(|tx| tx.nonce).collect::<Vec<_>>();
+		tracing::info!(
+			target: crate::LOG_TARGET, chain = ?client.state_machine,
+			?nonces,
+			"resuming in-flight transactions",
+		);
+		self.in_flight
+			.lock()
+			.extend(journal.transactions.into_iter().map(|tx| (tx.nonce, tx)));
+		for nonce in nonces {
+			let manager = self.clone();
+			let client = client.clone();
+			tokio::spawn(async move {
+				let Ok(permit) = manager.permits.clone().acquire_owned().await else { return };
+				manager.track(client, nonce, permit, None).await
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Assign the next nonce to `tx`, price it and broadcast it. Transactions execute in the
+	/// order they are sent, so a message that depends on another must be sent after it.
+	///
+	/// Returns once the transaction is broadcast, it's then tracked in the background until it's
+	/// confirmed. Waits for a slot if [`TxManagerConfig::max_in_flight`] transactions are pending.
+	pub async fn send(
+		self: &Arc<Self>,
+		client: &EvmClient,
+		tx: TransactionRequest,
+	) -> anyhow::Result<PendingTx> {
+		let permit = self.permits.clone().acquire_owned().await?;
+		let fees = self.initial_fees(client, &tx).await?;
+
+		// Held until the broadcast completes, so nonces reach the mempool in order
+		let mut next_nonce = self.next_nonce.lock().await;
+		let nonce = match *next_nonce {
+			Some(nonce) => nonce,
+			None => {
+				let pending = client.client.get_transaction_count(self.from).pending().await?;
+				let tracked = self.in_flight.lock().last_key_value().map(|(nonce, _)| nonce + 1);
+				pending.max(tracked.unwrap_or_default())
+			},
+		};
+
+		let request = fees.apply(tx.from(self.from).nonce(nonce));
+		let tx_hash = match self.broadcast(client, request.clone()).await {
+			Ok(hash) => hash,
+			Err(err) => {
+				// Nothing was sent on this nonce. Fetch it again next time, in case the node
+				// did see the transaction.
+				*next_nonce = None;
+				return Err(err);
+			},
+		};
+		*next_nonce = Some(nonce + 1);
+		drop(next_nonce);
+
+		tracing::info!(
+			target: crate::LOG_TARGET, chain = ?client.state_machine,
+			nonce,
+			?tx_hash,
+			?fees,
+			"transaction sent",
+		);
+		self.in_flight.lock().insert(
+			nonce,
+			InFlightTx { nonce, request, fees, hashes: vec![tx_hash], last_sent: now(), bumps: 0 },
+		);
+		self.persist();
+
+		let (sender, receipt) = oneshot::channel();
+		let manager = self.clone();
+		let client = client.clone();
+		tokio::spawn(async move { manager.track(client, nonce, permit, Some(sender)).await });
+
+		Ok(PendingTx { nonce, tx_hash, receipt })
+	}
+
+	/// Wait for a sent transaction to be confirmed. Reverted transactions are returned like any
+	/// other, the caller checks the receipt's status. Giving up after
+	/// [`TxManagerConfig::receipt_timeout_secs`] doesn't stop the transaction from being tracked.
+	pub async fn wait(&self, pending: PendingTx) -> anyhow::Result<TransactionReceipt> {
+		let PendingTx { nonce, receipt, .. } = pending;
+		let timeout = Duration::from_secs(self.config.receipt_timeout_secs);
+		match tokio::time::timeout(timeout, receipt).await {
+			Ok(Ok(result)) => result,
+			Ok(Err(_)) => Err(anyhow!("Transaction with nonce {nonce} is no longer tracked")),
+			Err(_) => Err(anyhow!(
+				"Transaction with nonce {nonce} on chain {} was not confirmed within {}s",
+				self.chain_id,
+				self.config.receipt_timeout_secs
+			)),
+		}
+	}
+
+	/// Poll and re-price a sent transaction until it's confirmed, then hand its receipt to
+	/// `sender`. The permit is held until the transaction is no longer in flight.
+	async fn track(
+		self: Arc<Self>,
+		client: EvmClient,
+		nonce: u64,
+		_permit: OwnedSemaphorePermit,
+		sender: Option<oneshot::Sender<anyhow::Result<TransactionReceipt>>>,
+	) {
+		let mut consumed = 0;
+		loop {
+			tokio::time::sleep(self.poll_interval()).await;
+			match self.poll(&client, nonce).await {
+				Ok(Poll::Confirmed(receipt)) => {
+					tracing::info!(
+						target: crate::LOG_TARGET, chain = ?client.state_machine,
+						nonce,
+						tx_hash = ?receipt.transaction_hash,
+						success = receipt.inner.status(),
+						"transaction confirmed",
+					);
+					self.remove(nonce);
+					if let Some(sender) = sender {
+						let _ = sender.send(Ok(receipt));
+					}
+					return;
+				},
+				Ok(Poll::NonceConsumed) => {
+					consumed += 1;
+					if consumed >= NONCE_CONSUMED_POLLS {
+						tracing::warn!(
+							target: crate::LOG_TARGET, chain = ?client.state_machine,
+							nonce,
+							"nonce was used by another transaction, no longer tracking it",
+						);
+						self.remove(nonce);
+						if let Some(sender) = sender {
+							let _ = sender.send(Err(anyhow!(
+								"Nonce {nonce} was used by a transaction the relayer didn't send"
+							)));
+						}
+						return;
+					}
+				},
+				Ok(Poll::Pending) => consumed = 0,
+				Err(err) => tracing::warn!(
+					target: crate::LOG_TARGET, chain = ?client.state_machine,
+					nonce,
+					"failed to poll transaction: {err:?}",
+				),
+			}
+		}
+	}
+
+	/// Look for a confirmed receipt of any version of the transaction with `nonce`, and re-price
+	/// it if it's due.
+	async fn poll(&self, client: &EvmClient, nonce: u64) -> anyhow::Result<Poll> {
+		let tx = self
+			.in_flight
+			.lock()
+			.get(&nonce)
+			.cloned()
+			.ok_or_else(|| anyhow!("Nonce {nonce} is not in flight"))?;
+
+		for hash in tx.hashes.iter().rev() {
+			let Some(receipt) = client.client.get_transaction_receipt(*hash).await? else {
+				continue;
+			};
+			let Some(block) = receipt.block_number else { continue };
+			let latest = client.client.get_block_number().await?;
+			let canonical =
+				client.client.get_block_by_number(block.into()).await?.map(|b| b.header.hash);
+			match receipt_status(
+				block,
+				receipt.block_hash,
+				canonical,
+				latest,
+				self.config.confirmations,
+			) {
+				ReceiptStatus::Confirmed => return Ok(Poll::Confirmed(receipt)),
+				// Don't re-price a transaction that's already in a block
+				ReceiptStatus::Included => return Ok(Poll::Pending),
+				ReceiptStatus::Reorged => tracing::warn!(
+					target: crate::LOG_TARGET, chain = ?client.state_machine,
+					nonce,
+					?hash,
+					block,
+					"transaction was reorged out, waiting for it to be included again",
+				),
+			}
+		}
+
+		let mined = client.client.get_transaction_count(self.from).latest().await?;
+		if mined > nonce {
+			return Ok(Poll::NonceConsumed);
+		}
+
+		if now().saturating_sub(tx.last_sent) >= self.config.resubmit_after_secs {
+			self.reprice(client, tx).await?;
+		}
+
+		Ok(Poll::Pending)
+	}
+
+	/// Broadcast a replacement of `tx` with higher fees. Once the fee cap is reached the latest
+	/// version is broadcast again instead, in case it was dropped from the mempool.
+	async fn reprice(&self, client: &EvmClient, tx: InFlightTx) -> anyhow::Result<()> {
+		let market = self.market_fees(client, &tx.fees).await.ok();
+		let bumped =
+			tx.fees.bump(self.config.fee_bump_percent, market, self.config.max_fee_per_gas);
+		let (fees, request) = match bumped {
+			Some(fees) => (fees, fees.apply(tx.request.clone())),
+			None => (tx.fees, tx.request.clone()),
+		};
+
+		let result = self.broadcast(client, request.clone()).await;
+		let mut in_flight = self.in_flight.lock();
+		let Some(entry) = in_flight.get_mut(&tx.nonce) else { return Ok(()) };
+		entry.last_sent = now();
+		match result {
+			Ok(hash) if bumped.is_some() => {
+				tracing::info!(
+					target: crate::LOG_TARGET, chain = ?client.state_machine,
+					nonce = tx.nonce,
+					?hash,
+					?fees,
+					bumps = entry.bumps + 1,
+					"re-priced stuck transaction",
+				);
+				entry.request = request;
+				entry.fees = fees;
+				entry.bumps += 1;
+				if !entry.hashes.contains(&hash) {
+					entry.hashes.push(hash);
+				}
+			},
+			Ok(_) => tracing::warn!(
+				target: crate::LOG_TARGET, chain = ?client.state_machine,
+				nonce = tx.nonce,
+				?fees,
+				"transaction is stuck at the fee cap, rebroadcasting it",
+			),
+			// The replacement may be refused, e.g because the market moved. Try again next round.
+			Err(err) => tracing::warn!(
+				target: crate::LOG_TARGET, chain = ?client.state_machine,
+				nonce = tx.nonce,
+				"failed to re-price transaction: {err:?}",
+			),
+		}
+		drop(in_flight);
+		self.persist();
+
+		Ok(())
+	}
+
+	/// Send a fully priced transaction, retrying while the rpc is rate limiting
+	async fn broadcast(
+		&self,
+		client: &EvmClient,
+		request: TransactionRequest,
+	) -> anyhow::Result<B256> {
+		let mut attempt = 0u32;
+		loop {
+			match client.signer.send_transaction(request.clone()).await {
+				Ok(pending) => return Ok(*pending.tx_hash()),
+				Err(err) => {
+					let err = anyhow::Error::from(err);
+					if !is_rate_limit_error(&err) || attempt >= MAX_RATE_LIMIT_RETRIES {
+						return Err(err);
+					}
+					attempt += 1;
+					tracing::info!(
+						target: crate::LOG_TARGET, chain = ?client.state_machine,
+						attempt,
+						max = MAX_RATE_LIMIT_RETRIES,
+						"rate limited, retrying broadcast in 1s",
+					);
+					tokio::time::sleep(Duration::from_secs(1)).await;
+				},
+			}
+		}
+	}
+
+	/// Fees for a new transaction. A gas price already set on `tx`, e.g by the gas oracle, is
+	/// used as a floor.
+	async fn initial_fees(
+		&self,
+		client: &EvmClient,
+		tx: &TransactionRequest,
+	) -> anyhow::Result<Fees> {
+		let floor = tx.gas_price.unwrap_or_default();
+		let fees = if self.eip1559(client).await? {
+			let estimate = client.client.estimate_eip1559_fees().await?;
+			Fees::Eip1559 {
+				max_fee_per_gas: estimate.max_fee_per_gas.max(floor),
+				max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+			}
+		} else if floor != 0 {
+			Fees::Legacy { gas_price: floor }
+		} else {
+			Fees::Legacy { gas_price: client.client.get_gas_price().await? }
+		};
+
+		match self.config.max_fee_per_gas {
+			Some(cap) if fees.max_fee_per_gas() > cap => Err(anyhow!(
+				"Current fees of {} wei per gas exceed the configured cap of {cap}",
+				fees.max_fee_per_gas()
+			)),
+			_ => Ok(fees),
+		}
+	}
+
+	/// Current market fees of the same kind as `fees`
+	async fn market_fees(&self, client: &EvmClient, fees: &Fees) -> anyhow::Result<Fees> {
+		Ok(match fees {
+			Fees::Legacy { .. } => Fees::Legacy { gas_price: client.client.get_gas_price().await? },
+			Fees::Eip1559 { .. } => {
+				let estimate = client.client.estimate_eip1559_fees().await?;
+				Fees::Eip1559 {
+					max_fee_per_gas: estimate.max_fee_per_gas,
+					max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+				}
+			},
+		})
+	}
+
+	async fn eip1559(&self, client: &EvmClient) -> anyhow::Result<bool> {
+		if let Some(eip1559) = self.config.eip1559 {
+			return Ok(eip1559);
+		}
+		self.eip1559
+			.get_or_try_init(|| async {
+				let block = client
+					.client
+					.get_block_by_number(Default::default())
+					.await?
+					.ok_or_else(|| anyhow!("Latest block not found"))?;
+				Ok(block.header.base_fee_per_gas.is_some())
+			})
+			.await
+			.copied()
+	}
+
+	fn poll_interval(&self) -> Duration {
+		Duration::from_secs(self.config.poll_interval_secs.max(1))
+	}
+
+	fn remove(&self, nonce: u64) {
+		self.in_flight.lock().remove(&nonce);
+		self.persist();
+	}
+
+	/// Write the in-flight transactions to the journal
+	fn persist(&self) {
+		let Some(path) = &self.config.journal else { return };
+		let journal = Journal {
+			chain_id: self.chain_id,
+			from: self.from,
+			transactions: self.in_flight.lock().values().cloned().collect(),
+		};
+		if let Err(err) = write_journal(path, &journal) {
+			tracing::error!(target: crate::LOG_TARGET, "failed to persist transactions to {path:?}: {err:?}");
+		}
+	}
+}
+
+fn load_journal(path: &Path) -> anyhow::Result<Journal> {
+	match std::fs::read(path) {
+		Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Journal::default()),
+		Err(err) => Err(err.into()),
+	}
+}
+
+/// Replace the journal atomically, so a crash mid-write never leaves it truncated
+fn write_journal(path: &Path, journal: &Journal) -> anyhow::Result<()> {
+	let tmp = path.with_extension("tmp");
+	std::fs::write(&tmp, serde_json::to_vec(journal)?)?;
+	std::fs::rename(tmp, path)?;
+	Ok(())
+}
+
+fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GWEI: u128 = 1_000_000_000;
+
+	#[test]
+	fn bumps_fees_geometrically_up_to_the_cap() {
+		let mut fees = Fees::Eip1559 { max_fee_per_gas: 10 * GWEI, max_priority_fee_per_gas: GWEI };
+		let mut history = vec![];
+		while let Some(bumped) = fees.bump(20, None, Some(20 * GWEI)) {
+			history.push(bumped.max_fee_per_gas());
+			fees = bumped;
+		}
+		assert_eq!(history, vec![12 * GWEI, 144 * GWEI / 10, 1728 * GWEI / 100, 20 * GWEI]);
+
+		// Every replacement raises both fees by at least the minimum nodes accept
+		let fees = Fees::Eip1559 { max_fee_per_gas: 10 * GWEI, max_priority_fee_per_gas: GWEI };
+		let Some(Fees::Eip1559 { max_priority_fee_per_gas, .. }) = fees.bump(1, None, None) else {
+			panic!("bump should succeed")
+		};
+		assert_eq!(max_priority_fee_per_gas, 11 * GWEI / 10);
+	}
+
+	#[test]
+	fn follows_the_market_when_it_moves_faster() {
+		let fees = Fees::Legacy { gas_price: 10 * GWEI };
+		let market = Fees::Legacy { gas_price: 30 * GWEI };
+		assert_eq!(fees.bump(20, Some(market), None), Some(market));
+		assert_eq!(
+			fees.bump(20, Some(market), Some(25 * GWEI)),
+			Some(Fees::Legacy { gas_price: 25 * GWEI })
+		);
+		assert_eq!(fees.bump(20, Some(market), Some(105 * GWEI / 10)), None);
+	}
+
+	#[test]
+	fn applies_fees_to_requests() {
+		let tx = TransactionRequest::default().gas_price(5);
+		let tx = Fees::Eip1559 { max_fee_per_gas: 10, max_priority_fee_per_gas: 2 }.apply(tx);
+		assert_eq!(
+			(tx.gas_price, tx.max_fee_per_gas, tx.max_priority_fee_per_gas, tx.transaction_type),
+			(None, Some(10), Some(2), Some(2))
+		);
+		let tx = Fees::Legacy { gas_price: 7 }.apply(tx);
+		assert_eq!(
+			(tx.gas_price, tx.max_fee_per_gas, tx.max_priority_fee_per_gas, tx.transaction_type),
+			(Some(7), None, None, Some(0))
+		);
+	}
+
+	#[test]
+	fn classifies_receipts() {
+		let hash = B256::repeat_byte(1);
+		let other = B256::repeat_byte(2);
+		assert_eq!(receipt_status(100, Some(hash), Some(hash), 100, 1), ReceiptStatus::Confirmed);
+		assert_eq!(receipt_status(100, Some(hash), Some(hash), 101, 3), ReceiptStatus::Included);
+		assert_eq!(receipt_status(100, Some(hash), Some(hash), 102, 3), ReceiptStatus::Confirmed);
+		assert_eq!(receipt_status(100, Some(hash), Some(other), 110, 3), ReceiptStatus::Reorged);
+		assert_eq!(receipt_status(100, Some(hash), None, 110, 3), ReceiptStatus::Reorged);
+	}
+
+	#[test]
+	fn journal_round_trips() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("txs.json");
+		assert!(load_journal(&path).unwrap().transactions.is_empty());
+
+		let fees = Fees::Eip1559 { max_fee_per_gas: 10 * GWEI, max_priority_fee_per_gas: GWEI };
+		let request = fees.apply(TransactionRequest::default().nonce(7).gas_limit(100_000));
+		let journal = Journal {
+			chain_id: 1,
+			from: Address::repeat_byte(3),
+			transactions: vec![InFlightTx {
+				nonce: 7,
+				request: request.clone(),
+				fees,
+				hashes: vec![B256::repeat_byte(4)],
+				last_sent: 1_700_000_000,
+				bumps: 2,
+			}],
+		};
+		write_journal(&path, &journal).unwrap();
+
+		let loaded = load_journal(&path).unwrap();
+		assert_eq!((loaded.chain_id, loaded.from), (1, Address::repeat_byte(3)));
+		let tx = &loaded.transactions[0];
+		assert_eq!((tx.nonce, tx.fees, tx.bumps), (7, fees, 2));
+		assert_eq!(tx.request, request);
+		assert_eq!(tx.hashes, vec![B256::repeat_byte(4)]);
+	}
+
+	#[test]
+	fn clients_with_the_same_signer_share_a_manager() {
+		let from = Address::repeat_byte(5);
+		let manager = TxManager::shared(Default::default(), 1, from);
+		assert!(Arc::ptr_eq(&manager, &TxManager::shared(Default::default(), 1, from)));
+
+		let other_chain = TxManager::shared(Default::default(), 2, from);
+		assert!(!Arc::ptr_eq(&manager, &other_chain));
+		let other_signer = TxManager::shared(Default::default(), 1, Address::repeat_byte(6));
+		assert!(!Arc::ptr_eq(&manager, &other_signer));
+	}
+
+	#[tokio::test]
+	async fn waiting_hands_back_the_tracked_outcome() {
+		let manager = TxManager::new(Default::default(), 1, Address::repeat_byte(7));
+		let (sender, receipt) = oneshot::channel();
+		sender.send(Err(anyhow!("nonce consumed"))).unwrap();
+		let pending = PendingTx { nonce: 3, tx_hash: B256::ZERO, receipt };
+		let err = manager.wait(pending).await.unwrap_err();
+		assert_eq!(err.to_string(), "nonce consumed");
+
+		// Nobody to report to, e.g the tracker was dropped
+		let (_, receipt) = oneshot::channel();
+		let pending = PendingTx { nonce: 4, tx_hash: B256::ZERO, receipt };
+		assert!(manager
+			.wait(pending)
+			.await
+			.unwrap_err()
+			.to_string()
+			.contains("no longer tracked"));
+	}
+
+	#[tokio::test]
+	async fn waiting_times_out_without_holding_a_permit() {
+		let config =
+			TxManagerConfig { receipt_timeout_secs: 0, max_in_flight: 1, ..Default::default() };
+		let manager = TxManager::new(config, 1, Address::repeat_byte(8));
+		let (_sender, receipt) = oneshot::channel();
+		let pending = PendingTx { nonce: 5, tx_hash: B256::ZERO, receipt };
+		let err = manager.wait(pending).await.unwrap_err();
+		assert!(err.to_string().contains("was not confirmed within 0s"));
+		// The caller never holds a permit, so a waiting caller can't starve the next send
+		assert_eq!(manager.permits.available_permits(), 1);
+	}
+}
@@ -299,6 +299,31 @@ impl TransactionPayment {
 		Ok(())
 	}
 
+	/// Returns the subset of `commitments` with a delivery recorded in the db. Entries are
+	/// deleted once their fees are claimed, so a missing entry does not mean the request was not
+	/// delivered.
+	pub async fn delivered_commitments(
+		&self,
+		commitments: &[H256],
+	) -> anyhow::Result<BTreeSet<H256>> {
+		if commitments.is_empty() {
+			return Ok(Default::default());
+		}
+
+		let hashes = commitments.iter().map(|hash| hex::encode(hash.as_bytes())).collect();
+		let entries = self
+			.db
+			.deliveries()
+			.find_many(vec![WhereParam::Hash(StringFilter::InVec(hashes))])
+			.exec()
+			.await?;
+
+		entries
+			.into_iter()
+			.map(|data| Ok(H256::from_slice(&hex::decode(&data.hash)?)))
+			.collect()
+	}
+
 	/// Delete the requests with the provided hashes from the database
 	pub async fn delete_entries(&self, reqs: Vec<Vec<u8>>) -> anyhow::Result<()> {
 		let actions = reqs
@@ -392,7 +417,7 @@ impl TransactionPayment {
 			let beneficiary_details = if cross_chain_type {
 				let beneficiary = source.address();
 				let prehash = beneficiary_message(nonce, source_chain, &beneficiary);
-				let details = Some((beneficiary, dest.sign(&prehash)));
+				let details = Some((beneficiary, dest.sign(&prehash).await?));
 				nonce += 1;
 				details
 			} else {
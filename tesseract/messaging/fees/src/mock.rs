@@ -95,6 +95,13 @@ impl IsmpProvider for MockHost {
 		unimplemented!()
 	}
 
+	async fn query_consensus_expiry(
+		&self,
+		_id: ConsensusStateId,
+	) -> Result<Duration, anyhow::Error> {
+		unimplemented!()
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, anyhow::Error> {
 		unimplemented!()
 	}
@@ -177,7 +184,7 @@ impl IsmpProvider for MockHost {
 		Default::default()
 	}
 
-	fn sign(&self, msg: &[u8]) -> Signature {
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error> {
 		todo!()
 	}
 
@@ -187,7 +194,6 @@ impl IsmpProvider for MockHost {
 	) -> Result<(), Error> {
 		todo!()
 	}
-
 }
 
 #[async_trait::async_trait]
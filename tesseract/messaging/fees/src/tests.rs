@@ -256,6 +256,46 @@ async fn highest_delivery_height() {
 	assert_eq!(height, 499);
 }
 
+#[tokio::test]
+async fn delivered_commitments() {
+	let path = temp_db_path("delivered_commitments");
+	let tx_payment = TransactionPayment::initialize(&path).await.unwrap();
+	let commitments = (0..4)
+		.map(|nonce| {
+			hash_request::<Hasher>(&Request::Post(PostRequest {
+				source: StateMachine::Evm(97),
+				dest: StateMachine::Evm(8002),
+				nonce,
+				from: vec![],
+				to: vec![],
+				timeout_timestamp: 0,
+				body: vec![],
+			}))
+		})
+		.collect::<Vec<_>>();
+	let receipts = commitments[..2]
+		.iter()
+		.enumerate()
+		.map(|(nonce, commitment)| TxReceipt {
+			query: Query {
+				source_chain: StateMachine::Evm(97),
+				dest_chain: StateMachine::Evm(8002),
+				nonce: nonce as u64,
+				commitment: *commitment,
+			},
+			height: 0,
+		})
+		.collect();
+
+	tx_payment.store_messages(receipts).await.unwrap();
+
+	let delivered = tx_payment.delivered_commitments(&commitments).await.unwrap();
+	assert_eq!(delivered, commitments[..2].iter().copied().collect());
+	assert!(tx_payment.delivered_commitments(&[]).await.unwrap().is_empty());
+
+	cleanup_db(&path);
+}
+
 // ─── Outbound consensus delivery claim persistence ──────────────────
 
 /// `list_pending_rotation_claims` returns every row in creation order.
@@ -133,6 +133,8 @@ async fn create_clients(
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 
 	let chain_b_config = SubstrateConfig {
@@ -148,6 +150,8 @@ async fn create_clients(
 		max_concurent_queries: None,
 		poll_interval: None,
 		fee_token_decimals: None,
+		additional_rpc_ws: vec![],
+		rpc_health: Default::default(),
 	};
 
 	// setup state machines
@@ -0,0 +1,450 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Historical backfill and gap repair.
+//!
+//! The inbound loop only moves forward, so requests emitted in a height range it skipped (RPC
+//! errors while querying events, a crash before a restart from a later height) are never
+//! delivered. [`backfill`] rescans a height range on the source chain and re-queues every request
+//! that has no receipt on the destination and has not timed out. [`detect_gaps`] runs it
+//! periodically over the heights the inbound loop has already moved past.
+
+use crate::{deliver_events, events::filter_events, LOG_TARGET};
+use futures::{stream::FuturesOrdered, StreamExt};
+use ismp::{
+	consensus::StateMachineHeight, events::Event, host::StateMachine, messaging::hash_request,
+	router::Request,
+};
+use primitive_types::H256;
+use std::{collections::HashMap, ops::RangeInclusive, sync::Arc, time::Duration};
+use tesseract_primitives::{config::RelayerConfig, Hasher, IsmpProvider, StateMachineUpdated};
+use transaction_fees::TransactionPayment;
+
+/// Number of source heights whose events are queried at once.
+pub(crate) const SCAN_CHUNK_SIZE: u64 = 10_000;
+
+/// Number of source heights behind the latest height that the gap detector rescans on its first
+/// pass, when `gap_scan_lookback` is not configured.
+pub const DEFAULT_GAP_SCAN_LOOKBACK: u64 = 10_000;
+
+/// Outcome of a backfill pass.
+#[derive(Debug, Clone, Default)]
+pub struct BackfillReport {
+	/// The source heights that were scanned, `None` if there was nothing to scan
+	pub scanned: Option<RangeInclusive<u64>>,
+	/// Requests to the destination found in the scanned range
+	pub found: usize,
+	/// Requests that were neither delivered nor timed out
+	pub undelivered: usize,
+	/// Requests delivered by this pass, requests that are unprofitable are stored for retries
+	/// instead
+	pub delivered: usize,
+}
+
+/// Scan `range` on `source` for requests to `dest` that have no receipt on `dest` and have not
+/// timed out. Returns the undelivered request events along with the number of requests found.
+pub async fn find_undelivered(
+	source: &Arc<dyn IsmpProvider>,
+	dest: &Arc<dyn IsmpProvider>,
+	tx_payment: &TransactionPayment,
+	config: &RelayerConfig,
+	coprocessor: StateMachine,
+	range: RangeInclusive<u64>,
+) -> Result<(Vec<Event>, usize), anyhow::Error> {
+	let dest_state_machine = dest.state_machine_id().state_id;
+	let mut events = vec![];
+	let mut start = *range.start();
+	while start <= *range.end() {
+		let end = start.saturating_add(SCAN_CHUNK_SIZE - 1).min(*range.end());
+		let update =
+			StateMachineUpdated { state_machine_id: source.state_machine_id(), latest_height: end };
+		let batch = source.query_ismp_events(start.saturating_sub(1), update).await?;
+		events.extend(
+			batch
+				.into_iter()
+				.filter(|ev| filter_events(config, dest_state_machine, coprocessor, ev)),
+		);
+		tracing::trace!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), "Scanned {start}..={end} for undelivered requests");
+		start = end + 1;
+	}
+
+	let found = events.len();
+	let commitments = events.iter().map(request_commitment).collect::<Vec<_>>();
+	// Deliveries recorded in the fee db are known to have landed, skip their receipt queries
+	let recorded = tx_payment.delivered_commitments(&commitments).await?;
+	let timestamp = dest.query_timestamp().await?;
+	let candidates = events
+		.into_iter()
+		.zip(commitments)
+		.filter(|(ev, commitment)| {
+			if recorded.contains(commitment) {
+				return false;
+			}
+			match ev {
+				Event::PostRequest(post) => !Request::Post(post.clone()).timed_out(timestamp),
+				// GetRequest timeouts are enforced on Hyperbridge when the response is produced
+				_ => true,
+			}
+		})
+		.collect::<Vec<_>>();
+
+	let mut undelivered = vec![];
+	for chunk in candidates.chunks(dest.max_concurrent_queries()) {
+		let receipts = chunk
+			.iter()
+			.map(|(ev, commitment)| {
+				let dest = dest.clone();
+				let commitment = *commitment;
+				let is_response = matches!(ev, Event::GetResponse(_));
+				async move {
+					if is_response {
+						dest.query_response_receipt(commitment).await
+					} else {
+						dest.query_request_receipt(commitment).await
+					}
+				}
+			})
+			.collect::<FuturesOrdered<_>>()
+			.collect::<Vec<_>>()
+			.await
+			.into_iter()
+			.collect::<Result<Vec<_>, _>>()?;
+
+		undelivered.extend(
+			chunk
+				.iter()
+				.zip(receipts)
+				.filter(|(_, relayer)| relayer.iter().all(|byte| *byte == 0))
+				.map(|((ev, _), _)| ev.clone()),
+		);
+	}
+
+	Ok((undelivered, found))
+}
+
+/// Re-queue every request in `range` on `source` that has not reached `dest`. Requests are proven
+/// at the latest height of `source` known to `dest`, so the range is capped at that height.
+/// Delivered requests are recorded in the fee db so their fees can be claimed, unprofitable ones
+/// are stored for retries when `unprofitable_retry_frequency` is set.
+pub async fn backfill(
+	source: Arc<dyn IsmpProvider>,
+	dest: Arc<dyn IsmpProvider>,
+	tx_payment: Arc<TransactionPayment>,
+	config: RelayerConfig,
+	coprocessor: StateMachine,
+	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	range: RangeInclusive<u64>,
+) -> Result<BackfillReport, anyhow::Error> {
+	let latest = dest.query_latest_height(source.state_machine_id()).await? as u64;
+	let range = *range.start()..=(*range.end()).min(latest);
+	if range.is_empty() {
+		tracing::info!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), "Nothing to backfill, latest height of source on dest is {latest}");
+		return Ok(Default::default());
+	}
+
+	let (events, found) =
+		find_undelivered(&source, &dest, &tx_payment, &config, coprocessor, range.clone()).await?;
+	let mut report =
+		BackfillReport { scanned: Some(range), found, undelivered: events.len(), delivered: 0 };
+	if events.is_empty() {
+		return Ok(report);
+	}
+
+	tracing::info!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), "Backfilling {} undelivered requests", events.len());
+	let state_machine_height = StateMachineHeight { id: source.state_machine_id(), height: latest };
+	let delivered = deliver_events(
+		dest.clone(),
+		source.clone(),
+		tx_payment.clone(),
+		events,
+		state_machine_height,
+		config,
+		coprocessor,
+		client_map,
+		None,
+	)
+	.await?;
+
+	report.delivered = delivered.len();
+	// Deliveries to hyperbridge and of requests from hyperbridge don't earn fees on the destination
+	let claimable = delivered
+		.into_iter()
+		.filter(|receipt| {
+			dest.state_machine_id().state_id != coprocessor && receipt.source() != coprocessor
+		})
+		.collect::<Vec<_>>();
+	if !claimable.is_empty() {
+		if let Err(err) = tx_payment.store_messages(claimable).await {
+			tracing::error!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), ?err, "Failed to persist backfilled deliveries to database");
+		}
+	}
+
+	Ok(report)
+}
+
+/// Periodically backfill the source heights the inbound loop has moved past. Each pass scans up
+/// to the latest height of `source` seen on `dest` at the previous pass, which leaves the live
+/// loop a full period to deliver recent requests before they're considered missing. The first
+/// pass reaches `lookback` heights back to repair gaps left before a restart.
+pub async fn detect_gaps(
+	source: Arc<dyn IsmpProvider>,
+	dest: Arc<dyn IsmpProvider>,
+	tx_payment: Arc<TransactionPayment>,
+	config: RelayerConfig,
+	coprocessor: StateMachine,
+	client_map: HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	frequency: Duration,
+	lookback: u64,
+) -> Result<(), anyhow::Error> {
+	let mut interval = tokio::time::interval(frequency);
+	let mut scanned: Option<u64> = None;
+	let mut settled: Option<u64> = None;
+	loop {
+		interval.tick().await;
+		let latest = match dest.query_latest_height(source.state_machine_id()).await {
+			Ok(height) => height as u64,
+			Err(err) => {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), ?err, "Gap detector failed to query latest height");
+				continue;
+			},
+		};
+
+		if let Some(range) = settled.and_then(|settled| gap_scan_range(scanned, settled, lookback))
+		{
+			match backfill(
+				source.clone(),
+				dest.clone(),
+				tx_payment.clone(),
+				config.clone(),
+				coprocessor,
+				&client_map,
+				range.clone(),
+			)
+			.await
+			{
+				Ok(report) => {
+					scanned = Some(*range.end());
+					if report.undelivered > 0 {
+						tracing::warn!(
+							target: LOG_TARGET,
+							source = %source.name(),
+							dest = %dest.name(),
+							"Gap detector found {} undelivered requests in {range:?}, delivered {}",
+							report.undelivered,
+							report.delivered,
+						);
+					}
+				},
+				Err(err) => {
+					tracing::error!(target: LOG_TARGET, source = %source.name(), dest = %dest.name(), ?err, "Gap detector failed to backfill {range:?}");
+				},
+			}
+		}
+
+		settled = Some(latest);
+	}
+}
+
+/// The source heights a gap detector pass scans: every height after the last scanned one up to
+/// `settled`, reaching at most `lookback` heights back. `None` when there is nothing new to scan.
+fn gap_scan_range(
+	scanned: Option<u64>,
+	settled: u64,
+	lookback: u64,
+) -> Option<RangeInclusive<u64>> {
+	let from = scanned
+		.map(|height| height + 1)
+		.unwrap_or(0)
+		.max(settled.saturating_sub(lookback));
+	(from <= settled).then(|| from..=settled)
+}
+
+fn request_commitment(event: &Event) -> H256 {
+	match event {
+		Event::PostRequest(post) => hash_request::<Hasher>(&Request::Post(post.clone())),
+		// Response receipts are keyed by the commitment of the request they respond to
+		Event::GetResponse(res) => hash_request::<Hasher>(&Request::Get(res.get.clone())),
+		event => unreachable!("Only application messages are filtered; qed. Unexpected: {event:?}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ismp::router::PostRequest;
+	use tesseract_primitives::{mocks::MockHost, Query, TxReceipt};
+
+	const HB: StateMachine = StateMachine::Kusama(4009);
+	const DEST: StateMachine = StateMachine::Evm(1);
+
+	fn post(dest: StateMachine, nonce: u64, timeout_timestamp: u64) -> PostRequest {
+		PostRequest {
+			source: HB,
+			dest,
+			nonce,
+			from: vec![1],
+			to: vec![2],
+			timeout_timestamp,
+			body: vec![],
+		}
+	}
+
+	fn commitment(post: &PostRequest) -> H256 {
+		hash_request::<Hasher>(&Request::Post(post.clone()))
+	}
+
+	async fn tx_payment(test_name: &str) -> (TransactionPayment, String) {
+		let nanos = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|d| d.as_nanos())
+			.unwrap_or(0);
+		let path =
+			format!("/tmp/tesseract-backfill-test-{test_name}-{}-{nanos}.db", std::process::id());
+		(TransactionPayment::initialize(&path).await.unwrap(), path)
+	}
+
+	fn cleanup_db(path: &str) {
+		let _ = std::fs::remove_file(path);
+		let _ = std::fs::remove_file(format!("{path}-journal"));
+	}
+
+	#[test]
+	fn gap_scan_range_follows_the_settled_height() {
+		// the first pass reaches `lookback` heights back
+		assert_eq!(gap_scan_range(None, 1_000, 100), Some(900..=1_000));
+		assert_eq!(gap_scan_range(None, 50, 100), Some(0..=50));
+		// later passes pick up after the last scanned height
+		assert_eq!(gap_scan_range(Some(1_000), 1_200, 100), Some(1_001..=1_200));
+		// nothing new since the last pass
+		assert_eq!(gap_scan_range(Some(1_200), 1_200, 100), None);
+		// after a long stall only the last `lookback` heights are scanned
+		assert_eq!(gap_scan_range(Some(1_000), 5_000, 100), Some(4_900..=5_000));
+	}
+
+	#[tokio::test]
+	async fn find_undelivered_skips_delivered_and_timed_out_requests() {
+		let undelivered = post(DEST, 0, 0);
+		let received = post(DEST, 1, 0);
+		let recorded = post(DEST, 2, 0);
+		let timed_out = post(DEST, 3, 500);
+		let elsewhere = post(StateMachine::Evm(56), 4, 0);
+		let late = post(DEST, 5, 2_000);
+
+		let source: Arc<dyn IsmpProvider> = Arc::new(
+			MockHost::new((), 0, HB)
+				.with_event(5, Event::PostRequest(undelivered.clone()))
+				.with_event(6, Event::PostRequest(received.clone()))
+				.with_event(7, Event::PostRequest(recorded.clone()))
+				.with_event(8, Event::PostRequest(timed_out.clone()))
+				.with_event(9, Event::PostRequest(elsewhere))
+				.with_event(12_000, Event::PostRequest(late.clone()))
+				// outside the scanned range
+				.with_event(20_000, Event::PostRequest(post(DEST, 6, 0))),
+		);
+		let dest: Arc<dyn IsmpProvider> = Arc::new(
+			MockHost::new((), 0, DEST)
+				.with_timestamp(Duration::from_secs(1_000))
+				.with_receipt(commitment(&received), vec![0xab; 32])
+				.with_receipt(commitment(&late), vec![0u8; 32]),
+		);
+		let (tx_payment, path) = tx_payment("find_undelivered").await;
+		tx_payment
+			.store_messages(vec![TxReceipt {
+				query: Query {
+					source_chain: HB,
+					dest_chain: DEST,
+					nonce: recorded.nonce,
+					commitment: commitment(&recorded),
+				},
+				height: 7,
+			}])
+			.await
+			.unwrap();
+
+		let (events, found) = find_undelivered(
+			&source,
+			&dest,
+			&tx_payment,
+			&RelayerConfig::default(),
+			HB,
+			1..=15_000,
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(found, 5);
+		assert_eq!(
+			events.iter().map(request_commitment).collect::<Vec<_>>(),
+			vec![commitment(&undelivered), commitment(&late)]
+		);
+		cleanup_db(&path);
+	}
+
+	#[tokio::test]
+	async fn find_undelivered_queries_the_range_in_chunks() {
+		let source = MockHost::<()>::new((), 0, HB);
+		let queries = source.event_queries.clone();
+		let source: Arc<dyn IsmpProvider> = Arc::new(source);
+		let dest: Arc<dyn IsmpProvider> = Arc::new(MockHost::new((), 0, DEST));
+		let (tx_payment, path) = tx_payment("find_undelivered_chunks").await;
+
+		find_undelivered(&source, &dest, &tx_payment, &RelayerConfig::default(), HB, 1..=25_000)
+			.await
+			.unwrap();
+
+		assert_eq!(*queries.lock().unwrap(), vec![(0, 10_000), (10_000, 20_000), (20_000, 25_000)]);
+		cleanup_db(&path);
+	}
+
+	#[tokio::test]
+	async fn backfill_is_capped_at_the_latest_height_known_to_dest() {
+		let source = MockHost::<()>::new((), 0, HB);
+		let queries = source.event_queries.clone();
+		let source: Arc<dyn IsmpProvider> = Arc::new(source);
+		let dest: Arc<dyn IsmpProvider> = Arc::new(MockHost::new((), 50, DEST));
+		let (tx_payment, path) = tx_payment("backfill_capped").await;
+		let tx_payment = Arc::new(tx_payment);
+
+		let report = backfill(
+			source.clone(),
+			dest.clone(),
+			tx_payment.clone(),
+			RelayerConfig::default(),
+			HB,
+			&HashMap::new(),
+			10..=200,
+		)
+		.await
+		.unwrap();
+		assert_eq!(report.scanned, Some(10..=50));
+		assert_eq!(*queries.lock().unwrap(), vec![(9, 50)]);
+
+		// nothing to scan past the latest height
+		let report = backfill(
+			source,
+			dest,
+			tx_payment,
+			RelayerConfig::default(),
+			HB,
+			&HashMap::new(),
+			100..=200,
+		)
+		.await
+		.unwrap();
+		assert_eq!(report.scanned, None);
+		assert_eq!(queries.lock().unwrap().len(), 1);
+		cleanup_db(&path);
+	}
+}
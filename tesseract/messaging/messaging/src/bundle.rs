@@ -0,0 +1,412 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Offline message proof bundles.
+//!
+//! A [`ProofBundle`] holds everything needed to deliver a single request to its destination: the
+//! message with its membership proof, and the state commitment of the source it is proven against.
+//! Bundles are exported with [`export_bundle`] and delivered later with [`replay_bundle`], so a
+//! failed delivery can be reproduced without access to the source chain, against a local fork of
+//! the destination or by a different relayer.
+
+use crate::{backfill::SCAN_CHUNK_SIZE, LOG_TARGET};
+use anyhow::{anyhow, Context};
+use codec::{Decode, Encode};
+use ismp::{
+	consensus::{ConsensusStateId, StateCommitment, StateMachineHeight, StateMachineId},
+	events::Event,
+	host::StateMachine,
+	messaging::{hash_request, hash_response, Message, Proof, RequestMessage, ResponseMessage},
+	router::Request,
+};
+use primitive_types::H256;
+use serde::{Deserialize, Serialize};
+use std::{ops::RangeInclusive, sync::Arc};
+use tesseract_primitives::{Cost, Hasher, IsmpProvider, Query, StateMachineUpdated};
+
+/// Version of the bundle format written by [`export_bundle`].
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A self-contained proof of a single request, ready to be delivered to its destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundle {
+	/// Version of the bundle format
+	pub version: u32,
+	/// Commitment of the request. For get responses this is the commitment of the get request.
+	pub commitment: H256,
+	/// The chain the request was emitted on
+	#[serde(with = "serde_hex_utils::as_string")]
+	pub source: StateMachine,
+	/// The chain the message is delivered to
+	#[serde(with = "serde_hex_utils::as_string")]
+	pub dest: StateMachine,
+	/// Consensus client of the source on the destination
+	#[serde(with = "serde_hex_utils::as_utf8_string")]
+	pub consensus_state_id: ConsensusStateId,
+	/// Height of the source the request is proven at
+	pub proof_height: u64,
+	/// State commitment of the source at `proof_height`, as stored on the destination
+	pub state_commitment: StateCommitment,
+	/// Timestamp of the destination when the bundle was exported, in seconds
+	pub dest_timestamp: u64,
+	/// The scale encoded [`Message`]
+	#[serde(with = "serde_hex_utils::as_hex")]
+	pub message: Vec<u8>,
+}
+
+impl ProofBundle {
+	/// The height of the source the message is proven at.
+	pub fn height(&self) -> StateMachineHeight {
+		StateMachineHeight {
+			id: StateMachineId {
+				state_id: self.source,
+				consensus_state_id: self.consensus_state_id,
+			},
+			height: self.proof_height,
+		}
+	}
+
+	/// Decode the bundled message, replacing its signer with `signer`.
+	pub fn message(&self, signer: Vec<u8>) -> Result<Message, anyhow::Error> {
+		let message = Message::decode(&mut &*self.message)
+			.map_err(|err| anyhow!("Failed to decode the bundled message: {err:?}"))?;
+		match message {
+			Message::Request(msg) => Ok(Message::Request(RequestMessage { signer, ..msg })),
+			Message::Response(msg) => Ok(Message::Response(ResponseMessage { signer, ..msg })),
+			_ => Err(anyhow!("Bundles only carry request or response messages")),
+		}
+	}
+}
+
+/// Outcome of replaying a bundle.
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+	/// Whether the message executed successfully in the gas estimate
+	pub successful_execution: bool,
+	/// Estimated cost of delivering the message
+	pub execution_cost: Cost,
+	/// Whether the message was delivered, `None` for dry runs
+	pub delivered: Option<bool>,
+}
+
+/// Find the request with `commitment` in `range` on `source` and bundle it with its proof at
+/// `height`, the latest height of `source` known to `dest` if not given. The range is capped at
+/// the proof height, since requests emitted after it cannot be proven there. When `dest` is the
+/// `coprocessor`, requests routed through it to any other chain are bundled as well.
+pub async fn export_bundle(
+	source: &Arc<dyn IsmpProvider>,
+	dest: &Arc<dyn IsmpProvider>,
+	coprocessor: StateMachine,
+	commitment: H256,
+	range: RangeInclusive<u64>,
+	height: Option<u64>,
+) -> Result<ProofBundle, anyhow::Error> {
+	let source_id = source.state_machine_id();
+	let dest_state_machine = dest.state_machine_id().state_id;
+	let height = match height {
+		Some(height) => height,
+		None => dest.query_latest_height(source_id).await? as u64,
+	};
+	let end = (*range.end()).min(height);
+
+	let mut found = None;
+	let mut start = *range.start();
+	while found.is_none() && start <= end {
+		let chunk_end = start.saturating_add(SCAN_CHUNK_SIZE - 1).min(end);
+		let update = StateMachineUpdated { state_machine_id: source_id, latest_height: chunk_end };
+		found = source
+			.query_ismp_events(start.saturating_sub(1), update)
+			.await?
+			.into_iter()
+			.find(|ev| match ev {
+				Event::PostRequest(post) =>
+					hash_request::<Hasher>(&Request::Post(post.clone())) == commitment,
+				Event::GetResponse(res) =>
+					hash_request::<Hasher>(&Request::Get(res.get.clone())) == commitment,
+				_ => false,
+			});
+		tracing::trace!(target: LOG_TARGET, source = %source.name(), "Scanned {start}..={chunk_end} for {commitment:?}");
+		start = chunk_end + 1;
+	}
+	let event = found.ok_or_else(|| {
+		anyhow!("No request with commitment {commitment:?} in {}..={end}", range.start())
+	})?;
+
+	let state_machine_height = StateMachineHeight { id: source_id, height };
+	let message = match event {
+		Event::PostRequest(post) => {
+			if post.dest != dest_state_machine && dest_state_machine != coprocessor {
+				Err(anyhow!("The request is addressed to {}, not {dest_state_machine}", post.dest))?
+			}
+			let query = Query {
+				source_chain: post.source,
+				dest_chain: post.dest,
+				nonce: post.nonce,
+				commitment,
+			};
+			let proof =
+				source.query_requests_proof(height, vec![query], dest_state_machine).await?;
+			Message::Request(RequestMessage {
+				requests: vec![post],
+				proof: Proof { height: state_machine_height, proof },
+				signer: dest.address(),
+			})
+		},
+		Event::GetResponse(res) => {
+			// The response is read by the chain that made the get request
+			if res.get.source != dest_state_machine && dest_state_machine != coprocessor {
+				Err(anyhow!(
+					"The response is addressed to {}, not {dest_state_machine}",
+					res.get.source
+				))?
+			}
+			let proof = source
+				.query_responses_proof(
+					height,
+					vec![hash_response::<Hasher>(&res)],
+					dest_state_machine,
+				)
+				.await?;
+			Message::Response(ResponseMessage {
+				requests: vec![res.get.clone()],
+				proof: Proof { height: state_machine_height, proof: (proof, vec![res]).encode() },
+				signer: dest.address(),
+			})
+		},
+		_ => unreachable!("Only application messages are matched; qed"),
+	};
+
+	let state_commitment = dest
+		.query_state_machine_commitment(state_machine_height)
+		.await
+		.with_context(|| {
+			format!("{} has no state commitment for {source_id:?} at {height}", dest.name())
+		})?;
+
+	Ok(ProofBundle {
+		version: BUNDLE_VERSION,
+		commitment,
+		source: source_id.state_id,
+		dest: dest_state_machine,
+		consensus_state_id: source_id.consensus_state_id,
+		proof_height: height,
+		state_commitment,
+		dest_timestamp: dest.query_timestamp().await?.as_secs(),
+		message: message.encode(),
+	})
+}
+
+/// Deliver a bundle to `dest`, signed by the `dest` client. The message is always gas estimated
+/// first, and only submitted when `dry_run` is false. `dest` can be a local fork of the
+/// destination, as long as it holds the state commitment the message is proven against.
+pub async fn replay_bundle(
+	dest: &Arc<dyn IsmpProvider>,
+	bundle: &ProofBundle,
+	coprocessor: StateMachine,
+	dry_run: bool,
+) -> Result<ReplayReport, anyhow::Error> {
+	if bundle.version != BUNDLE_VERSION {
+		Err(anyhow!("Unsupported bundle version {}, expected {BUNDLE_VERSION}", bundle.version))?
+	}
+	let dest_state_machine = dest.state_machine_id().state_id;
+	if bundle.dest != dest_state_machine {
+		Err(anyhow!("The bundle is addressed to {}, not {dest_state_machine}", bundle.dest))?
+	}
+
+	let height = bundle.height();
+	let state_commitment =
+		dest.query_state_machine_commitment(height).await.with_context(|| {
+			format!(
+				"{} has no state commitment for {} at {}, the proof cannot be verified",
+				dest.name(),
+				bundle.source,
+				bundle.proof_height
+			)
+		})?;
+	if state_commitment != bundle.state_commitment {
+		Err(anyhow!(
+			"{} holds a different state commitment for {} at {} than the bundle was proven against",
+			dest.name(),
+			bundle.source,
+			bundle.proof_height
+		))?
+	}
+
+	let message = bundle.message(dest.address())?;
+	let receipt = match message {
+		Message::Response(_) => dest.query_response_receipt(bundle.commitment).await?,
+		_ => dest.query_request_receipt(bundle.commitment).await?,
+	};
+	if receipt.iter().any(|byte| *byte != 0) {
+		tracing::warn!(target: LOG_TARGET, commitment = ?bundle.commitment, "{} already has a receipt for the bundled request", dest.name());
+	}
+
+	let estimate = dest
+		.estimate_gas(vec![message.clone()])
+		.await?
+		.pop()
+		.ok_or_else(|| anyhow!("No gas estimate returned for the bundled message"))?;
+	let mut report = ReplayReport {
+		successful_execution: estimate.successful_execution,
+		execution_cost: estimate.execution_cost,
+		delivered: None,
+	};
+	if dry_run {
+		return Ok(report);
+	}
+
+	let result = dest.submit(vec![message], coprocessor).await?;
+	report.delivered = Some(result.unsuccessful.is_empty());
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ismp::router::PostRequest;
+	use std::time::Duration;
+	use tesseract_primitives::mocks::MockHost;
+
+	const HB: StateMachine = StateMachine::Kusama(4009);
+	const SOURCE: StateMachine = StateMachine::Evm(1);
+	const DEST: StateMachine = StateMachine::Evm(56);
+
+	fn post(dest: StateMachine) -> PostRequest {
+		PostRequest {
+			source: SOURCE,
+			dest,
+			nonce: 7,
+			from: vec![1; 20],
+			to: vec![2; 20],
+			timeout_timestamp: 0,
+			body: vec![3; 8],
+		}
+	}
+
+	fn mock(state_machine: StateMachine) -> MockHost<()> {
+		MockHost::new((), 100, state_machine).with_timestamp(Duration::from_secs(1_700_000_000))
+	}
+
+	/// Export the request from a source holding it at height 50, then replay it to `dest`
+	async fn export_and_replay(dest: MockHost<()>) -> ProofBundle {
+		let request = post(DEST);
+		let source: Arc<dyn IsmpProvider> =
+			Arc::new(mock(SOURCE).with_event(50, Event::PostRequest(request.clone())));
+		let submitted = dest.submitted.clone();
+		let dest: Arc<dyn IsmpProvider> = Arc::new(dest);
+		let commitment = hash_request::<Hasher>(&Request::Post(request.clone()));
+
+		let bundle =
+			export_bundle(&source, &dest, HB, commitment, 0..=u64::MAX, None).await.unwrap();
+		assert_eq!(bundle.commitment, commitment);
+		assert_eq!(bundle.source, SOURCE);
+		assert_eq!(bundle.proof_height, 100);
+
+		let report = replay_bundle(&dest, &bundle, HB, false).await.unwrap();
+		assert!(report.successful_execution);
+		assert_eq!(report.delivered, Some(true));
+
+		let submitted = submitted.lock().unwrap().clone();
+		let [batch] = &submitted[..] else { panic!("expected a single submission") };
+		let [Message::Request(msg)] = &batch[..] else { panic!("expected a request message") };
+		assert_eq!(msg.requests, vec![request]);
+		assert_eq!(msg.proof.height, bundle.height());
+
+		bundle
+	}
+
+	#[tokio::test]
+	async fn exports_and_replays_a_direct_request() {
+		let bundle = export_and_replay(mock(DEST)).await;
+		assert_eq!(bundle.dest, DEST);
+	}
+
+	#[tokio::test]
+	async fn exports_and_replays_a_request_routed_through_hyperbridge() {
+		let bundle = export_and_replay(mock(HB)).await;
+		assert_eq!(bundle.dest, HB);
+	}
+
+	#[tokio::test]
+	async fn rejects_requests_addressed_elsewhere() {
+		let request = post(DEST);
+		let source: Arc<dyn IsmpProvider> =
+			Arc::new(mock(SOURCE).with_event(50, Event::PostRequest(request.clone())));
+		let dest: Arc<dyn IsmpProvider> = Arc::new(mock(StateMachine::Evm(137)));
+		let commitment = hash_request::<Hasher>(&Request::Post(request));
+
+		let err = export_bundle(&source, &dest, HB, commitment, 0..=u64::MAX, None)
+			.await
+			.unwrap_err();
+		assert!(err.to_string().contains("addressed to"));
+	}
+
+	#[tokio::test]
+	async fn replay_rejects_a_different_state_commitment() {
+		let bundle = export_and_replay(mock(DEST)).await;
+		let fork: Arc<dyn IsmpProvider> =
+			Arc::new(mock(DEST).with_timestamp(Duration::from_secs(1_800_000_000)));
+
+		assert!(replay_bundle(&fork, &bundle, HB, true).await.is_err());
+	}
+
+	#[test]
+	fn bundle_roundtrips_through_json() {
+		let post = PostRequest {
+			source: StateMachine::Evm(1),
+			dest: StateMachine::Kusama(4009),
+			nonce: 7,
+			from: vec![1; 20],
+			to: vec![2; 20],
+			timeout_timestamp: 0,
+			body: vec![3; 8],
+		};
+		let height = StateMachineHeight {
+			id: StateMachineId { state_id: StateMachine::Evm(1), consensus_state_id: *b"ETH0" },
+			height: 100,
+		};
+		let message = Message::Request(RequestMessage {
+			requests: vec![post.clone()],
+			proof: Proof { height, proof: vec![4; 32] },
+			signer: vec![5; 20],
+		});
+		let bundle = ProofBundle {
+			version: BUNDLE_VERSION,
+			commitment: hash_request::<Hasher>(&Request::Post(post)),
+			source: StateMachine::Evm(1),
+			dest: StateMachine::Kusama(4009),
+			consensus_state_id: *b"ETH0",
+			proof_height: 100,
+			state_commitment: StateCommitment {
+				timestamp: 1_700_000_000,
+				overlay_root: None,
+				state_root: H256::repeat_byte(6),
+			},
+			dest_timestamp: 1_700_000_100,
+			message: message.encode(),
+		};
+
+		let json = serde_json::to_string(&bundle).unwrap();
+		let decoded: ProofBundle = serde_json::from_str(&json).unwrap();
+		assert_eq!(decoded, bundle);
+		assert_eq!(decoded.height(), height);
+
+		let Message::Request(replayed) = decoded.message(vec![9; 20]).unwrap() else {
+			panic!("expected a request message")
+		};
+		assert_eq!(replayed.signer, vec![9; 20]);
+		assert_eq!(replayed.proof.proof, vec![4; 32]);
+	}
+}
@@ -8,12 +8,23 @@ use ismp::{
 		Event as IsmpEvent, Meta, RequestResponseHandled, StateMachineUpdated, TimeoutHandled,
 	},
 	host::StateMachine,
-	messaging::{hash_request, hash_response, Message, Proof, RequestMessage, ResponseMessage},
+	messaging::{
+		hash_request, hash_response, AggregatedRequestMessage, Message, Proof, RequestBatch,
+		RequestMessage, ResponseMessage,
+	},
 	router::{GetResponse, PostRequest, Request},
 };
 use sp_core::{H160, U256};
-use std::{collections::HashMap, sync::Arc};
-use tesseract_primitives::{config::RelayerConfig, Cost, Hasher, IsmpProvider, Query};
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use tesseract_primitives::{
+	config::RelayerConfig,
+	policy::{Delivery, Verdict},
+	Cost, Hasher, IsmpProvider, Query, TxReceipt,
+};
 use tokio_stream::StreamExt;
 
 #[derive(Debug)]
@@ -195,10 +206,9 @@ pub async fn translate_events_to_messages(
 			sink.clone(),
 			request_messages,
 			post_request_queries,
-			config.minimum_profit_percentage,
+			&config,
 			coprocessor,
 			&client_map,
-			config.deliver_failed.unwrap_or_default(),
 			consensus_prelude.clone(),
 		)
 		.await?;
@@ -252,6 +262,9 @@ pub async fn translate_events_to_messages(
 		}
 	}
 
+	let mut messages =
+		aggregate_request_messages(sink.state_machine_id().state_id, messages, sink.address());
+
 	// GetResponses only ever originate on the coprocessor and come back to the
 	// chain that made the request. Their values already ride in the events, so
 	// we gate each one for profitability and then batch the survivors into
@@ -271,10 +284,9 @@ pub async fn translate_events_to_messages(
 				sink.clone(),
 				response_messages,
 				response_queries,
-				config.minimum_profit_percentage,
+				&config,
 				coprocessor,
 				&client_map,
-				config.deliver_failed.unwrap_or_default(),
 				consensus_prelude,
 			)
 			.await?;
@@ -411,7 +423,9 @@ async fn build_get_response_candidates(
 ///
 /// Events are gated by `module_filter` via `is_allowed_module`, so operators
 /// can scope which modules they deliver. With no `module_filter` configured,
-/// `is_allowed_module` permits every module. The on-chain reward allowlist
+/// `is_allowed_module` permits every module. Modules on the route policy's
+/// `always_deliver` allowlist are permitted regardless of the filter. The
+/// on-chain reward allowlist
 /// (`pallet_ismp_relayer::OutboundRequestDeliveryReward`) is applied
 /// separately by the outbound task.
 ///
@@ -439,6 +453,71 @@ pub fn filter_events(
 	}
 }
 
+/// Upper bound on the combined proof size of an [`AggregatedRequestMessage`]. Verification cost
+/// grows with the proof, so this keeps an aggregated message within the block weight a single
+/// chunk of requests would use.
+const MAX_AGGREGATED_PROOF_SIZE: usize = 512 * 1024;
+
+/// Fold the request messages in `messages` into [`AggregatedRequestMessage`]s, so a backlog of
+/// requests proven at one or more heights is delivered in as few transactions as possible. Each
+/// aggregated message carries at most [`chunk_size`] requests and [`MAX_AGGREGATED_PROOF_SIZE`]
+/// bytes of proofs. Only substrate sinks (running pallet-ismp) understand aggregated messages; for
+/// every other sink the messages are returned unchanged.
+pub fn aggregate_request_messages(
+	sink: StateMachine,
+	messages: Vec<Message>,
+	signer: Vec<u8>,
+) -> Vec<Message> {
+	if !sink.is_substrate() {
+		return messages;
+	}
+
+	let (requests, others): (Vec<_>, Vec<_>) =
+		messages.into_iter().partition(|msg| matches!(msg, Message::Request(_)));
+	if requests.len() < 2 {
+		return requests.into_iter().chain(others).collect();
+	}
+
+	let max_requests = chunk_size(sink);
+	let mut aggregated: Vec<Vec<RequestBatch>> = vec![];
+	let (mut requests_len, mut proof_len) = (0, 0);
+	for batch in requests.into_iter().filter_map(|msg| match msg {
+		Message::Request(req) => Some(RequestBatch::from(req)),
+		_ => None,
+	}) {
+		let full = requests_len + batch.requests.len() > max_requests ||
+			proof_len + batch.proof.proof.len() > MAX_AGGREGATED_PROOF_SIZE;
+		if full || aggregated.is_empty() {
+			aggregated.push(vec![]);
+			(requests_len, proof_len) = (0, 0);
+		}
+		requests_len += batch.requests.len();
+		proof_len += batch.proof.proof.len();
+		aggregated.last_mut().expect("Pushed above").push(batch);
+	}
+
+	aggregated
+		.into_iter()
+		.map(|mut batches| {
+			// A lone batch doesn't need the aggregated encoding
+			if batches.len() == 1 {
+				let batch = batches.remove(0);
+				Message::Request(RequestMessage {
+					requests: batch.requests,
+					proof: batch.proof,
+					signer: signer.clone(),
+				})
+			} else {
+				Message::AggregatedRequest(AggregatedRequestMessage {
+					batches,
+					signer: signer.clone(),
+				})
+			}
+		})
+		.chain(others)
+		.collect()
+}
+
 pub fn chunk_size(state_machine: StateMachine) -> usize {
 	match state_machine {
 		StateMachine::Evm(_) => 100,
@@ -456,16 +535,27 @@ pub async fn return_successful_queries(
 	sink: Arc<dyn IsmpProvider>,
 	messages: Vec<Message>,
 	queries: Vec<Query>,
-	minimum_profit_percentage: u32,
+	config: &RelayerConfig,
 	coprocessor: StateMachine,
 	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
-	deliver_failed: bool,
 	consensus_prelude: Option<Message>,
 ) -> Result<ProfitabilityResult, anyhow::Error> {
 	if messages.is_empty() {
 		return Ok(Default::default());
 	}
 
+	let deliver_failed = config.deliver_failed.unwrap_or_default();
+	// Only query the gas price when a route caps it, an unavailable gas price leaves the cap
+	// unenforced rather than stalling delivery.
+	let gas_price = if config.policy.needs_gas_price() {
+		sink.gas_price().await.unwrap_or_else(|err| {
+			tracing::warn!(target: crate::LOG_TARGET, "Failed to query gas price on {:?}: {err:?}", sink.state_machine_id().state_id);
+			None
+		})
+	} else {
+		None
+	};
+
 	let mut queries_to_be_relayed = Vec::new();
 	let mut retriable_messages = Vec::new();
 	// Estimate each message together with the consensus update it rides in
@@ -523,37 +613,48 @@ pub async fn return_successful_queries(
 							return Ok((None, None))
 						};
 
-						let fee_metadata = match msg {
+						let (fee_metadata, modules) = match msg {
 							// A GetResponse is gated on the fee attached to its origin GetRequest,
 							// which the EVM host pays the relayer when it dispatches the response.
 							// `query.commitment` is the request commitment for both message kinds.
-							Message::Request(_) | Message::Response(_) =>
+							Message::Request(ref req) => (
+								og_source.query_request_fee_metadata(query.commitment).await?,
+								req.requests.iter().map(|post| post.from.clone()).collect::<Vec<_>>(),
+							),
+							Message::Response(ref res) => (
 								og_source.query_request_fee_metadata(query.commitment).await?,
+								res.requests.iter().map(|get| get.from.clone()).collect::<Vec<_>>(),
+							),
 							_ => Err(anyhow!("Unexpected message: {msg:?}"))?
 						};
 
 						// normalize fee_metadata to 18 decimals since gas cost is calculated in 18 decimals
 						let fee_token_decimal = og_source.fee_token_decimals().await?;
-						let mut fee_metadata: Cost = (fee_metadata * U256::from(10u128.pow(18u32.saturating_sub(fee_token_decimal.into()) as u32))).into();
-
-						let profit = (U256::from(minimum_profit_percentage) *
-							total_gas_to_be_expended_in_usd.0) /
-							U256::from(10000);
-						// 0 profit percentage means we want to relay all requests for free
-						let fee_with_profit: Cost = total_gas_to_be_expended_in_usd + profit;
-						if minimum_profit_percentage == 0 {
-							fee_metadata = U256::MAX.into()
+						let fee_metadata: Cost = (fee_metadata * U256::from(10u128.pow(18u32.saturating_sub(fee_token_decimal.into()) as u32))).into();
+
+						let delivery = Delivery {
+							source: query.source_chain,
+							dest: query.dest_chain,
+							modules: &modules,
+							execution_cost: total_gas_to_be_expended_in_usd,
+							fee: fee_metadata,
+							gas_price,
 						};
-
-						if fee_metadata < fee_with_profit {
-							tracing::info!(target: crate::LOG_TARGET, "Skipping unprofitable tx. Expected ${fee_with_profit}, user provided ${fee_metadata}");
-							(None, Some(msg))
-						} else {
-							tracing::trace!(
-								target: crate::LOG_TARGET, "Pushing tx to {:?} with cost ${fee_with_profit} and profit: ${}",
-									sink.state_machine_id().state_id, Cost(profit)
-							);
-							(Some(query), None)
+						let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+						match config.policy.evaluate(&delivery, config.minimum_profit_percentage, now) {
+							Verdict::Deliver { subsidized } => {
+								// charged to the route's spending once the delivery is confirmed
+								config.policy.reserve(query.commitment, &delivery, now);
+								tracing::trace!(
+									target: crate::LOG_TARGET, "Pushing tx to {:?} with cost ${total_gas_to_be_expended_in_usd} and fee: ${fee_metadata}, subsidized: {subsidized}",
+										sink.state_machine_id().state_id
+								);
+								(Some(query), None)
+							},
+							Verdict::Defer(reason) => {
+								tracing::info!(target: crate::LOG_TARGET, "Skipping tx {:?} to {:?}: {reason}", query.commitment, sink.state_machine_id().state_id);
+								(None, Some(msg))
+							},
 						}
 
 					} else {
@@ -580,7 +681,20 @@ pub async fn return_successful_queries(
 	Ok(ProfitabilityResult { queries: queries_to_be_relayed, retriable_messages })
 }
 
-fn is_allowed_module(config: &RelayerConfig, module: &[u8]) -> bool {
+/// Charge the execution cost of every delivered message to the spending caps of its routes.
+pub fn settle_deliveries(config: &RelayerConfig, receipts: &[TxReceipt]) {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+	config
+		.policy
+		.settle(receipts.iter().map(|receipt| receipt.query.commitment), now);
+}
+
+pub(crate) fn is_allowed_module(config: &RelayerConfig, module: &[u8]) -> bool {
+	// partner apps on the policy's always-deliver allowlist bypass the module filter
+	if config.policy.is_always_delivered(module) {
+		return true;
+	}
+
 	match config.module_filter {
 		Some(ref filters) =>
 			if !filters.is_empty() {
@@ -599,3 +713,65 @@ fn is_allowed_module(config: &RelayerConfig, module: &[u8]) -> bool {
 
 	true
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ismp::{consensus::StateMachineHeight, router::PostRequest};
+
+	fn request_message(requests: usize, proof: usize, height: u64) -> Message {
+		let post = |nonce| PostRequest {
+			source: StateMachine::Evm(1),
+			dest: StateMachine::Kusama(4009),
+			nonce,
+			from: vec![],
+			to: vec![],
+			timeout_timestamp: 0,
+			body: vec![],
+		};
+		Message::Request(RequestMessage {
+			requests: (0..requests as u64).map(post).collect(),
+			proof: Proof {
+				height: StateMachineHeight {
+					id: ismp::consensus::StateMachineId {
+						state_id: StateMachine::Evm(1),
+						consensus_state_id: *b"ETH0",
+					},
+					height,
+				},
+				proof: vec![0u8; proof],
+			},
+			signer: vec![],
+		})
+	}
+
+	#[test]
+	fn aggregates_within_chunk_and_proof_bounds() {
+		let sink = StateMachine::Kusama(4009);
+		let messages = (0..5).map(|height| request_message(60, 1024, height)).collect();
+		let aggregated = aggregate_request_messages(sink, messages, vec![]);
+		let sizes = aggregated
+			.iter()
+			.map(|msg| match msg {
+				Message::AggregatedRequest(msg) => msg.requests().len(),
+				Message::Request(msg) => msg.requests.len(),
+				_ => unreachable!(),
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(sizes, vec![180, 120]);
+
+		let messages = (0..3)
+			.map(|height| request_message(1, MAX_AGGREGATED_PROOF_SIZE / 2 + 1, height))
+			.collect();
+		let aggregated = aggregate_request_messages(sink, messages, vec![]);
+		assert_eq!(aggregated.len(), 3);
+		assert!(aggregated.iter().all(|msg| matches!(msg, Message::Request(_))));
+	}
+
+	#[test]
+	fn leaves_evm_messages_unchanged() {
+		let messages = (0..3).map(|height| request_message(1, 32, height)).collect();
+		let aggregated = aggregate_request_messages(StateMachine::Evm(1), messages, vec![]);
+		assert_eq!(aggregated.len(), 3);
+	}
+}
@@ -20,6 +20,8 @@ pub const LOG_TARGET: &str = concat!("messaging", "-inbound");
 /// How often both outbound claim tasks wake to process pending rows.
 pub(crate) const CLAIM_INTERVAL_SECS: u64 = 600;
 
+pub mod backfill;
+pub mod bundle;
 pub mod events;
 pub mod fees;
 mod get_requests;
@@ -31,16 +33,17 @@ pub mod outbound_request_claim;
 /// design is that retrying unprofitable messages is the outbound task's
 /// concern.
 pub mod retries;
+pub mod timeouts;
 
 use anyhow::anyhow;
 use get_requests::process_get_request_events;
 use itertools::Itertools;
 use polkadot_sdk::sc_service::TaskManager;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tracing::Instrument;
 
-use crate::events::{filter_events, translate_events_to_messages};
+use crate::events::{filter_events, settle_deliveries, translate_events_to_messages};
 use futures::{FutureExt, StreamExt};
 use ismp::{consensus::StateMachineHeight, events::Event, host::StateMachine, router::GetRequest};
 use primitive_types::U256;
@@ -160,6 +163,47 @@ where
 		);
 	}
 
+	// Gap detector: periodically re-queues requests the messaging tasks above moved past
+	// without delivering, in every direction they relay. Hyperbridge → EVM is covered by the
+	// outbound pipeline, see [`outbound::Outbound::spawn`].
+	if let Some(frequency) = config.gap_scan_frequency {
+		let hyperbridge: Arc<dyn IsmpProvider> = Arc::new(hyperbridge.clone());
+		let mut directions = vec![(chain_b.clone(), hyperbridge.clone())];
+		if chain_b_state.is_substrate() {
+			directions.push((hyperbridge, chain_b.clone()));
+		}
+
+		for (source, dest) in directions {
+			let client_map = client_map.clone();
+			let tx_payment = tx_payment.clone();
+			let config = config.clone();
+			let lookback = config.gap_scan_lookback.unwrap_or(backfill::DEFAULT_GAP_SCAN_LOOKBACK);
+			let name = format!("gap-detector-{}-{}", source.name(), dest.name());
+			let span =
+				tracing::info_span!("gap_detector", source = %source.name(), dest = %dest.name());
+			task_manager.spawn_essential_handle().spawn_blocking(
+				Box::leak(Box::new(name)),
+				"messaging",
+				async move {
+					let res = backfill::detect_gaps(
+						source,
+						dest,
+						tx_payment,
+						config,
+						coprocessor,
+						client_map,
+						Duration::from_secs(frequency),
+						lookback,
+					)
+					.await;
+					tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+				}
+				.instrument(span)
+				.boxed(),
+			);
+		}
+	}
+
 	// GET-request processor: drains the channel fed by the inbound messaging
 	// task, builds source + storage proofs, and submits each as a
 	// `GetRequestsWithProof` to Hyperbridge.
@@ -337,6 +381,42 @@ async fn handle_update(
 	}
 	// Advance latest known height by relayer
 	*previous_height = state_machine_update.latest_height;
+	let state_machine_height = StateMachineHeight {
+		id: state_machine_update.state_machine_id,
+		height: state_machine_update.latest_height,
+	};
+
+	deliver_events(
+		chain_a,
+		chain_b,
+		tx_payment,
+		events,
+		state_machine_height,
+		config,
+		coprocessor,
+		client_map,
+		fee_acc_sender,
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Translate `events` emitted on `chain_b` into messages proven at `state_machine_height`,
+/// deliver them to `chain_a` and persist what was delivered. Returns the receipts of every
+/// delivered request, receipts are only written to the fee db here when `fee_acc_sender` is set.
+pub(crate) async fn deliver_events(
+	chain_a: Arc<dyn IsmpProvider>,
+	chain_b: Arc<dyn IsmpProvider>,
+	tx_payment: Arc<TransactionPayment>,
+	events: Vec<Event>,
+	state_machine_height: StateMachineHeight,
+	config: RelayerConfig,
+	coprocessor: StateMachine,
+	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	fee_acc_sender: Option<FeeAccSender>,
+) -> Result<Vec<TxReceipt>, anyhow::Error> {
+	let mut delivered = vec![];
 	let log_events = events
 		.iter()
 		.chunk_by(|event| match event {
@@ -352,10 +432,6 @@ async fn handle_update(
 			format!("{acc}{}->{}: {} messages, ", chain_b.name(), state_machine, items.count())
 		});
 	tracing::info!(target: LOG_TARGET, "{log_events}");
-	let state_machine_height = StateMachineHeight {
-		id: state_machine_update.state_machine_id,
-		height: state_machine_update.latest_height,
-	};
 
 	let (messages, unprofitable) = translate_events_to_messages(
 		chain_b.clone(),
@@ -381,6 +457,8 @@ async fn handle_update(
 		let res = chain_a.submit(messages.clone(), coprocessor).await;
 		match res {
 			Ok(TxResult { receipts, unsuccessful, new_epochs: _ }) => {
+				settle_deliveries(&config, &receipts);
+				delivered = receipts.clone();
 				if let Some(sender) = fee_acc_sender {
 					// We should not store messages when they are delivered to hyperbridge
 					if chain_a.state_machine_id().state_id != coprocessor {
@@ -500,7 +578,7 @@ async fn handle_update(
 		}
 	}
 
-	Ok(())
+	Ok(delivered)
 }
 
 pub async fn fee_accumulation<A: IsmpProvider + Clone + Clone + HyperbridgeClaim + 'static>(
@@ -39,7 +39,7 @@ use tokio::sync::mpsc::Sender;
 use tracing::Instrument;
 use transaction_fees::TransactionPayment;
 
-use crate::events::{filter_events, translate_events_to_messages};
+use crate::events::{filter_events, settle_deliveries, translate_events_to_messages};
 
 /// Log/tracing target for the outbound pipeline.
 const LOG_TARGET: &str = concat!("messaging", "-outbound");
@@ -393,7 +393,7 @@ async fn submit_for_dest(
 			dest.clone(),
 			events,
 			state_machine_height,
-			relayer_config,
+			relayer_config.clone(),
 			coprocessor,
 			&client_map,
 			// Pass the consensus update as the gas-estimation prelude so EVM
@@ -450,6 +450,7 @@ async fn submit_for_dest(
 	// whose handler supports IHandlerV2 dispatch the whole batch as a single
 	// `batchCall(bytes[])` tx; everything else uses the legacy serial path.
 	let result = dest.submit(batch, hb_state_machine_id.state_id).await?;
+	settle_deliveries(&relayer_config, &result.receipts);
 
 	// Forward a claim for every hyperbridge-originated request just delivered.
 	forward_request_delivery_claims(
@@ -763,8 +764,8 @@ async fn catch_up_rotations(
 	Ok(new_epochs)
 }
 
-/// Static-friendly inputs for [`initialize`]. Lives next to the spawn
-/// responsibility itself so cli.rs only has to assemble values; it
+/// Static-friendly inputs for [`connect`]. Lives next to the spawn
+/// responsibility itself so the supervisor only has to assemble values; it
 /// doesn't have to know how outbound stitches its tasks together.
 pub struct OutboundInitParams {
 	/// Plain HB substrate config — used to build per-task substrate
@@ -794,46 +795,80 @@ pub struct OutboundInitParams {
 	pub consensus_hosts: HashMap<StateMachine, Arc<dyn IsmpHost>>,
 }
 
-/// Spawn the full outbound pipeline:
-///
-/// 1. Per-destination [`crate::fee_accumulation`] task (skipped when `fees_disabled`).
-/// 2. [`outbound_claim::run`](crate::outbound_claim::run) for the consensus delivery reward.
-/// 3. [`run`] itself — the `ProofAccepted` subscriber that fans out to every destination.
-///
-/// All three are essential tasks: if any of them ends, the surrounding
-/// [`TaskManager`] terminates the relayer process. cli.rs just assembles
-/// the [`OutboundInitParams`] and calls this once.
-pub async fn initialize(
+/// The outbound pipeline with its hyperbridge connections open, ready to be
+/// [`spawn`](Outbound::spawn)ed.
+pub struct Outbound {
 	params: OutboundInitParams,
-	task_manager: &polkadot_sdk::sc_service::TaskManager,
-) -> Result<(), anyhow::Error> {
-	let OutboundInitParams {
-		hyperbridge_config,
-		hyperbridge_provider,
-		destinations,
-		provider_clients,
-		proof_source,
-		relayer_config,
-		tx_payment,
-		fees_disabled,
-		consensus_hosts,
-	} = params;
+	/// One connection per destination's fee accumulation task, empty when fees are disabled
+	fee_clients: BTreeMap<StateMachine, SubstrateClient<KeccakSubstrateChain>>,
+	claim_client: SubstrateClient<KeccakSubstrateChain>,
+	request_claim_client: SubstrateClient<KeccakSubstrateChain>,
+	fan_out_client: SubstrateClient<KeccakSubstrateChain>,
+}
 
-	if destinations.is_empty() {
+/// Open every hyperbridge connection the outbound pipeline needs without
+/// spawning anything, so a failure leaves the running tasks untouched.
+/// Returns `None` when no destination is outbound-enabled.
+pub async fn connect(params: OutboundInitParams) -> Result<Option<Outbound>, anyhow::Error> {
+	if params.destinations.is_empty() {
 		tracing::info!(target: LOG_TARGET, "no outbound-enabled destinations; skipping outbound pipeline");
-		return Ok(());
+		return Ok(None);
+	}
+
+	let connect =
+		|| SubstrateClient::<KeccakSubstrateChain>::new(params.hyperbridge_config.clone());
+	let mut fee_clients = BTreeMap::new();
+	if !params.fees_disabled {
+		for sm in params.destinations.keys() {
+			fee_clients.insert(*sm, connect().await?);
+		}
 	}
+	let claim_client = connect().await?;
+	let request_claim_client = connect().await?;
+	let fan_out_client = connect().await?;
+
+	Ok(Some(Outbound { params, fee_clients, claim_client, request_claim_client, fan_out_client }))
+}
 
-	// Per-destination fee accumulation. Each task owns its own substrate
-	// client so the connection isn't shared across tokio tasks.
-	let mut fee_senders: HashMap<StateMachine, Sender<Vec<TxReceipt>>> = HashMap::new();
-	if !fees_disabled {
-		for (sm, provider) in &destinations {
+impl Outbound {
+	/// Spawn the full outbound pipeline:
+	///
+	/// 1. Per-destination [`crate::fee_accumulation`] task (skipped when `fees_disabled`).
+	/// 2. [`outbound_claim::run`](crate::outbound_claim::run) for the consensus delivery reward.
+	/// 3. [`run`] itself — the `ProofAccepted` subscriber that fans out to every destination.
+	/// 4. A [`crate::backfill::detect_gaps`] task per non-substrate destination, when
+	///    `gap_scan_frequency` is set.
+	///
+	/// All of them are essential tasks: if any of them ends, the surrounding
+	/// [`TaskManager`] terminates the relayer process.
+	pub fn spawn(self, task_manager: &polkadot_sdk::sc_service::TaskManager) {
+		let Outbound {
+			params,
+			fee_clients,
+			claim_client: claim_hb,
+			request_claim_client: request_claim_hb,
+			fan_out_client: outbound_hb_sub,
+		} = self;
+		let OutboundInitParams {
+			hyperbridge_config: _,
+			hyperbridge_provider,
+			destinations,
+			provider_clients,
+			proof_source,
+			relayer_config,
+			tx_payment,
+			fees_disabled: _,
+			consensus_hosts,
+		} = params;
+
+		// Per-destination fee accumulation. Each task owns its own substrate
+		// client so the connection isn't shared across tokio tasks.
+		let mut fee_senders: HashMap<StateMachine, Sender<Vec<TxReceipt>>> = HashMap::new();
+		for (sm, hb_for_fees) in fee_clients {
+			let Some(provider) = destinations.get(&sm) else { continue };
 			let (fee_sender, fee_receiver) = tokio::sync::mpsc::channel::<Vec<TxReceipt>>(512);
-			fee_senders.insert(*sm, fee_sender);
+			fee_senders.insert(sm, fee_sender);
 
-			let hb_for_fees =
-				SubstrateClient::<KeccakSubstrateChain>::new(hyperbridge_config.clone()).await?;
 			let dest = provider.clone();
 			let client_map = provider_clients.clone();
 			let tx_payment_for_fees = tx_payment.clone();
@@ -862,102 +897,138 @@ pub async fn initialize(
 				.boxed(),
 			);
 		}
-	}
 
-	// Periodic task that claims outbound consensus delivery rewards from the DB.
-	let claim_hb = SubstrateClient::<KeccakSubstrateChain>::new(hyperbridge_config.clone()).await?;
-	let claim_destinations: HashMap<StateMachine, Arc<dyn IsmpProvider>> =
-		destinations.iter().map(|(sm, p)| (*sm, p.clone())).collect();
-	let claim_tx_payment = tx_payment.clone();
-	let claim_consensus_hosts = consensus_hosts.clone();
-	let claim_name = format!("outbound-claim-{}", hyperbridge_provider.name());
-	let claim_span = tracing::info_span!("outbound_claim", hb = %hyperbridge_provider.name());
-	task_manager.spawn_essential_handle().spawn_blocking(
-		Box::leak(Box::new(claim_name)),
-		"outbound",
-		async move {
-			tracing::trace!(target: LOG_TARGET, "task started");
-			let res = crate::outbound_claim::run(
-				claim_hb,
-				claim_destinations,
-				claim_consensus_hosts,
-				Some(claim_tx_payment),
-			)
-			.await;
-			tracing::error!(target: LOG_TARGET, ?res, "task terminated");
-		}
-		.instrument(claim_span)
-		.boxed(),
-	);
+		// Periodic task that claims outbound consensus delivery rewards from the DB.
+		let claim_destinations: HashMap<StateMachine, Arc<dyn IsmpProvider>> =
+			destinations.iter().map(|(sm, p)| (*sm, p.clone())).collect();
+		let claim_tx_payment = tx_payment.clone();
+		let claim_consensus_hosts = consensus_hosts.clone();
+		let claim_name = format!("outbound-claim-{}", hyperbridge_provider.name());
+		let claim_span = tracing::info_span!("outbound_claim", hb = %hyperbridge_provider.name());
+		task_manager.spawn_essential_handle().spawn_blocking(
+			Box::leak(Box::new(claim_name)),
+			"outbound",
+			async move {
+				tracing::trace!(target: LOG_TARGET, "task started");
+				let res = crate::outbound_claim::run(
+					claim_hb,
+					claim_destinations,
+					claim_consensus_hosts,
+					Some(claim_tx_payment),
+				)
+				.await;
+				tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+			}
+			.instrument(claim_span)
+			.boxed(),
+		);
 
-	// Outbound request delivery reward claim task. Sibling of the consensus
-	// claim task; periodic 600s timer, DB-backed, keyed by request commitment.
-	let request_claim_hb =
-		SubstrateClient::<KeccakSubstrateChain>::new(hyperbridge_config.clone()).await?;
-	let request_claim_destinations: HashMap<StateMachine, Arc<dyn IsmpProvider>> =
-		destinations.iter().map(|(sm, p)| (*sm, p.clone())).collect();
-	let request_claim_tx_payment = tx_payment.clone();
-	let request_claim_consensus_hosts = consensus_hosts.clone();
-	let request_claim_name = format!("outbound-request-claim-{}", hyperbridge_provider.name());
-	let request_claim_span =
-		tracing::info_span!("outbound_request_claim", hb = %hyperbridge_provider.name());
-	task_manager.spawn_essential_handle().spawn_blocking(
-		Box::leak(Box::new(request_claim_name)),
-		"outbound",
-		async move {
-			tracing::trace!(target: LOG_TARGET, "task started");
-			let res = crate::outbound_request_claim::run(
-				request_claim_hb,
-				request_claim_destinations,
-				request_claim_consensus_hosts,
-				Some(request_claim_tx_payment),
-			)
-			.await;
-			tracing::error!(target: LOG_TARGET, ?res, "task terminated");
-		}
-		.instrument(request_claim_span)
-		.boxed(),
-	);
+		// Outbound request delivery reward claim task. Sibling of the consensus
+		// claim task; periodic 600s timer, DB-backed, keyed by request commitment.
+		let request_claim_destinations: HashMap<StateMachine, Arc<dyn IsmpProvider>> =
+			destinations.iter().map(|(sm, p)| (*sm, p.clone())).collect();
+		let request_claim_tx_payment = tx_payment.clone();
+		let request_claim_consensus_hosts = consensus_hosts.clone();
+		let request_claim_name = format!("outbound-request-claim-{}", hyperbridge_provider.name());
+		let request_claim_span =
+			tracing::info_span!("outbound_request_claim", hb = %hyperbridge_provider.name());
+		task_manager.spawn_essential_handle().spawn_blocking(
+			Box::leak(Box::new(request_claim_name)),
+			"outbound",
+			async move {
+				tracing::trace!(target: LOG_TARGET, "task started");
+				let res = crate::outbound_request_claim::run(
+					request_claim_hb,
+					request_claim_destinations,
+					request_claim_consensus_hosts,
+					Some(request_claim_tx_payment),
+				)
+				.await;
+				tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+			}
+			.instrument(request_claim_span)
+			.boxed(),
+		);
 
-	// Outbound fan-out itself.
-	let outbound_name = format!("outbound-{}", hyperbridge_provider.name());
-	let destinations_len = destinations.len();
-	let outbound_tx_payment = tx_payment;
-	let outbound_hb_sub =
-		SubstrateClient::<KeccakSubstrateChain>::new(hyperbridge_config.clone()).await?;
-	let outbound_span = tracing::info_span!(
-		"outbound",
-		hb = %hyperbridge_provider.name(),
-		destinations = destinations_len,
-	);
-	task_manager.spawn_essential_handle().spawn_blocking(
-		Box::leak(Box::new(outbound_name)),
-		"outbound",
-		async move {
-			tracing::trace!(target: LOG_TARGET, "task started");
-			let res = run(
-				hyperbridge_provider,
-				outbound_hb_sub,
-				destinations,
-				proof_source,
-				relayer_config,
-				provider_clients,
-				fee_senders,
-				Some(outbound_tx_payment),
-			)
-			.await;
-			tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+		// Gap detector for the outbound direction: re-queues hyperbridge requests to each
+		// destination that the fan-out moved past without delivering. Substrate destinations are
+		// already covered by the gap detector the inbound task spawns.
+		if let Some(frequency) = relayer_config.gap_scan_frequency {
+			let coprocessor = hyperbridge_provider.state_machine_id().state_id;
+			let lookback = relayer_config
+				.gap_scan_lookback
+				.unwrap_or(crate::backfill::DEFAULT_GAP_SCAN_LOOKBACK);
+			for dest in destinations
+				.values()
+				.filter(|dest| !dest.state_machine_id().state_id.is_substrate())
+			{
+				let source = hyperbridge_provider.clone();
+				let dest = dest.clone();
+				let tx_payment = tx_payment.clone();
+				let config = relayer_config.clone();
+				let client_map = provider_clients.clone();
+				let name = format!("gap-detector-{}-{}", source.name(), dest.name());
+				let span = tracing::info_span!("gap_detector", source = %source.name(), dest = %dest.name());
+				task_manager.spawn_essential_handle().spawn_blocking(
+					Box::leak(Box::new(name)),
+					"outbound",
+					async move {
+						let res = crate::backfill::detect_gaps(
+							source,
+							dest,
+							tx_payment,
+							config,
+							coprocessor,
+							client_map,
+							std::time::Duration::from_secs(frequency),
+							lookback,
+						)
+						.await;
+						tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+					}
+					.instrument(span)
+					.boxed(),
+				);
+			}
 		}
-		.instrument(outbound_span)
-		.boxed(),
-	);
 
-	tracing::trace!(
-		target: LOG_TARGET,
-		destinations = destinations_len,
-		"initialized outbound pipeline (fee accumulation + claim + fan-out)",
-	);
-	Ok(())
+		// Outbound fan-out itself.
+		let outbound_name = format!("outbound-{}", hyperbridge_provider.name());
+		let destinations_len = destinations.len();
+		let outbound_tx_payment = tx_payment;
+		let outbound_span = tracing::info_span!(
+			"outbound",
+			hb = %hyperbridge_provider.name(),
+			destinations = destinations_len,
+		);
+		task_manager.spawn_essential_handle().spawn_blocking(
+			Box::leak(Box::new(outbound_name)),
+			"outbound",
+			async move {
+				tracing::trace!(target: LOG_TARGET, "task started");
+				let res = run(
+					hyperbridge_provider,
+					outbound_hb_sub,
+					destinations,
+					proof_source,
+					relayer_config,
+					provider_clients,
+					fee_senders,
+					Some(outbound_tx_payment),
+				)
+				.await;
+				tracing::error!(target: LOG_TARGET, ?res, "task terminated");
+			}
+			.instrument(outbound_span)
+			.boxed(),
+		);
+
+		tracing::trace!(
+			target: LOG_TARGET,
+			destinations = destinations_len,
+			"initialized outbound pipeline (fee accumulation + claim + fan-out)",
+		);
+	}
 }
 
 #[cfg(test)]
@@ -53,7 +53,11 @@ pub async fn run(
 	tx_payment: Option<Arc<TransactionPayment>>,
 ) -> Result<(), anyhow::Error> {
 	let hb_provider: Arc<dyn IsmpProvider> = Arc::new(hyperbridge.clone());
-	let payee_bytes: [u8; 32] = hyperbridge.signer.public().0;
+	let payee_bytes: [u8; 32] = hyperbridge
+		.address
+		.as_slice()
+		.try_into()
+		.map_err(|_| anyhow!("hyperbridge signer account must be 32 bytes"))?;
 
 	let mut interval = tokio::time::interval(Duration::from_secs(crate::CLAIM_INTERVAL_SECS));
 	interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
@@ -270,7 +274,7 @@ pub async fn process_claim(
 		.context("query_state_proof on destination")?;
 
 	let msg = outbound_request_delivery_message(commitment, destination, payee);
-	let signature = dest.sign(&msg);
+	let signature = dest.sign(&msg).await.context("sign claim")?;
 
 	let claim = OutboundRequestDeliveryClaim {
 		request: pending.request.clone(),
@@ -1,7 +1,7 @@
 use std::{
 	collections::{BTreeSet, HashMap},
 	sync::Arc,
-	time::Duration,
+	time::{Duration, Instant},
 };
 
 use ismp::{
@@ -10,11 +10,14 @@ use ismp::{
 	messaging::{hash_request, Message, Proof, RequestMessage},
 	router::Request,
 };
+use primitive_types::{H256, U256};
 use tesseract_primitives::{config::RelayerConfig, Hasher, IsmpProvider, Query, TxResult};
 use transaction_fees::TransactionPayment;
 
 use crate::{
-	events::{chunk_size, return_successful_queries},
+	events::{
+		aggregate_request_messages, chunk_size, return_successful_queries, settle_deliveries,
+	},
 	FeeAccSender,
 };
 
@@ -33,9 +36,17 @@ pub async fn retry_unprofitable_messages(
 		dest.name()
 	);
 	// Default to every 5 minutes
-	let mut interval = tokio::time::interval(Duration::from_secs(
-		config.unprofitable_retry_frequency.unwrap_or(5 * 60),
-	));
+	let retry_frequency =
+		Duration::from_secs(config.unprofitable_retry_frequency.unwrap_or(5 * 60));
+	// Fee bumps are polled at least as often as messages are retried
+	let poll_frequency = config
+		.fee_bump_poll_frequency
+		.map(Duration::from_secs)
+		.unwrap_or(retry_frequency)
+		.min(retry_frequency);
+	let mut interval = tokio::time::interval(poll_frequency);
+	let mut last_retry: Option<Instant> = None;
+	let mut known_fees = HashMap::new();
 	loop {
 		interval.tick().await;
 		let unprofitables =
@@ -46,6 +57,16 @@ pub async fn retry_unprofitable_messages(
 				},
 			};
 
+		let bumped = fees_bumped(&unprofitables, &client_map, &mut known_fees).await;
+		let retry_due = last_retry.map_or(true, |at| at.elapsed() >= retry_frequency);
+		if !retry_due && !bumped {
+			continue;
+		}
+		if bumped {
+			tracing::info!(target: crate::LOG_TARGET, "Unprofitable Messages Retries: Fees were bumped for messages going to {}, retrying", dest.name());
+		}
+		last_retry = Some(Instant::now());
+
 		// Find messages that are  bundled as a batch and split them up so they can be reestimated
 		let mut batched_messages = vec![];
 		let mut unbatched_messages = vec![];
@@ -154,10 +175,9 @@ pub async fn retry_unprofitable_messages(
 				dest.clone(),
 				request_messages,
 				request_queries,
-				config.minimum_profit_percentage,
+				&config,
 				coprocessor,
 				&client_map,
-				config.deliver_failed.unwrap_or_default(),
 				// Retry loop doesn't carry a fresh consensus update — by the
 				// time it runs the light client is already at whatever height
 				// the original delivery saw.
@@ -226,6 +246,11 @@ pub async fn retry_unprofitable_messages(
 				},
 			}
 
+			let outgoing_messages = aggregate_request_messages(
+				dest.state_machine_id().state_id,
+				outgoing_messages,
+				dest.address(),
+			);
 			if !outgoing_messages.is_empty() {
 				tracing::info!(
 					target: crate::LOG_TARGET,
@@ -234,6 +259,7 @@ pub async fn retry_unprofitable_messages(
 				if let Ok(TxResult { receipts, unsuccessful, new_epochs: _ }) =
 					dest.submit(outgoing_messages, coprocessor).await
 				{
+					settle_deliveries(&config, &receipts);
 					if let Some(fee_acc_sender) = fee_acc_sender.clone() {
 						if !receipts.is_empty() {
 							// Store receipts in database before auto accumulation
@@ -307,3 +333,31 @@ pub async fn retry_unprofitable_messages(
 		}
 	}
 }
+
+/// Queries the current fee for every request in `messages` and reports whether any of them has
+/// been raised since it was last seen, e.g. through `fund_messages` or a priority tip on
+/// hyperbridge. `known_fees` is updated in place and pruned to the requests still awaiting a
+/// retry.
+async fn fees_bumped(
+	messages: &[(Message, i32)],
+	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	known_fees: &mut HashMap<H256, U256>,
+) -> bool {
+	let mut bumped = false;
+	let mut current_fees = HashMap::new();
+	for (msg, _) in messages {
+		let Message::Request(req_msg) = msg else { continue };
+		for post in &req_msg.requests {
+			let Some(source) = client_map.get(&post.source) else { continue };
+			let commitment = hash_request::<Hasher>(&Request::Post(post.clone()));
+			let Ok(fee) = source.query_request_fee_metadata(commitment).await else { continue };
+			if known_fees.get(&commitment).map_or(false, |known| fee > *known) {
+				bumped = true;
+			}
+			current_fees.insert(commitment, fee);
+		}
+	}
+	*known_fees = current_fees;
+
+	bumped
+}
@@ -0,0 +1,783 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timeout relaying for expired requests.
+//!
+//! Requests that are never delivered before their `timeout_timestamp` stay pending on their source
+//! chain, with the fee locked, until someone proves that the destination never received them.
+//! [`relay_timeouts`] tracks the requests dispatched on a source chain and, once a request has
+//! expired without a receipt on its destination, delivers the timeout in up to two steps:
+//!
+//! 1. If the request was routed through hyperbridge, hyperbridge holds a receipt for it. The
+//!    receipt is cleared by submitting a timeout to hyperbridge, proven by the non-membership of
+//!    the request receipt in the destination's state.
+//! 2. The timeout is then submitted to the source chain, proven by the non-membership of the
+//!    request receipt in hyperbridge's state.
+//!
+//! Get requests only need the second step and carry no proof, they are timed out against the
+//! source chain's own clock. Only substrate hosts accept proofless get timeouts, so they are only
+//! tracked on substrate source chains.
+//!
+//! When `state_dir` is configured, the tracked requests and the last scanned height are persisted
+//! after every pass, so a restart picks up where the previous run left off.
+
+use crate::{backfill::SCAN_CHUNK_SIZE, events::is_allowed_module, LOG_TARGET};
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use ismp::{
+	consensus::StateMachineHeight,
+	events::Event,
+	host::StateMachine,
+	messaging::{hash_request, Message, Proof, TimeoutMessage},
+	router::{GetRequest, PostRequest, Request},
+};
+use primitive_types::H256;
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tesseract_primitives::{
+	config::{RelayerConfig, TimeoutConfig},
+	policy::{SpendLedger, SECONDS_PER_DAY},
+	Hasher, IsmpProvider, StateMachineUpdated, StateProofQueryType,
+};
+
+/// How often tracked requests are checked for expiry, in seconds, when `frequency` is not
+/// configured.
+pub const DEFAULT_TIMEOUT_FREQUENCY: u64 = 300;
+
+/// Number of source heights behind the finalized height scanned for outstanding requests on
+/// startup, when `lookback` is not configured.
+pub const DEFAULT_TIMEOUT_LOOKBACK: u64 = 10_000;
+
+/// Requests dispatched on a source chain that may still need a timeout, keyed by commitment.
+#[derive(Debug, Default)]
+pub struct OutstandingRequests {
+	requests: BTreeMap<H256, Request>,
+}
+
+impl OutstandingRequests {
+	/// Track the requests dispatched by `source` in a batch of its events and forget the ones
+	/// whose timeouts were handled. Requests without a timeout never expire and are ignored, as
+	/// are get requests on chains that can't time them out without a proof.
+	pub fn observe(
+		&mut self,
+		source: StateMachine,
+		config: &RelayerConfig,
+		events: impl IntoIterator<Item = Event>,
+	) {
+		for event in events {
+			let request = match event {
+				Event::PostRequest(post) if post.source == source => Request::Post(post),
+				Event::GetRequest(get) if get.source == source && source.is_substrate() =>
+					Request::Get(get),
+				Event::PostRequestTimeoutHandled(handled) |
+				Event::GetRequestTimeoutHandled(handled) => {
+					self.requests.remove(&handled.commitment);
+					continue;
+				},
+				_ => continue,
+			};
+			// a zero timeout never expires
+			if request.timeout() == Duration::from_secs(u64::MAX) ||
+				!is_allowed_module(config, &request.source_module())
+			{
+				continue;
+			}
+			self.requests.insert(hash_request::<Hasher>(&request), request);
+		}
+	}
+
+	/// Requests that have expired by `timestamp`.
+	pub fn expired(&self, timestamp: Duration) -> Vec<(H256, Request)> {
+		self.requests
+			.iter()
+			.filter(|(_, request)| request.timed_out(timestamp))
+			.map(|(commitment, request)| (*commitment, request.clone()))
+			.collect()
+	}
+
+	/// Stop tracking a request that was delivered or timed out.
+	pub fn remove(&mut self, commitment: &H256) {
+		self.requests.remove(commitment);
+	}
+
+	/// Number of tracked requests.
+	pub fn len(&self) -> usize {
+		self.requests.len()
+	}
+
+	/// Returns true if no requests are tracked.
+	pub fn is_empty(&self) -> bool {
+		self.requests.is_empty()
+	}
+
+	/// Read the requests and the last scanned height persisted at `path`, if any.
+	pub async fn load(path: &Path) -> Result<Option<(Self, u64)>, anyhow::Error> {
+		let bytes = match tokio::fs::read(path).await {
+			Ok(bytes) => bytes,
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+			Err(err) => return Err(err.into()),
+		};
+		let (scanned, requests) = <(u64, Vec<(H256, Request)>)>::decode(&mut &bytes[..])?;
+		Ok(Some((Self { requests: requests.into_iter().collect() }, scanned)))
+	}
+
+	/// Persist the tracked requests and the last scanned height to `path`. The file is written
+	/// under a temporary name and renamed into place, so a crash never leaves a partial file.
+	pub async fn persist(&self, path: &Path, scanned: u64) -> Result<(), anyhow::Error> {
+		if let Some(dir) = path.parent() {
+			tokio::fs::create_dir_all(dir).await?;
+		}
+		let requests = self.requests.iter().collect::<Vec<_>>();
+		let tmp = path.with_extension("tmp");
+		tokio::fs::write(&tmp, (scanned, requests).encode()).await?;
+		tokio::fs::rename(&tmp, path).await?;
+		Ok(())
+	}
+}
+
+/// Where an expired post request is in the timeout flow.
+enum PostStatus {
+	/// The destination received it, nothing to time out
+	Delivered,
+	/// Hyperbridge holds a receipt for it that must be cleared first
+	RoutedThroughHyperbridge,
+	/// Hyperbridge has no receipt for it, the timeout can go to the source
+	Ready,
+}
+
+/// Track the requests dispatched on `source` and deliver timeouts for the ones that expire
+/// undelivered. Runs every `frequency` seconds, scanning the source events finalized since the
+/// previous pass, and starts `lookback` heights behind the finalized height.
+pub async fn relay_timeouts(
+	source: Arc<dyn IsmpProvider>,
+	hyperbridge: Arc<dyn IsmpProvider>,
+	client_map: HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	config: RelayerConfig,
+	timeouts: TimeoutConfig,
+) -> Result<(), anyhow::Error> {
+	let coprocessor = hyperbridge.state_machine_id().state_id;
+	let frequency = Duration::from_secs(timeouts.frequency.unwrap_or(DEFAULT_TIMEOUT_FREQUENCY));
+	let lookback = timeouts.lookback.unwrap_or(DEFAULT_TIMEOUT_LOOKBACK);
+	let state_file = timeouts.state_dir.as_ref().map(|dir| {
+		PathBuf::from(dir).join(format!("timeouts-{}", source.state_machine_id().state_id))
+	});
+	let (mut outstanding, mut scanned) = match state_file {
+		Some(ref path) => match OutstandingRequests::load(path).await {
+			Ok(Some((outstanding, scanned))) => {
+				tracing::info!(target: LOG_TARGET, source = %source.name(), "Resuming {} tracked requests from height {scanned}", outstanding.len());
+				(outstanding, Some(scanned))
+			},
+			Ok(None) => Default::default(),
+			Err(err) => {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Failed to load tracked requests from {}, rescanning", path.display());
+				Default::default()
+			},
+		},
+		None => Default::default(),
+	};
+	let mut ledger = SpendLedger::default();
+	let mut interval = tokio::time::interval(frequency);
+
+	loop {
+		interval.tick().await;
+		let latest = match source.query_finalized_height().await {
+			Ok(height) => height,
+			Err(err) => {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Timeout relayer failed to query finalized height");
+				continue;
+			},
+		};
+
+		let from = scanned.map(|height| height + 1).unwrap_or(latest.saturating_sub(lookback));
+		match scan(&source, &mut outstanding, &config, from, latest).await {
+			Ok(()) => scanned = Some(latest),
+			Err(err) => {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Timeout relayer failed to scan {from}..={latest}");
+				continue;
+			},
+		}
+
+		if !outstanding.is_empty() {
+			let pass = relay_expired(
+				&source,
+				&hyperbridge,
+				&client_map,
+				coprocessor,
+				&timeouts,
+				&mut outstanding,
+				&mut ledger,
+			)
+			.await;
+			if let Err(err) = pass {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Timeout relayer pass failed");
+			}
+		}
+
+		if let Some(ref path) = state_file {
+			if let Err(err) = outstanding.persist(path, latest).await {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Failed to persist tracked requests to {}", path.display());
+			}
+		}
+	}
+}
+
+/// Query the events of `source` in `from..=to` and fold them into `outstanding`.
+async fn scan(
+	source: &Arc<dyn IsmpProvider>,
+	outstanding: &mut OutstandingRequests,
+	config: &RelayerConfig,
+	from: u64,
+	to: u64,
+) -> Result<(), anyhow::Error> {
+	let state_machine = source.state_machine_id().state_id;
+	let mut start = from;
+	while start <= to {
+		let end = start.saturating_add(SCAN_CHUNK_SIZE - 1).min(to);
+		let update =
+			StateMachineUpdated { state_machine_id: source.state_machine_id(), latest_height: end };
+		let events = source.query_ismp_events(start.saturating_sub(1), update).await?;
+		outstanding.observe(state_machine, config, events);
+		start = end + 1;
+	}
+	Ok(())
+}
+
+/// Deliver timeouts for the requests in `outstanding` that have expired on `source`.
+async fn relay_expired(
+	source: &Arc<dyn IsmpProvider>,
+	hyperbridge: &Arc<dyn IsmpProvider>,
+	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	coprocessor: StateMachine,
+	timeouts: &TimeoutConfig,
+	outstanding: &mut OutstandingRequests,
+	ledger: &mut SpendLedger<(StateMachine, StateMachine)>,
+) -> Result<(), anyhow::Error> {
+	let timestamp = source.query_timestamp().await?;
+	let expired = outstanding.expired(timestamp);
+	if expired.is_empty() {
+		return Ok(());
+	}
+	tracing::trace!(target: LOG_TARGET, source = %source.name(), "{} of {} tracked requests have expired", expired.len(), outstanding.len());
+
+	let mut posts: BTreeMap<StateMachine, Vec<(H256, PostRequest)>> = BTreeMap::new();
+	let mut gets = vec![];
+	for (commitment, request) in expired {
+		match request {
+			Request::Post(post) => posts.entry(post.dest).or_default().push((commitment, post)),
+			Request::Get(get) => gets.push((commitment, get)),
+		}
+	}
+
+	for (dest, requests) in posts {
+		let mut routed = vec![];
+		let mut ready = vec![];
+		for (commitment, post) in requests {
+			match post_status(dest, commitment, hyperbridge, client_map, coprocessor).await {
+				Ok(PostStatus::Delivered) => outstanding.remove(&commitment),
+				Ok(PostStatus::RoutedThroughHyperbridge) => routed.push(post),
+				Ok(PostStatus::Ready) => ready.push(post),
+				Err(err) => {
+					tracing::error!(target: LOG_TARGET, source = %source.name(), %dest, ?err, "Failed to query receipts for expired request {commitment:?}");
+				},
+			}
+		}
+
+		if !routed.is_empty() {
+			match client_map.get(&dest) {
+				Some(dest_client) => {
+					let finalized = source.state_machine_id().state_id == coprocessor;
+					match timeout_on_hyperbridge(dest_client, hyperbridge, coprocessor, &routed)
+						.await
+					{
+						// Requests dispatched on hyperbridge are done once it handles the timeout
+						Ok(true) if finalized =>
+							for post in &routed {
+								outstanding
+									.remove(&hash_request::<Hasher>(&Request::Post(post.clone())));
+							},
+						Ok(_) => {},
+						Err(err) => {
+							tracing::error!(target: LOG_TARGET, source = %source.name(), %dest, ?err, "Failed to deliver {} timeouts to hyperbridge", routed.len());
+						},
+					}
+				},
+				None => {
+					tracing::warn!(target: LOG_TARGET, source = %source.name(), %dest, "{} expired requests were routed to {dest}, which is not configured, their timeouts can't be proven", routed.len());
+				},
+			}
+		}
+
+		if !ready.is_empty() && source.state_machine_id().state_id != coprocessor {
+			let route = (source.state_machine_id().state_id, dest);
+			match timeout_on_source(source, hyperbridge, timeouts, route, ready, ledger).await {
+				Ok(handled) =>
+					for commitment in handled {
+						outstanding.remove(&commitment);
+					},
+				Err(err) => {
+					tracing::error!(target: LOG_TARGET, source = %source.name(), %dest, ?err, "Failed to deliver timeouts to source");
+				},
+			}
+		}
+	}
+
+	if !gets.is_empty() {
+		let mut expired = vec![];
+		for (commitment, get) in gets {
+			match source.query_response_receipt(commitment).await {
+				Ok(receipt) if receipt.iter().any(|byte| *byte != 0) =>
+					outstanding.remove(&commitment),
+				Ok(_) => expired.push(get),
+				Err(err) => {
+					tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Failed to query response receipt for expired request {commitment:?}");
+				},
+			}
+		}
+		let route = (source.state_machine_id().state_id, coprocessor);
+		match submit_get_timeouts(source, timeouts, route, expired, ledger).await {
+			Ok(handled) =>
+				for commitment in handled {
+					outstanding.remove(&commitment);
+				},
+			Err(err) => {
+				tracing::error!(target: LOG_TARGET, source = %source.name(), ?err, "Failed to deliver get timeouts to source");
+			},
+		}
+	}
+
+	Ok(())
+}
+
+/// Find out where an expired post request to `dest` is in the timeout flow.
+async fn post_status(
+	dest: StateMachine,
+	commitment: H256,
+	hyperbridge: &Arc<dyn IsmpProvider>,
+	client_map: &HashMap<StateMachine, Arc<dyn IsmpProvider>>,
+	coprocessor: StateMachine,
+) -> Result<PostStatus, anyhow::Error> {
+	let delivered = |receipt: Vec<u8>| receipt.iter().any(|byte| *byte != 0);
+	let hyperbridge_receipt = delivered(hyperbridge.query_request_receipt(commitment).await?);
+	if dest == coprocessor {
+		return Ok(if hyperbridge_receipt { PostStatus::Delivered } else { PostStatus::Ready });
+	}
+
+	if let Some(dest_client) = client_map.get(&dest) {
+		if delivered(dest_client.query_request_receipt(commitment).await?) {
+			return Ok(PostStatus::Delivered);
+		}
+	}
+
+	Ok(if hyperbridge_receipt { PostStatus::RoutedThroughHyperbridge } else { PostStatus::Ready })
+}
+
+/// Clear hyperbridge's receipts for `requests`, proving their absence on `dest` at the latest
+/// height of `dest` known to hyperbridge. Returns false if that height is not yet past the timeouts
+/// or still in its challenge period.
+async fn timeout_on_hyperbridge(
+	dest: &Arc<dyn IsmpProvider>,
+	hyperbridge: &Arc<dyn IsmpProvider>,
+	coprocessor: StateMachine,
+	requests: &[PostRequest],
+) -> Result<bool, anyhow::Error> {
+	let height = StateMachineHeight {
+		id: dest.state_machine_id(),
+		height: hyperbridge.query_latest_height(dest.state_machine_id()).await?.into(),
+	};
+	let Some(requests) = provable(hyperbridge, height, requests).await? else {
+		return Ok(false);
+	};
+
+	let keys = requests
+		.iter()
+		.flat_map(|post| {
+			dest.request_receipt_full_key(hash_request::<Hasher>(&Request::Post(post.clone())))
+		})
+		.collect();
+	let proof = dest.query_state_proof(height.height, StateProofQueryType::Ismp(keys)).await?;
+	let count = requests.len();
+	let message =
+		Message::Timeout(TimeoutMessage::Post { requests, timeout_proof: Proof { height, proof } });
+	let result = hyperbridge.submit(vec![message], coprocessor).await?;
+	if !result.unsuccessful.is_empty() {
+		return Err(anyhow!("Hyperbridge rejected the timeout message"));
+	}
+
+	tracing::info!(target: LOG_TARGET, dest = %dest.name(), "Cleared {count} expired request receipts on hyperbridge");
+	Ok(true)
+}
+
+/// Deliver timeouts for `requests` to `source`, proving the absence of their receipts on
+/// hyperbridge at the latest height of hyperbridge known to `source`. Returns the commitments of
+/// the timed out requests.
+async fn timeout_on_source(
+	source: &Arc<dyn IsmpProvider>,
+	hyperbridge: &Arc<dyn IsmpProvider>,
+	timeouts: &TimeoutConfig,
+	route: (StateMachine, StateMachine),
+	requests: Vec<PostRequest>,
+	ledger: &mut SpendLedger<(StateMachine, StateMachine)>,
+) -> Result<Vec<H256>, anyhow::Error> {
+	let height = StateMachineHeight {
+		id: hyperbridge.state_machine_id(),
+		height: source.query_latest_height(hyperbridge.state_machine_id()).await?.into(),
+	};
+	let Some(requests) = provable(source, height, &requests).await? else {
+		return Ok(vec![]);
+	};
+
+	let commitments = requests
+		.iter()
+		.map(|post| hash_request::<Hasher>(&Request::Post(post.clone())))
+		.collect::<Vec<_>>();
+	let keys = commitments
+		.iter()
+		.flat_map(|commitment| hyperbridge.request_receipt_full_key(*commitment))
+		.collect();
+	let proof = hyperbridge
+		.query_state_proof(height.height, StateProofQueryType::Ismp(keys))
+		.await?;
+	let message =
+		Message::Timeout(TimeoutMessage::Post { requests, timeout_proof: Proof { height, proof } });
+
+	submit_timeout(source, timeouts, route, message, commitments, ledger).await
+}
+
+/// Deliver proofless timeouts for expired get requests to `source`.
+async fn submit_get_timeouts(
+	source: &Arc<dyn IsmpProvider>,
+	timeouts: &TimeoutConfig,
+	route: (StateMachine, StateMachine),
+	requests: Vec<GetRequest>,
+	ledger: &mut SpendLedger<(StateMachine, StateMachine)>,
+) -> Result<Vec<H256>, anyhow::Error> {
+	if requests.is_empty() {
+		return Ok(vec![]);
+	}
+	let commitments = requests
+		.iter()
+		.map(|get| hash_request::<Hasher>(&Request::Get(get.clone())))
+		.collect::<Vec<_>>();
+	let message = Message::Timeout(TimeoutMessage::Get { requests });
+
+	submit_timeout(source, timeouts, route, message, commitments, ledger).await
+}
+
+/// Simulate a timeout message on `source`, charge its cost to the route's daily spend and submit
+/// it. Returns `commitments` if the timeout was delivered.
+async fn submit_timeout(
+	source: &Arc<dyn IsmpProvider>,
+	timeouts: &TimeoutConfig,
+	route: (StateMachine, StateMachine),
+	message: Message,
+	commitments: Vec<H256>,
+	ledger: &mut SpendLedger<(StateMachine, StateMachine)>,
+) -> Result<Vec<H256>, anyhow::Error> {
+	let (source_state_machine, dest) = route;
+	let estimate = source
+		.estimate_gas(vec![message.clone()])
+		.await?
+		.pop()
+		.ok_or_else(|| anyhow!("Missing gas estimate for timeout message"))?;
+	if !estimate.successful_execution {
+		tracing::warn!(target: LOG_TARGET, source = %source.name(), %dest, "Timeout for {} requests fails in simulation, will retry", commitments.len());
+		return Ok(vec![]);
+	}
+
+	if let Some(cap) = timeouts.daily_spend_cap {
+		let day = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / SECONDS_PER_DAY;
+		if !ledger.try_spend(route, day, estimate.execution_cost, cap.0) {
+			tracing::warn!(target: LOG_TARGET, source = %source.name(), %dest, "Daily timeout spend cap of {} reached, deferring {} timeouts", cap.0, commitments.len());
+			return Ok(vec![]);
+		}
+	}
+
+	let result = source.submit(vec![message], source_state_machine).await?;
+	if !result.unsuccessful.is_empty() {
+		return Err(anyhow!("{} rejected the timeout message", source.name()));
+	}
+
+	tracing::info!(
+		target: LOG_TARGET,
+		source = %source.name(),
+		%dest,
+		cost = %estimate.execution_cost,
+		"Delivered timeouts for {} expired requests",
+		commitments.len(),
+	);
+	Ok(commitments)
+}
+
+/// Filter `requests` down to the ones that the state commitment at `height` on `verifier` can time
+/// out. Returns `None` if the height is still in its challenge period or none of the requests have
+/// expired at the commitment's timestamp.
+async fn provable(
+	verifier: &Arc<dyn IsmpProvider>,
+	height: StateMachineHeight,
+	requests: &[PostRequest],
+) -> Result<Option<Vec<PostRequest>>, anyhow::Error> {
+	let update_time = verifier.query_state_machine_update_time(height).await?;
+	let challenge_period = verifier.query_challenge_period(height.id).await?;
+	if verifier.query_timestamp().await? < update_time + challenge_period {
+		return Ok(None);
+	}
+
+	let timestamp = verifier.query_state_machine_commitment(height).await?.timestamp();
+	let requests = requests
+		.iter()
+		.filter(|post| Request::Post((*post).clone()).timed_out(timestamp))
+		.cloned()
+		.collect::<Vec<_>>();
+	Ok((!requests.is_empty()).then_some(requests))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ismp::events::TimeoutHandled;
+	use tesseract_primitives::mocks::MockHost;
+
+	const HYPERBRIDGE: StateMachine = StateMachine::Polkadot(3367);
+
+	fn post(nonce: u64, timeout_timestamp: u64) -> PostRequest {
+		PostRequest {
+			source: StateMachine::Evm(1),
+			dest: StateMachine::Evm(56),
+			nonce,
+			from: vec![1; 20],
+			to: vec![2; 20],
+			timeout_timestamp,
+			body: vec![],
+		}
+	}
+
+	fn get(source: StateMachine, nonce: u64) -> GetRequest {
+		GetRequest {
+			source,
+			dest: StateMachine::Evm(1),
+			nonce,
+			from: vec![1; 32],
+			keys: vec![],
+			height: 10,
+			context: vec![],
+			timeout_timestamp: 100,
+		}
+	}
+
+	fn commitment(request: Request) -> H256 {
+		hash_request::<Hasher>(&request)
+	}
+
+	/// A chain whose clock is past the timeouts of every request in these tests
+	fn chain(state_machine: StateMachine) -> MockHost<()> {
+		MockHost::new((), 0, state_machine).with_timestamp(Duration::from_secs(200))
+	}
+
+	async fn relay(
+		source: &MockHost<()>,
+		hyperbridge: &MockHost<()>,
+		clients: Vec<MockHost<()>>,
+		outstanding: &mut OutstandingRequests,
+	) -> Result<(), anyhow::Error> {
+		let client_map = clients
+			.into_iter()
+			.map(|client| (client.state_machine, Arc::new(client) as Arc<dyn IsmpProvider>))
+			.collect();
+		relay_expired(
+			&(Arc::new(source.clone()) as Arc<dyn IsmpProvider>),
+			&(Arc::new(hyperbridge.clone()) as Arc<dyn IsmpProvider>),
+			&client_map,
+			HYPERBRIDGE,
+			&TimeoutConfig::default(),
+			outstanding,
+			&mut SpendLedger::default(),
+		)
+		.await
+	}
+
+	#[test]
+	fn tracks_expiring_requests_until_their_timeout_is_handled() {
+		let config = RelayerConfig::default();
+		let mut outstanding = OutstandingRequests::default();
+		let (expiring, eternal, foreign) = (post(0, 100), post(1, 0), post(2, 100));
+		let foreign = PostRequest { source: StateMachine::Evm(10), ..foreign };
+		outstanding.observe(
+			StateMachine::Evm(1),
+			&config,
+			vec![
+				Event::PostRequest(expiring.clone()),
+				Event::PostRequest(eternal),
+				Event::PostRequest(foreign),
+			],
+		);
+		assert_eq!(outstanding.len(), 1);
+		assert!(outstanding.expired(Duration::from_secs(99)).is_empty());
+
+		let expired = outstanding.expired(Duration::from_secs(100));
+		let commitment = hash_request::<Hasher>(&Request::Post(expiring.clone()));
+		assert_eq!(expired.len(), 1);
+		assert_eq!(expired[0].0, commitment);
+
+		outstanding.observe(
+			StateMachine::Evm(1),
+			&config,
+			vec![Event::PostRequestTimeoutHandled(TimeoutHandled {
+				commitment,
+				source: expiring.source,
+				dest: expiring.dest,
+			})],
+		);
+		assert!(outstanding.is_empty());
+	}
+
+	#[test]
+	fn get_requests_are_only_tracked_on_substrate_sources() {
+		let config = RelayerConfig::default();
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(
+			StateMachine::Evm(56),
+			&config,
+			vec![Event::GetRequest(get(StateMachine::Evm(56), 0))],
+		);
+		assert!(outstanding.is_empty());
+
+		outstanding.observe(
+			StateMachine::Polkadot(2000),
+			&config,
+			vec![Event::GetRequest(get(StateMachine::Polkadot(2000), 0))],
+		);
+		assert_eq!(outstanding.len(), 1);
+	}
+
+	#[test]
+	fn module_filter_applies_to_timeouts() {
+		let config = RelayerConfig {
+			module_filter: Some(vec![hex::encode([3u8; 20])]),
+			..Default::default()
+		};
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(StateMachine::Evm(1), &config, vec![Event::PostRequest(post(0, 100))]);
+		assert!(outstanding.is_empty());
+	}
+
+	#[tokio::test]
+	async fn expired_requests_are_timed_out_on_the_source() {
+		let (source, hyperbridge) = (chain(StateMachine::Evm(1)), chain(HYPERBRIDGE));
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(
+			StateMachine::Evm(1),
+			&RelayerConfig::default(),
+			vec![Event::PostRequest(post(0, 100))],
+		);
+
+		relay(&source, &hyperbridge, vec![], &mut outstanding).await.unwrap();
+
+		assert!(outstanding.is_empty());
+		assert!(hyperbridge.submissions().is_empty());
+		let submissions = source.submissions();
+		assert_eq!(submissions.len(), 1);
+		assert!(matches!(
+			&submissions[0][..],
+			[Message::Timeout(TimeoutMessage::Post { requests, .. })] if requests == &vec![post(0, 100)]
+		));
+	}
+
+	#[tokio::test]
+	async fn delivered_requests_are_dropped_and_routed_ones_cleared_on_hyperbridge() {
+		let (delivered, routed) = (post(0, 100), post(1, 100));
+		let (delivered_commitment, routed_commitment) = (
+			commitment(Request::Post(delivered.clone())),
+			commitment(Request::Post(routed.clone())),
+		);
+		let source = chain(StateMachine::Evm(1));
+		let dest = chain(StateMachine::Evm(56)).with_receipt(delivered_commitment, vec![1; 20]);
+		let hyperbridge = chain(HYPERBRIDGE).with_receipt(routed_commitment, vec![1; 32]);
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(
+			StateMachine::Evm(1),
+			&RelayerConfig::default(),
+			vec![Event::PostRequest(delivered), Event::PostRequest(routed.clone())],
+		);
+
+		relay(&source, &hyperbridge, vec![dest], &mut outstanding).await.unwrap();
+
+		// the routed request is only done once the source times it out against hyperbridge
+		let expired = outstanding.expired(Duration::from_secs(200));
+		assert_eq!(expired.len(), 1);
+		assert_eq!(expired[0].0, routed_commitment);
+		assert!(source.submissions().is_empty());
+		let submissions = hyperbridge.submissions();
+		assert_eq!(submissions.len(), 1);
+		assert!(matches!(
+			&submissions[0][..],
+			[Message::Timeout(TimeoutMessage::Post { requests, .. })] if requests == &vec![routed]
+		));
+	}
+
+	#[tokio::test]
+	async fn receipt_query_failures_skip_only_that_request() {
+		let source_chain = StateMachine::Polkadot(2000);
+		let (failing, ready) = (get(source_chain, 0), get(source_chain, 1));
+		let source = chain(source_chain).with_failing_receipt(commitment(Request::Get(failing)));
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(
+			source_chain,
+			&RelayerConfig::default(),
+			vec![Event::GetRequest(get(source_chain, 0)), Event::GetRequest(ready.clone())],
+		);
+
+		relay(&source, &chain(HYPERBRIDGE), vec![], &mut outstanding).await.unwrap();
+
+		// the failing request stays tracked for the next pass
+		let expired = outstanding.expired(Duration::from_secs(200));
+		assert_eq!(expired.len(), 1);
+		assert_eq!(expired[0].0, commitment(Request::Get(get(source_chain, 0))));
+		let submissions = source.submissions();
+		assert_eq!(submissions.len(), 1);
+		assert!(matches!(
+			&submissions[0][..],
+			[Message::Timeout(TimeoutMessage::Get { requests })] if requests == &vec![ready]
+		));
+	}
+
+	#[tokio::test]
+	async fn tracked_requests_survive_restarts() {
+		let path = std::env::temp_dir()
+			.join(format!("tesseract-timeouts-{}", std::process::id()))
+			.join("timeouts-EVM-1");
+		assert!(OutstandingRequests::load(&path).await.unwrap().is_none());
+
+		let mut outstanding = OutstandingRequests::default();
+		outstanding.observe(
+			StateMachine::Evm(1),
+			&RelayerConfig::default(),
+			vec![Event::PostRequest(post(0, 100)), Event::PostRequest(post(1, 300))],
+		);
+		outstanding.persist(&path, 42).await.unwrap();
+
+		let (loaded, scanned) = OutstandingRequests::load(&path).await.unwrap().unwrap();
+		assert_eq!(scanned, 42);
+		assert_eq!(loaded.len(), 2);
+		assert_eq!(
+			loaded.expired(Duration::from_secs(200)),
+			outstanding.expired(Duration::from_secs(200))
+		);
+		tokio::fs::remove_dir_all(path.parent().unwrap()).await.unwrap();
+	}
+}
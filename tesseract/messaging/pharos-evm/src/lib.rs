@@ -170,6 +170,10 @@ impl IsmpProvider for PharosEvmClient {
 		self.evm.query_challenge_period(id).await
 	}
 
+	async fn query_consensus_expiry(&self, id: ConsensusStateId) -> Result<Duration, Error> {
+		self.evm.query_consensus_expiry(id).await
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, Error> {
 		self.evm.query_timestamp().await
 	}
@@ -361,8 +365,8 @@ impl IsmpProvider for PharosEvmClient {
 		self.evm.address()
 	}
 
-	fn sign(&self, msg: &[u8]) -> Signature {
-		self.evm.sign(msg)
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error> {
+		self.evm.sign(msg).await
 	}
 
 	async fn set_latest_finalized_height(
@@ -394,6 +398,10 @@ impl IsmpProvider for PharosEvmClient {
 	async fn fee_token_decimals(&self) -> Result<u8, Error> {
 		self.evm.fee_token_decimals().await
 	}
+
+	async fn gas_price(&self) -> Result<Option<U256>, Error> {
+		self.evm.gas_price().await
+	}
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,267 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signed consensus checkpoints.
+//!
+//! A checkpoint is the [`CreateConsensusState`] message a consensus host produces for a chain,
+//! signed by the relayer that exported it. It is written as JSON, so the consensus state,
+//! unbonding period, challenge periods and state machine commitments can be reviewed before the
+//! client is created, and the same file can be verified against the exporter's key by anyone
+//! importing it. The signature covers the SCALE encoding of the checkpoint, see
+//! [`Checkpoint::signing_hash`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use ismp::{host::StateMachine, messaging::CreateConsensusState};
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sp_core::{ecdsa, sr25519, Pair};
+use sp_crypto_hashing::keccak_256;
+
+use crate::{
+	signer::{KeyType, RelayerSigner},
+	IsmpHost,
+};
+
+/// Domain separator of checkpoint signatures
+const CHECKPOINT_DOMAIN: &[u8] = b"tesseract-consensus-checkpoint";
+
+/// Everything needed to create a consensus client for a chain
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+pub struct Checkpoint {
+	/// The chain the consensus client tracks
+	pub state_machine: StateMachine,
+	/// Unix timestamp of the export, in seconds
+	pub exported_at: u64,
+	/// The message creating the consensus client
+	#[serde(with = "message")]
+	pub message: CreateConsensusState,
+}
+
+impl Checkpoint {
+	/// The 32 byte hash signed by the exporter
+	pub fn signing_hash(&self) -> [u8; 32] {
+		keccak_256(&(CHECKPOINT_DOMAIN, self).encode())
+	}
+
+	/// Check that the checkpoint is younger than the unbonding period at `now`, a unix timestamp
+	/// in seconds. Past it, the validators of the exported consensus state may have unbonded, and
+	/// a client created from it could be fed a fork signed by them.
+	pub fn ensure_fresh(&self, now: u64) -> Result<(), anyhow::Error> {
+		let age = now.saturating_sub(self.exported_at);
+		if age >= self.message.unbonding_period {
+			Err(anyhow!(
+				"Checkpoint was exported {age}s ago, past the unbonding period of {}s",
+				self.message.unbonding_period
+			))?
+		}
+		Ok(())
+	}
+}
+
+/// A checkpoint and the signature of the relayer that exported it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+	/// The checkpoint
+	pub checkpoint: Checkpoint,
+	/// Type of the exporter's key
+	pub key_type: KeyType,
+	/// Public key of the exporter, compressed for secp256k1 keys
+	#[serde(with = "serde_hex_utils::as_hex")]
+	pub signer: Vec<u8>,
+	/// Signature over [`Checkpoint::signing_hash`]
+	#[serde(with = "serde_hex_utils::as_hex")]
+	pub signature: Vec<u8>,
+}
+
+impl SignedCheckpoint {
+	/// Sign `checkpoint` with `signer`
+	pub async fn sign(
+		checkpoint: Checkpoint,
+		signer: &dyn RelayerSigner,
+	) -> Result<Self, anyhow::Error> {
+		let signature = signer.sign(&checkpoint.signing_hash()).await?;
+		Ok(Self { checkpoint, key_type: signer.key_type(), signer: signer.public_key(), signature })
+	}
+
+	/// Check the signature, and that the checkpoint was exported by one of `trusted_signers`
+	pub fn verify(&self, trusted_signers: &[Vec<u8>]) -> Result<&Checkpoint, anyhow::Error> {
+		let hash = self.checkpoint.signing_hash();
+		let valid = match self.key_type {
+			KeyType::Secp256k1 => {
+				let mut signature = <[u8; 65]>::try_from(self.signature.as_slice())
+					.map_err(|_| anyhow!("secp256k1 signatures must be 65 bytes"))?;
+				if signature[64] >= 27 {
+					signature[64] -= 27;
+				}
+				ecdsa::Signature::from_raw(signature)
+					.recover_prehashed(&hash)
+					.is_some_and(|public| public.0.as_slice() == self.signer.as_slice())
+			},
+			KeyType::Sr25519 => {
+				let signature = sr25519::Signature::try_from(self.signature.as_slice())
+					.map_err(|_| anyhow!("sr25519 signatures must be 64 bytes"))?;
+				let public = sr25519::Public::try_from(self.signer.as_slice())
+					.map_err(|_| anyhow!("sr25519 public keys must be 32 bytes"))?;
+				sr25519::Pair::verify(&signature, hash, &public)
+			},
+		};
+		if !valid {
+			Err(anyhow!("Invalid checkpoint signature"))?
+		}
+
+		if !trusted_signers.contains(&self.signer) {
+			Err(anyhow!("Checkpoint was signed by untrusted key 0x{}", hex::encode(&self.signer)))?
+		}
+
+		Ok(&self.checkpoint)
+	}
+}
+
+/// JSON form of [`CreateConsensusState`]. State machines aren't valid JSON object keys, so the
+/// challenge periods are written as a list of pairs.
+mod message {
+	use std::collections::BTreeMap;
+
+	use ismp::{
+		consensus::{ConsensusClientId, ConsensusStateId, StateMachineId},
+		host::StateMachine,
+		messaging::{CreateConsensusState, StateCommitmentHeight},
+	};
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	#[derive(Serialize, Deserialize)]
+	struct Message {
+		#[serde(with = "serde_hex_utils::as_hex")]
+		consensus_state: Vec<u8>,
+		#[serde(with = "serde_hex_utils::as_utf8_string")]
+		consensus_client_id: ConsensusClientId,
+		#[serde(with = "serde_hex_utils::as_utf8_string")]
+		consensus_state_id: ConsensusStateId,
+		unbonding_period: u64,
+		challenge_periods: Vec<(StateMachine, u64)>,
+		state_machine_commitments: Vec<(StateMachineId, StateCommitmentHeight)>,
+	}
+
+	pub fn serialize<S: Serializer>(
+		message: &CreateConsensusState,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		Message {
+			consensus_state: message.consensus_state.clone(),
+			consensus_client_id: message.consensus_client_id,
+			consensus_state_id: message.consensus_state_id,
+			unbonding_period: message.unbonding_period,
+			challenge_periods: message.challenge_periods.clone().into_iter().collect(),
+			state_machine_commitments: message.state_machine_commitments.clone(),
+		}
+		.serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<CreateConsensusState, D::Error> {
+		let message = Message::deserialize(deserializer)?;
+		Ok(CreateConsensusState {
+			consensus_state: message.consensus_state,
+			consensus_client_id: message.consensus_client_id,
+			consensus_state_id: message.consensus_state_id,
+			unbonding_period: message.unbonding_period,
+			challenge_periods: message.challenge_periods.into_iter().collect::<BTreeMap<_, _>>(),
+			state_machine_commitments: message.state_machine_commitments,
+		})
+	}
+}
+
+/// Export a signed checkpoint of the consensus client `host` creates for its chain
+pub async fn export_checkpoint(
+	host: &dyn IsmpHost,
+	signer: &dyn RelayerSigner,
+) -> Result<SignedCheckpoint, anyhow::Error> {
+	let state_machine = host.provider().state_machine_id().state_id;
+	let message = host
+		.query_initial_consensus_state()
+		.await?
+		.ok_or_else(|| anyhow!("{state_machine} does not have a consensus state"))?;
+	let exported_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+	SignedCheckpoint::sign(Checkpoint { state_machine, exported_at, message }, signer).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::signer::LocalSigner;
+
+	fn checkpoint() -> Checkpoint {
+		Checkpoint {
+			state_machine: StateMachine::Evm(1),
+			exported_at: 1_700_000_000,
+			message: CreateConsensusState {
+				consensus_state: vec![1, 2, 3],
+				consensus_client_id: *b"ETH0",
+				consensus_state_id: *b"ETH0",
+				unbonding_period: 27 * 24 * 60 * 60,
+				challenge_periods: [(StateMachine::Evm(1), 300)].into_iter().collect(),
+				state_machine_commitments: vec![],
+			},
+		}
+	}
+
+	#[tokio::test]
+	async fn signed_checkpoints_verify() {
+		for key_type in [KeyType::Secp256k1, KeyType::Sr25519] {
+			let signer = LocalSigner::generate(key_type);
+			let signed = SignedCheckpoint::sign(checkpoint(), &signer).await.unwrap();
+
+			let json = serde_json::to_string_pretty(&signed).unwrap();
+			let decoded: SignedCheckpoint = serde_json::from_str(&json).unwrap();
+			assert_eq!(decoded, signed);
+			assert_eq!(decoded.verify(&[signer.public_key()]).unwrap(), &checkpoint());
+		}
+	}
+
+	#[tokio::test]
+	async fn rejects_tampered_checkpoints() {
+		for key_type in [KeyType::Secp256k1, KeyType::Sr25519] {
+			let signer = LocalSigner::generate(key_type);
+			let mut signed = SignedCheckpoint::sign(checkpoint(), &signer).await.unwrap();
+
+			signed.checkpoint.message.unbonding_period += 1;
+			assert!(signed.verify(&[signer.public_key()]).is_err());
+		}
+	}
+
+	#[tokio::test]
+	async fn rejects_untrusted_signers() {
+		let signer = LocalSigner::generate(KeyType::Sr25519);
+		let other = LocalSigner::generate(KeyType::Sr25519);
+		let signed = SignedCheckpoint::sign(checkpoint(), &signer).await.unwrap();
+
+		assert!(signed.verify(&[other.public_key()]).is_err());
+		assert!(signed.verify(&[]).is_err());
+	}
+
+	#[test]
+	fn rejects_checkpoints_older_than_the_unbonding_period() {
+		let checkpoint = checkpoint();
+		let unbonding_period = checkpoint.message.unbonding_period;
+
+		assert!(checkpoint.ensure_fresh(checkpoint.exported_at).is_ok());
+		assert!(checkpoint.ensure_fresh(checkpoint.exported_at + unbonding_period - 1).is_ok());
+		assert!(checkpoint.ensure_fresh(checkpoint.exported_at + unbonding_period).is_err());
+	}
+}
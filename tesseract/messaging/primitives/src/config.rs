@@ -15,6 +15,7 @@
 
 //! Relayer configuration options
 
+use crate::policy::{RoutePolicy, UsdAmount};
 use serde::{Deserialize, Serialize};
 
 /// Configuration options for the relayer.
@@ -31,10 +32,42 @@ pub struct RelayerConfig {
 	/// How frequently to retry unprofitable or failed messages in seconds.
 	/// If this is value not supplied retries will not be enabled
 	pub unprofitable_retry_frequency: Option<u64>,
+	/// How frequently to check unprofitable messages for fee bumps in seconds. A message whose
+	/// fee was raised on its source chain is retried right away instead of waiting for the next
+	/// `unprofitable_retry_frequency` tick. Defaults to `unprofitable_retry_frequency`.
+	pub fee_bump_poll_frequency: Option<u64>,
+	/// How frequently to scan for requests the relayer moved past without delivering, in
+	/// seconds. If this value is not supplied the gap detector is not enabled
+	pub gap_scan_frequency: Option<u64>,
+	/// Number of source chain heights behind the latest height the gap detector scans on its first
+	/// pass after startup.
+	pub gap_scan_lookback: Option<u64>,
 	/// Delivery endpoints: chains you intend to deliver messages to
 	pub delivery_endpoints: Vec<String>,
 	/// Flag to tell the messsaging process to deliver failed transactions
 	pub deliver_failed: Option<bool>,
 	/// Should the relayer run the fee accumulation task?
 	pub disable_fee_accumulation: Option<bool>,
+	/// Per-route delivery rules, messages that match no route fall back to
+	/// `minimum_profit_percentage`.
+	#[serde(default)]
+	pub policy: RoutePolicy,
+	/// Deliver timeouts for expired requests back to their source chain. Timeout relaying is
+	/// disabled if this section is not supplied.
+	pub timeouts: Option<TimeoutConfig>,
+}
+
+/// Options for the timeout relaying task.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TimeoutConfig {
+	/// How frequently to check outstanding requests for expiry, in seconds. Defaults to 300.
+	pub frequency: Option<u64>,
+	/// Number of source chain heights behind the finalized height scanned for outstanding
+	/// requests on startup. Defaults to 10,000.
+	pub lookback: Option<u64>,
+	/// Maximum amount in USD spent on timeout deliveries per route per UTC day.
+	pub daily_spend_cap: Option<UsdAmount>,
+	/// Directory the outstanding requests of each chain are persisted in. When set, a restart
+	/// resumes tracking from the last scanned height instead of rescanning `lookback` heights.
+	pub state_dir: Option<String>,
 }
@@ -0,0 +1,286 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consensus client expiry tracking.
+//!
+//! A consensus client that isn't updated within its unbonding period expires, and can then only
+//! be recovered through governance. Hosts that only update their counterparties when there are
+//! messages to prove can let a client on a quiet route expire. The tracker polls the time left
+//! before each client a relayer serves expires, warns once it is within
+//! [`ExpiryConfig::warning_margin`], and forces an update through
+//! [`IsmpHost::refresh_consensus`] once it is within [`ExpiryConfig::refresh_margin`].
+
+use std::{
+	net::SocketAddr,
+	sync::{Arc, OnceLock},
+	time::Duration,
+};
+
+use ismp::consensus::ConsensusStateId;
+use polkadot_sdk::substrate_prometheus_endpoint::{
+	register, CounterVec, GaugeVec, Opts, PrometheusError, U64,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{IsmpHost, IsmpProvider};
+
+pub use polkadot_sdk::substrate_prometheus_endpoint::Registry;
+
+/// Default time before expiry at which an update is forced, 1 day
+const DEFAULT_REFRESH_MARGIN: u64 = 24 * 60 * 60;
+
+/// Default time before expiry at which warnings are logged, 3 days
+const DEFAULT_WARNING_MARGIN: u64 = 3 * 24 * 60 * 60;
+
+/// Default interval between checks, 10 minutes
+const DEFAULT_FREQUENCY: u64 = 10 * 60;
+
+/// Outcome of [`IsmpHost::refresh_consensus`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusRefresh {
+	/// A consensus update was submitted to the counterparty
+	Refreshed,
+	/// The counterparty's client is already at the latest height this host can prove
+	UpToDate,
+	/// This host cannot force updates, its counterparties are only updated alongside messages
+	Unsupported,
+}
+
+/// Options for the expiry tracker, all values are in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExpiryConfig {
+	/// Force a consensus update once a client is this close to expiring. Defaults to 1 day.
+	pub refresh_margin: Option<u64>,
+	/// Warn once a client is this close to expiring. Defaults to 3 days.
+	pub warning_margin: Option<u64>,
+	/// How frequently to check the time left before each client expires. Defaults to 600.
+	pub frequency: Option<u64>,
+}
+
+impl ExpiryConfig {
+	/// Classify the time left before a client expires
+	pub fn status(&self, remaining: Duration) -> ExpiryStatus {
+		let remaining = remaining.as_secs();
+		if remaining == 0 {
+			ExpiryStatus::Expired
+		} else if remaining <= self.refresh_margin.unwrap_or(DEFAULT_REFRESH_MARGIN) {
+			ExpiryStatus::Refresh
+		} else if remaining <= self.warning_margin.unwrap_or(DEFAULT_WARNING_MARGIN) {
+			ExpiryStatus::Warning
+		} else {
+			ExpiryStatus::Healthy
+		}
+	}
+
+	fn frequency(&self) -> Duration {
+		Duration::from_secs(self.frequency.unwrap_or(DEFAULT_FREQUENCY))
+	}
+}
+
+/// How close a consensus client is to expiring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryStatus {
+	/// Outside the warning margin
+	Healthy,
+	/// Inside the warning margin
+	Warning,
+	/// Inside the refresh margin, an update should be forced
+	Refresh,
+	/// The unbonding period has elapsed
+	Expired,
+}
+
+/// Prometheus metrics reported by the expiry trackers
+pub struct ExpiryMetrics {
+	seconds_to_expiry: GaugeVec<U64>,
+	near_expiry: GaugeVec<U64>,
+	forced_updates: CounterVec<U64>,
+}
+
+static METRICS: OnceLock<ExpiryMetrics> = OnceLock::new();
+
+impl ExpiryMetrics {
+	/// Register the metrics with `registry`, and report to them from every tracker in this
+	/// process. Only the first registration takes effect.
+	pub fn register(registry: &Registry) -> Result<(), PrometheusError> {
+		let labels = &["state_machine", "counterparty"];
+		let metrics = ExpiryMetrics {
+			seconds_to_expiry: register(
+				GaugeVec::new(
+					Opts::new(
+						"tesseract_consensus_seconds_to_expiry",
+						"Seconds left before the consensus client expires on the counterparty",
+					),
+					labels,
+				)?,
+				registry,
+			)?,
+			near_expiry: register(
+				GaugeVec::new(
+					Opts::new(
+						"tesseract_consensus_near_expiry",
+						"1 if the consensus client is within the warning margin of expiring",
+					),
+					labels,
+				)?,
+				registry,
+			)?,
+			forced_updates: register(
+				CounterVec::new(
+					Opts::new(
+						"tesseract_consensus_forced_updates_total",
+						"Consensus updates forced because the client was about to expire",
+					),
+					labels,
+				)?,
+				registry,
+			)?,
+		};
+		let _ = METRICS.set(metrics);
+		Ok(())
+	}
+
+	fn global() -> Option<&'static ExpiryMetrics> {
+		METRICS.get()
+	}
+}
+
+/// Serve the metrics in `registry` on `address`. Never returns unless the server fails.
+pub async fn serve_metrics(address: SocketAddr, registry: Registry) -> Result<(), anyhow::Error> {
+	polkadot_sdk::substrate_prometheus_endpoint::init_prometheus(address, registry).await?;
+	Ok(())
+}
+
+/// Time left before the consensus client `consensus_state_id` expires on `counterparty`
+pub async fn time_to_expiry(
+	counterparty: &dyn IsmpProvider,
+	consensus_state_id: ConsensusStateId,
+) -> Result<Duration, anyhow::Error> {
+	let expiry = counterparty.query_consensus_expiry(consensus_state_id).await?;
+	let now = counterparty.query_timestamp().await?;
+	Ok(expiry.saturating_sub(now))
+}
+
+/// Track the expiry of `host`'s consensus client on `counterparty`, forcing an update when it is
+/// about to expire. Never returns.
+pub async fn track_expiry(
+	host: Arc<dyn IsmpHost>,
+	counterparty: Arc<dyn IsmpProvider>,
+	config: ExpiryConfig,
+) -> Result<(), anyhow::Error> {
+	let mut interval = tokio::time::interval(config.frequency());
+	loop {
+		interval.tick().await;
+		if let Err(err) = check_expiry(&*host, counterparty.clone(), &config).await {
+			tracing::error!(
+				target: crate::LOG_TARGET,
+				state_machine = %host.provider().state_machine_id().state_id,
+				counterparty = %counterparty.state_machine_id().state_id,
+				"Failed to check consensus client expiry: {err:?}",
+			);
+		}
+	}
+}
+
+/// Check the expiry of `host`'s consensus client on `counterparty` once
+pub async fn check_expiry(
+	host: &dyn IsmpHost,
+	counterparty: Arc<dyn IsmpProvider>,
+	config: &ExpiryConfig,
+) -> Result<ExpiryStatus, anyhow::Error> {
+	let id = host.provider().state_machine_id();
+	let state_machine = id.state_id.to_string();
+	let counterparty_name = counterparty.state_machine_id().state_id.to_string();
+	let labels = [state_machine.as_str(), counterparty_name.as_str()];
+
+	let remaining = time_to_expiry(&*counterparty, id.consensus_state_id).await?;
+	let status = config.status(remaining);
+	if let Some(metrics) = ExpiryMetrics::global() {
+		metrics.seconds_to_expiry.with_label_values(&labels).set(remaining.as_secs());
+		let near_expiry = status != ExpiryStatus::Healthy;
+		metrics.near_expiry.with_label_values(&labels).set(near_expiry as u64);
+	}
+
+	let hours = remaining.as_secs() / 3600;
+	match status {
+		ExpiryStatus::Healthy => {
+			tracing::trace!(
+				target: crate::LOG_TARGET,
+				"{state_machine} client on {counterparty_name} expires in {hours}h",
+			);
+		},
+		ExpiryStatus::Warning => {
+			tracing::warn!(
+				target: crate::LOG_TARGET,
+				"{state_machine} client on {counterparty_name} expires in {hours}h",
+			);
+		},
+		ExpiryStatus::Refresh => match host.refresh_consensus(counterparty.clone()).await? {
+			ConsensusRefresh::Refreshed => {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					"{state_machine} client on {counterparty_name} expires in {hours}h, forced an update",
+				);
+				if let Some(metrics) = ExpiryMetrics::global() {
+					metrics.forced_updates.with_label_values(&labels).inc();
+				}
+			},
+			ConsensusRefresh::UpToDate | ConsensusRefresh::Unsupported => {
+				tracing::warn!(
+					target: crate::LOG_TARGET,
+					"{state_machine} client on {counterparty_name} expires in {hours}h",
+				);
+			},
+		},
+		ExpiryStatus::Expired => {
+			tracing::error!(
+				target: crate::LOG_TARGET,
+				"{state_machine} client on {counterparty_name} has expired, it can only be recovered through governance",
+			);
+		},
+	}
+
+	Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn classifies_time_to_expiry() {
+		let config = ExpiryConfig {
+			refresh_margin: Some(100),
+			warning_margin: Some(1_000),
+			frequency: None,
+		};
+
+		assert_eq!(config.status(Duration::ZERO), ExpiryStatus::Expired);
+		assert_eq!(config.status(Duration::from_secs(1)), ExpiryStatus::Refresh);
+		assert_eq!(config.status(Duration::from_secs(100)), ExpiryStatus::Refresh);
+		assert_eq!(config.status(Duration::from_secs(101)), ExpiryStatus::Warning);
+		assert_eq!(config.status(Duration::from_secs(1_000)), ExpiryStatus::Warning);
+		assert_eq!(config.status(Duration::from_secs(1_001)), ExpiryStatus::Healthy);
+	}
+
+	#[test]
+	fn defaults_refresh_a_day_before_expiry() {
+		let config = ExpiryConfig::default();
+
+		assert_eq!(config.status(Duration::from_secs(23 * 3600)), ExpiryStatus::Refresh);
+		assert_eq!(config.status(Duration::from_secs(2 * 24 * 3600)), ExpiryStatus::Warning);
+		assert_eq!(config.status(Duration::from_secs(4 * 24 * 3600)), ExpiryStatus::Healthy);
+	}
+}
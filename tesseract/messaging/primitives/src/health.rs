@@ -0,0 +1,443 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Health tracking for chains configured with several RPC endpoints.
+//!
+//! An [`EndpointPool`] scores every endpoint of a chain by its error rate, latency and how far its
+//! head trails the best head seen across the pool. Clients ask the pool for the order in which to
+//! try endpoints for a read, and for the endpoints a transaction should be broadcast to.
+//!
+//! Each endpoint has a circuit breaker. It opens after a number of consecutive failures and the
+//! endpoint is skipped until the cooldown expires, after which a single trial request decides
+//! whether it closes again. The trial is routed ahead of every other endpoint, and no other request
+//! reaches the endpoint until its outcome is recorded, either by the trial itself or by the next
+//! head probe. An endpoint that keeps failing its trials is flapping, and its cooldown
+//! doubles on every failed trial up to `max_cooldown`.
+//!
+//! ```toml
+//! [ethereum.rpc_health]
+//! failure_threshold = 3
+//! cooldown = 30
+//! max_head_lag = 5
+//! broadcast = 3
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{
+	future::Future,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+/// Consecutive failures that open an endpoint's circuit breaker.
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Seconds an opened circuit breaker stays open before its first trial request.
+const DEFAULT_COOLDOWN: u64 = 30;
+
+/// Upper bound in seconds on the cooldown of a flapping endpoint.
+const DEFAULT_MAX_COOLDOWN: u64 = 600;
+
+/// Blocks an endpoint's head may trail the best known head before it's deprioritized.
+const DEFAULT_MAX_HEAD_LAG: u64 = 5;
+
+/// Number of endpoints a transaction is broadcast to.
+const DEFAULT_BROADCAST: usize = 3;
+
+/// Seconds between head probes of every endpoint.
+const DEFAULT_PROBE_INTERVAL: u64 = 12;
+
+/// Weight of the latest sample in the latency and error rate moving averages.
+const SMOOTHING: f64 = 0.2;
+
+/// How much a fully failing endpoint's latency is inflated when ranking endpoints.
+const ERROR_PENALTY: f64 = 10.0;
+
+/// Health tracking of a chain's RPC endpoints.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointHealthConfig {
+	/// Consecutive failures after which an endpoint is taken out of rotation, defaults to 3
+	pub failure_threshold: Option<u32>,
+	/// Seconds a failing endpoint is taken out of rotation for, defaults to 30. Doubled every
+	/// time the endpoint fails again right after its cooldown.
+	pub cooldown: Option<u64>,
+	/// Upper bound in seconds on the cooldown of a flapping endpoint, defaults to 600
+	pub max_cooldown: Option<u64>,
+	/// Blocks an endpoint may trail the most recent head across all endpoints before reads are
+	/// routed to it last, defaults to 5
+	pub max_head_lag: Option<u64>,
+	/// Number of endpoints transactions are broadcast to, defaults to 3
+	pub broadcast: Option<usize>,
+	/// Seconds between head and latency probes of every endpoint, defaults to 12
+	pub probe_interval: Option<u64>,
+}
+
+impl EndpointHealthConfig {
+	fn failure_threshold(&self) -> u32 {
+		self.failure_threshold.unwrap_or(DEFAULT_FAILURE_THRESHOLD).max(1)
+	}
+
+	fn cooldown(&self, trips: u32) -> Duration {
+		let base = self.cooldown.unwrap_or(DEFAULT_COOLDOWN);
+		let max = self.max_cooldown.unwrap_or(DEFAULT_MAX_COOLDOWN).max(base);
+		let cooldown = base.saturating_mul(1u64 << trips.saturating_sub(1).min(32));
+		Duration::from_secs(cooldown.min(max))
+	}
+
+	fn max_head_lag(&self) -> u64 {
+		self.max_head_lag.unwrap_or(DEFAULT_MAX_HEAD_LAG)
+	}
+
+	fn broadcast(&self) -> usize {
+		self.broadcast.unwrap_or(DEFAULT_BROADCAST).max(1)
+	}
+
+	fn probe_interval(&self) -> Duration {
+		Duration::from_secs(self.probe_interval.unwrap_or(DEFAULT_PROBE_INTERVAL).max(1))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Breaker {
+	/// Requests are routed to the endpoint
+	Closed,
+	/// The endpoint is skipped until `until`. `trips` counts the times it was opened since the
+	/// endpoint last succeeded.
+	Open { until: Instant, trips: u32 },
+	/// The cooldown expired and a trial request was routed to the endpoint, its outcome decides
+	/// whether the breaker closes or opens again
+	HalfOpen { trips: u32 },
+}
+
+#[derive(Debug, Clone)]
+struct Endpoint {
+	url: String,
+	/// Moving average of request latency in milliseconds, `None` until the first success
+	latency: Option<f64>,
+	/// Moving average of the share of failed requests
+	error_rate: f64,
+	consecutive_failures: u32,
+	/// Latest head reported by a probe
+	head: Option<u64>,
+	breaker: Breaker,
+}
+
+/// Health of the RPC endpoints of a single chain.
+#[derive(Debug)]
+pub struct EndpointPool {
+	config: EndpointHealthConfig,
+	endpoints: Mutex<Vec<Endpoint>>,
+}
+
+impl EndpointPool {
+	/// Track the health of `urls`, in order of preference while no requests have been made.
+	pub fn new(urls: Vec<String>, config: EndpointHealthConfig) -> Self {
+		let endpoints = urls
+			.into_iter()
+			.map(|url| Endpoint {
+				url,
+				latency: None,
+				error_rate: 0.0,
+				consecutive_failures: 0,
+				head: None,
+				breaker: Breaker::Closed,
+			})
+			.collect();
+		Self { config, endpoints: Mutex::new(endpoints) }
+	}
+
+	/// Number of endpoints in the pool.
+	pub fn len(&self) -> usize {
+		self.endpoints.lock().expect("not poisoned").len()
+	}
+
+	/// Returns true if the pool has no endpoints.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Url of the endpoint at `index`.
+	pub fn url(&self, index: usize) -> String {
+		self.endpoints.lock().expect("not poisoned")[index].url.clone()
+	}
+
+	/// Indices of the endpoints in the order they should be tried for a read. Endpoints whose
+	/// cooldown just expired come first, as this is their single trial. Endpoints with an open
+	/// breaker or a trial in flight are left out, unless no other endpoint is available, in which
+	/// case all endpoints are returned with the ones closest to their trial first.
+	pub fn ranked(&self) -> Vec<usize> {
+		self.ranked_at(Instant::now())
+	}
+
+	/// The healthiest endpoints, that a transaction should be broadcast to.
+	pub fn broadcast_targets(&self) -> Vec<usize> {
+		let mut ranked = self.ranked();
+		ranked.truncate(self.config.broadcast());
+		ranked
+	}
+
+	/// Record a successful request to the endpoint at `index`.
+	pub fn record_success(&self, index: usize, latency: Duration) {
+		let mut endpoints = self.endpoints.lock().expect("not poisoned");
+		let endpoint = &mut endpoints[index];
+		let latency = latency.as_secs_f64() * 1000.0;
+		endpoint.latency = Some(match endpoint.latency {
+			Some(average) => average + SMOOTHING * (latency - average),
+			None => latency,
+		});
+		endpoint.error_rate -= SMOOTHING * endpoint.error_rate;
+		endpoint.consecutive_failures = 0;
+		if endpoint.breaker != Breaker::Closed {
+			tracing::info!(target: crate::LOG_TARGET, url = %endpoint.url, "rpc endpoint recovered");
+			endpoint.breaker = Breaker::Closed;
+		}
+	}
+
+	/// Record a failed request to the endpoint at `index`.
+	pub fn record_failure(&self, index: usize) {
+		self.record_failure_at(index, Instant::now())
+	}
+
+	/// Record the head reported by the endpoint at `index`.
+	pub fn record_head(&self, index: usize, head: u64) {
+		let mut endpoints = self.endpoints.lock().expect("not poisoned");
+		endpoints[index].head = Some(head);
+	}
+
+	/// Probe every endpoint for its head at the configured interval, recording the latency and
+	/// outcome of each probe. Probes stop once the pool is dropped.
+	pub fn spawn_probe<F, Fut>(self: &Arc<Self>, probe: F)
+	where
+		F: Fn(usize) -> Fut + Send + 'static,
+		Fut: Future<Output = anyhow::Result<u64>> + Send + 'static,
+	{
+		let pool = Arc::downgrade(self);
+		let interval = self.config.probe_interval();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(interval);
+			loop {
+				interval.tick().await;
+				let Some(len) = pool.upgrade().map(|pool| pool.len()) else { return };
+				let probes = (0..len).map(|index| {
+					let started = Instant::now();
+					let probe = probe(index);
+					async move { (index, probe.await, started.elapsed()) }
+				});
+				let results = futures::future::join_all(probes).await;
+
+				let Some(pool) = pool.upgrade() else { return };
+				for (index, result, latency) in results {
+					match result {
+						Ok(head) => {
+							pool.record_success(index, latency);
+							pool.record_head(index, head);
+						},
+						Err(err) => {
+							tracing::debug!(target: crate::LOG_TARGET, url = %pool.url(index), ?err, "rpc endpoint probe failed");
+							pool.record_failure(index);
+						},
+					}
+				}
+			}
+		});
+	}
+
+	fn ranked_at(&self, now: Instant) -> Vec<usize> {
+		let mut endpoints = self.endpoints.lock().expect("not poisoned");
+		let best_head = endpoints.iter().filter_map(|endpoint| endpoint.head).max();
+		let max_head_lag = self.config.max_head_lag();
+
+		let mut trials = vec![];
+		let mut available = vec![];
+		for (index, endpoint) in endpoints.iter_mut().enumerate() {
+			match endpoint.breaker {
+				Breaker::Open { until, .. } if until > now => continue,
+				Breaker::Open { trips, .. } => {
+					endpoint.breaker = Breaker::HalfOpen { trips };
+					trials.push(index);
+					continue;
+				},
+				Breaker::HalfOpen { .. } => continue,
+				Breaker::Closed => {},
+			}
+			let lagging = match (best_head, endpoint.head) {
+				(Some(best), Some(head)) => best.saturating_sub(head) > max_head_lag,
+				_ => false,
+			};
+			// Endpoints without a latency sample yet are tried first so they get one
+			let score =
+				endpoint.latency.unwrap_or_default() * (1.0 + ERROR_PENALTY * endpoint.error_rate);
+			available.push((lagging, score, index));
+		}
+
+		if trials.is_empty() && available.is_empty() {
+			let mut open = endpoints
+				.iter()
+				.enumerate()
+				.map(|(index, endpoint)| match endpoint.breaker {
+					Breaker::Open { until, .. } => (until, index),
+					_ => (now, index),
+				})
+				.collect::<Vec<_>>();
+			open.sort();
+			return open.into_iter().map(|(_, index)| index).collect();
+		}
+
+		available.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)));
+		trials
+			.into_iter()
+			.chain(available.into_iter().map(|(_, _, index)| index))
+			.collect()
+	}
+
+	fn record_failure_at(&self, index: usize, now: Instant) {
+		let mut endpoints = self.endpoints.lock().expect("not poisoned");
+		let endpoint = &mut endpoints[index];
+		endpoint.error_rate += SMOOTHING * (1.0 - endpoint.error_rate);
+		endpoint.consecutive_failures += 1;
+
+		let trips = match endpoint.breaker {
+			// A failed trial reopens the breaker straight away
+			Breaker::HalfOpen { trips } => trips + 1,
+			Breaker::Closed if endpoint.consecutive_failures >= self.config.failure_threshold() =>
+				1,
+			_ => return,
+		};
+		let cooldown = self.config.cooldown(trips);
+		tracing::warn!(
+			target: crate::LOG_TARGET,
+			url = %endpoint.url,
+			failures = endpoint.consecutive_failures,
+			cooldown = cooldown.as_secs(),
+			"rpc endpoint taken out of rotation",
+		);
+		endpoint.breaker = Breaker::Open { until: now + cooldown, trips };
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn pool(urls: usize) -> EndpointPool {
+		EndpointPool::new(
+			(0..urls).map(|i| format!("https://rpc-{i}")).collect(),
+			EndpointHealthConfig::default(),
+		)
+	}
+
+	#[test]
+	fn ranks_by_latency_and_errors() {
+		let pool = pool(3);
+		assert_eq!(pool.ranked(), vec![0, 1, 2]);
+
+		pool.record_success(0, Duration::from_millis(300));
+		pool.record_success(1, Duration::from_millis(50));
+		pool.record_success(2, Duration::from_millis(100));
+		assert_eq!(pool.ranked(), vec![1, 2, 0]);
+
+		// A single failure is not enough to open the breaker, but it costs the endpoint its spot
+		pool.record_failure(1);
+		assert_eq!(pool.ranked(), vec![2, 1, 0]);
+	}
+
+	#[test]
+	fn lagging_endpoints_are_tried_last() {
+		let pool = pool(2);
+		pool.record_success(0, Duration::from_millis(10));
+		pool.record_success(1, Duration::from_millis(100));
+		pool.record_head(0, 100);
+		pool.record_head(1, 110);
+		assert_eq!(pool.ranked(), vec![1, 0]);
+
+		pool.record_head(0, 106);
+		assert_eq!(pool.ranked(), vec![0, 1]);
+	}
+
+	#[test]
+	fn breaker_opens_and_recovers() {
+		let pool = pool(2);
+		pool.record_success(1, Duration::from_millis(200));
+		let now = Instant::now();
+		for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+			pool.record_failure_at(0, now);
+		}
+		assert_eq!(pool.ranked_at(now), vec![1]);
+		assert_eq!(pool.broadcast_targets(), vec![1]);
+
+		// The cooldown expired, the endpoint gets a trial and closes again once it succeeds
+		let later = now + Duration::from_secs(DEFAULT_COOLDOWN);
+		assert!(pool.ranked_at(later).contains(&0));
+		pool.record_success(0, Duration::from_millis(10));
+		assert_eq!(pool.ranked_at(later), vec![0, 1]);
+	}
+
+	#[test]
+	fn half_open_endpoints_get_a_single_trial() {
+		let pool = pool(2);
+		pool.record_success(0, Duration::from_millis(10));
+		pool.record_success(1, Duration::from_millis(200));
+		let now = Instant::now();
+		for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+			pool.record_failure_at(0, now);
+		}
+
+		// The trial goes first, and no other request reaches the endpoint while it's in flight
+		let later = now + Duration::from_secs(DEFAULT_COOLDOWN);
+		assert_eq!(pool.ranked_at(later), vec![0, 1]);
+		assert_eq!(pool.ranked_at(later), vec![1]);
+		assert_eq!(pool.ranked_at(later + Duration::from_secs(1)), vec![1]);
+
+		// A failed trial opens the breaker again, a successful one closes it
+		pool.record_failure_at(0, later);
+		assert_eq!(pool.ranked_at(later + Duration::from_secs(1)), vec![1]);
+		let trial = later + Duration::from_secs(DEFAULT_COOLDOWN * 2);
+		assert_eq!(pool.ranked_at(trial), vec![0, 1]);
+		pool.record_success(0, Duration::from_millis(10));
+		assert_eq!(pool.ranked_at(trial), vec![0, 1]);
+		assert_eq!(pool.ranked_at(trial), vec![0, 1]);
+	}
+
+	#[test]
+	fn flapping_endpoints_back_off() {
+		let pool = pool(2);
+		let mut now = Instant::now();
+		for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+			pool.record_failure_at(0, now);
+		}
+
+		// Every failed trial doubles the cooldown
+		for cooldown in [DEFAULT_COOLDOWN, DEFAULT_COOLDOWN * 2, DEFAULT_COOLDOWN * 4] {
+			assert_eq!(pool.ranked_at(now + Duration::from_secs(cooldown - 1)), vec![1]);
+			now += Duration::from_secs(cooldown);
+			assert_eq!(pool.ranked_at(now), vec![0, 1]);
+			pool.record_failure_at(0, now);
+		}
+		assert_eq!(pool.ranked_at(now + Duration::from_secs(DEFAULT_COOLDOWN * 8 - 1)), vec![1]);
+	}
+
+	#[test]
+	fn falls_back_to_open_endpoints() {
+		let pool = pool(2);
+		let now = Instant::now();
+		for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+			pool.record_failure_at(1, now);
+		}
+		for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+			pool.record_failure_at(0, now + Duration::from_secs(1));
+		}
+		assert_eq!(pool.ranked_at(now), vec![1, 0]);
+	}
+}
@@ -17,13 +17,20 @@
 
 /// Log/tracing target for this crate.
 pub const LOG_TARGET: &str = "messaging-primitives";
+pub mod checkpoint;
 pub mod config;
+pub mod expiry;
+pub mod health;
 #[cfg(feature = "testing")]
 pub mod mocks;
+pub mod policy;
+pub mod proof_cache;
 pub mod queue;
 pub mod serde_adapters;
+pub mod signer;
 
 use anyhow::anyhow;
+pub use expiry::ConsensusRefresh;
 use futures::{Stream, StreamExt};
 pub use ismp::events::StateMachineUpdated;
 
@@ -384,6 +391,11 @@ pub trait IsmpProvider: ByzantineHandler + Send + Sync {
 	/// Query the challenge period for client
 	async fn query_challenge_period(&self, id: StateMachineId) -> Result<Duration, anyhow::Error>;
 
+	/// Query the timestamp at which the consensus client `id` expires on this chain, unless it is
+	/// updated before then
+	async fn query_consensus_expiry(&self, id: ConsensusStateId)
+		-> Result<Duration, anyhow::Error>;
+
 	/// Query the latest timestamp for chain
 	async fn query_timestamp(&self) -> Result<Duration, anyhow::Error>;
 
@@ -552,8 +564,8 @@ pub trait IsmpProvider: ByzantineHandler + Send + Sync {
 	/// Relayer's address on this chain
 	fn address(&self) -> Vec<u8>;
 
-	/// Sign a prehashed message using the Relayer's private key
-	fn sign(&self, msg: &[u8]) -> Signature;
+	/// Sign a prehashed message using the Relayer's signer
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error>;
 
 	/// Set the initial height with the finalized height on counterparty
 	async fn set_latest_finalized_height(
@@ -582,6 +594,12 @@ pub trait IsmpProvider: ByzantineHandler + Send + Sync {
 	}
 
 	async fn fee_token_decimals(&self) -> Result<u8, anyhow::Error>;
+
+	/// Current gas price on this chain in wei, used to enforce the `maximum_gas_price` of the
+	/// relayer's route policy. Chains without a meaningful gas price return `None`.
+	async fn gas_price(&self) -> Result<Option<U256>, anyhow::Error> {
+		Ok(None)
+	}
 }
 
 /// Provides an interface for handling byzantine behaviour. Implementations of this should watch for
@@ -637,6 +655,17 @@ pub trait IsmpHost: Send + Sync {
 	) -> anyhow::Result<()> {
 		Ok(())
 	}
+
+	/// Submit a consensus update for this host's chain to the counterparty, even if there are no
+	/// messages to deliver. Called by the expiry tracker when the counterparty's client is about
+	/// to expire. The default returns [`ConsensusRefresh::Unsupported`], hosts that only update
+	/// their counterparties when there are messages to prove override this.
+	async fn refresh_consensus(
+		&self,
+		_counterparty: Arc<dyn IsmpProvider>,
+	) -> anyhow::Result<ConsensusRefresh> {
+		Ok(ConsensusRefresh::Unsupported)
+	}
 }
 
 #[async_trait::async_trait]
@@ -15,6 +15,7 @@ use pallet_ismp_relayer::withdrawal::WithdrawalProof;
 use parity_scale_codec::Codec;
 use primitive_types::{H256, U256};
 use std::{
+	collections::{HashMap, HashSet},
 	sync::{Arc, Mutex},
 	time::Duration,
 };
@@ -34,6 +35,19 @@ pub struct MockHost<C> {
 	pub name: Arc<Mutex<String>>,
 	/// The consensus state id embedded in [`IsmpProvider::state_machine_id`].
 	pub consensus_state_id: Arc<Mutex<ConsensusStateId>>,
+	/// The timestamp returned by [`IsmpProvider::query_timestamp`], also used as the timestamp
+	/// of every state commitment.
+	pub timestamp: Arc<Mutex<Duration>>,
+	/// Request and response receipts by commitment, missing receipts are empty.
+	pub receipts: Arc<Mutex<HashMap<H256, Vec<u8>>>>,
+	/// Commitments whose receipt queries return an error.
+	pub failing_receipts: Arc<Mutex<HashSet<H256>>>,
+	/// Events returned by [`IsmpProvider::query_ismp_events`], keyed by the height they were
+	/// emitted at.
+	pub events: Arc<Mutex<Vec<(u64, Event)>>>,
+	/// Records the `(previous_height, latest_height)` of every
+	/// [`IsmpProvider::query_ismp_events`] call.
+	pub event_queries: Arc<Mutex<Vec<(u64, u64)>>>,
 }
 
 impl<C> MockHost<C> {
@@ -47,6 +61,11 @@ impl<C> MockHost<C> {
 			address: Arc::new(Mutex::new(Vec::new())),
 			name: Arc::new(Mutex::new("Mock".to_string())),
 			consensus_state_id: Arc::new(Mutex::new(*b"Mock")),
+			timestamp: Arc::new(Mutex::new(Duration::from_secs(0))),
+			receipts: Arc::new(Mutex::new(HashMap::new())),
+			failing_receipts: Arc::new(Mutex::new(HashSet::new())),
+			events: Arc::new(Mutex::new(Vec::new())),
+			event_queries: Arc::new(Mutex::new(Vec::new())),
 		}
 	}
 
@@ -74,6 +93,38 @@ impl<C> MockHost<C> {
 		*self.consensus_state_id.lock().unwrap() = id;
 		self
 	}
+
+	pub fn with_timestamp(self, timestamp: Duration) -> Self {
+		*self.timestamp.lock().unwrap() = timestamp;
+		self
+	}
+
+	pub fn with_receipt(self, commitment: H256, relayer: Vec<u8>) -> Self {
+		self.receipts.lock().unwrap().insert(commitment, relayer);
+		self
+	}
+
+	pub fn with_failing_receipt(self, commitment: H256) -> Self {
+		self.failing_receipts.lock().unwrap().insert(commitment);
+		self
+	}
+
+	pub fn with_event(self, height: u64, event: Event) -> Self {
+		self.events.lock().unwrap().push((height, event));
+		self
+	}
+
+	/// Clone of the `(previous_height, latest_height)` of every event query so far.
+	pub fn event_queries(&self) -> Vec<(u64, u64)> {
+		self.event_queries.lock().unwrap().clone()
+	}
+
+	fn receipt(&self, commitment: H256) -> Result<Vec<u8>, anyhow::Error> {
+		if self.failing_receipts.lock().unwrap().contains(&commitment) {
+			return Err(anyhow!("mock: receipt query failure"));
+		}
+		Ok(self.receipts.lock().unwrap().get(&commitment).cloned().unwrap_or_default())
+	}
 }
 
 #[async_trait::async_trait]
@@ -155,7 +206,11 @@ impl<C: Codec + Send + Sync> IsmpProvider for MockHost<C> {
 		&self,
 		_height: StateMachineHeight,
 	) -> Result<StateCommitment, Error> {
-		todo!()
+		Ok(StateCommitment {
+			timestamp: self.timestamp.lock().unwrap().as_secs(),
+			overlay_root: None,
+			state_root: H256::zero(),
+		})
 	}
 
 	async fn query_state_machine_update_time(
@@ -169,8 +224,12 @@ impl<C: Codec + Send + Sync> IsmpProvider for MockHost<C> {
 		Ok(Duration::from_secs(0))
 	}
 
+	async fn query_consensus_expiry(&self, _id: ConsensusStateId) -> Result<Duration, Error> {
+		Ok(Duration::MAX)
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, Error> {
-		Ok(Duration::from_secs(0))
+		Ok(*self.timestamp.lock().unwrap())
 	}
 
 	async fn query_requests_proof(
@@ -196,10 +255,18 @@ impl<C: Codec + Send + Sync> IsmpProvider for MockHost<C> {
 
 	async fn query_ismp_events(
 		&self,
-		_previous_height: u64,
-		_event: StateMachineUpdated,
+		previous_height: u64,
+		event: StateMachineUpdated,
 	) -> Result<Vec<Event>, Error> {
-		todo!()
+		self.event_queries.lock().unwrap().push((previous_height, event.latest_height));
+		Ok(self
+			.events
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(height, _)| *height > previous_height && *height <= event.latest_height)
+			.map(|(_, event)| event.clone())
+			.collect())
 	}
 
 	fn name(&self) -> String {
@@ -281,7 +348,7 @@ impl<C: Codec + Send + Sync> IsmpProvider for MockHost<C> {
 		self.address.lock().unwrap().clone()
 	}
 
-	fn sign(&self, _msg: &[u8]) -> Signature {
+	async fn sign(&self, _msg: &[u8]) -> Result<Signature, anyhow::Error> {
 		todo!()
 	}
 
@@ -310,12 +377,12 @@ impl<C: Codec + Send + Sync> IsmpProvider for MockHost<C> {
 		todo!()
 	}
 
-	async fn query_request_receipt(&self, _hash: H256) -> Result<Vec<u8>, anyhow::Error> {
-		todo!()
+	async fn query_request_receipt(&self, hash: H256) -> Result<Vec<u8>, anyhow::Error> {
+		self.receipt(hash)
 	}
 
-	async fn query_response_receipt(&self, _hash: H256) -> Result<Vec<u8>, anyhow::Error> {
-		Ok(Default::default())
+	async fn query_response_receipt(&self, hash: H256) -> Result<Vec<u8>, anyhow::Error> {
+		self.receipt(hash)
 	}
 
 	async fn fee_token_decimals(&self) -> Result<u8, anyhow::Error> {
@@ -334,6 +401,9 @@ impl<C: Send + Sync> Clone for MockHost<C> {
 			address: self.address.clone(),
 			name: self.name.clone(),
 			consensus_state_id: self.consensus_state_id.clone(),
+			timestamp: self.timestamp.clone(),
+			receipts: self.receipts.clone(),
+			failing_receipts: self.failing_receipts.clone(),
 		}
 	}
 }
@@ -0,0 +1,815 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Declarative delivery policy for the relayer.
+//!
+//! A [`RoutePolicy`] is a list of [`RouteRule`]s matched against the source chain, destination
+//! chain and source modules of every message the relayer considers delivering. For each module
+//! in a message, the first rule that matches decides the minimum fee, profit margin, gas price
+//! ceiling, time-of-day windows and daily spending cap, and a message is only delivered when the
+//! rules of all its modules allow it. Messages that match no rule fall back to the global
+//! `minimum_profit_percentage`, which keeps existing configs behaving exactly as before.
+//!
+//! Spending caps are charged once a delivery is confirmed: a message that passes evaluation is
+//! [`reserve`](RoutePolicy::reserve)d under its commitment and only charged when its receipt is
+//! [`settle`](RoutePolicy::settle)d.
+//!
+//! ```toml
+//! [relayer.policy]
+//! # partner apps that are delivered regardless of the fee they attach
+//! always_deliver = ["0xa09b1c60e8650245f92518c8a17314878c4043ed"]
+//!
+//! [[relayer.policy.routes]]
+//! source = "EVM-1"
+//! dest = "EVM-56"
+//! minimum_fee = "0.50"
+//! minimum_profit_bps = 1500
+//! maximum_gas_price = 5
+//! windows = ["22:00-06:00"]
+//! daily_spend_cap = "250"
+//! ```
+
+use crate::Cost;
+use ismp::host::StateMachine;
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	fmt,
+	hash::Hash,
+	sync::{Arc, Mutex},
+};
+
+/// Length of the UTC day that spending caps reset on.
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Number of wei in a gwei, `maximum_gas_price` is configured in gwei.
+const GWEI: u64 = 1_000_000_000;
+
+/// Per-route delivery rules, evaluated for every message delivered to a chain other than the
+/// coprocessor.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RoutePolicy {
+	/// Source modules that are always delivered, regardless of the fee they attach. Gas price,
+	/// time-of-day and spending limits of the matching route still apply.
+	#[serde(default)]
+	pub always_deliver: Vec<ModuleId>,
+	/// Route rules, the first rule that matches a message applies.
+	#[serde(default)]
+	pub routes: Vec<RouteRule>,
+	/// Amount spent on each capped route today and the charges awaiting confirmation. Shared by
+	/// every clone of the policy so that all messaging tasks in the process draw from the same
+	/// budget, and carried across config reloads with [`RoutePolicy::inherit_spending`]. Not
+	/// persisted across restarts.
+	#[serde(skip)]
+	spending: Arc<Mutex<Spending>>,
+}
+
+/// Spending of the capped routes, keyed by route so it outlives the rule's position in the config.
+#[derive(Debug, Default)]
+struct Spending {
+	ledger: SpendLedger<RouteKey>,
+	/// Charges of messages that passed evaluation but haven't been confirmed delivered yet
+	pending: HashMap<H256, PendingCharge>,
+}
+
+/// The identity of a capped route: its source, destination and modules.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RouteKey {
+	source: Option<StateMachine>,
+	dest: Option<StateMachine>,
+	modules: Vec<Vec<u8>>,
+}
+
+/// Execution cost to charge to some capped routes once a delivery is confirmed.
+#[derive(Debug)]
+struct PendingCharge {
+	day: u64,
+	routes: Vec<RouteKey>,
+	cost: Cost,
+}
+
+/// Delivery rules for the messages of a single route.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RouteRule {
+	/// Source chain of the message, any chain when omitted.
+	#[serde(default, with = "crate::serde_adapters::option_state_machine")]
+	pub source: Option<StateMachine>,
+	/// Destination chain of the message, any chain when omitted.
+	#[serde(default, with = "crate::serde_adapters::option_state_machine")]
+	pub dest: Option<StateMachine>,
+	/// Source modules this rule applies to, any module when empty.
+	#[serde(default)]
+	pub modules: Vec<ModuleId>,
+	/// Minimum fee in USD the message must carry.
+	pub minimum_fee: Option<UsdAmount>,
+	/// Minimum profit over the execution cost in basis points, overrides the global
+	/// `minimum_profit_percentage`. `0` delivers every message regardless of the fee.
+	pub minimum_profit_bps: Option<u32>,
+	/// Highest destination gas price in gwei at which messages are delivered. Only enforced on
+	/// chains that report a gas price.
+	pub maximum_gas_price: Option<u64>,
+	/// UTC time-of-day windows in which messages are delivered, e.g. `"22:00-06:00"`. Messages
+	/// are delivered at any time when empty.
+	#[serde(default)]
+	pub windows: Vec<TimeWindow>,
+	/// Maximum execution cost in USD spent on this route per UTC day.
+	pub daily_spend_cap: Option<UsdAmount>,
+	/// Deliver every message on this route regardless of the fee it attaches.
+	#[serde(default)]
+	pub always_deliver: bool,
+}
+
+/// A message the relayer is considering delivering.
+#[derive(Debug, Clone, Copy)]
+pub struct Delivery<'a> {
+	/// The chain the message originates from
+	pub source: StateMachine,
+	/// The chain the message is delivered to
+	pub dest: StateMachine,
+	/// The modules that dispatched the requests in the message
+	pub modules: &'a [Vec<u8>],
+	/// Estimated cost of delivering the message
+	pub execution_cost: Cost,
+	/// Fee attached to the message, normalized to 18 decimals
+	pub fee: Cost,
+	/// Current gas price on the destination in wei, if known
+	pub gas_price: Option<U256>,
+}
+
+/// Outcome of evaluating a [`Delivery`] against the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+	/// Deliver the message. `subsidized` is true when fee checks were bypassed.
+	Deliver { subsidized: bool },
+	/// Don't deliver the message now, it may be retried later.
+	Defer(DeferReason),
+}
+
+/// Why a message was not delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeferReason {
+	/// The current time is outside every allowed window of the route
+	OutsideWindow,
+	/// The destination gas price is above the route's ceiling
+	GasPriceTooHigh { gas_price: U256, maximum: U256 },
+	/// The attached fee is below the route's minimum fee
+	BelowMinimumFee { fee: Cost, minimum: Cost },
+	/// The attached fee does not cover the execution cost plus the required profit
+	Unprofitable { fee: Cost, required: Cost },
+	/// The route has exhausted its spending cap for the day
+	SpendCapReached { cap: Cost },
+}
+
+impl fmt::Display for DeferReason {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DeferReason::OutsideWindow => write!(f, "outside of delivery windows"),
+			DeferReason::GasPriceTooHigh { gas_price, maximum } =>
+				write!(f, "gas price {gas_price} wei is above the maximum of {maximum} wei"),
+			DeferReason::BelowMinimumFee { fee, minimum } =>
+				write!(f, "fee ${fee} is below the minimum fee of ${minimum}"),
+			DeferReason::Unprofitable { fee, required } =>
+				write!(f, "unprofitable, expected ${required}, user provided ${fee}"),
+			DeferReason::SpendCapReached { cap } =>
+				write!(f, "daily spending cap of ${cap} reached"),
+		}
+	}
+}
+
+impl RoutePolicy {
+	/// Returns true if `module` is on the always-deliver allowlist.
+	pub fn is_always_delivered(&self, module: &[u8]) -> bool {
+		self.always_deliver.iter().any(|id| id.0 == module)
+	}
+
+	/// Returns true if any route enforces a gas price ceiling, so callers only query the
+	/// destination gas price when it is needed.
+	pub fn needs_gas_price(&self) -> bool {
+		self.routes.iter().any(|rule| rule.maximum_gas_price.is_some())
+	}
+
+	/// Returns the first route rule that applies to the given message.
+	pub fn rule_for(
+		&self,
+		source: StateMachine,
+		dest: StateMachine,
+		module: &[u8],
+	) -> Option<(usize, &RouteRule)> {
+		self.routes
+			.iter()
+			.enumerate()
+			.find(|(_, rule)| rule.matches(source, dest, module))
+	}
+
+	/// Evaluate a delivery at `now` (unix seconds). `default_profit_bps` applies to modules that
+	/// match no route. Every module of the message is checked against its own rule, and the
+	/// message is deferred if any of them defers it. Spending caps are checked against the
+	/// confirmed spending of the day, nothing is charged until the delivery is
+	/// [`reserve`](Self::reserve)d and [`settle`](Self::settle)d.
+	pub fn evaluate(&self, delivery: &Delivery, default_profit_bps: u32, now: u64) -> Verdict {
+		let fallback =
+			RouteRule { minimum_profit_bps: Some(default_profit_bps), ..Default::default() };
+		let mut subsidized = true;
+		for module in modules(delivery) {
+			let rule = self
+				.rule_for(delivery.source, delivery.dest, module)
+				.map(|(_, rule)| rule)
+				.unwrap_or(&fallback);
+			match self.check(rule, module, delivery, default_profit_bps, now) {
+				Ok(free) => subsidized &= free,
+				Err(reason) => return Verdict::Defer(reason),
+			}
+		}
+
+		let day = now / SECONDS_PER_DAY;
+		let spending = self.spending.lock().expect("Spending lock is never poisoned");
+		for (route, cap) in self.capped_routes(delivery) {
+			if !spending.ledger.has_room(&route, day, delivery.execution_cost, cap) {
+				return Verdict::Defer(DeferReason::SpendCapReached { cap });
+			}
+		}
+
+		Verdict::Deliver { subsidized }
+	}
+
+	/// Record that the message with `commitment` is about to be delivered, so its execution cost
+	/// is charged to its capped routes when the delivery is [`settle`](Self::settle)d. Charges
+	/// that are never settled are dropped at the end of the day.
+	pub fn reserve(&self, commitment: H256, delivery: &Delivery, now: u64) {
+		let routes = self
+			.capped_routes(delivery)
+			.into_iter()
+			.map(|(route, _)| route)
+			.collect::<Vec<_>>();
+		if routes.is_empty() {
+			return;
+		}
+
+		let day = now / SECONDS_PER_DAY;
+		let mut spending = self.spending.lock().expect("Spending lock is never poisoned");
+		spending.pending.retain(|_, charge| charge.day == day);
+		spending
+			.pending
+			.insert(commitment, PendingCharge { day, routes, cost: delivery.execution_cost });
+	}
+
+	/// Charge the reserved execution cost of every delivered commitment to its routes.
+	pub fn settle(&self, delivered: impl IntoIterator<Item = H256>, now: u64) {
+		let day = now / SECONDS_PER_DAY;
+		let mut spending = self.spending.lock().expect("Spending lock is never poisoned");
+		for commitment in delivered {
+			let Some(charge) = spending.pending.remove(&commitment) else { continue };
+			if charge.day != day {
+				continue;
+			}
+			for route in charge.routes {
+				spending.ledger.charge(route, day, charge.cost);
+			}
+		}
+	}
+
+	/// Keep drawing from the spending of `previous`, so reloading the config doesn't reset the
+	/// daily caps of the routes that are still configured.
+	pub fn inherit_spending(&mut self, previous: &RoutePolicy) {
+		self.spending = previous.spending.clone();
+	}
+
+	/// Check a single module of a delivery against `rule`, returning whether its fee checks were
+	/// bypassed.
+	fn check(
+		&self,
+		rule: &RouteRule,
+		module: &[u8],
+		delivery: &Delivery,
+		default_profit_bps: u32,
+		now: u64,
+	) -> Result<bool, DeferReason> {
+		if !rule.windows.is_empty() &&
+			!rule.windows.iter().any(|window| window.contains(now % SECONDS_PER_DAY))
+		{
+			return Err(DeferReason::OutsideWindow);
+		}
+
+		if let (Some(maximum), Some(gas_price)) = (rule.maximum_gas_price, delivery.gas_price) {
+			let maximum = U256::from(maximum) * U256::from(GWEI);
+			if gas_price > maximum {
+				return Err(DeferReason::GasPriceTooHigh { gas_price, maximum });
+			}
+		}
+
+		let subsidized = rule.always_deliver || self.is_always_delivered(module);
+		if !subsidized {
+			if let Some(minimum) = rule.minimum_fee {
+				if delivery.fee < minimum.0 {
+					return Err(DeferReason::BelowMinimumFee {
+						fee: delivery.fee,
+						minimum: minimum.0,
+					});
+				}
+			}
+
+			// 0 profit means we want to relay all requests for free
+			let profit_bps = rule.minimum_profit_bps.unwrap_or(default_profit_bps);
+			if profit_bps != 0 {
+				let cost = delivery.execution_cost;
+				let required = cost + (U256::from(profit_bps) * cost.0) / U256::from(10_000u32);
+				if delivery.fee < required {
+					return Err(DeferReason::Unprofitable { fee: delivery.fee, required });
+				}
+			}
+		}
+
+		Ok(subsidized)
+	}
+
+	/// The capped routes a delivery is charged to, each at most once.
+	fn capped_routes(&self, delivery: &Delivery) -> Vec<(RouteKey, Cost)> {
+		let mut routes: Vec<(RouteKey, Cost)> = vec![];
+		for module in modules(delivery) {
+			let Some((_, rule)) = self.rule_for(delivery.source, delivery.dest, module) else {
+				continue;
+			};
+			let Some(cap) = rule.daily_spend_cap else { continue };
+			let route = rule.key();
+			if !routes.iter().any(|(existing, _)| *existing == route) {
+				routes.push((route, cap.0));
+			}
+		}
+		routes
+	}
+}
+
+/// The modules of a delivery, a message without requests is matched as an empty module.
+fn modules<'a>(delivery: &Delivery<'a>) -> Vec<&'a [u8]> {
+	if delivery.modules.is_empty() {
+		return vec![&[][..]];
+	}
+	delivery.modules.iter().map(Vec::as_slice).collect()
+}
+
+impl RouteRule {
+	/// Returns true if this rule applies to a message on the given route.
+	pub fn matches(&self, source: StateMachine, dest: StateMachine, module: &[u8]) -> bool {
+		self.source.map_or(true, |s| s == source) &&
+			self.dest.map_or(true, |d| d == dest) &&
+			(self.modules.is_empty() || self.modules.iter().any(|id| id.0 == module))
+	}
+
+	fn key(&self) -> RouteKey {
+		RouteKey {
+			source: self.source,
+			dest: self.dest,
+			modules: self.modules.iter().map(|id| id.0.clone()).collect(),
+		}
+	}
+}
+
+/// Spending per capped route for the current UTC day.
+#[derive(Debug)]
+pub struct SpendLedger<K> {
+	day: u64,
+	spent: HashMap<K, Cost>,
+}
+
+impl<K> Default for SpendLedger<K> {
+	fn default() -> Self {
+		Self { day: 0, spent: HashMap::new() }
+	}
+}
+
+impl<K: Hash + Eq> SpendLedger<K> {
+	/// Returns true if charging `amount` to `route` on `day` stays within `cap`.
+	pub fn has_room(&self, route: &K, day: u64, amount: Cost, cap: Cost) -> bool {
+		let spent = if day == self.day {
+			self.spent.get(route).copied().unwrap_or_default()
+		} else {
+			Cost::default()
+		};
+		spent + amount.0 <= cap
+	}
+
+	/// Charge `amount` to `route`, resetting all routes at the start of a new day.
+	pub fn charge(&mut self, route: K, day: u64, amount: Cost) {
+		if day != self.day {
+			self.day = day;
+			self.spent.clear();
+		}
+		let spent = self.spent.entry(route).or_default();
+		*spent = *spent + amount.0;
+	}
+
+	/// Charge `amount` to `route` if it stays within `cap`, resetting all routes at the start of
+	/// a new day.
+	pub fn try_spend(&mut self, route: K, day: u64, amount: Cost, cap: Cost) -> bool {
+		if !self.has_room(&route, day, amount, cap) {
+			return false;
+		}
+		self.charge(route, day, amount);
+		true
+	}
+}
+
+/// A module identifier configured as a hex string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModuleId(pub Vec<u8>);
+
+impl TryFrom<String> for ModuleId {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		hex::decode(value.trim_start_matches("0x"))
+			.map(ModuleId)
+			.map_err(|err| format!("Invalid module identifier {value}: {err}"))
+	}
+}
+
+impl From<ModuleId> for String {
+	fn from(value: ModuleId) -> Self {
+		format!("0x{}", hex::encode(value.0))
+	}
+}
+
+/// A USD amount configured as a decimal string, e.g. `"12.5"`, stored with 18 decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UsdAmount(pub Cost);
+
+impl TryFrom<String> for UsdAmount {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		let invalid = || format!("Invalid USD amount {value}");
+		let (whole, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+		if fraction.len() > 18 ||
+			!whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) ||
+			(whole.is_empty() && fraction.is_empty())
+		{
+			return Err(invalid());
+		}
+
+		let whole = if whole.is_empty() {
+			U256::zero()
+		} else {
+			U256::from_dec_str(whole).map_err(|_| invalid())?
+		};
+		let fraction = format!("{fraction:0<18}");
+		let fraction = U256::from_dec_str(&fraction).map_err(|_| invalid())?;
+
+		whole
+			.checked_mul(U256::from(10u64.pow(18)))
+			.and_then(|whole| whole.checked_add(fraction))
+			.map(|amount| UsdAmount(Cost(amount)))
+			.ok_or_else(invalid)
+	}
+}
+
+impl From<UsdAmount> for String {
+	fn from(value: UsdAmount) -> Self {
+		value.0.to_string()
+	}
+}
+
+/// A UTC time-of-day window configured as `"HH:MM-HH:MM"`. Windows whose end is before their
+/// start wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeWindow {
+	/// Start of the window in seconds since midnight, inclusive
+	pub start: u64,
+	/// End of the window in seconds since midnight, exclusive
+	pub end: u64,
+}
+
+impl TimeWindow {
+	/// Returns true if `seconds` since midnight falls within this window.
+	pub fn contains(&self, seconds: u64) -> bool {
+		if self.start <= self.end {
+			seconds >= self.start && seconds < self.end
+		} else {
+			seconds >= self.start || seconds < self.end
+		}
+	}
+}
+
+impl TryFrom<String> for TimeWindow {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		let invalid = || format!("Invalid time window {value}, expected HH:MM-HH:MM");
+		let parse = |time: &str| -> Option<u64> {
+			let (hours, minutes) = time.trim().split_once(':')?;
+			let (hours, minutes) = (hours.parse::<u64>().ok()?, minutes.parse::<u64>().ok()?);
+			(hours <= 24 && minutes < 60 && hours * 60 + minutes <= 24 * 60)
+				.then_some((hours * 60 + minutes) * 60)
+		};
+		let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+		Ok(TimeWindow {
+			start: parse(start).ok_or_else(invalid)?,
+			end: parse(end).ok_or_else(invalid)?,
+		})
+	}
+}
+
+impl From<TimeWindow> for String {
+	fn from(value: TimeWindow) -> Self {
+		let format = |seconds: u64| format!("{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60);
+		format!("{}-{}", format(value.start), format(value.end))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PARTNER: [u8; 20] = [1u8; 20];
+	const OTHER: [u8; 20] = [2u8; 20];
+
+	fn partner() -> Vec<Vec<u8>> {
+		vec![PARTNER.to_vec()]
+	}
+
+	fn other() -> Vec<Vec<u8>> {
+		vec![OTHER.to_vec()]
+	}
+
+	fn usd(value: &str) -> Cost {
+		UsdAmount::try_from(value.to_string()).unwrap().0
+	}
+
+	fn delivery<'a>(modules: &'a [Vec<u8>], cost: &str, fee: &str) -> Delivery<'a> {
+		Delivery {
+			source: StateMachine::Evm(1),
+			dest: StateMachine::Evm(56),
+			modules,
+			execution_cost: usd(cost),
+			fee: usd(fee),
+			gas_price: None,
+		}
+	}
+
+	fn policy(toml: &str) -> RoutePolicy {
+		toml::from_str(toml).unwrap()
+	}
+
+	#[test]
+	fn parses_usd_amounts() {
+		assert_eq!(usd("1"), Cost(U256::from(10u64.pow(18))));
+		assert_eq!(usd("0.5"), Cost(U256::from(5 * 10u64.pow(17))));
+		assert_eq!(usd(".25"), Cost(U256::from(25 * 10u64.pow(16))));
+		assert!(UsdAmount::try_from("1.2.3".to_string()).is_err());
+		assert!(UsdAmount::try_from("-1".to_string()).is_err());
+		assert!(UsdAmount::try_from("".to_string()).is_err());
+	}
+
+	#[test]
+	fn time_windows_wrap_around_midnight() {
+		let day = TimeWindow::try_from("09:00-17:30".to_string()).unwrap();
+		assert!(day.contains(9 * 3600));
+		assert!(!day.contains(17 * 3600 + 30 * 60));
+		assert_eq!(String::from(day), "09:00-17:30");
+
+		let night = TimeWindow::try_from("22:00-06:00".to_string()).unwrap();
+		assert!(night.contains(23 * 3600));
+		assert!(night.contains(3600));
+		assert!(!night.contains(12 * 3600));
+
+		assert!(TimeWindow::try_from("25:00-06:00".to_string()).is_err());
+		assert!(TimeWindow::try_from("22:00".to_string()).is_err());
+	}
+
+	#[test]
+	fn unmatched_routes_use_the_global_profit_margin() {
+		let policy = RoutePolicy::default();
+		assert_eq!(
+			policy.evaluate(&delivery(&other(), "1", "1.05"), 1000, 0),
+			Verdict::Defer(DeferReason::Unprofitable { fee: usd("1.05"), required: usd("1.1") })
+		);
+		assert_eq!(
+			policy.evaluate(&delivery(&other(), "1", "1.1"), 1000, 0),
+			Verdict::Deliver { subsidized: false }
+		);
+		// 0 delivers every message regardless of the fee
+		assert_eq!(
+			policy.evaluate(&delivery(&other(), "1", "0"), 0, 0),
+			Verdict::Deliver { subsidized: false }
+		);
+	}
+
+	#[test]
+	fn first_matching_route_applies() {
+		let policy = policy(
+			r#"
+			[[routes]]
+			source = "EVM-1"
+			dest = "EVM-56"
+			modules = ["0x0101010101010101010101010101010101010101"]
+			minimum_profit_bps = 0
+
+			[[routes]]
+			dest = "EVM-56"
+			minimum_fee = "2"
+			minimum_profit_bps = 5000
+			"#,
+		);
+
+		assert_eq!(
+			policy.evaluate(&delivery(&partner(), "1", "0"), 1000, 0),
+			Verdict::Deliver { subsidized: false }
+		);
+		assert_eq!(
+			policy.evaluate(&delivery(&other(), "1", "1.6"), 1000, 0),
+			Verdict::Defer(DeferReason::BelowMinimumFee { fee: usd("1.6"), minimum: usd("2") })
+		);
+		assert_eq!(
+			policy.evaluate(&delivery(&other(), "2", "2.5"), 1000, 0),
+			Verdict::Defer(DeferReason::Unprofitable { fee: usd("2.5"), required: usd("3") })
+		);
+
+		let modules = other();
+		let mut elsewhere = delivery(&modules, "1", "1.1");
+		elsewhere.dest = StateMachine::Evm(10);
+		assert_eq!(policy.evaluate(&elsewhere, 1000, 0), Verdict::Deliver { subsidized: false });
+	}
+
+	#[test]
+	fn partners_bypass_fee_checks_but_not_route_limits() {
+		let policy = policy(
+			r#"
+			always_deliver = ["0x0101010101010101010101010101010101010101"]
+
+			[[routes]]
+			minimum_profit_bps = 1000
+			maximum_gas_price = 5
+			windows = ["09:00-17:00"]
+			"#,
+		);
+
+		let modules = partner();
+		let mut partner = delivery(&modules, "1", "0");
+		assert_eq!(policy.evaluate(&partner, 0, 10 * 3600), Verdict::Deliver { subsidized: true });
+		assert_eq!(
+			policy.evaluate(&partner, 0, 18 * 3600),
+			Verdict::Defer(DeferReason::OutsideWindow)
+		);
+
+		partner.gas_price = Some(U256::from(6 * GWEI));
+		assert_eq!(
+			policy.evaluate(&partner, 0, 10 * 3600),
+			Verdict::Defer(DeferReason::GasPriceTooHigh {
+				gas_price: U256::from(6 * GWEI),
+				maximum: U256::from(5 * GWEI),
+			})
+		);
+
+		assert!(matches!(
+			policy.evaluate(&delivery(&other(), "1", "0"), 0, 10 * 3600),
+			Verdict::Defer(DeferReason::Unprofitable { .. })
+		));
+	}
+
+	#[test]
+	fn daily_spend_caps_reset_each_day() {
+		let policy = policy(
+			r#"
+			[[routes]]
+			always_deliver = true
+			daily_spend_cap = "2.5"
+			"#,
+		);
+		let modules = other();
+		let message = delivery(&modules, "1", "0");
+
+		policy.reserve(H256::repeat_byte(1), &message, 0);
+		policy.settle([H256::repeat_byte(1)], 0);
+		// clones share the same budget
+		policy.clone().reserve(H256::repeat_byte(2), &message, 1);
+		policy.clone().settle([H256::repeat_byte(2)], 1);
+		assert_eq!(
+			policy.evaluate(&message, 0, 2),
+			Verdict::Defer(DeferReason::SpendCapReached { cap: usd("2.5") })
+		);
+		assert_eq!(
+			policy.evaluate(&message, 0, SECONDS_PER_DAY),
+			Verdict::Deliver { subsidized: true }
+		);
+	}
+
+	#[test]
+	fn spending_is_charged_once_delivery_is_confirmed() {
+		let policy = policy(
+			r#"
+			[[routes]]
+			always_deliver = true
+			daily_spend_cap = "1.5"
+			"#,
+		);
+		let modules = other();
+		let message = delivery(&modules, "1", "0");
+
+		// evaluating and reserving doesn't charge anything
+		for _ in 0..3 {
+			assert_eq!(policy.evaluate(&message, 0, 0), Verdict::Deliver { subsidized: true });
+		}
+		policy.reserve(H256::repeat_byte(1), &message, 0);
+		policy.reserve(H256::repeat_byte(2), &message, 0);
+		assert_eq!(policy.evaluate(&message, 0, 0), Verdict::Deliver { subsidized: true });
+
+		// only the delivered commitment is charged, and only once
+		policy.settle([H256::repeat_byte(1), H256::repeat_byte(3)], 0);
+		policy.settle([H256::repeat_byte(1)], 0);
+		assert_eq!(
+			policy.evaluate(&message, 0, 0),
+			Verdict::Defer(DeferReason::SpendCapReached { cap: usd("1.5") })
+		);
+		let cheaper = delivery(&modules, "0.5", "0");
+		assert_eq!(policy.evaluate(&cheaper, 0, 0), Verdict::Deliver { subsidized: true });
+
+		// charges reserved on a previous day are dropped
+		policy.settle([H256::repeat_byte(2)], SECONDS_PER_DAY);
+		assert_eq!(
+			policy.evaluate(&message, 0, SECONDS_PER_DAY),
+			Verdict::Deliver { subsidized: true }
+		);
+	}
+
+	#[test]
+	fn spending_survives_config_reloads() {
+		let config = r#"
+			[[routes]]
+			dest = "EVM-56"
+			always_deliver = true
+			daily_spend_cap = "1"
+			"#;
+		let previous = policy(config);
+		let modules = other();
+		let message = delivery(&modules, "1", "0");
+		previous.reserve(H256::repeat_byte(1), &message, 0);
+		previous.settle([H256::repeat_byte(1)], 0);
+
+		// the same route moved behind a new rule keeps its spending
+		let mut reloaded = policy(&format!(
+			r#"
+			[[routes]]
+			dest = "EVM-10"
+			minimum_profit_bps = 0
+			{config}"#
+		));
+		reloaded.inherit_spending(&previous);
+		assert_eq!(
+			reloaded.evaluate(&message, 0, 0),
+			Verdict::Defer(DeferReason::SpendCapReached { cap: usd("1") })
+		);
+
+		// a changed route starts from scratch
+		let mut changed = policy(&config.replace("EVM-56", "EVM-1"));
+		changed.inherit_spending(&previous);
+		let mut to_ethereum = message;
+		to_ethereum.dest = StateMachine::Evm(1);
+		assert_eq!(changed.evaluate(&to_ethereum, 0, 0), Verdict::Deliver { subsidized: true });
+	}
+
+	#[test]
+	fn every_module_of_a_message_is_matched() {
+		let policy = policy(
+			r#"
+			always_deliver = ["0x0101010101010101010101010101010101010101"]
+
+			[[routes]]
+			modules = ["0x0202020202020202020202020202020202020202"]
+			windows = ["09:00-17:00"]
+			"#,
+		);
+		let modules = vec![PARTNER.to_vec(), OTHER.to_vec()];
+		let message = delivery(&modules, "1", "0");
+
+		// the second module's window applies even though the first module is unrestricted
+		assert_eq!(
+			policy.evaluate(&message, 0, 18 * 3600),
+			Verdict::Defer(DeferReason::OutsideWindow)
+		);
+		// the message is only subsidized if every module is
+		assert!(matches!(
+			policy.evaluate(&message, 1000, 10 * 3600),
+			Verdict::Defer(DeferReason::Unprofitable { .. })
+		));
+		let partners = vec![PARTNER.to_vec(), PARTNER.to_vec()];
+		assert_eq!(
+			policy.evaluate(&delivery(&partners, "1", "0"), 1000, 10 * 3600),
+			Verdict::Deliver { subsidized: true }
+		);
+	}
+}
@@ -0,0 +1,357 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-disk cache of consensus proofs, shared by the consensus hosts of a relayer.
+//!
+//! A consensus proof only depends on the trusted state the counterparty holds and the height it
+//! proves, so every counterparty tracking a chain at the same trusted height can be sent the same
+//! proof. Proofs are stored under `<dir>/<consensus_state_id>/<trusted_height>/` in files named
+//! after their target height and the keccak hash of their contents, the hash is checked when a
+//! proof is read back. Hosts go through [`cached_proof`], which proves at most once per trusted
+//! height: concurrent callers wait for the proof in flight, later callers reuse the stored one.
+//!
+//! ```toml
+//! [relayer]
+//! proof_cache = "/var/lib/tesseract/proofs"
+//! ```
+
+use std::{
+	collections::HashMap,
+	future::Future,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, OnceLock},
+	time::{Duration, SystemTime},
+};
+
+use anyhow::anyhow;
+use ismp::consensus::ConsensusStateId;
+use parity_scale_codec::{Decode, Encode};
+use sp_crypto_hashing::keccak_256;
+use tokio::{fs, sync::OwnedMutexGuard};
+
+/// Cache shared by every consensus host in the process, see [`ProofCache::init`]
+static PROOF_CACHE: OnceLock<ProofCache> = OnceLock::new();
+
+/// How long proofs are kept after they were generated
+const DEFAULT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Identifies a consensus proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProofCacheKey {
+	/// Consensus state id of the client the proof is for
+	pub consensus_state_id: ConsensusStateId,
+	/// Height of the trusted state the proof builds on
+	pub trusted_height: u64,
+	/// Height the proof advances the client to
+	pub target_height: u64,
+}
+
+/// On-disk, content-addressed consensus proof cache
+#[derive(Clone)]
+pub struct ProofCache {
+	/// Root directory of the cache
+	dir: PathBuf,
+	/// How long proofs are kept
+	retention: Duration,
+	/// Proofs being generated, by consensus state id and trusted height
+	in_flight: Arc<Mutex<HashMap<(ConsensusStateId, u64), Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl ProofCache {
+	/// Open the cache rooted at `dir`, creating the directory if needed
+	pub async fn open(dir: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+		let dir = dir.as_ref().to_path_buf();
+		fs::create_dir_all(&dir).await?;
+		Ok(Self { dir, retention: DEFAULT_RETENTION, in_flight: Default::default() })
+	}
+
+	/// Open the cache rooted at `dir` and make it the cache used by [`cached_proof`] for the rest
+	/// of the process
+	pub async fn init(dir: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+		let cache = Self::open(dir).await?;
+		PROOF_CACHE
+			.set(cache)
+			.map_err(|_| anyhow!("The consensus proof cache was already initialized"))
+	}
+
+	/// The cache initialized with [`ProofCache::init`], if any
+	pub fn global() -> Option<&'static ProofCache> {
+		PROOF_CACHE.get()
+	}
+
+	/// Read the proof stored for `key`
+	pub async fn get(&self, key: &ProofCacheKey) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		let prefix = format!("{}-", key.target_height);
+		for path in self.entries(key.consensus_state_id, key.trusted_height).await? {
+			let is_match = path
+				.file_name()
+				.and_then(|name| name.to_str())
+				.is_some_and(|name| name.starts_with(&prefix));
+			if is_match {
+				if let Some(proof) = read_proof(&path).await? {
+					return Ok(Some(proof));
+				}
+			}
+		}
+		Ok(None)
+	}
+
+	/// Read the stored proof with the highest target height from `trusted_height`
+	pub async fn latest(
+		&self,
+		consensus_state_id: ConsensusStateId,
+		trusted_height: u64,
+	) -> Result<Option<(ProofCacheKey, Vec<u8>)>, anyhow::Error> {
+		let mut entries = self
+			.entries(consensus_state_id, trusted_height)
+			.await?
+			.into_iter()
+			.filter_map(|path| Some((target_height(&path)?, path)))
+			.collect::<Vec<_>>();
+		entries.sort_by_key(|(target_height, _)| std::cmp::Reverse(*target_height));
+
+		for (target_height, path) in entries {
+			if let Some(proof) = read_proof(&path).await? {
+				let key = ProofCacheKey { consensus_state_id, trusted_height, target_height };
+				return Ok(Some((key, proof)));
+			}
+		}
+		Ok(None)
+	}
+
+	/// Store the proof for `key`. The file is written under a temporary name and renamed into
+	/// place, so readers in this or another process never observe a partial proof.
+	pub async fn insert(&self, key: &ProofCacheKey, proof: &[u8]) -> Result<(), anyhow::Error> {
+		let dir = self.trusted_dir(key.consensus_state_id, key.trusted_height);
+		fs::create_dir_all(&dir).await?;
+
+		let name = format!("{}-{}", key.target_height, hex::encode(keccak_256(proof)));
+		let tmp = dir.join(format!(".{name}.{}", std::process::id()));
+		fs::write(&tmp, proof).await?;
+		fs::rename(&tmp, dir.join(name)).await?;
+		Ok(())
+	}
+
+	/// Remove the proofs stored from `trusted_height`
+	pub async fn evict(
+		&self,
+		consensus_state_id: ConsensusStateId,
+		trusted_height: u64,
+	) -> Result<(), anyhow::Error> {
+		match fs::remove_dir_all(self.trusted_dir(consensus_state_id, trusted_height)).await {
+			Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Remove proofs of `consensus_state_id` generated longer than the retention period ago
+	pub async fn prune(&self, consensus_state_id: ConsensusStateId) -> Result<(), anyhow::Error> {
+		let Some(cutoff) = SystemTime::now().checked_sub(self.retention) else { return Ok(()) };
+		let client_dir = self.dir.join(hex::encode(consensus_state_id));
+		let Ok(mut trusted_dirs) = fs::read_dir(&client_dir).await else { return Ok(()) };
+		while let Some(trusted_dir) = trusted_dirs.next_entry().await? {
+			let modified = trusted_dir.metadata().await?.modified()?;
+			if modified < cutoff {
+				fs::remove_dir_all(trusted_dir.path()).await?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Wait until no other caller is generating a proof from `trusted_height`
+	async fn lock(
+		&self,
+		consensus_state_id: ConsensusStateId,
+		trusted_height: u64,
+	) -> OwnedMutexGuard<()> {
+		let lock = {
+			let mut in_flight = self.in_flight.lock().expect("Lock is not poisoned");
+			in_flight.retain(|_, lock| Arc::strong_count(lock) > 1);
+			in_flight.entry((consensus_state_id, trusted_height)).or_default().clone()
+		};
+		lock.lock_owned().await
+	}
+
+	fn trusted_dir(&self, consensus_state_id: ConsensusStateId, trusted_height: u64) -> PathBuf {
+		self.dir.join(hex::encode(consensus_state_id)).join(trusted_height.to_string())
+	}
+
+	/// Paths of the proofs stored from `trusted_height`
+	async fn entries(
+		&self,
+		consensus_state_id: ConsensusStateId,
+		trusted_height: u64,
+	) -> Result<Vec<PathBuf>, anyhow::Error> {
+		let Ok(mut dir) = fs::read_dir(self.trusted_dir(consensus_state_id, trusted_height)).await
+		else {
+			return Ok(vec![]);
+		};
+		let mut entries = vec![];
+		while let Some(entry) = dir.next_entry().await? {
+			let is_tmp = entry.file_name().to_str().map_or(true, |name| name.starts_with('.'));
+			if !is_tmp {
+				entries.push(entry.path());
+			}
+		}
+		Ok(entries)
+	}
+}
+
+/// Target height encoded in a proof's file name
+fn target_height(path: &Path) -> Option<u64> {
+	path.file_name()?.to_str()?.split_once('-')?.0.parse().ok()
+}
+
+/// Read a proof, discarding it if its contents don't match the hash in its name
+async fn read_proof(path: &Path) -> Result<Option<Vec<u8>>, anyhow::Error> {
+	let proof = fs::read(path).await?;
+	let expected = path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.and_then(|name| name.split_once('-'))
+		.map(|(_, hash)| hash.to_string());
+	if expected.as_deref() != Some(hex::encode(keccak_256(&proof)).as_str()) {
+		tracing::warn!(target: crate::LOG_TARGET, "Discarding corrupt consensus proof {path:?}");
+		fs::remove_file(path).await?;
+		return Ok(None);
+	}
+	Ok(Some(proof))
+}
+
+/// Fetch a consensus proof that builds on the trusted state at `trusted_height`.
+///
+/// With a process-wide [`ProofCache`], a proof already stored from `trusted_height` is returned
+/// instead of generating a new one, otherwise `prove` is called and its proof is stored under the
+/// target height it returns. Callers for the same trusted height wait for each other, so a proof
+/// is generated once no matter how many counterparties ask for it. Without a cache this just
+/// calls `prove`.
+pub async fn cached_proof<T, F, Fut>(
+	consensus_state_id: ConsensusStateId,
+	trusted_height: u64,
+	prove: F,
+) -> Result<Option<T>, anyhow::Error>
+where
+	T: Encode + Decode,
+	F: FnOnce() -> Fut,
+	Fut: Future<Output = Result<Option<(u64, T)>, anyhow::Error>>,
+{
+	let Some(cache) = ProofCache::global() else {
+		return Ok(prove().await?.map(|(_, proof)| proof));
+	};
+
+	let _guard = cache.lock(consensus_state_id, trusted_height).await;
+	match cache.latest(consensus_state_id, trusted_height).await {
+		Ok(Some((key, proof))) => match T::decode(&mut &proof[..]) {
+			Ok(proof) => {
+				tracing::trace!(target: crate::LOG_TARGET, ?key, "Reusing cached consensus proof");
+				return Ok(Some(proof));
+			},
+			Err(err) => {
+				tracing::warn!(target: crate::LOG_TARGET, ?key, "Failed to decode cached consensus proof: {err:?}");
+			},
+		},
+		Ok(None) => {},
+		Err(err) => {
+			tracing::warn!(target: crate::LOG_TARGET, "Failed to read the consensus proof cache: {err:?}");
+		},
+	}
+
+	let Some((target_height, proof)) = prove().await? else { return Ok(None) };
+	let key = ProofCacheKey { consensus_state_id, trusted_height, target_height };
+	if let Err(err) = cache.insert(&key, &proof.encode()).await {
+		tracing::warn!(target: crate::LOG_TARGET, ?key, "Failed to cache consensus proof: {err:?}");
+	}
+	if let Err(err) = cache.prune(consensus_state_id).await {
+		tracing::warn!(target: crate::LOG_TARGET, "Failed to prune the consensus proof cache: {err:?}");
+	}
+
+	Ok(Some(proof))
+}
+
+/// Drop the cached proofs from `trusted_height`, for proofs the counterparty turned out to reject
+pub async fn evict_proofs(consensus_state_id: ConsensusStateId, trusted_height: u64) {
+	let Some(cache) = ProofCache::global() else { return };
+	if let Err(err) = cache.evict(consensus_state_id, trusted_height).await {
+		tracing::warn!(target: crate::LOG_TARGET, "Failed to evict cached consensus proofs: {err:?}");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ID: ConsensusStateId = *b"TEST";
+
+	fn key(trusted_height: u64, target_height: u64) -> ProofCacheKey {
+		ProofCacheKey { consensus_state_id: ID, trusted_height, target_height }
+	}
+
+	#[tokio::test]
+	async fn stores_and_reads_back_proofs() {
+		let dir = tempfile::tempdir().unwrap();
+		let cache = ProofCache::open(dir.path()).await.unwrap();
+
+		cache.insert(&key(10, 20), b"first").await.unwrap();
+		cache.insert(&key(10, 25), b"second").await.unwrap();
+		cache.insert(&key(11, 30), b"other").await.unwrap();
+
+		assert_eq!(cache.get(&key(10, 20)).await.unwrap(), Some(b"first".to_vec()));
+		assert_eq!(cache.get(&key(10, 21)).await.unwrap(), None);
+		let (latest, proof) = cache.latest(ID, 10).await.unwrap().unwrap();
+		assert_eq!(latest, key(10, 25));
+		assert_eq!(proof, b"second".to_vec());
+		assert!(cache.latest(*b"NONE", 10).await.unwrap().is_none());
+
+		cache.evict(ID, 10).await.unwrap();
+		assert!(cache.latest(ID, 10).await.unwrap().is_none());
+		assert!(cache.latest(ID, 11).await.unwrap().is_some());
+	}
+
+	#[tokio::test]
+	async fn discards_corrupt_proofs() {
+		let dir = tempfile::tempdir().unwrap();
+		let cache = ProofCache::open(dir.path()).await.unwrap();
+
+		cache.insert(&key(10, 20), b"proof").await.unwrap();
+		let path = cache.entries(ID, 10).await.unwrap().pop().unwrap();
+		std::fs::write(&path, b"tampered").unwrap();
+
+		assert_eq!(cache.get(&key(10, 20)).await.unwrap(), None);
+		assert!(!path.exists());
+	}
+
+	#[tokio::test]
+	async fn proves_once_per_trusted_height() {
+		let dir = tempfile::tempdir().unwrap();
+		ProofCache::init(dir.path()).await.unwrap();
+		let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+		let fetch = |target_height: u64| {
+			let calls = calls.clone();
+			cached_proof(ID, 10, move || async move {
+				calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+				tokio::time::sleep(Duration::from_millis(50)).await;
+				Ok(Some((target_height, target_height.encode())))
+			})
+		};
+		let (first, second) = tokio::join!(fetch(20), fetch(21));
+		let third: Option<Vec<u8>> = fetch(22).await.unwrap();
+
+		assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+		let first: Option<Vec<u8>> = first.unwrap();
+		assert_eq!(first, second.unwrap());
+		assert_eq!(first, third);
+	}
+}
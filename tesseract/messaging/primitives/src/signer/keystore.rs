@@ -0,0 +1,251 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Password-protected keystore files
+
+use super::{KeyType, LocalSigner};
+use anyhow::{anyhow, Context};
+use base64::Engine;
+use crypto_secretbox::{aead::Aead, KeyInit, Nonce, XSalsa20Poly1305};
+use serde::Deserialize;
+use sp_core::{sr25519, Pair};
+use std::path::Path;
+
+/// Prefix of the decrypted polkadot-js key material
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+/// Separates the secret key from the public key in the decrypted key material
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+/// Length of an sr25519 secret key, the scalar followed by the nonce
+const SECRET_KEY_LENGTH: usize = 64;
+/// Length of the scrypt salt
+const SALT_LENGTH: usize = 32;
+/// Length of the xsalsa20-poly1305 nonce
+const NONCE_LENGTH: usize = 24;
+
+/// Decrypt the keystore at `path`. secp256k1 keys are read from geth-style JSON keystores,
+/// sr25519 keys from polkadot-js JSON exports.
+pub fn decrypt_keystore(
+	path: &Path,
+	password: &str,
+	key_type: KeyType,
+) -> Result<LocalSigner, anyhow::Error> {
+	match key_type {
+		KeyType::Secp256k1 => {
+			let secret = eth_keystore::decrypt_key(path, password)
+				.map_err(|err| anyhow!("Failed to decrypt keystore {path:?}: {err}"))?;
+			LocalSigner::from_seed(KeyType::Secp256k1, &secret)
+		},
+		KeyType::Sr25519 => {
+			let json = std::fs::read_to_string(path)
+				.with_context(|| format!("Failed to read keystore {path:?}"))?;
+			let keystore: PolkadotJsKeystore = serde_json::from_str(&json)
+				.with_context(|| format!("{path:?} is not a polkadot-js keystore"))?;
+			Ok(LocalSigner::Sr25519(
+				decrypt_polkadot_js(&keystore, password)
+					.with_context(|| format!("Failed to decrypt keystore {path:?}"))?,
+			))
+		},
+	}
+}
+
+/// A polkadot-js JSON account export
+#[derive(Deserialize)]
+struct PolkadotJsKeystore {
+	/// base64 encoded, encrypted key material
+	encoded: String,
+	/// How `encoded` was produced
+	encoding: PolkadotJsEncoding,
+}
+
+#[derive(Deserialize)]
+struct PolkadotJsEncoding {
+	/// Format and type of the key, `["pkcs8", "sr25519"]`
+	content: Vec<String>,
+	/// Encryption, `["scrypt", "xsalsa20-poly1305"]`
+	#[serde(rename = "type")]
+	kind: Vec<String>,
+}
+
+fn decrypt_polkadot_js(
+	keystore: &PolkadotJsKeystore,
+	password: &str,
+) -> Result<sr25519::Pair, anyhow::Error> {
+	if keystore.encoding.content.get(1).map(String::as_str) != Some("sr25519") {
+		Err(anyhow!("Keystore does not hold an sr25519 key: {:?}", keystore.encoding.content))?
+	}
+	if keystore.encoding.kind != ["scrypt", "xsalsa20-poly1305"] {
+		Err(anyhow!("Unsupported keystore encryption {:?}", keystore.encoding.kind))?
+	}
+
+	let encoded = base64::engine::general_purpose::STANDARD.decode(&keystore.encoded)?;
+	if encoded.len() < SALT_LENGTH + 12 + NONCE_LENGTH {
+		Err(anyhow!("Keystore is truncated"))?
+	}
+	let (salt, rest) = encoded.split_at(SALT_LENGTH);
+	let (params, rest) = rest.split_at(12);
+	let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+	let [n, p, r] = [0, 4, 8].map(|offset| {
+		u32::from_le_bytes(params[offset..offset + 4].try_into().expect("slice is 4 bytes"))
+	});
+	if !n.is_power_of_two() {
+		Err(anyhow!("Invalid scrypt parameter N = {n}"))?
+	}
+
+	let mut key = [0u8; 32];
+	let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, key.len())
+		.map_err(|err| anyhow!("Invalid scrypt parameters: {err}"))?;
+	scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+		.map_err(|err| anyhow!("scrypt failed: {err}"))?;
+	let decrypted = XSalsa20Poly1305::new(&key.into())
+		.decrypt(Nonce::from_slice(nonce), ciphertext)
+		.map_err(|_| anyhow!("Wrong password"))?;
+
+	let secret = decrypted
+		.strip_prefix(PKCS8_HEADER.as_slice())
+		.filter(|rest| rest.len() >= SECRET_KEY_LENGTH + PKCS8_DIVIDER.len() + 32)
+		.ok_or_else(|| anyhow!("Unexpected keystore contents"))?;
+	let (secret, rest) = secret.split_at(SECRET_KEY_LENGTH);
+	let public = rest
+		.strip_prefix(PKCS8_DIVIDER.as_slice())
+		.ok_or_else(|| anyhow!("Unexpected keystore contents"))?;
+
+	// polkadot-js stores the scalar in ed25519 form, multiplied by the cofactor
+	let mut secret: [u8; SECRET_KEY_LENGTH] = secret.try_into().expect("split at 64 bytes");
+	divide_scalar_by_cofactor(&mut secret[..32]);
+	let pair = sr25519::Pair::from_seed_slice(&secret).map_err(|err| anyhow!("{err:?}"))?;
+	if pair.public().0.as_slice() != &public[..32] {
+		Err(anyhow!("Keystore public key does not match its secret key"))?
+	}
+
+	Ok(pair)
+}
+
+/// Divides a little-endian scalar by 8
+fn divide_scalar_by_cofactor(scalar: &mut [u8]) {
+	let mut low = 0u8;
+	for byte in scalar.iter_mut().rev() {
+		let remainder = *byte & 0b111;
+		*byte >>= 3;
+		*byte += low;
+		low = remainder << 5;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::signer::RelayerSigner;
+
+	/// Multiplies a little-endian scalar by 8, the inverse of [`divide_scalar_by_cofactor`]
+	fn multiply_scalar_by_cofactor(scalar: &mut [u8]) {
+		let mut high = 0u8;
+		for byte in scalar.iter_mut() {
+			let carry = *byte & 0b1110_0000;
+			*byte <<= 3;
+			*byte += high;
+			high = carry >> 5;
+		}
+	}
+
+	/// Produces a keystore the way polkadot-js does
+	fn encrypt_polkadot_js(pair: &sr25519::Pair, password: &str) -> String {
+		let salt = [5u8; SALT_LENGTH];
+		let nonce = [9u8; NONCE_LENGTH];
+		// Cheap parameters, the format is what's being tested
+		let (n, p, r) = (1u32 << 10, 1u32, 8u32);
+
+		let mut secret = pair.to_raw_vec();
+		multiply_scalar_by_cofactor(&mut secret[..32]);
+		let mut plaintext = PKCS8_HEADER.to_vec();
+		plaintext.extend(secret);
+		plaintext.extend(PKCS8_DIVIDER);
+		plaintext.extend(pair.public().0);
+
+		let mut key = [0u8; 32];
+		scrypt::scrypt(
+			password.as_bytes(),
+			&salt,
+			&scrypt::Params::new(10, r, p, 32).unwrap(),
+			&mut key,
+		)
+		.unwrap();
+		let ciphertext = XSalsa20Poly1305::new(&key.into())
+			.encrypt(Nonce::from_slice(&nonce), &plaintext[..])
+			.unwrap();
+
+		let mut encoded = salt.to_vec();
+		for param in [n, p, r] {
+			encoded.extend(param.to_le_bytes());
+		}
+		encoded.extend(nonce);
+		encoded.extend(ciphertext);
+
+		serde_json::json!({
+			"encoded": base64::engine::general_purpose::STANDARD.encode(encoded),
+			"encoding": {
+				"content": ["pkcs8", "sr25519"],
+				"type": ["scrypt", "xsalsa20-poly1305"],
+				"version": "3"
+			},
+			"address": "",
+			"meta": {}
+		})
+		.to_string()
+	}
+
+	#[test]
+	fn cofactor_division_round_trips() {
+		let mut scalar = [0xabu8; 32];
+		scalar[31] = 0x0f;
+		let original = scalar;
+		multiply_scalar_by_cofactor(&mut scalar);
+		divide_scalar_by_cofactor(&mut scalar);
+		assert_eq!(scalar, original);
+	}
+
+	#[test]
+	fn decrypts_polkadot_js_keystores() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("relayer.json");
+		let pair = sr25519::Pair::from_seed(&[3u8; 32]);
+		std::fs::write(&path, encrypt_polkadot_js(&pair, "correct horse")).unwrap();
+
+		let signer = decrypt_keystore(&path, "correct horse", KeyType::Sr25519).unwrap();
+		assert_eq!(signer.public_key(), pair.public().0.to_vec());
+
+		assert!(decrypt_keystore(&path, "battery staple", KeyType::Sr25519).is_err());
+	}
+
+	#[test]
+	fn decrypts_geth_keystores() {
+		let dir = tempfile::tempdir().unwrap();
+		let secret = [4u8; 32];
+		let name = eth_keystore::encrypt_key(
+			dir.path(),
+			&mut rand::thread_rng(),
+			secret,
+			"correct horse",
+			Some("relayer.json"),
+		)
+		.unwrap();
+		let path = dir.path().join(name);
+
+		let signer = decrypt_keystore(&path, "correct horse", KeyType::Secp256k1).unwrap();
+		let expected = LocalSigner::from_seed(KeyType::Secp256k1, &secret).unwrap();
+		assert_eq!(signer.public_key(), expected.public_key());
+
+		assert!(decrypt_keystore(&path, "battery staple", KeyType::Secp256k1).is_err());
+	}
+}
@@ -0,0 +1,438 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relayer signing keys.
+//!
+//! The `signer` value of every chain config names where the relayer's key for that chain comes
+//! from:
+//!
+//! - `0x<hex>`, or a path to a file containing the hex-encoded key. The key is held in memory.
+//! - `keystore:<path>`, a password-protected keystore file. Geth-style JSON for secp256k1 keys and
+//!   polkadot-js JSON for sr25519 keys. The password is read from the environment variable
+//!   [`DEFAULT_PASSWORD_ENV`], or from the variable or file given as
+//!   `keystore:<path>?password_env=<VAR>` or `keystore:<path>?password_file=<path>`.
+//! - `remote:<url>?public_key=0x<hex>`, a remote signer holding the key, see [`RemoteSigner`].
+//!   [`server`] is a stand-in for such a signer.
+
+mod keystore;
+mod remote;
+pub mod server;
+
+pub use keystore::decrypt_keystore;
+pub use remote::RemoteSigner;
+
+use anyhow::{anyhow, Context};
+use sp_core::{bytes::from_hex, ecdsa, sr25519, Pair};
+use std::{fmt, path::PathBuf, str::FromStr};
+
+/// Environment variable holding the keystore password when none is configured
+pub const DEFAULT_PASSWORD_ENV: &str = "TESSERACT_KEYSTORE_PASSWORD";
+
+/// The kinds of keys used by relayers
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+	/// secp256k1 keys, used on EVM chains
+	Secp256k1,
+	/// sr25519 keys, used on substrate chains
+	Sr25519,
+}
+
+impl KeyType {
+	/// Name of the key type in remote signer urls
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			KeyType::Secp256k1 => "secp256k1",
+			KeyType::Sr25519 => "sr25519",
+		}
+	}
+}
+
+impl FromStr for KeyType {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"secp256k1" => Ok(KeyType::Secp256k1),
+			"sr25519" => Ok(KeyType::Sr25519),
+			other => Err(anyhow!("Unknown key type {other}")),
+		}
+	}
+}
+
+/// Where a keystore password is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordSource {
+	/// An environment variable
+	Env(String),
+	/// A file, trailing newlines are ignored
+	File(PathBuf),
+}
+
+impl PasswordSource {
+	/// Read the password
+	pub fn read(&self) -> Result<String, anyhow::Error> {
+		match self {
+			PasswordSource::Env(var) => std::env::var(var)
+				.with_context(|| format!("Keystore password variable {var} is not set")),
+			PasswordSource::File(path) => Ok(std::fs::read_to_string(path)
+				.with_context(|| format!("Failed to read keystore password from {path:?}"))?
+				.trim_end_matches(['\r', '\n'])
+				.to_string()),
+		}
+	}
+}
+
+/// Where a relayer key comes from, parsed from a chain's `signer` config value
+#[derive(Clone, PartialEq, Eq)]
+pub enum SignerSource {
+	/// A hex-encoded key, or the path to a file containing one
+	Key(String),
+	/// A password-protected keystore file
+	Keystore {
+		/// Path to the keystore
+		path: PathBuf,
+		/// Where the password is read from
+		password: PasswordSource,
+	},
+	/// A remote signer
+	Remote {
+		/// Base url of the signer
+		url: String,
+		/// Public key the signer signs with
+		public_key: Vec<u8>,
+	},
+}
+
+impl fmt::Debug for SignerSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			// Never print the key itself
+			SignerSource::Key(_) => f.write_str("Key(..)"),
+			SignerSource::Keystore { path, password } => f
+				.debug_struct("Keystore")
+				.field("path", path)
+				.field("password", password)
+				.finish(),
+			SignerSource::Remote { url, public_key } => f
+				.debug_struct("Remote")
+				.field("url", url)
+				.field("public_key", &hex::encode(public_key))
+				.finish(),
+		}
+	}
+}
+
+impl FromStr for SignerSource {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(rest) = s.strip_prefix("keystore:") {
+			let (path, query) = split_query(rest);
+			let password = match query {
+				None => PasswordSource::Env(DEFAULT_PASSWORD_ENV.to_string()),
+				Some(("password_env", var)) => PasswordSource::Env(var.to_string()),
+				Some(("password_file", path)) => PasswordSource::File(path.into()),
+				Some((param, _)) => Err(anyhow!("Unknown keystore parameter {param}"))?,
+			};
+			return Ok(SignerSource::Keystore { path: path.into(), password });
+		}
+
+		if let Some(rest) = s.strip_prefix("remote:") {
+			let (url, query) = split_query(rest);
+			let public_key = match query {
+				Some(("public_key", key)) =>
+					from_hex(key).map_err(|_| anyhow!("Remote signer public key must be hex"))?,
+				_ => Err(anyhow!(
+					"Remote signers must be configured as remote:<url>?public_key=0x<hex>"
+				))?,
+			};
+			return Ok(SignerSource::Remote {
+				url: url.trim_end_matches('/').to_string(),
+				public_key,
+			});
+		}
+
+		Ok(SignerSource::Key(s.to_string()))
+	}
+}
+
+/// Splits `value` into the part before `?` and the single `key=value` parameter after it
+fn split_query(value: &str) -> (&str, Option<(&str, &str)>) {
+	match value.rsplit_once('?') {
+		Some((head, query)) => match query.split_once('=') {
+			Some(param) => (head, Some(param)),
+			None => (value, None),
+		},
+		None => (value, None),
+	}
+}
+
+/// Signs on behalf of the relayer
+#[async_trait::async_trait]
+pub trait RelayerSigner: Send + Sync {
+	/// The type of the signing key
+	fn key_type(&self) -> KeyType;
+
+	/// The public key, compressed for secp256k1 keys
+	fn public_key(&self) -> Vec<u8>;
+
+	/// Sign `message`. secp256k1 signers expect a 32 byte prehashed message and return a
+	/// 65 byte `r || s || v` signature with `v` in `{27, 28}`. sr25519 signers sign the message as
+	/// is and return a 64 byte signature.
+	async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, anyhow::Error>;
+}
+
+/// A key held in memory
+#[derive(Clone)]
+pub enum LocalSigner {
+	/// A secp256k1 key
+	Secp256k1(ecdsa::Pair),
+	/// An sr25519 key
+	Sr25519(sr25519::Pair),
+}
+
+impl LocalSigner {
+	/// Generate a random key
+	pub fn generate(key_type: KeyType) -> Self {
+		match key_type {
+			KeyType::Secp256k1 => LocalSigner::Secp256k1(ecdsa::Pair::generate().0),
+			KeyType::Sr25519 => LocalSigner::Sr25519(sr25519::Pair::generate().0),
+		}
+	}
+
+	/// Construct a key from its secret seed
+	pub fn from_seed(key_type: KeyType, seed: &[u8]) -> Result<Self, anyhow::Error> {
+		Ok(match key_type {
+			KeyType::Secp256k1 => LocalSigner::Secp256k1(
+				ecdsa::Pair::from_seed_slice(seed).map_err(|err| anyhow!("{err:?}"))?,
+			),
+			KeyType::Sr25519 => LocalSigner::Sr25519(
+				sr25519::Pair::from_seed_slice(seed).map_err(|err| anyhow!("{err:?}"))?,
+			),
+		})
+	}
+
+	/// Sign `message`, see [`RelayerSigner::sign`]
+	pub fn sign_sync(&self, message: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			LocalSigner::Secp256k1(pair) => {
+				let prehash: [u8; 32] = message
+					.try_into()
+					.map_err(|_| anyhow!("secp256k1 signers only sign 32 byte prehashes"))?;
+				let mut signature = pair.sign_prehashed(&prehash).0.to_vec();
+				signature[64] += 27;
+				Ok(signature)
+			},
+			LocalSigner::Sr25519(pair) => Ok(pair.sign(message).0.to_vec()),
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl RelayerSigner for LocalSigner {
+	fn key_type(&self) -> KeyType {
+		match self {
+			LocalSigner::Secp256k1(_) => KeyType::Secp256k1,
+			LocalSigner::Sr25519(_) => KeyType::Sr25519,
+		}
+	}
+
+	fn public_key(&self) -> Vec<u8> {
+		match self {
+			LocalSigner::Secp256k1(pair) => pair.public().0.to_vec(),
+			LocalSigner::Sr25519(pair) => pair.public().0.to_vec(),
+		}
+	}
+
+	async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		self.sign_sync(message)
+	}
+}
+
+/// A relayer key, either held in memory or by a remote signer
+#[derive(Clone)]
+pub enum Signer {
+	/// A key held in memory
+	Local(LocalSigner),
+	/// A key held by a remote signer
+	Remote(RemoteSigner),
+}
+
+impl Signer {
+	/// Load the key named by a chain's `signer` config value. Chains without a signer get a
+	/// random key, they only run in inbound mode and never sign anything with it.
+	pub async fn load(signer: Option<&str>, key_type: KeyType) -> Result<Self, anyhow::Error> {
+		let Some(signer) = signer else {
+			return Ok(Signer::Local(LocalSigner::generate(key_type)))
+		};
+
+		match signer.parse::<SignerSource>()? {
+			SignerSource::Key(raw) => {
+				let bytes = match from_hex(&raw) {
+					Ok(bytes) => bytes,
+					Err(_) => {
+						// Treat the value as a file path containing hex bytes.
+						let contents = tokio::fs::read_to_string(&raw)
+							.await
+							.context("Signer must be a hex-encoded key or a path to one")?;
+						from_hex(contents.trim())?
+					},
+				};
+				Ok(Signer::Local(LocalSigner::from_seed(key_type, &bytes)?))
+			},
+			SignerSource::Keystore { path, password } => {
+				let password = password.read()?;
+				// Key derivation is deliberately slow, keep it off the async runtime
+				let signer = tokio::task::spawn_blocking(move || {
+					decrypt_keystore(&path, &password, key_type)
+				})
+				.await??;
+				Ok(Signer::Local(signer))
+			},
+			SignerSource::Remote { url, public_key } =>
+				Ok(Signer::Remote(RemoteSigner::connect(url, key_type, public_key).await?)),
+		}
+	}
+
+	/// The key, if it is held in memory
+	pub fn local(&self) -> Option<&LocalSigner> {
+		match self {
+			Signer::Local(signer) => Some(signer),
+			Signer::Remote(_) => None,
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl RelayerSigner for Signer {
+	fn key_type(&self) -> KeyType {
+		match self {
+			Signer::Local(signer) => signer.key_type(),
+			Signer::Remote(signer) => signer.key_type(),
+		}
+	}
+
+	fn public_key(&self) -> Vec<u8> {
+		match self {
+			Signer::Local(signer) => signer.public_key(),
+			Signer::Remote(signer) => signer.public_key(),
+		}
+	}
+
+	async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			Signer::Local(signer) => signer.sign_sync(message),
+			Signer::Remote(signer) => signer.sign(message).await,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	const SEED: [u8; 32] = [7u8; 32];
+
+	#[test]
+	fn parses_signer_sources() {
+		assert_eq!(
+			"0xabcd".parse::<SignerSource>().unwrap(),
+			SignerSource::Key("0xabcd".to_string())
+		);
+		assert_eq!(
+			"keystore:/keys/relayer.json".parse::<SignerSource>().unwrap(),
+			SignerSource::Keystore {
+				path: "/keys/relayer.json".into(),
+				password: PasswordSource::Env(DEFAULT_PASSWORD_ENV.to_string()),
+			}
+		);
+		assert_eq!(
+			"keystore:/keys/relayer.json?password_file=/run/secrets/pass"
+				.parse::<SignerSource>()
+				.unwrap(),
+			SignerSource::Keystore {
+				path: "/keys/relayer.json".into(),
+				password: PasswordSource::File("/run/secrets/pass".into()),
+			}
+		);
+		assert_eq!(
+			"remote:http://127.0.0.1:9000/?public_key=0x0102"
+				.parse::<SignerSource>()
+				.unwrap(),
+			SignerSource::Remote {
+				url: "http://127.0.0.1:9000".to_string(),
+				public_key: vec![1, 2]
+			}
+		);
+		assert!("remote:http://127.0.0.1:9000".parse::<SignerSource>().is_err());
+		assert!("keystore:/keys/relayer.json?password=hunter2".parse::<SignerSource>().is_err());
+	}
+
+	#[test]
+	fn debug_output_never_contains_the_key() {
+		let source = SignerSource::Key(hex::encode(SEED));
+		assert!(!format!("{source:?}").contains(&hex::encode(SEED)));
+	}
+
+	#[tokio::test]
+	async fn loads_raw_keys_as_before() {
+		let signer = Signer::load(Some(&format!("0x{}", hex::encode(SEED))), KeyType::Secp256k1)
+			.await
+			.unwrap();
+		let expected = ecdsa::Pair::from_seed_slice(&SEED).unwrap();
+		assert_eq!(signer.public_key(), expected.public().0.to_vec());
+
+		let signer = Signer::load(Some(&hex::encode(SEED)), KeyType::Sr25519).await.unwrap();
+		let expected = sr25519::Pair::from_seed_slice(&SEED).unwrap();
+		assert_eq!(signer.public_key(), expected.public().0.to_vec());
+	}
+
+	#[tokio::test]
+	async fn remote_signatures_verify_against_the_served_keys() {
+		let secp = LocalSigner::from_seed(KeyType::Secp256k1, &SEED).unwrap();
+		let sr = LocalSigner::from_seed(KeyType::Sr25519, &SEED).unwrap();
+		let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		tokio::spawn(server::serve(
+			listener,
+			vec![Arc::new(secp.clone()) as Arc<dyn RelayerSigner>, Arc::new(sr.clone())],
+		));
+
+		let prehash = [42u8; 32];
+		let source = format!("remote:{url}?public_key=0x{}", hex::encode(secp.public_key()));
+		let remote = Signer::load(Some(&source), KeyType::Secp256k1).await.unwrap();
+		let mut signature = remote.sign(&prehash).await.unwrap();
+		assert_eq!(signature, secp.sign_sync(&prehash).unwrap());
+		signature[64] -= 27;
+		let signature = ecdsa::Signature::from_raw(signature.try_into().unwrap());
+		assert_eq!(signature.recover_prehashed(&prehash).unwrap().0.to_vec(), secp.public_key());
+
+		let message = b"withdraw relayer fees";
+		let source = format!("remote:{url}?public_key=0x{}", hex::encode(sr.public_key()));
+		let remote = Signer::load(Some(&source), KeyType::Sr25519).await.unwrap();
+		let signature = remote.sign(message).await.unwrap();
+		let LocalSigner::Sr25519(pair) = &sr else { unreachable!() };
+		let signature = sr25519::Signature::from_raw(signature.try_into().unwrap());
+		assert!(sr25519::Pair::verify(&signature, message, &pair.public()));
+
+		// The signer refuses keys it doesn't hold, so misconfiguration fails at startup
+		let source = format!("remote:{url}?public_key=0x{}", hex::encode([3u8; 33]));
+		assert!(Signer::load(Some(&source), KeyType::Secp256k1).await.is_err());
+	}
+}
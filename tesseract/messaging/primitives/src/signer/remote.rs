@@ -0,0 +1,117 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client for remote signers
+
+use super::{KeyType, RelayerSigner};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use sp_core::bytes::from_hex;
+use std::time::Duration;
+
+/// Body of a signing request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignRequest {
+	/// Hex-encoded message to sign
+	pub data: String,
+}
+
+/// A key held by a remote signer speaking a Web3Signer-style HTTP api:
+///
+/// - `GET {url}/api/v1/{key_type}/publicKeys` returns a JSON array of the hex-encoded public keys
+///   the signer holds.
+/// - `POST {url}/api/v1/{key_type}/sign/0x{public_key}` with a [`SignRequest`] body returns the
+///   hex-encoded signature as plain text, in the format described by [`RelayerSigner::sign`].
+#[derive(Clone)]
+pub struct RemoteSigner {
+	client: reqwest::Client,
+	url: String,
+	key_type: KeyType,
+	public_key: Vec<u8>,
+}
+
+impl RemoteSigner {
+	/// Connect to the signer at `url`, checking that it holds `public_key`
+	pub async fn connect(
+		url: String,
+		key_type: KeyType,
+		public_key: Vec<u8>,
+	) -> Result<Self, anyhow::Error> {
+		let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+		let keys = client
+			.get(format!("{url}/api/v1/{}/publicKeys", key_type.as_str()))
+			.send()
+			.await
+			.and_then(|response| response.error_for_status())
+			.with_context(|| format!("Remote signer at {url} is unreachable"))?
+			.json::<Vec<String>>()
+			.await?;
+
+		let held = keys.iter().any(|key| from_hex(key).ok().as_ref() == Some(&public_key));
+		if !held {
+			Err(anyhow!(
+				"Remote signer at {url} does not hold the {} key 0x{}",
+				key_type.as_str(),
+				hex::encode(&public_key)
+			))?
+		}
+
+		Ok(Self { client, url, key_type, public_key })
+	}
+}
+
+#[async_trait::async_trait]
+impl RelayerSigner for RemoteSigner {
+	fn key_type(&self) -> KeyType {
+		self.key_type
+	}
+
+	fn public_key(&self) -> Vec<u8> {
+		self.public_key.clone()
+	}
+
+	async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		let signature = self
+			.client
+			.post(format!(
+				"{}/api/v1/{}/sign/0x{}",
+				self.url,
+				self.key_type.as_str(),
+				hex::encode(&self.public_key)
+			))
+			.json(&SignRequest { data: format!("0x{}", hex::encode(message)) })
+			.send()
+			.await
+			.and_then(|response| response.error_for_status())
+			.with_context(|| format!("Remote signer at {} failed to sign", self.url))?
+			.text()
+			.await?;
+		let signature = from_hex(signature.trim())
+			.map_err(|_| anyhow!("Remote signer returned a malformed signature"))?;
+
+		let expected = match self.key_type {
+			KeyType::Secp256k1 => 65,
+			KeyType::Sr25519 => 64,
+		};
+		if signature.len() != expected {
+			Err(anyhow!(
+				"Remote signer returned a {} byte signature, expected {expected}",
+				signature.len()
+			))?
+		}
+
+		Ok(signature)
+	}
+}
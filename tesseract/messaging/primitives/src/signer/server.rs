@@ -0,0 +1,89 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A stand-in remote signer, serving the api [`RemoteSigner`](super::RemoteSigner) expects for
+//! keys held in memory. Meant for local testing, it has no authentication.
+
+use super::{remote::SignRequest, KeyType, RelayerSigner};
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	routing::{get, post},
+	Json, Router,
+};
+use sp_core::bytes::from_hex;
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::net::TcpListener;
+
+type Signers = Arc<BTreeMap<(KeyType, Vec<u8>), Arc<dyn RelayerSigner>>>;
+
+/// Serve `signers` on `listener` until the task is dropped
+pub async fn serve(
+	listener: TcpListener,
+	signers: Vec<Arc<dyn RelayerSigner>>,
+) -> Result<(), anyhow::Error> {
+	let signers: Signers = Arc::new(
+		signers
+			.into_iter()
+			.map(|signer| ((signer.key_type(), signer.public_key()), signer))
+			.collect(),
+	);
+	let app = Router::new()
+		.route("/upcheck", get(|| async { "OK" }))
+		.route("/api/v1/:key_type/publicKeys", get(public_keys))
+		.route("/api/v1/:key_type/sign/:public_key", post(sign))
+		.with_state(signers);
+
+	axum::serve(listener, app).await?;
+
+	Ok(())
+}
+
+async fn public_keys(
+	State(signers): State<Signers>,
+	Path(key_type): Path<String>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+	let key_type = key_type.parse::<KeyType>().map_err(|_| StatusCode::NOT_FOUND)?;
+	Ok(Json(
+		signers
+			.keys()
+			.filter(|(kind, _)| *kind == key_type)
+			.map(|(_, public_key)| format!("0x{}", hex::encode(public_key)))
+			.collect(),
+	))
+}
+
+async fn sign(
+	State(signers): State<Signers>,
+	Path((key_type, public_key)): Path<(String, String)>,
+	Json(request): Json<SignRequest>,
+) -> Result<String, (StatusCode, String)> {
+	let key_type = key_type
+		.parse::<KeyType>()
+		.map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+	let public_key = from_hex(&public_key)
+		.map_err(|_| (StatusCode::BAD_REQUEST, "Public key must be hex".to_string()))?;
+	let signer = signers
+		.get(&(key_type, public_key))
+		.ok_or_else(|| (StatusCode::NOT_FOUND, "Unknown key".to_string()))?;
+	let message = from_hex(&request.data)
+		.map_err(|_| (StatusCode::BAD_REQUEST, "Data must be hex".to_string()))?;
+	let signature = signer
+		.sign(&message)
+		.await
+		.map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+
+	Ok(format!("0x{}", hex::encode(signature)))
+}
@@ -217,6 +217,10 @@ where
 		self.evm.query_challenge_period(id).await
 	}
 
+	async fn query_consensus_expiry(&self, id: ConsensusStateId) -> Result<Duration, Error> {
+		self.evm.query_consensus_expiry(id).await
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, Error> {
 		self.evm.query_timestamp().await
 	}
@@ -365,8 +369,8 @@ where
 		self.evm.address()
 	}
 
-	fn sign(&self, msg: &[u8]) -> Signature {
-		self.evm.sign(msg)
+	async fn sign(&self, msg: &[u8]) -> Result<Signature, anyhow::Error> {
+		self.evm.sign(msg).await
 	}
 
 	async fn set_latest_finalized_height(
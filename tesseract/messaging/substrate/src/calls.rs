@@ -1,7 +1,7 @@
 //! Functions for updating configuration on pallets
 
 use crate::{
-	extrinsic::{send_unsigned_extrinsic, system_dry_run_unsigned, InMemorySigner},
+	extrinsic::{send_unsigned_extrinsic, system_dry_run_unsigned},
 	SubstrateClient,
 };
 use anyhow::anyhow;
@@ -21,7 +21,6 @@ use pallet_ismp_relayer::{
 	OutboundConsensusDeliveryClaim, OutboundRequestDeliveryClaim,
 };
 use pallet_state_coprocessor::impls::GetRequestsWithProof;
-use polkadot_sdk::sp_core::Pair;
 use sp_core::{
 	storage::{ChildInfo, StorageData, StorageKey},
 	U256,
@@ -42,7 +41,7 @@ use subxt::{
 	OnlineClient,
 };
 use subxt_utils::{
-	relayer_account_balance_storage_key, relayer_nonce_storage_key, send_extrinsic,
+	relayer_account_balance_storage_key, relayer_nonce_storage_key,
 	values::{
 		create_consensus_state_to_value, get_requests_with_proof_to_value,
 		host_params_btreemap_to_value, outbound_consensus_delivery_claim_to_value,
@@ -77,16 +76,6 @@ where
 		&self,
 		message: CreateConsensusState,
 	) -> Result<(), anyhow::Error> {
-		let binding = self.signer.public();
-		let public_key_slice: &[u8] = binding.as_ref();
-
-		let public_key_array: [u8; 32] =
-			public_key_slice.try_into().expect("sr25519 public key should be 32 bytes");
-
-		let account_id = AccountId32::from(public_key_array);
-
-		let signer = InMemorySigner { account_id: account_id.into(), signer: self.signer.clone() };
-
 		let call = subxt::dynamic::tx(
 			"Ismp",
 			"create_consensus_client",
@@ -94,7 +83,7 @@ where
 		);
 
 		let sudo_payload = subxt::dynamic::tx("Sudo", "sudo", vec![call.into_value()]);
-		send_extrinsic(&self.client, &signer, &sudo_payload, None, true).await?;
+		self.submit_signed(&sudo_payload).await?;
 
 		Ok(())
 	}
@@ -110,8 +99,7 @@ where
 		);
 		let sudo_payload =
 			subxt::dynamic::tx("Sudo", "sudo", vec![host_executive_payload.into_value()]);
-		let signer = InMemorySigner::new(self.signer.clone());
-		send_extrinsic(&self.client, &signer, &sudo_payload, None, true).await?;
+		self.submit_signed(&sudo_payload).await?;
 
 		Ok(())
 	}
@@ -262,7 +250,7 @@ where
 				fetch_relayer_nonce(&self.client, &self.rpc, self.address.clone(), chain).await?;
 
 			let message = message(nonce, chain, Some(counterparty.address().clone()));
-			let signature = { self.sign(&message) };
+			let signature = self.sign(&message).await?;
 
 			results.push(
 				execute_withdrawal(
@@ -280,7 +268,7 @@ where
 			fetch_relayer_nonce(&self.client, &self.rpc, counterparty.address(), chain).await?;
 
 		let message = message(nonce, chain, None);
-		let signature = { counterparty.sign(&message) };
+		let signature = counterparty.sign(&message).await?;
 
 		results.push(execute_withdrawal(self, None, signature, counterparty.clone(), chain).await?);
 
@@ -362,7 +350,6 @@ where
 		state_machine_id: ismp::consensus::StateMachineId,
 		proxy: primitive_types::H160,
 	) -> anyhow::Result<()> {
-		let signer = self.in_memory_signer();
 		let call = subxt::dynamic::tx(
 			"Fishermen",
 			"blacklist_dispute_game",
@@ -371,7 +358,7 @@ where
 				subxt::ext::scale_value::Value::from_bytes(proxy.0.to_vec()),
 			],
 		);
-		send_extrinsic(&self.client, &signer, &call, Some(100), true).await?;
+		self.submit_signed(&call).await?;
 		Ok(())
 	}
 
@@ -380,7 +367,6 @@ where
 		state_machine_id: ismp::consensus::StateMachineId,
 		claim: primitive_types::H256,
 	) -> anyhow::Result<()> {
-		let signer = self.in_memory_signer();
 		let call = subxt::dynamic::tx(
 			"Fishermen",
 			"blacklist_arbitrum_claim",
@@ -389,7 +375,7 @@ where
 				subxt::ext::scale_value::Value::from_bytes(claim.0.to_vec()),
 			],
 		);
-		send_extrinsic(&self.client, &signer, &call, Some(100), true).await?;
+		self.submit_signed(&call).await?;
 		Ok(())
 	}
 }
@@ -16,10 +16,10 @@
 /// Log/tracing target for this crate.
 pub const LOG_TARGET: &str = "messaging-substrate";
 
-use anyhow::Context;
+use anyhow::anyhow;
 use std::sync::Arc;
 
-use polkadot_sdk::sp_core::{bytes::from_hex, sr25519, Pair};
+use polkadot_sdk::sp_core::sr25519;
 use serde::{Deserialize, Serialize};
 use subxt::{
 	backend::legacy::LegacyRpcMethods,
@@ -35,9 +35,14 @@ use pallet_ismp::child_trie::{
 	request_commitment_storage_key, request_receipt_storage_key, response_receipt_storage_key,
 };
 use substrate_state_machine::HashAlgorithm;
-use tesseract_primitives::{IsmpProvider, StateMachineUpdated, StreamError};
+use tesseract_primitives::{
+	health::EndpointHealthConfig,
+	signer::{KeyType, LocalSigner, RelayerSigner, Signer},
+	IsmpProvider, StateMachineUpdated, StreamError,
+};
 
 pub use crate::provider::system_events_key;
+use crate::transport::PooledRpcClient;
 
 mod byzantine;
 pub mod calls;
@@ -45,6 +50,7 @@ pub mod config;
 pub mod extrinsic;
 mod provider;
 pub mod registry;
+mod transport;
 
 #[cfg(feature = "testing")]
 mod testing;
@@ -77,6 +83,13 @@ pub struct SubstrateConfig {
 	pub poll_interval: Option<u64>,
 	/// Decimals for the fee token on this substrate chain
 	pub fee_token_decimals: Option<u8>,
+	/// Additional websocket RPC urls for the chain. When set, requests are routed to the
+	/// healthiest of these and `rpc_ws`, and extrinsics are broadcast to several of them.
+	#[serde(default)]
+	pub additional_rpc_ws: Vec<String>,
+	/// Health tracking of the RPC endpoints when `additional_rpc_ws` is set
+	#[serde(default)]
+	pub rpc_health: EndpointHealthConfig,
 }
 
 impl SubstrateConfig {
@@ -128,12 +141,12 @@ pub struct SubstrateClient<C: subxt::Config> {
 	state_machine: StateMachine,
 	/// The hashing algorithm that substrate chain uses.
 	hashing: HashAlgorithm,
-	/// Private key of the signing account. For chains the operator did not
-	/// configure a signer for, this is freshly generated. The relayer's
-	/// `outbound_enabled()` filter keeps signer-less chains out of any task
-	/// that would actually broadcast an extrinsic, so the dummy is never
-	/// used to send anything.
-	pub signer: sr25519::Pair,
+	/// Key of the signing account, which may be held by a remote signer. For
+	/// chains the operator did not configure a signer for, this is freshly
+	/// generated. The relayer's `outbound_enabled()` filter keeps signer-less
+	/// chains out of any task that would actually broadcast an extrinsic, so
+	/// the dummy is never used to send anything.
+	pub signer: Arc<Signer>,
 	/// Public Address
 	pub address: Vec<u8>,
 	/// Initial height from which to start querying messages
@@ -161,8 +174,14 @@ where
 	pub async fn new(config: SubstrateConfig) -> Result<Self, anyhow::Error> {
 		let config_clone = config.clone();
 		let max_rpc_payload_size = config.max_rpc_payload_size.unwrap_or(300u32 * 1024 * 1024);
-		let (client, rpc_client) =
-			subxt_utils::client::ws_client::<C>(&config.rpc_ws, max_rpc_payload_size).await?;
+		let (client, rpc_client) = if config.additional_rpc_ws.is_empty() {
+			subxt_utils::client::ws_client::<C>(&config.rpc_ws, max_rpc_payload_size).await?
+		} else {
+			let rpc_client =
+				RpcClient::new(PooledRpcClient::connect(&config, max_rpc_payload_size).await?);
+			let client = OnlineClient::<C>::from_rpc_client(rpc_client.clone()).await?;
+			(client, rpc_client)
+		};
 		let rpc = LegacyRpcMethods::<C>::new(rpc_client.clone());
 		// If latest height of the state machine on the counterparty is not provided in config
 		// Set it to the latest parachain height
@@ -175,19 +194,13 @@ where
 				.number()
 				.into()
 		};
-		// If the operator configured a signer, parse it; otherwise generate a
+		// If the operator configured a signer, load it; otherwise generate a
 		// throwaway one so all the signer-shaped fields downstream still
 		// type-check. Inbound-only chains never reach a path that signs,
 		// because the relayer's `outbound_enabled()` filter keeps them out
 		// of outbound and fee-withdrawal tasks before any signing call.
-		let signer = match config.signer.as_deref() {
-			Some(raw) => {
-				let bytes = from_hex(raw).context("Signer must be a valid hex-encoded String")?;
-				sr25519::Pair::from_seed_slice(&bytes)?
-			},
-			None => sr25519::Pair::generate().0,
-		};
-		let address = signer.public().0.to_vec();
+		let signer = Arc::new(Signer::load(config.signer.as_deref(), KeyType::Sr25519).await?);
+		let address = signer.public_key();
 		let mut consensus_state_id: ConsensusStateId = Default::default();
 		consensus_state_id.copy_from_slice(config.consensus_state_id.expect("Resolved").as_bytes());
 
@@ -207,8 +220,13 @@ where
 		})
 	}
 
-	pub fn signer(&self) -> sr25519::Pair {
-		self.signer.clone()
+	/// The signing key, for callers that have to sign synchronously. Fails if the key is held
+	/// by a remote signer.
+	pub fn signer(&self) -> Result<sr25519::Pair, anyhow::Error> {
+		match self.signer.local() {
+			Some(LocalSigner::Sr25519(pair)) => Ok(pair.clone()),
+			_ => Err(anyhow!("The signer for {:?} is not held in memory", self.state_machine)),
+		}
 	}
 
 	/// Resolved state machine identifier for this client.
@@ -222,11 +240,8 @@ where
 	}
 
 	pub fn account(&self) -> C::AccountId {
-		let binding = self.signer.public();
-		let public_key_slice: &[u8] = binding.as_ref();
-
 		let public_key_array: [u8; 32] =
-			public_key_slice.try_into().expect("Public key must be 32 bytes");
+			self.address.as_slice().try_into().expect("Public key must be 32 bytes");
 
 		let account_id = subxt::utils::AccountId32::from(public_key_array);
 
@@ -24,7 +24,7 @@ use hex_literal::hex;
 use polkadot_sdk::{
 	sp_core::{
 		storage::{ChildInfo, StorageData, StorageKey},
-		Pair, H160, U256,
+		H160, U256,
 	},
 	sp_io::hashing::keccak_256,
 };
@@ -39,7 +39,9 @@ use subxt::{
 };
 
 use ismp::{
-	consensus::{ConsensusClientId, StateCommitment, StateMachineHeight, StateMachineId},
+	consensus::{
+		ConsensusClientId, ConsensusStateId, StateCommitment, StateMachineHeight, StateMachineId,
+	},
 	events::{Event, StateCommitmentVetoed},
 	host::StateMachine,
 	messaging::{hash_request, CreateConsensusState, Message},
@@ -54,19 +56,19 @@ use pallet_ismp_relayer::withdrawal::Signature;
 use pallet_ismp_rpc::BlockNumberOrHash;
 use substrate_state_machine::StateMachineProof;
 use subxt_utils::{
-	host_params_storage_key, send_extrinsic, state_machine_commitment_storage_key,
+	host_params_storage_key, send_extrinsic_with_signature, state_machine_commitment_storage_key,
 	state_machine_update_time_storage_key,
 	values::{messages_to_value, state_machine_height_to_value},
 };
 use tesseract_primitives::{
-	wait_for_challenge_period, BoxStream, EstimateGasReturnParams, Hasher, IsmpProvider,
-	ProofAccepted, Query, StateMachineUpdated, StateProofQueryType,
+	signer::RelayerSigner, wait_for_challenge_period, BoxStream, EstimateGasReturnParams, Hasher,
+	IsmpProvider, ProofAccepted, Query, StateMachineUpdated, StateProofQueryType,
 	StorageKey as TesseractStorageKey, TxReceipt, TxResult,
 };
 
 use crate::{
 	calls::RequestMetadata,
-	extrinsic::{send_unsigned_extrinsic, system_dry_run_unsigned, InMemorySigner},
+	extrinsic::{send_unsigned_extrinsic, system_dry_run_unsigned},
 	SubstrateClient,
 };
 
@@ -85,18 +87,30 @@ impl<C> SubstrateClient<C>
 where
 	C: subxt::Config + Send + Sync + Clone,
 {
-	/// Construct an in-memory signer for this client's configured key. Shared by every
-	/// extrinsic-submitting trait method.
-	pub(crate) fn in_memory_signer(&self) -> InMemorySigner<C>
+	/// Sign `payload` with this client's configured key and submit it, waiting for
+	/// finalization. Shared by every extrinsic-submitting method, the key may be held by a
+	/// remote signer.
+	pub(crate) async fn submit_signed<Tx: Payload>(
+		&self,
+		payload: &Tx,
+	) -> Result<HashFor<C>, anyhow::Error>
 	where
-		<C as subxt::Config>::AccountId: From<subxt::utils::AccountId32>,
+		C::AccountId: From<AccountId32> + Into<C::Address> + Clone + 'static,
+		C::Signature: From<MultiSignature> + Send + Sync,
+		<C::ExtrinsicParams as ExtrinsicParams<C>>::Params: Send + Sync + DefaultParams,
 	{
-		let binding = self.signer.public();
-		let public_key_slice: &[u8] = binding.as_ref();
-		let public_key_array: [u8; 32] =
-			public_key_slice.try_into().expect("Public key must be 32 bytes");
-		let account_id = AccountId32::from(public_key_array);
-		InMemorySigner { account_id: account_id.into(), signer: self.signer.clone() }
+		let account_id = AccountId32::from(
+			<[u8; 32]>::try_from(self.address.as_slice()).expect("Public key must be 32 bytes"),
+		);
+		let signer = self.signer.clone();
+		let sign = |payload: Vec<u8>| async move {
+			signer
+				.sign(&payload)
+				.await?
+				.try_into()
+				.map_err(|_| anyhow!("Expected a 64 byte sr25519 signature"))
+		};
+		send_extrinsic_with_signature(&self.client, account_id.into(), sign, payload, true).await
 	}
 
 	/// Scans `frame_system::Events` across parachain blocks `(cursor, tip]` in
@@ -833,12 +847,13 @@ where
 		let is_hyperbridge = self.state_machine == coprocessor;
 		for msg in &mut messages {
 			if let Some(bytes) = encode_message(&msg) {
-				let signature = self.sign(&bytes);
+				let signature = self.sign(&bytes).await?;
 				let encoded_signer = signature.encode();
 
 				match msg {
 					Message::Request(ref mut req) => req.signer = encoded_signer,
 					Message::Response(ref mut res) => res.signer = encoded_signer,
+					Message::AggregatedRequest(ref mut req) => req.signer = encoded_signer,
 					Message::Consensus(ref mut con) => con.signer = encoded_signer,
 					_ => {},
 				}
@@ -895,28 +910,29 @@ where
 			block.number().into()
 		};
 		for msg in messages {
-			match msg {
-				Message::Request(req_msg) =>
-					for post in req_msg.requests {
-						let req = Request::Post(post);
-						let commitment = hash_request::<Hasher>(&req);
-						if receipts.contains(&commitment) {
-							let tx_receipt = TxReceipt {
-								query: Query {
-									source_chain: req.source_chain(),
-									dest_chain: req.dest_chain(),
-									nonce: req.nonce(),
-									commitment,
-								},
-								height,
-							};
-
-							results.push(tx_receipt);
-						}
-					},
+			let posts = match msg {
+				Message::Request(req_msg) => req_msg.requests,
+				Message::AggregatedRequest(req_msg) => req_msg.requests(),
 				// `Message::Response` carries only GetRequests being responded to post-#840;
 				// no relayer receipt to record.
-				_ => {},
+				_ => continue,
+			};
+			for post in posts {
+				let req = Request::Post(post);
+				let commitment = hash_request::<Hasher>(&req);
+				if receipts.contains(&commitment) {
+					let tx_receipt = TxReceipt {
+						query: Query {
+							source_chain: req.source_chain(),
+							dest_chain: req.dest_chain(),
+							nonce: req.nonce(),
+							commitment,
+						},
+						height,
+					};
+
+					results.push(tx_receipt);
+				}
 			}
 		}
 		Ok(TxResult { receipts: results, ..Default::default() })
@@ -929,6 +945,16 @@ where
 		Ok(Duration::from_secs(response))
 	}
 
+	async fn query_consensus_expiry(
+		&self,
+		id: ConsensusStateId,
+	) -> Result<Duration, anyhow::Error> {
+		let params = rpc_params![id];
+		let response: u64 = self.rpc_client.request("ismp_queryConsensusExpiry", params).await?;
+
+		Ok(Duration::from_secs(response))
+	}
+
 	async fn query_timestamp(&self) -> Result<Duration, anyhow::Error> {
 		let timestamp_key =
 			hex!("f0c365c3cf59d671eb72da0e7a4113c49f1f0515f462cdcf84e0f1d6045dfcbb").to_vec();
@@ -954,9 +980,9 @@ where
 		self.address.clone()
 	}
 
-	fn sign(&self, msg: &[u8]) -> tesseract_primitives::Signature {
-		let signature = self.signer.sign(msg).0.to_vec();
-		Signature::Sr25519 { public_key: self.address.clone(), signature }
+	async fn sign(&self, msg: &[u8]) -> Result<tesseract_primitives::Signature, anyhow::Error> {
+		let signature = self.signer.sign(msg).await?;
+		Ok(Signature::Sr25519 { public_key: self.address.clone(), signature })
 	}
 
 	async fn set_latest_finalized_height(
@@ -994,13 +1020,12 @@ where
 	}
 
 	async fn veto_state_commitment(&self, height: StateMachineHeight) -> Result<(), Error> {
-		let signer = self.in_memory_signer();
 		let call = subxt::dynamic::tx(
 			"Fishermen",
 			"veto_state_commitment",
 			vec![state_machine_height_to_value(&height)],
 		);
-		send_extrinsic(&self.client, &signer, &call, Some(100), true).await?;
+		self.submit_signed(&call).await?;
 		Ok(())
 	}
 
@@ -1046,6 +1071,8 @@ pub fn system_events_key() -> StorageKey {
 fn encode_message(msg: &Message) -> Option<[u8; 32]> {
 	return match msg {
 		Message::Request(request_message) => Some(keccak_256(&request_message.requests.encode())),
+		Message::AggregatedRequest(request_message) =>
+			Some(keccak_256(&request_message.requests().encode())),
 		Message::Response(response_message) =>
 			Some(keccak_256(&response_message.requests.encode())),
 		Message::Consensus(consensus_message) =>
@@ -1091,6 +1118,8 @@ mod tests {
 			max_concurent_queries: None,
 			poll_interval: None,
 			fee_token_decimals: None,
+			additional_rpc_ws: vec![],
+			rpc_health: Default::default(),
 		};
 
 		let client =
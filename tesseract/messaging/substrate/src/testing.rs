@@ -27,15 +27,12 @@ use subxt::{
 };
 
 use pallet_ismp_demo::{EvmParams, GetRequest, TransferParams};
-use subxt_utils::{
-	send_extrinsic,
-	values::{
-		account_vec_to_value, evm_params_to_value, get_request_ismp_demo_to_value,
-		transfer_params_to_value,
-	},
+use subxt_utils::values::{
+	account_vec_to_value, evm_params_to_value, get_request_ismp_demo_to_value,
+	transfer_params_to_value,
 };
 
-use crate::{extrinsic::InMemorySigner, SubstrateClient};
+use crate::SubstrateClient;
 
 impl<C> SubstrateClient<C>
 where
@@ -60,16 +57,14 @@ where
 			vec![transfer_params_to_value::<C>(&params)],
 		);
 
-		let signer = InMemorySigner::new(self.signer.clone());
-		let tx_block_hash = send_extrinsic(&self.client, &signer, &call, None, true).await?;
+		let tx_block_hash = self.submit_signed(&call).await?;
 		Ok(tx_block_hash)
 	}
 
 	pub async fn dispatch_to_evm(&self, params: EvmParams) -> Result<(), anyhow::Error> {
 		let call =
 			subxt::dynamic::tx("IsmpDemo", "dispatch_to_evm", vec![evm_params_to_value(&params)]);
-		let signer = InMemorySigner::new(self.signer.clone());
-		send_extrinsic(&self.client, &signer, &call, None, true).await?;
+		self.submit_signed(&call).await?;
 
 		Ok(())
 	}
@@ -80,8 +75,7 @@ where
 			"get_request",
 			vec![get_request_ismp_demo_to_value(&get_req)],
 		);
-		let signer = InMemorySigner::new(self.signer.clone());
-		let tx_block_hash = send_extrinsic(&self.client, &signer, &tx, None, true).await?;
+		let tx_block_hash = self.submit_signed(&tx).await?;
 
 		Ok(tx_block_hash)
 	}
@@ -139,8 +133,7 @@ where
 
 		let tx = subxt::dynamic::tx("Sudo", "sudo", vec![call.into_value()]);
 
-		let signer = InMemorySigner::new(self.signer().clone());
-		send_extrinsic(&self.client, &signer, &tx, None, true).await?;
+		self.submit_signed(&tx).await?;
 
 		Ok(())
 	}
@@ -152,8 +145,7 @@ where
 			vec![account_vec_to_value::<C>(&accounts)],
 		);
 		let tx = subxt::dynamic::tx("Sudo", "sudo", vec![call.into_value()]);
-		let signer = InMemorySigner::new(self.signer().clone());
-		send_extrinsic(&self.client, &signer, &tx, None, true).await?;
+		self.submit_signed(&tx).await?;
 
 		Ok(())
 	}
@@ -0,0 +1,320 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rpc client for substrate chains configured with `additional_rpc_ws`.
+//!
+//! [`PooledRpcClient`] holds a reconnecting websocket client per endpoint and routes every request
+//! according to the health of the endpoints tracked in an [`EndpointPool`]. Requests and
+//! subscriptions go to the healthiest endpoint and move on to the next one if the endpoint fails.
+//! Extrinsic submissions are broadcast to the healthiest few endpoints at once. Watched
+//! submissions are watched on the healthiest endpoint, while the other endpoints are only sent the
+//! extrinsic.
+
+use std::{future::Future, iter, sync::Arc, time::Instant};
+
+use anyhow::anyhow;
+use futures::future::select_ok;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use subxt::ext::subxt_rpcs::{
+	client::{
+		reconnecting_rpc_client::RpcClient as ReconnectingRpcClient, RawRpcFuture,
+		RawRpcSubscription, RpcClientT,
+	},
+	Error as RpcError,
+};
+use tesseract_primitives::health::EndpointPool;
+
+use crate::{SubstrateConfig, LOG_TARGET};
+
+/// RPC method that submits an extrinsic, sent to several endpoints at once.
+const SUBMIT_METHOD: &str = "author_submitExtrinsic";
+
+/// Subscriptions that submit an extrinsic and watch its status. Their only param is the
+/// extrinsic, like [`SUBMIT_METHOD`]'s.
+const SUBMIT_AND_WATCH_METHODS: &[&str] =
+	&["author_submitAndWatchExtrinsic", "transactionWatch_v1_submitAndWatch"];
+
+/// Rpc client over several endpoints of the same chain.
+pub struct PooledRpcClient<C = ReconnectingRpcClient> {
+	clients: Vec<C>,
+	pool: Arc<EndpointPool>,
+}
+
+impl PooledRpcClient {
+	/// Connect to `rpc_ws` and every url in `additional_rpc_ws`, and start probing their heads.
+	/// Endpoints that can't be reached on startup are left out until the next restart.
+	pub async fn connect(
+		config: &SubstrateConfig,
+		max_rpc_payload_size: u32,
+	) -> Result<Self, anyhow::Error> {
+		let mut urls = vec![];
+		let mut clients = vec![];
+		for url in iter::once(&config.rpc_ws).chain(&config.additional_rpc_ws) {
+			match subxt_utils::client::ws_rpc_client(url, max_rpc_payload_size).await {
+				Ok(client) => {
+					urls.push(url.clone());
+					clients.push(client);
+				},
+				Err(err) => {
+					log::warn!(target: LOG_TARGET, "Skipping unreachable rpc endpoint {url}: {err:?}");
+				},
+			}
+		}
+		if clients.is_empty() {
+			Err(anyhow!("None of the rpc endpoints of the substrate chain are reachable"))?
+		}
+
+		let pool = Arc::new(EndpointPool::new(urls, config.rpc_health.clone()));
+		let probes = clients.clone();
+		pool.spawn_probe(move |index| {
+			let client = probes[index].clone();
+			async move { head(&client).await }
+		});
+
+		Ok(Self { clients, pool })
+	}
+}
+
+impl<C: RpcClientT + Clone> PooledRpcClient<C> {
+	/// Send the extrinsic in `params` to every broadcast target except `watched`, without waiting
+	/// for the outcome.
+	fn broadcast_extrinsic(&self, watched: usize, params: Option<Box<RawValue>>) {
+		for index in self.pool.broadcast_targets().into_iter().filter(|index| *index != watched) {
+			let client = self.clients[index].clone();
+			let pool = self.pool.clone();
+			let params = params.clone();
+			tokio::spawn(async move {
+				let _ = record(&pool, index, client.request_raw(SUBMIT_METHOD, params)).await;
+			});
+		}
+	}
+}
+
+impl<C: RpcClientT + Clone> RpcClientT for PooledRpcClient<C> {
+	fn request_raw<'a>(
+		&'a self,
+		method: &'a str,
+		params: Option<Box<RawValue>>,
+	) -> RawRpcFuture<'a, Box<RawValue>> {
+		Box::pin(async move {
+			if method == SUBMIT_METHOD {
+				let sends = self.pool.broadcast_targets().into_iter().map(|index| {
+					let client = self.clients[index].clone();
+					let pool = self.pool.clone();
+					let (method, params) = (method.to_string(), params.clone());
+					// Spawned so the slower submissions still reach their endpoint once one
+					// succeeds
+					let handle = tokio::spawn(async move {
+						record(&pool, index, client.request_raw(&method, params)).await
+					});
+					Box::pin(
+						async move { handle.await.map_err(|err| RpcError::Client(Box::new(err)))? },
+					)
+				});
+				return select_ok(sends).await.map(|(response, _)| response);
+			}
+
+			let mut last_err = None;
+			for index in self.pool.ranked() {
+				let request = self.clients[index].request_raw(method, params.clone());
+				match record(&self.pool, index, request).await {
+					Err(err) if is_endpoint_failure(&err) => last_err = Some(err),
+					res => return res,
+				}
+			}
+			Err(last_err.unwrap_or_else(|| RpcError::Client("no rpc endpoints".into())))
+		})
+	}
+
+	fn subscribe_raw<'a>(
+		&'a self,
+		sub: &'a str,
+		params: Option<Box<RawValue>>,
+		unsub: &'a str,
+	) -> RawRpcFuture<'a, RawRpcSubscription> {
+		Box::pin(async move {
+			// The subscription stays on the endpoint it was opened on, which resubscribes after a
+			// reconnect
+			let mut last_err = None;
+			for index in self.pool.ranked() {
+				let subscription = self.clients[index].subscribe_raw(sub, params.clone(), unsub);
+				match record(&self.pool, index, subscription).await {
+					Err(err) if is_endpoint_failure(&err) => last_err = Some(err),
+					res => {
+						if res.is_ok() && SUBMIT_AND_WATCH_METHODS.contains(&sub) {
+							self.broadcast_extrinsic(index, params);
+						}
+						return res;
+					},
+				}
+			}
+			Err(last_err.unwrap_or_else(|| RpcError::Client("no rpc endpoints".into())))
+		})
+	}
+}
+
+/// Await a request to the endpoint at `index` and record the outcome.
+async fn record<T>(
+	pool: &EndpointPool,
+	index: usize,
+	request: impl Future<Output = Result<T, RpcError>>,
+) -> Result<T, RpcError> {
+	let started = Instant::now();
+	let res = request.await;
+	match &res {
+		Err(err) if is_endpoint_failure(err) => {
+			log::debug!(target: LOG_TARGET, "Rpc request to {} failed: {err:?}", pool.url(index));
+			pool.record_failure(index);
+		},
+		_ => pool.record_success(index, started.elapsed()),
+	}
+	res
+}
+
+/// Error responses from the node (invalid extrinsics, unknown blocks, ...) are answers, not
+/// failures of the endpoint.
+fn is_endpoint_failure(err: &RpcError) -> bool {
+	!matches!(err, RpcError::User(_) | RpcError::Deserialization(_))
+}
+
+/// Number of the best block of the endpoint.
+async fn head(client: &impl RpcClientT) -> Result<u64, anyhow::Error> {
+	#[derive(Deserialize)]
+	struct Header {
+		number: String,
+	}
+
+	let header = client.request_raw("chain_getHeader", None).await?;
+	let header: Header = serde_json::from_str(header.get())?;
+	Ok(u64::from_str_radix(header.number.trim_start_matches("0x"), 16)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{sync::Mutex, time::Duration};
+	use subxt::ext::subxt_rpcs::client::RawRpcSubscription;
+
+	/// An endpoint that records the methods it's called with.
+	#[derive(Clone, Default)]
+	struct Endpoint {
+		calls: Arc<Mutex<Vec<String>>>,
+		down: bool,
+	}
+
+	impl Endpoint {
+		fn calls(&self) -> Vec<String> {
+			self.calls.lock().unwrap().clone()
+		}
+
+		fn call(&self, method: &str) -> Result<(), RpcError> {
+			self.calls.lock().unwrap().push(method.to_string());
+			if self.down {
+				Err(RpcError::Client("connection refused".into()))?
+			}
+			Ok(())
+		}
+	}
+
+	impl RpcClientT for Endpoint {
+		fn request_raw<'a>(
+			&'a self,
+			method: &'a str,
+			_params: Option<Box<RawValue>>,
+		) -> RawRpcFuture<'a, Box<RawValue>> {
+			Box::pin(async move {
+				self.call(method)?;
+				Ok(RawValue::from_string("\"0x01\"".into()).unwrap())
+			})
+		}
+
+		fn subscribe_raw<'a>(
+			&'a self,
+			sub: &'a str,
+			_params: Option<Box<RawValue>>,
+			_unsub: &'a str,
+		) -> RawRpcFuture<'a, RawRpcSubscription> {
+			Box::pin(async move {
+				self.call(sub)?;
+				Ok(RawRpcSubscription { stream: Box::pin(futures::stream::empty()), id: None })
+			})
+		}
+	}
+
+	fn client(endpoints: &[Endpoint]) -> PooledRpcClient<Endpoint> {
+		let urls = (0..endpoints.len()).map(|i| format!("ws://rpc-{i}")).collect();
+		PooledRpcClient {
+			clients: endpoints.to_vec(),
+			pool: Arc::new(EndpointPool::new(urls, Default::default())),
+		}
+	}
+
+	fn extrinsic() -> Option<Box<RawValue>> {
+		Some(RawValue::from_string("[\"0x0400\"]".into()).unwrap())
+	}
+
+	#[tokio::test]
+	async fn watched_submissions_are_broadcast() {
+		let endpoints = vec![Endpoint::default(), Endpoint::default(), Endpoint::default()];
+		let client = client(&endpoints);
+		client
+			.subscribe_raw("author_submitAndWatchExtrinsic", extrinsic(), "author_unwatchExtrinsic")
+			.await
+			.unwrap();
+		// Let the spawned submissions run
+		tokio::time::sleep(Duration::from_millis(10)).await;
+
+		assert_eq!(endpoints[0].calls(), vec!["author_submitAndWatchExtrinsic"]);
+		assert_eq!(endpoints[1].calls(), vec![SUBMIT_METHOD]);
+		assert_eq!(endpoints[2].calls(), vec![SUBMIT_METHOD]);
+	}
+
+	#[tokio::test]
+	async fn subscriptions_fail_over_without_broadcasting() {
+		let endpoints = vec![
+			Endpoint { down: true, ..Default::default() },
+			Endpoint::default(),
+			Endpoint::default(),
+		];
+		let client = client(&endpoints);
+		client
+			.subscribe_raw("chain_subscribeNewHeads", None, "chain_unsubscribeNewHeads")
+			.await
+			.unwrap();
+		tokio::time::sleep(Duration::from_millis(10)).await;
+
+		assert_eq!(endpoints[0].calls(), vec!["chain_subscribeNewHeads"]);
+		assert_eq!(endpoints[1].calls(), vec!["chain_subscribeNewHeads"]);
+		assert!(endpoints[2].calls().is_empty());
+	}
+
+	#[tokio::test]
+	async fn submissions_succeed_if_any_endpoint_accepts() {
+		let endpoints = vec![
+			Endpoint { down: true, ..Default::default() },
+			Endpoint::default(),
+			Endpoint::default(),
+		];
+		let client = client(&endpoints);
+		let response = client.request_raw(SUBMIT_METHOD, extrinsic()).await.unwrap();
+		assert_eq!(response.get(), "\"0x01\"");
+		tokio::time::sleep(Duration::from_millis(10)).await;
+
+		for endpoint in &endpoints {
+			assert_eq!(endpoint.calls(), vec![SUBMIT_METHOD]);
+		}
+	}
+}
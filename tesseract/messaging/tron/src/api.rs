@@ -23,8 +23,8 @@
 use anyhow::{anyhow, Context};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sp_core::Pair;
 use std::time::Duration;
+use tesseract_primitives::signer::RelayerSigner;
 
 /// Configuration for a [`TronApi`] client.
 #[derive(Debug, Clone)]
@@ -196,11 +196,132 @@ impl TronApi {
 	/// Fetch account resource information (energy, bandwidth).
 	///
 	/// Maps to `POST /wallet/getaccountresource`.
-	pub async fn get_account_resource(&self, address: &str) -> anyhow::Result<serde_json::Value> {
+	pub async fn get_account_resource(
+		&self,
+		address: &str,
+	) -> anyhow::Result<AccountResourceResponse> {
 		self.post("/wallet/getaccountresource", &serde_json::json!({ "address": address }))
 			.await
 	}
 
+	/// Fetch the resources `from` has delegated to `to` under Stake 2.0.
+	///
+	/// Maps to `POST /wallet/getdelegatedresourcev2`.
+	pub async fn get_delegated_resource(
+		&self,
+		from: &str,
+		to: &str,
+	) -> anyhow::Result<DelegatedResourceResponse> {
+		self.post(
+			"/wallet/getdelegatedresourcev2",
+			&serde_json::json!({ "fromAddress": from, "toAddress": to }),
+		)
+		.await
+	}
+
+	/// Build an **unsigned** Stake 2.0 transaction staking `balance` SUN for `resource`.
+	///
+	/// Maps to `POST /wallet/freezebalancev2`.
+	pub async fn freeze_balance(
+		&self,
+		owner: &str,
+		balance: u64,
+		resource: ResourceCode,
+	) -> anyhow::Result<UnsignedTransaction> {
+		self.system_contract(
+			"/wallet/freezebalancev2",
+			serde_json::json!({
+				"owner_address": owner,
+				"frozen_balance": balance,
+				"resource": resource,
+			}),
+		)
+		.await
+	}
+
+	/// Build an **unsigned** Stake 2.0 transaction unstaking `balance` SUN staked for
+	/// `resource`. The TRX can be withdrawn once the unstaking period has passed.
+	///
+	/// Maps to `POST /wallet/unfreezebalancev2`.
+	pub async fn unfreeze_balance(
+		&self,
+		owner: &str,
+		balance: u64,
+		resource: ResourceCode,
+	) -> anyhow::Result<UnsignedTransaction> {
+		self.system_contract(
+			"/wallet/unfreezebalancev2",
+			serde_json::json!({
+				"owner_address": owner,
+				"unfreeze_balance": balance,
+				"resource": resource,
+			}),
+		)
+		.await
+	}
+
+	/// Build an **unsigned** transaction delegating the resources of `balance` staked SUN to
+	/// `receiver`.
+	///
+	/// Maps to `POST /wallet/delegateresource`.
+	pub async fn delegate_resource(
+		&self,
+		owner: &str,
+		receiver: &str,
+		balance: u64,
+		resource: ResourceCode,
+	) -> anyhow::Result<UnsignedTransaction> {
+		self.system_contract(
+			"/wallet/delegateresource",
+			serde_json::json!({
+				"owner_address": owner,
+				"receiver_address": receiver,
+				"balance": balance,
+				"resource": resource,
+				"lock": false,
+			}),
+		)
+		.await
+	}
+
+	/// Build an **unsigned** transaction reclaiming the resources of `balance` staked SUN
+	/// delegated to `receiver`.
+	///
+	/// Maps to `POST /wallet/undelegateresource`.
+	pub async fn undelegate_resource(
+		&self,
+		owner: &str,
+		receiver: &str,
+		balance: u64,
+		resource: ResourceCode,
+	) -> anyhow::Result<UnsignedTransaction> {
+		self.system_contract(
+			"/wallet/undelegateresource",
+			serde_json::json!({
+				"owner_address": owner,
+				"receiver_address": receiver,
+				"balance": balance,
+				"resource": resource,
+			}),
+		)
+		.await
+	}
+
+	/// Call an endpoint that builds a system contract transaction. These return the unsigned
+	/// transaction itself, or an object with an `Error` field.
+	async fn system_contract(
+		&self,
+		path: &str,
+		body: serde_json::Value,
+	) -> anyhow::Result<UnsignedTransaction> {
+		let raw: serde_json::Value = self.post(path, &body).await?;
+		if let Some(error) = raw.get("Error") {
+			return Err(anyhow!("POST {path} failed: {error}"));
+		}
+		serde_json::from_value(raw.clone())
+			.with_context(|| format!("POST {path}: failed to parse transaction: {raw}"))
+	}
+
 	/// Fetch the chain parameters.
 	///
 	/// Maps to `POST /wallet/getchainparameters`.
@@ -387,20 +508,24 @@ pub struct SignedTransaction {
 }
 
 impl SignedTransaction {
-	/// Sign an [`UnsignedTransaction`] using the provided secp256k1 secret key.
+	/// Sign an [`UnsignedTransaction`] with the relayer's secp256k1 key, which may be held by a
+	/// remote signer.
 	///
 	/// TRON signing: `signature = secp256k1_sign(SHA256(raw_data))`.
-	/// The key is the raw 32-byte secret key.
-	pub fn sign(unsigned: UnsignedTransaction, secret_key: &[u8; 32]) -> anyhow::Result<Self> {
+	pub async fn sign(
+		unsigned: UnsignedTransaction,
+		signer: &dyn RelayerSigner,
+	) -> anyhow::Result<Self> {
 		let tx_id_bytes = unsigned.compute_tx_id()?;
 
-		let signing_key =
-			sp_core::ecdsa::Pair::from_seed_slice(secret_key).context("invalid secret key")?;
-
-		// TRON expects a raw secp256k1 signature (r || s || v), 65 bytes.
-		// sp_core::ecdsa::Pair::sign produces a 65-byte compact signature.
-		let sig = signing_key.sign_prehashed(&tx_id_bytes);
-		let sig_hex = hex::encode(sig.0);
+		// TRON expects a raw secp256k1 signature (r || s || v), 65 bytes, with `v` as the bare
+		// recovery id. Relayer signers return `v` in `{27, 28}`.
+		let mut sig = signer.sign(&tx_id_bytes).await?;
+		if sig.len() != 65 {
+			Err(anyhow!("Expected a 65 byte secp256k1 signature, got {} bytes", sig.len()))?
+		}
+		sig[64] = sig[64].checked_sub(27).context("invalid signature recovery id")?;
+		let sig_hex = hex::encode(sig);
 
 		Ok(Self {
 			visible: unsigned.visible,
@@ -590,6 +715,128 @@ pub struct AccountResponse {
 	/// Balance in SUN.
 	#[serde(default)]
 	pub balance: u64,
+	/// SUN staked under Stake 2.0 and not delegated, by resource.
+	#[serde(rename = "frozenV2", default)]
+	pub frozen_v2: Vec<FrozenV2>,
+	/// Staked SUN whose bandwidth is delegated to other accounts.
+	#[serde(rename = "delegated_frozenV2_balance_for_bandwidth", default)]
+	pub delegated_for_bandwidth: u64,
+	/// Energy related account fields.
+	#[serde(default)]
+	pub account_resource: AccountEnergyResource,
+}
+
+impl AccountResponse {
+	/// SUN staked for `resource`, including stake delegated to other accounts.
+	pub fn staked(&self, resource: ResourceCode) -> u64 {
+		let own = self
+			.frozen_v2
+			.iter()
+			.filter(|frozen| frozen.kind.unwrap_or(ResourceCode::Bandwidth) == resource)
+			.map(|frozen| frozen.amount)
+			.sum::<u64>();
+		let delegated = match resource {
+			ResourceCode::Energy => self.account_resource.delegated_for_energy,
+			ResourceCode::Bandwidth => self.delegated_for_bandwidth,
+		};
+		own + delegated
+	}
+}
+
+/// A Stake 2.0 balance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FrozenV2 {
+	/// The staked resource, absent for bandwidth.
+	#[serde(rename = "type", default)]
+	pub kind: Option<ResourceCode>,
+	/// Staked SUN.
+	#[serde(default)]
+	pub amount: u64,
+}
+
+/// The `account_resource` sub-object inside [`AccountResponse`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountEnergyResource {
+	/// Staked SUN whose energy is delegated to other accounts.
+	#[serde(rename = "delegated_frozenV2_balance_for_energy", default)]
+	pub delegated_for_energy: u64,
+}
+
+/// A staked resource, as named by the Stake 2.0 endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResourceCode {
+	/// Used to execute smart contracts.
+	Energy,
+	/// Used for the transaction's size.
+	Bandwidth,
+}
+
+/// Response from `getaccountresource`. Every field is absent when zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountResourceResponse {
+	/// Daily free bandwidth.
+	#[serde(rename = "freeNetLimit", default)]
+	pub free_net_limit: u64,
+	/// Free bandwidth used in the current window.
+	#[serde(rename = "freeNetUsed", default)]
+	pub free_net_used: u64,
+	/// Bandwidth from staked TRX.
+	#[serde(rename = "NetLimit", default)]
+	pub net_limit: u64,
+	/// Staked bandwidth used in the current window.
+	#[serde(rename = "NetUsed", default)]
+	pub net_used: u64,
+	/// Energy from staked TRX.
+	#[serde(rename = "EnergyLimit", default)]
+	pub energy_limit: u64,
+	/// Energy used in the current window.
+	#[serde(rename = "EnergyUsed", default)]
+	pub energy_used: u64,
+	/// Energy available to all stakers on the network.
+	#[serde(rename = "TotalEnergyLimit", default)]
+	pub total_energy_limit: u64,
+	/// TRX staked for energy across the network.
+	#[serde(rename = "TotalEnergyWeight", default)]
+	pub total_energy_weight: u64,
+	/// Bandwidth available to all stakers on the network.
+	#[serde(rename = "TotalNetLimit", default)]
+	pub total_net_limit: u64,
+	/// TRX staked for bandwidth across the network.
+	#[serde(rename = "TotalNetWeight", default)]
+	pub total_net_weight: u64,
+}
+
+impl AccountResourceResponse {
+	/// Energy the account can spend right now.
+	pub fn energy_available(&self) -> u64 {
+		self.energy_limit.saturating_sub(self.energy_used)
+	}
+
+	/// Bandwidth the account can spend right now, free and staked.
+	pub fn bandwidth_available(&self) -> u64 {
+		self.free_net_limit.saturating_sub(self.free_net_used) +
+			self.net_limit.saturating_sub(self.net_used)
+	}
+}
+
+/// Response from `getdelegatedresourcev2`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DelegatedResourceResponse {
+	/// Delegations between the two accounts.
+	#[serde(rename = "delegatedResource", default)]
+	pub delegated_resource: Vec<DelegatedResource>,
+}
+
+/// Resources one account delegated to another.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DelegatedResource {
+	/// Staked SUN whose energy is delegated.
+	#[serde(default)]
+	pub frozen_balance_for_energy: u64,
+	/// Staked SUN whose bandwidth is delegated.
+	#[serde(default)]
+	pub frozen_balance_for_bandwidth: u64,
 }
 
 /// Chain parameters response.
@@ -0,0 +1,549 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Energy and bandwidth for relayer transactions.
+//!
+//! Contract calls on TRON consume energy and bandwidth, and whatever the
+//! sending account doesn't have is paid for by burning TRX. Before each
+//! submission the relayer compares the account's available resources with the
+//! transaction's estimate and asks its [`EnergyProvider`] to cover the
+//! shortfall:
+//!
+//! - [`StakeProvider`] stakes the relayer's own TRX under Stake 2.0, first reclaiming stake it
+//!   delegated to other accounts.
+//! - [`CatFeeProvider`] rents energy from CatFee.
+//! - [`MockEnergyProvider`] records requests, for tests.
+//!
+//! Each provider spends against a configured budget. Once it is exhausted the
+//! shortfall is burned, bounded by the transaction's `fee_limit`. A top-up that
+//! fails for any other reason fails the submission.
+
+use std::{
+	collections::VecDeque,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use tesseract_primitives::signer::Signer;
+
+use crate::{
+	address::{base58_to_hex, hex_to_base58, is_base58_address},
+	api::{AccountResourceResponse, ResourceCode, SignedTransaction, TronApi, UnsignedTransaction},
+	catfee::{CatFeeClient, CatFeeConfig},
+	tx::wait_for_receipt,
+};
+
+/// 1 TRX in SUN.
+const SUN_PER_TRX: u64 = 1_000_000;
+
+/// How long CatFee spending counts against the budget.
+const CATFEE_BUDGET_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Chain parameter holding the SUN burned per unit of energy.
+const ENERGY_FEE_PARAMETER: &str = "getEnergyFee";
+
+/// Energy and bandwidth amounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Resources {
+	/// Energy, consumed by contract execution.
+	pub energy: u64,
+	/// Bandwidth, consumed by the transaction's size.
+	pub bandwidth: u64,
+}
+
+impl Resources {
+	/// What the account is missing to cover `self` without burning TRX.
+	pub fn shortfall(&self, account: &AccountResourceResponse) -> Resources {
+		Resources {
+			energy: self.energy.saturating_sub(account.energy_available()),
+			bandwidth: self.bandwidth.saturating_sub(account.bandwidth_available()),
+		}
+	}
+
+	/// Whether there's nothing to cover.
+	pub fn is_zero(&self) -> bool {
+		self.energy == 0 && self.bandwidth == 0
+	}
+}
+
+/// A way of getting energy and bandwidth onto the relayer's account.
+#[async_trait::async_trait]
+pub trait EnergyProvider: Send + Sync {
+	/// Name used in logs.
+	fn name(&self) -> &'static str;
+
+	/// Make at least `shortfall` more resources available to the relayer's
+	/// account. `account` is the account's current state. Returns without
+	/// topping up if the provider's budget doesn't cover the shortfall.
+	async fn top_up(
+		&self,
+		shortfall: Resources,
+		account: &AccountResourceResponse,
+	) -> anyhow::Result<()>;
+}
+
+/// Which [`EnergyProvider`] a TRON chain uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum EnergyConfig {
+	/// Stake the relayer's own TRX.
+	Stake(StakeConfig),
+	/// Rent energy from CatFee.
+	Catfee(CatFeeProviderConfig),
+}
+
+/// Configuration for the [`StakeProvider`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeConfig {
+	/// Most SUN the relayer may have staked for each resource, including stake
+	/// delegated to other accounts.
+	pub max_stake_sun: u64,
+	/// Accounts the relayer delegated stake to. Their delegations are
+	/// reclaimed before any more TRX is staked.
+	#[serde(default)]
+	pub reclaim_from: Vec<String>,
+}
+
+/// Configuration for the [`CatFeeProvider`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatFeeProviderConfig {
+	/// CatFee API key.
+	pub api_key: String,
+	/// CatFee API secret.
+	pub api_secret: String,
+	/// Rental period in hours, 1 or 24.
+	#[serde(default = "default_rental_period")]
+	pub period_hours: u32,
+	/// Most SUN to spend on rentals in any 24 hours. Unlimited when omitted.
+	#[serde(default)]
+	pub max_spend_sun_per_day: Option<u64>,
+}
+
+fn default_rental_period() -> u32 {
+	1
+}
+
+/// Build the provider described by `config`.
+pub fn build_provider(
+	config: EnergyConfig,
+	api: TronApi,
+	owner_address: String,
+	signer: Arc<Signer>,
+	timeout: Duration,
+) -> anyhow::Result<Arc<dyn EnergyProvider>> {
+	Ok(match config {
+		EnergyConfig::Stake(config) => {
+			if config.max_stake_sun < SUN_PER_TRX {
+				Err(anyhow!("max_stake_sun must allow staking at least 1 TRX"))?
+			}
+			Arc::new(StakeProvider { api, owner_address, signer, config })
+		},
+		EnergyConfig::Catfee(config) => {
+			if config.period_hours != 1 && config.period_hours != 24 {
+				Err(anyhow!("CatFee rental period must be 1 or 24 hours"))?
+			}
+			let client = CatFeeClient::new(CatFeeConfig {
+				api_key: config.api_key.clone(),
+				api_secret: config.api_secret.clone(),
+				timeout,
+				..Default::default()
+			})?;
+			let receiver = if is_base58_address(&owner_address) {
+				owner_address
+			} else {
+				hex_to_base58(&owner_address)
+					.context("Failed to convert TRON address from hex to base58")?
+			};
+			Arc::new(CatFeeProvider {
+				client,
+				api,
+				receiver,
+				period_hours: config.period_hours,
+				budget: SpendBudget::new(config.max_spend_sun_per_day, CATFEE_BUDGET_WINDOW),
+			})
+		},
+	})
+}
+
+/// Cover the shortfall between `required` and what the relayer's account has.
+pub async fn ensure_resources(
+	provider: &dyn EnergyProvider,
+	api: &TronApi,
+	owner_address: &str,
+	required: Resources,
+) -> anyhow::Result<()> {
+	let account = api.get_account_resource(owner_address).await?;
+	let shortfall = required.shortfall(&account);
+	log::trace!(
+		target: crate::LOG_TARGET, "Resources required: {required:?}, available: energy={}, bandwidth={}",
+		account.energy_available(),
+		account.bandwidth_available(),
+	);
+	if shortfall.is_zero() {
+		return Ok(());
+	}
+
+	log::info!(
+		target: crate::LOG_TARGET, "Topping up {shortfall:?} via {}",
+		provider.name(),
+	);
+	provider.top_up(shortfall, &account).await
+}
+
+/// SUN to stake for `amount` more of a resource, given the network's total
+/// resource limit and total staked TRX. Rounded up to whole TRX.
+pub fn stake_for(amount: u64, total_limit: u64, total_weight: u64) -> anyhow::Result<u64> {
+	if total_limit == 0 {
+		Err(anyhow!("Network resource limit is unknown"))?
+	}
+	let trx = (amount as u128 * total_weight.max(1) as u128).div_ceil(total_limit as u128);
+	Ok((trx.max(1) as u64).saturating_mul(SUN_PER_TRX))
+}
+
+/// The Stake 2.0 endpoints are called with hex addresses.
+fn to_hex_address(address: &str) -> anyhow::Result<String> {
+	if is_base58_address(address) {
+		base58_to_hex(address)
+	} else {
+		Ok(address.to_string())
+	}
+}
+
+/// Stakes the relayer's own TRX under Stake 2.0.
+pub struct StakeProvider {
+	api: TronApi,
+	owner_address: String,
+	signer: Arc<Signer>,
+	config: StakeConfig,
+}
+
+impl StakeProvider {
+	/// Make `sun` more staked SUN for `resource` count towards the relayer,
+	/// reclaiming delegations before staking new TRX.
+	async fn stake(&self, resource: ResourceCode, mut sun: u64) -> anyhow::Result<()> {
+		for receiver in &self.config.reclaim_from {
+			if sun == 0 {
+				return Ok(());
+			}
+			let receiver = &to_hex_address(receiver)?;
+			let delegations =
+				self.api.get_delegated_resource(&self.owner_address, receiver).await?;
+			let delegated = delegations
+				.delegated_resource
+				.iter()
+				.map(|delegation| match resource {
+					ResourceCode::Energy => delegation.frozen_balance_for_energy,
+					ResourceCode::Bandwidth => delegation.frozen_balance_for_bandwidth,
+				})
+				.sum::<u64>();
+			let reclaim = delegated.min(sun);
+			if reclaim == 0 {
+				continue;
+			}
+
+			log::info!(
+				target: crate::LOG_TARGET, "Reclaiming {reclaim} SUN of {resource:?} stake delegated to {receiver}",
+			);
+			let tx = self
+				.api
+				.undelegate_resource(&self.owner_address, receiver, reclaim, resource)
+				.await?;
+			self.send(tx).await?;
+			sun -= reclaim;
+		}
+		if sun == 0 {
+			return Ok(());
+		}
+
+		let staked = self.api.get_account(&self.owner_address).await?.staked(resource);
+		let allowed = self.config.max_stake_sun.saturating_sub(staked);
+		// Stake is in whole TRX
+		let sun = sun.min(allowed) / SUN_PER_TRX * SUN_PER_TRX;
+		if sun == 0 {
+			log::warn!(
+				target: crate::LOG_TARGET, "{resource:?} stake budget of {} SUN is exhausted ({staked} SUN staked), burning TRX instead",
+				self.config.max_stake_sun
+			);
+			return Ok(());
+		}
+
+		log::info!(target: crate::LOG_TARGET, "Staking {sun} SUN for {resource:?}");
+		let tx = self.api.freeze_balance(&self.owner_address, sun, resource).await?;
+		self.send(tx).await
+	}
+
+	/// Unstake `sun` SUN staked for `resource`. The TRX can be withdrawn once
+	/// the network's unstaking period has passed.
+	pub async fn unstake(&self, resource: ResourceCode, sun: u64) -> anyhow::Result<()> {
+		let tx = self.api.unfreeze_balance(&self.owner_address, sun, resource).await?;
+		self.send(tx).await
+	}
+
+	/// Delegate the resources of `sun` staked SUN to `receiver`.
+	pub async fn delegate(
+		&self,
+		receiver: &str,
+		resource: ResourceCode,
+		sun: u64,
+	) -> anyhow::Result<()> {
+		let receiver = to_hex_address(receiver)?;
+		let tx = self
+			.api
+			.delegate_resource(&self.owner_address, &receiver, sun, resource)
+			.await?;
+		self.send(tx).await
+	}
+
+	/// Sign, broadcast and wait for a staking transaction.
+	async fn send(&self, tx: UnsignedTransaction) -> anyhow::Result<()> {
+		let tx_id = tx.tx_id.clone();
+		let signed = SignedTransaction::sign(tx, self.signer.as_ref())
+			.await
+			.context("failed to sign TRON transaction")?;
+		self.api.broadcast_transaction(&signed).await?.into_result()?;
+		let info = wait_for_receipt(&self.api, &tx_id).await?;
+		// System contracts have no contract result, only failures are flagged
+		if info.result.as_deref() == Some("FAILED") {
+			Err(anyhow!("Staking transaction {tx_id} failed: {:?}", info.res_message))?
+		}
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl EnergyProvider for StakeProvider {
+	fn name(&self) -> &'static str {
+		"stake"
+	}
+
+	async fn top_up(
+		&self,
+		shortfall: Resources,
+		account: &AccountResourceResponse,
+	) -> anyhow::Result<()> {
+		if shortfall.energy > 0 {
+			let sun = stake_for(
+				shortfall.energy,
+				account.total_energy_limit,
+				account.total_energy_weight,
+			)?;
+			self.stake(ResourceCode::Energy, sun).await?;
+		}
+		if shortfall.bandwidth > 0 {
+			let sun =
+				stake_for(shortfall.bandwidth, account.total_net_limit, account.total_net_weight)?;
+			self.stake(ResourceCode::Bandwidth, sun).await?;
+		}
+		Ok(())
+	}
+}
+
+/// Rents energy from CatFee.
+pub struct CatFeeProvider {
+	client: CatFeeClient,
+	api: TronApi,
+	/// The relayer's base58 address.
+	receiver: String,
+	period_hours: u32,
+	budget: SpendBudget,
+}
+
+#[async_trait::async_trait]
+impl EnergyProvider for CatFeeProvider {
+	fn name(&self) -> &'static str {
+		"catfee"
+	}
+
+	async fn top_up(
+		&self,
+		shortfall: Resources,
+		_account: &AccountResourceResponse,
+	) -> anyhow::Result<()> {
+		// Only energy is rented, bandwidth is cheap enough to burn for
+		if shortfall.energy == 0 {
+			return Ok(());
+		}
+		// CatFee only prices an order once it's placed, so a purchase is budgeted at the cost
+		// of burning TRX for the same energy, the most a rental is worth.
+		let energy_fee = self
+			.api
+			.get_chain_parameters()
+			.await?
+			.get(ENERGY_FEE_PARAMETER)
+			.ok_or_else(|| anyhow!("Chain parameter {ENERGY_FEE_PARAMETER} is missing"))?;
+		let cost = shortfall.energy.saturating_mul(energy_fee);
+		if !self.budget.has_room_for(cost) {
+			log::warn!(
+				target: crate::LOG_TARGET, "CatFee daily budget can't cover {cost} SUN ({} SUN spent), burning TRX instead",
+				self.budget.spent(),
+			);
+			return Ok(());
+		}
+
+		log::info!(target: crate::LOG_TARGET, "Purchasing {} energy units via CatFee", shortfall.energy);
+		let order = self
+			.client
+			.purchase_energy(
+				shortfall.energy,
+				&self.receiver,
+				self.period_hours,
+				Duration::from_secs(180),
+			)
+			.await
+			.context("Failed to purchase energy via CatFee")?;
+		self.budget.record(order.pay_amount_sun.max(0) as u64);
+
+		log::info!(
+			target: crate::LOG_TARGET, "Energy purchase completed successfully ({} SUN)",
+			order.pay_amount_sun,
+		);
+		Ok(())
+	}
+}
+
+/// Spending within a rolling window.
+pub struct SpendBudget {
+	limit: Option<u64>,
+	window: Duration,
+	spent: parking_lot::Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl SpendBudget {
+	/// A budget of `limit` per `window`, unlimited when `limit` is `None`.
+	pub fn new(limit: Option<u64>, window: Duration) -> Self {
+		Self { limit, window, spent: Default::default() }
+	}
+
+	/// Amount spent within the window.
+	pub fn spent(&self) -> u64 {
+		let mut spent = self.spent.lock();
+		while spent.front().is_some_and(|(at, _)| at.elapsed() >= self.window) {
+			spent.pop_front();
+		}
+		spent.iter().map(|(_, amount)| amount).sum()
+	}
+
+	/// Whether `amount` more may be spent.
+	pub fn has_room_for(&self, amount: u64) -> bool {
+		self.limit.map_or(true, |limit| self.spent().saturating_add(amount) <= limit)
+	}
+
+	/// Record a purchase.
+	pub fn record(&self, amount: u64) {
+		self.spent.lock().push_back((Instant::now(), amount));
+	}
+}
+
+/// Records top-up requests instead of acquiring anything.
+#[cfg(any(test, feature = "testing"))]
+#[derive(Default)]
+pub struct MockEnergyProvider {
+	/// Shortfalls passed to [`EnergyProvider::top_up`].
+	pub requests: parking_lot::Mutex<Vec<Resources>>,
+	/// Fail every top-up.
+	pub fail: bool,
+}
+
+#[cfg(any(test, feature = "testing"))]
+#[async_trait::async_trait]
+impl EnergyProvider for MockEnergyProvider {
+	fn name(&self) -> &'static str {
+		"mock"
+	}
+
+	async fn top_up(
+		&self,
+		shortfall: Resources,
+		_account: &AccountResourceResponse,
+	) -> anyhow::Result<()> {
+		self.requests.lock().push(shortfall);
+		if self.fail {
+			Err(anyhow!("mock top-up failed"))?
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn account() -> AccountResourceResponse {
+		AccountResourceResponse {
+			free_net_limit: 600,
+			free_net_used: 100,
+			net_limit: 1_000,
+			net_used: 1_000,
+			energy_limit: 50_000,
+			energy_used: 20_000,
+			total_energy_limit: 180_000_000_000,
+			total_energy_weight: 18_000_000_000,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn computes_shortfall_from_available_resources() {
+		let account = account();
+		let required = Resources { energy: 100_000, bandwidth: 400 };
+		assert_eq!(required.shortfall(&account), Resources { energy: 70_000, bandwidth: 0 });
+		assert!(Resources { energy: 30_000, bandwidth: 500 }.shortfall(&account).is_zero());
+	}
+
+	#[test]
+	fn stakes_whole_trx_for_the_shortfall() {
+		let account = account();
+		// 10 energy per staked TRX
+		let sun =
+			stake_for(70_000, account.total_energy_limit, account.total_energy_weight).unwrap();
+		assert_eq!(sun, 7_000 * SUN_PER_TRX);
+		let sun = stake_for(1, account.total_energy_limit, account.total_energy_weight).unwrap();
+		assert_eq!(sun, SUN_PER_TRX);
+		assert!(stake_for(1, 0, 0).is_err());
+	}
+
+	#[test]
+	fn budget_tracks_spending() {
+		let budget = SpendBudget::new(Some(100), Duration::from_secs(60));
+		assert!(budget.has_room_for(100));
+		assert!(!budget.has_room_for(101));
+		budget.record(60);
+		assert!(budget.has_room_for(40));
+		// A purchase that would overshoot the limit is refused, even with room left
+		assert!(!budget.has_room_for(41));
+		budget.record(40);
+		assert!(!budget.has_room_for(1));
+		assert_eq!(budget.spent(), 100);
+
+		let budget = SpendBudget::new(Some(100), Duration::ZERO);
+		budget.record(500);
+		assert!(budget.has_room_for(100));
+
+		assert!(SpendBudget::new(None, Duration::from_secs(60)).has_room_for(u64::MAX));
+	}
+
+	#[tokio::test]
+	async fn mock_provider_records_requests() {
+		let provider = MockEnergyProvider::default();
+		let shortfall = Resources { energy: 70_000, bandwidth: 0 };
+		provider.top_up(shortfall, &account()).await.unwrap();
+		assert_eq!(*provider.requests.lock(), vec![shortfall]);
+
+		let provider = MockEnergyProvider { fail: true, ..Default::default() };
+		assert!(provider.top_up(shortfall, &account()).await.is_err());
+	}
+}
@@ -47,25 +47,24 @@ pub const LOG_TARGET: &str = "messaging-tron";
 pub mod address;
 pub mod api;
 pub mod catfee;
+pub mod energy;
 pub mod provider;
 pub mod tx;
 
 use std::sync::Arc;
 
-use anyhow::anyhow;
 use ismp::{host::StateMachine, messaging::Message};
-use polkadot_sdk::frame_support::crypto;
 use serde::{Deserialize, Serialize};
-use sp_core::Pair;
 use tesseract_evm::{EvmClient, EvmConfig};
 use tesseract_primitives::{
 	queue::{start_pipeline, PipelineQueue},
+	signer::Signer,
 	TxResult,
 };
 
 use crate::{
 	api::{to_tron_hex, TronApi, TronApiConfig},
-	catfee::{CatFeeClient, CatFeeConfig},
+	energy::{build_provider, CatFeeProviderConfig, EnergyConfig, EnergyProvider},
 };
 
 /// TRON mainnet chain ID, matching `TronHost.CHAIN_ID`.
@@ -133,14 +132,19 @@ pub struct TronConfig {
 	pub tron_api_timeout_secs: u64,
 
 	/// CatFee API key for purchasing energy/bandwidth.
-	/// If both api_key and api_secret are provided, CatFee integration will be enabled
-	/// automatically.
+	/// If both api_key and api_secret are provided and no `energy` provider is
+	/// configured, CatFee integration will be enabled automatically.
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub catfee_api_key: Option<String>,
 
 	/// CatFee API secret for HMAC signature generation (required with api_key).
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub catfee_api_secret: Option<String>,
+
+	/// Where energy and bandwidth for relayer transactions come from. When
+	/// omitted, and no CatFee credentials are given, the relayer burns TRX.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub energy: Option<EnergyConfig>,
 }
 
 fn default_fee_limit() -> u64 {
@@ -176,12 +180,12 @@ pub struct TronClient {
 	/// TRON native HTTP API client — used only for transaction submission.
 	pub tron_api: TronApi,
 
-	/// CatFee API client for purchasing energy/bandwidth (optional).
-	pub catfee: Option<CatFeeClient>,
+	/// Tops up energy and bandwidth before submissions (optional).
+	pub energy: Option<Arc<dyn EnergyProvider>>,
 
-	/// The 32-byte secp256k1 secret key (same key as used by `evm.signer`,
-	/// but we need the raw bytes for TRON's signing scheme).
-	pub(crate) secret_key: [u8; 32],
+	/// The relayer's secp256k1 key, the same key `evm` signs with. TRON
+	/// transactions are signed with it directly rather than through `evm.signer`.
+	pub(crate) signer: Arc<Signer>,
 
 	/// Owner address in TRON hex format (`41`-prefixed, 42 hex chars).
 	pub owner_address: String,
@@ -205,60 +209,53 @@ impl TronClient {
 	/// This initialises an [`EvmClient`] for JSON-RPC reads and a [`TronApi`]
 	/// for TRON-native transaction submission.
 	pub async fn new(mut config: TronConfig) -> anyhow::Result<Self> {
-		// If the operator configured a signer, parse it; otherwise generate a
-		// throwaway one so a TRON chain can still be declared in the config
-		// for inbound-only relaying. The relayer's `outbound_enabled()`
-		// filter keeps signer-less chains out of any task that would
-		// actually broadcast a TRON transaction.
-		let secret_key = match config.evm.signer.as_deref() {
-			Some(raw_signer) => {
-				let key_bytes = match sp_core::bytes::from_hex(raw_signer) {
-					Ok(bytes) => bytes,
-					Err(_) => {
-						let contents = tokio::fs::read_to_string(raw_signer).await?;
-						sp_core::bytes::from_hex(contents.trim())?
-					},
-				};
-				if key_bytes.len() != 32 {
-					return Err(anyhow!("Signer key must be 32 bytes, got {}", key_bytes.len()));
-				}
-				let mut secret_key = [0u8; 32];
-				secret_key.copy_from_slice(&key_bytes);
-				secret_key
-			},
-			None => sp_core::ecdsa::Pair::generate().1,
-		};
-
-		// Derive the TRON hex address from the secret key.
-		let pair = sp_core::ecdsa::Pair::from_seed_slice(&secret_key)?;
-		let evm_addr = crypto::ecdsa::ECDSAExt::to_eth_address(&pair.public()).expect("Infallible");
-		let owner_address = to_tron_hex(&hex::encode(evm_addr));
-
 		// add a default RPC URL
 		config.evm.rpc_urls.push(format!("{}/jsonrpc", config.tron_api_url));
 		let evm = EvmClient::new(config.evm.clone()).await?;
 
+		// The inner EvmClient has already loaded the configured signer, or
+		// generated a throwaway one so a TRON chain can still be declared in
+		// the config for inbound-only relaying. The relayer's
+		// `outbound_enabled()` filter keeps signer-less chains out of any task
+		// that would actually broadcast a TRON transaction.
+		let signer = evm.signing_key.clone();
+
+		// The TRON hex address is the EVM address of the key.
+		let owner_address = to_tron_hex(&hex::encode(&evm.address));
+
 		let tron_api = TronApi::new(TronApiConfig {
 			full_host: config.tron_api_url.clone(),
 			api_key: config.tron_api_key.clone(),
 			timeout: std::time::Duration::from_secs(config.tron_api_timeout_secs),
 		})?;
 
-		// Initialize CatFee client if API credentials are provided
-		let catfee = if let (Some(api_key), Some(api_secret)) =
-			(&config.catfee_api_key, &config.catfee_api_secret)
-		{
-			let catfee_config = CatFeeConfig {
-				api_key: api_key.clone(),
-				api_secret: api_secret.clone(),
-				timeout: std::time::Duration::from_secs(config.tron_api_timeout_secs),
-				..Default::default()
-			};
-			log::info!(target: LOG_TARGET, "CatFee integration enabled: {:#?}", catfee_config);
-			Some(CatFeeClient::new(catfee_config)?)
-		} else {
-			log::info!(target: LOG_TARGET, "CatFee integration disabled (API credentials not provided)");
-			None
+		// The legacy CatFee fields select CatFee when no provider is configured
+		let energy_config = config.energy.clone().or_else(|| {
+			let (api_key, api_secret) =
+				(config.catfee_api_key.clone()?, config.catfee_api_secret.clone()?);
+			Some(EnergyConfig::Catfee(CatFeeProviderConfig {
+				api_key,
+				api_secret,
+				period_hours: 1,
+				max_spend_sun_per_day: None,
+			}))
+		});
+		let energy = match energy_config {
+			Some(energy_config) => {
+				let provider = build_provider(
+					energy_config,
+					tron_api.clone(),
+					owner_address.clone(),
+					signer.clone(),
+					std::time::Duration::from_secs(config.tron_api_timeout_secs),
+				)?;
+				log::info!(target: LOG_TARGET, "Energy provider enabled: {}", provider.name());
+				Some(provider)
+			},
+			None => {
+				log::info!(target: LOG_TARGET, "No energy provider configured, burning TRX for resources");
+				None
+			},
 		};
 
 		let ismp_host_address = to_tron_hex(&format!("{:?}", config.evm.ismp_host));
@@ -269,8 +266,8 @@ impl TronClient {
 		let mut client = Self {
 			evm,
 			tron_api,
-			catfee,
-			secret_key,
+			energy,
+			signer,
 			owner_address,
 			ismp_host_address,
 			fee_limit,
@@ -315,8 +312,8 @@ impl Clone for TronClient {
 		Self {
 			evm: self.evm.clone(),
 			tron_api: self.tron_api.clone(),
-			catfee: self.catfee.clone(),
-			secret_key: self.secret_key,
+			energy: self.energy.clone(),
+			signer: self.signer.clone(),
 			owner_address: self.owner_address.clone(),
 			ismp_host_address: self.ismp_host_address.clone(),
 			fee_limit: self.fee_limit,
@@ -369,6 +366,7 @@ mod tests {
 			tron_api_timeout_secs: 10,
 			catfee_api_key: None,
 			catfee_api_secret: None,
+			energy: None,
 		};
 
 		let client = TronClient::new(config).await.expect("Failed to create client");
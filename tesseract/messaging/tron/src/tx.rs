@@ -42,18 +42,21 @@ use sp_crypto_hashing::keccak_256;
 use tesseract_primitives::{Hasher, Query, TxReceipt, TxResult};
 
 use crate::{
-	address::{hex_to_base58, is_base58_address},
 	api::{
 		SignedTransaction, TransactionInfo, TriggerConstantContractResponse,
 		TriggerContractRequest, TriggerSmartContractResponse, TronApi,
 	},
-	catfee::CatFeeClient,
+	energy::{ensure_resources, Resources},
 	TronClient,
 };
 
 // Re-use tesseract-evm's ABI encoding for all ISMP message types.
 use tesseract_evm::tx::generate_contract_calls;
 
+/// Bytes a contract call transaction takes on top of its calldata, counting
+/// the envelope and signature. Used to estimate its bandwidth.
+const TRANSACTION_OVERHEAD_BYTES: u64 = 300;
+
 /// Request body for `POST /wallet/triggersmartcontract` using the raw `data`
 /// field instead of `function_selector` + `parameter`.
 ///
@@ -166,6 +169,8 @@ pub async fn submit_messages(
 			Message::Response(_) => "handlePostResponses",
 			Message::Timeout(_) => "handleTimeout",
 			Message::FraudProof(_) => "fraudProof",
+			// Rejected by `generate_contract_calls`, the EVM hosts have no aggregated handler
+			Message::AggregatedRequest(_) => Err(anyhow!("Aggregated requests are not supported"))?,
 		};
 
 		log::info!(
@@ -249,9 +254,16 @@ async fn trigger_sign_broadcast(
 
 	log::trace!(target: crate::LOG_TARGET, "Estimated energy: {}", estimated_energy);
 
-	// Step 2: Purchase energy via CatFee if available
-	if let Some(catfee_client) = &client.catfee {
-		purchase_energy_via_catfee(catfee_client, &client.owner_address, estimated_energy).await?;
+	// Step 2: Top up energy and bandwidth, anything the provider's budget doesn't cover is paid
+	// for by burning TRX
+	if let Some(provider) = &client.energy {
+		let required = Resources {
+			energy: estimated_energy,
+			bandwidth: (calldata_hex.len() / 2) as u64 + TRANSACTION_OVERHEAD_BYTES,
+		};
+		ensure_resources(provider.as_ref(), &client.tron_api, &client.owner_address, required)
+			.await
+			.with_context(|| format!("Failed to top up resources via {}", provider.name()))?;
 	}
 
 	let trigger_req = TriggerWithDataRequest {
@@ -280,7 +292,8 @@ async fn trigger_sign_broadcast(
 	log::trace!(target: crate::LOG_TARGET, "Got unsigned transaction with tx_id={}", tx_id);
 
 	log::trace!(target: crate::LOG_TARGET, "Signing transaction");
-	let signed = SignedTransaction::sign(unsigned, &client.secret_key)
+	let signed = SignedTransaction::sign(unsigned, client.signer.as_ref())
+		.await
 		.context("failed to sign TRON transaction")?;
 
 	log::trace!(target: crate::LOG_TARGET, "Broadcasting transaction tx_id={}", tx_id);
@@ -644,46 +657,3 @@ async fn estimate_transaction_energy(
 
 	Ok(energy_with_margin)
 }
-
-/// Purchase energy via the CatFee API.
-///
-/// This executes the complete purchase flow: create order and wait for confirmation.
-///
-/// # Arguments
-/// * `catfee_client` - The CatFee API client
-/// * `receiver_address` - The address that will receive the energy (hex or base58 format)
-/// * `energy_required` - Amount of energy needed
-async fn purchase_energy_via_catfee(
-	catfee_client: &CatFeeClient,
-	receiver_address: &str,
-	energy_required: u64,
-) -> anyhow::Result<()> {
-	log::info!(target: crate::LOG_TARGET, "Purchasing {} energy units via CatFee", energy_required);
-
-	// Convert TRON hex address (41-prefixed) to base58 if needed
-	let receiver_base58 = if is_base58_address(receiver_address) {
-		// Already in base58 format
-		receiver_address.to_string()
-	} else {
-		// Convert from hex to base58
-		hex_to_base58(receiver_address)
-			.context("Failed to convert TRON address from hex to base58")?
-	};
-
-	log::trace!(target: crate::LOG_TARGET, "Using receiver address (base58): {}", receiver_base58);
-
-	// Maximum wait time for order completion (3 minutes)
-	let max_wait = Duration::from_secs(180);
-
-	// Period: 1 hour (sufficient for immediate transaction)
-	let period_hours = 1;
-
-	catfee_client
-		.purchase_energy(energy_required, &receiver_base58, period_hours, max_wait)
-		.await
-		.context("Failed to purchase energy via CatFee")?;
-
-	log::info!(target: crate::LOG_TARGET, "Energy purchase completed successfully");
-
-	Ok(())
-}
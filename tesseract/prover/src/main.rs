@@ -18,7 +18,10 @@ use anyhow::anyhow;
 use clap::Parser;
 use primitive_types::H256;
 use std::sync::Arc;
-use tesseract_beefy::prover::{BeefyProver, Prover};
+use tesseract_beefy::{
+	pool::{ProvingPool, ProvingWorker},
+	prover::{BeefyProver, ProofVariant, Prover, ProverConfig},
+};
 use tesseract_primitives::IsmpProvider;
 use tesseract_substrate::{
 	config::{Blake2SubstrateChain, KeccakSubstrateChain},
@@ -33,6 +36,10 @@ pub struct Cli {
 	/// Path to the relayer config file
 	#[arg(short, long)]
 	pub config: String,
+	/// Run as a worker of the SP1 proving pool configured under `[prover.sp1_pool]`, proving
+	/// jobs handed out by a coordinating prover instead of submitting proofs.
+	#[arg(long)]
+	pub worker: bool,
 }
 
 #[tokio::main]
@@ -48,6 +55,33 @@ async fn main() -> Result<(), anyhow::Error> {
 
 	let mut config = tokio::fs::read_to_string(cli.config).await?.parse::<toml::Table>()?;
 
+	if cli.worker {
+		let mut prover_config: ProverConfig = config
+			.remove("prover")
+			.ok_or_else(|| anyhow!("Prover config missing; qed"))?
+			.try_into()?;
+		let pool = prover_config
+			.sp1_pool
+			.take()
+			.ok_or_else(|| anyhow!("Proving workers need a [prover.sp1_pool] config"))?;
+		prover_config.proof_variant = ProofVariant::Sp1;
+
+		// Each job carries the account of the coordinator that submits its proof.
+		let Prover::Sp1(prover) = Prover::<
+			Blake2SubstrateChain,
+			KeccakSubstrateChain,
+			zk_beefy::LocalProver,
+		>::new(prover_config, H256::zero())
+		.await?
+		else {
+			unreachable!("The sp1 variant without a pool builds a local SP1 prover; qed")
+		};
+		let worker = ProvingWorker::new(prover, ProvingPool::new(pool).await?);
+		worker.run().await;
+
+		return Ok(());
+	}
+
 	let substrate = {
 		let substrate_config: tesseract_substrate::SubstrateConfig = config
 			.remove("substrate")
@@ -89,7 +123,7 @@ async fn main() -> Result<(), anyhow::Error> {
 				Arc::new(tesseract_beefy::backend::OnchainBackend::<KeccakSubstrateChain>::new(
 					substrate.client.clone(),
 					substrate.rpc_client.clone(),
-					substrate.signer.clone(),
+					substrate.signer()?,
 					state_machine_id,
 				))
 			},
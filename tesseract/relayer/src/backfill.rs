@@ -0,0 +1,170 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0.
+
+//! `backfill` subcommand.
+//!
+//! Rescans a height range on a source chain for requests that never reached
+//! their destination (no receipt on the destination, not timed out) and
+//! delivers them through the same path as the inbound messaging task. Use it
+//! to repair gaps left by RPC outages or by restarting the relayer from a
+//! later height. The long-running relayer does the same over a trailing
+//! window when `relayer.gap_scan_frequency` is set.
+
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use ismp::{events::Event, host::StateMachine};
+use messaging::backfill::{backfill, find_undelivered};
+use tesseract_primitives::IsmpProvider;
+use tesseract_substrate::{config::KeccakSubstrateChain, SubstrateClient};
+use transaction_fees::TransactionPayment;
+
+use crate::config::{setup_logging, HyperbridgeConfig};
+
+#[derive(Debug, clap::Args)]
+#[command(
+	about = "Re-deliver requests in a height range of a source chain that never reached their destination."
+)]
+pub struct Backfill {
+	/// Source chain to scan, e.g. `EVM-1`.
+	#[arg(long)]
+	pub source: String,
+	/// Destination chain, e.g. `EVM-56`. Defaults to Hyperbridge.
+	#[arg(long)]
+	pub dest: Option<String>,
+	/// First source height to scan.
+	#[arg(long)]
+	pub from: u64,
+	/// Last source height to scan. Defaults to, and is capped at, the latest
+	/// height of the source chain known to the destination.
+	#[arg(long)]
+	pub to: Option<u64>,
+	/// Only list the undelivered requests, without delivering them.
+	#[arg(long)]
+	pub dry_run: bool,
+}
+
+impl Backfill {
+	/// Entry point invoked by `main.rs` when the user passes the `backfill`
+	/// subcommand.
+	pub async fn run(&self, config_path: &str, db: &str) -> anyhow::Result<()> {
+		let _ = setup_logging();
+		let config = HyperbridgeConfig::parse_conf(config_path).await?;
+		let hyperbridge =
+			SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
+				.await?;
+		let hyperbridge_provider: Arc<dyn IsmpProvider> = Arc::new(hyperbridge);
+		let coprocessor = hyperbridge_provider.state_machine_id().state_id;
+
+		let mut clients: HashMap<StateMachine, Arc<dyn IsmpProvider>> = HashMap::new();
+		for (sm, pc) in &config.chains {
+			let provider = pc
+				.messaging
+				.clone()
+				.into_client(hyperbridge_provider.clone())
+				.await
+				.with_context(|| format!("failed to build messaging client for {sm}"))?;
+			clients.insert(*sm, provider);
+		}
+		clients.insert(coprocessor, hyperbridge_provider.clone());
+
+		let parse = |value: &str| {
+			StateMachine::from_str(value)
+				.map_err(|err| anyhow!("invalid state machine '{value}': {err}"))
+		};
+		let source_sm = parse(&self.source)?;
+		let dest_sm = self.dest.as_deref().map(parse).transpose()?.unwrap_or(coprocessor);
+		if source_sm == dest_sm {
+			return Err(anyhow!("source and destination must differ"));
+		}
+		let client = |sm: StateMachine| {
+			clients
+				.get(&sm)
+				.cloned()
+				.ok_or_else(|| anyhow!("{sm} is not configured, add a `[{sm}]` block"))
+		};
+		let (source, dest) = (client(source_sm)?, client(dest_sm)?);
+		if !self.dry_run && config.chains.get(&dest_sm).map_or(false, |pc| !pc.outbound_enabled()) {
+			return Err(anyhow!(
+				"{dest_sm} has no signer configured, requests cannot be delivered to it"
+			));
+		}
+
+		let tx_payment = Arc::new(
+			TransactionPayment::initialize(db)
+				.await
+				.context("Error initializing fee database")?,
+		);
+		let relayer_config: tesseract_primitives::config::RelayerConfig =
+			config.relayer.clone().into();
+		let to = match self.to {
+			Some(to) => to,
+			None => dest.query_latest_height(source.state_machine_id()).await? as u64,
+		};
+
+		if self.dry_run {
+			let (events, found) = find_undelivered(
+				&source,
+				&dest,
+				&tx_payment,
+				&relayer_config,
+				coprocessor,
+				self.from..=to,
+			)
+			.await?;
+			for event in &events {
+				match event {
+					Event::PostRequest(post) => tracing::info!(
+						target: crate::LOG_TARGET,
+						source = %post.source,
+						dest = %post.dest,
+						nonce = post.nonce,
+						"undelivered post request",
+					),
+					Event::GetResponse(res) => tracing::info!(
+						target: crate::LOG_TARGET,
+						source = %res.get.source,
+						dest = %res.get.dest,
+						nonce = res.get.nonce,
+						"undelivered get response",
+					),
+					_ => {},
+				}
+			}
+			tracing::info!(
+				target: crate::LOG_TARGET,
+				source = %source_sm,
+				dest = %dest_sm,
+				found,
+				undelivered = events.len(),
+				"backfill dry run complete for {}..={to}",
+				self.from,
+			);
+			return Ok(());
+		}
+
+		let report = backfill(
+			source,
+			dest,
+			tx_payment,
+			relayer_config,
+			coprocessor,
+			&clients,
+			self.from..=to,
+		)
+		.await?;
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			source = %source_sm,
+			dest = %dest_sm,
+			scanned = ?report.scanned,
+			found = report.found,
+			undelivered = report.undelivered,
+			delivered = report.delivered,
+			"backfill complete",
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,201 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0.
+
+//! `export-bundle` and `replay-bundle` subcommands.
+//!
+//! `export-bundle` writes a request, its membership proof and the state commitment it is proven
+//! against to a JSON file. `replay-bundle` delivers such a file to the destination, or to a local
+//! fork of it with `--rpc`, so a failed delivery can be reproduced without the source chain.
+
+use std::{str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use ismp::host::StateMachine;
+use messaging::bundle::{export_bundle, replay_bundle, ProofBundle};
+use primitive_types::H256;
+use tesseract_primitives::IsmpProvider;
+use tesseract_substrate::{config::KeccakSubstrateChain, SubstrateClient};
+
+use crate::config::{setup_logging, HyperbridgeConfig};
+
+#[derive(Debug, clap::Args)]
+#[command(about = "Export a request and its proof to a bundle file that can be replayed offline.")]
+pub struct ExportBundle {
+	/// Source chain of the request, e.g. `EVM-1`.
+	#[arg(long)]
+	pub source: String,
+	/// Destination chain, e.g. `EVM-56`. Defaults to Hyperbridge, which also takes requests it
+	/// routes to other chains.
+	#[arg(long)]
+	pub dest: Option<String>,
+	/// Commitment of the request. For get responses, the commitment of the get request.
+	#[arg(long)]
+	pub commitment: String,
+	/// First source height to search for the request.
+	#[arg(long)]
+	pub from: u64,
+	/// Last source height to search for the request. Defaults to, and is capped at, the proof
+	/// height.
+	#[arg(long)]
+	pub to: Option<u64>,
+	/// Source height to prove the request at. Defaults to the latest height of the source chain
+	/// known to the destination.
+	#[arg(long)]
+	pub at: Option<u64>,
+	/// Path of the bundle file to write.
+	#[arg(long)]
+	pub out: String,
+}
+
+#[derive(Debug, clap::Args)]
+#[command(about = "Deliver a bundle written by `export-bundle` to its destination.")]
+pub struct ReplayBundle {
+	/// Path of the bundle file to replay.
+	#[arg(long)]
+	pub bundle: String,
+	/// Submit through this rpc endpoint instead of the configured ones, e.g. an anvil fork or a
+	/// simnode of the destination.
+	#[arg(long)]
+	pub rpc: Option<String>,
+	/// Only estimate the delivery, without submitting it.
+	#[arg(long)]
+	pub dry_run: bool,
+}
+
+impl ExportBundle {
+	/// Entry point invoked by `main.rs` when the user passes the `export-bundle`
+	/// subcommand.
+	pub async fn run(&self, config_path: &str) -> anyhow::Result<()> {
+		let _ = setup_logging();
+		let config = HyperbridgeConfig::parse_conf(config_path).await?;
+		let hyperbridge_provider: Arc<dyn IsmpProvider> = Arc::new(
+			SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
+				.await?,
+		);
+		let coprocessor = hyperbridge_provider.state_machine_id().state_id;
+
+		let source_sm = parse_state_machine(&self.source)?;
+		let dest_sm = self
+			.dest
+			.as_deref()
+			.map(parse_state_machine)
+			.transpose()?
+			.unwrap_or(coprocessor);
+		if source_sm == dest_sm {
+			return Err(anyhow!("source and destination must differ"));
+		}
+		let commitment = H256::from_str(self.commitment.trim_start_matches("0x"))
+			.map_err(|err| anyhow!("invalid commitment '{}': {err:?}", self.commitment))?;
+
+		let client = |sm: StateMachine| {
+			let hyperbridge = hyperbridge_provider.clone();
+			let config = config.chains.get(&sm).map(|pc| pc.messaging.clone());
+			async move {
+				if sm == coprocessor {
+					return Ok(hyperbridge);
+				}
+				config
+					.ok_or_else(|| anyhow!("{sm} is not configured, add a `[{sm}]` block"))?
+					.into_client(hyperbridge)
+					.await
+					.with_context(|| format!("failed to build messaging client for {sm}"))
+			}
+		};
+		let (source, dest) = (client(source_sm).await?, client(dest_sm).await?);
+
+		let bundle = export_bundle(
+			&source,
+			&dest,
+			coprocessor,
+			commitment,
+			self.from..=self.to.unwrap_or(u64::MAX),
+			self.at,
+		)
+		.await?;
+		let json = serde_json::to_string_pretty(&bundle)?;
+		tokio::fs::write(&self.out, json)
+			.await
+			.with_context(|| format!("failed to write bundle to {}", self.out))?;
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			source = %source_sm,
+			dest = %dest_sm,
+			?commitment,
+			height = bundle.proof_height,
+			out = %self.out,
+			"exported proof bundle",
+		);
+		Ok(())
+	}
+}
+
+impl ReplayBundle {
+	/// Entry point invoked by `main.rs` when the user passes the `replay-bundle`
+	/// subcommand.
+	pub async fn run(&self, config_path: &str) -> anyhow::Result<()> {
+		let _ = setup_logging();
+		let bundle: ProofBundle = serde_json::from_slice(
+			&tokio::fs::read(&self.bundle)
+				.await
+				.with_context(|| format!("failed to read bundle from {}", self.bundle))?,
+		)
+		.with_context(|| format!("{} is not a valid proof bundle", self.bundle))?;
+
+		let config = HyperbridgeConfig::parse_conf(config_path).await?;
+		let hyperbridge =
+			SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
+				.await?;
+		let hyperbridge_provider: Arc<dyn IsmpProvider> = Arc::new(hyperbridge);
+		let coprocessor = hyperbridge_provider.state_machine_id().state_id;
+
+		let dest: Arc<dyn IsmpProvider> = if bundle.dest == coprocessor {
+			match self.rpc.clone() {
+				Some(rpc_ws) => {
+					let mut substrate = config.hyperbridge.substrate.clone();
+					substrate.rpc_ws = rpc_ws;
+					substrate.additional_rpc_ws.clear();
+					Arc::new(SubstrateClient::<KeccakSubstrateChain>::new(substrate).await?)
+				},
+				None => hyperbridge_provider,
+			}
+		} else {
+			let messaging = config
+				.chains
+				.get(&bundle.dest)
+				.map(|pc| pc.messaging.clone())
+				.ok_or_else(|| {
+					anyhow!("{} is not configured, add a `[{}]` block", bundle.dest, bundle.dest)
+				})?;
+			let messaging = match self.rpc.clone() {
+				Some(rpc) => messaging.with_rpc_url(rpc),
+				None => messaging,
+			};
+			messaging
+				.into_client(hyperbridge_provider)
+				.await
+				.with_context(|| format!("failed to build messaging client for {}", bundle.dest))?
+		};
+
+		let report = replay_bundle(&dest, &bundle, coprocessor, self.dry_run).await?;
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			source = %bundle.source,
+			dest = %bundle.dest,
+			commitment = ?bundle.commitment,
+			successful_execution = report.successful_execution,
+			execution_cost = %report.execution_cost,
+			delivered = ?report.delivered,
+			"replayed proof bundle",
+		);
+		if report.delivered == Some(false) {
+			return Err(anyhow!("{} rejected the bundled message", bundle.dest));
+		}
+		Ok(())
+	}
+}
+
+fn parse_state_machine(value: &str) -> anyhow::Result<StateMachine> {
+	StateMachine::from_str(value).map_err(|err| anyhow!("invalid state machine '{value}': {err}"))
+}
@@ -0,0 +1,165 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0.
+
+//! `export-checkpoint` and `import-checkpoint` subcommands.
+//!
+//! `export-checkpoint` writes the message creating a consensus client for a chain, signed by the
+//! relayer's key, to a JSON file. `import-checkpoint` verifies such a file against a set of
+//! trusted keys, rejects it once it is older than its unbonding period, and prints the
+//! hyperbridge call creating the client, so a new deployment can be bootstrapped from a reviewed
+//! checkpoint instead of a trusted rpc.
+
+use std::{
+	str::FromStr,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context};
+use ismp::host::StateMachine;
+use subxt_utils::values::create_consensus_state_to_value;
+use tesseract_consensus_config::create_client_map;
+use tesseract_primitives::{
+	checkpoint::{export_checkpoint, SignedCheckpoint},
+	signer::{KeyType, Signer},
+};
+use tesseract_substrate::{config::KeccakSubstrateChain, SubstrateClient};
+
+use crate::config::{setup_logging, HyperbridgeConfig};
+
+#[derive(Debug, clap::Args)]
+#[command(about = "Export a signed checkpoint of the consensus client for a chain.")]
+pub struct ExportCheckpoint {
+	/// Chain whose consensus client should be exported, e.g. `EVM-1`.
+	pub state_machine: String,
+	/// Path of the checkpoint file to write.
+	#[arg(long)]
+	pub out: String,
+	/// Key signing the checkpoint, in any form accepted by the `signer` config fields.
+	/// Defaults to the hyperbridge signer.
+	#[arg(long)]
+	pub signer: Option<String>,
+	/// Type of the signing key.
+	#[arg(long, default_value = "sr25519")]
+	pub key_type: KeyType,
+}
+
+#[derive(Debug, clap::Args)]
+#[command(
+	about = "Verify a checkpoint and print the hyperbridge call creating its consensus client."
+)]
+pub struct ImportCheckpoint {
+	/// Path of the checkpoint file written by `export-checkpoint`.
+	#[arg(long)]
+	pub checkpoint: String,
+	/// Hex encoded public key the checkpoint may be signed by. Can be repeated.
+	#[arg(long = "trusted-signer", required = true)]
+	pub trusted_signers: Vec<String>,
+	/// Wrap the call in the sudo extrinsic.
+	#[arg(long)]
+	pub sudo: bool,
+	/// Also write the hex encoded call to this file.
+	#[arg(long)]
+	pub out: Option<String>,
+}
+
+impl ExportCheckpoint {
+	/// Entry point invoked by `main.rs` when the user passes the `export-checkpoint`
+	/// subcommand.
+	pub async fn run(&self, config_path: &str) -> anyhow::Result<()> {
+		let _ = setup_logging();
+		let state_machine = StateMachine::from_str(&self.state_machine)
+			.map_err(|err| anyhow!("invalid state machine '{}': {err}", self.state_machine))?;
+		let config = HyperbridgeConfig::parse_conf(config_path).await?;
+		let consensus_hosts = create_client_map(config.consensus_chains()).await?;
+		let host = consensus_hosts.get(&state_machine).ok_or_else(|| {
+			anyhow!(
+				"no consensus host for {state_machine} — did you forget `[chains.{state_machine}.consensus]`?"
+			)
+		})?;
+
+		let signer = self.signer.as_deref().or(config.hyperbridge.substrate.signer.as_deref());
+		let signer = Signer::load(signer, self.key_type).await?;
+		let checkpoint = export_checkpoint(host.as_ref(), &signer).await?;
+
+		let json = serde_json::to_string_pretty(&checkpoint)?;
+		tokio::fs::write(&self.out, json)
+			.await
+			.with_context(|| format!("failed to write checkpoint to {}", self.out))?;
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			%state_machine,
+			signer = %hex::encode(&checkpoint.signer),
+			out = %self.out,
+			"exported consensus checkpoint",
+		);
+		Ok(())
+	}
+}
+
+impl ImportCheckpoint {
+	/// Entry point invoked by `main.rs` when the user passes the `import-checkpoint`
+	/// subcommand.
+	pub async fn run(&self, config_path: &str) -> anyhow::Result<()> {
+		let _ = setup_logging();
+		let signed: SignedCheckpoint = serde_json::from_slice(
+			&tokio::fs::read(&self.checkpoint)
+				.await
+				.with_context(|| format!("failed to read checkpoint from {}", self.checkpoint))?,
+		)
+		.with_context(|| format!("{} is not a valid checkpoint", self.checkpoint))?;
+		let trusted = self
+			.trusted_signers
+			.iter()
+			.map(|key| {
+				hex::decode(key.trim_start_matches("0x"))
+					.map_err(|err| anyhow!("invalid trusted signer '{key}': {err}"))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		let checkpoint = signed.verify(&trusted)?;
+		checkpoint.ensure_fresh(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())?;
+		let message = &checkpoint.message;
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			state_machine = %checkpoint.state_machine,
+			exported_at = checkpoint.exported_at,
+			consensus_client_id = %String::from_utf8_lossy(&message.consensus_client_id),
+			consensus_state_id = %String::from_utf8_lossy(&message.consensus_state_id),
+			unbonding_period = message.unbonding_period,
+			challenge_periods = ?message.challenge_periods,
+			state_machine_commitments = ?message.state_machine_commitments,
+			"verified consensus checkpoint",
+		);
+
+		let config = HyperbridgeConfig::parse_conf(config_path).await?;
+		let hyperbridge =
+			SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
+				.await?;
+		let metadata = hyperbridge.client.metadata();
+		let create = subxt::dynamic::tx(
+			"Ismp",
+			"create_consensus_client",
+			vec![create_consensus_state_to_value(message)],
+		);
+		let call = if self.sudo {
+			subxt::dynamic::tx("Sudo", "sudo", vec![create.into_value()])
+				.encode_call_data(&metadata)?
+		} else {
+			create.encode_call_data(&metadata)?
+		};
+
+		let call = format!("0x{}", hex::encode(call));
+		tracing::info!(
+			target: crate::LOG_TARGET,
+			state_machine = %checkpoint.state_machine,
+			"create_consensus_client call:\n{call}",
+		);
+		if let Some(out) = &self.out {
+			tokio::fs::write(out, &call)
+				.await
+				.with_context(|| format!("failed to write call to {out}"))?;
+		}
+		Ok(())
+	}
+}
@@ -13,7 +13,7 @@
 
 use std::{collections::BTreeMap, sync::Arc};
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use codec::Decode;
 use futures::{stream::FuturesUnordered, StreamExt};
 use ismp::host::StateMachine;
@@ -68,7 +68,11 @@ impl ClaimRewards {
 				.context("error initializing fee database")?,
 		);
 
-		let payee_bytes: [u8; 32] = hyperbridge.signer.public().0;
+		let payee_bytes: [u8; 32] = hyperbridge
+			.address
+			.as_slice()
+			.try_into()
+			.map_err(|_| anyhow!("hyperbridge signer account must be 32 bytes"))?;
 
 		let all_rows = tx_payment
 			.list_pending_rotation_claims()
@@ -13,27 +13,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{
-	collections::{BTreeMap, HashMap},
-	sync::Arc,
-};
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Context;
 use clap::Parser;
-use futures::FutureExt;
 use ismp::host::StateMachine;
-use polkadot_sdk::sc_service::TaskManager;
 use tesseract_consensus_config::create_client_map;
-use tesseract_primitives::IsmpProvider;
+use tesseract_primitives::{
+	expiry::{serve_metrics, ExpiryMetrics, Registry},
+	proof_cache::ProofCache,
+	IsmpProvider,
+};
 use tesseract_substrate::{config::KeccakSubstrateChain, SubstrateClient};
-use tracing::Instrument;
 use transaction_fees::TransactionPayment;
 
 use crate::{
+	backfill::Backfill,
+	bundle::{ExportBundle, ReplayBundle},
+	checkpoint::{ExportCheckpoint, ImportCheckpoint},
 	claim_rewards::ClaimRewards,
 	config::{setup_logging, HyperbridgeConfig},
 	fees::AccumulateFees,
 	provider::{ConsensusProofSource, OffchainProofSource},
+	reload,
+	signer_server::SignerServer,
+	supervisor::{Shared, Supervisor},
 };
 
 #[derive(Parser, Debug)]
@@ -71,6 +75,9 @@ pub struct Cli {
 	/// Path to the relayer database file (for fee tracking)
 	#[arg(short, long)]
 	pub db: String,
+	/// Reload the config whenever the file changes, in addition to on SIGHUP
+	#[arg(long)]
+	pub watch_config: bool,
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -95,6 +102,24 @@ pub enum Subcommand {
 	/// a parachain consensus proof to Hyperbridge first so the claim proof can
 	/// be anchored against a known state commitment.
 	ClaimRewards(ClaimRewards),
+	/// Serve keys over the remote signer api, for testing relayers
+	/// configured with a `remote:` signer.
+	SignerServer(SignerServer),
+	/// Re-deliver requests in a height range of a source chain that have no
+	/// receipt on their destination and have not timed out.
+	Backfill(Backfill),
+	/// Write a request, its proof and the state commitment it is proven
+	/// against to a bundle file.
+	ExportBundle(ExportBundle),
+	/// Deliver a bundle written by `export-bundle` to its destination, or to
+	/// a local fork of it.
+	ReplayBundle(ReplayBundle),
+	/// Write the message creating the consensus client for a chain, signed
+	/// by the relayer's key, to a checkpoint file.
+	ExportCheckpoint(ExportCheckpoint),
+	/// Verify a checkpoint against trusted keys and print the hyperbridge
+	/// call creating its consensus client.
+	ImportCheckpoint(ImportCheckpoint),
 }
 
 const BANNER: &str = r"
@@ -120,10 +145,27 @@ impl Cli {
 		);
 
 		let config = HyperbridgeConfig::parse_conf(&self.config).await?;
-		let relayer = config.relayer.clone();
 
-		let tokio_handle = tokio::runtime::Handle::current();
-		let mut task_manager = TaskManager::new(tokio_handle, None)?;
+		if let Some(ref dir) = config.relayer.proof_cache {
+			ProofCache::init(dir)
+				.await
+				.context("Error initializing consensus proof cache")?;
+			tracing::info!(target: crate::LOG_TARGET, dir = %dir, "caching consensus proofs");
+		}
+
+		// Metrics are registered once for the process, reloads don't restart the server.
+		if let Some(ref address) = config.relayer.metrics {
+			let address =
+				address.parse().with_context(|| format!("Invalid metrics address {address}"))?;
+			let registry = Registry::new();
+			ExpiryMetrics::register(&registry).context("Error registering metrics")?;
+			tokio::spawn(async move {
+				if let Err(err) = serve_metrics(address, registry).await {
+					tracing::error!(target: crate::LOG_TARGET, ?err, "metrics server terminated");
+				}
+			});
+			tracing::info!(target: crate::LOG_TARGET, %address, "serving metrics");
+		}
 
 		let tx_payment = Arc::new(
 			TransactionPayment::initialize(&self.db)
@@ -146,259 +188,34 @@ impl Cli {
 			Arc::new(OffchainProofSource::new(hb_rpc_client));
 		tracing::info!(target: crate::LOG_TARGET, hb = %hyperbridge_provider.name(), %coprocessor, "connected to Hyperbridge");
 
-		// Build the IsmpHost for every chain that opted into inbound consensus.
-		// `create_client_map` takes paired (consensus variant, host kind)
-		// entries — we assemble those from each chain's `PerChainConfig`.
-		let consensus_hosts = create_client_map(config.consensus_chains()).await?;
-		// Redundant with the final "relayer tasks initialized" summary below.
-		tracing::trace!(target: crate::LOG_TARGET, count = consensus_hosts.len(), "consensus hosts built");
-
-		// One `Arc<dyn IsmpProvider>` per chain. Recreate providers for messaging DO NOT reuse the
-		// consensus host's provider; Some consensus hosts like op-host and arb-host overwrite the
-		// consensus state id for their internal needs
-		let mut providers: HashMap<StateMachine, Arc<dyn IsmpProvider>> = HashMap::new();
-		for (sm, pc) in &config.chains {
-			let provider = pc
-				.messaging
-				.clone()
-				.into_client(hyperbridge_provider.clone())
-				.await
-				.with_context(|| format!("failed to build messaging client for {sm}"))?;
-			providers.insert(*sm, provider);
-		}
-		tracing::trace!(target: crate::LOG_TARGET, count = providers.len(), "chain providers built");
-
-		let mut provider_clients = providers.clone();
-		provider_clients.insert(coprocessor, hyperbridge_provider.clone());
-
-		let messaging_config: tesseract_primitives::config::RelayerConfig = relayer.clone().into();
-		let fees_disabled = messaging_config.disable_fee_accumulation.unwrap_or_default();
-
 		// Hyperbridge's own consensus host (if `[hyperbridge.consensus]` is
-		// set) gets pulled out of the map first — we don't want it to appear
-		// in the chain→hyperbridge loop (that would submit HB proofs to HB
-		// itself) and we need it separately to fan out HB→substrate
-		// counterparty tasks below.
-		let hb_state_machine = hyperbridge_provider.state_machine_id().state_id;
-		let hb_consensus_host = consensus_hosts.get(&hb_state_machine).cloned();
-
-		// Inbound consensus — chain → hyperbridge. One task per chain with a
-		// `[<chain>.consensus]` sub-table, excluding hyperbridge itself.
-		for (state_machine, host) in &consensus_hosts {
-			if *state_machine == hb_state_machine {
-				continue;
-			}
-			let hb = hyperbridge_provider.clone();
-			let name = format!("inbound-consensus-{}-{}", host.provider().name(), hb.name());
-			let host = host.clone();
-			let span = tracing::info_span!(
-				"inbound_consensus",
-				chain = %host.provider().name(),
-				hb = %hb.name(),
-			);
-
-			task_manager.spawn_essential_handle().spawn_blocking(
-				Box::leak(Box::new(name)),
-				"consensus",
-				async move {
-					tracing::trace!(target: crate::LOG_TARGET, "task started");
-					let res = host.start_consensus(hb).await;
-					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
-				}
-				.instrument(span)
-				.boxed(),
-			);
-
-			tracing::trace!(target: crate::LOG_TARGET, %state_machine, "initialized consensus task");
-		}
-
-		// Hyperbridge → substrate counterparty consensus. When
-		// `[hyperbridge.consensus]` is configured, spawn one task per
-		// substrate counterparty that ships hyperbridge's own consensus
-		// proofs (e.g. parachain headers) to that counterparty. EVM
-		// counterparties are skipped — their hyperbridge consensus path runs
-		// via the BEEFY consensus proofs pipeline, not this one.
-		if let Some(hb_host) = hb_consensus_host {
-			for (state_machine, provider) in &providers {
-				if *state_machine == hb_state_machine || !state_machine.is_substrate() {
-					continue;
-				}
-				let host = hb_host.clone();
-				let counterparty = provider.clone();
-				let name = format!(
-					"outbound-consensus-{}-{}",
-					hb_host.provider().name(),
-					counterparty.name(),
-				);
-				let span = tracing::info_span!(
-					"outbound_consensus",
-					hb = %hb_host.provider().name(),
-					chain = %counterparty.name(),
-				);
-				task_manager.spawn_essential_handle().spawn_blocking(
-					Box::leak(Box::new(name)),
-					"consensus",
-					async move {
-						tracing::trace!(target: crate::LOG_TARGET, "task started");
-						let res = host.start_consensus(counterparty).await;
-						tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
-					}
-					.instrument(span)
-					.boxed(),
-				);
-				tracing::trace!(
-					target: crate::LOG_TARGET,
-					%state_machine,
-					"initialized hyperbridge->substrate consensus task",
-				);
-			}
-		}
-
-		// Inbound messaging — every chain in `[chains.*]` gets an inbound
-		// messaging task. There's no opt-in gate: if you configured the chain,
-		// you want its inbound messages relayed. Fee accumulation is NOT wired
-		// here — it's an outbound-relayer concern and is spawned below only for
-		// chains that opted into outbound.
-		for (state_machine, provider) in &providers {
-			let mut hb_for_messaging =
-				SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
-					.await?;
-			hb_for_messaging.set_latest_finalized_height(provider.clone()).await?;
-
-			messaging::inbound(
-				hb_for_messaging,
-				provider.clone(),
-				messaging_config.clone(),
-				coprocessor,
-				tx_payment.clone(),
-				provider_clients.clone(),
-				&task_manager,
-				None,
-			)
-			.await?;
-
-			tracing::trace!(target: crate::LOG_TARGET, %state_machine, "initialized inbound messaging task");
-		}
-
-		// Outbound: one task, fans out over every chain that has a non-empty
-		// signer configured. The signer's presence is the toggle (a chain
-		// without a signer cannot submit transactions, so it stays inbound
-		// only). Fee accumulation is part of the outbound pipeline: each
-		// outbound-enabled chain gets a dedicated fee-accumulation task that
-		// drains the receipts the outbound fan-out produces after a
-		// successful destination submit.
-		// Outbound fan-out only targets EVM destinations. The HB→substrate
-		// consensus path runs through the dedicated parachain-consensus task
-		// spawned above; outbound messaging is currently an EVM-only flow
-		// driven by the BEEFY consensus proofs pipeline.
-		let destinations: BTreeMap<StateMachine, Arc<dyn IsmpProvider>> = config
-			.chains
-			.iter()
-			.filter(|(sm, pc)| matches!(sm, StateMachine::Evm(_)) && pc.outbound_enabled())
-			.filter_map(|(sm, _)| providers.get(sm).map(|p| (*sm, p.clone())))
-			.collect();
-
-		// Outbound pipeline. The initializer owns the spawn responsibility
-		// for fee accumulation + outbound_claim + outbound::run; cli.rs
-		// just hands it the inputs.
-		messaging::outbound::initialize(
-			messaging::outbound::OutboundInitParams {
-				hyperbridge_config: config.hyperbridge.substrate.clone(),
-				hyperbridge_provider: hyperbridge_provider.clone(),
-				destinations,
-				provider_clients: provider_clients.clone(),
-				proof_source: proof_source.clone(),
-				relayer_config: messaging_config.clone(),
-				tx_payment: tx_payment.clone(),
-				fees_disabled,
-				consensus_hosts: consensus_hosts.clone(),
-			},
-			&task_manager,
+		// set) is built once here and shared by the chain task groups, which
+		// use it to fan out HB→substrate counterparty tasks. The per-chain
+		// consensus hosts are built by the supervisor.
+		let hyperbridge_consensus = create_client_map(
+			config
+				.consensus_chains()
+				.into_iter()
+				.filter(|(state_machine, _)| *state_machine == coprocessor)
+				.collect(),
 		)
-		.await?;
-
-		// Fee withdrawal: one global task, periodic per
-		// `relayer.withdrawal_frequency`. Queries each destination's unclaimed
-		// balance on HB, submits a withdrawal request once the minimum
-		// threshold is crossed. The withdrawal POST that comes back is
-		// delivered by the relayer on the destination chain, so it requires
-		// a signer there. Skip chains without one.
-		{
-			let hb_for_withdraw =
-				SubstrateClient::<KeccakSubstrateChain>::new(config.hyperbridge.substrate.clone())
-					.await?;
-			let withdraw_clients: HashMap<StateMachine, Arc<dyn IsmpProvider>> = config
-				.chains
-				.iter()
-				.filter(|(_, pc)| pc.outbound_enabled())
-				.filter_map(|(sm, _)| providers.get(sm).map(|p| (*sm, p.clone())))
-				.collect();
-			let withdraw_cfg = messaging_config.clone();
-			let withdraw_db = tx_payment.clone();
-			let withdraw_proof_source = proof_source.clone();
-			let name = format!("fee-withdraw-{}", hyperbridge_provider.name());
-			let span = tracing::info_span!("fee_withdrawal", hb = %hyperbridge_provider.name());
-			task_manager.spawn_essential_handle().spawn_blocking(
-				Box::leak(Box::new(name)),
-				"fees",
-				async move {
-					tracing::trace!(target: crate::LOG_TARGET, "task started");
-					let res = messaging::fees::auto_withdraw(
-						hb_for_withdraw,
-						withdraw_clients,
-						withdraw_cfg,
-						withdraw_db,
-						withdraw_proof_source,
-					)
-					.await;
-					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
-				}
-				.instrument(span)
-				.boxed(),
-			);
-		}
-
-		// Liveness monitor — opt-in via `relayer.maximum_update_intervals`.
-		// Watches each listed `(state_machine, max_interval)` for staleness on
-		// both the inbound consensus side (HB's view of the chain) and the
-		// outbound HB → substrate side (the chain's view of HB). When any
-		// update goes past its interval the task returns Ok(()) and the
-		// essential-task handle terminates the process so an external
-		// supervisor can restart it.
-		if let Some(intervals) = relayer.maximum_update_intervals.clone().filter(|v| !v.is_empty())
-		{
-			let monitor_hb = hyperbridge_provider.clone();
-			let monitor_providers = providers.clone();
-			let name = format!("monitor-{}", hyperbridge_provider.name());
-			let span = tracing::info_span!(
-				"monitor",
-				hb = %hyperbridge_provider.name(),
-				entries = intervals.len(),
-			);
-			task_manager.spawn_essential_handle().spawn_blocking(
-				Box::leak(Box::new(name)),
-				"monitor",
-				async move {
-					tracing::trace!(target: crate::LOG_TARGET, "task started");
-					let res =
-						crate::monitor::monitor_clients(monitor_hb, monitor_providers, intervals)
-							.await;
-					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
-				}
-				.instrument(span)
-				.boxed(),
-			);
-			tracing::info!(target: crate::LOG_TARGET, "initialized liveness monitor task");
-		}
-
-		tracing::info!(
-			target: crate::LOG_TARGET, chains = config.chains.len(),
-			consensus_enabled = consensus_hosts.len(),
-			fees = !fees_disabled,
-			"relayer tasks initialized",
-		);
-		task_manager.future().await?;
-		Ok(())
+		.await?
+		.remove(&coprocessor);
+
+		let shared = Shared {
+			hyperbridge_config: config.hyperbridge.substrate.clone(),
+			hyperbridge: hyperbridge_provider,
+			hyperbridge_consensus,
+			proof_source,
+			tx_payment,
+		};
+		let supervisor =
+			Supervisor::start(shared, tokio::runtime::Handle::current(), config).await?;
+
+		// Reloads are triggered by SIGHUP, and by edits to the config file
+		// with `--watch-config`.
+		let reloads = reload::reload_triggers(self.config.clone(), self.watch_config)?;
+		supervisor.run(self.config.clone(), reloads).await
 	}
 
 	/// `log-consensus-state <STATE_MACHINE>` — one-shot: fetch and print the
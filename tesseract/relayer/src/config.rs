@@ -53,6 +53,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tesseract_config::AnyConfig as MessagingConfig;
 use tesseract_consensus_config::AnyConfig as ConsensusConfig;
+use tesseract_primitives::{config::TimeoutConfig, expiry::ExpiryConfig, policy::RoutePolicy};
 use tesseract_substrate::SubstrateConfig;
 use toml::{Table, Value};
 
@@ -65,6 +66,9 @@ pub struct RelayerConfig {
 	pub withdrawal_frequency: Option<u64>,
 	pub minimum_withdrawal_amount: Option<u64>,
 	pub unprofitable_retry_frequency: Option<u64>,
+	pub fee_bump_poll_frequency: Option<u64>,
+	pub gap_scan_frequency: Option<u64>,
+	pub gap_scan_lookback: Option<u64>,
 	pub deliver_failed: Option<bool>,
 	pub disable_fee_accumulation: Option<bool>,
 	/// Per-`(state_machine_id, max_interval_secs)` entries enabling the
@@ -75,6 +79,19 @@ pub struct RelayerConfig {
 	/// side). If any update lags by more than its `max_interval`, the
 	/// relayer process exits so an external supervisor can restart it.
 	pub maximum_update_intervals: Option<Vec<(StateMachineId, u64)>>,
+	/// Per-route delivery rules, see [`RoutePolicy`].
+	#[serde(default)]
+	pub policy: RoutePolicy,
+	/// Timeout relaying for expired requests, see [`TimeoutConfig`].
+	pub timeouts: Option<TimeoutConfig>,
+	/// Directory of the on-disk consensus proof cache, see
+	/// [`ProofCache`](tesseract_primitives::proof_cache::ProofCache).
+	pub proof_cache: Option<String>,
+	/// Consensus client expiry tracking, see [`ExpiryConfig`]. Enabled with the default margins
+	/// if this is not supplied.
+	pub expiry: Option<ExpiryConfig>,
+	/// Address to serve Prometheus metrics on, e.g. `127.0.0.1:9615`.
+	pub metrics: Option<String>,
 }
 
 impl Default for RelayerConfig {
@@ -85,9 +102,17 @@ impl Default for RelayerConfig {
 			withdrawal_frequency: None,
 			minimum_withdrawal_amount: None,
 			unprofitable_retry_frequency: None,
+			fee_bump_poll_frequency: None,
+			gap_scan_frequency: None,
+			gap_scan_lookback: None,
 			deliver_failed: None,
 			disable_fee_accumulation: None,
 			maximum_update_intervals: None,
+			policy: RoutePolicy::default(),
+			timeouts: None,
+			proof_cache: None,
+			expiry: None,
+			metrics: None,
 		}
 	}
 }
@@ -100,11 +125,16 @@ impl From<RelayerConfig> for tesseract_primitives::config::RelayerConfig {
 			withdrawal_frequency: config.withdrawal_frequency,
 			minimum_withdrawal_amount: config.minimum_withdrawal_amount,
 			unprofitable_retry_frequency: config.unprofitable_retry_frequency,
+			fee_bump_poll_frequency: config.fee_bump_poll_frequency,
+			gap_scan_frequency: config.gap_scan_frequency,
+			gap_scan_lookback: config.gap_scan_lookback,
 			// Unused by the consolidated relayer — every chain in `[chains.*]`
 			// gets inbound messaging spawned automatically.
 			delivery_endpoints: Vec::new(),
 			deliver_failed: config.deliver_failed,
 			disable_fee_accumulation: config.disable_fee_accumulation,
+			policy: config.policy,
+			timeouts: config.timeouts,
 		}
 	}
 }
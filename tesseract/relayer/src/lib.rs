@@ -23,9 +23,15 @@
 /// Log/tracing target for this crate.
 pub const LOG_TARGET: &str = "tesseract";
 
+pub mod backfill;
+pub mod bundle;
+pub mod checkpoint;
 pub mod claim_rewards;
 pub mod cli;
 pub mod config;
 pub mod fees;
 pub mod monitor;
 pub mod provider;
+pub mod reload;
+pub mod signer_server;
+pub mod supervisor;
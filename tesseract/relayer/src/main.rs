@@ -29,6 +29,12 @@ async fn main() -> Result<(), anyhow::Error> {
 		Some(Subcommand::Withdraw) => return cli.withdraw_once().await,
 		Some(Subcommand::AccumulateFees(cmd)) => return cmd.run(&cli.config, &cli.db).await,
 		Some(Subcommand::ClaimRewards(cmd)) => return cmd.run(&cli.config, &cli.db).await,
+		Some(Subcommand::SignerServer(cmd)) => return cmd.run().await,
+		Some(Subcommand::Backfill(cmd)) => return cmd.run(&cli.config, &cli.db).await,
+		Some(Subcommand::ExportBundle(cmd)) => return cmd.run(&cli.config).await,
+		Some(Subcommand::ReplayBundle(cmd)) => return cmd.run(&cli.config).await,
+		Some(Subcommand::ExportCheckpoint(cmd)) => return cmd.run(&cli.config).await,
+		Some(Subcommand::ImportCheckpoint(cmd)) => return cmd.run(&cli.config).await,
 		None => {},
 	}
 
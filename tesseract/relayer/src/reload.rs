@@ -0,0 +1,365 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Config reloads.
+//!
+//! The relayer reloads its config on `SIGHUP`, and with `--watch-config` whenever the contents of
+//! the config file change. The new config is parsed and diffed against the running one with
+//! [`ConfigDiff`], and [`crate::supervisor::Supervisor`] restarts only the task groups
+//! whose inputs changed. A config that fails to parse, or whose chains fail to connect, is
+//! rejected as a whole and the running tasks are left untouched.
+
+use std::{collections::BTreeSet, fmt, time::Duration};
+
+use ismp::host::StateMachine;
+use serde::Serialize;
+use tokio::{
+	signal::unix::{signal, SignalKind},
+	sync::mpsc,
+};
+
+use crate::{config::HyperbridgeConfig, supervisor::Group};
+
+/// How often the config file is checked for changes with `--watch-config`.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What triggered a reload.
+#[derive(Debug, Clone, Copy)]
+pub enum ReloadCause {
+	/// The process received `SIGHUP`
+	Signal,
+	/// The contents of the config file changed
+	FileChanged,
+}
+
+impl fmt::Display for ReloadCause {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReloadCause::Signal => write!(f, "SIGHUP"),
+			ReloadCause::FileChanged => write!(f, "config file change"),
+		}
+	}
+}
+
+/// Spawn the listeners that trigger reloads: `SIGHUP`, and the config file watcher when `watch` is
+/// set. Triggers that arrive while a reload is pending are coalesced into it.
+pub fn reload_triggers(path: String, watch: bool) -> anyhow::Result<mpsc::Receiver<ReloadCause>> {
+	let (tx, rx) = mpsc::channel(1);
+
+	let mut hangup = signal(SignalKind::hangup())?;
+	let signal_tx = tx.clone();
+	tokio::spawn(async move {
+		while hangup.recv().await.is_some() {
+			let _ = signal_tx.try_send(ReloadCause::Signal);
+		}
+	});
+
+	if watch {
+		tokio::spawn(async move {
+			// Compare contents rather than modification times, editors and config map mounts
+			// replace the file instead of writing to it
+			let mut last = tokio::fs::read(&path).await.ok();
+			let mut interval = tokio::time::interval(WATCH_INTERVAL);
+			loop {
+				interval.tick().await;
+				let current = match tokio::fs::read(&path).await {
+					Ok(contents) => Some(contents),
+					Err(err) => {
+						tracing::warn!(target: crate::LOG_TARGET, %path, ?err, "failed to read config file");
+						continue;
+					},
+				};
+				if current != last {
+					last = current;
+					let _ = tx.try_send(ReloadCause::FileChanged);
+				}
+			}
+		});
+	}
+
+	Ok(rx)
+}
+
+/// The sections that differ between two configs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+	/// The `[hyperbridge]` section changed, which can't be applied without a restart
+	pub hyperbridge: bool,
+	/// The `[relayer]` section changed
+	pub relayer: bool,
+	/// Chains that were added
+	pub added: BTreeSet<StateMachine>,
+	/// Chains that were removed
+	pub removed: BTreeSet<StateMachine>,
+	/// Chains whose messaging config (RPC endpoints, signer, ...) changed
+	pub messaging: BTreeSet<StateMachine>,
+	/// Chains whose `[<chain>.consensus]` sub-table changed
+	pub consensus: BTreeSet<StateMachine>,
+}
+
+impl ConfigDiff {
+	/// Compare the running config with a reloaded one.
+	pub fn between(old: &HyperbridgeConfig, new: &HyperbridgeConfig) -> Self {
+		let mut diff = ConfigDiff {
+			hyperbridge: !same(&old.hyperbridge.substrate, &new.hyperbridge.substrate) ||
+				!same(&old.hyperbridge.consensus, &new.hyperbridge.consensus),
+			relayer: !same(&old.relayer, &new.relayer),
+			..Default::default()
+		};
+
+		for (state_machine, chain) in &new.chains {
+			let Some(previous) = old.chains.get(state_machine) else {
+				diff.added.insert(*state_machine);
+				continue;
+			};
+			if !same(&previous.messaging, &chain.messaging) {
+				diff.messaging.insert(*state_machine);
+			}
+			if !same(&previous.consensus, &chain.consensus) {
+				diff.consensus.insert(*state_machine);
+			}
+		}
+		diff.removed =
+			old.chains.keys().filter(|sm| !new.chains.contains_key(sm)).copied().collect();
+
+		diff
+	}
+
+	/// Returns true if the configs are the same.
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+
+	/// Returns true if any chain's messaging client has to be rebuilt, or a chain was added or
+	/// removed. Every task that holds the map of chain clients is restarted in that case.
+	pub fn clients_changed(&self) -> bool {
+		!self.added.is_empty() || !self.removed.is_empty() || !self.messaging.is_empty()
+	}
+
+	/// The task groups to respawn to apply this diff, given every chain in the reloaded config.
+	///
+	/// A chain's consensus group embeds its messaging client, so it follows both of its
+	/// sections. Messaging groups follow their own chain and the `[relayer]` section, and all
+	/// of them are respawned when a chain is added so they can route to it. A group that keeps
+	/// running holds on to its previous client for a chain whose messaging config changed or was
+	/// removed, until the next time it's respawned.
+	pub fn restarted_groups(
+		&self,
+		chains: impl IntoIterator<Item = StateMachine>,
+	) -> BTreeSet<Group> {
+		let mut groups = BTreeSet::new();
+		for state_machine in chains {
+			let changed =
+				self.added.contains(&state_machine) || self.messaging.contains(&state_machine);
+			if changed || self.consensus.contains(&state_machine) {
+				groups.insert(Group::Consensus(state_machine));
+			}
+			if changed || self.relayer || !self.added.is_empty() {
+				groups.insert(Group::Messaging(state_machine));
+			}
+		}
+		// The global tasks hold every chain's clients, the consensus hosts and the relayer config
+		if self.clients_changed() || self.relayer || !self.consensus.is_empty() {
+			groups.insert(Group::Global);
+		}
+		groups
+	}
+}
+
+impl fmt::Display for ConfigDiff {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_empty() {
+			return write!(f, "no changes");
+		}
+
+		let mut parts = vec![];
+		if self.hyperbridge {
+			parts.push("[hyperbridge] changed".to_string());
+		}
+		if self.relayer {
+			parts.push("[relayer] changed".to_string());
+		}
+		for (label, chains) in [
+			("added", &self.added),
+			("removed", &self.removed),
+			("messaging changed", &self.messaging),
+			("consensus changed", &self.consensus),
+		] {
+			if !chains.is_empty() {
+				let chains = chains.iter().map(ToString::to_string).collect::<Vec<_>>();
+				parts.push(format!("{label}: {}", chains.join(", ")));
+			}
+		}
+		write!(f, "{}", parts.join("; "))
+	}
+}
+
+/// Compare two config values through their serialized form. Values that fail to serialize are
+/// treated as different, so the affected tasks are restarted rather than left stale.
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+	// toml has no representation for a bare `None`, wrap the value in a table where it's omitted
+	#[derive(Serialize)]
+	struct Wrapped<'a, T> {
+		value: &'a T,
+	}
+
+	match (toml::Value::try_from(Wrapped { value: a }), toml::Value::try_from(Wrapped { value: b }))
+	{
+		(Ok(a), Ok(b)) => a == b,
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::config::{HyperbridgeSection, PerChainConfig, RelayerConfig};
+	use std::collections::HashMap;
+	use tesseract_config::AnyConfig;
+	use tesseract_evm::EvmConfig;
+	use tesseract_substrate::SubstrateConfig;
+
+	pub(crate) fn evm_chain(chain_id: u32, rpc_url: &str) -> PerChainConfig {
+		PerChainConfig {
+			messaging: AnyConfig::Evm(EvmConfig {
+				rpc_urls: vec![rpc_url.to_string()],
+				state_machine: Some(StateMachine::Evm(chain_id)),
+				..EvmConfig::default()
+			}),
+			consensus: None,
+		}
+	}
+
+	pub(crate) fn config(chains: Vec<PerChainConfig>) -> HyperbridgeConfig {
+		HyperbridgeConfig {
+			hyperbridge: HyperbridgeSection {
+				substrate: SubstrateConfig {
+					state_machine: Some(StateMachine::Polkadot(4009)),
+					hashing: None,
+					consensus_state_id: Some("DOT0".into()),
+					rpc_ws: "ws://127.0.0.1:9933".into(),
+					max_rpc_payload_size: None,
+					signer: None,
+					initial_height: None,
+					max_concurent_queries: None,
+					poll_interval: None,
+					fee_token_decimals: None,
+					additional_rpc_ws: vec![],
+					rpc_health: Default::default(),
+				},
+				consensus: None,
+			},
+			chains: chains
+				.into_iter()
+				.map(|chain| (chain.messaging.state_machine(), chain))
+				.collect::<HashMap<_, _>>(),
+			relayer: RelayerConfig::default(),
+		}
+	}
+
+	#[test]
+	fn identical_configs_have_no_diff() {
+		let old = config(vec![evm_chain(1, "https://eth.rpc"), evm_chain(56, "https://bsc.rpc")]);
+		let new = config(vec![evm_chain(1, "https://eth.rpc"), evm_chain(56, "https://bsc.rpc")]);
+		let diff = ConfigDiff::between(&old, &new);
+		assert!(diff.is_empty());
+		assert!(!diff.clients_changed());
+	}
+
+	#[test]
+	fn detects_added_removed_and_changed_chains() {
+		let old = config(vec![evm_chain(1, "https://eth.rpc"), evm_chain(56, "https://bsc.rpc")]);
+		let mut new =
+			config(vec![evm_chain(1, "https://eth-backup.rpc"), evm_chain(10, "https://op.rpc")]);
+		new.relayer.minimum_profit_percentage = 500;
+
+		let diff = ConfigDiff::between(&old, &new);
+		assert!(!diff.hyperbridge);
+		assert!(diff.relayer);
+		assert_eq!(diff.added, BTreeSet::from([StateMachine::Evm(10)]));
+		assert_eq!(diff.removed, BTreeSet::from([StateMachine::Evm(56)]));
+		assert_eq!(diff.messaging, BTreeSet::from([StateMachine::Evm(1)]));
+		assert!(diff.consensus.is_empty());
+		assert!(diff.clients_changed());
+	}
+
+	#[test]
+	fn hyperbridge_changes_are_flagged() {
+		let old = config(vec![]);
+		assert!(!ConfigDiff::between(&old, &config(vec![])).hyperbridge);
+
+		let mut new = config(vec![]);
+		new.hyperbridge.substrate.rpc_ws = "ws://127.0.0.1:9944".into();
+		assert!(ConfigDiff::between(&old, &new).hyperbridge);
+	}
+
+	#[test]
+	fn only_changed_chains_are_restarted() {
+		let chains = [StateMachine::Evm(1), StateMachine::Evm(56), StateMachine::Evm(10)];
+		let old = config(vec![
+			evm_chain(1, "https://eth.rpc"),
+			evm_chain(56, "https://bsc.rpc"),
+			evm_chain(10, "https://op.rpc"),
+		]);
+		let mut new = config(vec![
+			evm_chain(1, "https://eth-backup.rpc"),
+			evm_chain(56, "https://bsc.rpc"),
+			evm_chain(10, "https://op.rpc"),
+		]);
+		assert_eq!(
+			ConfigDiff::between(&old, &new).restarted_groups(chains),
+			BTreeSet::from([
+				Group::Consensus(StateMachine::Evm(1)),
+				Group::Messaging(StateMachine::Evm(1)),
+				Group::Global,
+			])
+		);
+
+		// A relayer change reaches every messaging group but no consensus group
+		new.chains = old.chains.clone();
+		new.relayer.minimum_profit_percentage = 500;
+		assert_eq!(
+			ConfigDiff::between(&old, &new).restarted_groups(chains),
+			chains
+				.iter()
+				.map(|sm| Group::Messaging(*sm))
+				.chain([Group::Global])
+				.collect::<BTreeSet<_>>()
+		);
+	}
+
+	#[test]
+	fn added_chains_restart_every_messaging_group() {
+		let old = config(vec![evm_chain(1, "https://eth.rpc"), evm_chain(56, "https://bsc.rpc")]);
+		let new = config(vec![evm_chain(1, "https://eth.rpc"), evm_chain(10, "https://op.rpc")]);
+		assert_eq!(
+			ConfigDiff::between(&old, &new)
+				.restarted_groups([StateMachine::Evm(1), StateMachine::Evm(10)]),
+			BTreeSet::from([
+				Group::Consensus(StateMachine::Evm(10)),
+				Group::Messaging(StateMachine::Evm(1)),
+				Group::Messaging(StateMachine::Evm(10)),
+				Group::Global,
+			])
+		);
+
+		// Removing a chain only stops its own groups, which the supervisor drops
+		let new = config(vec![evm_chain(1, "https://eth.rpc")]);
+		assert_eq!(
+			ConfigDiff::between(&old, &new).restarted_groups([StateMachine::Evm(1)]),
+			BTreeSet::from([Group::Global])
+		);
+	}
+}
@@ -0,0 +1,68 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0.
+
+//! `signer-server` subcommand.
+//!
+//! Serves keys over the remote signer api, so relayers configured with
+//! `signer = "remote:<url>?public_key=0x.."` can be run against it locally.
+//! Keys are given in the same formats accepted for the `signer` config value.
+//! The server has no authentication, bind it to a trusted interface only.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Context;
+use tesseract_primitives::signer::{server, KeyType, RelayerSigner, Signer};
+
+use crate::config::setup_logging;
+
+const LOG_TARGET: &str = "tesseract-signer-server";
+
+#[derive(Debug, clap::Args)]
+#[command(about = "Serve relayer keys over the remote signer api, for local testing.")]
+pub struct SignerServer {
+	/// Address to listen on
+	#[arg(long, default_value = "127.0.0.1:9000")]
+	pub listen: SocketAddr,
+	/// secp256k1 keys to serve, hex, a key file or `keystore:<path>`
+	#[arg(long)]
+	pub secp256k1: Vec<String>,
+	/// sr25519 keys to serve, hex, a key file or `keystore:<path>`
+	#[arg(long)]
+	pub sr25519: Vec<String>,
+}
+
+impl SignerServer {
+	pub async fn run(&self) -> anyhow::Result<()> {
+		let _ = setup_logging();
+
+		let sources = self
+			.secp256k1
+			.iter()
+			.map(|source| (source, KeyType::Secp256k1))
+			.chain(self.sr25519.iter().map(|source| (source, KeyType::Sr25519)));
+		let mut signers: Vec<Arc<dyn RelayerSigner>> = vec![];
+		for (source, key_type) in sources {
+			let signer = match Signer::load(Some(source), key_type).await? {
+				Signer::Local(signer) => signer,
+				Signer::Remote(_) =>
+					Err(anyhow::anyhow!("The signer server only serves local keys"))?,
+			};
+			tracing::info!(
+				target: LOG_TARGET,
+				key_type = key_type.as_str(),
+				public_key = %format!("0x{}", hex::encode(signer.public_key())),
+				"serving key",
+			);
+			signers.push(Arc::new(signer));
+		}
+
+		let listener = tokio::net::TcpListener::bind(self.listen)
+			.await
+			.with_context(|| format!("failed to bind {}", self.listen))?;
+		tracing::info!(target: LOG_TARGET, listen = %self.listen, "signer server started");
+
+		server::serve(listener, signers).await
+	}
+}
@@ -0,0 +1,745 @@
+// Copyright (C) Polytope Labs Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Task groups of the long-running relayer.
+//!
+//! Every task is spawned into the [`TaskManager`] of a [`Group`]: one per chain for its consensus
+//! tasks, one per chain for its messaging tasks, and a global one for the tasks that span every
+//! chain. Dropping a group's task manager stops its tasks, which is how a config reload replaces
+//! only the groups whose inputs changed while the rest keep running.
+//!
+//! Reloads are applied in two steps. Staging builds the clients of every added or changed chain,
+//! opens the hyperbridge connections of the groups that are respawned and creates their task
+//! managers, without touching the running groups. Only once everything is staged are the affected
+//! groups stopped and spawned again, so a reload that fails leaves the relayer as it was.
+
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use futures::{future::select_all, FutureExt};
+use ismp::host::StateMachine;
+use polkadot_sdk::sc_service::{Error as ServiceError, TaskManager};
+use tesseract_consensus_config::create_client_map;
+use tesseract_primitives::{IsmpHost, IsmpProvider};
+use tesseract_substrate::{config::KeccakSubstrateChain, SubstrateClient, SubstrateConfig};
+use tokio::{runtime::Handle, sync::mpsc};
+use tracing::Instrument;
+use transaction_fees::TransactionPayment;
+
+use crate::{
+	config::{HyperbridgeConfig, PerChainConfig},
+	provider::ConsensusProofSource,
+	reload::{ConfigDiff, ReloadCause},
+};
+
+/// A set of tasks that is started and stopped together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Group {
+	/// Outbound fan-out, fee withdrawal, expiry trackers and the liveness monitor
+	Global,
+	/// Inbound consensus for a chain, and hyperbridge consensus for it if it's a substrate chain
+	Consensus(StateMachine),
+	/// Inbound messaging, gap detection and timeout relaying for a chain
+	Messaging(StateMachine),
+}
+
+impl fmt::Display for Group {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Group::Global => write!(f, "global"),
+			Group::Consensus(state_machine) => write!(f, "consensus-{state_machine}"),
+			Group::Messaging(state_machine) => write!(f, "messaging-{state_machine}"),
+		}
+	}
+}
+
+/// Clients that live as long as the process and are shared by every group. They're built from
+/// the `[hyperbridge]` section, so changing it requires a restart.
+pub struct Shared {
+	/// Hyperbridge's substrate config, used to open a connection per task
+	pub hyperbridge_config: SubstrateConfig,
+	/// Hyperbridge as an [`IsmpProvider`]
+	pub hyperbridge: Arc<dyn IsmpProvider>,
+	/// Hyperbridge's own consensus host, if `[hyperbridge.consensus]` is set
+	pub hyperbridge_consensus: Option<Arc<dyn IsmpHost>>,
+	/// Source of the consensus proofs accepted on hyperbridge
+	pub proof_source: Arc<dyn ConsensusProofSource>,
+	/// Local fee database
+	pub tx_payment: Arc<TransactionPayment>,
+}
+
+/// The clients of a configured chain.
+#[derive(Clone)]
+struct Chain {
+	config: PerChainConfig,
+	provider: Arc<dyn IsmpProvider>,
+	consensus: Option<Arc<dyn IsmpHost>>,
+}
+
+/// Everything a reload needs to replace the affected groups, built before any of them is stopped.
+struct Staged {
+	config: HyperbridgeConfig,
+	/// Every configured chain once the reload is applied
+	chains: HashMap<StateMachine, Chain>,
+	/// Task managers of the consensus groups to respawn
+	consensus: BTreeMap<StateMachine, TaskManager>,
+	/// Task managers and hyperbridge connections of the messaging groups to respawn
+	messaging: BTreeMap<StateMachine, (TaskManager, SubstrateClient<KeccakSubstrateChain>)>,
+	/// Task manager and connections of the global group, if it's respawned
+	global: Option<(TaskManager, GlobalConnections)>,
+}
+
+/// Hyperbridge connections of the global group.
+struct GlobalConnections {
+	outbound: Option<messaging::outbound::Outbound>,
+	withdraw: SubstrateClient<KeccakSubstrateChain>,
+}
+
+/// Owns the task groups of the running relayer and swaps them on config reloads.
+pub struct Supervisor {
+	shared: Shared,
+	handle: Handle,
+	config: HyperbridgeConfig,
+	chains: HashMap<StateMachine, Chain>,
+	groups: BTreeMap<Group, TaskManager>,
+}
+
+impl Supervisor {
+	/// Build the clients for every chain in `config` and spawn all task groups.
+	pub async fn start(
+		shared: Shared,
+		handle: Handle,
+		config: HyperbridgeConfig,
+	) -> anyhow::Result<Self> {
+		let mut supervisor = Supervisor {
+			shared,
+			handle,
+			config: HyperbridgeConfig {
+				hyperbridge: config.hyperbridge.clone(),
+				chains: Default::default(),
+				relayer: config.relayer.clone(),
+			},
+			chains: Default::default(),
+			groups: Default::default(),
+		};
+		// Start from a config without chains, so every chain is added and every group spawned
+		let diff = ConfigDiff { relayer: true, ..ConfigDiff::between(&supervisor.config, &config) };
+		let staged = supervisor.stage(config, &diff).await?;
+		supervisor.commit(&diff, staged).await?;
+
+		tracing::info!(
+			target: crate::LOG_TARGET, chains = supervisor.chains.len(),
+			consensus_enabled = supervisor.chains.values().filter(|c| c.consensus.is_some()).count(),
+			fees = !supervisor.config.relayer.disable_fee_accumulation.unwrap_or_default(),
+			"relayer tasks initialized",
+		);
+		Ok(supervisor)
+	}
+
+	/// Run until a task terminates, applying the config at `config_path` on every reload
+	/// trigger. A config that fails to parse, or whose chains fail to connect, is rejected and
+	/// the running tasks are left untouched.
+	pub async fn run(
+		mut self,
+		config_path: String,
+		mut reloads: mpsc::Receiver<ReloadCause>,
+	) -> anyhow::Result<()> {
+		enum Event {
+			Terminated(Group, Result<(), ServiceError>),
+			Reload(ReloadCause),
+		}
+
+		loop {
+			let event = tokio::select! {
+				(group, res) = self.terminated() => Event::Terminated(group, res),
+				Some(cause) = reloads.recv() => Event::Reload(cause),
+			};
+
+			let cause = match event {
+				Event::Terminated(group, res) => {
+					tracing::error!(target: crate::LOG_TARGET, %group, ?res, "task group terminated");
+					res?;
+					return Ok(());
+				},
+				Event::Reload(cause) => cause,
+			};
+
+			tracing::info!(target: crate::LOG_TARGET, %cause, path = %config_path, "reloading config");
+			let config = match HyperbridgeConfig::parse_conf(&config_path).await {
+				Ok(config) => config,
+				Err(err) => {
+					tracing::error!(target: crate::LOG_TARGET, ?err, "config reload rejected, keeping the running config");
+					continue;
+				},
+			};
+			let diff = ConfigDiff::between(&self.config, &config);
+			if diff.is_empty() {
+				tracing::info!(target: crate::LOG_TARGET, "config unchanged");
+				continue;
+			}
+			let staged = match self.stage(config, &diff).await {
+				Ok(staged) => staged,
+				Err(err) => {
+					tracing::error!(target: crate::LOG_TARGET, ?err, %diff, "config reload rejected, keeping the running config");
+					continue;
+				},
+			};
+			// The affected groups are stopped from here on, failing to respawn them is fatal
+			self.commit(&diff, staged).await?;
+			tracing::info!(target: crate::LOG_TARGET, %diff, "config reloaded");
+		}
+	}
+
+	/// Resolves once an essential task in any group terminates.
+	async fn terminated(&mut self) -> (Group, Result<(), ServiceError>) {
+		if self.groups.is_empty() {
+			return futures::future::pending().await;
+		}
+		let futures = self
+			.groups
+			.iter_mut()
+			.map(|(group, tasks)| {
+				let group = *group;
+				tasks.future().map(move |res| (group, res)).boxed()
+			})
+			.collect::<Vec<_>>();
+		select_all(futures).await.0
+	}
+
+	/// Build everything the groups affected by `diff` need to run `config`: the clients of every
+	/// added or changed chain, and the task managers and hyperbridge connections of every group
+	/// that is respawned. Nothing is stopped here, so an error rejects the config without
+	/// disturbing the running tasks.
+	async fn stage(
+		&self,
+		mut config: HyperbridgeConfig,
+		diff: &ConfigDiff,
+	) -> anyhow::Result<Staged> {
+		if diff.hyperbridge {
+			return Err(anyhow!(
+				"the [hyperbridge] section changed, restart the relayer to apply it"
+			));
+		}
+
+		let mut chains = self.chains.clone();
+		for state_machine in &diff.removed {
+			chains.remove(state_machine);
+		}
+		chains.extend(self.build_chains(&config, diff).await?);
+		// Routes that are still configured keep the spending they've accrued today
+		config.relayer.policy.inherit_spending(&self.config.relayer.policy);
+
+		let mut staged = Staged {
+			config,
+			chains,
+			consensus: Default::default(),
+			messaging: Default::default(),
+			global: None,
+		};
+		for group in diff.restarted_groups(staged.chains.keys().copied()) {
+			let task_manager = self.task_manager()?;
+			match group {
+				Group::Consensus(state_machine) => {
+					staged.consensus.insert(state_machine, task_manager);
+				},
+				Group::Messaging(state_machine) => {
+					let provider = staged.chains[&state_machine].provider.clone();
+					let mut hyperbridge = SubstrateClient::<KeccakSubstrateChain>::new(
+						self.shared.hyperbridge_config.clone(),
+					)
+					.await?;
+					hyperbridge.set_latest_finalized_height(provider).await.with_context(|| {
+						format!(
+							"failed to query the latest height of {state_machine} on hyperbridge"
+						)
+					})?;
+					staged.messaging.insert(state_machine, (task_manager, hyperbridge));
+				},
+				Group::Global => {
+					let connections = self.connect_global(&staged.config, &staged.chains).await?;
+					staged.global = Some((task_manager, connections));
+				},
+			}
+		}
+
+		Ok(staged)
+	}
+
+	/// Build the clients of every chain that was added or changed in `config`.
+	async fn build_chains(
+		&self,
+		config: &HyperbridgeConfig,
+		diff: &ConfigDiff,
+	) -> anyhow::Result<HashMap<StateMachine, Chain>> {
+		let mut consensus_chains = config.consensus_chains();
+		let mut built = HashMap::new();
+		for state_machine in diff.added.iter().chain(&diff.messaging).chain(&diff.consensus) {
+			if built.contains_key(state_machine) {
+				continue;
+			}
+			let chain_config = config.chains.get(state_machine).cloned().expect("diffed; qed");
+			let provider = match self.chains.get(state_machine) {
+				Some(chain) if !diff.messaging.contains(state_machine) => chain.provider.clone(),
+				_ => chain_config
+					.messaging
+					.clone()
+					.into_client(self.shared.hyperbridge.clone())
+					.await
+					.with_context(|| {
+						format!("failed to build messaging client for {state_machine}")
+					})?,
+			};
+			let consensus = match consensus_chains.remove(state_machine) {
+				Some(entry) => create_client_map(HashMap::from([(*state_machine, entry)]))
+					.await
+					.with_context(|| format!("failed to build consensus host for {state_machine}"))?
+					.remove(state_machine),
+				None => None,
+			};
+			built.insert(*state_machine, Chain { config: chain_config, provider, consensus });
+		}
+
+		Ok(built)
+	}
+
+	/// Stop the groups affected by `diff` and spawn them again into their staged task managers.
+	async fn commit(&mut self, diff: &ConfigDiff, staged: Staged) -> anyhow::Result<()> {
+		let Staged { config, chains, consensus, messaging, global } = staged;
+		self.config = config;
+		self.chains = chains;
+		for state_machine in &diff.removed {
+			self.groups.remove(&Group::Consensus(*state_machine));
+			self.groups.remove(&Group::Messaging(*state_machine));
+		}
+
+		for (state_machine, task_manager) in consensus {
+			self.groups.remove(&Group::Consensus(state_machine));
+			if self.spawn_consensus(state_machine, &task_manager) {
+				self.groups.insert(Group::Consensus(state_machine), task_manager);
+			}
+		}
+		for (state_machine, (task_manager, hyperbridge)) in messaging {
+			self.groups.remove(&Group::Messaging(state_machine));
+			self.spawn_messaging(state_machine, hyperbridge, &task_manager).await?;
+			self.groups.insert(Group::Messaging(state_machine), task_manager);
+		}
+		if let Some((task_manager, connections)) = global {
+			self.groups.remove(&Group::Global);
+			self.spawn_global(connections, &task_manager);
+			self.groups.insert(Group::Global, task_manager);
+		}
+
+		Ok(())
+	}
+
+	fn task_manager(&self) -> anyhow::Result<TaskManager> {
+		Ok(TaskManager::new(self.handle.clone(), None)?)
+	}
+
+	/// Every provider in `chains`, and hyperbridge's.
+	fn provider_clients(
+		&self,
+		chains: &HashMap<StateMachine, Chain>,
+	) -> HashMap<StateMachine, Arc<dyn IsmpProvider>> {
+		let mut clients = chains
+			.iter()
+			.map(|(state_machine, chain)| (*state_machine, chain.provider.clone()))
+			.collect::<HashMap<_, _>>();
+		clients.insert(
+			self.shared.hyperbridge.state_machine_id().state_id,
+			self.shared.hyperbridge.clone(),
+		);
+		clients
+	}
+
+	/// Returns false if the chain has no consensus tasks to run.
+	fn spawn_consensus(&self, state_machine: StateMachine, task_manager: &TaskManager) -> bool {
+		let chain = self.chains.get(&state_machine).expect("spawned for configured chains; qed");
+		let hb = self.shared.hyperbridge.clone();
+		let hb_consensus_host = self
+			.shared
+			.hyperbridge_consensus
+			.clone()
+			.filter(|_| state_machine.is_substrate());
+		if chain.consensus.is_none() && hb_consensus_host.is_none() {
+			return false;
+		}
+
+		// Inbound consensus — chain → hyperbridge, for chains with a
+		// `[<chain>.consensus]` sub-table.
+		if let Some(host) = chain.consensus.clone() {
+			let name = format!("inbound-consensus-{}-{}", host.provider().name(), hb.name());
+			let span = tracing::info_span!(
+				"inbound_consensus",
+				chain = %host.provider().name(),
+				hb = %hb.name(),
+			);
+			let hb = hb.clone();
+			task_manager.spawn_essential_handle().spawn_blocking(
+				Box::leak(Box::new(name)),
+				"consensus",
+				async move {
+					tracing::trace!(target: crate::LOG_TARGET, "task started");
+					let res = host.start_consensus(hb).await;
+					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
+				}
+				.instrument(span)
+				.boxed(),
+			);
+			tracing::trace!(target: crate::LOG_TARGET, %state_machine, "initialized consensus task");
+		}
+
+		// Hyperbridge → substrate counterparty consensus. When
+		// `[hyperbridge.consensus]` is configured, every substrate
+		// counterparty gets a task that ships hyperbridge's own consensus
+		// proofs (e.g. parachain headers) to it. EVM counterparties are
+		// skipped — their hyperbridge consensus path runs via the BEEFY
+		// consensus proofs pipeline, not this one.
+		if let Some(host) = hb_consensus_host {
+			let counterparty = chain.provider.clone();
+			let name =
+				format!("outbound-consensus-{}-{}", host.provider().name(), counterparty.name());
+			let span = tracing::info_span!(
+				"outbound_consensus",
+				hb = %host.provider().name(),
+				chain = %counterparty.name(),
+			);
+			task_manager.spawn_essential_handle().spawn_blocking(
+				Box::leak(Box::new(name)),
+				"consensus",
+				async move {
+					tracing::trace!(target: crate::LOG_TARGET, "task started");
+					let res = host.start_consensus(counterparty).await;
+					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
+				}
+				.instrument(span)
+				.boxed(),
+			);
+			tracing::trace!(
+				target: crate::LOG_TARGET,
+				%state_machine,
+				"initialized hyperbridge->substrate consensus task",
+			);
+		}
+
+		true
+	}
+
+	async fn spawn_messaging(
+		&self,
+		state_machine: StateMachine,
+		hb_for_messaging: SubstrateClient<KeccakSubstrateChain>,
+		task_manager: &TaskManager,
+	) -> anyhow::Result<()> {
+		let chain = self.chains.get(&state_machine).expect("spawned for configured chains; qed");
+		let provider = chain.provider.clone();
+		let provider_clients = self.provider_clients(&self.chains);
+		let messaging_config: tesseract_primitives::config::RelayerConfig =
+			self.config.relayer.clone().into();
+		let coprocessor = self.shared.hyperbridge.state_machine_id().state_id;
+
+		// Inbound messaging — every chain in `[chains.*]` gets an inbound
+		// messaging task. There's no opt-in gate: if you configured the chain,
+		// you want its inbound messages relayed. Fee accumulation is NOT wired
+		// here — it's an outbound-relayer concern and is spawned by the global
+		// group only for chains that opted into outbound. The hyperbridge connection was opened
+		// while staging.
+		messaging::inbound(
+			hb_for_messaging,
+			provider.clone(),
+			messaging_config.clone(),
+			coprocessor,
+			self.shared.tx_payment.clone(),
+			provider_clients.clone(),
+			task_manager,
+			None,
+		)
+		.await?;
+		tracing::trace!(target: crate::LOG_TARGET, %state_machine, "initialized inbound messaging task");
+
+		// Timeout relaying — opt-in via `[relayer.timeouts]`. Tracks the
+		// requests this chain dispatches and delivers timeouts back to it once
+		// they expire undelivered. Timeouts are submitted to the source chain,
+		// so EVM chains need a signer; substrate chains accept them unsigned.
+		if let Some(timeouts) = messaging_config.timeouts.clone() {
+			if chain.config.outbound_enabled() || state_machine.is_substrate() {
+				let hb = self.shared.hyperbridge.clone();
+				let name = format!("timeouts-{}", provider.name());
+				let span = tracing::info_span!("timeouts", chain = %provider.name());
+				task_manager.spawn_essential_handle().spawn_blocking(
+					Box::leak(Box::new(name)),
+					"messaging",
+					async move {
+						tracing::trace!(target: crate::LOG_TARGET, "task started");
+						let res = messaging::timeouts::relay_timeouts(
+							provider,
+							hb,
+							provider_clients,
+							messaging_config,
+							timeouts,
+						)
+						.await;
+						tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
+					}
+					.instrument(span)
+					.boxed(),
+				);
+				tracing::trace!(target: crate::LOG_TARGET, %state_machine, "initialized timeout relaying task");
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Open the hyperbridge connections the global group needs to run `config` over `chains`.
+	async fn connect_global(
+		&self,
+		config: &HyperbridgeConfig,
+		chains: &HashMap<StateMachine, Chain>,
+	) -> anyhow::Result<GlobalConnections> {
+		let hyperbridge = self.shared.hyperbridge.clone();
+		let messaging_config: tesseract_primitives::config::RelayerConfig =
+			config.relayer.clone().into();
+		let fees_disabled = messaging_config.disable_fee_accumulation.unwrap_or_default();
+
+		// Outbound: one task, fans out over every chain that has a non-empty
+		// signer configured. The signer's presence is the toggle (a chain
+		// without a signer cannot submit transactions, so it stays inbound
+		// only). Fee accumulation is part of the outbound pipeline: each
+		// outbound-enabled chain gets a dedicated fee-accumulation task that
+		// drains the receipts the outbound fan-out produces after a
+		// successful destination submit.
+		// Outbound fan-out only targets EVM destinations. The HB→substrate
+		// consensus path runs through the dedicated parachain-consensus task
+		// in the consensus groups; outbound messaging is currently an EVM-only
+		// flow driven by the BEEFY consensus proofs pipeline.
+		let destinations: BTreeMap<StateMachine, Arc<dyn IsmpProvider>> = chains
+			.iter()
+			.filter(|(sm, chain)| {
+				matches!(sm, StateMachine::Evm(_)) && chain.config.outbound_enabled()
+			})
+			.map(|(sm, chain)| (*sm, chain.provider.clone()))
+			.collect();
+		let mut consensus_hosts: HashMap<StateMachine, Arc<dyn IsmpHost>> = chains
+			.iter()
+			.filter_map(|(sm, chain)| chain.consensus.clone().map(|host| (*sm, host)))
+			.collect();
+		if let Some(host) = self.shared.hyperbridge_consensus.clone() {
+			consensus_hosts.insert(hyperbridge.state_machine_id().state_id, host);
+		}
+		let outbound = messaging::outbound::connect(messaging::outbound::OutboundInitParams {
+			hyperbridge_config: self.shared.hyperbridge_config.clone(),
+			hyperbridge_provider: hyperbridge.clone(),
+			destinations,
+			provider_clients: self.provider_clients(chains),
+			proof_source: self.shared.proof_source.clone(),
+			relayer_config: messaging_config,
+			tx_payment: self.shared.tx_payment.clone(),
+			fees_disabled,
+			consensus_hosts,
+		})
+		.await?;
+		let withdraw =
+			SubstrateClient::<KeccakSubstrateChain>::new(self.shared.hyperbridge_config.clone())
+				.await?;
+
+		Ok(GlobalConnections { outbound, withdraw })
+	}
+
+	fn spawn_global(&self, connections: GlobalConnections, task_manager: &TaskManager) {
+		let GlobalConnections { outbound, withdraw: hb_for_withdraw } = connections;
+		let hyperbridge = self.shared.hyperbridge.clone();
+		let messaging_config: tesseract_primitives::config::RelayerConfig =
+			self.config.relayer.clone().into();
+
+		// Expiry trackers, one per consensus client this relayer keeps alive: every chain's
+		// client on hyperbridge, and hyperbridge's client on each substrate counterparty. They
+		// live in the global group so `[relayer.expiry]` changes take effect on reload.
+		let mut expiry_routes: Vec<(Arc<dyn IsmpHost>, Arc<dyn IsmpProvider>)> = self
+			.chains
+			.values()
+			.filter_map(|chain| chain.consensus.clone().map(|host| (host, hyperbridge.clone())))
+			.collect();
+		if let Some(host) = self.shared.hyperbridge_consensus.clone() {
+			expiry_routes.extend(
+				self.chains
+					.iter()
+					.filter(|(sm, _)| sm.is_substrate())
+					.map(|(_, chain)| (host.clone(), chain.provider.clone())),
+			);
+		}
+		let expiry = self.config.relayer.expiry.clone().unwrap_or_default();
+		let trackers = expiry_routes.into_iter().map(|(host, counterparty)| {
+			let span = tracing::info_span!(
+				"expiry",
+				chain = %host.provider().name(),
+				counterparty = %counterparty.name(),
+			);
+			tesseract_primitives::expiry::track_expiry(host, counterparty, expiry.clone())
+				.instrument(span)
+		});
+		task_manager.spawn_handle().spawn(
+			"expiry",
+			"expiry",
+			futures::future::join_all(trackers).map(|_| ()).boxed(),
+		);
+
+		// Outbound pipeline. It owns the spawn responsibility for fee
+		// accumulation + outbound_claim + outbound::run; the supervisor just
+		// hands it the inputs while staging.
+		if let Some(outbound) = outbound {
+			outbound.spawn(task_manager);
+		}
+
+		// Fee withdrawal: one global task, periodic per
+		// `relayer.withdrawal_frequency`. Queries each destination's unclaimed
+		// balance on HB, submits a withdrawal request once the minimum
+		// threshold is crossed. The withdrawal POST that comes back is
+		// delivered by the relayer on the destination chain, so it requires
+		// a signer there. Skip chains without one.
+		{
+			let withdraw_clients: HashMap<StateMachine, Arc<dyn IsmpProvider>> = self
+				.chains
+				.iter()
+				.filter(|(_, chain)| chain.config.outbound_enabled())
+				.map(|(sm, chain)| (*sm, chain.provider.clone()))
+				.collect();
+			let withdraw_cfg = messaging_config.clone();
+			let withdraw_db = self.shared.tx_payment.clone();
+			let withdraw_proof_source = self.shared.proof_source.clone();
+			let name = format!("fee-withdraw-{}", hyperbridge.name());
+			let span = tracing::info_span!("fee_withdrawal", hb = %hyperbridge.name());
+			task_manager.spawn_essential_handle().spawn_blocking(
+				Box::leak(Box::new(name)),
+				"fees",
+				async move {
+					tracing::trace!(target: crate::LOG_TARGET, "task started");
+					let res = messaging::fees::auto_withdraw(
+						hb_for_withdraw,
+						withdraw_clients,
+						withdraw_cfg,
+						withdraw_db,
+						withdraw_proof_source,
+					)
+					.await;
+					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
+				}
+				.instrument(span)
+				.boxed(),
+			);
+		}
+
+		// Liveness monitor — opt-in via `relayer.maximum_update_intervals`.
+		// Watches each listed `(state_machine, max_interval)` for staleness on
+		// both the inbound consensus side (HB's view of the chain) and the
+		// outbound HB → substrate side (the chain's view of HB). When any
+		// update goes past its interval the task returns Ok(()) and the
+		// essential-task handle terminates the process so an external
+		// supervisor can restart it.
+		if let Some(intervals) =
+			self.config.relayer.maximum_update_intervals.clone().filter(|v| !v.is_empty())
+		{
+			let monitor_hb = hyperbridge.clone();
+			let monitor_providers = self
+				.chains
+				.iter()
+				.map(|(sm, chain)| (*sm, chain.provider.clone()))
+				.collect::<HashMap<_, _>>();
+			let name = format!("monitor-{}", hyperbridge.name());
+			let span = tracing::info_span!(
+				"monitor",
+				hb = %hyperbridge.name(),
+				entries = intervals.len(),
+			);
+			task_manager.spawn_essential_handle().spawn_blocking(
+				Box::leak(Box::new(name)),
+				"monitor",
+				async move {
+					tracing::trace!(target: crate::LOG_TARGET, "task started");
+					let res =
+						crate::monitor::monitor_clients(monitor_hb, monitor_providers, intervals)
+							.await;
+					tracing::error!(target: crate::LOG_TARGET, ?res, "task terminated");
+				}
+				.instrument(span)
+				.boxed(),
+			);
+			tracing::info!(target: crate::LOG_TARGET, "initialized liveness monitor task");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::reload::tests::{config, evm_chain};
+	use tesseract_primitives::{mocks::MockHost, ProofKey};
+	use tokio::sync::oneshot;
+
+	struct NoProofs;
+
+	#[async_trait::async_trait]
+	impl ConsensusProofSource for NoProofs {
+		async fn fetch(&self, key: ProofKey) -> Result<Vec<u8>, anyhow::Error> {
+			Err(anyhow!("no proof for {key:?}"))
+		}
+	}
+
+	#[tokio::test]
+	async fn failed_reloads_keep_the_running_groups() {
+		let dir = tempfile::tempdir().unwrap();
+		let db = dir.path().join("fees.db");
+		let running = config(vec![]);
+		let mut supervisor = Supervisor {
+			shared: Shared {
+				hyperbridge_config: running.hyperbridge.substrate.clone(),
+				hyperbridge: Arc::new(MockHost::new((), 0, StateMachine::Polkadot(4009))),
+				hyperbridge_consensus: None,
+				proof_source: Arc::new(NoProofs),
+				tx_payment: Arc::new(
+					TransactionPayment::initialize(db.to_str().unwrap()).await.unwrap(),
+				),
+			},
+			handle: Handle::current(),
+			config: running.clone(),
+			chains: Default::default(),
+			groups: Default::default(),
+		};
+		// A global task that holds the sender for as long as it runs
+		let (alive, mut stopped) = oneshot::channel::<()>();
+		let task_manager = supervisor.task_manager().unwrap();
+		task_manager.spawn_handle().spawn("alive", "test", async move {
+			let _alive = alive;
+			futures::future::pending::<()>().await
+		});
+		supervisor.groups.insert(Group::Global, task_manager);
+
+		// The relayer change restarts the global group, but the added chain fails to build
+		let mut reloaded = config(vec![evm_chain(1, "not a url")]);
+		reloaded.relayer.minimum_profit_percentage = 500;
+		let diff = ConfigDiff::between(&supervisor.config, &reloaded);
+		assert!(diff.relayer);
+		assert!(supervisor.stage(reloaded, &diff).await.is_err());
+
+		assert_eq!(supervisor.groups.keys().copied().collect::<Vec<_>>(), vec![Group::Global]);
+		assert!(supervisor.chains.is_empty());
+		assert_eq!(supervisor.config.relayer.minimum_profit_percentage, 0);
+		assert!(matches!(stopped.try_recv(), Err(oneshot::error::TryRecvError::Empty)));
+	}
+}